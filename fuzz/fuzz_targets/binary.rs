@@ -0,0 +1,7 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rfol::proof::LK;
+
+fuzz_target!(|input: &[u8]| {
+    let _ = LK::read_binary(&mut &input[..]);
+});
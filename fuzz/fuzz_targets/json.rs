@@ -0,0 +1,8 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rfol::proof::LK;
+use rfol::serialize::from_json;
+
+fuzz_target!(|input: &str| {
+    let _ = from_json::<LK>(input);
+});
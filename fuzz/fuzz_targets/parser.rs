@@ -0,0 +1,9 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rfol::limits::Limits;
+
+fuzz_target!(|input: &str| {
+    let limits = Limits::untrusted();
+    let _ = limits.parse_formula(input);
+    let _ = limits.parse_sequent(input);
+});
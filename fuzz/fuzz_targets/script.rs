@@ -0,0 +1,8 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rfol::limits::Limits;
+use rfol::proof::LK;
+
+fuzz_target!(|input: &str| {
+    let _ = LK::from_script_with(input, &Limits::untrusted());
+});
@@ -0,0 +1,7 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rfol::smtlib::parse_smtlib;
+
+fuzz_target!(|input: &str| {
+    let _ = parse_smtlib(input);
+});
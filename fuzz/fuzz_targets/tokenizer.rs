@@ -0,0 +1,8 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rfol::limits::Limits;
+use rfol::tokenizer::Tokenizer;
+
+fuzz_target!(|input: &str| {
+    let _ = Tokenizer::with_limits(&Limits::untrusted()).try_tokenize(input);
+});
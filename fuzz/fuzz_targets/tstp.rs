@@ -0,0 +1,7 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rfol::tptp::parse_tstp;
+
+fuzz_target!(|input: &str| {
+    let _ = parse_tstp(input);
+});
@@ -0,0 +1,106 @@
+use crate::language::*;
+use crate::proof::*;
+
+fn _side(sequent: &Sequent, left: bool) -> &Vec<Formula> {
+    if left {
+        &sequent.antecedent
+    } else {
+        &sequent.succedent
+    }
+}
+
+// Exchanges the formulas at `j` and `j + 1` of a side of the end sequent.
+fn _exchange(proof: LK, left: bool, j: usize) -> LK {
+    let mut sequent = proof.last().clone();
+    if left {
+        sequent.antecedent.swap(j, j + 1);
+        LK::ExchangeLeft(Box::new(proof), sequent)
+    } else {
+        sequent.succedent.swap(j, j + 1);
+        LK::ExchangeRight(Box::new(proof), sequent)
+    }
+}
+
+// Moves the formula at `from` to `to` of a side by exchanges.
+fn _move(mut proof: LK, left: bool, from: usize, to: usize) -> LK {
+    for j in (to..from).rev() {
+        proof = _exchange(proof, left, j);
+    }
+    for j in from..to {
+        proof = _exchange(proof, left, j);
+    }
+    proof
+}
+
+// Contracts two occurrences of `fml` on a side, moving them next to each
+// other at the principal position first.
+fn _contract(mut proof: LK, left: bool, fml: &Formula) -> LK {
+    let len = _side(proof.last(), left).len();
+    let (first, second) = if left { (0, 1) } else { (len - 1, len - 2) };
+    let i = _side(proof.last(), left)
+        .iter()
+        .position(|f| f == fml)
+        .unwrap();
+    proof = _move(proof, left, i, first);
+    let occurrences = _side(proof.last(), left).iter().enumerate();
+    let i = occurrences
+        .filter(|(i, f)| *i != first && *f == fml)
+        .map(|(i, _)| i)
+        .next()
+        .unwrap();
+    proof = _move(proof, left, i, second);
+    let mut sequent = proof.last().clone();
+    if left {
+        sequent.antecedent.remove(0);
+        LK::ContractionLeft(Box::new(proof), sequent)
+    } else {
+        sequent.succedent.pop();
+        LK::ContractionRight(Box::new(proof), sequent)
+    }
+}
+
+/// Derives `target` from `proof` by weakenings, contractions and exchanges.
+/// Fails if a formula of the end sequent of `proof` does not occur on the
+/// same side of `target`.
+pub fn adapt(mut proof: LK, target: &Sequent) -> Result<LK, &'static str> {
+    for left in [true, false] {
+        let tgt = _side(target, left);
+        if _side(proof.last(), left).iter().any(|f| !tgt.contains(f)) {
+            return Err("Target does not follow by structural rules.");
+        }
+        let count = |fmls: &[Formula], fml: &Formula| fmls.iter().filter(|f| *f == fml).count();
+        while let Some(fml) = _side(proof.last(), left)
+            .iter()
+            .find(|f| count(_side(proof.last(), left), f) > count(tgt, f))
+            .cloned()
+        {
+            proof = _contract(proof, left, &fml);
+        }
+        let mut rest = _side(proof.last(), left).clone();
+        let mut missing = vec![];
+        for fml in tgt {
+            match rest.iter().position(|f| f == fml) {
+                Some(i) => {
+                    rest.remove(i);
+                }
+                None => missing.push(fml.clone()),
+            }
+        }
+        for fml in missing {
+            let mut sequent = proof.last().clone();
+            proof = if left {
+                sequent.antecedent.insert(0, fml);
+                LK::WeakeningLeft(Box::new(proof), sequent)
+            } else {
+                sequent.succedent.push(fml);
+                LK::WeakeningRight(Box::new(proof), sequent)
+            };
+        }
+        for (k, fml) in tgt.iter().enumerate() {
+            let current = _side(proof.last(), left);
+            let m = k + current[k..].iter().position(|f| f == fml).unwrap();
+            proof = _move(proof, left, m, k);
+        }
+    }
+    Ok(proof)
+}
@@ -0,0 +1,167 @@
+use crate::proof::*;
+use std::collections::HashMap;
+
+/// A proof node of a `ProofArena`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(u32);
+
+/// An inference whose premises are nodes of the same arena.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArenaNode {
+    pub rule: &'static str,
+    pub premises: Vec<NodeId>,
+    pub sequent: Sequent,
+}
+
+/// Proofs stored as a flat vector of inferences referring to their premises
+/// by id, for proofs too large to keep as boxed `LK` trees. Premises are
+/// always added before the inferences using them, and may be shared.
+/// Conversion and checking walk the arena without recursion.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProofArena {
+    nodes: Vec<ArenaNode>,
+}
+
+// Only the conclusions of the premises are looked at by
+// `is_valid_inference`, so axioms stand in for the premise proofs.
+fn _shallow(rule: &str, premises: Vec<Sequent>, sequent: Sequent) -> Option<LK> {
+    LK::from_parts(rule, premises.into_iter().map(LK::Axiom).collect(), sequent)
+}
+
+impl ProofArena {
+    pub fn new() -> ProofArena {
+        ProofArena::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn contains(&self, id: NodeId) -> bool {
+        (id.0 as usize) < self.nodes.len()
+    }
+
+    pub fn node(&self, id: NodeId) -> &ArenaNode {
+        &self.nodes[id.0 as usize]
+    }
+
+    fn _sequents(&self, ids: &[NodeId]) -> Vec<Sequent> {
+        ids.iter()
+            .map(|id| self.node(*id).sequent.clone())
+            .collect()
+    }
+
+    /// Adds the inference `rule`, named as by `LK::rule_name`, with the
+    /// conclusion `sequent`. Fails if a premise is not in the arena or the
+    /// rule takes another number of premises.
+    pub fn add(
+        &mut self,
+        rule: &str,
+        premises: Vec<NodeId>,
+        sequent: Sequent,
+    ) -> Result<NodeId, &'static str> {
+        if premises.iter().any(|p| !self.contains(*p)) {
+            return Err("Premise is not in the arena.");
+        }
+        let template = _shallow(rule, self._sequents(&premises), sequent.clone())
+            .ok_or("Unknown rule or wrong number of premises.")?;
+        let id = NodeId(self.nodes.len() as u32);
+        self.nodes.push(ArenaNode {
+            rule: template.rule_name(),
+            premises,
+            sequent,
+        });
+        Ok(id)
+    }
+
+    /// Adds every inference of `proof`, returning the node of its end
+    /// sequent.
+    pub fn insert(&mut self, proof: &LK) -> NodeId {
+        let mut stack = vec![(proof, false)];
+        let mut ids = vec![];
+        while let Some((proof, expanded)) = stack.pop() {
+            let premises = proof.premises();
+            if !expanded {
+                stack.push((proof, true));
+                stack.extend(premises.into_iter().rev().map(|p| (p, false)));
+                continue;
+            }
+            let premises = ids.split_off(ids.len() - premises.len());
+            let id = NodeId(self.nodes.len() as u32);
+            self.nodes.push(ArenaNode {
+                rule: proof.rule_name(),
+                premises,
+                sequent: proof.last().clone(),
+            });
+            ids.push(id);
+        }
+        ids.pop().unwrap()
+    }
+
+    pub fn from_lk(proof: &LK) -> (ProofArena, NodeId) {
+        let mut arena = ProofArena::new();
+        let root = arena.insert(proof);
+        (arena, root)
+    }
+
+    /// The proof ending in `root` as an `LK` tree. Shared premises are
+    /// copied.
+    pub fn to_lk(&self, root: NodeId) -> LK {
+        let mut uses = vec![0; self.nodes.len()];
+        for node in &self.nodes {
+            for premise in &node.premises {
+                uses[premise.0 as usize] += 1;
+            }
+        }
+        let mut built: HashMap<NodeId, LK> = HashMap::new();
+        let mut stack = vec![root];
+        while let Some(&id) = stack.last() {
+            if built.contains_key(&id) {
+                stack.pop();
+                continue;
+            }
+            let node = self.node(id);
+            let missing = node
+                .premises
+                .iter()
+                .filter(|p| !built.contains_key(p))
+                .copied()
+                .collect::<Vec<_>>();
+            if !missing.is_empty() {
+                stack.extend(missing);
+                continue;
+            }
+            stack.pop();
+            let premises = node
+                .premises
+                .iter()
+                .map(|p| {
+                    uses[p.0 as usize] -= 1;
+                    if uses[p.0 as usize] == 0 {
+                        built.remove(p).unwrap()
+                    } else {
+                        built[p].clone()
+                    }
+                })
+                .collect();
+            let proof = LK::from_parts(node.rule, premises, node.sequent.clone()).unwrap();
+            built.insert(id, proof);
+        }
+        built.remove(&root).unwrap()
+    }
+
+    /// The first node, in the order of the arena, that is not a valid
+    /// inference from its premises.
+    pub fn invalid_node(&self) -> Option<NodeId> {
+        (0..self.nodes.len() as u32).map(NodeId).find(|&id| {
+            let node = self.node(id);
+            let premises = self._sequents(&node.premises);
+            !_shallow(node.rule, premises, node.sequent.clone())
+                .is_some_and(|lk| lk.is_valid_inference())
+        })
+    }
+}
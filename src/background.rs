@@ -0,0 +1,84 @@
+use crate::proof::*;
+use crate::solver::*;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+#[derive(Default)]
+struct Shared {
+    result: Option<Option<LK>>,
+    waker: Option<Waker>,
+}
+
+/// Result of a proof search running on a background thread. Dropping the
+/// future cancels the search.
+pub struct ProofFuture {
+    shared: Arc<Mutex<Shared>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ProofFuture {
+    /// Stops the search. The future then resolves to `None` unless the
+    /// search has already finished.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.shared.lock().unwrap().result.is_some()
+    }
+}
+
+impl Future for ProofFuture {
+    type Output = Option<LK>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<LK>> {
+        let mut shared = self.shared.lock().unwrap();
+        match shared.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for ProofFuture {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+/// Runs `prover` on `goal` on a new thread so async executors are not
+/// blocked. Cancellation is observed by the LK search of `solver` and by
+/// `FirstOrderProver`; other provers run to completion, their result being
+/// discarded. A prover that panics resolves the future to `None`.
+pub fn prove_async<P: Prover + Send + 'static>(prover: P, goal: Sequent) -> ProofFuture {
+    let shared = Arc::new(Mutex::new(Shared::default()));
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let future = ProofFuture {
+        shared: shared.clone(),
+        cancelled: cancelled.clone(),
+    };
+    thread::spawn(move || {
+        set_cancellation_flag(Some(cancelled.clone()));
+        let proof = panic::catch_unwind(AssertUnwindSafe(|| prover.prove_sequent(&goal)))
+            .ok()
+            .flatten();
+        let mut shared = shared.lock().unwrap();
+        shared.result = Some(if cancelled.load(Ordering::Relaxed) {
+            None
+        } else {
+            proof
+        });
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    });
+    future
+}
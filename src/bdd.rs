@@ -0,0 +1,243 @@
+use crate::language::*;
+use crate::truth_table::is_propositional;
+use std::collections::HashMap;
+
+const FALSE: usize = 0;
+const TRUE: usize = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Op {
+    And,
+    Or,
+    Implies,
+    Iff,
+}
+
+impl Op {
+    fn eval(self, lhs: bool, rhs: bool) -> bool {
+        match self {
+            Op::And => lhs && rhs,
+            Op::Or => lhs || rhs,
+            Op::Implies => !lhs || rhs,
+            Op::Iff => lhs == rhs,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Node {
+    /// Index of the atom in the ordering, the length of the ordering for
+    /// the terminals.
+    var: usize,
+    low: usize,
+    high: usize,
+}
+
+/// A reduced ordered binary decision diagram of a propositional formula.
+/// Diagrams over the same ordering are equal exactly when their formulas
+/// are equivalent.
+#[derive(Debug, Clone)]
+pub struct Bdd {
+    ordering: Vec<String>,
+    nodes: Vec<Node>,
+    root: usize,
+}
+
+#[derive(Default)]
+struct Builder {
+    nodes: Vec<Node>,
+    unique: HashMap<Node, usize>,
+    computed: HashMap<(Op, usize, usize), usize>,
+}
+
+impl Builder {
+    fn new(vars: usize) -> Builder {
+        let terminal = Node {
+            var: vars,
+            low: FALSE,
+            high: FALSE,
+        };
+        Builder {
+            nodes: vec![terminal, terminal],
+            ..Builder::default()
+        }
+    }
+
+    fn node(&mut self, var: usize, low: usize, high: usize) -> usize {
+        if low == high {
+            return low;
+        }
+        let node = Node { var, low, high };
+        let next = self.nodes.len();
+        let index = *self.unique.entry(node).or_insert(next);
+        if index == next {
+            self.nodes.push(node);
+        }
+        index
+    }
+
+    fn apply(&mut self, op: Op, lhs: usize, rhs: usize) -> usize {
+        if lhs <= TRUE && rhs <= TRUE {
+            return op.eval(lhs == TRUE, rhs == TRUE) as usize;
+        }
+        if let Some(&result) = self.computed.get(&(op, lhs, rhs)) {
+            return result;
+        }
+        let (l, r) = (self.nodes[lhs], self.nodes[rhs]);
+        let var = l.var.min(r.var);
+        let branches = |index: usize, node: Node| {
+            if node.var == var {
+                (node.low, node.high)
+            } else {
+                (index, index)
+            }
+        };
+        let (l_low, l_high) = branches(lhs, l);
+        let (r_low, r_high) = branches(rhs, r);
+        let low = self.apply(op, l_low, r_low);
+        let high = self.apply(op, l_high, r_high);
+        let result = self.node(var, low, high);
+        self.computed.insert((op, lhs, rhs), result);
+        result
+    }
+
+    fn formula(&mut self, fml: &Formula, ordering: &HashMap<&str, usize>) -> usize {
+        let mut binary = |op, lhs: &Formula, rhs: &Formula| {
+            let lhs = self.formula(lhs, ordering);
+            let rhs = self.formula(rhs, ordering);
+            self.apply(op, lhs, rhs)
+        };
+        match fml {
+            Formula::Pred(name, _) => self.node(ordering[name.as_str()], FALSE, TRUE),
+            Formula::Not(fml) => {
+                let fml = self.formula(fml, ordering);
+                self.apply(Op::Implies, fml, FALSE)
+            }
+            Formula::And(lhs, rhs) => binary(Op::And, lhs, rhs),
+            Formula::Or(lhs, rhs) => binary(Op::Or, lhs, rhs),
+            Formula::Implies(lhs, rhs) => binary(Op::Implies, lhs, rhs),
+            Formula::Iff(lhs, rhs) => binary(Op::Iff, lhs, rhs),
+            Formula::Top => TRUE,
+            Formula::Bottom => FALSE,
+            Formula::Equal(_, _) | Formula::Forall(_, _) | Formula::Exists(_, _) => unreachable!(),
+        }
+    }
+}
+
+impl Bdd {
+    /// The atoms in the order they are tested, from the root.
+    pub fn ordering(&self) -> &[String] {
+        &self.ordering
+    }
+
+    // The nodes reachable from the root, children before parents, each
+    // with its children given by position in the list.
+    fn _canonical(&self) -> Vec<Node> {
+        let mut positions = HashMap::from([(FALSE, 0), (TRUE, 1)]);
+        let mut nodes = vec![self.nodes[FALSE], self.nodes[TRUE]];
+        let mut stack = vec![(self.root, false)];
+        while let Some((index, expanded)) = stack.pop() {
+            if positions.contains_key(&index) {
+                continue;
+            }
+            let node = self.nodes[index];
+            if expanded {
+                positions.insert(index, nodes.len());
+                nodes.push(Node {
+                    var: node.var,
+                    low: positions[&node.low],
+                    high: positions[&node.high],
+                });
+            } else {
+                stack.extend([(index, true), (node.high, false), (node.low, false)]);
+            }
+        }
+        nodes
+    }
+
+    /// The number of nodes reachable from the root, the two terminals
+    /// included.
+    pub fn node_count(&self) -> usize {
+        self._canonical().len()
+    }
+
+    pub fn is_tautology(&self) -> bool {
+        self.root == TRUE
+    }
+
+    pub fn is_satisfiable(&self) -> bool {
+        self.root != FALSE
+    }
+
+    /// Values of the atoms on a path to `⊤` making the formula true, in
+    /// order, or `None` if it is unsatisfiable. Atoms not on the path may
+    /// take either value.
+    pub fn satisfying_assignment(&self) -> Option<Vec<(String, bool)>> {
+        if !self.is_satisfiable() {
+            return None;
+        }
+        let mut assignment = vec![];
+        let mut index = self.root;
+        while index != TRUE {
+            let node = self.nodes[index];
+            let value = node.low == FALSE;
+            assignment.push((self.ordering[node.var].clone(), value));
+            index = if value { node.high } else { node.low };
+        }
+        Some(assignment)
+    }
+}
+
+impl PartialEq for Bdd {
+    fn eq(&self, other: &Bdd) -> bool {
+        self.ordering == other.ordering && self._canonical() == other._canonical()
+    }
+}
+
+impl Eq for Bdd {}
+
+impl Formula {
+    /// The reduced ordered BDD of a propositional formula, testing its
+    /// atoms in the order of `ordering`, which must contain all of them.
+    pub fn to_bdd(&self, ordering: &[String]) -> Result<Bdd, &'static str> {
+        if !is_propositional(self) {
+            return Err("Not a propositional formula.");
+        }
+        let positions = ordering
+            .iter()
+            .enumerate()
+            .map(|(i, atom)| (atom.as_str(), i))
+            .collect::<HashMap<_, _>>();
+        if positions.len() < ordering.len() {
+            return Err("Duplicate atom in the ordering.");
+        }
+        if !self
+            .get_preds()
+            .iter()
+            .all(|p| positions.contains_key(p.name.as_str()))
+        {
+            return Err("Atom missing from the ordering.");
+        }
+        let mut builder = Builder::new(ordering.len());
+        let root = builder.formula(self, &positions);
+        Ok(Bdd {
+            ordering: ordering.to_vec(),
+            nodes: builder.nodes,
+            root,
+        })
+    }
+
+    /// Whether two propositional formulas are equivalent, by comparing
+    /// their BDDs over the atoms of both in order of name.
+    pub fn is_equivalent_by_bdd(&self, other: &Formula) -> Result<bool, &'static str> {
+        let mut ordering = self
+            .get_preds()
+            .into_iter()
+            .chain(other.get_preds())
+            .map(|p| p.name)
+            .collect::<Vec<_>>();
+        ordering.sort();
+        ordering.dedup();
+        Ok(self.to_bdd(&ordering)? == other.to_bdd(&ordering)?)
+    }
+}
@@ -0,0 +1,446 @@
+use crate::language::*;
+use crate::proof::*;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+/// Compact binary encoding of proofs, written by `LK::write_binary`:
+///
+/// ```text
+/// "RFOL" version               // magic bytes, then the format version byte
+/// symbols: n (len utf8)*n      // every predicate, function and variable name
+/// terms: n (len entry)*n       // every distinct term, once
+/// formulas: n (len entry)*n    // every distinct formula, once
+/// proof                        // rule, [premise count,] antecedent, succedent, premises
+/// ```
+///
+/// Numbers are unsigned LEB128. A term entry is a tag (`0` variable, `1`
+/// function) followed by a symbol index and argument term indices; a formula
+/// entry is a tag in the order of `Formula`'s variants followed by symbol,
+/// term and formula indices. Entries only refer to earlier entries, so equal
+/// subterms and subformulas, which make up most of a proof, are stored once.
+/// Sequents are lists of formula indices, and rules are indices into
+/// `RULES`; the premise count is only written for `TheoryRule`.
+pub const BINARY_VERSION: u8 = 1;
+
+const MAGIC: &[u8; 4] = b"RFOL";
+
+/// Most premises read for a `TheoryRule`.
+const MAX_THEORY_PREMISES: usize = 1 << 16;
+
+/// Most terms and formulas nested in one another in a proof read.
+const MAX_DEPTH: usize = 256;
+
+/// Largest total size of the terms and formulas of a proof read, counting
+/// each term, formula and symbol byte once for every place it is expanded
+/// from the shared tables.
+const MAX_SIZE: usize = 1 << 22;
+
+/// Rule names in the order of their binary tags.
+const RULES: [&str; 28] = [
+    "Axiom",
+    "WeakeningLeft",
+    "WeakeningRight",
+    "ContractionLeft",
+    "ContractionRight",
+    "ExchangeLeft",
+    "ExchangeRight",
+    "AndLeft1",
+    "AndLeft2",
+    "AndRight",
+    "OrLeft",
+    "OrRight1",
+    "OrRight2",
+    "ImpliesLeft",
+    "ImpliesRight",
+    "IffLeft",
+    "IffRight",
+    "NotLeft",
+    "NotRight",
+    "ForallLeft",
+    "ForallRight",
+    "ExistsLeft",
+    "ExistsRight",
+    "EqualityLeft",
+    "EqualityRight",
+    "Cut",
+    "TheoryRule",
+    "TheoryAxiom",
+];
+
+fn _write_number(out: &mut Vec<u8>, mut n: usize) {
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+// The number of parts of an entry of `len` numbers after the first `min`.
+fn _parts(len: usize, min: usize) -> io::Result<usize> {
+    len.checked_sub(min)
+        .ok_or_else(|| _invalid("entry too short"))
+}
+
+fn _invalid(reason: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason)
+}
+
+/// Interning tables built while encoding. Each term and formula is keyed by
+/// its tag and the indices of its parts, so looking one up does not hash
+/// the whole tree.
+#[derive(Default)]
+struct Encoder {
+    symbols: HashMap<String, usize>,
+    symbol_table: Vec<u8>,
+    terms: HashMap<Vec<usize>, usize>,
+    term_table: Vec<u8>,
+    formulas: HashMap<Vec<usize>, usize>,
+    formula_table: Vec<u8>,
+    proof: Vec<u8>,
+}
+
+impl Encoder {
+    fn entry(
+        map: &mut HashMap<Vec<usize>, usize>,
+        table: &mut Vec<u8>,
+        entry: Vec<usize>,
+    ) -> usize {
+        let next = map.len();
+        *map.entry(entry).or_insert_with_key(|entry| {
+            _write_number(table, entry.len());
+            for &n in entry {
+                _write_number(table, n);
+            }
+            next
+        })
+    }
+
+    fn symbol(&mut self, name: &str) -> usize {
+        if let Some(&index) = self.symbols.get(name) {
+            return index;
+        }
+        let index = self.symbols.len();
+        _write_number(&mut self.symbol_table, name.len());
+        self.symbol_table.extend_from_slice(name.as_bytes());
+        self.symbols.insert(name.to_string(), index);
+        index
+    }
+
+    fn term(&mut self, term: &Term) -> usize {
+        let entry = match term {
+            Term::Var(name) => vec![0, self.symbol(name)],
+            Term::Func(name, args) => {
+                let mut entry = vec![1, self.symbol(name)];
+                entry.extend(args.iter().map(|arg| self.term(arg)));
+                entry
+            }
+        };
+        Encoder::entry(&mut self.terms, &mut self.term_table, entry)
+    }
+
+    fn formula(&mut self, fml: &Formula) -> usize {
+        use Formula::*;
+        let entry = match fml {
+            Pred(name, args) => {
+                let mut entry = vec![0, self.symbol(name)];
+                entry.extend(args.iter().map(|arg| self.term(arg)));
+                entry
+            }
+            Equal(lhs, rhs) => vec![1, self.term(lhs), self.term(rhs)],
+            Not(fml) => vec![2, self.formula(fml)],
+            And(lhs, rhs) => vec![3, self.formula(lhs), self.formula(rhs)],
+            Or(lhs, rhs) => vec![4, self.formula(lhs), self.formula(rhs)],
+            Implies(lhs, rhs) => vec![5, self.formula(lhs), self.formula(rhs)],
+            Iff(lhs, rhs) => vec![6, self.formula(lhs), self.formula(rhs)],
+            Forall(var, fml) => vec![7, self.term(var), self.formula(fml)],
+            Exists(var, fml) => vec![8, self.term(var), self.formula(fml)],
+            Top => vec![9],
+            Bottom => vec![10],
+        };
+        Encoder::entry(&mut self.formulas, &mut self.formula_table, entry)
+    }
+
+    fn proof(&mut self, proof: &LK) {
+        let mut stack = vec![proof];
+        while let Some(proof) = stack.pop() {
+            let rule = RULES.iter().position(|&r| r == proof.rule_name()).unwrap();
+            _write_number(&mut self.proof, rule);
+            if let LK::TheoryRule(premises, _) = proof {
+                _write_number(&mut self.proof, premises.len());
+            }
+            for fmls in [&proof.last().antecedent, &proof.last().succedent] {
+                let indices = fmls.iter().map(|fml| self.formula(fml)).collect::<Vec<_>>();
+                _write_number(&mut self.proof, indices.len());
+                for index in indices {
+                    _write_number(&mut self.proof, index);
+                }
+            }
+            stack.extend(proof.premises().into_iter().rev());
+        }
+    }
+}
+
+// An inference read up to its premises.
+struct Frame {
+    rule: &'static str,
+    sequent: Sequent,
+    premise_count: usize,
+    premises: Vec<LK>,
+}
+
+impl Frame {
+    fn build(self) -> LK {
+        if self.rule == "TheoryRule" {
+            return LK::TheoryRule(self.premises, self.sequent);
+        }
+        LK::from_parts(self.rule, self.premises, self.sequent).unwrap()
+    }
+}
+
+/// The size and depth of a term or formula once expanded.
+#[derive(Clone, Copy, Default)]
+struct Shape {
+    size: usize,
+    depth: usize,
+}
+
+struct Decoder<'a, R: Read> {
+    reader: &'a mut R,
+    symbols: Vec<String>,
+    terms: Vec<Term>,
+    formulas: Vec<Formula>,
+    term_shapes: Vec<Shape>,
+    formula_shapes: Vec<Shape>,
+    // The parts of the table entry being read.
+    parts: Shape,
+    // The size of everything expanded so far, against `MAX_SIZE`.
+    size: usize,
+}
+
+impl<'a, R: Read> Decoder<'a, R> {
+    fn number(&mut self) -> io::Result<usize> {
+        let mut n = 0usize;
+        for shift in (0..64).step_by(7) {
+            let mut byte = [0u8];
+            self.reader.read_exact(&mut byte)?;
+            n |= ((byte[0] & 0x7f) as usize) << shift;
+            if byte[0] & 0x80 == 0 {
+                return Ok(n);
+            }
+        }
+        Err(_invalid("number too large"))
+    }
+
+    fn index(&mut self, len: usize) -> io::Result<usize> {
+        let index = self.number()?;
+        if index < len {
+            Ok(index)
+        } else {
+            Err(_invalid("index out of range"))
+        }
+    }
+
+    fn expand(&mut self, shape: Shape) -> io::Result<()> {
+        self.size += shape.size;
+        if self.size > MAX_SIZE {
+            return Err(_invalid("proof too large"));
+        }
+        self.parts.size += shape.size;
+        self.parts.depth = self.parts.depth.max(shape.depth);
+        Ok(())
+    }
+
+    // Ends a table entry made of the parts read since the last one.
+    fn entry(&mut self) -> io::Result<Shape> {
+        self.expand(Shape { size: 1, depth: 0 })?;
+        let parts = std::mem::take(&mut self.parts);
+        if parts.depth >= MAX_DEPTH {
+            return Err(_invalid("nesting too deep"));
+        }
+        Ok(Shape {
+            size: parts.size,
+            depth: parts.depth + 1,
+        })
+    }
+
+    fn symbol(&mut self) -> io::Result<String> {
+        let index = self.index(self.symbols.len())?;
+        let size = self.symbols[index].len();
+        self.expand(Shape { size, depth: 0 })?;
+        Ok(self.symbols[index].clone())
+    }
+
+    fn term(&mut self) -> io::Result<Term> {
+        let index = self.index(self.terms.len())?;
+        self.expand(self.term_shapes[index])?;
+        Ok(self.terms[index].clone())
+    }
+
+    fn formula(&mut self) -> io::Result<Box<Formula>> {
+        let index = self.index(self.formulas.len())?;
+        self.expand(self.formula_shapes[index])?;
+        Ok(Box::new(self.formulas[index].clone()))
+    }
+
+    fn read_tables(&mut self) -> io::Result<()> {
+        for _ in 0..self.number()? {
+            let len = self.number()?;
+            let mut bytes = vec![];
+            self.reader.take(len as u64).read_to_end(&mut bytes)?;
+            if bytes.len() != len {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            let symbol = String::from_utf8(bytes).map_err(|_| _invalid("symbol not UTF-8"))?;
+            self.symbols.push(symbol);
+        }
+        for _ in 0..self.number()? {
+            let len = self.number()?;
+            let args = _parts(len, 2)?;
+            let term = match self.number()? {
+                0 if args == 0 => Term::Var(self.symbol()?),
+                1 => Term::Func(
+                    self.symbol()?,
+                    (0..args).map(|_| self.term()).collect::<io::Result<_>>()?,
+                ),
+                _ => return Err(_invalid("unknown term")),
+            };
+            let shape = self.entry()?;
+            self.terms.push(term);
+            self.term_shapes.push(shape);
+        }
+        for _ in 0..self.number()? {
+            use Formula::*;
+            let len = self.number()?;
+            let parts = _parts(len, 1)?;
+            let fml = match (self.number()?, parts) {
+                (0, n) if n >= 1 => Pred(
+                    self.symbol()?,
+                    (1..n).map(|_| self.term()).collect::<io::Result<_>>()?,
+                ),
+                (1, 2) => Equal(self.term()?, self.term()?),
+                (2, 1) => Not(self.formula()?),
+                (3, 2) => And(self.formula()?, self.formula()?),
+                (4, 2) => Or(self.formula()?, self.formula()?),
+                (5, 2) => Implies(self.formula()?, self.formula()?),
+                (6, 2) => Iff(self.formula()?, self.formula()?),
+                (7, 2) => Forall(self.term()?, self.formula()?),
+                (8, 2) => Exists(self.term()?, self.formula()?),
+                (9, 0) => Top,
+                (10, 0) => Bottom,
+                _ => return Err(_invalid("unknown formula")),
+            };
+            let shape = self.entry()?;
+            self.formulas.push(fml);
+            self.formula_shapes.push(shape);
+        }
+        Ok(())
+    }
+
+    fn frame(&mut self) -> io::Result<Frame> {
+        let rule = RULES[self.index(RULES.len())?];
+        let premise_count = match rule {
+            "TheoryRule" => self.number()?,
+            "Axiom" | "TheoryAxiom" => 0,
+            "AndRight" | "OrLeft" | "ImpliesLeft" | "IffLeft" | "IffRight" | "Cut" => 2,
+            _ => 1,
+        };
+        if premise_count > MAX_THEORY_PREMISES {
+            return Err(_invalid("too many premises"));
+        }
+        let mut sides = vec![];
+        for _ in 0..2 {
+            let len = self.number()?;
+            let fmls = (0..len)
+                .map(|_| self.formula().map(|fml| *fml))
+                .collect::<io::Result<Vec<_>>>()?;
+            sides.push(fmls);
+        }
+        let succedent = sides.pop().unwrap();
+        let antecedent = sides.pop().unwrap();
+        Ok(Frame {
+            rule,
+            sequent: Sequent {
+                antecedent,
+                succedent,
+            },
+            premise_count,
+            premises: vec![],
+        })
+    }
+
+    fn proof(&mut self) -> io::Result<LK> {
+        // The inferences whose premises are still being read.
+        let mut stack: Vec<Frame> = vec![];
+        loop {
+            let frame = self.frame()?;
+            if frame.premise_count > 0 {
+                stack.push(frame);
+                continue;
+            }
+            let mut proof = frame.build();
+            loop {
+                let Some(parent) = stack.last_mut() else {
+                    return Ok(proof);
+                };
+                parent.premises.push(proof);
+                if parent.premises.len() < parent.premise_count {
+                    break;
+                }
+                proof = stack.pop().unwrap().build();
+            }
+        }
+    }
+}
+
+impl LK {
+    /// Writes the proof in the binary format described at `BINARY_VERSION`.
+    pub fn write_binary<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut encoder = Encoder::default();
+        encoder.proof(self);
+        let mut header = MAGIC.to_vec();
+        header.push(BINARY_VERSION);
+        _write_number(&mut header, encoder.symbols.len());
+        writer.write_all(&header)?;
+        writer.write_all(&encoder.symbol_table)?;
+        for (len, table) in [
+            (encoder.terms.len(), &encoder.term_table),
+            (encoder.formulas.len(), &encoder.formula_table),
+        ] {
+            let mut count = vec![];
+            _write_number(&mut count, len);
+            writer.write_all(&count)?;
+            writer.write_all(table)?;
+        }
+        writer.write_all(&encoder.proof)
+    }
+
+    /// Reads a proof written by `write_binary`. Malformed input, and input
+    /// expanding past `MAX_SIZE` or nesting past `MAX_DEPTH`, is an
+    /// `InvalidData` error; the proof read is not checked.
+    pub fn read_binary<R: Read>(reader: &mut R) -> io::Result<LK> {
+        let mut header = [0u8; 5];
+        reader.read_exact(&mut header)?;
+        if &header[..4] != MAGIC {
+            return Err(_invalid("not a binary proof"));
+        }
+        if header[4] != BINARY_VERSION {
+            return Err(_invalid("unsupported binary proof version"));
+        }
+        let mut decoder = Decoder {
+            reader,
+            symbols: vec![],
+            terms: vec![],
+            formulas: vec![],
+            term_shapes: vec![],
+            formula_shapes: vec![],
+            parts: Shape::default(),
+            size: 0,
+        };
+        decoder.read_tables()?;
+        decoder.proof()
+    }
+}
@@ -0,0 +1,231 @@
+use crate::arena::*;
+use crate::check::*;
+use crate::language::*;
+use crate::proof::*;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildError {
+    /// A premise that was not added to this builder.
+    UnknownPremise(NodeId),
+    /// No rule of that name takes that many premises.
+    UnknownRule,
+    Invalid(Box<ProofError>),
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BuildError::UnknownPremise(id) => write!(f, "unknown premise {:?}", id),
+            BuildError::UnknownRule => write!(f, "unknown rule or wrong number of premises"),
+            BuildError::Invalid(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+/// Builds proofs bottom-up, one inference at a time, checking each as it is
+/// added. Inferences are referred to by the `NodeId` returned when adding
+/// them and may be used as premises more than once.
+#[derive(Debug, Clone, Default)]
+pub struct ProofBuilder {
+    arena: ProofArena,
+}
+
+macro_rules! unary_rules {
+    ($($method: ident => $rule: expr),* $(,)?) => {
+        $(
+            pub fn $method(
+                &mut self,
+                premise: NodeId,
+                sequent: Sequent,
+            ) -> Result<NodeId, BuildError> {
+                self.infer($rule, &[premise], sequent)
+            }
+        )*
+    };
+}
+
+macro_rules! binary_rules {
+    ($($method: ident => $rule: expr),* $(,)?) => {
+        $(
+            pub fn $method(
+                &mut self,
+                left: NodeId,
+                right: NodeId,
+                sequent: Sequent,
+            ) -> Result<NodeId, BuildError> {
+                self.infer($rule, &[left, right], sequent)
+            }
+        )*
+    };
+}
+
+impl ProofBuilder {
+    pub fn new() -> ProofBuilder {
+        ProofBuilder::default()
+    }
+
+    pub fn sequent(&self, id: NodeId) -> &Sequent {
+        &self.arena.node(id).sequent
+    }
+
+    fn _check(&self, rule: &str, premises: &[NodeId], sequent: &Sequent) -> Result<(), BuildError> {
+        if let Some(&id) = premises.iter().find(|id| !self.arena.contains(**id)) {
+            return Err(BuildError::UnknownPremise(id));
+        }
+        let shallow = premises
+            .iter()
+            .map(|id| LK::Axiom(self.sequent(*id).clone()))
+            .collect();
+        LK::from_parts(rule, shallow, sequent.clone())
+            .ok_or(BuildError::UnknownRule)?
+            .check_inference()
+            .map_err(|error| BuildError::Invalid(Box::new(error)))
+    }
+
+    /// Adds the inference `rule`, named as by `LK::rule_name`, of `sequent`
+    /// from `premises`.
+    pub fn infer(
+        &mut self,
+        rule: &str,
+        premises: &[NodeId],
+        sequent: Sequent,
+    ) -> Result<NodeId, BuildError> {
+        self._check(rule, premises, &sequent)?;
+        self.arena
+            .add(rule, premises.to_vec(), sequent)
+            .map_err(|_| BuildError::UnknownRule)
+    }
+
+    pub fn axiom(&mut self, sequent: Sequent) -> Result<NodeId, BuildError> {
+        self.infer("Axiom", &[], sequent)
+    }
+
+    unary_rules! {
+        weakening_left => "WeakeningLeft",
+        weakening_right => "WeakeningRight",
+        contraction_left => "ContractionLeft",
+        contraction_right => "ContractionRight",
+        exchange_left => "ExchangeLeft",
+        exchange_right => "ExchangeRight",
+        and_left1 => "AndLeft1",
+        and_left2 => "AndLeft2",
+        or_right1 => "OrRight1",
+        or_right2 => "OrRight2",
+        implies_right => "ImpliesRight",
+        not_left => "NotLeft",
+        not_right => "NotRight",
+    }
+
+    binary_rules! {
+        and_right => "AndRight",
+        or_left => "OrLeft",
+        implies_left => "ImpliesLeft",
+        iff_left => "IffLeft",
+        iff_right => "IffRight",
+        cut => "Cut",
+    }
+
+    // Adds the quantifier inference `rule`, also checking that the formula
+    // of the premise is the body of the principal formula with `term` for
+    // the quantified variable.
+    fn _quantifier(
+        &mut self,
+        rule: &'static str,
+        premise: NodeId,
+        term: &Term,
+        sequent: Sequent,
+    ) -> Result<NodeId, BuildError> {
+        self._check(rule, &[premise], &sequent)?;
+        let conclusion = &sequent;
+        let premise_sequent = self.sequent(premise);
+        let left = rule.ends_with("Left");
+        let (principal, instance) = if left {
+            (conclusion.ant_first(), premise_sequent.ant_first())
+        } else {
+            (conclusion.suc_last(), premise_sequent.suc_last())
+        };
+        let (var, body) = match principal {
+            Formula::Forall(var, body) | Formula::Exists(var, body) => (var, body),
+            _ => unreachable!(),
+        };
+        let context = if left {
+            [
+                premise_sequent.ant_but_first(),
+                &premise_sequent.succedent[..],
+            ]
+            .concat()
+        } else {
+            [
+                &premise_sequent.antecedent[..],
+                premise_sequent.suc_but_last(),
+            ]
+            .concat()
+        };
+        let eigenvariable = rule == "ForallRight" || rule == "ExistsLeft";
+        let reason = if &body.substitute(var.clone(), term.clone()) != instance {
+            "premise formula is not the instance for the given term"
+        } else if eigenvariable && context.iter().any(|f| f.get_free_vars().contains(term)) {
+            "eigenvariable occurs free in context"
+        } else {
+            return Ok(self.arena.add(rule, vec![premise], sequent).unwrap());
+        };
+        Err(BuildError::Invalid(Box::new(ProofError {
+            path: vec![],
+            rule,
+            premises: vec![premise_sequent.clone()],
+            conclusion: sequent,
+            reason,
+            countermodel: None,
+        })))
+    }
+
+    /// Instantiates the universal quantifier on the left with `term`.
+    pub fn forall_left(
+        &mut self,
+        premise: NodeId,
+        term: &Term,
+        sequent: Sequent,
+    ) -> Result<NodeId, BuildError> {
+        self._quantifier("ForallLeft", premise, term, sequent)
+    }
+
+    /// Generalizes over the eigenvariable `var` on the right.
+    pub fn forall_right(
+        &mut self,
+        premise: NodeId,
+        var: &Term,
+        sequent: Sequent,
+    ) -> Result<NodeId, BuildError> {
+        self._quantifier("ForallRight", premise, var, sequent)
+    }
+
+    /// Generalizes over the eigenvariable `var` on the left.
+    pub fn exists_left(
+        &mut self,
+        premise: NodeId,
+        var: &Term,
+        sequent: Sequent,
+    ) -> Result<NodeId, BuildError> {
+        self._quantifier("ExistsLeft", premise, var, sequent)
+    }
+
+    /// Instantiates the existential quantifier on the right with `term`.
+    pub fn exists_right(
+        &mut self,
+        premise: NodeId,
+        term: &Term,
+        sequent: Sequent,
+    ) -> Result<NodeId, BuildError> {
+        self._quantifier("ExistsRight", premise, term, sequent)
+    }
+
+    /// The proof ending in `root`.
+    pub fn build(&self, root: NodeId) -> LK {
+        self.arena.to_lk(root)
+    }
+
+    pub fn into_arena(self) -> ProofArena {
+        self.arena
+    }
+}
@@ -0,0 +1,157 @@
+use crate::language::*;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// Metadata of a subformula, see `CachedFormula`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormulaInfo {
+    pub free_vars: HashSet<Term>,
+    pub bound_vars: HashSet<Term>,
+    pub hash: u64,
+    /// As given by `Formula::size`.
+    pub size: usize,
+}
+
+/// A formula with the metadata of each of its subformulas, computed in a
+/// single pass when it is built. Formulas are immutable, so the metadata
+/// never has to be invalidated. Hashing and comparing use the cached hash.
+#[derive(Debug, Clone)]
+pub struct CachedFormula {
+    formula: Formula,
+    // In the order of `Formula::iter_subformulas`.
+    infos: Vec<FormulaInfo>,
+}
+
+// Pushes the metadata of `fml` and its subformulas in pre-order.
+fn _compute(fml: &Formula, infos: &mut Vec<FormulaInfo>) {
+    let index = infos.len();
+    infos.push(FormulaInfo {
+        free_vars: HashSet::new(),
+        bound_vars: HashSet::new(),
+        hash: 0,
+        size: 1,
+    });
+    let mut hasher = DefaultHasher::new();
+    std::mem::discriminant(fml).hash(&mut hasher);
+    let mut free_vars = HashSet::new();
+    let mut bound_vars = HashSet::new();
+    let mut children = vec![];
+    match fml {
+        Formula::Pred(name, terms) => {
+            name.hash(&mut hasher);
+            terms.hash(&mut hasher);
+            free_vars.extend(terms.iter().flat_map(|t| t.get_vars()));
+        }
+        Formula::Equal(lhs, rhs) => {
+            lhs.hash(&mut hasher);
+            rhs.hash(&mut hasher);
+            free_vars.extend(lhs.get_vars());
+            free_vars.extend(rhs.get_vars());
+        }
+        Formula::Not(sub) | Formula::Forall(_, sub) | Formula::Exists(_, sub) => {
+            children.push(infos.len());
+            _compute(sub, infos);
+        }
+        Formula::And(lhs, rhs)
+        | Formula::Or(lhs, rhs)
+        | Formula::Implies(lhs, rhs)
+        | Formula::Iff(lhs, rhs) => {
+            children.push(infos.len());
+            _compute(lhs, infos);
+            children.push(infos.len());
+            _compute(rhs, infos);
+        }
+        Formula::Top | Formula::Bottom => {}
+    }
+    for &child in &children {
+        let child = &infos[child];
+        child.hash.hash(&mut hasher);
+        free_vars.extend(child.free_vars.iter().cloned());
+        bound_vars.extend(child.bound_vars.iter().cloned());
+    }
+    if let Formula::Forall(var, _) | Formula::Exists(var, _) = fml {
+        var.hash(&mut hasher);
+        free_vars.remove(var);
+        bound_vars.insert(var.clone());
+    }
+    let size = 1 + children.iter().map(|&c| infos[c].size).sum::<usize>();
+    infos[index] = FormulaInfo {
+        free_vars,
+        bound_vars,
+        hash: hasher.finish(),
+        size,
+    };
+}
+
+impl CachedFormula {
+    pub fn new(formula: Formula) -> CachedFormula {
+        let mut infos = vec![];
+        _compute(&formula, &mut infos);
+        CachedFormula { formula, infos }
+    }
+
+    pub fn formula(&self) -> &Formula {
+        &self.formula
+    }
+
+    pub fn into_formula(self) -> Formula {
+        self.formula
+    }
+
+    /// The metadata of the whole formula.
+    pub fn info(&self) -> &FormulaInfo {
+        &self.infos[0]
+    }
+
+    pub fn free_vars(&self) -> &HashSet<Term> {
+        &self.info().free_vars
+    }
+
+    pub fn bound_vars(&self) -> &HashSet<Term> {
+        &self.info().bound_vars
+    }
+
+    pub fn size(&self) -> usize {
+        self.info().size
+    }
+
+    /// The subformulas in the order of `Formula::iter_subformulas`, each
+    /// with its metadata.
+    pub fn subformulas(&self) -> impl Iterator<Item = (&Formula, &FormulaInfo)> {
+        self.formula.iter_subformulas().zip(self.infos.iter())
+    }
+}
+
+impl From<Formula> for CachedFormula {
+    fn from(formula: Formula) -> CachedFormula {
+        CachedFormula::new(formula)
+    }
+}
+
+impl AsRef<Formula> for CachedFormula {
+    fn as_ref(&self) -> &Formula {
+        &self.formula
+    }
+}
+
+impl PartialEq for CachedFormula {
+    fn eq(&self, other: &CachedFormula) -> bool {
+        self.info().hash == other.info().hash && self.formula == other.formula
+    }
+}
+
+impl Eq for CachedFormula {}
+
+impl Hash for CachedFormula {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.info().hash);
+    }
+}
+
+impl fmt::Display for CachedFormula {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.formula)
+    }
+}
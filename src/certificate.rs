@@ -0,0 +1,143 @@
+use crate::language::*;
+use crate::parser::*;
+use crate::proof::*;
+use crate::prover::*;
+use crate::tokenizer::*;
+use crate::trace::instantiation;
+use std::fmt;
+use std::str::FromStr;
+
+const RULES: [&str; 4] = ["ForallLeft", "ExistsRight", "ForallRight", "ExistsLeft"];
+
+/// A quantifier inference: the term `rule` instantiates `formula` with, the
+/// eigenvariable for `ForallRight` and `ExistsLeft`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuantifierInstance {
+    pub rule: &'static str,
+    pub formula: Formula,
+    pub term: Term,
+}
+
+/// The quantifier inferences of a proof, from which a proof of the same
+/// sequent can be searched for without guessing terms. Written one
+/// inference per line, as the rule name followed by the formula and the
+/// term:
+///
+/// ```text
+/// ForallLeft (V x (P x)) (a)
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Certificate {
+    pub instances: Vec<QuantifierInstance>,
+}
+
+impl Certificate {
+    pub fn new() -> Certificate {
+        Certificate::default()
+    }
+
+    /// The terms `rule` instantiates `fml` with, in order and without
+    /// repetitions.
+    pub fn terms(&self, rule: &str, fml: &Formula) -> Vec<&Term> {
+        let mut terms = vec![];
+        for instance in &self.instances {
+            if instance.rule == rule && &instance.formula == fml && !terms.contains(&&instance.term)
+            {
+                terms.push(&instance.term);
+            }
+        }
+        terms
+    }
+
+    /// A proof of `goal` by `FirstOrderProver::search_with_certificate`,
+    /// with as many instances on a branch as the certificate has.
+    pub fn rebuild(&self, goal: &Sequent) -> Result<LK, SearchFailure> {
+        let prover = FirstOrderProver {
+            max_instantiations: self.instances.len() as u32,
+            max_steps: None,
+            ..FirstOrderProver::default()
+        };
+        prover.search_with_certificate(goal, self)
+    }
+}
+
+impl LK {
+    /// The quantifier inferences of the proof, conclusions before premises
+    /// and left to right. Quantifiers whose variable does not occur in their
+    /// scope are left out.
+    pub fn certificate(&self) -> Certificate {
+        let mut instances = vec![];
+        let mut stack = vec![self];
+        while let Some(proof) = stack.pop() {
+            let premises = proof.premises();
+            let rule = proof.rule_name();
+            if let (Some(&rule), [premise]) = (RULES.iter().find(|&&r| r == rule), &premises[..]) {
+                let conclusion = proof.last();
+                let formula = match rule {
+                    "ForallLeft" | "ExistsLeft" => conclusion.ant_first(),
+                    _ => conclusion.suc_last(),
+                };
+                if let Some(term) = instantiation(rule, conclusion, premise.last()) {
+                    instances.push(QuantifierInstance {
+                        rule,
+                        formula: formula.clone(),
+                        term,
+                    });
+                }
+            }
+            stack.extend(premises.into_iter().rev());
+        }
+        Certificate { instances }
+    }
+}
+
+impl fmt::Display for Certificate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for instance in &self.instances {
+            writeln!(
+                f,
+                "{} {} {}",
+                instance.rule,
+                instance.formula.to_sexpr(),
+                instance.term.to_sexpr()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// The instance written on `line`, a rule name, a formula and a term.
+fn _parse_instance(line: &str) -> Result<QuantifierInstance, &'static str> {
+    let (rule, rest) = line.split_at(line.find(' ').unwrap_or(line.len()));
+    let rule = *RULES
+        .iter()
+        .find(|&&r| r == rule)
+        .ok_or("expected a quantifier rule name")?;
+    let tokens = Tokenizer::new().try_tokenize(rest)?;
+    let mut parser = Parser::new();
+    let formula = parser.parse(&tokens)?;
+    let read = tokens.len() - parser.iter.clone().count();
+    let term = parser.parse_term(&tokens[read..])?;
+    let term = at_end(&mut parser, term)?;
+    Ok(QuantifierInstance {
+        rule,
+        formula,
+        term,
+    })
+}
+
+impl FromStr for Certificate {
+    type Err = (usize, &'static str);
+
+    /// Reads a certificate as written by `Display`, failing with the line
+    /// number and the reason. Blank lines are skipped.
+    fn from_str(s: &str) -> Result<Certificate, (usize, &'static str)> {
+        let mut instances = vec![];
+        for (index, line) in s.lines().enumerate() {
+            if !line.trim().is_empty() {
+                instances.push(_parse_instance(line.trim()).map_err(|e| (index + 1, e))?);
+            }
+        }
+        Ok(Certificate { instances })
+    }
+}
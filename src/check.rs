@@ -0,0 +1,460 @@
+use crate::exchange::*;
+use crate::language::*;
+use crate::model::*;
+use crate::proof::*;
+use crate::solver::*;
+use crate::theory::*;
+use std::collections::HashMap;
+use std::fmt;
+
+/// An invalid inference of a proof, as found by `LK::check`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProofError {
+    /// Indices of the premises leading from the end sequent to the
+    /// inference.
+    pub path: Vec<usize>,
+    pub rule: &'static str,
+    pub premises: Vec<Sequent>,
+    pub conclusion: Sequent,
+    /// The condition of the rule that does not hold.
+    pub reason: &'static str,
+    /// A finite model where the premises hold for all values of their free
+    /// variables but the conclusion does not, if the inference is unsound
+    /// and `CheckOptions::max_domain_size` was given.
+    pub countermodel: Option<FiniteModel>,
+}
+
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "invalid {} at {:?}: {}",
+            self.rule, self.path, self.reason
+        )?;
+        for premise in &self.premises {
+            writeln!(f, "  premise:    {}", premise)?;
+        }
+        write!(f, "  conclusion: {}", self.conclusion)?;
+        if let Some(model) = &self.countermodel {
+            write!(
+                f,
+                "\n  unsound: the conclusion fails in a model of size {} where the premises hold",
+                model.domain_size
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// A model of size at most `max_domain_size` where the premises of `proof`
+// hold for all values of their free variables and the conclusion does not.
+fn _countermodel(proof: &LK, max_domain_size: u32) -> Option<FiniteModel> {
+    let close = |sequent: &Sequent| {
+        let fml = sequent.to_formula();
+        fml.get_free_vars()
+            .into_iter()
+            .fold(fml, |fml, var| Formula::Forall(var, Box::new(fml)))
+    };
+    let premises = proof
+        .premises()
+        .iter()
+        .map(|p| close(p.last()))
+        .reduce(|l, r| and!(l, r));
+    let conclusion = close(proof.last());
+    let fml = match premises {
+        Some(premises) => implies!(premises, conclusion),
+        None => conclusion,
+    };
+    refute_on_finite_models(fml, max_domain_size)
+}
+
+fn _free_in(var: &Term, fmls: &[Formula]) -> bool {
+    fmls.iter().any(|fml| fml.get_free_vars().contains(var))
+}
+
+// Whether the principal formula of the conclusion has the main connective
+// of the rule.
+fn _principal_fits(proof: &LK) -> bool {
+    use LK::*;
+    let conclusion = proof.last();
+    let ant = conclusion.antecedent.first();
+    let suc = conclusion.succedent.last();
+    match proof {
+        AndLeft1(_, _) | AndLeft2(_, _) => matches!(ant, Some(Formula::And(_, _))),
+        AndRight(_, _) => matches!(suc, Some(Formula::And(_, _))),
+        OrLeft(_, _) => matches!(ant, Some(Formula::Or(_, _))),
+        OrRight1(_, _) | OrRight2(_, _) => matches!(suc, Some(Formula::Or(_, _))),
+        ImpliesLeft(_, _) => matches!(ant, Some(Formula::Implies(_, _))),
+        ImpliesRight(_, _) => matches!(suc, Some(Formula::Implies(_, _))),
+        IffLeft(_, _) => matches!(ant, Some(Formula::Iff(_, _))),
+        IffRight(_, _) => matches!(suc, Some(Formula::Iff(_, _))),
+        NotLeft(_, _) => matches!(ant, Some(Formula::Not(_))),
+        NotRight(_, _) => matches!(suc, Some(Formula::Not(_))),
+        ForallLeft(_, _) => matches!(ant, Some(Formula::Forall(_, _))),
+        ForallRight(_, _) => matches!(suc, Some(Formula::Forall(_, _))),
+        ExistsLeft(_, _) => matches!(ant, Some(Formula::Exists(_, _))),
+        ExistsRight(_, _) => matches!(suc, Some(Formula::Exists(_, _))),
+        EqualityLeft(_, _) | EqualityRight(_, _) => matches!(ant, Some(Formula::Equal(_, _))),
+        _ => true,
+    }
+}
+
+// Whether the formulas of the premises the rule works on are the immediate
+// subformulas of the principal formula, for the propositional rules.
+fn _active_fits(proof: &LK) -> bool {
+    use LK::*;
+    let conclusion = proof.last();
+    let premises = proof.premises();
+    let premise = |i: usize| premises[i].last();
+    let (lhs, rhs) = match conclusion
+        .antecedent
+        .first()
+        .filter(|_| _is_left_rule(proof))
+        .or(conclusion.succedent.last())
+    {
+        Some(Formula::And(lhs, rhs))
+        | Some(Formula::Or(lhs, rhs))
+        | Some(Formula::Implies(lhs, rhs))
+        | Some(Formula::Iff(lhs, rhs)) => (&**lhs, &**rhs),
+        Some(Formula::Not(fml)) => (&**fml, &**fml),
+        _ => return true,
+    };
+    match proof {
+        AndLeft1(_, _) => premise(0).ant_first() == lhs,
+        AndLeft2(_, _) => premise(0).ant_first() == rhs,
+        AndRight(_, _) => premise(0).suc_last() == lhs && premise(1).suc_last() == rhs,
+        OrLeft(_, _) => premise(0).ant_first() == lhs && premise(1).ant_first() == rhs,
+        OrRight1(_, _) => premise(0).suc_last() == lhs,
+        OrRight2(_, _) => premise(0).suc_last() == rhs,
+        ImpliesLeft(_, _) => premise(0).suc_last() == lhs && premise(1).ant_first() == rhs,
+        ImpliesRight(_, _) => premise(0).ant_first() == lhs && premise(0).suc_last() == rhs,
+        IffLeft(_, _) => {
+            let succedent = &premise(1).succedent;
+            premise(0).antecedent[..2] == [lhs.clone(), rhs.clone()]
+                && succedent[succedent.len() - 2..] == [lhs.clone(), rhs.clone()]
+        }
+        IffRight(_, _) => {
+            premise(0).ant_first() == lhs
+                && premise(0).suc_last() == rhs
+                && premise(1).ant_first() == rhs
+                && premise(1).suc_last() == lhs
+        }
+        NotLeft(_, _) => premise(0).suc_last() == lhs,
+        NotRight(_, _) => premise(0).ant_first() == lhs,
+        _ => true,
+    }
+}
+
+fn _is_left_rule(proof: &LK) -> bool {
+    use LK::*;
+    matches!(
+        proof,
+        AndLeft1(_, _)
+            | AndLeft2(_, _)
+            | OrLeft(_, _)
+            | ImpliesLeft(_, _)
+            | IffLeft(_, _)
+            | NotLeft(_, _)
+            | ForallLeft(_, _)
+            | ExistsLeft(_, _)
+    )
+}
+
+// The reason the quantifier inference `proof` is invalid, if it is to do
+// with the instance of the quantified formula.
+fn _quantifier_reason(proof: &LK) -> Option<&'static str> {
+    use LK::*;
+    let conclusion = proof.last();
+    let premise = proof.premises().into_iter().next()?.last();
+    let (var, body, instance, context) = match proof {
+        ForallLeft(_, _) | ExistsLeft(_, _) => match conclusion.ant_first() {
+            Formula::Forall(var, body) | Formula::Exists(var, body) => (
+                var,
+                body,
+                premise.ant_first(),
+                [premise.ant_but_first(), &premise.succedent[..]].concat(),
+            ),
+            _ => return None,
+        },
+        ForallRight(_, _) | ExistsRight(_, _) => match conclusion.suc_last() {
+            Formula::Forall(var, body) | Formula::Exists(var, body) => (
+                var,
+                body,
+                premise.suc_last(),
+                [&premise.antecedent[..], premise.suc_but_last()].concat(),
+            ),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let is_instance = |term: &Term| {
+        body.is_substitutible(var.clone(), term.clone())
+            && &body.substitute(var.clone(), term.clone()) == instance
+    };
+    match proof {
+        ForallLeft(_, _) | ExistsRight(_, _) => {
+            if !instance.iter_subterms().any(is_instance) {
+                Some("premise formula is not an instance of the quantified formula")
+            } else {
+                None
+            }
+        }
+        _ => {
+            let eigenvariables = instance
+                .get_free_vars()
+                .into_iter()
+                .filter(is_instance)
+                .collect::<Vec<_>>();
+            if eigenvariables.is_empty() {
+                Some("premise formula is not an instance of the quantified formula with a variable")
+            } else if eigenvariables.iter().all(|v| _free_in(v, &context)) {
+                Some("eigenvariable occurs free in context")
+            } else {
+                None
+            }
+        }
+    }
+}
+
+// The violated condition of the rule, if it is the shape of the sequents
+// or the main connective of the principal formula.
+fn _shape_reason(proof: &LK) -> Option<&'static str> {
+    let fits = |sequent: &Sequent, (ant, suc): (usize, usize)| {
+        sequent.antecedent.len() >= ant && sequent.succedent.len() >= suc
+    };
+    let premises = proof.premises();
+    let (min_conclusion, min_premises) = proof.min_lengths();
+    if !fits(proof.last(), min_conclusion)
+        || !premises
+            .iter()
+            .zip(min_premises)
+            .all(|(premise, min)| fits(premise.last(), min))
+    {
+        return Some("sequent too short for the rule");
+    }
+    if !_principal_fits(proof) {
+        return Some("principal formula has the wrong main connective");
+    }
+    None
+}
+
+// The condition of the rule that the invalid inference `proof` violates.
+fn _reason(proof: &LK) -> &'static str {
+    use LK::*;
+    if let Some(reason) = _shape_reason(proof) {
+        return reason;
+    }
+    let premises = proof.premises();
+    if !_active_fits(proof) {
+        return "premise formulas are not the subformulas of the principal formula";
+    }
+    if let Some(reason) = _quantifier_reason(proof) {
+        return reason;
+    }
+    match proof {
+        Axiom(_) => "not an axiom A ⇒ A, ⇒ t = t, ⇒ ⊤ or ⊥ ⇒",
+        TheoryRule(_, _) => "not an instance of a rule of the theory",
+        TheoryAxiom(_) => "not an instance of an axiom of the theory",
+        ContractionLeft(p, _) if p.last().antecedent[0] != p.last().antecedent[1] => {
+            "contracted formulas differ"
+        }
+        ContractionRight(p, _) if p.last().suc_but_last().last() != Some(p.last().suc_last()) => {
+            "contracted formulas differ"
+        }
+        ExchangeLeft(_, _) | ExchangeRight(_, _) => {
+            "conclusion is not the premise with two adjacent formulas exchanged"
+        }
+        EqualityLeft(_, _) | EqualityRight(_, _) => {
+            "conclusion formula is not the premise formula with equals replaced"
+        }
+        Cut(_, _) if premises[0].last().suc_last() != premises[1].last().ant_first() => {
+            "cut formulas differ"
+        }
+        _ => "side formulas of premises and conclusion differ",
+    }
+}
+
+/// Limits and hints for `LK::check_with`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CheckOptions {
+    /// Most candidate instance terms or eigenvariables tried for a
+    /// quantifier inference that is not annotated. Inferences with more fail
+    /// to check instead of taking time quadratic in the size of the
+    /// formula. `None` for no limit.
+    pub max_candidates: Option<usize>,
+    /// Instance terms or eigenvariables of quantifier inferences, by path
+    /// from the end sequent. An annotated inference is checked with a
+    /// single substitution.
+    pub instantiations: HashMap<Vec<usize>, Term>,
+    /// Most inferences checked. Larger proofs fail to check at the first
+    /// inference beyond the limit. `None` for no limit.
+    pub max_nodes: Option<usize>,
+    /// Largest domain searched for a countermodel of an invalid inference,
+    /// see `ProofError::countermodel`. `None` to not search.
+    pub max_domain_size: Option<u32>,
+    /// Theory whose rules `LK::TheoryRule` inferences and axioms
+    /// `LK::TheoryAxiom` initial sequents are checked against.
+    pub theory: Theory,
+    /// Compares the sides of premises and conclusion up to permutation, as
+    /// calculi on multisets do. `LK::with_exchanges` makes the exchanges
+    /// explicit.
+    pub implicit_exchange: bool,
+}
+
+/// Whether the last inference of `proof` is valid, `LK::TheoryRule`
+/// inferences being instances of the rules of `theory` and
+/// `LK::TheoryAxiom` sequents of its axioms.
+pub fn is_valid_in(proof: &LK, theory: &Theory) -> bool {
+    match proof {
+        LK::TheoryRule(ps, s) => {
+            let premises = ps.iter().map(|p| p.last()).collect::<Vec<_>>();
+            (theory.rules.iter()).any(|r| r.is_instance(&premises, s))
+        }
+        LK::TheoryAxiom(s) => {
+            s.antecedent.is_empty()
+                && s.succedent.len() == 1
+                && theory.has_instance(&s.succedent[0])
+        }
+        _ => proof.is_valid_inference(),
+    }
+}
+
+fn _is_quantifier_rule(proof: &LK) -> bool {
+    use LK::*;
+    matches!(
+        proof,
+        ForallLeft(_, _) | ForallRight(_, _) | ExistsLeft(_, _) | ExistsRight(_, _)
+    )
+}
+
+// The number of candidates `is_valid_inference` tries for the quantifier
+// inference `proof`.
+fn _candidates(proof: &LK) -> usize {
+    use LK::*;
+    match proof {
+        ForallLeft(p, _) => p
+            .last()
+            .antecedent
+            .first()
+            .map_or(0, |f| f.iter_subterms().count()),
+        ExistsRight(p, _) => p
+            .last()
+            .succedent
+            .last()
+            .map_or(0, |f| f.iter_subterms().count()),
+        ExistsLeft(p, _) => p
+            .last()
+            .antecedent
+            .first()
+            .map_or(0, |f| f.get_free_vars().len()),
+        ForallRight(p, _) => p
+            .last()
+            .succedent
+            .last()
+            .map_or(0, |f| f.get_free_vars().len()),
+        _ => 0,
+    }
+}
+
+fn _error(proof: &LK, reason: &'static str) -> ProofError {
+    ProofError {
+        path: vec![],
+        rule: proof.rule_name(),
+        premises: proof.premises().iter().map(|p| p.last().clone()).collect(),
+        conclusion: proof.last().clone(),
+        reason,
+        countermodel: None,
+    }
+}
+
+fn _path(parents: &[Option<(usize, usize)>], node: usize) -> Vec<usize> {
+    let mut path = vec![];
+    let mut current = node;
+    while let Some((parent, index)) = parents[current] {
+        path.push(index);
+        current = parent;
+    }
+    path.reverse();
+    path
+}
+
+impl LK {
+    /// Checks the last inference only, like `is_valid_inference`. The path
+    /// of an error is empty.
+    #[allow(clippy::result_large_err)]
+    pub fn check_inference(&self) -> Result<(), ProofError> {
+        if self.is_valid_inference() {
+            return Ok(());
+        }
+        Err(_error(self, _reason(self)))
+    }
+
+    /// Checks every inference of the proof, reporting the first invalid one
+    /// met walking up from the end sequent, premises left to right.
+    #[allow(clippy::result_large_err)]
+    pub fn check(&self) -> Result<(), ProofError> {
+        self.check_with(&CheckOptions::default())
+    }
+
+    /// Same as `check`, with the limits and annotations of `options`.
+    #[allow(clippy::result_large_err)]
+    pub fn check_with(&self, options: &CheckOptions) -> Result<(), ProofError> {
+        // The parent and premise index of every node pushed so far.
+        let mut parents: Vec<Option<(usize, usize)>> = vec![None];
+        let mut stack = vec![(self, 0)];
+        while let Some((proof, node)) = stack.pop() {
+            let quantifier = _is_quantifier_rule(proof);
+            let instantiation = if quantifier && !options.instantiations.is_empty() {
+                options.instantiations.get(&_path(&parents, node))
+            } else {
+                None
+            };
+            let limited = instantiation.is_none()
+                && quantifier
+                && options
+                    .max_candidates
+                    .is_some_and(|max| _candidates(proof) > max);
+            let result = match instantiation {
+                Some(term) if !proof.is_valid_inference_with(Some(term)) => Err(_error(
+                    proof,
+                    _shape_reason(proof)
+                        .unwrap_or("premise formula is not the instance for the annotated term"),
+                )),
+                Some(_) => Ok(()),
+                None if limited => Err(_error(
+                    proof,
+                    "too many candidate instances to check without an annotation",
+                )),
+                None if is_valid_in(proof, &options.theory) => Ok(()),
+                None if options.implicit_exchange
+                    && arrangement(proof, &options.theory).is_some() =>
+                {
+                    Ok(())
+                }
+                None => proof.check_inference(),
+            };
+            if let Err(mut error) = result {
+                error.path = _path(&parents, node);
+                // The search would cost what the candidate limit avoids.
+                if let (Some(max_domain_size), false) = (options.max_domain_size, limited) {
+                    error.countermodel = _countermodel(proof, max_domain_size);
+                }
+                return Err(error);
+            }
+            let premises = proof.premises();
+            if options
+                .max_nodes
+                .is_some_and(|max| parents.len() + premises.len() > max)
+            {
+                let mut error = _error(proof, "proof has more inferences than the limit");
+                error.path = _path(&parents, node);
+                return Err(error);
+            }
+            for (index, premise) in premises.into_iter().enumerate().rev() {
+                stack.push((premise, parents.len()));
+                parents.push(Some((node, index)));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use crate::language::*;
+use std::collections::HashSet;
+
+/// Syntactic fragments recognized by `Formula::classify`. Quantifiers are
+/// classified by their polarity, so `¬∃x A` counts as universal; quantifiers
+/// below `↔` count as both universal and existential.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Fragment {
+    /// No variables.
+    Ground,
+    /// Nullary predicates, `⊤` and `⊥` only.
+    Propositional,
+    QuantifierFree,
+    /// Universal quantifiers only (∀*).
+    Universal,
+    /// Existential quantifiers only (∃*).
+    Existential,
+    /// Function-free with no existential quantifier in the scope of a
+    /// universal one (∃*∀*, Bernays–Schönfinkel).
+    EffectivelyPropositional,
+    /// Function-free with unary and nullary predicates only.
+    Monadic,
+    /// Function-free with at most two variable names (FO²).
+    TwoVariable,
+    EqualityFree,
+    /// No function symbols of positive arity. Constants are allowed.
+    FunctionFree,
+    /// Every clause of the clause form has at most one positive literal.
+    Horn,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Polarity {
+    Positive,
+    Negative,
+    Both,
+}
+
+impl Polarity {
+    fn flip(self) -> Polarity {
+        match self {
+            Polarity::Positive => Polarity::Negative,
+            Polarity::Negative => Polarity::Positive,
+            Polarity::Both => Polarity::Both,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Quantifiers {
+    universal: bool,
+    existential: bool,
+    existential_under_universal: bool,
+}
+
+fn _scan(fml: &Formula, polarity: Polarity, under_universal: bool, acc: &mut Quantifiers) {
+    match fml {
+        Formula::Not(fml) => _scan(fml, polarity.flip(), under_universal, acc),
+        Formula::And(lhs, rhs) | Formula::Or(lhs, rhs) => {
+            _scan(lhs, polarity, under_universal, acc);
+            _scan(rhs, polarity, under_universal, acc);
+        }
+        Formula::Implies(lhs, rhs) => {
+            _scan(lhs, polarity.flip(), under_universal, acc);
+            _scan(rhs, polarity, under_universal, acc);
+        }
+        Formula::Iff(lhs, rhs) => {
+            _scan(lhs, Polarity::Both, under_universal, acc);
+            _scan(rhs, Polarity::Both, under_universal, acc);
+        }
+        Formula::Forall(_, body) | Formula::Exists(_, body) => {
+            let forall = matches!(fml, Formula::Forall(_, _));
+            let (universal, existential) = match polarity {
+                Polarity::Positive => (forall, !forall),
+                Polarity::Negative => (!forall, forall),
+                Polarity::Both => (true, true),
+            };
+            acc.universal |= universal;
+            acc.existential |= existential;
+            acc.existential_under_universal |= existential && under_universal;
+            _scan(body, polarity, under_universal || universal, acc);
+        }
+        Formula::Pred(_, _) | Formula::Equal(_, _) | Formula::Top | Formula::Bottom => {}
+    }
+}
+
+impl Formula {
+    /// The fragments the formula belongs to, in the order of `Fragment`.
+    pub fn classify(&self) -> Vec<Fragment> {
+        let mut quantifiers = Quantifiers::default();
+        _scan(self, Polarity::Positive, false, &mut quantifiers);
+        let vars = self
+            .get_free_vars()
+            .into_iter()
+            .chain(self.get_bound_vars())
+            .collect::<HashSet<_>>();
+        let preds = self.get_preds();
+        let function_free = self.get_funcs().iter().all(|sym| sym.arity == 0);
+        let equality_free = !self
+            .iter_subformulas()
+            .any(|f| matches!(f, Formula::Equal(_, _)));
+        let quantifier_free = !quantifiers.universal && !quantifiers.existential;
+        let horn = self
+            .to_cnf()
+            .iter()
+            .all(|clause| clause.iter().filter(|literal| literal.positive).count() <= 1);
+
+        let mut fragments = vec![];
+        let mut add = |fragment, member| {
+            if member {
+                fragments.push(fragment);
+            }
+        };
+        add(Fragment::Ground, vars.is_empty());
+        add(
+            Fragment::Propositional,
+            quantifier_free && equality_free && preds.iter().all(|sym| sym.arity == 0),
+        );
+        add(Fragment::QuantifierFree, quantifier_free);
+        add(Fragment::Universal, !quantifiers.existential);
+        add(Fragment::Existential, !quantifiers.universal);
+        add(
+            Fragment::EffectivelyPropositional,
+            function_free && !quantifiers.existential_under_universal,
+        );
+        add(
+            Fragment::Monadic,
+            function_free && preds.iter().all(|sym| sym.arity <= 1),
+        );
+        add(Fragment::TwoVariable, function_free && vars.len() <= 2);
+        add(Fragment::EqualityFree, equality_free);
+        add(Fragment::FunctionFree, function_free);
+        add(Fragment::Horn, horn);
+        fragments
+    }
+}
@@ -0,0 +1,284 @@
+use crate::fresh::*;
+use crate::language::*;
+use std::collections::{HashMap, HashSet};
+
+/// An atomic formula (`Pred` or `Equal`) or its negation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Literal {
+    pub atom: Formula,
+    pub positive: bool,
+}
+
+impl Literal {
+    pub fn negate(&self) -> Literal {
+        Literal {
+            atom: self.atom.clone(),
+            positive: !self.positive,
+        }
+    }
+
+    pub fn to_formula(&self) -> Formula {
+        if self.positive {
+            self.atom.clone()
+        } else {
+            not!(self.atom.clone())
+        }
+    }
+}
+
+/// A disjunction of literals. The empty clause is false.
+pub type Clause = HashSet<Literal>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CnfConversion {
+    /// Distributes `∨` over `∧`, giving an equivalent matrix at the price of
+    /// exponentially many clauses in the worst case.
+    Distributive,
+    /// Names every compound subformula with a fresh predicate over its free
+    /// variables, giving linearly many clauses that are equisatisfiable.
+    Tseitin,
+}
+
+fn _literal(fml: &Formula) -> Option<Literal> {
+    match fml {
+        Formula::Pred(_, _) | Formula::Equal(_, _) => Some(Literal {
+            atom: fml.clone(),
+            positive: true,
+        }),
+        Formula::Not(atom) => _literal(atom).map(|l| l.negate()),
+        _ => None,
+    }
+}
+
+fn _nnf(fml: &Formula, positive: bool) -> Formula {
+    use Formula::*;
+    match fml {
+        Pred(_, _) | Equal(_, _) => {
+            if positive {
+                fml.clone()
+            } else {
+                not!(fml.clone())
+            }
+        }
+        Top | Bottom => {
+            if (fml == &Top) == positive {
+                Top
+            } else {
+                Bottom
+            }
+        }
+        Not(fml) => _nnf(fml, !positive),
+        And(lhs, rhs) | Or(lhs, rhs) => {
+            let lhs = _nnf(lhs, positive);
+            let rhs = _nnf(rhs, positive);
+            if matches!(fml, And(_, _)) == positive {
+                match (lhs, rhs) {
+                    (Bottom, _) | (_, Bottom) => Bottom,
+                    (Top, fml) | (fml, Top) => fml,
+                    (lhs, rhs) => and!(lhs, rhs),
+                }
+            } else {
+                match (lhs, rhs) {
+                    (Top, _) | (_, Top) => Top,
+                    (Bottom, fml) | (fml, Bottom) => fml,
+                    (lhs, rhs) => or!(lhs, rhs),
+                }
+            }
+        }
+        Implies(lhs, rhs) => _nnf(&or!(not!(*lhs.clone()), *rhs.clone()), positive),
+        Iff(lhs, rhs) => _nnf(
+            &and!(
+                implies!(*lhs.clone(), *rhs.clone()),
+                implies!(*rhs.clone(), *lhs.clone())
+            ),
+            positive,
+        ),
+        Forall(var, body) | Exists(var, body) => {
+            let body = _nnf(body, positive);
+            match body {
+                Top | Bottom => body,
+                _ if matches!(fml, Forall(_, _)) == positive => forall!(var.clone(), body),
+                _ => exists!(var.clone(), body),
+            }
+        }
+    }
+}
+
+fn _rename(term: &Term, env: &HashMap<Term, Term>) -> Term {
+    match term {
+        Term::Var(_) => env.get(term).cloned().unwrap_or_else(|| term.clone()),
+        Term::Func(name, terms) => Term::Func(
+            name.clone(),
+            terms.iter().map(|t| _rename(t, env)).collect(),
+        ),
+    }
+}
+
+// Drops the universal quantifiers of a Skolemized NNF formula, renaming
+// variables bound more than once apart.
+fn _matrix(
+    fml: &Formula,
+    env: &HashMap<Term, Term>,
+    taken: &mut HashSet<Term>,
+    symbols: &mut SymbolGenerator,
+) -> Formula {
+    match fml {
+        Formula::Pred(name, terms) => Formula::Pred(
+            name.clone(),
+            terms.iter().map(|t| _rename(t, env)).collect(),
+        ),
+        Formula::Equal(lhs, rhs) => Formula::Equal(_rename(lhs, env), _rename(rhs, env)),
+        Formula::Not(atom) => not!(_matrix(atom, env, taken, symbols)),
+        Formula::And(lhs, rhs) => and!(
+            _matrix(lhs, env, taken, symbols),
+            _matrix(rhs, env, taken, symbols)
+        ),
+        Formula::Or(lhs, rhs) => or!(
+            _matrix(lhs, env, taken, symbols),
+            _matrix(rhs, env, taken, symbols)
+        ),
+        Formula::Forall(var, body) => {
+            let mut env = env.clone();
+            match var {
+                Term::Var(name) if taken.contains(var) => {
+                    env.insert(var.clone(), symbols.fresh_var(name));
+                }
+                _ => {
+                    env.remove(var);
+                    taken.insert(var.clone());
+                }
+            }
+            _matrix(body, &env, taken, symbols)
+        }
+        _ => fml.clone(),
+    }
+}
+
+fn _is_tautology(clause: &Clause) -> bool {
+    clause.iter().any(|l| clause.contains(&l.negate()))
+}
+
+fn _distribute(fml: &Formula) -> Vec<Clause> {
+    match fml {
+        Formula::Top => vec![],
+        Formula::Bottom => vec![Clause::new()],
+        Formula::And(lhs, rhs) => {
+            let mut clauses = _distribute(lhs);
+            for clause in _distribute(rhs) {
+                if !clauses.contains(&clause) {
+                    clauses.push(clause);
+                }
+            }
+            clauses
+        }
+        Formula::Or(lhs, rhs) => {
+            let rclauses = _distribute(rhs);
+            let mut clauses = vec![];
+            for lclause in _distribute(lhs) {
+                for rclause in &rclauses {
+                    let clause = lclause.union(rclause).cloned().collect::<Clause>();
+                    if !_is_tautology(&clause) && !clauses.contains(&clause) {
+                        clauses.push(clause);
+                    }
+                }
+            }
+            clauses
+        }
+        fml => vec![_literal(fml).into_iter().collect()],
+    }
+}
+
+// Returns a literal equisatisfiably standing for `fml`, adding the clauses
+// defining the introduced predicates. Only the implication from the new atom
+// to the subformula is needed since NNF subformulas occur positively.
+fn _define(fml: &Formula, clauses: &mut Vec<Clause>, symbols: &mut SymbolGenerator) -> Literal {
+    if let Some(literal) = _literal(fml) {
+        return literal;
+    }
+    let mut vars = fml.get_free_vars().into_iter().collect::<Vec<_>>();
+    vars.sort_by_key(|v| format!("{}", v));
+    let pred = symbols.fresh_pred("ts", vars.len() as u32);
+    let name = Literal {
+        atom: Formula::Pred(pred.name, vars),
+        positive: true,
+    };
+    match fml {
+        Formula::And(lhs, rhs) => {
+            for fml in [lhs, rhs].iter() {
+                let literal = _define(fml, clauses, symbols);
+                clauses.push(vec![name.negate(), literal].into_iter().collect());
+            }
+        }
+        Formula::Or(_, _) => {
+            let mut clause = _disjuncts(fml, clauses, symbols);
+            clause.insert(name.negate());
+            clauses.push(clause);
+        }
+        _ => unreachable!(),
+    }
+    name
+}
+
+fn _disjuncts(fml: &Formula, clauses: &mut Vec<Clause>, symbols: &mut SymbolGenerator) -> Clause {
+    match fml {
+        Formula::Or(lhs, rhs) => {
+            let mut clause = _disjuncts(lhs, clauses, symbols);
+            clause.extend(_disjuncts(rhs, clauses, symbols));
+            clause
+        }
+        fml => vec![_define(fml, clauses, symbols)].into_iter().collect(),
+    }
+}
+
+fn _definitional(fml: &Formula, clauses: &mut Vec<Clause>, symbols: &mut SymbolGenerator) {
+    match fml {
+        Formula::Top => {}
+        Formula::Bottom => clauses.push(Clause::new()),
+        Formula::And(lhs, rhs) => {
+            _definitional(lhs, clauses, symbols);
+            _definitional(rhs, clauses, symbols);
+        }
+        fml => {
+            let clause = _disjuncts(fml, clauses, symbols);
+            if !_is_tautology(&clause) {
+                clauses.push(clause);
+            }
+        }
+    }
+}
+
+impl Formula {
+    /// Negation normal form: only `∧`, `∨`, `∀`, `∃` and negated atoms, with
+    /// `⊤`/`⊥` folded away unless the whole formula is one of them.
+    pub fn to_nnf(&self) -> Formula {
+        _nnf(self, true)
+    }
+
+    /// Clause form of the formula with the distributive conversion.
+    pub fn to_cnf(&self) -> Vec<Clause> {
+        self.to_cnf_with(CnfConversion::Distributive, &mut SymbolGenerator::new())
+    }
+
+    /// Clause form of the formula. Existential quantifiers are Skolemized
+    /// and the remaining universally quantified variables, renamed apart,
+    /// are left free in the clauses. The Skolem functions and, with
+    /// `CnfConversion::Tseitin`, the definition predicates are recorded in
+    /// `symbols`.
+    pub fn to_cnf_with(
+        &self,
+        conversion: CnfConversion,
+        symbols: &mut SymbolGenerator,
+    ) -> Vec<Clause> {
+        let skolemized = self.to_nnf().skolemize(symbols);
+        let mut taken = skolemized.get_free_vars();
+        let matrix = _matrix(&skolemized, &HashMap::new(), &mut taken, symbols);
+        match conversion {
+            CnfConversion::Distributive => _distribute(&matrix),
+            CnfConversion::Tseitin => {
+                let mut clauses = vec![];
+                _definitional(&matrix, &mut clauses, symbols);
+                clauses
+            }
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use crate::adapt::*;
+use crate::language::*;
+use crate::proof::*;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+
+// Why two terms were merged: the given equation of that index, or the
+// congruence of their arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Reason {
+    Given(usize),
+    Congruence,
+}
+
+/// Congruence closure of equations between terms, variables standing for
+/// unknown constants. Every merge is recorded with its reason so that
+/// derived equalities can be explained by the given equations.
+#[derive(Debug, Clone, Default)]
+pub struct CongruenceClosure {
+    terms: Vec<Term>,
+    ids: HashMap<Term, usize>,
+    parents: Vec<usize>,
+    // Applications with an argument in the class, by representative.
+    uses: Vec<Vec<usize>>,
+    // Applications by symbol and representatives of their arguments.
+    signatures: HashMap<(String, Vec<usize>), usize>,
+    // The merges made, a forest over the terms.
+    edges: Vec<Vec<(usize, Reason)>>,
+    equations: Vec<(Term, Term)>,
+    pending: Vec<(usize, usize, Reason)>,
+}
+
+impl CongruenceClosure {
+    pub fn new() -> CongruenceClosure {
+        CongruenceClosure::default()
+    }
+
+    fn _find(&self, mut id: usize) -> usize {
+        while self.parents[id] != id {
+            id = self.parents[id];
+        }
+        id
+    }
+
+    fn _signature(&self, id: usize) -> Option<(String, Vec<usize>)> {
+        match &self.terms[id] {
+            Term::Func(name, args) if !args.is_empty() => Some((
+                name.clone(),
+                args.iter().map(|a| self._find(self.ids[a])).collect(),
+            )),
+            _ => None,
+        }
+    }
+
+    // Records the signature of `id`, scheduling a merge with an application
+    // of the same signature.
+    fn _register(&mut self, id: usize) {
+        if let Some(signature) = self._signature(id) {
+            match self.signatures.get(&signature) {
+                Some(&other) if self._find(other) != self._find(id) => {
+                    self.pending.push((id, other, Reason::Congruence))
+                }
+                Some(_) => {}
+                None => {
+                    self.signatures.insert(signature, id);
+                }
+            }
+        }
+    }
+
+    fn _close(&mut self) {
+        while let Some((lhs, rhs, reason)) = self.pending.pop() {
+            let (mut from, mut to) = (self._find(lhs), self._find(rhs));
+            if from == to {
+                continue;
+            }
+            self.edges[lhs].push((rhs, reason));
+            self.edges[rhs].push((lhs, reason));
+            if self.uses[from].len() > self.uses[to].len() {
+                std::mem::swap(&mut from, &mut to);
+            }
+            self.parents[from] = to;
+            let uses = std::mem::take(&mut self.uses[from]);
+            for &id in &uses {
+                self._register(id);
+            }
+            self.uses[to].extend(uses);
+        }
+    }
+
+    /// Adds `term` and its subterms, returning its index.
+    pub fn add_term(&mut self, term: &Term) -> usize {
+        if let Some(&id) = self.ids.get(term) {
+            return id;
+        }
+        let args = match term {
+            Term::Func(_, args) => args.iter().map(|a| self.add_term(a)).collect(),
+            Term::Var(_) => vec![],
+        };
+        let id = self.terms.len();
+        self.terms.push(term.clone());
+        self.ids.insert(term.clone(), id);
+        self.parents.push(id);
+        self.uses.push(vec![]);
+        self.edges.push(vec![]);
+        for arg in args {
+            let root = self._find(arg);
+            self.uses[root].push(id);
+        }
+        self._register(id);
+        self._close();
+        id
+    }
+
+    /// Adds the equation `lhs = rhs` and closes under congruence.
+    pub fn merge(&mut self, lhs: &Term, rhs: &Term) {
+        let (l, r) = (self.add_term(lhs), self.add_term(rhs));
+        let index = self.equations.len();
+        self.equations.push((lhs.clone(), rhs.clone()));
+        self.pending.push((l, r, Reason::Given(index)));
+        self._close();
+    }
+
+    pub fn are_equal(&mut self, lhs: &Term, rhs: &Term) -> bool {
+        let (l, r) = (self.add_term(lhs), self.add_term(rhs));
+        self._find(l) == self._find(r)
+    }
+
+    // The merges on the path from `from` to `to` in the forest of merges.
+    fn _path(&self, from: usize, to: usize) -> Option<Vec<(usize, usize, Reason)>> {
+        let mut previous = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+        previous.insert(from, None);
+        while let Some(id) = queue.pop_front() {
+            if id == to {
+                break;
+            }
+            for &(next, reason) in &self.edges[id] {
+                if let Entry::Vacant(entry) = previous.entry(next) {
+                    entry.insert(Some((id, reason)));
+                    queue.push_back(next);
+                }
+            }
+        }
+        let mut path = vec![];
+        let mut id = to;
+        while let Some(step) = previous.get(&id)? {
+            path.push((step.0, id, step.1));
+            id = step.0;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Indices of the given equations that `lhs = rhs` follows from, or
+    /// `None` if it does not follow.
+    pub fn explain(&self, lhs: &Term, rhs: &Term) -> Option<Vec<usize>> {
+        let (l, r) = (*self.ids.get(lhs)?, *self.ids.get(rhs)?);
+        let mut used = vec![];
+        for (from, to, reason) in self._path(l, r)? {
+            match reason {
+                Reason::Given(index) => used.push(index),
+                Reason::Congruence => {
+                    for (a, b) in _arguments(&self.terms[from], &self.terms[to]) {
+                        used.extend(self.explain(a, b)?);
+                    }
+                }
+            }
+        }
+        used.sort_unstable();
+        used.dedup();
+        Some(used)
+    }
+
+    /// A proof of `E ⇒ lhs = rhs` from some of the given equations `E`, by
+    /// the equality rules of LK, or `None` if it does not follow.
+    pub fn prove(&self, lhs: &Term, rhs: &Term) -> Option<LK> {
+        if lhs == rhs {
+            return Some(LK::Axiom(_sequent(
+                vec![],
+                vec![equal!(lhs.clone(), rhs.clone())],
+            )));
+        }
+        let (l, r) = (*self.ids.get(lhs)?, *self.ids.get(rhs)?);
+        let mut proof: Option<LK> = None;
+        for (from, to, reason) in self._path(l, r)? {
+            let (a, b) = (&self.terms[from], &self.terms[to]);
+            let step = match reason {
+                Reason::Given(index) => {
+                    let (s, t) = &self.equations[index];
+                    let given = LK::Axiom(_sequent(
+                        vec![equal!(s.clone(), t.clone())],
+                        vec![equal!(s.clone(), t.clone())],
+                    ));
+                    if (s, t) == (a, b) {
+                        given
+                    } else {
+                        _cut(given, _symmetry(a, b))
+                    }
+                }
+                Reason::Congruence => {
+                    let pairs = _arguments(a, b);
+                    let reflexivity =
+                        LK::Axiom(_sequent(vec![], vec![equal!(a.clone(), a.clone())]));
+                    let mut step = _congruence(reflexivity, pairs.clone());
+                    for (x, y) in pairs.into_iter().rev() {
+                        step = _cut(self.prove(x, y)?, step);
+                    }
+                    step
+                }
+            };
+            proof = Some(match proof {
+                None => step,
+                Some(proof) => {
+                    let s = proof.last().suc_last().clone();
+                    let first = match &s {
+                        Formula::Equal(first, _) => first.clone(),
+                        _ => unreachable!(),
+                    };
+                    _cut(proof, _cut(step, _transitivity(&first, a, b)))
+                }
+            });
+        }
+        proof
+    }
+}
+
+fn _sequent(antecedent: Vec<Formula>, succedent: Vec<Formula>) -> Sequent {
+    Sequent {
+        antecedent,
+        succedent,
+    }
+}
+
+// The pairs of arguments of two applications of the same function.
+fn _arguments<'a>(lhs: &'a Term, rhs: &'a Term) -> Vec<(&'a Term, &'a Term)> {
+    match (lhs, rhs) {
+        (Term::Func(_, xs), Term::Func(_, ys)) => xs.iter().zip(ys).collect(),
+        _ => vec![],
+    }
+}
+
+// Infers `s = t, Γ ⇒ Δ, replaced` from the proof of `Γ ⇒ Δ, A` by `=R`.
+fn _replace(proof: LK, s: &Term, t: &Term, replaced: Formula) -> LK {
+    let mut conclusion = proof.last().clone();
+    conclusion
+        .antecedent
+        .insert(0, equal!(s.clone(), t.clone()));
+    conclusion.succedent.pop();
+    conclusion.succedent.push(replaced);
+    LK::EqualityRight(Box::new(proof), conclusion)
+}
+
+// A proof of `b = a ⇒ a = b`.
+fn _symmetry(a: &Term, b: &Term) -> LK {
+    let reflexivity = LK::Axiom(_sequent(vec![], vec![equal!(b.clone(), b.clone())]));
+    _replace(reflexivity, b, a, equal!(a.clone(), b.clone()))
+}
+
+// A proof of `b = c, a = b ⇒ a = c`.
+fn _transitivity(a: &Term, b: &Term, c: &Term) -> LK {
+    let fml = equal!(a.clone(), b.clone());
+    let axiom = LK::Axiom(_sequent(vec![fml.clone()], vec![fml]));
+    _replace(axiom, b, c, equal!(a.clone(), c.clone()))
+}
+
+// A proof of `xₙ = yₙ, …, x₁ = y₁, Γ ⇒ A(y₁, …, yₙ)` from `proof` of
+// `Γ ⇒ A(x₁, …, xₙ)`, for the atom `A` or the equation `s = A`.
+fn _congruence(mut proof: LK, pairs: Vec<(&Term, &Term)>) -> LK {
+    for (i, (x, y)) in pairs.into_iter().enumerate() {
+        let replaced = match proof.last().suc_last().clone() {
+            Formula::Pred(name, mut args) => {
+                args[i] = y.clone();
+                Formula::Pred(name, args)
+            }
+            Formula::Equal(lhs, Term::Func(name, mut args)) => {
+                args[i] = y.clone();
+                equal!(lhs, Term::Func(name, args))
+            }
+            _ => unreachable!(),
+        };
+        proof = _replace(proof, x, y, replaced);
+    }
+    proof
+}
+
+// Cuts the formula `proof` proves, the last of its succedent, with its
+// first occurrence in the antecedent of `other`.
+fn _cut(proof: LK, other: LK) -> LK {
+    let fml = proof.last().suc_last().clone();
+    let mut target = other.last().clone();
+    let i = target.antecedent.iter().position(|f| *f == fml).unwrap();
+    let moved = target.antecedent.remove(i);
+    target.antecedent.insert(0, moved);
+    let other = adapt(other, &target).unwrap();
+    let conclusion = _sequent(
+        [&proof.last().antecedent[..], other.last().ant_but_first()].concat(),
+        [proof.last().suc_but_last(), &other.last().succedent[..]].concat(),
+    );
+    LK::Cut(Box::new([proof, other]), conclusion)
+}
+
+/// Decides `sequent` of equations and predicates by congruence closure: it
+/// holds if an equation of the succedent, or an atom of the succedent with
+/// the arguments of one of the antecedent, follows from the equations of
+/// the antecedent. Proves it by the equality rules of LK.
+pub fn prove_by_congruence(sequent: &Sequent) -> Option<LK> {
+    let mut closure = CongruenceClosure::new();
+    for fml in sequent.antecedent.iter().chain(&sequent.succedent) {
+        match fml {
+            Formula::Equal(lhs, rhs) => {
+                closure.add_term(lhs);
+                closure.add_term(rhs);
+            }
+            Formula::Pred(_, args) => {
+                for arg in args {
+                    closure.add_term(arg);
+                }
+            }
+            _ => {}
+        }
+    }
+    for fml in &sequent.antecedent {
+        if let Formula::Equal(lhs, rhs) = fml {
+            closure.merge(lhs, rhs);
+        }
+    }
+    let atom = |fml: &Formula, name: &String, ys: &[Term]| match fml {
+        Formula::Pred(other, xs) if other == name && xs.len() == ys.len() => {
+            let pairs = xs.iter().zip(ys).collect::<Vec<_>>();
+            let axiom = LK::Axiom(_sequent(vec![fml.clone()], vec![fml.clone()]));
+            let mut step = _congruence(axiom, pairs.clone());
+            for (x, y) in pairs.into_iter().rev() {
+                step = _cut(closure.prove(x, y)?, step);
+            }
+            Some(step)
+        }
+        _ => None,
+    };
+    let proof = sequent.succedent.iter().find_map(|fml| match fml {
+        Formula::Equal(lhs, rhs) => closure.prove(lhs, rhs),
+        Formula::Pred(name, ys) => (sequent.antecedent.iter()).find_map(|f| atom(f, name, ys)),
+        _ => None,
+    })?;
+    Some(adapt(proof, sequent).unwrap())
+}
@@ -0,0 +1,388 @@
+use crate::adapt::*;
+use crate::check::*;
+use crate::fresh::*;
+use crate::language::*;
+use crate::proof::*;
+use crate::trace::instantiation;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fmt;
+
+/// Limits and checks for `LK::eliminate_cuts_with`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CutOptions {
+    /// Most reduction steps, each removing the occurrences of a cut formula
+    /// from the end sequents of two subproofs. Reductions may grow a proof
+    /// exponentially, this bounds the work. `None` for no limit.
+    pub max_steps: Option<usize>,
+    /// Most inferences of a proof built by a reduction. Moving a cut above
+    /// a branching inference copies the other subproof into each branch, so
+    /// few steps may build a huge proof. `None` for no limit.
+    pub max_size: Option<usize>,
+    /// Options the proof and the replacement of each cut are checked with.
+    pub check: CheckOptions,
+}
+
+impl Default for CutOptions {
+    fn default() -> CutOptions {
+        CutOptions {
+            max_steps: Some(100_000),
+            max_size: Some(10_000),
+            check: CheckOptions::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CutError {
+    /// The proof, or the replacement of a cut, does not check.
+    Invalid(Box<ProofError>),
+    TooManySteps,
+    TooLarge,
+    /// A cut formula is principal on both sides, with an inference by the
+    /// rule that reductions do not go through: an axiom of the theory, a
+    /// theory rule or an equality rule.
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for CutError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CutError::Invalid(error) => write!(f, "{}", error),
+            CutError::TooManySteps => write!(f, "step limit of cut elimination exceeded"),
+            CutError::TooLarge => write!(f, "size limit of cut elimination exceeded"),
+            CutError::Unsupported(rule) => write!(f, "cannot reduce a cut against {}", rule),
+        }
+    }
+}
+
+// Whether the last inference of `proof` has `fml` as a principal formula on
+// the given side, or is one reductions cannot move the cut above.
+fn _introduces(proof: &LK, fml: &Formula, left: bool) -> bool {
+    match proof {
+        LK::Axiom(_) | LK::TheoryAxiom(_) | LK::TheoryRule(_, _) => true,
+        _ if proof.spec().structural => false,
+        _ => {
+            let s = proof.last();
+            let (ka, ks) = proof.spec().principals;
+            if left {
+                s.antecedent[..ka].contains(fml)
+            } else {
+                s.succedent[s.succedent.len() - ks..].contains(fml)
+            }
+        }
+    }
+}
+
+fn _without(fmls: &[Formula], fml: &Formula) -> Vec<Formula> {
+    fmls.iter().filter(|f| *f != fml).cloned().collect()
+}
+
+fn _substitute(proof: &LK, var: &Term, term: &Term) -> LK {
+    let sub = |fmls: &[Formula]| {
+        fmls.iter()
+            .map(|f| f.substitute(var.clone(), term.clone()))
+            .collect()
+    };
+    proof.map_sequents(&mut |s: &Sequent| Sequent {
+        antecedent: sub(&s.antecedent),
+        succedent: sub(&s.succedent),
+    })
+}
+
+// The eigenvariable of a `ForallRight` or `ExistsLeft` inference.
+fn _eigenvariable(proof: &LK) -> Option<Term> {
+    match proof {
+        LK::ForallRight(p, s) | LK::ExistsLeft(p, s) => {
+            instantiation(proof.rule_name(), s, p.last())
+        }
+        _ => None,
+    }
+}
+
+struct Eliminator<'a> {
+    options: &'a CutOptions,
+    steps: usize,
+    symbols: SymbolGenerator,
+}
+
+impl Eliminator<'_> {
+    // `proof`, unless it is over the size limit.
+    fn _sized(&self, proof: LK) -> Result<LK, CutError> {
+        match self.options.max_size {
+            Some(max) if proof.size() > max => Err(CutError::TooLarge),
+            _ => Ok(proof),
+        }
+    }
+
+    fn _rename(&mut self, proof: &LK, var: &Term) -> LK {
+        let fresh = self.symbols.fresh_var(&var.to_string());
+        proof.with_premises(vec![_substitute(proof.premises()[0], var, &fresh)])
+    }
+
+    // `proof` with the eigenvariable of its last inference renamed apart
+    // from `vars`.
+    fn _rename_apart<'p>(&mut self, proof: &'p LK, vars: &HashSet<Term>) -> Cow<'p, LK> {
+        match _eigenvariable(proof) {
+            Some(var) if vars.contains(&var) => Cow::Owned(self._rename(proof, &var)),
+            _ => Cow::Borrowed(proof),
+        }
+    }
+
+    // `proof` with every eigenvariable renamed to a fresh variable, so terms
+    // can be substituted for its free variables.
+    fn _regularize(&mut self, proof: &LK) -> LK {
+        let premises = proof
+            .premises()
+            .iter()
+            .map(|p| self._regularize(p))
+            .collect();
+        let proof = proof.with_premises(premises);
+        match _eigenvariable(&proof) {
+            Some(var) => self._rename(&proof, &var),
+            None => proof,
+        }
+    }
+
+    // The last inference of `proof` again, from `premises` proving its
+    // premises up to structural rules, with `extra` added to the context
+    // and `removed` taken out of it on the given side.
+    fn _reinfer(
+        &mut self,
+        proof: &LK,
+        premises: Vec<LK>,
+        extra: &Sequent,
+        (left, removed): (bool, &Formula),
+    ) -> Result<LK, CutError> {
+        let rule = proof.rule_name();
+        let s = proof.last();
+        let (ka, ks) = proof.spec().principals;
+        let (front, ant) = s.antecedent.split_at(ka);
+        let (suc, back) = s.succedent.split_at(s.succedent.len() - ks);
+        let keep = |fmls: &[Formula], side: bool| match side == left {
+            true => _without(fmls, removed),
+            false => fmls.to_vec(),
+        };
+        let ant = [keep(ant, true), extra.antecedent.clone()].concat();
+        let suc = [extra.succedent.clone(), keep(suc, false)].concat();
+        let sequent = |front: &[Formula], back: &[Formula], copies: usize| Sequent {
+            antecedent: (front.iter().cloned())
+                .chain((0..copies).flat_map(|_| ant.iter().cloned()))
+                .collect(),
+            succedent: ((0..copies).flat_map(|_| suc.iter().cloned()))
+                .chain(back.iter().cloned())
+                .collect(),
+        };
+        let originals = proof.premises();
+        let targets = match proof {
+            // Its premises split the context, each gets all of it.
+            LK::ImpliesLeft(_, _) => vec![
+                sequent(&[], std::slice::from_ref(originals[0].last().suc_last()), 1),
+                sequent(
+                    std::slice::from_ref(originals[1].last().ant_first()),
+                    &[],
+                    1,
+                ),
+            ],
+            _ => (originals.iter())
+                .map(|p| {
+                    let p = p.last();
+                    let front = &p.antecedent[..p.antecedent.len() - (s.antecedent.len() - ka)];
+                    let back = &p.succedent[s.succedent.len() - ks..];
+                    sequent(front, back, 1)
+                })
+                .collect(),
+        };
+        let premises = (premises.into_iter().zip(&targets))
+            .map(|(p, target)| adapt(p, target).expect("a reduced premise is within its target"))
+            .collect();
+        let copies = if let LK::ImpliesLeft(_, _) = proof {
+            2
+        } else {
+            1
+        };
+        let proof = LK::from_parts(rule, premises, sequent(front, back, copies)).unwrap();
+        proof
+            .check_inference()
+            .map_err(|e| CutError::Invalid(Box::new(e)))?;
+        self._sized(adapt(proof, &sequent(front, back, 1)).unwrap())
+    }
+
+    // A cut-free proof of `Γ, Π* ⇒ Δ*, Λ` up to structural rules, from
+    // cut-free proofs of `Γ ⇒ Δ` and `Π ⇒ Λ`, where `Π*` and `Δ*` are `Π`
+    // and `Δ` without `fml`. This is Gentzen's mix, reduced on the rank of
+    // `fml` in either proof until it is principal on both sides, then on
+    // its degree.
+    fn _mix(&mut self, mut left: &LK, mut right: &LK, fml: &Formula) -> Result<LK, CutError> {
+        // A cut moves above a structural inference to its premise. This is
+        // a loop as `adapt` leaves long chains of them.
+        loop {
+            self.steps += 1;
+            if self.options.max_steps.is_some_and(|max| self.steps > max) {
+                return Err(CutError::TooManySteps);
+            }
+            if !left.last().succedent.contains(fml) {
+                return Ok(left.clone());
+            }
+            if !right.last().antecedent.contains(fml) {
+                return Ok(right.clone());
+            }
+            if matches!(left, LK::Axiom(s) if s.antecedent.contains(fml)) {
+                return Ok(right.clone());
+            }
+            if matches!(right, LK::Axiom(s) if s.succedent.contains(fml)) {
+                return Ok(left.clone());
+            }
+            if !_introduces(left, fml, false) {
+                if !left.spec().structural {
+                    break;
+                }
+                left = left.premises()[0];
+            } else if !_introduces(right, fml, true) && right.spec().structural {
+                right = right.premises()[0];
+            } else {
+                break;
+            }
+        }
+        let left = self._rename_apart(left, &right.last().to_formula().get_free_vars());
+        let right = self._rename_apart(right, &left.last().to_formula().get_free_vars());
+        if !_introduces(&left, fml, false) {
+            let premises = (left.premises().iter())
+                .map(|p| self._mix(p, &right, fml))
+                .collect::<Result<Vec<_>, _>>()?;
+            let extra = Sequent {
+                antecedent: _without(&right.last().antecedent, fml),
+                succedent: right.last().succedent.clone(),
+            };
+            return self._reinfer(&left, premises, &extra, (false, fml));
+        }
+        if !_introduces(&right, fml, true) {
+            let premises = (right.premises().iter())
+                .map(|q| self._mix(&left, q, fml))
+                .collect::<Result<Vec<_>, _>>()?;
+            let extra = Sequent {
+                antecedent: left.last().antecedent.clone(),
+                succedent: _without(&left.last().succedent, fml),
+            };
+            return self._reinfer(&right, premises, &extra, (true, fml));
+        }
+        self._reduce(&left, &right, fml)
+    }
+
+    // `_mix` where `fml` is principal in the last inferences of both proofs.
+    // The other occurrences are removed from their premises first, then
+    // the immediate subformulas are mixed.
+    fn _reduce(&mut self, left: &LK, right: &LK, fml: &Formula) -> Result<LK, CutError> {
+        use LK::*;
+        let supported = |p: &LK| {
+            !matches!(p, Axiom(_) | TheoryAxiom(_) | TheoryRule(_, _))
+                && !p.rule_name().starts_with("Equality")
+        };
+        if !supported(left) || !supported(right) {
+            let rule = if supported(left) { right } else { left };
+            return Err(CutError::Unsupported(rule.rule_name()));
+        }
+        let ls = (left.premises().iter())
+            .map(|p| self._mix(p, right, fml))
+            .collect::<Result<Vec<_>, _>>()?;
+        let rs = (right.premises().iter())
+            .map(|q| self._mix(left, q, fml))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (a, b) = match fml {
+            Formula::And(a, b)
+            | Formula::Or(a, b)
+            | Formula::Implies(a, b)
+            | Formula::Iff(a, b) => (&**a, &**b),
+            Formula::Not(a) => (&**a, &**a),
+            _ => (fml, fml),
+        };
+        match (left, right) {
+            (AndRight(_, _), AndLeft1(_, _)) => self._mix(&ls[0], &rs[0], a),
+            (AndRight(_, _), AndLeft2(_, _)) => self._mix(&ls[1], &rs[0], b),
+            (OrRight1(_, _), OrLeft(_, _)) => self._mix(&ls[0], &rs[0], a),
+            (OrRight2(_, _), OrLeft(_, _)) => self._mix(&ls[0], &rs[1], b),
+            (ImpliesRight(_, _), ImpliesLeft(_, _)) => {
+                let mixed = self._mix(&ls[0], &rs[1], b)?;
+                self._mix(&rs[0], &mixed, a)
+            }
+            (NotRight(_, _), NotLeft(_, _)) => self._mix(&rs[0], &ls[0], a),
+            (IffRight(_, _), IffLeft(_, _)) => {
+                let forward = self._mix(&rs[1], &ls[0], a)?;
+                let backward = self._mix(&ls[1], &rs[0], a)?;
+                self._mix(&forward, &backward, b)
+            }
+            (ForallRight(_, _), ForallLeft(q, _)) => {
+                let instance = q.last().ant_first();
+                let mut proof = ls[0].clone();
+                let eigenvariable = _eigenvariable(left);
+                let term = instantiation("ForallLeft", right.last(), q.last());
+                if let (Some(var), Some(term)) = (eigenvariable, term) {
+                    proof = _substitute(&self._regularize(&proof), &var, &term);
+                }
+                self._mix(&proof, &rs[0], instance)
+            }
+            (ExistsRight(p, _), ExistsLeft(_, _)) => {
+                let instance = p.last().suc_last();
+                let mut proof = rs[0].clone();
+                let eigenvariable = _eigenvariable(right);
+                let term = instantiation("ExistsRight", left.last(), p.last());
+                if let (Some(var), Some(term)) = (eigenvariable, term) {
+                    proof = _substitute(&self._regularize(&proof), &var, &term);
+                }
+                self._mix(&ls[0], &proof, instance)
+            }
+            _ => unreachable!("a formula is principal on both sides by dual rules"),
+        }
+    }
+
+    fn _eliminate(&mut self, proof: &LK) -> Result<LK, CutError> {
+        let premises = (proof.premises().iter())
+            .map(|p| self._eliminate(p))
+            .collect::<Result<Vec<_>, _>>()?;
+        match proof {
+            LK::Cut(_, conclusion) => {
+                let fml = premises[0].last().suc_last().clone();
+                let mixed = self._mix(&premises[0], &premises[1], &fml)?;
+                let proof = adapt(mixed, conclusion).expect("a mix proves part of the cut");
+                proof
+                    .check_with(&self.options.check)
+                    .map_err(|e| CutError::Invalid(Box::new(e)))?;
+                Ok(proof)
+            }
+            _ => Ok(proof.with_premises(premises)),
+        }
+    }
+}
+
+impl LK {
+    pub fn is_cut_free(&self) -> bool {
+        let mut stack = vec![self];
+        while let Some(proof) = stack.pop() {
+            if let LK::Cut(_, _) = proof {
+                return false;
+            }
+            stack.extend(proof.premises());
+        }
+        true
+    }
+
+    /// A cut-free proof of the same end sequent, see `eliminate_cuts_with`.
+    pub fn eliminate_cuts(&self) -> Result<LK, CutError> {
+        self.eliminate_cuts_with(&CutOptions::default())
+    }
+
+    /// A cut-free proof of the same end sequent, by Gentzen's reductions.
+    /// Cuts are replaced from the top down, each by a cut-free proof checked
+    /// before going on. Fails on cuts against theory or equality inferences
+    /// that no reduction moves the cut above.
+    pub fn eliminate_cuts_with(&self, options: &CutOptions) -> Result<LK, CutError> {
+        self.check_with(&options.check)
+            .map_err(|e| CutError::Invalid(Box::new(e)))?;
+        let mut eliminator = Eliminator {
+            options,
+            steps: 0,
+            symbols: SymbolGenerator::avoiding_proof(self),
+        };
+        eliminator._eliminate(self)
+    }
+}
@@ -0,0 +1,244 @@
+use crate::fresh::*;
+use crate::language::*;
+use crate::proof::*;
+use crate::signature::*;
+use crate::sort::Sort;
+use crate::theory::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Constructor {
+    pub name: String,
+    /// Sorts of the arguments. Arguments of the datatype's own sort are
+    /// recursive.
+    pub args: Vec<Sort>,
+}
+
+/// A free datatype: every value is built by exactly one constructor from
+/// uniquely determined arguments.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Datatype {
+    pub name: Sort,
+    pub constructors: Vec<Constructor>,
+}
+
+fn _fold_and(fmls: Vec<Formula>) -> Option<Formula> {
+    fmls.into_iter().rev().fold(None, |acc, fml| match acc {
+        Some(acc) => Some(and!(fml, acc)),
+        None => Some(fml),
+    })
+}
+
+fn _forall_all(vars: &[Term], fml: Formula) -> Formula {
+    vars.iter()
+        .rev()
+        .fold(fml, |fml, var| forall!(var.clone(), fml))
+}
+
+impl Datatype {
+    pub fn new(name: &str) -> Datatype {
+        Datatype {
+            name: name.into(),
+            constructors: vec![],
+        }
+    }
+
+    pub fn add_constructor(&mut self, name: &str, args: Vec<Sort>) {
+        self.constructors.push(Constructor {
+            name: name.into(),
+            args,
+        });
+    }
+
+    fn _symbols(&self) -> SymbolGenerator {
+        let mut symbols = SymbolGenerator::new();
+        for constructor in &self.constructors {
+            symbols.avoid_name(&constructor.name);
+        }
+        symbols
+    }
+
+    fn _fresh_vars(symbols: &mut SymbolGenerator, prefix: &str, n: usize) -> Vec<Term> {
+        (0..n).map(|_| symbols.fresh_var(prefix)).collect()
+    }
+
+    /// Declares the constructors with their sorts.
+    pub fn declare_in(&self, signature: &mut Signature) {
+        for constructor in &self.constructors {
+            signature.declare_function(
+                &constructor.name,
+                constructor.args.clone(),
+                self.name.clone(),
+            );
+        }
+    }
+
+    /// `∀x̄ ∀ȳ ¬(c(x̄) = d(ȳ))` for every pair of distinct constructors.
+    pub fn distinctness_axioms(&self) -> Vec<Formula> {
+        let mut axioms = vec![];
+        for (i, c) in self.constructors.iter().enumerate() {
+            for d in &self.constructors[i + 1..] {
+                let mut symbols = self._symbols();
+                let xs = Datatype::_fresh_vars(&mut symbols, "x", c.args.len());
+                let ys = Datatype::_fresh_vars(&mut symbols, "y", d.args.len());
+                let fml = not!(equal!(
+                    Term::Func(c.name.clone(), xs.clone()),
+                    Term::Func(d.name.clone(), ys.clone())
+                ));
+                axioms.push(_forall_all(&[xs, ys].concat(), fml));
+            }
+        }
+        axioms
+    }
+
+    /// `∀x̄ ∀ȳ (c(x̄) = c(ȳ) → x₁ = y₁ ∧ … ∧ xₙ = yₙ)` for every constructor
+    /// with arguments.
+    pub fn injectivity_axioms(&self) -> Vec<Formula> {
+        let mut axioms = vec![];
+        for c in &self.constructors {
+            let mut symbols = self._symbols();
+            let xs = Datatype::_fresh_vars(&mut symbols, "x", c.args.len());
+            let ys = Datatype::_fresh_vars(&mut symbols, "y", c.args.len());
+            let equalities = xs
+                .iter()
+                .zip(&ys)
+                .map(|(x, y)| equal!(x.clone(), y.clone()))
+                .collect();
+            if let Some(conclusion) = _fold_and(equalities) {
+                let fml = implies!(
+                    equal!(
+                        Term::Func(c.name.clone(), xs.clone()),
+                        Term::Func(c.name.clone(), ys.clone())
+                    ),
+                    conclusion
+                );
+                axioms.push(_forall_all(&[xs, ys].concat(), fml));
+            }
+        }
+        axioms
+    }
+
+    // `∀x̄ (φ(x_i) ∧ … → φ(c(x̄)))` over the recursive arguments `x_i`.
+    fn _step(&self, constructor: &Constructor, var: &Term, fml: &Formula) -> Formula {
+        let mut symbols = self._symbols();
+        symbols.avoid(fml);
+        symbols.avoid_term(var);
+        let xs = Datatype::_fresh_vars(&mut symbols, "x", constructor.args.len());
+        let hypotheses = xs
+            .iter()
+            .zip(&constructor.args)
+            .filter(|(_, sort)| **sort == self.name)
+            .map(|(x, _)| fml.substitute(var.clone(), x.clone()))
+            .collect();
+        let conclusion = fml.substitute(
+            var.clone(),
+            Term::Func(constructor.name.clone(), xs.clone()),
+        );
+        let step = match _fold_and(hypotheses) {
+            Some(hypothesis) => implies!(hypothesis, conclusion),
+            None => conclusion,
+        };
+        _forall_all(&xs, step)
+    }
+
+    /// The instance `step₁ ∧ … ∧ stepₙ → ∀var fml` of the induction schema,
+    /// with one step per constructor.
+    pub fn induction_axiom(&self, var: &Term, fml: &Formula) -> Formula {
+        let steps = self
+            .constructors
+            .iter()
+            .map(|c| self._step(c, var, fml))
+            .collect();
+        let conclusion = forall!(var.clone(), fml.clone());
+        match _fold_and(steps) {
+            Some(steps) => implies!(steps, conclusion),
+            None => conclusion,
+        }
+    }
+
+    /// Induction on the goal `Γ ⇒ Δ, ∀x φ`, giving the subgoals `Γ ⇒ Δ, stepᵢ`
+    /// of `induction_axiom`.
+    pub fn induction(&self, goal: &Sequent) -> Result<Induction, &'static str> {
+        let (var, fml) = match goal.succedent.last() {
+            Some(Formula::Forall(var, fml)) => (var, fml),
+            _ => return Err("Goal is not of the form Γ ⇒ Δ, ∀x φ."),
+        };
+        if self.constructors.is_empty() {
+            return Err("Datatype has no constructors.");
+        }
+        let subgoals = self
+            .constructors
+            .iter()
+            .map(|c| {
+                let mut subgoal = goal.clone();
+                *subgoal.succedent.last_mut().unwrap() = self._step(c, var, fml);
+                subgoal
+            })
+            .collect();
+        Ok(Induction {
+            goal: goal.clone(),
+            axiom: self.induction_axiom(var, fml),
+            subgoals,
+        })
+    }
+}
+
+/// An application of the induction schema of a datatype to a goal.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Induction {
+    pub goal: Sequent,
+    /// The instance of the induction schema used.
+    pub axiom: Formula,
+    /// One subgoal per constructor.
+    pub subgoals: Vec<Sequent>,
+}
+
+impl Induction {
+    /// A proof of `axiom, Γ ⇒ Δ, ∀x φ` from proofs of the subgoals, in order.
+    pub fn justify(&self, proofs: Vec<LK>) -> Result<LK, &'static str> {
+        if proofs.len() != self.subgoals.len()
+            || proofs
+                .iter()
+                .zip(&self.subgoals)
+                .any(|(p, s)| p.last() != s)
+        {
+            return Err("Proofs do not end in the subgoals.");
+        }
+        let (steps, conclusion) = match &self.axiom {
+            Formula::Implies(steps, conclusion) => (steps, conclusion),
+            _ => return Err("Induction axiom has no steps."),
+        };
+        let mut proofs = proofs.into_iter().rev();
+        let mut proof = proofs.next().unwrap();
+        for left in proofs {
+            let mut sequent = left.last().clone();
+            let lhs = sequent.succedent.pop().unwrap();
+            sequent
+                .succedent
+                .push(and!(lhs, proof.last().suc_last().clone()));
+            proof = LK::AndRight(Box::new([left, proof]), sequent);
+        }
+        if proof.last().suc_last() != &**steps {
+            return Err("Proofs do not end in the subgoals.");
+        }
+        let mut sequent = self.goal.clone();
+        sequent.antecedent.insert(0, self.axiom.clone());
+        Ok(LK::ImpliesLeft(
+            Box::new([
+                proof,
+                LK::Axiom(Sequent {
+                    antecedent: vec![(**conclusion).clone()],
+                    succedent: vec![(**conclusion).clone()],
+                }),
+            ]),
+            sequent,
+        ))
+    }
+}
+
+impl Theory {
+    /// Adds the distinctness and injectivity axioms of `datatype`.
+    pub fn add_datatype(&mut self, datatype: &Datatype) {
+        self.axioms.extend(datatype.distinctness_axioms());
+        self.axioms.extend(datatype.injectivity_axioms());
+    }
+}
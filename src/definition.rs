@@ -0,0 +1,151 @@
+use crate::language::*;
+use crate::substitution::*;
+use std::collections::{HashMap, HashSet};
+
+/// Definitions `P(x₁, …, xₙ) :↔ φ` of predicate symbols and
+/// `f(x₁, …, xₙ) := t` of function symbols, unfolded by
+/// `Formula::expand_definitions`.
+///
+/// Definitions may use the symbols defined before them but not, directly or
+/// through other definitions, the symbol they define.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Definitions {
+    pub(crate) predicates: HashMap<String, (Vec<Term>, Formula)>,
+    pub(crate) functions: HashMap<String, (Vec<Term>, Term)>,
+}
+
+fn _check_params(params: &[Term], free_vars: HashSet<Term>) -> Result<(), &'static str> {
+    let mut seen = HashSet::new();
+    for param in params {
+        if !matches!(param, Term::Var(_)) || !seen.insert(param) {
+            return Err("Parameters must be distinct variables.");
+        }
+    }
+    if free_vars.iter().any(|var| !seen.contains(var)) {
+        return Err("Definition has free variables that are not parameters.");
+    }
+    Ok(())
+}
+
+impl Definitions {
+    pub fn new() -> Definitions {
+        Definitions::default()
+    }
+
+    fn _is_defined(&self, name: &str) -> bool {
+        self.predicates.contains_key(name) || self.functions.contains_key(name)
+    }
+
+    /// Defines the predicate `name` by `body` with the free variables
+    /// `params`.
+    pub fn define_predicate(
+        &mut self,
+        name: &str,
+        params: Vec<Term>,
+        body: Formula,
+    ) -> Result<(), &'static str> {
+        if self._is_defined(name) {
+            return Err("Symbol is already defined.");
+        }
+        _check_params(&params, body.get_free_vars())?;
+        let expanded = body.expand_definitions(self)?;
+        if expanded.get_preds().iter().any(|sym| sym.name == name)
+            || expanded.get_funcs().iter().any(|sym| sym.name == name)
+        {
+            return Err("Definition is circular.");
+        }
+        self.predicates.insert(name.into(), (params, body));
+        Ok(())
+    }
+
+    /// Defines the function `name` by `body` with the variables `params`.
+    pub fn define_function(
+        &mut self,
+        name: &str,
+        params: Vec<Term>,
+        body: Term,
+    ) -> Result<(), &'static str> {
+        if self._is_defined(name) {
+            return Err("Symbol is already defined.");
+        }
+        _check_params(&params, body.get_vars())?;
+        let expanded = self._expand_term(&body)?;
+        if expanded.get_funcs().iter().any(|sym| sym.name == name) {
+            return Err("Definition is circular.");
+        }
+        self.functions.insert(name.into(), (params, body));
+        Ok(())
+    }
+
+    fn _instance(params: &[Term], args: Vec<Term>) -> Result<Substitution, &'static str> {
+        if params.len() != args.len() {
+            return Err("Defined symbol used with a different arity.");
+        }
+        Ok(params.iter().cloned().zip(args).collect())
+    }
+
+    fn _expand_term(&self, term: &Term) -> Result<Term, &'static str> {
+        match term {
+            Term::Var(_) => Ok(term.clone()),
+            Term::Func(name, terms) => {
+                let terms = terms
+                    .iter()
+                    .map(|t| self._expand_term(t))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match self.functions.get(name) {
+                    Some((params, body)) => {
+                        let sub = Definitions::_instance(params, terms)?;
+                        self._expand_term(&body.substitute_with(&sub))
+                    }
+                    None => Ok(Term::Func(name.clone(), terms)),
+                }
+            }
+        }
+    }
+
+    fn _expand(&self, fml: &Formula) -> Result<Formula, &'static str> {
+        let expand = |fml: &Formula| self._expand(fml).map(Box::new);
+        Ok(match fml {
+            Formula::Pred(name, terms) => {
+                let terms = terms
+                    .iter()
+                    .map(|t| self._expand_term(t))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match self.predicates.get(name) {
+                    Some((params, body)) => {
+                        let sub = Definitions::_instance(params, terms)?;
+                        self._expand(&body.substitute_with(&sub))?
+                    }
+                    None => Formula::Pred(name.clone(), terms),
+                }
+            }
+            Formula::Equal(lhs, rhs) => {
+                Formula::Equal(self._expand_term(lhs)?, self._expand_term(rhs)?)
+            }
+            Formula::Not(fml) => Formula::Not(expand(fml)?),
+            Formula::And(lhs, rhs) => Formula::And(expand(lhs)?, expand(rhs)?),
+            Formula::Or(lhs, rhs) => Formula::Or(expand(lhs)?, expand(rhs)?),
+            Formula::Implies(lhs, rhs) => Formula::Implies(expand(lhs)?, expand(rhs)?),
+            Formula::Iff(lhs, rhs) => Formula::Iff(expand(lhs)?, expand(rhs)?),
+            Formula::Forall(var, fml) => Formula::Forall(var.clone(), expand(fml)?),
+            Formula::Exists(var, fml) => Formula::Exists(var.clone(), expand(fml)?),
+            Formula::Top | Formula::Bottom => fml.clone(),
+        })
+    }
+}
+
+impl Term {
+    /// Unfolds the defined function symbols of the term.
+    pub fn expand_definitions(&self, defs: &Definitions) -> Result<Term, &'static str> {
+        defs._expand_term(self)
+    }
+}
+
+impl Formula {
+    /// Unfolds the defined symbols of the formula. Bound variables of the
+    /// definitions are renamed where they would capture an argument. Fails
+    /// if a defined symbol is used with another arity than in its definition.
+    pub fn expand_definitions(&self, defs: &Definitions) -> Result<Formula, &'static str> {
+        defs._expand(self)
+    }
+}
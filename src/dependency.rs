@@ -0,0 +1,216 @@
+use crate::datatype::*;
+use crate::definition::*;
+use crate::language::*;
+use crate::record::json_string;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
+
+/// The symbols each symbol is directly defined in terms of. Every symbol
+/// occurring is a node; symbols without a definition have no dependencies.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DependencyGraph {
+    pub dependencies: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Symbols depending on each other in a circle, starting from the least one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    pub symbols: Vec<String>,
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "cyclic dependency {} -> {}",
+            self.symbols.join(" -> "),
+            self.symbols[0]
+        )
+    }
+}
+
+fn _symbols(fml: &Formula) -> BTreeSet<String> {
+    fml.get_preds()
+        .into_iter()
+        .chain(fml.get_funcs())
+        .map(|sym| sym.name)
+        .collect()
+}
+
+impl DependencyGraph {
+    pub fn new() -> DependencyGraph {
+        DependencyGraph::default()
+    }
+
+    /// Records that `symbol` is defined in terms of `dependencies`.
+    pub fn add(&mut self, symbol: &str, dependencies: BTreeSet<String>) {
+        for dependency in &dependencies {
+            self.dependencies.entry(dependency.clone()).or_default();
+        }
+        self.dependencies
+            .entry(symbol.into())
+            .or_default()
+            .extend(dependencies);
+    }
+
+    /// Symbols `symbol` depends on directly or through other symbols.
+    pub fn transitive_dependencies(&self, symbol: &str) -> BTreeSet<String> {
+        let mut reached = BTreeSet::new();
+        let mut stack = vec![symbol.to_string()];
+        while let Some(symbol) = stack.pop() {
+            for dependency in self.dependencies.get(&symbol).into_iter().flatten() {
+                if reached.insert(dependency.clone()) {
+                    stack.push(dependency.clone());
+                }
+            }
+        }
+        reached
+    }
+
+    // Shortest path from `symbol` back to itself.
+    fn _cycle_through(&self, symbol: &str) -> Option<Cycle> {
+        let mut previous = BTreeMap::new();
+        let mut queue = VecDeque::from(vec![symbol.to_string()]);
+        while let Some(current) = queue.pop_front() {
+            for next in self.dependencies.get(&current).into_iter().flatten() {
+                if next == symbol {
+                    let mut symbols = vec![current.clone()];
+                    while let Some(prev) = previous.get(symbols.last().unwrap()) {
+                        symbols.push(String::clone(prev));
+                    }
+                    symbols.reverse();
+                    return Some(Cycle { symbols });
+                }
+                if !previous.contains_key(next) {
+                    previous.insert(next.clone(), current.clone());
+                    queue.push_back(next.clone());
+                }
+            }
+        }
+        None
+    }
+
+    /// One cycle for each group of mutually dependent symbols.
+    pub fn cycles(&self) -> Vec<Cycle> {
+        let mut seen = BTreeSet::new();
+        let mut cycles = vec![];
+        for symbol in self.dependencies.keys() {
+            if seen.contains(symbol) {
+                continue;
+            }
+            if let Some(cycle) = self._cycle_through(symbol) {
+                let reached = self.transitive_dependencies(symbol);
+                seen.extend(
+                    reached
+                        .into_iter()
+                        .filter(|s| self.transitive_dependencies(s).contains(symbol)),
+                );
+                cycles.push(cycle);
+            }
+        }
+        cycles
+    }
+
+    /// The symbols ordered so that each comes after its dependencies, or the
+    /// cycles preventing such an order.
+    pub fn topological_order(&self) -> Result<Vec<String>, Vec<Cycle>> {
+        let mut remaining = self
+            .dependencies
+            .iter()
+            .map(|(symbol, dependencies)| (symbol.clone(), dependencies.len()))
+            .collect::<BTreeMap<_, _>>();
+        let mut dependents = BTreeMap::<&String, Vec<&String>>::new();
+        for (symbol, dependencies) in &self.dependencies {
+            for dependency in dependencies {
+                dependents.entry(dependency).or_default().push(symbol);
+            }
+        }
+        let mut ready = remaining
+            .iter()
+            .filter(|(_, &count)| count == 0)
+            .map(|(symbol, _)| symbol.clone())
+            .collect::<BTreeSet<_>>();
+        let mut order = vec![];
+        while let Some(symbol) = ready.iter().next().cloned() {
+            ready.remove(&symbol);
+            for dependent in dependents.get(&symbol).into_iter().flatten() {
+                let count = remaining.get_mut(*dependent).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    ready.insert(String::clone(dependent));
+                }
+            }
+            order.push(symbol);
+        }
+        if order.len() == self.dependencies.len() {
+            Ok(order)
+        } else {
+            Err(self.cycles())
+        }
+    }
+
+    /// Node-link JSON as read by networkx's `node_link_graph`, with edges
+    /// pointing from symbols to their dependencies.
+    pub fn to_json(&self) -> String {
+        let nodes = self
+            .dependencies
+            .keys()
+            .map(|symbol| format!("{{\"id\": {}}}", json_string(symbol)))
+            .collect::<Vec<_>>();
+        let links = self
+            .dependencies
+            .iter()
+            .flat_map(|(symbol, dependencies)| {
+                dependencies.iter().map(move |dependency| {
+                    format!(
+                        "{{\"source\": {}, \"target\": {}}}",
+                        json_string(symbol),
+                        json_string(dependency)
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        format!(
+            "{{\"directed\": true, \"multigraph\": false, \"graph\": {{}}, \"nodes\": [{}], \"links\": [{}]}}",
+            nodes.join(", "),
+            links.join(", ")
+        )
+    }
+}
+
+impl Definitions {
+    /// The defined symbols with the symbols their definitions use.
+    pub fn dependency_graph(&self) -> DependencyGraph {
+        let mut graph = DependencyGraph::new();
+        for (name, (_, body)) in &self.predicates {
+            graph.add(name, _symbols(body));
+        }
+        for (name, (_, body)) in &self.functions {
+            graph.add(
+                name,
+                body.get_funcs().into_iter().map(|sym| sym.name).collect(),
+            );
+        }
+        graph
+    }
+}
+
+impl Datatype {
+    /// The sorts of the arguments of its constructors.
+    pub fn dependencies(&self) -> BTreeSet<String> {
+        self.constructors
+            .iter()
+            .flat_map(|c| c.args.iter().cloned())
+            .collect()
+    }
+
+    /// The graph of sorts of `datatypes`. Recursive datatypes show up as
+    /// cycles, which unlike for definitions are allowed.
+    pub fn dependency_graph(datatypes: &[Datatype]) -> DependencyGraph {
+        let mut graph = DependencyGraph::new();
+        for datatype in datatypes {
+            graph.add(&datatype.name, datatype.dependencies());
+        }
+        graph
+    }
+}
@@ -0,0 +1,65 @@
+use crate::check::*;
+use crate::proof::*;
+#[cfg(feature = "serde")]
+use crate::serialize::*;
+
+/// A proof that checks under one set of options and not under the other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerdictChange {
+    /// Position of the proof in the list checked.
+    pub index: usize,
+    pub before: Result<(), ProofError>,
+    pub after: Result<(), ProofError>,
+}
+
+/// The proofs whose verdict differs between `before` and `after`, in order.
+pub fn verdict_changes(
+    proofs: &[LK],
+    before: &CheckOptions,
+    after: &CheckOptions,
+) -> Vec<VerdictChange> {
+    proofs
+        .iter()
+        .enumerate()
+        .filter_map(|(index, proof)| {
+            let (before, after) = (proof.check_with(before), proof.check_with(after));
+            (before.is_ok() != after.is_ok()).then_some(VerdictChange {
+                index,
+                before,
+                after,
+            })
+        })
+        .collect()
+}
+
+/// The proofs that check with `options` only once contexts are compared up
+/// to permutation, see `CheckOptions::implicit_exchange`, or the other way
+/// round. These are the proofs that migrating between strict LK and a
+/// calculus on multisets affects.
+pub fn exchange_sensitive(proofs: &[LK], options: &CheckOptions) -> Vec<VerdictChange> {
+    let strict = CheckOptions {
+        implicit_exchange: false,
+        ..options.clone()
+    };
+    let multiset = CheckOptions {
+        implicit_exchange: true,
+        ..options.clone()
+    };
+    verdict_changes(proofs, &strict, &multiset)
+}
+
+/// `verdict_changes` for proofs stored as documents written by `to_json`.
+/// Fails with the position of the first document that does not read.
+#[cfg(feature = "serde")]
+pub fn stored_verdict_changes(
+    documents: &[&str],
+    before: &CheckOptions,
+    after: &CheckOptions,
+) -> Result<Vec<VerdictChange>, (usize, FormatError)> {
+    let proofs = documents
+        .iter()
+        .enumerate()
+        .map(|(i, document)| from_json(document).map_err(|e| (i, e)))
+        .collect::<Result<Vec<LK>, _>>()?;
+    Ok(verdict_changes(&proofs, before, after))
+}
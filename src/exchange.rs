@@ -0,0 +1,166 @@
+use crate::adapt::*;
+use crate::check::*;
+use crate::language::*;
+use crate::proof::*;
+use crate::theory::*;
+
+// The arrangements of `fmls` with `count` of them moved to the front, the
+// others keeping their order. Equal formulas are moved only once.
+fn _fronts(fmls: &[Formula], count: usize) -> Vec<Vec<Formula>> {
+    if count == 0 {
+        return vec![fmls.to_vec()];
+    }
+    let mut fronts = vec![];
+    for (i, fml) in fmls.iter().enumerate() {
+        if fmls[..i].contains(fml) {
+            continue;
+        }
+        let rest = [&fmls[..i], &fmls[i + 1..]].concat();
+        for tail in _fronts(&rest, count - 1) {
+            fronts.push([std::slice::from_ref(fml), &tail[..]].concat());
+        }
+    }
+    fronts
+}
+
+// Same as `_fronts`, moving the formulas to the back.
+fn _backs(fmls: &[Formula], count: usize) -> Vec<Vec<Formula>> {
+    let reversed = fmls.iter().rev().cloned().collect::<Vec<_>>();
+    let reverse = |fmls: Vec<Formula>| fmls.into_iter().rev().collect();
+    _fronts(&reversed, count).into_iter().map(reverse).collect()
+}
+
+// `fmls` ordered as they first occur in `order`, the others after them.
+fn _follow(fmls: &[Formula], order: &[Formula]) -> Vec<Formula> {
+    let mut rest = fmls.to_vec();
+    let mut ordered = vec![];
+    for fml in order {
+        if let Some(i) = rest.iter().position(|f| f == fml) {
+            ordered.push(rest.remove(i));
+        }
+    }
+    ordered.extend(rest);
+    ordered
+}
+
+// The numbers of principal formulas at the front of the antecedent and the
+// back of the succedent of the conclusion.
+fn _principal_counts(proof: &LK) -> (usize, usize) {
+    match proof {
+        LK::ContractionLeft(_, _) => (1, 0),
+        LK::ContractionRight(_, _) => (0, 1),
+        _ => proof.min_lengths().0,
+    }
+}
+
+// The arrangements of the premises of `proof`, one choice of active
+// formulas each, with their contexts ordered after `context`, that of the
+// conclusion.
+fn _premise_choices(proof: &LK, context: &Sequent) -> Vec<Vec<Sequent>> {
+    let (_, counts) = proof.min_lengths();
+    let mut choices = vec![vec![]];
+    for (premise, (ant, suc)) in proof.premises().into_iter().zip(counts) {
+        let s = premise.last();
+        let mut arrangements = vec![];
+        for antecedent in _fronts(&s.antecedent, ant) {
+            for succedent in _backs(&s.succedent, suc) {
+                let (active, rest) = antecedent.split_at(ant);
+                let antecedent = [active, &_follow(rest, &context.antecedent)].concat();
+                let (rest, active) = succedent.split_at(succedent.len() - suc);
+                let succedent = [&_follow(rest, &context.succedent)[..], active].concat();
+                arrangements.push(Sequent {
+                    antecedent,
+                    succedent,
+                });
+            }
+        }
+        choices = choices
+            .into_iter()
+            .flat_map(|choice: Vec<Sequent>| {
+                (arrangements.iter())
+                    .map(|a| [&choice[..], std::slice::from_ref(a)].concat())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+    }
+    choices
+}
+
+/// Permutations of the sides of the premises and of the conclusion of the
+/// last inference of `proof` under which it is a valid inference, with the
+/// rules of `theory`. Tries the formulas of the conclusion as principal
+/// formulas in turn and orders the contexts of the other sequents after
+/// those of the conclusion.
+pub fn arrangement(proof: &LK, theory: &Theory) -> Option<(Vec<Sequent>, Sequent)> {
+    let s = proof.last();
+    let (ant, suc) = _principal_counts(proof);
+    if s.antecedent.len() < ant || s.succedent.len() < suc {
+        return None;
+    }
+    for antecedent in _fronts(&s.antecedent, ant) {
+        for succedent in _backs(&s.succedent, suc) {
+            let context = Sequent {
+                antecedent: antecedent[ant..].to_vec(),
+                succedent: succedent[..succedent.len() - suc].to_vec(),
+            };
+            for premises in _premise_choices(proof, &context) {
+                // The contexts of the premises, in turn.
+                let (mut ants, mut sucs) = (vec![], vec![]);
+                for (premise, (a, b)) in premises.iter().zip(proof.min_lengths().1) {
+                    ants.extend_from_slice(&premise.antecedent[a..]);
+                    sucs.extend_from_slice(&premise.succedent[..premise.succedent.len() - b]);
+                }
+                let conclusion = Sequent {
+                    antecedent: [&antecedent[..ant], &_follow(&context.antecedent, &ants)].concat(),
+                    succedent: [
+                        &_follow(&context.succedent, &sucs)[..],
+                        &succedent[succedent.len() - suc..],
+                    ]
+                    .concat(),
+                };
+                let placeholders = premises.iter().cloned().map(LK::Axiom).collect();
+                match LK::from_parts(proof.rule_name(), placeholders, conclusion) {
+                    Some(inference) if is_valid_in(&inference, theory) => {
+                        return Some((premises, inference.last().clone()))
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    None
+}
+
+impl LK {
+    /// The same proof with the exchanges that `CheckOptions::implicit_exchange`
+    /// allows made explicit, so that it checks without the option. Fails
+    /// with the first inference that is invalid even up to exchange.
+    #[allow(clippy::result_large_err)]
+    pub fn with_exchanges(&self, options: &CheckOptions) -> Result<LK, ProofError> {
+        self.check_with(&CheckOptions {
+            implicit_exchange: true,
+            ..options.clone()
+        })?;
+        Ok(self._with_exchanges(&options.theory))
+    }
+
+    fn _with_exchanges(&self, theory: &Theory) -> LK {
+        let premises = self
+            .premises()
+            .into_iter()
+            .map(|p| p._with_exchanges(theory))
+            .collect::<Vec<_>>();
+        if is_valid_in(self, theory) {
+            return self.with_premises(premises);
+        }
+        let (arranged, conclusion) =
+            arrangement(self, theory).expect("the proof is valid up to exchange");
+        let premises = premises
+            .into_iter()
+            .zip(&arranged)
+            .map(|(p, s)| adapt(p, s).unwrap())
+            .collect();
+        let proof = LK::from_parts(self.rule_name(), premises, conclusion).unwrap();
+        adapt(proof, self.last()).unwrap()
+    }
+}
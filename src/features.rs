@@ -0,0 +1,143 @@
+use crate::language::*;
+
+/// Names of the entries of `Formula::features`, in order.
+pub const FEATURE_NAMES: [&str; 19] = [
+    "size",
+    "depth",
+    "preds",
+    "equalities",
+    "nots",
+    "ands",
+    "ors",
+    "implies",
+    "iffs",
+    "foralls",
+    "exists",
+    "quantifier_alternations",
+    "pred_symbols",
+    "func_symbols",
+    "free_vars",
+    "bound_vars",
+    "clauses",
+    "max_clause_width",
+    "mean_clause_width",
+];
+
+fn _count(fml: &Formula, counts: &mut [f64; 11]) {
+    counts[0] += 1.0;
+    match fml {
+        Formula::Pred(_, _) => counts[2] += 1.0,
+        Formula::Equal(_, _) => counts[3] += 1.0,
+        Formula::Not(fml) => {
+            counts[4] += 1.0;
+            _count(fml, counts);
+        }
+        Formula::And(lhs, rhs)
+        | Formula::Or(lhs, rhs)
+        | Formula::Implies(lhs, rhs)
+        | Formula::Iff(lhs, rhs) => {
+            match fml {
+                Formula::And(_, _) => counts[5] += 1.0,
+                Formula::Or(_, _) => counts[6] += 1.0,
+                Formula::Implies(_, _) => counts[7] += 1.0,
+                _ => counts[8] += 1.0,
+            }
+            _count(lhs, counts);
+            _count(rhs, counts);
+        }
+        Formula::Forall(_, fml) => {
+            counts[9] += 1.0;
+            _count(fml, counts);
+        }
+        Formula::Exists(_, fml) => {
+            counts[10] += 1.0;
+            _count(fml, counts);
+        }
+        Formula::Top | Formula::Bottom => {}
+    }
+}
+
+// Counts changes between ∀ and ∃ along the quantifier prefix of each branch,
+// taking the polarity of the position into account.
+fn _alternations(fml: &Formula, positive: bool, last: Option<bool>) -> u32 {
+    match fml {
+        Formula::Not(fml) => _alternations(fml, !positive, last),
+        Formula::And(lhs, rhs) | Formula::Or(lhs, rhs) => {
+            _alternations(lhs, positive, last).max(_alternations(rhs, positive, last))
+        }
+        Formula::Implies(lhs, rhs) => {
+            _alternations(lhs, !positive, last).max(_alternations(rhs, positive, last))
+        }
+        Formula::Iff(lhs, rhs) => [lhs, rhs]
+            .iter()
+            .flat_map(|f| {
+                vec![
+                    _alternations(f, positive, last),
+                    _alternations(f, !positive, last),
+                ]
+            })
+            .max()
+            .unwrap(),
+        Formula::Forall(_, body) | Formula::Exists(_, body) => {
+            let universal = matches!(fml, Formula::Forall(_, _)) == positive;
+            let step = match last {
+                Some(l) if l != universal => 1,
+                _ => 0,
+            };
+            step + _alternations(body, positive, Some(universal))
+        }
+        _ => 0,
+    }
+}
+
+fn _conjuncts<'a>(fml: &'a Formula, conjuncts: &mut Vec<&'a Formula>) {
+    match fml {
+        Formula::And(lhs, rhs) => {
+            _conjuncts(lhs, conjuncts);
+            _conjuncts(rhs, conjuncts);
+        }
+        fml => conjuncts.push(fml),
+    }
+}
+
+fn _width(fml: &Formula) -> u32 {
+    match fml {
+        Formula::Or(lhs, rhs) | Formula::Implies(lhs, rhs) => _width(lhs) + _width(rhs),
+        _ => 1,
+    }
+}
+
+impl Formula {
+    /// Numeric feature vector of the formula for learned guidance, laid out
+    /// as described by `FEATURE_NAMES`. Clause statistics are taken over the
+    /// conjuncts below the leading quantifiers, reading `∨` and `→` as
+    /// clause-building connectives.
+    pub fn features(&self) -> Vec<f64> {
+        let mut counts = [0.0; 11];
+        _count(self, &mut counts);
+        counts[1] = self.depth() as f64;
+
+        let mut matrix = self;
+        while let Formula::Forall(_, fml) | Formula::Exists(_, fml) = matrix {
+            matrix = fml;
+        }
+        let mut conjuncts = vec![];
+        _conjuncts(matrix, &mut conjuncts);
+        let widths = conjuncts.iter().map(|c| _width(c)).collect::<Vec<_>>();
+        let max_width = widths.iter().copied().max().unwrap_or(0);
+        let mean_width = widths.iter().sum::<u32>() as f64 / widths.len() as f64;
+
+        let mut features = counts.to_vec();
+        features.extend(vec![
+            _alternations(self, true, None) as f64,
+            self.get_preds().len() as f64,
+            self.get_funcs().len() as f64,
+            self.get_free_vars().len() as f64,
+            self.get_bound_vars().len() as f64,
+            conjuncts.len() as f64,
+            max_width as f64,
+            mean_width,
+        ]);
+        features
+    }
+}
@@ -0,0 +1,118 @@
+use crate::language::*;
+use crate::proof::*;
+use std::collections::HashSet;
+
+/// Hands out names that do not occur in the formulas registered with
+/// `avoid`, nor in any name handed out before. Function and predicate
+/// symbols introduced through `fresh_func` and `fresh_pred` are remembered so
+/// callers can extend their signature.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SymbolGenerator {
+    used: HashSet<String>,
+    introduced: Vec<NonLogicalSymbol>,
+    introduced_preds: Vec<NonLogicalSymbol>,
+}
+
+impl SymbolGenerator {
+    pub fn new() -> SymbolGenerator {
+        SymbolGenerator {
+            used: HashSet::new(),
+            introduced: vec![],
+            introduced_preds: vec![],
+        }
+    }
+
+    /// Generator avoiding every name of `fmls`.
+    pub fn avoiding<'a>(fmls: impl IntoIterator<Item = &'a Formula>) -> SymbolGenerator {
+        let mut symbols = SymbolGenerator::new();
+        fmls.into_iter().for_each(|fml| symbols.avoid(fml));
+        symbols
+    }
+
+    /// Generator avoiding every name occurring anywhere in `proof`, e.g. to
+    /// pick eigenvariables when extending it.
+    pub fn avoiding_proof(proof: &LK) -> SymbolGenerator {
+        let mut symbols = SymbolGenerator::new();
+        symbols.avoid_proof(proof);
+        symbols
+    }
+
+    pub fn avoid_name(&mut self, name: &str) {
+        self.used.insert(name.to_string());
+    }
+
+    /// Reserves every variable, function and predicate name of `fml`.
+    pub fn avoid(&mut self, fml: &Formula) {
+        for var in fml.get_free_vars().union(&fml.get_bound_vars()) {
+            if let Term::Var(name) = var {
+                self.used.insert(name.clone());
+            }
+        }
+        for sym in fml.get_funcs().into_iter().chain(fml.get_preds()) {
+            self.used.insert(sym.name);
+        }
+    }
+
+    /// Reserves every variable and function name of `term`.
+    pub fn avoid_term(&mut self, term: &Term) {
+        for var in term.get_vars() {
+            if let Term::Var(name) = var {
+                self.used.insert(name);
+            }
+        }
+        for sym in term.get_funcs() {
+            self.used.insert(sym.name);
+        }
+    }
+
+    pub fn avoid_sequent(&mut self, sequent: &Sequent) {
+        for fml in sequent.antecedent.iter().chain(&sequent.succedent) {
+            self.avoid(fml);
+        }
+    }
+
+    /// Reserves the names of every sequent of `proof`, so instances and
+    /// eigenvariables of quantifier inferences are covered too.
+    pub fn avoid_proof(&mut self, proof: &LK) {
+        proof.replay(
+            &mut |_: &'static str, _: &[&Sequent], conclusion: &Sequent| {
+                self.avoid_sequent(conclusion)
+            },
+        );
+    }
+
+    fn _fresh(&mut self, prefix: &str) -> String {
+        let name = (0..)
+            .map(|n| format!("{}{}", prefix, n))
+            .find(|name| !self.used.contains(name))
+            .unwrap();
+        self.used.insert(name.clone());
+        name
+    }
+
+    pub fn fresh_var(&mut self, prefix: &str) -> Term {
+        Term::Var(self._fresh(prefix))
+    }
+
+    pub fn fresh_func(&mut self, prefix: &str, arity: u32) -> NonLogicalSymbol {
+        let sym = nlsym!(self._fresh(prefix), arity);
+        self.introduced.push(sym.clone());
+        sym
+    }
+
+    pub fn fresh_pred(&mut self, prefix: &str, arity: u32) -> NonLogicalSymbol {
+        let sym = nlsym!(self._fresh(prefix), arity);
+        self.introduced_preds.push(sym.clone());
+        sym
+    }
+
+    /// Function symbols handed out by `fresh_func`, in order.
+    pub fn introduced(&self) -> &[NonLogicalSymbol] {
+        &self.introduced
+    }
+
+    /// Predicate symbols handed out by `fresh_pred`, in order.
+    pub fn introduced_preds(&self) -> &[NonLogicalSymbol] {
+        &self.introduced_preds
+    }
+}
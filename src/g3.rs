@@ -0,0 +1,341 @@
+use crate::adapt::*;
+use crate::language::*;
+use crate::proof::*;
+
+/// A proof in the multiset calculus G3c. The order of the formulas of a
+/// sequent does not matter, premises share the context of the conclusion,
+/// axioms carry arbitrary contexts and there are no structural rules.
+/// `ForallLeft` and `ExistsRight` keep the principal formula in the premise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum G3 {
+    /// `A, Γ ⇒ Δ, A`, `⊥, Γ ⇒ Δ`, `Γ ⇒ Δ, ⊤` or `Γ ⇒ Δ, t = t`.
+    Axiom(Sequent),
+    /// `A, B, Γ ⇒ Δ` to `A ∧ B, Γ ⇒ Δ`.
+    AndLeft(Box<G3>, Sequent),
+    AndRight(Box<[G3; 2]>, Sequent),
+    OrLeft(Box<[G3; 2]>, Sequent),
+    /// `Γ ⇒ Δ, A, B` to `Γ ⇒ Δ, A ∨ B`.
+    OrRight(Box<G3>, Sequent),
+    /// `Γ ⇒ Δ, A` and `B, Γ ⇒ Δ` to `A → B, Γ ⇒ Δ`.
+    ImpliesLeft(Box<[G3; 2]>, Sequent),
+    ImpliesRight(Box<G3>, Sequent),
+    /// `A, B, Γ ⇒ Δ` and `Γ ⇒ Δ, A, B` to `A ↔ B, Γ ⇒ Δ`.
+    IffLeft(Box<[G3; 2]>, Sequent),
+    /// `A, Γ ⇒ Δ, B` and `B, Γ ⇒ Δ, A` to `Γ ⇒ Δ, A ↔ B`.
+    IffRight(Box<[G3; 2]>, Sequent),
+    NotLeft(Box<G3>, Sequent),
+    NotRight(Box<G3>, Sequent),
+    /// `∀x A, A[t/x], Γ ⇒ Δ` to `∀x A, Γ ⇒ Δ`.
+    ForallLeft(Box<G3>, Sequent),
+    ForallRight(Box<G3>, Sequent),
+    ExistsLeft(Box<G3>, Sequent),
+    /// `Γ ⇒ Δ, ∃x A, A[t/x]` to `Γ ⇒ Δ, ∃x A`.
+    ExistsRight(Box<G3>, Sequent),
+}
+
+// `fmls` with one occurrence of each formula of `removed` taken out.
+fn _minus(fmls: &[Formula], removed: &[Formula]) -> Vec<Formula> {
+    let mut rest = fmls.to_vec();
+    for fml in removed {
+        if let Some(i) = rest.iter().position(|f| f == fml) {
+            rest.remove(i);
+        }
+    }
+    rest
+}
+
+fn _same(lhs: &[Formula], rhs: &[Formula]) -> bool {
+    lhs.len() == rhs.len() && _minus(lhs, rhs).is_empty()
+}
+
+fn _sequent(antecedent: Vec<Formula>, succedent: Vec<Formula>) -> Sequent {
+    Sequent {
+        antecedent,
+        succedent,
+    }
+}
+
+fn _infer(rule: &str, premises: Vec<LK>, sequent: Sequent) -> Option<LK> {
+    LK::from_parts(rule, premises, sequent).filter(|proof| proof.is_valid_inference())
+}
+
+impl G3 {
+    pub fn last(&self) -> &Sequent {
+        use G3::*;
+        match self {
+            Axiom(s) => s,
+            AndLeft(_, s)
+            | AndRight(_, s)
+            | OrLeft(_, s)
+            | OrRight(_, s)
+            | ImpliesLeft(_, s)
+            | ImpliesRight(_, s)
+            | IffLeft(_, s)
+            | IffRight(_, s)
+            | NotLeft(_, s)
+            | NotRight(_, s)
+            | ForallLeft(_, s)
+            | ForallRight(_, s)
+            | ExistsLeft(_, s)
+            | ExistsRight(_, s) => s,
+        }
+    }
+
+    pub fn premises(&self) -> Vec<&G3> {
+        use G3::*;
+        match self {
+            Axiom(_) => vec![],
+            AndLeft(p, _)
+            | OrRight(p, _)
+            | ImpliesRight(p, _)
+            | NotLeft(p, _)
+            | NotRight(p, _)
+            | ForallLeft(p, _)
+            | ForallRight(p, _)
+            | ExistsLeft(p, _)
+            | ExistsRight(p, _) => vec![&**p],
+            AndRight(ps, _)
+            | OrLeft(ps, _)
+            | ImpliesLeft(ps, _)
+            | IffLeft(ps, _)
+            | IffRight(ps, _) => ps.iter().collect(),
+        }
+    }
+
+    // The side of the principal formula, `None` for axioms, whose formulas
+    // are indexed through both sides.
+    fn _left(&self) -> Option<bool> {
+        use G3::*;
+        match self {
+            Axiom(_) => None,
+            AndLeft(_, _)
+            | OrLeft(_, _)
+            | ImpliesLeft(_, _)
+            | IffLeft(_, _)
+            | NotLeft(_, _)
+            | ForallLeft(_, _)
+            | ExistsLeft(_, _) => Some(true),
+            _ => Some(false),
+        }
+    }
+
+    // Derives the conclusion in LK from `premises`, proofs of the premises,
+    // with the `principal`-th formula of its side as the principal formula.
+    // `None` if the inference is not valid that way.
+    fn _step(&self, mut premises: Vec<LK>, principal: usize) -> Option<LK> {
+        use Formula::*;
+        let s = self.last();
+        let left = match self._left() {
+            Some(left) => left,
+            None => {
+                let axiom = match [&s.antecedent[..], &s.succedent[..]]
+                    .concat()
+                    .get(principal)?
+                {
+                    Bottom if principal < s.antecedent.len() => _sequent(vec![Bottom], vec![]),
+                    f if principal < s.antecedent.len() => {
+                        if !s.succedent.contains(f) {
+                            return None;
+                        }
+                        _sequent(vec![f.clone()], vec![f.clone()])
+                    }
+                    f => _sequent(vec![], vec![f.clone()]),
+                };
+                return _infer("Axiom", vec![], axiom).and_then(|p| adapt(p, s).ok());
+            }
+        };
+        let f = if left {
+            s.antecedent.get(principal)?
+        } else {
+            s.succedent.get(principal)?
+        };
+        let (mut g, mut d) = (s.antecedent.clone(), s.succedent.clone());
+        if left {
+            g.remove(principal);
+        } else {
+            d.remove(principal);
+        }
+        let seq = |front: &[Formula], back: &[Formula]| {
+            _sequent([front, &g].concat(), [&d[..], back].concat())
+        };
+        // Orders the premise as `front, Γ ⇒ Δ, back`.
+        let fit = |p: LK, front: &[Formula], back: &[Formula]| {
+            let target = seq(front, back);
+            if _same(&p.last().antecedent, &target.antecedent)
+                && _same(&p.last().succedent, &target.succedent)
+            {
+                adapt(p, &target).ok()
+            } else {
+                None
+            }
+        };
+        // The formula of the premise besides `front, Γ ⇒ Δ, back` on the
+        // side of the principal formula.
+        let extra = |p: &LK, front: &[Formula], back: &[Formula]| {
+            let target = seq(front, back);
+            let rest = if left {
+                _minus(&p.last().antecedent, &target.antecedent)
+            } else {
+                _minus(&p.last().succedent, &target.succedent)
+            };
+            match &rest[..] {
+                [fml] => Some(fml.clone()),
+                _ => None,
+            }
+        };
+        let f2 = [f.clone(), f.clone()];
+        let f = std::slice::from_ref(f);
+        let premise = premises.pop()?;
+        let proof = match (self, &f[0]) {
+            (G3::AndLeft(_, _), And(a, b)) => {
+                let (a, b) = ((**a).clone(), (**b).clone());
+                let p = fit(premise, &[a, b.clone()], &[])?;
+                let p = _infer("AndLeft1", vec![p], seq(&[f[0].clone(), b.clone()], &[]))?;
+                let p = adapt(p, &seq(&[b, f[0].clone()], &[])).ok()?;
+                _infer("AndLeft2", vec![p], seq(&f2, &[]))?
+            }
+            (G3::OrRight(_, _), Or(a, b)) => {
+                let (a, b) = ((**a).clone(), (**b).clone());
+                let p = fit(premise, &[], &[b.clone(), a])?;
+                let p = _infer("OrRight1", vec![p], seq(&[], &[b.clone(), f[0].clone()]))?;
+                let p = adapt(p, &seq(&[], &[f[0].clone(), b])).ok()?;
+                _infer("OrRight2", vec![p], seq(&[], &f2))?
+            }
+            (G3::ImpliesRight(_, _), Implies(a, b)) => {
+                let p = fit(premise, &[(**a).clone()], &[(**b).clone()])?;
+                _infer("ImpliesRight", vec![p], seq(&[], f))?
+            }
+            (G3::NotLeft(_, _), Not(a)) => {
+                let p = fit(premise, &[], &[(**a).clone()])?;
+                _infer("NotLeft", vec![p], seq(f, &[]))?
+            }
+            (G3::NotRight(_, _), Not(a)) => {
+                let p = fit(premise, &[(**a).clone()], &[])?;
+                _infer("NotRight", vec![p], seq(&[], f))?
+            }
+            (G3::ForallLeft(_, _), Forall(_, _)) => {
+                let instance = extra(&premise, f, &[])?;
+                let p = fit(premise, &[instance, f[0].clone()], &[])?;
+                _infer("ForallLeft", vec![p], seq(&f2, &[]))?
+            }
+            (G3::ExistsRight(_, _), Exists(_, _)) => {
+                let instance = extra(&premise, &[], f)?;
+                let p = fit(premise, &[], &[f[0].clone(), instance])?;
+                _infer("ExistsRight", vec![p], seq(&[], &f2))?
+            }
+            (G3::ForallRight(_, _), Forall(_, _)) => {
+                let instance = extra(&premise, &[], &[])?;
+                let p = fit(premise, &[], &[instance])?;
+                _infer("ForallRight", vec![p], seq(&[], f))?
+            }
+            (G3::ExistsLeft(_, _), Exists(_, _)) => {
+                let instance = extra(&premise, &[], &[])?;
+                let p = fit(premise, &[instance], &[])?;
+                _infer("ExistsLeft", vec![p], seq(f, &[]))?
+            }
+            (G3::AndRight(_, _), And(a, b)) => {
+                let p1 = fit(premises.pop()?, &[], &[(**a).clone()])?;
+                let p2 = fit(premise, &[], &[(**b).clone()])?;
+                _infer("AndRight", vec![p1, p2], seq(&[], f))?
+            }
+            (G3::OrLeft(_, _), Or(a, b)) => {
+                let p1 = fit(premises.pop()?, &[(**a).clone()], &[])?;
+                let p2 = fit(premise, &[(**b).clone()], &[])?;
+                _infer("OrLeft", vec![p1, p2], seq(f, &[]))?
+            }
+            (G3::ImpliesLeft(_, _), Implies(a, b)) => {
+                let p1 = fit(premises.pop()?, &[], &[(**a).clone()])?;
+                let p2 = fit(premise, &[(**b).clone()], &[])?;
+                let sequent = _sequent([f, &g[..], &g[..]].concat(), [&d[..], &d[..]].concat());
+                _infer("ImpliesLeft", vec![p1, p2], sequent)?
+            }
+            (G3::IffLeft(_, _), Iff(a, b)) => {
+                let ab = [(**a).clone(), (**b).clone()];
+                let p1 = fit(premises.pop()?, &ab, &[])?;
+                let p2 = fit(premise, &[], &ab)?;
+                _infer("IffLeft", vec![p1, p2], seq(f, &[]))?
+            }
+            (G3::IffRight(_, _), Iff(a, b)) => {
+                let (a, b) = (&[(**a).clone()], &[(**b).clone()]);
+                let p1 = fit(premises.pop()?, a, b)?;
+                let p2 = fit(premise, b, a)?;
+                _infer("IffRight", vec![p1, p2], seq(&[], f))?
+            }
+            _ => return None,
+        };
+        adapt(proof, s).ok()
+    }
+
+    // The index of a formula that makes the inference valid as its
+    // principal formula.
+    fn _principal(&self) -> Option<usize> {
+        let s = self.last();
+        let candidates = match self._left() {
+            None => s.antecedent.len() + s.succedent.len(),
+            Some(true) => s.antecedent.len(),
+            Some(false) => s.succedent.len(),
+        };
+        let placeholders = || {
+            self.premises()
+                .iter()
+                .map(|p| LK::Axiom(p.last().clone()))
+                .collect::<Vec<_>>()
+        };
+        (0..candidates).find(|&i| self._step(placeholders(), i).is_some())
+    }
+
+    // Derives `conclusion` in LK by the single-premise `rule` from `premise`,
+    // with the `principal`-th formula of its side as the principal formula.
+    pub(crate) fn infer(
+        rule: fn(Box<G3>, Sequent) -> G3,
+        premise: LK,
+        conclusion: Sequent,
+        principal: usize,
+    ) -> Option<LK> {
+        let placeholder = Box::new(G3::Axiom(premise.last().clone()));
+        rule(placeholder, conclusion)._step(vec![premise], principal)
+    }
+
+    // Same as `infer`, for the two-premise `rule`.
+    pub(crate) fn infer_binary(
+        rule: fn(Box<[G3; 2]>, Sequent) -> G3,
+        premises: [LK; 2],
+        conclusion: Sequent,
+        principal: usize,
+    ) -> Option<LK> {
+        let placeholders = Box::new([
+            G3::Axiom(premises[0].last().clone()),
+            G3::Axiom(premises[1].last().clone()),
+        ]);
+        rule(placeholders, conclusion)._step(Vec::from(premises), principal)
+    }
+
+    /// The same proof in LK, with the exchanges, contractions and weakenings
+    /// G3c leaves implicit. Fails if an inference is invalid.
+    pub fn to_lk(&self) -> Result<LK, &'static str> {
+        let principal = self._principal().ok_or("Invalid inference.")?;
+        let premises = self
+            .premises()
+            .iter()
+            .map(|p| p.to_lk())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(self._step(premises, principal).unwrap())
+    }
+}
+
+impl Proof for G3 {
+    fn is_valid_proof(&self) -> bool {
+        let mut stack = vec![self];
+        while let Some(proof) = stack.pop() {
+            if !proof.is_valid_inference() {
+                return false;
+            }
+            stack.extend(proof.premises());
+        }
+        true
+    }
+
+    fn is_valid_inference(&self) -> bool {
+        self._principal().is_some()
+    }
+}
@@ -0,0 +1,207 @@
+use crate::fresh::*;
+use crate::language::*;
+use crate::proof::*;
+use crate::rewrite::restructure;
+use crate::visit::*;
+use std::collections::{HashMap, HashSet};
+
+/// A goal together with a more general one, as prepared before induction.
+/// A proof of `generalized` is turned into one of `goal` by `instantiate`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generalization {
+    pub goal: Sequent,
+    pub generalized: Sequent,
+    /// The fresh variables of `generalized` with the terms they replace.
+    pub replaced: Vec<(Term, Term)>,
+    /// The hypotheses of `goal` left out of `generalized`.
+    pub dropped: Vec<Formula>,
+}
+
+#[derive(Default)]
+struct TermCounter {
+    counts: HashMap<Term, usize>,
+}
+
+impl TermVisitor for TermCounter {
+    fn visit_term(&mut self, term: &Term) {
+        *self.counts.entry(term.clone()).or_insert(0) += 1;
+        walk_term(self, term);
+    }
+}
+
+impl FormulaVisitor for TermCounter {}
+
+struct TermReplacer<'a> {
+    from: &'a Term,
+    to: &'a Term,
+}
+
+impl<'a> TermFolder for TermReplacer<'a> {
+    fn fold_term(&mut self, term: &Term) -> Term {
+        if term == self.from {
+            self.to.clone()
+        } else {
+            fold_subterms(self, term)
+        }
+    }
+}
+
+impl<'a> FormulaFolder for TermReplacer<'a> {}
+
+// Predicate and function names and free variables of `fml`.
+fn _symbols(fml: &Formula) -> HashSet<String> {
+    let mut symbols = fml
+        .get_preds()
+        .into_iter()
+        .chain(fml.get_funcs())
+        .map(|sym| sym.name)
+        .collect::<HashSet<_>>();
+    for var in fml.get_free_vars() {
+        if let Term::Var(name) = var {
+            symbols.insert(format!("?{}", name));
+        }
+    }
+    symbols
+}
+
+// Splits the antecedent into the hypotheses sharing symbols, directly or
+// through other hypotheses, with the succedent and the others.
+fn _relevant(sequent: &Sequent) -> (Vec<Formula>, Vec<Formula>) {
+    if sequent.succedent.is_empty() {
+        return (sequent.antecedent.clone(), vec![]);
+    }
+    let mut symbols = sequent
+        .succedent
+        .iter()
+        .flat_map(_symbols)
+        .collect::<HashSet<_>>();
+    let mut relevant = vec![false; sequent.antecedent.len()];
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (i, fml) in sequent.antecedent.iter().enumerate() {
+            let fml_symbols = _symbols(fml);
+            if !relevant[i] && !fml_symbols.is_disjoint(&symbols) {
+                relevant[i] = true;
+                symbols.extend(fml_symbols);
+                changed = true;
+            }
+        }
+    }
+    let (kept, dropped): (Vec<_>, Vec<_>) = sequent
+        .antecedent
+        .iter()
+        .cloned()
+        .zip(relevant)
+        .partition(|(_, relevant)| *relevant);
+    (
+        kept.into_iter().map(|(fml, _)| fml).collect(),
+        dropped.into_iter().map(|(fml, _)| fml).collect(),
+    )
+}
+
+fn _count_terms(fmls: &[Formula]) -> HashMap<Term, usize> {
+    let mut counter = TermCounter::default();
+    for fml in fmls {
+        counter.visit_formula(fml);
+    }
+    counter.counts
+}
+
+impl Sequent {
+    /// Heuristic generalization of the sequent as a goal: hypotheses sharing
+    /// no symbol or free variable with the succedent, even through other
+    /// hypotheses, are dropped, then compound terms occurring more than once,
+    /// at least once in the succedent, are replaced by fresh variables,
+    /// largest first. Terms with a variable bound somewhere in the sequent
+    /// are left alone.
+    pub fn generalize(&self) -> Generalization {
+        let (antecedent, dropped) = _relevant(self);
+        let mut generalized = Sequent {
+            antecedent,
+            succedent: self.succedent.clone(),
+        };
+        let bound = self
+            .antecedent
+            .iter()
+            .chain(&self.succedent)
+            .flat_map(|fml| fml.get_bound_vars())
+            .collect::<HashSet<_>>();
+        let mut symbols = SymbolGenerator::new();
+        symbols.avoid_sequent(self);
+
+        let mut candidates = _count_terms(&self.succedent)
+            .into_keys()
+            .filter(|term| matches!(term, Term::Func(_, args) if !args.is_empty()))
+            .filter(|term| term.get_vars().is_disjoint(&bound))
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|term| {
+            (
+                std::cmp::Reverse(term.get_subterms().len()),
+                term.to_sexpr(),
+            )
+        });
+        let mut replaced = vec![];
+        for term in candidates {
+            let fmls = [&generalized.antecedent[..], &generalized.succedent[..]].concat();
+            if _count_terms(&fmls).get(&term).copied().unwrap_or(0) < 2
+                || !_count_terms(&generalized.succedent).contains_key(&term)
+            {
+                continue;
+            }
+            let var = symbols.fresh_var("z");
+            let mut replacer = TermReplacer {
+                from: &term,
+                to: &var,
+            };
+            for fml in generalized
+                .antecedent
+                .iter_mut()
+                .chain(generalized.succedent.iter_mut())
+            {
+                *fml = replacer.fold_formula(fml);
+            }
+            replaced.push((var, term));
+        }
+        Generalization {
+            goal: self.clone(),
+            generalized,
+            replaced,
+            dropped,
+        }
+    }
+}
+
+impl Generalization {
+    /// A proof of `goal` from one of `generalized`, substituting the
+    /// replaced terms back for their variables and weakening the dropped
+    /// hypotheses in. Fails if the substitution breaks an eigenvariable
+    /// condition of `proof`.
+    pub fn instantiate(&self, proof: &LK) -> Result<LK, &'static str> {
+        if proof.last() != &self.generalized {
+            return Err("Proof does not end in the generalized goal.");
+        }
+        let mut instantiated = proof.clone();
+        for (var, term) in self.replaced.iter().rev() {
+            instantiated = instantiated.map_sequents(&mut |sequent: &Sequent| Sequent {
+                antecedent: sequent
+                    .antecedent
+                    .iter()
+                    .map(|fml| fml.substitute(var.clone(), term.clone()))
+                    .collect(),
+                succedent: sequent
+                    .succedent
+                    .iter()
+                    .map(|fml| fml.substitute(var.clone(), term.clone()))
+                    .collect(),
+            });
+        }
+        fn is_valid(proof: &LK) -> bool {
+            proof.is_valid_inference() && proof.premises().iter().all(|p| is_valid(p))
+        }
+        if !is_valid(&instantiated) {
+            return Err("Instantiated proof is not valid.");
+        }
+        Ok(restructure(instantiated, &self.goal))
+    }
+}
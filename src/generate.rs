@@ -0,0 +1,166 @@
+use crate::language::*;
+use crate::proof::*;
+use crate::solver::*;
+use std::collections::HashSet;
+
+/// Settings for `exercises`. The same configuration and seed always yield
+/// the same exercises.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExerciseConfig {
+    pub count: usize,
+    pub seed: u64,
+    /// Predicate symbols to build atoms from.
+    pub preds: Vec<String>,
+    pub max_formula_depth: u32,
+    pub max_hypotheses: usize,
+    /// Whether to generate `∀`/`∃` with unary atoms over the bound variables.
+    pub quantifiers: bool,
+    /// Sequents whose shortest proof is lower than this are discarded.
+    pub min_proof_depth: u32,
+    pub max_proof_depth: u32,
+    /// Number of random sequents to try before giving up on `count`.
+    pub max_attempts: usize,
+}
+
+impl Default for ExerciseConfig {
+    fn default() -> Self {
+        ExerciseConfig {
+            count: 10,
+            seed: 1,
+            preds: vec!["p".into(), "q".into(), "r".into()],
+            max_formula_depth: 3,
+            max_hypotheses: 2,
+            quantifiers: false,
+            min_proof_depth: 3,
+            max_proof_depth: 6,
+            max_attempts: 1000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+/// A provable sequent together with the proof found by the prover.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exercise {
+    pub sequent: Sequent,
+    pub proof: LK,
+    /// Height of the proof, minimal for the prover's rule set since proofs
+    /// are searched by iterative deepening.
+    pub height: u32,
+    /// Distinct rules used by the proof, axioms excluded.
+    pub rules: Vec<&'static str>,
+    pub difficulty: Difficulty,
+}
+
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
+
+fn _formula(config: &ExerciseConfig, rng: &mut XorShift, depth: u32, bound: &[Term]) -> Formula {
+    if depth == 0 || rng.next(4) == 0 {
+        let name = config.preds[rng.next(config.preds.len())].clone();
+        return if config.quantifiers && !bound.is_empty() {
+            Formula::Pred(name, vec![bound[rng.next(bound.len())].clone()])
+        } else {
+            Formula::Pred(name, vec![])
+        };
+    }
+    let sub = |rng: &mut XorShift| Box::new(_formula(config, rng, depth - 1, bound));
+    match rng.next(if config.quantifiers { 7 } else { 5 }) {
+        0 => Formula::Not(sub(rng)),
+        1 => Formula::And(sub(rng), sub(rng)),
+        2 => Formula::Or(sub(rng), sub(rng)),
+        3 => Formula::Implies(sub(rng), sub(rng)),
+        4 => Formula::Iff(sub(rng), sub(rng)),
+        choice => {
+            let var = var!(format!("x{}", bound.len()));
+            let mut inner = bound.to_vec();
+            inner.push(var.clone());
+            let body = Box::new(_formula(config, rng, depth - 1, &inner));
+            if choice == 5 {
+                Formula::Forall(var, body)
+            } else {
+                Formula::Exists(var, body)
+            }
+        }
+    }
+}
+
+fn _difficulty(height: u32, variety: usize) -> Difficulty {
+    if height <= 3 && variety <= 3 {
+        Difficulty::Easy
+    } else if height <= 5 && variety <= 5 {
+        Difficulty::Medium
+    } else {
+        Difficulty::Hard
+    }
+}
+
+/// Generates up to `config.count` distinct provable sequents `Γ ⇒ A` from
+/// random formulas, sorted from easiest to hardest. Difficulty is graded by
+/// the height of the shortest proof and the number of distinct rules it
+/// needs.
+pub fn exercises(config: &ExerciseConfig) -> Vec<Exercise> {
+    let mut rng = XorShift(config.seed.max(1));
+    let mut seen = HashSet::new();
+    let mut exercises = vec![];
+    for _ in 0..config.max_attempts {
+        if exercises.len() >= config.count {
+            break;
+        }
+        let antecedent = (0..rng.next(config.max_hypotheses + 1))
+            .map(|_| _formula(config, &mut rng, config.max_formula_depth, &[]))
+            .collect::<Vec<_>>();
+        let conclusion = _formula(config, &mut rng, config.max_formula_depth, &[]);
+        let sequent = Sequent {
+            antecedent,
+            succedent: vec![conclusion.clone()],
+        };
+        if !seen.insert(sequent.clone()) {
+            continue;
+        }
+        // Rejects most non-theorems before the comparatively expensive search.
+        let fml = sequent
+            .antecedent
+            .iter()
+            .rev()
+            .fold(conclusion, |acc, hyp| implies!(hyp.clone(), acc));
+        if refute_on_finite_models(fml, 1).is_some() {
+            continue;
+        }
+        if let Ok(proof) = prove_sequent_with_lk(sequent.clone(), config.max_proof_depth, false) {
+            let height = proof.height();
+            if height < config.min_proof_depth {
+                continue;
+            }
+            let mut rules = vec![];
+            proof.replay(&mut |rule: &'static str, _: &[&Sequent], _: &Sequent| {
+                if rule != "Axiom" && !rules.contains(&rule) {
+                    rules.push(rule);
+                }
+            });
+            exercises.push(Exercise {
+                difficulty: _difficulty(height, rules.len()),
+                sequent,
+                proof,
+                height,
+                rules,
+            });
+        }
+    }
+    exercises.sort_by_key(|e| (e.difficulty, e.height, e.rules.len()));
+    exercises
+}
@@ -0,0 +1,124 @@
+//! Golden files for the renderers. Each sample proof `<name>.lk` of
+//! `tests/golden`, a script as read by `LK::from_script`, is rendered by
+//! every renderer and exporter and compared with `<name>.<renderer>.txt`.
+//! Running the tests with `RFOL_UPDATE_GOLDEN=1` writes the renderings
+//! instead, to be reviewed in the diff.
+
+use crate::graph::DotOptions;
+use crate::language::*;
+use crate::proof::*;
+use std::fs;
+use std::path::PathBuf;
+
+pub const UPDATE: &str = "RFOL_UPDATE_GOLDEN";
+
+pub fn dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+/// The sample proofs by name, in order of name.
+pub fn samples() -> Vec<(String, LK)> {
+    let mut samples = fs::read_dir(dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|e| e == "lk"))
+        .map(|path| {
+            let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+            let script = fs::read_to_string(&path).unwrap();
+            let proof = LK::from_script(&script).unwrap_or_else(|e| panic!("{}: {}", name, e));
+            (name, proof)
+        })
+        .collect::<Vec<_>>();
+    samples.sort_by(|a, b| a.0.cmp(&b.0));
+    samples
+}
+
+/// The renderers by name, with the options they render with.
+pub fn renderers() -> Vec<(&'static str, RenderOptions)> {
+    vec![
+        ("unicode", RenderOptions::default()),
+        (
+            "ascii",
+            RenderOptions {
+                notation: Notation::Ascii,
+                ..RenderOptions::default()
+            },
+        ),
+        (
+            "narrow",
+            RenderOptions {
+                max_width: Some(40),
+                ..RenderOptions::default()
+            },
+        ),
+        (
+            "named",
+            RenderOptions {
+                labels: LabelScheme::Named,
+                ..RenderOptions::default()
+            },
+        ),
+    ]
+}
+
+/// An export to another format, by name.
+pub type Renderer = (&'static str, fn(&LK) -> String);
+
+/// The exports to other formats by name.
+pub fn exporters() -> Vec<Renderer> {
+    vec![
+        ("latex", LK::to_latex),
+        ("dot", |proof| proof.to_dot(&DotOptions::default())),
+        ("html", LK::to_html),
+    ]
+}
+
+// The lines of `expected` and `actual`, prefixed with `-` if only in the
+// former, `+` if only in the latter, from a longest common subsequence.
+fn _diff(expected: &str, actual: &str) -> String {
+    let (old, new) = (
+        expected.lines().collect::<Vec<_>>(),
+        actual.lines().collect::<Vec<_>>(),
+    );
+    let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j, mut diff) = (0, 0, String::new());
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            diff += &format!(" {}\n", old[i]);
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && common[i + 1][j] >= common[i][j + 1]) {
+            diff += &format!("-{}\n", old[i]);
+            i += 1;
+        } else {
+            diff += &format!("+{}\n", new[j]);
+            j += 1;
+        }
+    }
+    diff
+}
+
+/// Compares `actual` with the golden file `file`, or writes it there in
+/// update mode. The error is the diff from the golden file.
+pub fn check(file: &str, actual: &str) -> Result<(), String> {
+    let path = dir().join(file);
+    if std::env::var_os(UPDATE).is_some() {
+        fs::write(&path, actual).unwrap();
+        return Ok(());
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_default();
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(format!("{} differs:\n{}", file, _diff(&expected, actual)))
+    }
+}
@@ -0,0 +1,271 @@
+use crate::language::Notation;
+use crate::normal_form::is_structural;
+use crate::proof::*;
+use crate::record::json_string;
+use std::collections::HashMap;
+
+/// A distinct subproof of a `ProofGraph`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphNode {
+    pub rule: &'static str,
+    pub sequent: Sequent,
+    pub height: u32,
+    /// Number of inferences of the subproof written out as a tree.
+    pub tree_size: usize,
+    /// Number of times the subproof occurs in the proof tree.
+    pub occurrences: usize,
+    /// Number of inferences in the graph using the node as a premise.
+    pub parents: usize,
+}
+
+/// An inference step from the node `conclusion` to its `index`-th premise.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GraphEdge {
+    pub conclusion: usize,
+    pub premise: usize,
+    pub index: usize,
+}
+
+/// A proof as a directed acyclic graph in which structurally identical
+/// subproofs are shared. Nodes are ordered premises first, so the end
+/// sequent is the last node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProofGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+type NodeKey = (&'static str, Sequent, Vec<usize>);
+
+fn _add(proof: &LK, graph: &mut ProofGraph, ids: &mut HashMap<NodeKey, usize>) -> usize {
+    let premises = proof
+        .premises()
+        .iter()
+        .map(|p| _add(p, graph, ids))
+        .collect::<Vec<_>>();
+    let key = (proof.rule_name(), proof.last().clone(), premises.clone());
+    if let Some(&id) = ids.get(&key) {
+        graph.nodes[id].occurrences += 1;
+        return id;
+    }
+    let id = graph.nodes.len();
+    graph.nodes.push(GraphNode {
+        rule: proof.rule_name(),
+        sequent: proof.last().clone(),
+        height: 1 + premises
+            .iter()
+            .map(|&p| graph.nodes[p].height)
+            .max()
+            .unwrap_or(0),
+        tree_size: 1 + premises
+            .iter()
+            .map(|&p| graph.nodes[p].tree_size)
+            .sum::<usize>(),
+        occurrences: 1,
+        parents: 0,
+    });
+    for (index, &premise) in premises.iter().enumerate() {
+        graph.nodes[premise].parents += 1;
+        graph.edges.push(GraphEdge {
+            conclusion: id,
+            premise,
+            index,
+        });
+    }
+    ids.insert(key, id);
+    id
+}
+
+pub(crate) fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl ProofGraph {
+    /// Nodes occurring more than once in the proof tree.
+    pub fn shared(&self) -> impl Iterator<Item = (usize, &GraphNode)> {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.occurrences > 1)
+    }
+
+    /// GraphML document with the node statistics as attributes, readable by
+    /// networkx and Gephi. Node ids are `n<index>`.
+    pub fn to_graphml(&self) -> String {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+        );
+        for (name, ty) in &[
+            ("rule", "string"),
+            ("sequent", "string"),
+            ("height", "int"),
+            ("tree_size", "int"),
+            ("occurrences", "int"),
+            ("parents", "int"),
+        ] {
+            xml.push_str(&format!(
+                "  <key id=\"{0}\" for=\"node\" attr.name=\"{0}\" attr.type=\"{1}\"/>\n",
+                name, ty
+            ));
+        }
+        xml.push_str("  <key id=\"index\" for=\"edge\" attr.name=\"index\" attr.type=\"int\"/>\n");
+        xml.push_str("  <graph id=\"proof\" edgedefault=\"directed\">\n");
+        for (id, node) in self.nodes.iter().enumerate() {
+            xml.push_str(&format!(
+                "    <node id=\"n{}\"><data key=\"rule\">{}</data><data key=\"sequent\">{}</data>\
+                 <data key=\"height\">{}</data><data key=\"tree_size\">{}</data>\
+                 <data key=\"occurrences\">{}</data><data key=\"parents\">{}</data></node>\n",
+                id,
+                node.rule,
+                xml_escape(&node.sequent.to_string()),
+                node.height,
+                node.tree_size,
+                node.occurrences,
+                node.parents
+            ));
+        }
+        for edge in &self.edges {
+            xml.push_str(&format!(
+                "    <edge source=\"n{}\" target=\"n{}\"><data key=\"index\">{}</data></edge>\n",
+                edge.conclusion, edge.premise, edge.index
+            ));
+        }
+        xml.push_str("  </graph>\n</graphml>\n");
+        xml
+    }
+
+    /// Node-link JSON as read by networkx's `node_link_graph`, with edges
+    /// pointing from conclusions to premises.
+    pub fn to_json(&self) -> String {
+        let nodes = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(id, node)| {
+                format!(
+                    "{{\"id\": {}, \"rule\": {}, \"sequent\": {}, \"height\": {}, \"tree_size\": {}, \"occurrences\": {}, \"parents\": {}}}",
+                    id,
+                    json_string(node.rule),
+                    json_string(&node.sequent.to_string()),
+                    node.height,
+                    node.tree_size,
+                    node.occurrences,
+                    node.parents
+                )
+            })
+            .collect::<Vec<_>>();
+        let links = self
+            .edges
+            .iter()
+            .map(|edge| {
+                format!(
+                    "{{\"source\": {}, \"target\": {}, \"key\": {}, \"index\": {}}}",
+                    edge.conclusion, edge.premise, edge.index, edge.index
+                )
+            })
+            .collect::<Vec<_>>();
+        format!(
+            "{{\"directed\": true, \"multigraph\": true, \"graph\": {{}}, \"nodes\": [{}], \"links\": [{}]}}",
+            nodes.join(", "),
+            links.join(", ")
+        )
+    }
+}
+
+/// Options for `LK::to_dot`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DotOptions {
+    pub notation: Notation,
+    pub labels: LabelScheme,
+    /// Leaves out the conclusions of structural inferences: each run of
+    /// them is merged into the inference above it, whose label lists the
+    /// structural rules after its own, e.g. `∧R (xL, wL)`.
+    pub collapse_structural: bool,
+}
+
+impl Default for DotOptions {
+    fn default() -> Self {
+        DotOptions {
+            notation: Notation::Unicode,
+            labels: LabelScheme::Symbolic,
+            collapse_structural: false,
+        }
+    }
+}
+
+fn _dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn _add_dot(
+    proof: &LK,
+    options: &DotOptions,
+    lines: &mut Vec<String>,
+    ids: &mut HashMap<(String, Sequent, Vec<usize>), usize>,
+) -> usize {
+    let mut top = proof;
+    let mut collapsed = vec![];
+    while options.collapse_structural && is_structural(top) {
+        collapsed.push(options.labels.label(top.rule_name(), options.notation));
+        top = top.premises()[0];
+    }
+    let premises = top
+        .premises()
+        .iter()
+        .map(|p| _add_dot(p, options, lines, ids))
+        .collect::<Vec<_>>();
+    let mut label = options.labels.label(top.rule_name(), options.notation);
+    if !collapsed.is_empty() {
+        label = format!("{} ({})", label, collapsed.join(", "));
+    }
+    let key = (label, proof.last().clone(), premises);
+    if let Some(&id) = ids.get(&key) {
+        return id;
+    }
+    let (label, sequent, premises) = (&key.0, &key.1, &key.2);
+    let id = ids.len();
+    let sequent = _dot_escape(&sequent.to_string_with(options.notation));
+    let label = _dot_escape(label);
+    if premises.is_empty() {
+        lines.push(format!(
+            "  n{} [label=\"{}\", xlabel=\"{}\"];",
+            id, sequent, label
+        ));
+    } else {
+        lines.push(format!("  n{} [label=\"{}\"];", id, sequent));
+    }
+    for premise in premises {
+        lines.push(format!("  n{} -> n{} [label=\"{}\"];", id, premise, label));
+    }
+    ids.insert(key, id);
+    id
+}
+
+impl LK {
+    /// Graphviz graph with a node per sequent and edges from conclusions to
+    /// premises labelled with the rule, drawn with the end sequent at the
+    /// bottom. Identical subproofs are drawn once, as in `to_graph`.
+    pub fn to_dot(&self, options: &DotOptions) -> String {
+        let mut lines = vec![
+            "digraph proof {".to_string(),
+            "  rankdir=BT;".to_string(),
+            "  node [shape=plaintext];".to_string(),
+        ];
+        _add_dot(self, options, &mut lines, &mut HashMap::new());
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+
+    pub fn to_graph(&self) -> ProofGraph {
+        let mut graph = ProofGraph {
+            nodes: vec![],
+            edges: vec![],
+        };
+        _add(self, &mut graph, &mut HashMap::new());
+        graph
+    }
+}
@@ -0,0 +1,273 @@
+use crate::cnf::*;
+use crate::fresh::*;
+use crate::language::*;
+use crate::provenance::*;
+use crate::sat::*;
+use crate::signature::*;
+use crate::substitution::*;
+use crate::theory::*;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// A ground instance of a clause of an axiom taking part in a refutation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoreInstance {
+    /// Index of the axiom in the theory.
+    pub axiom: usize,
+    /// The clause of the axiom's clause form.
+    pub clause: Clause,
+    /// Positions in the axiom of the atoms of `clause`.
+    pub origins: ClauseProvenance,
+    /// The ground terms substituted for the variables of `clause`, sorted by
+    /// variable.
+    pub instantiation: Vec<(Term, Term)>,
+    pub instance: Clause,
+}
+
+/// The axioms and ground clause instances of an unsatisfiable core, minimal
+/// in that leaving out any instance makes the rest satisfiable.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RefutationReport {
+    /// Indices of the axioms used, in increasing order.
+    pub axioms: Vec<usize>,
+    /// Names of `axioms` as given by `Theory::name`.
+    pub names: Vec<String>,
+    pub instances: Vec<CoreInstance>,
+}
+
+fn _clause_string(clause: &Clause) -> String {
+    let mut literals = clause
+        .iter()
+        .map(|l| l.to_formula().to_string())
+        .collect::<Vec<_>>();
+    literals.sort();
+    match literals.len() {
+        0 => "⊥".into(),
+        _ => literals.join(" ∨ "),
+    }
+}
+
+impl fmt::Display for RefutationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "inconsistent axioms: {}", self.names.join(", "))?;
+        for instance in &self.instances {
+            let name = &self.names[self.axioms.binary_search(&instance.axiom).unwrap()];
+            write!(f, "  {}: {}", name, _clause_string(&instance.instance))?;
+            if !instance.instantiation.is_empty() {
+                let instantiation = instance
+                    .instantiation
+                    .iter()
+                    .map(|(var, term)| format!("{} := {}", var, term))
+                    .collect::<Vec<_>>();
+                write!(f, " [{}]", instantiation.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+// The tuples of `arity` elements of `terms`.
+fn _tuples(terms: &[Term], arity: u32) -> Vec<Vec<Term>> {
+    let mut tuples = vec![vec![]];
+    for _ in 0..arity {
+        tuples = tuples
+            .into_iter()
+            .flat_map(|prefix: Vec<Term>| {
+                terms
+                    .iter()
+                    .map(move |t| [&prefix[..], std::slice::from_ref(t)].concat())
+            })
+            .collect();
+    }
+    tuples
+}
+
+// The instantiations of `vars` with `terms`.
+fn _instantiations(vars: &[Term], terms: &[Term]) -> Vec<Substitution> {
+    _tuples(terms, vars.len() as u32)
+        .into_iter()
+        .map(|tuple| vars.iter().cloned().zip(tuple).collect())
+        .collect()
+}
+
+impl Signature {
+    /// The ground terms with at most `depth` nested function applications,
+    /// the constants first and then each level in order of symbol. Without
+    /// constants, a fresh constant stands for an arbitrary element.
+    pub fn herbrand_universe(&self, depth: u32) -> Vec<Term> {
+        let mut constants = self.constants.iter().cloned().collect::<Vec<_>>();
+        constants.sort();
+        if constants.is_empty() {
+            let mut symbols = SymbolGenerator::new();
+            for sym in self.functions.iter().chain(&self.predicates) {
+                symbols.avoid_name(&sym.name);
+            }
+            constants.push(symbols.fresh_func("c", 0).name);
+        }
+        let mut terms = constants
+            .into_iter()
+            .map(|name| Term::Func(name, vec![]))
+            .collect::<Vec<_>>();
+        let mut funcs = self.functions.iter().collect::<Vec<_>>();
+        funcs.sort_by_key(|f| (f.name.clone(), f.arity));
+        for _ in 0..depth {
+            let mut next = terms.clone();
+            for func in &funcs {
+                for args in _tuples(&terms, func.arity) {
+                    let term = Term::Func(func.name.clone(), args);
+                    if !next.contains(&term) {
+                        next.push(term);
+                    }
+                }
+            }
+            terms = next;
+        }
+        terms
+    }
+
+    /// The ground atoms over `herbrand_universe(depth)`, in order of
+    /// predicate symbol.
+    pub fn herbrand_base(&self, depth: u32) -> Vec<Formula> {
+        let terms = self.herbrand_universe(depth);
+        let mut preds = self.predicates.iter().collect::<Vec<_>>();
+        preds.sort_by_key(|p| (p.name.clone(), p.arity));
+        preds
+            .into_iter()
+            .flat_map(|pred| {
+                _tuples(&terms, pred.arity)
+                    .into_iter()
+                    .map(move |args| Formula::Pred(pred.name.clone(), args))
+            })
+            .collect()
+    }
+}
+
+impl Formula {
+    /// The instances of the formula replacing its free variables, in order
+    /// of name, with every combination of `terms`.
+    pub fn ground_instances(&self, terms: &[Term]) -> Vec<Formula> {
+        let mut vars = self.get_free_vars().into_iter().collect::<Vec<_>>();
+        vars.sort_by_key(|v| v.to_string());
+        _instantiations(&vars, terms)
+            .iter()
+            .map(|sub| sub.apply_to_formula(self))
+            .collect()
+    }
+}
+
+/// The instances of `clause` replacing its variables with every
+/// combination of `terms`, with the substitution giving each.
+pub fn clause_instances(clause: &Clause, terms: &[Term]) -> Vec<(Substitution, Clause)> {
+    let mut vars = clause
+        .iter()
+        .flat_map(|l| l.atom.get_free_vars())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    vars.sort_by_key(|v| v.to_string());
+    _instantiations(&vars, terms)
+        .into_iter()
+        .map(|sub| {
+            let instance = clause
+                .iter()
+                .map(|l| Literal {
+                    atom: sub.apply_to_formula(&l.atom),
+                    positive: l.positive,
+                })
+                .collect();
+            (sub, instance)
+        })
+        .collect()
+}
+
+fn _satisfiable(clauses: Vec<Vec<i32>>) -> bool {
+    CdclSolver::default().solve(&clauses).is_some()
+}
+
+impl Theory {
+    /// Refutes the theory by instantiating the variables of its clause form
+    /// with ground terms of at most `depth` nested function applications
+    /// and checking the ground clauses for propositional unsatisfiability.
+    /// On success the unsatisfiable core is traced back to the axioms.
+    pub fn refute_by_grounding(&self, depth: u32) -> Option<RefutationReport> {
+        let mut symbols = SymbolGenerator::avoiding(&self.axioms);
+        let mut clauses = vec![];
+        for (axiom, fml) in self.axioms.iter().enumerate() {
+            let (cnf, provenances) =
+                fml.to_cnf_with_provenance(CnfConversion::Distributive, &mut symbols);
+            for (clause, origins) in cnf.into_iter().zip(provenances) {
+                clauses.push((axiom, clause, origins));
+            }
+        }
+        let mut signature = Signature::new();
+        for (_, clause, _) in &clauses {
+            for literal in clause {
+                signature.extend(literal.atom.signature());
+            }
+        }
+        if signature.constants.is_empty() {
+            signature.add_function(symbols.fresh_func("c", 0));
+        }
+        let terms = signature.herbrand_universe(depth);
+
+        let mut instances = vec![];
+        let mut atoms = HashMap::new();
+        let mut encoded = vec![];
+        for (axiom, clause, origins) in &clauses {
+            for (sub, instance) in clause_instances(clause, &terms) {
+                let mut encoding = vec![];
+                for literal in &instance {
+                    let count = atoms.len() as i32;
+                    let atom = *atoms.entry(literal.atom.clone()).or_insert(count + 1);
+                    encoding.push(if literal.positive { atom } else { -atom });
+                }
+                encoded.push(encoding);
+                let mut instantiation = sub
+                    .iter()
+                    .map(|(v, t)| (v.clone(), t.clone()))
+                    .collect::<Vec<_>>();
+                instantiation.sort_by_key(|(v, _)| v.to_string());
+                instances.push(CoreInstance {
+                    axiom: *axiom,
+                    clause: clause.clone(),
+                    origins: origins.clone(),
+                    instantiation,
+                    instance,
+                });
+            }
+        }
+        if _satisfiable(encoded.clone()) {
+            return None;
+        }
+
+        // Deletion-based minimization of the core.
+        let mut core = (0..encoded.len()).collect::<Vec<_>>();
+        let mut i = 0;
+        while i < core.len() {
+            let rest = core
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(_, &c)| encoded[c].clone())
+                .collect();
+            if _satisfiable(rest) {
+                i += 1;
+            } else {
+                core.remove(i);
+            }
+        }
+        let instances = core
+            .into_iter()
+            .map(|c| instances[c].clone())
+            .collect::<Vec<_>>();
+        let mut axioms = instances.iter().map(|i| i.axiom).collect::<Vec<_>>();
+        axioms.sort_unstable();
+        axioms.dedup();
+        Some(RefutationReport {
+            names: axioms.iter().map(|&a| self.name(a)).collect(),
+            axioms,
+            instances,
+        })
+    }
+}
@@ -0,0 +1,483 @@
+use crate::adapt::*;
+use crate::language::*;
+use crate::nd::*;
+use crate::proof::*;
+use crate::prover::*;
+use crate::schema::*;
+use crate::visit::*;
+
+/// The axiom schemas of `Hilbert` proofs. The propositional ones are
+/// written with formulas `A`, `B` and `C`; in the others `φ(t)` is `φ(x)`
+/// with `t` substituted for the free occurrences of `x`, `x` not free in
+/// `A`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HilbertAxiom {
+    /// `A → (B → A)`.
+    K,
+    /// `(A → (B → C)) → ((A → B) → (A → C))`.
+    S,
+    /// `¬¬A → A`.
+    DoubleNegation,
+    /// `(A → ⊥) → ¬A`.
+    NotIntro,
+    /// `¬A → (A → ⊥)`.
+    NotElim,
+    /// `A → (B → A ∧ B)`.
+    AndIntro,
+    /// `A ∧ B → A`.
+    AndElim1,
+    /// `A ∧ B → B`.
+    AndElim2,
+    /// `A → A ∨ B`.
+    OrIntro1,
+    /// `B → A ∨ B`.
+    OrIntro2,
+    /// `(A → C) → ((B → C) → (A ∨ B → C))`.
+    OrElim,
+    /// `(A → B) → ((B → A) → (A ↔ B))`.
+    IffIntro,
+    /// `(A ↔ B) → (A → B)`.
+    IffElim1,
+    /// `(A ↔ B) → (B → A)`.
+    IffElim2,
+    /// `⊤`.
+    TopIntro,
+    /// `⊥ → A`.
+    BottomElim,
+    /// `∀x φ(x) → φ(t)`.
+    Instantiation,
+    /// `φ(t) → ∃x φ(x)`.
+    Witness,
+    /// `∀x (A → φ(x)) → (A → ∀x φ(x))`.
+    Distribution,
+    /// `∀x (φ(x) → A) → (∃x φ(x) → A)`.
+    ExistsElim,
+    /// `t = t`.
+    Reflexivity,
+    /// `s = t → (φ(s) → φ(t))`, where `φ(t)` is `φ(s)` with some
+    /// occurrences of `s` replaced by `t`.
+    Substitution,
+}
+
+/// Why a line of a `Hilbert` proof holds. Lines are referred to by index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Justification {
+    Hypothesis(usize),
+    Axiom(HilbertAxiom),
+    /// From the lines `A` and `A → B`, in this order, to `B`.
+    ModusPonens(usize, usize),
+    /// From the line `A` to `∀x A`, for `x` not free in the hypotheses.
+    Generalization(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub formula: Formula,
+    pub justification: Justification,
+}
+
+/// A proof in a Hilbert system: a sequence of formulas, each an instance
+/// of an axiom schema, a hypothesis, or following from earlier lines by
+/// modus ponens or generalization. The last line is the conclusion.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Hilbert {
+    pub hypotheses: Vec<Formula>,
+    pub lines: Vec<Line>,
+}
+
+fn _infer(rule: &str, premises: Vec<LK>, sequent: Sequent) -> Option<LK> {
+    LK::from_parts(rule, premises, sequent).filter(|proof| proof.is_valid_inference())
+}
+
+fn _axiom(fml: &Formula) -> LK {
+    LK::Axiom(Sequent {
+        antecedent: vec![fml.clone()],
+        succedent: vec![fml.clone()],
+    })
+}
+
+fn _fit(proof: LK, antecedent: Vec<Formula>, succedent: Vec<Formula>) -> Option<LK> {
+    adapt(
+        proof,
+        &Sequent {
+            antecedent,
+            succedent,
+        },
+    )
+    .ok()
+}
+
+// `Γ ⇒ A → B` from a proof of `A, Γ ⇒ B`, in any order.
+fn _implies_right(proof: LK, context: &[Formula], fml: &Formula) -> Option<LK> {
+    let (lhs, rhs) = match fml {
+        Formula::Implies(lhs, rhs) => ((**lhs).clone(), (**rhs).clone()),
+        _ => return None,
+    };
+    let premise = _fit(proof, [&[lhs], context].concat(), vec![rhs])?;
+    let sequent = Sequent {
+        antecedent: context.to_vec(),
+        succedent: vec![fml.clone()],
+    };
+    _infer("ImpliesRight", vec![premise], sequent)
+}
+
+// `A → B, A ⇒ B`.
+fn _modus_ponens(lhs: &Formula, rhs: &Formula) -> Option<LK> {
+    let sequent = Sequent {
+        antecedent: vec![implies!(lhs.clone(), rhs.clone()), lhs.clone()],
+        succedent: vec![rhs.clone()],
+    };
+    _infer("ImpliesLeft", vec![_axiom(lhs), _axiom(rhs)], sequent)
+}
+
+// Replaces the metavariables of a propositional schema by formulas.
+struct Instantiate<'a>(&'a Instantiation);
+
+impl TermFolder for Instantiate<'_> {}
+
+impl FormulaFolder for Instantiate<'_> {
+    fn fold_formula(&mut self, fml: &Formula) -> Formula {
+        match fml {
+            Formula::Pred(name, args) if args.is_empty() => match self.0.formulas.get(name) {
+                Some((_, fml)) => fml.clone(),
+                None => fml.clone(),
+            },
+            _ => fold_subformulas(self, fml),
+        }
+    }
+}
+
+impl HilbertAxiom {
+    // The schema of a propositional axiom.
+    fn _schema(self) -> Option<&'static str> {
+        use HilbertAxiom::*;
+        Some(match self {
+            K => "(> A (> B A))",
+            S => "(> (> A (> B C)) (> (> A B) (> A C)))",
+            DoubleNegation => "(> (~ (~ A)) A)",
+            NotIntro => "(> (> A ⊥) (~ A))",
+            NotElim => "(> (~ A) (> A ⊥))",
+            AndIntro => "(> A (> B (^ A B)))",
+            AndElim1 => "(> (^ A B) A)",
+            AndElim2 => "(> (^ A B) B)",
+            OrIntro1 => "(> A (v A B))",
+            OrIntro2 => "(> B (v A B))",
+            OrElim => "(> (> A C) (> (> B C) (> (v A B) C)))",
+            IffIntro => "(> (> A B) (> (> B A) (<> A B)))",
+            IffElim1 => "(> (<> A B) (> A B))",
+            IffElim2 => "(> (<> A B) (> B A))",
+            TopIntro => "⊤",
+            BottomElim => "(> ⊥ A)",
+            _ => return None,
+        })
+    }
+
+    /// A proof of `⇒ fml` if it is an instance of the schema.
+    pub fn proof(self, fml: &Formula) -> Option<LK> {
+        use Formula::*;
+        use HilbertAxiom::*;
+        if let Some(schema) = self._schema() {
+            let formula = schema.parse::<Formula>().unwrap();
+            let vars = formula.get_preds().into_iter().collect();
+            let inst = Schema::new(formula.clone(), vars, vec![]).matches(fml)?;
+            // The proof of the schema stays valid with its metavariables
+            // replaced, as it only decomposes the schema.
+            let proof = FirstOrderProver::default()
+                .search(&Sequent::from(formula))
+                .ok()?;
+            let mut instantiate = Instantiate(&inst);
+            return Some(proof.map_sequents(&mut |s| {
+                Sequent {
+                    antecedent: s
+                        .antecedent
+                        .iter()
+                        .map(|f| instantiate.fold_formula(f))
+                        .collect(),
+                    succedent: s
+                        .succedent
+                        .iter()
+                        .map(|f| instantiate.fold_formula(f))
+                        .collect(),
+                }
+            }));
+        }
+        let (lhs, rhs) = match fml {
+            Equal(_, _) if self == Reflexivity => {
+                return _infer("Axiom", vec![], Sequent::from(fml.clone()))
+            }
+            Implies(lhs, rhs) => (&**lhs, &**rhs),
+            _ => return None,
+        };
+        let sequent = |antecedent: Vec<&Formula>, fml: &Formula| Sequent {
+            antecedent: antecedent.into_iter().cloned().collect(),
+            succedent: vec![fml.clone()],
+        };
+        // A proof of `lhs ⇒ rhs`, whose inferences check the instance.
+        let inner = match (self, lhs, rhs) {
+            (Instantiation, _, _) => {
+                _infer("ForallLeft", vec![_axiom(rhs)], sequent(vec![lhs], rhs))?
+            }
+            (Witness, _, _) => _infer("ExistsRight", vec![_axiom(lhs)], sequent(vec![lhs], rhs))?,
+            (Distribution, Forall(_, body), Implies(a, quantified)) => match &**body {
+                Implies(_, phi) => {
+                    let instance = _infer(
+                        "ForallLeft",
+                        vec![_modus_ponens(a, phi)?],
+                        sequent(vec![lhs, a], phi),
+                    )?;
+                    let general = _infer(
+                        "ForallRight",
+                        vec![instance],
+                        sequent(vec![lhs, a], quantified),
+                    )?;
+                    _implies_right(general, std::slice::from_ref(lhs), rhs)?
+                }
+                _ => return None,
+            },
+            (ExistsElim, Forall(_, body), Implies(quantified, a)) => match &**body {
+                Implies(phi, _) => {
+                    let instance = _infer(
+                        "ForallLeft",
+                        vec![_modus_ponens(phi, a)?],
+                        sequent(vec![lhs, phi], a),
+                    )?;
+                    let instance = _fit(
+                        instance,
+                        vec![(**phi).clone(), lhs.clone()],
+                        vec![(**a).clone()],
+                    )?;
+                    let elim = _infer(
+                        "ExistsLeft",
+                        vec![instance],
+                        sequent(vec![quantified, lhs], a),
+                    )?;
+                    _implies_right(elim, std::slice::from_ref(lhs), rhs)?
+                }
+                _ => return None,
+            },
+            (Substitution, Equal(_, _), Implies(before, after)) => {
+                let replaced = _infer(
+                    "EqualityRight",
+                    vec![_axiom(before)],
+                    sequent(vec![lhs, before], after),
+                )?;
+                _implies_right(replaced, std::slice::from_ref(lhs), rhs)?
+            }
+            _ => return None,
+        };
+        _implies_right(inner, &[], fml)
+    }
+}
+
+impl Hilbert {
+    pub fn conclusion(&self) -> Option<&Formula> {
+        self.lines.last().map(|line| &line.formula)
+    }
+
+    /// The hypotheses entailing the conclusion.
+    pub fn sequent(&self) -> Option<Sequent> {
+        Some(Sequent {
+            antecedent: self.hypotheses.clone(),
+            succedent: vec![self.conclusion()?.clone()],
+        })
+    }
+
+    fn _check_line(&self, i: usize) -> Result<(), &'static str> {
+        let fml = &self.lines[i].formula;
+        let earlier = |j: usize| match self.lines[..i].get(j) {
+            Some(line) => Ok(&line.formula),
+            None => Err("Line refers to a line not before it."),
+        };
+        match self.lines[i].justification {
+            Justification::Hypothesis(j) => match self.hypotheses.get(j) {
+                Some(hypothesis) if hypothesis == fml => Ok(()),
+                Some(_) => Err("Line is not the hypothesis."),
+                None => Err("Hypothesis does not exist."),
+            },
+            Justification::Axiom(axiom) => match axiom.proof(fml) {
+                Some(_) => Ok(()),
+                None => Err("Line is not an instance of the axiom schema."),
+            },
+            Justification::ModusPonens(j, k) => {
+                if earlier(k)? == &implies!(earlier(j)?.clone(), fml.clone()) {
+                    Ok(())
+                } else {
+                    Err("Modus ponens does not apply.")
+                }
+            }
+            Justification::Generalization(j) => match fml {
+                Formula::Forall(var, body) if &**body == earlier(j)? => {
+                    if self
+                        .hypotheses
+                        .iter()
+                        .any(|h| h.get_free_vars().contains(var))
+                    {
+                        Err("Generalized variable is free in a hypothesis.")
+                    } else {
+                        Ok(())
+                    }
+                }
+                _ => Err("Line is not a generalization of the line."),
+            },
+        }
+    }
+
+    /// Checks every line, failing with the index of the first wrong one and
+    /// the reason.
+    pub fn check(&self) -> Result<(), (usize, &'static str)> {
+        if self.lines.is_empty() {
+            return Err((0, "The proof has no lines."));
+        }
+        (0..self.lines.len()).try_for_each(|i| self._check_line(i).map_err(|e| (i, e)))
+    }
+
+    /// The deduction theorem: a proof of `A → C` from the hypotheses but
+    /// the last one, `A`, of this proof of `C`. Each line `B` becomes lines
+    /// ending with `A → B`, using the schemas `K` and `S` and, for
+    /// generalizations, `Distribution`.
+    pub fn discharge(&self) -> Result<Hilbert, &'static str> {
+        use Justification::*;
+        self.check().map_err(|_| "The proof is not valid.")?;
+        let (discharged, hypotheses) = match self.hypotheses.split_last() {
+            Some((discharged, hypotheses)) => (discharged, hypotheses.to_vec()),
+            None => return Err("The proof has no hypotheses."),
+        };
+        let mut lines: Vec<Line> = vec![];
+        // The line of `A → B` for each line `B`.
+        let mut implications = vec![];
+        let push = |lines: &mut Vec<Line>, formula: Formula, justification| {
+            lines.push(Line {
+                formula,
+                justification,
+            });
+            lines.len() - 1
+        };
+        let imp = |fml: &Formula| implies!(discharged.clone(), fml.clone());
+        for line in &self.lines {
+            let fml = &line.formula;
+            let last = match line.justification {
+                Hypothesis(j) if j == hypotheses.len() => {
+                    // `A → A` from `K` and `S`.
+                    let a = discharged;
+                    let aa = imp(a);
+                    let s = implies!(
+                        implies!(a.clone(), implies!(aa.clone(), a.clone())),
+                        implies!(implies!(a.clone(), aa.clone()), aa.clone())
+                    );
+                    let i = push(&mut lines, s, Axiom(HilbertAxiom::S));
+                    let k = implies!(a.clone(), implies!(aa.clone(), a.clone()));
+                    let j = push(&mut lines, k, Axiom(HilbertAxiom::K));
+                    let i = push(
+                        &mut lines,
+                        implies!(implies!(a.clone(), aa.clone()), aa.clone()),
+                        ModusPonens(j, i),
+                    );
+                    let j = push(
+                        &mut lines,
+                        implies!(a.clone(), aa.clone()),
+                        Axiom(HilbertAxiom::K),
+                    );
+                    push(&mut lines, aa, ModusPonens(j, i))
+                }
+                Hypothesis(_) | Axiom(_) => {
+                    let i = push(&mut lines, fml.clone(), line.justification.clone());
+                    let k = implies!(fml.clone(), imp(fml));
+                    let j = push(&mut lines, k, Axiom(HilbertAxiom::K));
+                    push(&mut lines, imp(fml), ModusPonens(i, j))
+                }
+                ModusPonens(j, k) => {
+                    // From `A → B` and `A → (B → C)` to `A → C` by `S`.
+                    let (lhs, rhs) = (implications[j], implications[k]);
+                    let b = &self.lines[j].formula;
+                    let s = implies!(
+                        imp(&implies!(b.clone(), fml.clone())),
+                        implies!(imp(b), imp(fml))
+                    );
+                    let i = push(&mut lines, s, Axiom(HilbertAxiom::S));
+                    let i = push(&mut lines, implies!(imp(b), imp(fml)), ModusPonens(rhs, i));
+                    push(&mut lines, imp(fml), ModusPonens(lhs, i))
+                }
+                Generalization(j) => {
+                    let (var, body) = match fml {
+                        Formula::Forall(var, body) => (var, &**body),
+                        _ => unreachable!(),
+                    };
+                    let general = forall!(var.clone(), imp(body));
+                    let i = push(&mut lines, general.clone(), Generalization(implications[j]));
+                    let d = implies!(general, imp(fml));
+                    let k = push(&mut lines, d, Axiom(HilbertAxiom::Distribution));
+                    push(&mut lines, imp(fml), ModusPonens(i, k))
+                }
+            };
+            implications.push(last);
+        }
+        // The implication for the conclusion comes last already.
+        Ok(Hilbert { hypotheses, lines })
+    }
+
+    /// The same proof in LK, of `Γ ⇒ C` for the hypotheses `Γ` and the
+    /// conclusion `C`. Modus ponens becomes a cut.
+    pub fn to_lk(&self) -> Result<LK, &'static str> {
+        self.check().map_err(|_| "The proof is not valid.")?;
+        let context = &self.hypotheses;
+        let fit = |proof: LK, fml: &Formula| _fit(proof, context.clone(), vec![fml.clone()]);
+        let mut proofs: Vec<LK> = vec![];
+        for line in &self.lines {
+            let fml = &line.formula;
+            let proof = match line.justification {
+                Justification::Hypothesis(_) => fit(_axiom(fml), fml),
+                Justification::Axiom(axiom) => fit(axiom.proof(fml).unwrap(), fml),
+                Justification::ModusPonens(j, k) => {
+                    let lhs = &self.lines[j].formula;
+                    let sequent = Sequent {
+                        antecedent: [&[implies!(lhs.clone(), fml.clone())], &context[..]].concat(),
+                        succedent: vec![fml.clone()],
+                    };
+                    let elim = _infer("ImpliesLeft", vec![proofs[j].clone(), _axiom(fml)], sequent);
+                    let sequent = Sequent {
+                        antecedent: [&context[..], &context[..]].concat(),
+                        succedent: vec![fml.clone()],
+                    };
+                    (elim.and_then(|elim| _infer("Cut", vec![proofs[k].clone(), elim], sequent)))
+                        .and_then(|cut| fit(cut, fml))
+                }
+                Justification::Generalization(j) => {
+                    let sequent = Sequent {
+                        antecedent: context.clone(),
+                        succedent: vec![fml.clone()],
+                    };
+                    _infer("ForallRight", vec![proofs[j].clone()], sequent)
+                }
+            };
+            proofs.push(proof.ok_or("A line could not be translated.")?);
+        }
+        Ok(proofs.pop().unwrap())
+    }
+
+    /// The same proof in natural deduction, of the conclusion from the
+    /// hypotheses. Axioms are translated through LK, see `lk_to_nd`.
+    pub fn to_nd(&self) -> Result<ND, &'static str> {
+        self.check().map_err(|_| "The proof is not valid.")?;
+        let judgment = |fml: &Formula| Sequent {
+            antecedent: self.hypotheses.clone(),
+            succedent: vec![fml.clone()],
+        };
+        let mut proofs: Vec<ND> = vec![];
+        for line in &self.lines {
+            let fml = &line.formula;
+            let proof = match line.justification {
+                Justification::Hypothesis(_) => ND::Assumption(judgment(fml)),
+                Justification::Axiom(axiom) => lk_to_nd(&axiom.proof(fml).unwrap())?,
+                Justification::ModusPonens(j, k) => ND::ImpliesElim(
+                    Box::new([proofs[k].clone(), proofs[j].clone()]),
+                    judgment(fml),
+                ),
+                Justification::Generalization(j) => {
+                    ND::ForallIntro(Box::new(proofs[j].clone()), judgment(fml))
+                }
+            };
+            proofs.push(proof);
+        }
+        Ok(proofs.pop().unwrap())
+    }
+}
@@ -0,0 +1,291 @@
+use crate::language::*;
+use crate::proof::*;
+use crate::visit::*;
+use std::collections::HashMap;
+
+/// Placeholder for a term or a formula left out, to be inferred by
+/// unification: the variable `_` where a term is expected, the atom `_`
+/// where a formula is.
+pub const HOLE: &str = "_";
+
+// Whether `name` is that of a hole, numbered or not.
+fn _is_hole(name: &str) -> bool {
+    name.starts_with('_') && name[1..].chars().all(|c| c.is_ascii_digit())
+}
+
+/// Holes numbered apart as `_1`, `_2`, … and the terms and formulas they
+/// are bound to by unification. Names of that form are reserved for the
+/// numbered holes.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Holes {
+    count: usize,
+    terms: HashMap<String, Term>,
+    formulas: HashMap<String, Formula>,
+}
+
+struct Numbering<'a>(&'a mut Holes);
+
+impl TermFolder for Numbering<'_> {
+    fn fold_term(&mut self, term: &Term) -> Term {
+        match term {
+            Term::Var(name) if name == HOLE => self.0.term(),
+            _ => fold_subterms(self, term),
+        }
+    }
+}
+
+impl FormulaFolder for Numbering<'_> {
+    fn fold_formula(&mut self, fml: &Formula) -> Formula {
+        match fml {
+            Formula::Pred(name, args) if name == HOLE && args.is_empty() => self.0.formula(),
+            _ => fold_subformulas(self, fml),
+        }
+    }
+}
+
+struct Resolution<'a>(&'a Holes);
+
+impl TermFolder for Resolution<'_> {
+    fn fold_term(&mut self, term: &Term) -> Term {
+        match term {
+            Term::Var(name) if self.0.terms.contains_key(name) => {
+                self.fold_term(&self.0.terms[name])
+            }
+            _ => fold_subterms(self, term),
+        }
+    }
+}
+
+impl FormulaFolder for Resolution<'_> {
+    fn fold_formula(&mut self, fml: &Formula) -> Formula {
+        match fml {
+            Formula::Pred(name, args) if args.is_empty() && self.0.formulas.contains_key(name) => {
+                self.fold_formula(&self.0.formulas[name])
+            }
+            _ => fold_subformulas(self, fml),
+        }
+    }
+}
+
+#[derive(Default)]
+struct HoleFinder {
+    found: bool,
+}
+
+impl TermVisitor for HoleFinder {
+    fn visit_term(&mut self, term: &Term) {
+        match term {
+            Term::Var(name) if _is_hole(name) => self.found = true,
+            _ => walk_term(self, term),
+        }
+    }
+}
+
+impl FormulaVisitor for HoleFinder {
+    fn visit_formula(&mut self, fml: &Formula) {
+        match fml {
+            Formula::Pred(name, args) if args.is_empty() && _is_hole(name) => self.found = true,
+            _ => walk_formula(self, fml),
+        }
+    }
+}
+
+impl Holes {
+    pub fn new() -> Holes {
+        Holes::default()
+    }
+
+    /// A fresh hole for a term.
+    pub fn term(&mut self) -> Term {
+        self.count += 1;
+        Term::Var(format!("_{}", self.count))
+    }
+
+    /// A fresh hole for a formula.
+    pub fn formula(&mut self) -> Formula {
+        self.count += 1;
+        Formula::Pred(format!("_{}", self.count), vec![])
+    }
+
+    /// `term` with each hole `_` replaced by a fresh numbered hole.
+    pub fn number_term(&mut self, term: &Term) -> Term {
+        Numbering(self).fold_term(term)
+    }
+
+    /// `fml` with each hole `_` replaced by a fresh numbered hole.
+    pub fn number(&mut self, fml: &Formula) -> Formula {
+        Numbering(self).fold_formula(fml)
+    }
+
+    pub fn number_sequent(&mut self, sequent: &Sequent) -> Sequent {
+        Sequent {
+            antecedent: sequent.antecedent.iter().map(|f| self.number(f)).collect(),
+            succedent: sequent.succedent.iter().map(|f| self.number(f)).collect(),
+        }
+    }
+
+    /// `term` with its bound holes replaced by what they are bound to.
+    pub fn resolve_term(&self, term: &Term) -> Term {
+        Resolution(self).fold_term(term)
+    }
+
+    /// `fml` with its bound holes replaced by what they are bound to.
+    pub fn resolve(&self, fml: &Formula) -> Formula {
+        Resolution(self).fold_formula(fml)
+    }
+
+    pub fn resolve_sequent(&self, sequent: &Sequent) -> Sequent {
+        Sequent {
+            antecedent: sequent.antecedent.iter().map(|f| self.resolve(f)).collect(),
+            succedent: sequent.succedent.iter().map(|f| self.resolve(f)).collect(),
+        }
+    }
+
+    fn _bind_term(&mut self, name: &str, term: &Term) -> bool {
+        if term.get_vars().contains(&Term::Var(name.to_string())) {
+            return false;
+        }
+        self.terms.insert(name.to_string(), term.clone());
+        true
+    }
+
+    fn _bind_formula(&mut self, name: &str, fml: &Formula) -> bool {
+        if fml
+            .get_preds()
+            .iter()
+            .any(|p| p.name == name && p.arity == 0)
+        {
+            return false;
+        }
+        self.formulas.insert(name.to_string(), fml.clone());
+        true
+    }
+
+    fn _unify_terms(&mut self, lhs: &Term, rhs: &Term) -> bool {
+        let (lhs, rhs) = (self.resolve_term(lhs), self.resolve_term(rhs));
+        match (&lhs, &rhs) {
+            _ if lhs == rhs => true,
+            (Term::Var(name), _) if _is_hole(name) => self._bind_term(name, &rhs),
+            (_, Term::Var(name)) if _is_hole(name) => self._bind_term(name, &lhs),
+            (Term::Func(f, xs), Term::Func(g, ys)) => {
+                f == g
+                    && xs.len() == ys.len()
+                    && xs.iter().zip(ys).all(|(x, y)| self._unify_terms(x, y))
+            }
+            _ => false,
+        }
+    }
+
+    fn _unify(&mut self, lhs: &Formula, rhs: &Formula) -> bool {
+        use Formula::*;
+        let (lhs, rhs) = (self.resolve(lhs), self.resolve(rhs));
+        match (&lhs, &rhs) {
+            _ if lhs == rhs => true,
+            (Pred(name, args), _) if args.is_empty() && _is_hole(name) => {
+                self._bind_formula(name, &rhs)
+            }
+            (_, Pred(name, args)) if args.is_empty() && _is_hole(name) => {
+                self._bind_formula(name, &lhs)
+            }
+            (Pred(p, xs), Pred(q, ys)) => {
+                p == q
+                    && xs.len() == ys.len()
+                    && xs.iter().zip(ys).all(|(x, y)| self._unify_terms(x, y))
+            }
+            (Equal(a, b), Equal(c, d)) => self._unify_terms(a, c) && self._unify_terms(b, d),
+            (Not(f), Not(g)) => self._unify(f, g),
+            (And(a, b), And(c, d))
+            | (Or(a, b), Or(c, d))
+            | (Implies(a, b), Implies(c, d))
+            | (Iff(a, b), Iff(c, d)) => self._unify(a, c) && self._unify(b, d),
+            (Forall(x, f), Forall(y, g)) | (Exists(x, f), Exists(y, g)) => {
+                self._unify_terms(x, y) && self._unify(f, g)
+            }
+            _ => false,
+        }
+    }
+
+    // Runs `unify`, undoing its bindings if it fails.
+    fn _atomically(&mut self, unify: impl FnOnce(&mut Holes) -> bool) -> bool {
+        let saved = (self.terms.clone(), self.formulas.clone());
+        let unified = unify(self);
+        if !unified {
+            (self.terms, self.formulas) = saved;
+        }
+        unified
+    }
+
+    /// Binds holes so that `lhs` and `rhs` become the same term. Leaves the
+    /// bindings as they were if they cannot.
+    pub fn unify_terms(&mut self, lhs: &Term, rhs: &Term) -> bool {
+        self._atomically(|holes| holes._unify_terms(lhs, rhs))
+    }
+
+    /// Binds holes so that `lhs` and `rhs` become the same formula. Leaves
+    /// the bindings as they were if they cannot.
+    pub fn unify(&mut self, lhs: &Formula, rhs: &Formula) -> bool {
+        self._atomically(|holes| holes._unify(lhs, rhs))
+    }
+
+    /// Same as `unify`, formula by formula of sequents of the same lengths.
+    pub fn unify_sequents(&mut self, lhs: &Sequent, rhs: &Sequent) -> bool {
+        self._atomically(|holes| {
+            lhs.antecedent.len() == rhs.antecedent.len()
+                && lhs.succedent.len() == rhs.succedent.len()
+                && (lhs.antecedent.iter().zip(&rhs.antecedent))
+                    .chain(lhs.succedent.iter().zip(&rhs.succedent))
+                    .all(|(l, r)| holes._unify(l, r))
+        })
+    }
+}
+
+impl Term {
+    /// Whether the term has holes, numbered or not.
+    pub fn has_holes(&self) -> bool {
+        let mut finder = HoleFinder::default();
+        finder.visit_term(self);
+        finder.found
+    }
+
+    /// The term with its holes filled by unification with `expected`, whose
+    /// own holes may be filled too, or `None` if they do not unify or holes
+    /// of the term are left unfilled.
+    pub fn elaborate(&self, expected: &Term) -> Option<Term> {
+        let mut holes = Holes::new();
+        let term = holes.number_term(self);
+        let expected = holes.number_term(expected);
+        if !holes.unify_terms(&term, &expected) {
+            return None;
+        }
+        Some(holes.resolve_term(&term)).filter(|t| !t.has_holes())
+    }
+}
+
+impl Formula {
+    /// Whether the formula has holes, numbered or not.
+    pub fn has_holes(&self) -> bool {
+        let mut finder = HoleFinder::default();
+        finder.visit_formula(self);
+        finder.found
+    }
+
+    /// Same as `Term::elaborate`, for formulas.
+    pub fn elaborate(&self, expected: &Formula) -> Option<Formula> {
+        let mut holes = Holes::new();
+        let fml = holes.number(self);
+        let expected = holes.number(expected);
+        if !holes.unify(&fml, &expected) {
+            return None;
+        }
+        Some(holes.resolve(&fml)).filter(|f| !f.has_holes())
+    }
+}
+
+impl Sequent {
+    pub fn has_holes(&self) -> bool {
+        self.antecedent
+            .iter()
+            .chain(&self.succedent)
+            .any(|f| f.has_holes())
+    }
+}
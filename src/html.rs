@@ -0,0 +1,71 @@
+use crate::graph::xml_escape;
+use crate::language::Notation;
+use crate::proof::*;
+
+const KATEX: &str = "https://cdn.jsdelivr.net/npm/katex@0.16.9/dist";
+
+const STYLE: &str = "\
+ul.proof, ul.proof ul { list-style: none; padding-left: 1.5em; }
+ul.proof summary, ul.proof .leaf { white-space: nowrap; }
+ul.proof .rule { color: #666; margin-left: 1em; }";
+
+impl LK {
+    fn _html_lines(&self, depth: usize, lines: &mut Vec<String>) {
+        let indent = "  ".repeat(depth);
+        let line = format!(
+            "\\({}\\)<span class=\"rule\">{}</span>",
+            xml_escape(&self.last().to_latex()),
+            xml_escape(&LabelScheme::Symbolic.label(self.rule_name(), Notation::Unicode))
+        );
+        if self.premises().is_empty() {
+            lines.push(format!("{}<li class=\"leaf\">{}</li>", indent, line));
+            return;
+        }
+        lines.push(format!("{}<li><details open>", indent));
+        lines.push(format!("{}  <summary>{}</summary>", indent, line));
+        lines.push(format!("{}  <ul>", indent));
+        for premise in self.premises() {
+            premise._html_lines(depth + 2, lines);
+        }
+        lines.push(format!("{}  </ul>", indent));
+        lines.push(format!("{}</details></li>", indent));
+    }
+
+    /// The proof as nested lists, the end sequent first and the premises
+    /// of each inference in a `<details>` element below its conclusion, so
+    /// that subproofs can be folded. Sequents are LaTeX between `\(` and
+    /// `\)`, for MathJax or KaTeX to render.
+    pub fn to_html_fragment(&self) -> String {
+        let mut lines = vec!["<ul class=\"proof\">".to_string()];
+        self._html_lines(1, &mut lines);
+        lines.push("</ul>".to_string());
+        lines.join("\n")
+    }
+
+    /// A standalone page showing `to_html_fragment` with its formulas
+    /// rendered by KaTeX, loaded from a CDN.
+    pub fn to_html(&self) -> String {
+        format!(
+            "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{}</title>
+<link rel=\"stylesheet\" href=\"{katex}/katex.min.css\">
+<script defer src=\"{katex}/katex.min.js\"></script>
+<script defer src=\"{katex}/contrib/auto-render.min.js\" onload=\"renderMathInElement(document.body)\"></script>
+<style>
+{}
+</style>
+</head>
+<body>
+{}
+</body>
+</html>",
+            xml_escape(self.last().to_string().trim()),
+            STYLE,
+            self.to_html_fragment(),
+            katex = KATEX
+        )
+    }
+}
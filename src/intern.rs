@@ -0,0 +1,302 @@
+use crate::language::*;
+use std::collections::{HashMap, HashSet};
+
+/// A term of an `Interner`. Two ids of the same interner are equal exactly
+/// when the terms are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TermId(u32);
+
+/// A formula of an `Interner`, compared in constant time like `TermId`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FormulaId(u32);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TermNode {
+    Var(String),
+    Func(String, Vec<TermId>),
+}
+
+/// A `Formula` node whose children are interned.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FormulaNode {
+    Pred(String, Vec<TermId>),
+    Equal(TermId, TermId),
+    Not(FormulaId),
+    And(FormulaId, FormulaId),
+    Or(FormulaId, FormulaId),
+    Implies(FormulaId, FormulaId),
+    Iff(FormulaId, FormulaId),
+    Forall(TermId, FormulaId),
+    Exists(TermId, FormulaId),
+    Top,
+    Bottom,
+}
+
+impl FormulaNode {
+    fn children(&self) -> Vec<FormulaId> {
+        match self {
+            FormulaNode::Not(fml) | FormulaNode::Forall(_, fml) | FormulaNode::Exists(_, fml) => {
+                vec![*fml]
+            }
+            FormulaNode::And(lhs, rhs)
+            | FormulaNode::Or(lhs, rhs)
+            | FormulaNode::Implies(lhs, rhs)
+            | FormulaNode::Iff(lhs, rhs) => vec![*lhs, *rhs],
+            _ => vec![],
+        }
+    }
+}
+
+fn _union(sets: impl IntoIterator<Item = Vec<TermId>>) -> Vec<TermId> {
+    let mut union = sets.into_iter().flatten().collect::<Vec<_>>();
+    union.sort();
+    union.dedup();
+    union
+}
+
+/// Hash-consed terms and formulas: every distinct term and formula is stored
+/// once and referred to by id, so equal subterms are shared and compared in
+/// constant time. The free variables of every node are kept along with it.
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    terms: Vec<TermNode>,
+    term_vars: Vec<Vec<TermId>>,
+    term_ids: HashMap<TermNode, TermId>,
+    formulas: Vec<FormulaNode>,
+    free_vars: Vec<Vec<TermId>>,
+    formula_ids: HashMap<FormulaNode, FormulaId>,
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+
+    /// The id of `node`, adding it if it is new.
+    pub fn make_term(&mut self, node: TermNode) -> TermId {
+        if let Some(&id) = self.term_ids.get(&node) {
+            return id;
+        }
+        let id = TermId(self.terms.len() as u32);
+        let vars = match &node {
+            TermNode::Var(_) => vec![id],
+            TermNode::Func(_, terms) => _union(terms.iter().map(|t| self.term_vars(*t).to_vec())),
+        };
+        self.terms.push(node.clone());
+        self.term_vars.push(vars);
+        self.term_ids.insert(node, id);
+        id
+    }
+
+    /// The id of `node`, adding it if it is new.
+    pub fn make(&mut self, node: FormulaNode) -> FormulaId {
+        if let Some(&id) = self.formula_ids.get(&node) {
+            return id;
+        }
+        let id = FormulaId(self.formulas.len() as u32);
+        let free_vars = match &node {
+            FormulaNode::Pred(_, terms) => {
+                _union(terms.iter().map(|t| self.term_vars(*t).to_vec()))
+            }
+            FormulaNode::Equal(lhs, rhs) => _union(vec![
+                self.term_vars(*lhs).to_vec(),
+                self.term_vars(*rhs).to_vec(),
+            ]),
+            FormulaNode::Forall(var, body) | FormulaNode::Exists(var, body) => self
+                .free_vars(*body)
+                .iter()
+                .copied()
+                .filter(|v| v != var)
+                .collect(),
+            _ => _union(node.children().iter().map(|c| self.free_vars(*c).to_vec())),
+        };
+        self.formulas.push(node.clone());
+        self.free_vars.push(free_vars);
+        self.formula_ids.insert(node, id);
+        id
+    }
+
+    pub fn term_node(&self, id: TermId) -> &TermNode {
+        &self.terms[id.0 as usize]
+    }
+
+    pub fn node(&self, id: FormulaId) -> &FormulaNode {
+        &self.formulas[id.0 as usize]
+    }
+
+    /// The variables of the term, sorted by id.
+    pub fn term_vars(&self, id: TermId) -> &[TermId] {
+        &self.term_vars[id.0 as usize]
+    }
+
+    /// The free variables of the formula, sorted by id.
+    pub fn free_vars(&self, id: FormulaId) -> &[TermId] {
+        &self.free_vars[id.0 as usize]
+    }
+
+    pub fn intern_term(&mut self, term: &Term) -> TermId {
+        let node = match term {
+            Term::Var(name) => TermNode::Var(name.clone()),
+            Term::Func(name, terms) => TermNode::Func(
+                name.clone(),
+                terms.iter().map(|t| self.intern_term(t)).collect(),
+            ),
+        };
+        self.make_term(node)
+    }
+
+    pub fn intern(&mut self, fml: &Formula) -> FormulaId {
+        let node = match fml {
+            Formula::Pred(name, terms) => FormulaNode::Pred(
+                name.clone(),
+                terms.iter().map(|t| self.intern_term(t)).collect(),
+            ),
+            Formula::Equal(lhs, rhs) => {
+                FormulaNode::Equal(self.intern_term(lhs), self.intern_term(rhs))
+            }
+            Formula::Not(fml) => FormulaNode::Not(self.intern(fml)),
+            Formula::And(lhs, rhs) => FormulaNode::And(self.intern(lhs), self.intern(rhs)),
+            Formula::Or(lhs, rhs) => FormulaNode::Or(self.intern(lhs), self.intern(rhs)),
+            Formula::Implies(lhs, rhs) => FormulaNode::Implies(self.intern(lhs), self.intern(rhs)),
+            Formula::Iff(lhs, rhs) => FormulaNode::Iff(self.intern(lhs), self.intern(rhs)),
+            Formula::Forall(var, fml) => {
+                FormulaNode::Forall(self.intern_term(var), self.intern(fml))
+            }
+            Formula::Exists(var, fml) => {
+                FormulaNode::Exists(self.intern_term(var), self.intern(fml))
+            }
+            Formula::Top => FormulaNode::Top,
+            Formula::Bottom => FormulaNode::Bottom,
+        };
+        self.make(node)
+    }
+
+    /// The term with the id as an owned tree.
+    pub fn term(&self, id: TermId) -> Term {
+        match self.term_node(id) {
+            TermNode::Var(name) => Term::Var(name.clone()),
+            TermNode::Func(name, terms) => {
+                Term::Func(name.clone(), terms.iter().map(|t| self.term(*t)).collect())
+            }
+        }
+    }
+
+    /// The formula with the id as an owned tree.
+    pub fn formula(&self, id: FormulaId) -> Formula {
+        let sub = |id: &FormulaId| Box::new(self.formula(*id));
+        match self.node(id) {
+            FormulaNode::Pred(name, terms) => {
+                Formula::Pred(name.clone(), terms.iter().map(|t| self.term(*t)).collect())
+            }
+            FormulaNode::Equal(lhs, rhs) => Formula::Equal(self.term(*lhs), self.term(*rhs)),
+            FormulaNode::Not(fml) => Formula::Not(sub(fml)),
+            FormulaNode::And(lhs, rhs) => Formula::And(sub(lhs), sub(rhs)),
+            FormulaNode::Or(lhs, rhs) => Formula::Or(sub(lhs), sub(rhs)),
+            FormulaNode::Implies(lhs, rhs) => Formula::Implies(sub(lhs), sub(rhs)),
+            FormulaNode::Iff(lhs, rhs) => Formula::Iff(sub(lhs), sub(rhs)),
+            FormulaNode::Forall(var, fml) => Formula::Forall(self.term(*var), sub(fml)),
+            FormulaNode::Exists(var, fml) => Formula::Exists(self.term(*var), sub(fml)),
+            FormulaNode::Top => Formula::Top,
+            FormulaNode::Bottom => Formula::Bottom,
+        }
+    }
+
+    /// The distinct subformulas, in time linear in their number.
+    pub fn subformulas(&self, id: FormulaId) -> HashSet<FormulaId> {
+        let mut seen = HashSet::new();
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            if seen.insert(id) {
+                stack.extend(self.node(id).children());
+            }
+        }
+        seen
+    }
+
+    fn _substitute_term(&mut self, id: TermId, var: TermId, term: TermId) -> TermId {
+        if self.term_vars(id).binary_search(&var).is_err() {
+            return id;
+        }
+        match self.term_node(id).clone() {
+            TermNode::Var(_) => term,
+            TermNode::Func(name, terms) => {
+                let terms = terms
+                    .iter()
+                    .map(|t| self._substitute_term(*t, var, term))
+                    .collect();
+                self.make_term(TermNode::Func(name, terms))
+            }
+        }
+    }
+
+    // A variable named after `var` not occurring anywhere in the interner.
+    fn _fresh_var(&mut self, var: TermId) -> TermId {
+        let name = match self.term_node(var) {
+            TermNode::Var(name) => name.clone(),
+            TermNode::Func(name, _) => name.clone(),
+        };
+        let mut idx = 0;
+        while self
+            .term_ids
+            .contains_key(&TermNode::Var(format!("{}{}", name, idx)))
+        {
+            idx += 1;
+        }
+        self.make_term(TermNode::Var(format!("{}{}", name, idx)))
+    }
+
+    /// Replaces the free occurrences of `var` by `term`. Bound variables
+    /// that would capture a variable of `term` are renamed to variables new
+    /// to the interner. Subformulas without `var` free are shared with the
+    /// original.
+    pub fn substitute(&mut self, id: FormulaId, var: TermId, term: TermId) -> FormulaId {
+        if self.free_vars(id).binary_search(&var).is_err() {
+            return id;
+        }
+        let node = match self.node(id).clone() {
+            FormulaNode::Pred(name, terms) => FormulaNode::Pred(
+                name,
+                terms
+                    .iter()
+                    .map(|t| self._substitute_term(*t, var, term))
+                    .collect(),
+            ),
+            FormulaNode::Equal(lhs, rhs) => FormulaNode::Equal(
+                self._substitute_term(lhs, var, term),
+                self._substitute_term(rhs, var, term),
+            ),
+            FormulaNode::Not(fml) => FormulaNode::Not(self.substitute(fml, var, term)),
+            FormulaNode::And(lhs, rhs) => FormulaNode::And(
+                self.substitute(lhs, var, term),
+                self.substitute(rhs, var, term),
+            ),
+            FormulaNode::Or(lhs, rhs) => FormulaNode::Or(
+                self.substitute(lhs, var, term),
+                self.substitute(rhs, var, term),
+            ),
+            FormulaNode::Implies(lhs, rhs) => FormulaNode::Implies(
+                self.substitute(lhs, var, term),
+                self.substitute(rhs, var, term),
+            ),
+            FormulaNode::Iff(lhs, rhs) => FormulaNode::Iff(
+                self.substitute(lhs, var, term),
+                self.substitute(rhs, var, term),
+            ),
+            FormulaNode::Forall(bound, body) | FormulaNode::Exists(bound, body) => {
+                let (bound, body) = if self.term_vars(term).contains(&bound) {
+                    let fresh = self._fresh_var(bound);
+                    (fresh, self.substitute(body, bound, fresh))
+                } else {
+                    (bound, body)
+                };
+                let body = self.substitute(body, var, term);
+                match self.node(id) {
+                    FormulaNode::Forall(_, _) => FormulaNode::Forall(bound, body),
+                    _ => FormulaNode::Exists(bound, body),
+                }
+            }
+            FormulaNode::Top | FormulaNode::Bottom => unreachable!(),
+        };
+        self.make(node)
+    }
+}
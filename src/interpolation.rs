@@ -0,0 +1,341 @@
+use crate::fresh::*;
+use crate::language::*;
+use crate::proof::*;
+use crate::prover::*;
+use crate::signature::*;
+use crate::visit::*;
+use std::collections::HashSet;
+
+/// A partition of the formulas of a sequent into two parts, `true` marking
+/// the formulas of the first one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Split {
+    pub antecedent: Vec<bool>,
+    pub succedent: Vec<bool>,
+}
+
+impl Split {
+    /// The split of `Gamma => Delta` into `Gamma =>` and `=> Delta`, whose
+    /// interpolants are those of the implication from `Gamma` to `Delta`.
+    pub fn antecedent_first(sequent: &Sequent) -> Split {
+        Split {
+            antecedent: vec![true; sequent.antecedent.len()],
+            succedent: vec![false; sequent.succedent.len()],
+        }
+    }
+
+    /// The first and the second part of `sequent`.
+    pub fn parts(&self, sequent: &Sequent) -> (Sequent, Sequent) {
+        let part = |first: bool| Sequent {
+            antecedent: (sequent.antecedent.iter().zip(&self.antecedent))
+                .filter(|(_, &l)| l == first)
+                .map(|(f, _)| f.clone())
+                .collect(),
+            succedent: (sequent.succedent.iter().zip(&self.succedent))
+                .filter(|(_, &l)| l == first)
+                .map(|(f, _)| f.clone())
+                .collect(),
+        };
+        (part(true), part(false))
+    }
+}
+
+/// A Craig interpolant `C` of a split sequent: `Gamma1 => Delta1, C` and
+/// `C, Gamma2 => Delta2` are provable, and every non-logical symbol and
+/// free variable of `C` occurs in both parts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interpolant {
+    pub formula: Formula,
+    /// Proofs of the two sequents, if asked for and found.
+    pub proofs: Option<(LK, LK)>,
+}
+
+fn _and(lhs: Formula, rhs: Formula) -> Formula {
+    match (lhs, rhs) {
+        (Formula::Top, fml) | (fml, Formula::Top) => fml,
+        (Formula::Bottom, _) | (_, Formula::Bottom) => Formula::Bottom,
+        (lhs, rhs) if lhs == rhs => lhs,
+        (lhs, rhs) => and!(lhs, rhs),
+    }
+}
+
+fn _or(lhs: Formula, rhs: Formula) -> Formula {
+    match (lhs, rhs) {
+        (Formula::Bottom, fml) | (fml, Formula::Bottom) => fml,
+        (Formula::Top, _) | (_, Formula::Top) => Formula::Top,
+        (lhs, rhs) if lhs == rhs => lhs,
+        (lhs, rhs) => or!(lhs, rhs),
+    }
+}
+
+// The part of the principal formula of `proof`, which has one.
+fn _principal(proof: &LK, split: &Split) -> bool {
+    match proof.spec().principals {
+        (0, _) => *split.succedent.last().unwrap(),
+        _ => split.antecedent[0],
+    }
+}
+
+// The splits of the premises of `proof` following the formulas of its
+// conclusion: the context keeps its part and active formulas take the part
+// of the principal formula.
+fn _premise_splits(proof: &LK, split: &Split) -> Result<Vec<Split>, &'static str> {
+    let premises = proof.premises();
+    let swap = |labels: &[bool], fmls: &[Formula], others: &[Formula]| {
+        let mut labels = labels.to_vec();
+        if let Some(i) = (fmls.iter().zip(others)).position(|(f, g)| f != g) {
+            labels.swap(i, i + 1);
+        }
+        labels
+    };
+    match proof {
+        LK::Cut(_, _) => return Err("The proof has cuts."),
+        LK::EqualityLeft(_, _) | LK::EqualityRight(_, _) => {
+            return Err("Equality rules are not supported.")
+        }
+        LK::TheoryRule(_, _) | LK::TheoryAxiom(_) => return Err("Theory rules are not supported."),
+        LK::ExchangeLeft(premise, conclusion) => {
+            let antecedent = swap(
+                &split.antecedent,
+                &conclusion.antecedent,
+                &premise.last().antecedent,
+            );
+            return Ok(vec![Split {
+                antecedent,
+                ..split.clone()
+            }]);
+        }
+        LK::ExchangeRight(premise, conclusion) => {
+            let succedent = swap(
+                &split.succedent,
+                &conclusion.succedent,
+                &premise.last().succedent,
+            );
+            return Ok(vec![Split {
+                succedent,
+                ..split.clone()
+            }]);
+        }
+        _ => {}
+    }
+    if premises.is_empty() {
+        return Ok(vec![]);
+    }
+    let principal = _principal(proof, split);
+    let (ant, suc) = proof.spec().principals;
+    let context = Split {
+        antecedent: split.antecedent[ant..].to_vec(),
+        succedent: split.succedent[..split.succedent.len() - suc].to_vec(),
+    };
+    let extend = |context: &Split, sequent: &Sequent| {
+        let k = sequent.antecedent.len() - context.antecedent.len();
+        let m = sequent.succedent.len() - context.succedent.len();
+        Split {
+            antecedent: [vec![principal; k], context.antecedent.clone()].concat(),
+            succedent: [context.succedent.clone(), vec![principal; m]].concat(),
+        }
+    };
+    if let LK::ImpliesLeft(ps, _) = proof {
+        // The contexts of the premises are split between them.
+        let (lhs, rhs) = (ps[0].last(), ps[1].last());
+        let (g, d) = (lhs.antecedent.len(), lhs.succedent.len() - 1);
+        let first = Split {
+            antecedent: context.antecedent[..g].to_vec(),
+            succedent: context.succedent[..d].to_vec(),
+        };
+        let second = Split {
+            antecedent: context.antecedent[g..].to_vec(),
+            succedent: context.succedent[d..].to_vec(),
+        };
+        return Ok(vec![extend(&first, lhs), extend(&second, rhs)]);
+    }
+    Ok(premises
+        .iter()
+        .map(|premise| extend(&context, premise.last()))
+        .collect())
+}
+
+// The interpolant of an axiom.
+fn _axiom(sequent: &Sequent, split: &Split) -> Formula {
+    if sequent.antecedent != sequent.succedent {
+        // `=> t = t`, `=> Top` or `Bottom =>`, which holds in its part.
+        let first = split.antecedent.iter().chain(&split.succedent).all(|&l| l);
+        return if first { Formula::Bottom } else { Formula::Top };
+    }
+    let pairs = (split.antecedent.iter().zip(&split.succedent))
+        .zip(&sequent.antecedent)
+        .map(|((&l, &r), fml)| (l, r, fml))
+        .collect::<Vec<_>>();
+    if pairs.iter().any(|&(l, r, _)| l && r) {
+        Formula::Bottom
+    } else if pairs.iter().any(|&(l, r, _)| !l && !r) {
+        Formula::Top
+    } else {
+        match pairs[0] {
+            (true, _, fml) => fml.clone(),
+            (false, _, fml) => not!(fml.clone()),
+        }
+    }
+}
+
+// Replaces the maximal subterms foreign to a part of a sequent, free
+// variables not free in it and terms headed by symbols not occurring in it,
+// by fresh variables.
+struct Abstraction<'a> {
+    signature: Signature,
+    vars: HashSet<Term>,
+    bound: Vec<Term>,
+    abstracted: Vec<(Term, Term)>,
+    symbols: &'a mut SymbolGenerator,
+    captured: bool,
+}
+
+impl Abstraction<'_> {
+    fn _is_foreign(&self, term: &Term) -> bool {
+        match term {
+            Term::Var(_) => !self.bound.contains(term) && !self.vars.contains(term),
+            Term::Func(name, args) if args.is_empty() => !self.signature.constants.contains(name),
+            Term::Func(name, args) => !self.signature.functions.contains(&NonLogicalSymbol {
+                name: name.clone(),
+                arity: args.len() as u32,
+            }),
+        }
+    }
+}
+
+impl TermFolder for Abstraction<'_> {
+    fn fold_term(&mut self, term: &Term) -> Term {
+        if !self._is_foreign(term) {
+            return fold_subterms(self, term);
+        }
+        if term.get_vars().iter().any(|v| self.bound.contains(v)) {
+            self.captured = true;
+            return term.clone();
+        }
+        if let Some((_, var)) = self.abstracted.iter().find(|(t, _)| t == term) {
+            return var.clone();
+        }
+        let var = self.symbols.fresh_var("y");
+        self.abstracted.push((term.clone(), var.clone()));
+        var
+    }
+}
+
+impl FormulaFolder for Abstraction<'_> {
+    fn fold_formula(&mut self, fml: &Formula) -> Formula {
+        match fml {
+            Formula::Forall(var, body) | Formula::Exists(var, body) => {
+                self.bound.push(var.clone());
+                let body = Box::new(self.fold_formula(body));
+                self.bound.pop();
+                match fml {
+                    Formula::Forall(_, _) => Formula::Forall(var.clone(), body),
+                    _ => Formula::Exists(var.clone(), body),
+                }
+            }
+            _ => fold_subformulas(self, fml),
+        }
+    }
+}
+
+// Quantifies the terms of `fml` foreign to `side` away, universally if
+// `side` is the first part and existentially otherwise.
+fn _abstract(
+    fml: Formula,
+    side: &Sequent,
+    first: bool,
+    symbols: &mut SymbolGenerator,
+) -> Result<Formula, &'static str> {
+    let mut abstraction = Abstraction {
+        signature: side.signature(),
+        vars: (side.antecedent.iter().chain(&side.succedent))
+            .flat_map(|f| f.get_free_vars())
+            .collect(),
+        bound: vec![],
+        abstracted: vec![],
+        symbols,
+        captured: false,
+    };
+    let mut fml = abstraction.fold_formula(&fml);
+    if abstraction.captured {
+        return Err("A term to quantify away contains a bound variable.");
+    }
+    // Distinct terms with the same head may denote the same element, so
+    // they cannot be replaced by independent variables.
+    let abstracted = abstraction.abstracted;
+    for (i, (term, _)) in abstracted.iter().enumerate() {
+        if let Term::Func(name, args) = term {
+            let shared = abstracted[i + 1..]
+                .iter()
+                .any(|(t, _)| matches!(t, Term::Func(n, _) if n == name));
+            if !args.is_empty() && shared {
+                return Err("A function symbol to quantify away is applied twice.");
+            }
+        }
+    }
+    for (_, var) in abstracted.into_iter().rev() {
+        fml = if first {
+            forall!(var, fml)
+        } else {
+            exists!(var, fml)
+        };
+    }
+    Ok(fml)
+}
+
+fn _interpolant(
+    proof: &LK,
+    split: &Split,
+    symbols: &mut SymbolGenerator,
+) -> Result<Formula, &'static str> {
+    let splits = _premise_splits(proof, split)?;
+    let premises = proof.premises();
+    let interpolants = (premises.iter().zip(&splits))
+        .map(|(premise, split)| _interpolant(premise, split, symbols))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut interpolants = interpolants.into_iter();
+    match (interpolants.next(), interpolants.next()) {
+        (None, _) => Ok(_axiom(proof.last(), split)),
+        (Some(lhs), Some(rhs)) if _principal(proof, split) => Ok(_or(lhs, rhs)),
+        (Some(lhs), Some(rhs)) => Ok(_and(lhs, rhs)),
+        // The term instantiating the quantifier may bring symbols missing
+        // from the part of the principal formula.
+        (Some(fml), None) if matches!(proof, LK::ForallLeft(_, _) | LK::ExistsRight(_, _)) => {
+            let first = _principal(proof, split);
+            let (lhs, rhs) = split.parts(proof.last());
+            _abstract(fml, if first { &lhs } else { &rhs }, first, symbols)
+        }
+        (Some(fml), None) => Ok(fml),
+    }
+}
+
+impl LK {
+    /// A Craig interpolant for `split` of the end sequent, computed from the
+    /// cut-free proof by Maehara's method. The proofs of the two sequents
+    /// are searched for with the `FirstOrderProver` if `with_proofs`.
+    /// Equality and theory rules are not supported.
+    pub fn interpolant(
+        &self,
+        split: &Split,
+        with_proofs: bool,
+    ) -> Result<Interpolant, &'static str> {
+        let end = self.last();
+        if split.antecedent.len() != end.antecedent.len()
+            || split.succedent.len() != end.succedent.len()
+        {
+            return Err("The split does not fit the end sequent.");
+        }
+        let mut symbols = SymbolGenerator::avoiding_proof(self);
+        let formula = _interpolant(self, split, &mut symbols)?;
+        let proofs = if with_proofs {
+            let (mut lhs, mut rhs) = split.parts(end);
+            lhs.succedent.push(formula.clone());
+            rhs.antecedent.insert(0, formula.clone());
+            let prover = FirstOrderProver::default();
+            prover.search(&lhs).ok().zip(prover.search(&rhs).ok())
+        } else {
+            None
+        };
+        Ok(Interpolant { formula, proofs })
+    }
+}
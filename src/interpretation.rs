@@ -0,0 +1,186 @@
+use crate::language::*;
+use std::collections::HashMap;
+
+type FuncInterpretation = Box<dyn Fn(&[Term]) -> Option<Term>>;
+type PredInterpretation = Box<dyn Fn(&[Term]) -> Option<bool>>;
+type ValueDeclaration = Box<dyn Fn(&Term) -> bool>;
+
+/// Interpreted function and predicate symbols used by `partial_eval`.
+///
+/// Functions receive already evaluated ground arguments and return the folded
+/// term, predicates return the truth value of a ground atom. Both may return
+/// `None` to leave the application untouched.
+pub struct Interpretation {
+    funcs: HashMap<NonLogicalSymbol, FuncInterpretation>,
+    preds: HashMap<NonLogicalSymbol, PredInterpretation>,
+    values: Option<ValueDeclaration>,
+}
+
+impl Interpretation {
+    pub fn new() -> Interpretation {
+        Interpretation {
+            funcs: HashMap::new(),
+            preds: HashMap::new(),
+            values: None,
+        }
+    }
+
+    pub fn interpret_func<F>(&mut self, s: NonLogicalSymbol, f: F)
+    where
+        F: Fn(&[Term]) -> Option<Term> + 'static,
+    {
+        self.funcs.insert(s, Box::new(f));
+    }
+
+    pub fn interpret_pred<F>(&mut self, s: NonLogicalSymbol, f: F)
+    where
+        F: Fn(&[Term]) -> Option<bool> + 'static,
+    {
+        self.preds.insert(s, Box::new(f));
+    }
+
+    /// Declares which ground terms are values, i.e. pairwise distinct
+    /// elements such as numerals. Equalities between two different values
+    /// evaluate to `⊥`.
+    pub fn declare_values<F>(&mut self, f: F)
+    where
+        F: Fn(&Term) -> bool + 'static,
+    {
+        self.values = Some(Box::new(f));
+    }
+
+    fn is_value(&self, term: &Term) -> bool {
+        match &self.values {
+            Some(f) => f(term),
+            None => false,
+        }
+    }
+}
+
+impl Default for Interpretation {
+    fn default() -> Self {
+        Interpretation::new()
+    }
+}
+
+impl Term {
+    pub fn is_ground(&self) -> bool {
+        match self {
+            Term::Var(_) => false,
+            Term::Func(_, terms) => terms.iter().all(|t| t.is_ground()),
+        }
+    }
+
+    pub fn partial_eval(&self, interpretation: &Interpretation) -> Term {
+        match self {
+            Term::Var(_) => self.clone(),
+            Term::Func(name, terms) => {
+                let terms = terms
+                    .iter()
+                    .map(|t| t.partial_eval(interpretation))
+                    .collect::<Vec<_>>();
+                let func = NonLogicalSymbol {
+                    name: name.into(),
+                    arity: terms.len() as u32,
+                };
+                if let Some(f) = interpretation.funcs.get(&func) {
+                    if terms.iter().all(|t| t.is_ground()) {
+                        if let Some(term) = f(&terms) {
+                            return term;
+                        }
+                    }
+                }
+                Term::Func(name.into(), terms)
+            }
+        }
+    }
+}
+
+impl Formula {
+    /// Folds interpreted subterms, decides ground interpreted atoms and
+    /// simplifies the connectives around the resulting `⊤`/`⊥`.
+    pub fn partial_eval(&self, interpretation: &Interpretation) -> Formula {
+        use Formula::*;
+        match self {
+            Pred(name, terms) => {
+                let terms = terms
+                    .iter()
+                    .map(|t| t.partial_eval(interpretation))
+                    .collect::<Vec<_>>();
+                let pred = NonLogicalSymbol {
+                    name: name.into(),
+                    arity: terms.len() as u32,
+                };
+                if let Some(f) = interpretation.preds.get(&pred) {
+                    if terms.iter().all(|t| t.is_ground()) {
+                        match f(&terms) {
+                            Some(true) => return Top,
+                            Some(false) => return Bottom,
+                            None => {}
+                        }
+                    }
+                }
+                Pred(name.into(), terms)
+            }
+            Equal(lhs, rhs) => {
+                let lhs = lhs.partial_eval(interpretation);
+                let rhs = rhs.partial_eval(interpretation);
+                if lhs == rhs {
+                    Top
+                } else if interpretation.is_value(&lhs) && interpretation.is_value(&rhs) {
+                    Bottom
+                } else {
+                    Equal(lhs, rhs)
+                }
+            }
+            Not(fml) => match fml.partial_eval(interpretation) {
+                Top => Bottom,
+                Bottom => Top,
+                fml => not!(fml),
+            },
+            And(lhs, rhs) => match (
+                lhs.partial_eval(interpretation),
+                rhs.partial_eval(interpretation),
+            ) {
+                (Bottom, _) | (_, Bottom) => Bottom,
+                (Top, fml) | (fml, Top) => fml,
+                (lhs, rhs) => and!(lhs, rhs),
+            },
+            Or(lhs, rhs) => match (
+                lhs.partial_eval(interpretation),
+                rhs.partial_eval(interpretation),
+            ) {
+                (Top, _) | (_, Top) => Top,
+                (Bottom, fml) | (fml, Bottom) => fml,
+                (lhs, rhs) => or!(lhs, rhs),
+            },
+            Implies(lhs, rhs) => match (
+                lhs.partial_eval(interpretation),
+                rhs.partial_eval(interpretation),
+            ) {
+                (Bottom, _) | (_, Top) => Top,
+                (Top, fml) => fml,
+                (fml, Bottom) => not!(fml),
+                (lhs, rhs) => implies!(lhs, rhs),
+            },
+            Iff(lhs, rhs) => match (
+                lhs.partial_eval(interpretation),
+                rhs.partial_eval(interpretation),
+            ) {
+                (Top, fml) | (fml, Top) => fml,
+                (Bottom, Bottom) => Top,
+                (Bottom, fml) | (fml, Bottom) => not!(fml),
+                (lhs, rhs) => iff!(lhs, rhs),
+            },
+            Forall(var, fml) => match fml.partial_eval(interpretation) {
+                fml @ Top | fml @ Bottom => fml,
+                fml => forall!(var.clone(), fml),
+            },
+            Exists(var, fml) => match fml.partial_eval(interpretation) {
+                fml @ Top | fml @ Bottom => fml,
+                fml => exists!(var.clone(), fml),
+            },
+            Top | Bottom => self.clone(),
+        }
+    }
+}
@@ -9,12 +9,16 @@ pub enum Token {
     And,
     Or,
     Implies,
+    Iff,
     Symbol(String),
     Forall,
     Exists,
+    Top,
+    Bottom,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Term {
     Var(String),
     Func(String, Vec<Term>),
@@ -83,6 +87,7 @@ macro_rules! func{
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Formula {
     Pred(String, Vec<Term>),
     Equal(Term, Term),
@@ -90,17 +95,43 @@ pub enum Formula {
     And(Box<Formula>, Box<Formula>),
     Or(Box<Formula>, Box<Formula>),
     Implies(Box<Formula>, Box<Formula>),
+    Iff(Box<Formula>, Box<Formula>),
     Forall(Term, Box<Formula>),
     Exists(Term, Box<Formula>),
+    Top,
+    Bottom,
 }
 
-impl Display for Formula {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+/// Symbols used when rendering formulas, sequents and proofs. `Ascii` writes
+/// `~`, `/\`, `\/`, `->`, `<->`, `forall`, `exists`, `true`, `false` and `=>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Notation {
+    Unicode,
+    Ascii,
+}
+
+impl Notation {
+    /// Symbols for `¬`, `∧`, `∨`, `→`, `↔`, `∀`, `∃`, `⊤` and `⊥`, in order.
+    /// Quantifier symbols include the space separating them from the
+    /// variable, if any.
+    pub(crate) fn symbols(self) -> [&'static str; 9] {
+        match self {
+            Notation::Unicode => ["¬", "∧", "∨", "→", "↔", "∀", "∃", "⊤", "⊥"],
+            Notation::Ascii => [
+                "~", "/\\", "\\/", "->", "<->", "forall ", "exists ", "true", "false",
+            ],
+        }
+    }
+}
+
+impl Formula {
+    pub fn to_string_with(&self, notation: Notation) -> String {
+        let [not, and, or, implies, iff, forall, exists, top, bottom] = notation.symbols();
+        let render = |fml: &Formula| fml.to_string_with(notation);
         match self {
             Formula::Pred(s, terms) => {
                 if terms.len() > 0 {
-                    write!(
-                        f,
+                    format!(
                         "{}({})",
                         s,
                         terms
@@ -110,20 +141,31 @@ impl Display for Formula {
                             .join(",")
                     )
                 } else {
-                    write!(f, "{}", s)
+                    s.to_string()
                 }
             }
-            Formula::Equal(lhs, rhs) => write!(f, "{} = {}", *lhs, *rhs),
-            Formula::Not(fml) => write!(f, "¬{}", *fml),
-            Formula::And(lhs, rhs) => write!(f, "({} ∧ {})", *lhs, *rhs),
-            Formula::Or(lhs, rhs) => write!(f, "({} ∨ {})", *lhs, *rhs),
-            Formula::Implies(lhs, rhs) => write!(f, "({} → {})", *lhs, *rhs),
-            Formula::Forall(term, fml) => write!(f, "∀{} {}", *term, *fml),
-            Formula::Exists(term, fml) => write!(f, "∃{} {}", *term, *fml),
+            Formula::Equal(lhs, rhs) => format!("{} = {}", lhs, rhs),
+            Formula::Not(fml) => format!("{}{}", not, render(fml)),
+            Formula::And(lhs, rhs) => format!("({} {} {})", render(lhs), and, render(rhs)),
+            Formula::Or(lhs, rhs) => format!("({} {} {})", render(lhs), or, render(rhs)),
+            Formula::Implies(lhs, rhs) => {
+                format!("({} {} {})", render(lhs), implies, render(rhs))
+            }
+            Formula::Iff(lhs, rhs) => format!("({} {} {})", render(lhs), iff, render(rhs)),
+            Formula::Forall(term, fml) => format!("{}{} {}", forall, term, render(fml)),
+            Formula::Exists(term, fml) => format!("{}{} {}", exists, term, render(fml)),
+            Formula::Top => top.to_string(),
+            Formula::Bottom => bottom.to_string(),
         }
     }
 }
 
+impl Display for Formula {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_string_with(Notation::Unicode))
+    }
+}
+
 macro_rules! pred{
     ($name: expr) => { Formula::Pred($name.into(), vec![]) };
     ($name: expr, $($args: expr),*) => { Formula::Pred($name.into(), vec![$( $args ),*]) };
@@ -153,6 +195,11 @@ macro_rules! implies {
         Formula::Implies(Box::new($lhs), Box::new($rhs))
     };
 }
+macro_rules! iff {
+    ($lhs: expr, $rhs: expr) => {
+        Formula::Iff(Box::new($lhs), Box::new($rhs))
+    };
+}
 macro_rules! forall {
     ($var: expr, $fml: expr) => {
         Formula::Forall($var, Box::new($fml))
@@ -179,61 +226,137 @@ macro_rules! nlsym {
     };
 }
 
+use crate::substitution::Substitution;
+use crate::tokenizer::quote_symbol;
+use crate::visit::*;
 use std::collections::HashSet;
 
-impl Term {
-    fn _get_vars(&self, vars: &mut HashSet<Term>) {
-        match self {
-            t @ Term::Var(_) => {
-                vars.insert(t.clone());
+// Variables of the visited terms not bound at their position, and the
+// variables of the visited quantifiers.
+#[derive(Default)]
+struct VarCollector {
+    bound: Vec<Term>,
+    free: HashSet<Term>,
+    quantified: HashSet<Term>,
+}
+
+impl TermVisitor for VarCollector {
+    fn visit_term(&mut self, term: &Term) {
+        if let Term::Var(_) = term {
+            if !self.bound.contains(term) {
+                self.free.insert(term.clone());
             }
-            Term::Func(_, terms) => {
-                terms.iter().for_each(|term| term._get_vars(vars));
+        }
+        walk_term(self, term);
+    }
+}
+
+impl FormulaVisitor for VarCollector {
+    fn visit_formula(&mut self, fml: &Formula) {
+        match fml {
+            Formula::Forall(var, body) | Formula::Exists(var, body) => {
+                self.quantified.insert(var.clone());
+                self.bound.push(var.clone());
+                self.visit_formula(body);
+                self.bound.pop();
             }
+            _ => walk_formula(self, fml),
         }
     }
+}
 
-    pub fn get_vars(&self) -> HashSet<Term> {
-        let mut vars: HashSet<Term> = HashSet::new();
-        self._get_vars(&mut vars);
-        vars
+#[derive(Default)]
+struct SymbolCollector {
+    funcs: HashSet<NonLogicalSymbol>,
+    preds: HashSet<NonLogicalSymbol>,
+}
+
+impl TermVisitor for SymbolCollector {
+    fn visit_term(&mut self, term: &Term) {
+        if let Term::Func(name, terms) = term {
+            self.funcs.insert(NonLogicalSymbol {
+                name: name.into(),
+                arity: terms.len() as u32,
+            });
+        }
+        walk_term(self, term);
     }
+}
 
-    fn _get_funcs(&self, funcs: &mut HashSet<NonLogicalSymbol>) {
-        match self {
-            Term::Func(name, terms) => {
-                funcs.insert(NonLogicalSymbol {
-                    name: name.into(),
-                    arity: terms.len() as u32,
-                });
-                terms.iter().for_each(|term| term._get_funcs(funcs));
-            }
-            _ => (),
+impl FormulaVisitor for SymbolCollector {
+    fn visit_formula(&mut self, fml: &Formula) {
+        if let Formula::Pred(name, terms) = fml {
+            self.preds.insert(NonLogicalSymbol {
+                name: name.into(),
+                arity: terms.len() as u32,
+            });
         }
+        walk_formula(self, fml);
     }
+}
 
-    pub fn get_funcs(&self) -> HashSet<NonLogicalSymbol> {
-        let mut funcs: HashSet<NonLogicalSymbol> = HashSet::new();
-        self._get_funcs(&mut funcs);
-        funcs
+#[derive(Default)]
+struct SubtermCollector {
+    terms: HashSet<Term>,
+}
+
+impl TermVisitor for SubtermCollector {
+    fn visit_term(&mut self, term: &Term) {
+        self.terms.insert(term.clone());
+        walk_term(self, term);
+    }
+}
+
+impl FormulaVisitor for SubtermCollector {}
+
+#[derive(Default)]
+struct SubformulaCollector {
+    formulas: HashSet<Formula>,
+}
+
+impl TermVisitor for SubformulaCollector {
+    fn visit_term(&mut self, _: &Term) {}
+}
+
+impl FormulaVisitor for SubformulaCollector {
+    fn visit_formula(&mut self, fml: &Formula) {
+        self.formulas.insert(fml.clone());
+        walk_formula(self, fml);
     }
+}
 
-    fn _get_subterms(&self, terms: &mut HashSet<Term>) {
+impl Term {
+    /// Renders the term in the syntax accepted by `Parser::parse_term`, so
+    /// that parsing the result gives back an equal term.
+    pub fn to_sexpr(&self) -> String {
         match self {
-            Term::Func(_, subterms) => {
-                for subterm in subterms {
-                    terms.extend(subterm.get_subterms());
-                }
-            }
-            _ => {}
+            Term::Var(s) => quote_symbol(s),
+            Term::Func(s, terms) => format!(
+                "({})",
+                std::iter::once(quote_symbol(s))
+                    .chain(terms.iter().map(|t| t.to_sexpr()))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
         }
-        terms.insert(self.clone());
+    }
+
+    pub fn get_vars(&self) -> HashSet<Term> {
+        let mut collector = VarCollector::default();
+        collector.visit_term(self);
+        collector.free
+    }
+
+    pub fn get_funcs(&self) -> HashSet<NonLogicalSymbol> {
+        let mut collector = SymbolCollector::default();
+        collector.visit_term(self);
+        collector.funcs
     }
 
     pub fn get_subterms(&self) -> HashSet<Term> {
-        let mut terms = HashSet::new();
-        self._get_subterms(&mut terms);
-        terms
+        let mut collector = SubtermCollector::default();
+        collector.visit_term(self);
+        collector.terms
     }
 
     pub fn substitute(&self, var: Term, term: Term) -> Term {
@@ -257,191 +380,102 @@ impl Term {
 }
 
 impl Formula {
-    fn _group_vars(&self, free_vars: &mut HashSet<Term>, bound_vars: &mut HashSet<Term>) {
+    /// Renders the formula in the Polish notation accepted by `Parser::parse`,
+    /// so that parsing the result gives back an equal formula.
+    pub fn to_sexpr(&self) -> String {
         match self {
-            Formula::Forall(var, fml) | Formula::Exists(var, fml) => {
-                bound_vars.insert(var.clone());
-                fml._group_vars(free_vars, bound_vars);
-            }
-            Formula::Pred(_, terms) => {
-                let vars = terms
-                    .iter()
-                    .flat_map(|term| term.get_vars())
-                    .filter(|var| !bound_vars.contains(var));
-                free_vars.extend(vars);
-            }
-            Formula::Equal(lhs, rhs) => {
-                let terms = [lhs, rhs];
-                let vars = terms
-                    .iter()
-                    .flat_map(|term| term.get_vars())
-                    .filter(|var| !bound_vars.contains(var));
-                free_vars.extend(vars);
-            }
-            Formula::Not(fml) => {
-                (*fml)._group_vars(free_vars, bound_vars);
-            }
-            Formula::And(lhs, rhs) | Formula::Or(lhs, rhs) | Formula::Implies(lhs, rhs) => {
-                (*lhs)._group_vars(free_vars, bound_vars);
-                (*rhs)._group_vars(free_vars, bound_vars);
+            Formula::Pred(s, terms) => {
+                if terms.is_empty() {
+                    quote_symbol(s)
+                } else {
+                    format!(
+                        "({} {})",
+                        quote_symbol(s),
+                        terms
+                            .iter()
+                            .map(|t| t.to_sexpr())
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    )
+                }
             }
+            Formula::Equal(lhs, rhs) => format!("(= {} {})", lhs.to_sexpr(), rhs.to_sexpr()),
+            Formula::Not(fml) => format!("(~ {})", fml.to_sexpr()),
+            Formula::And(lhs, rhs) => format!("(^ {} {})", lhs.to_sexpr(), rhs.to_sexpr()),
+            Formula::Or(lhs, rhs) => format!("(v {} {})", lhs.to_sexpr(), rhs.to_sexpr()),
+            Formula::Implies(lhs, rhs) => format!("(> {} {})", lhs.to_sexpr(), rhs.to_sexpr()),
+            Formula::Iff(lhs, rhs) => format!("(<> {} {})", lhs.to_sexpr(), rhs.to_sexpr()),
+            Formula::Forall(var, fml) => format!("(V{} {})", var.to_sexpr(), fml.to_sexpr()),
+            Formula::Exists(var, fml) => format!("(E{} {})", var.to_sexpr(), fml.to_sexpr()),
+            Formula::Top => "⊤".to_string(),
+            Formula::Bottom => "⊥".to_string(),
         }
     }
 
     pub fn get_free_vars(&self) -> HashSet<Term> {
-        let mut free_vars = HashSet::new();
-        let mut bound_vars = HashSet::new();
-        self._group_vars(&mut free_vars, &mut bound_vars);
-        free_vars
+        let mut collector = VarCollector::default();
+        collector.visit_formula(self);
+        collector.free
     }
 
     pub fn get_bound_vars(&self) -> HashSet<Term> {
-        let mut free_vars = HashSet::new();
-        let mut bound_vars = HashSet::new();
-        self._group_vars(&mut free_vars, &mut bound_vars);
-        bound_vars
-    }
-
-    fn _get_funcs(&self, funcs: &mut HashSet<NonLogicalSymbol>) {
-        match self {
-            Formula::Forall(_, formula) | Formula::Exists(_, formula) => formula._get_funcs(funcs),
-            Formula::Pred(_, terms) => {
-                funcs.extend(terms.iter().flat_map(|term| term.get_funcs()));
-            }
-            Formula::Equal(lhs, rhs) => {
-                let terms = [lhs, rhs];
-                funcs.extend(terms.iter().flat_map(|term| term.get_funcs()));
-            }
-            Formula::Not(fml) => (*fml)._get_funcs(funcs),
-            Formula::And(lhs, rhs) | Formula::Or(lhs, rhs) | Formula::Implies(lhs, rhs) => {
-                (*lhs)._get_funcs(funcs);
-                (*rhs)._get_funcs(funcs);
-            }
-        }
+        let mut collector = VarCollector::default();
+        collector.visit_formula(self);
+        collector.quantified
     }
 
     pub fn get_funcs(&self) -> HashSet<NonLogicalSymbol> {
-        let mut funcs = HashSet::new();
-        self._get_funcs(&mut funcs);
-        funcs
-    }
-
-    fn _get_preds(&self, preds: &mut HashSet<NonLogicalSymbol>) {
-        match self {
-            Formula::Forall(_, fml) | Formula::Exists(_, fml) => fml._get_preds(preds),
-            Formula::Pred(name, terms) => {
-                preds.insert(NonLogicalSymbol {
-                    name: name.into(),
-                    arity: terms.len() as u32,
-                });
-            }
-            Formula::Not(fml) => (*fml)._get_preds(preds),
-            Formula::And(lhs, rhs) | Formula::Or(lhs, rhs) | Formula::Implies(lhs, rhs) => {
-                (*lhs)._get_preds(preds);
-                (*rhs)._get_preds(preds);
-            }
-            _ => (),
-        }
+        let mut collector = SymbolCollector::default();
+        collector.visit_formula(self);
+        collector.funcs
     }
 
     pub fn get_preds(&self) -> HashSet<NonLogicalSymbol> {
-        let mut preds = HashSet::new();
-        self._get_preds(&mut preds);
-        preds
+        let mut collector = SymbolCollector::default();
+        collector.visit_formula(self);
+        collector.preds
     }
 
-    pub fn is_substitutible(&self, var: Term, term: Term) -> bool {
-        let free_vars = self.get_free_vars();
-        let bound_vars = self.get_bound_vars();
-        if free_vars.contains(&var) {
-            let term_vars = term.get_vars();
-            term_vars.iter().any(|v| !bound_vars.contains(v))
-        } else {
-            true
-        }
-    }
-
-    fn _get_subterms(&self, terms: &mut HashSet<Term>) {
+    fn _is_free_for(&self, var: &Term, term_vars: &HashSet<Term>) -> bool {
         match self {
-            Formula::Pred(_, subterms) => {
-                for subterm in subterms {
-                    terms.extend(subterm.get_subterms());
-                }
+            Formula::Not(fml) => fml._is_free_for(var, term_vars),
+            Formula::And(lhs, rhs)
+            | Formula::Or(lhs, rhs)
+            | Formula::Implies(lhs, rhs)
+            | Formula::Iff(lhs, rhs) => {
+                lhs._is_free_for(var, term_vars) && rhs._is_free_for(var, term_vars)
             }
-            Formula::Equal(lterm, rterm) => {
-                terms.extend(lterm.get_subterms());
-                terms.extend(rterm.get_subterms());
+            Formula::Forall(v, fml) | Formula::Exists(v, fml) => {
+                v == var
+                    || !fml.get_free_vars().contains(var)
+                    || (!term_vars.contains(v) && fml._is_free_for(var, term_vars))
             }
-            Formula::Not(fml) => fml._get_subterms(terms),
-            Formula::And(lhs, rhs) | Formula::Or(lhs, rhs) | Formula::Implies(lhs, rhs) => {
-                lhs._get_subterms(terms);
-                rhs._get_subterms(terms);
-            }
-            Formula::Forall(_, fml) | Formula::Exists(_, fml) => fml._get_subterms(terms),
+            Formula::Pred(_, _) | Formula::Equal(_, _) | Formula::Top | Formula::Bottom => true,
         }
     }
 
-    pub fn get_subterms(&self) -> HashSet<Term> {
-        let mut terms = HashSet::new();
-        self._get_subterms(&mut terms);
-        terms
+    /// Whether `term` can replace the free occurrences of `var` without any
+    /// of its variables getting bound by a quantifier of the formula.
+    pub fn is_substitutible(&self, var: Term, term: Term) -> bool {
+        self._is_free_for(&var, &term.get_vars())
     }
 
-    fn _get_subformulas(&self, formulas: &mut HashSet<Formula>) {
-        formulas.insert(self.clone());
-        match self {
-            Formula::Not(fml) => {
-                fml._get_subformulas(formulas);
-            }
-            Formula::And(lhs, rhs) | Formula::Or(lhs, rhs) | Formula::Implies(lhs, rhs) => {
-                lhs._get_subformulas(formulas);
-                rhs._get_subformulas(formulas);
-            }
-            Formula::Forall(_, fml) | Formula::Exists(_, fml) => fml._get_subformulas(formulas),
-            _ => {}
-        }
+    pub fn get_subterms(&self) -> HashSet<Term> {
+        let mut collector = SubtermCollector::default();
+        collector.visit_formula(self);
+        collector.terms
     }
 
     pub fn get_subformulas(&self) -> HashSet<Formula> {
-        let mut formulas = HashSet::new();
-        self._get_subformulas(&mut formulas);
-        formulas
+        let mut collector = SubformulaCollector::default();
+        collector.visit_formula(self);
+        collector.formulas
     }
 
+    /// Replaces the free occurrences of `var` by `term`, renaming bound
+    /// variables that would capture a variable of `term`.
     pub fn substitute(&self, var: Term, term: Term) -> Formula {
-        match self {
-            Formula::Pred(s, subterms) => Formula::Pred(
-                s.into(),
-                subterms
-                    .iter()
-                    .map(|t| t.substitute(var.clone(), term.clone()))
-                    .collect(),
-            ),
-            Formula::Equal(lterm, rterm) => Formula::Equal(
-                lterm.substitute(var.clone(), term.clone()),
-                rterm.substitute(var.clone(), term.clone()),
-            ),
-            Formula::Not(fml) => Formula::Not(Box::new((*fml).substitute(var, term))),
-            Formula::And(lhs, rhs) => Formula::And(
-                Box::new((*lhs).substitute(var.clone(), term.clone())),
-                Box::new((*rhs).substitute(var.clone(), term.clone())),
-            ),
-            Formula::Or(lhs, rhs) => Formula::Or(
-                Box::new((*lhs).substitute(var.clone(), term.clone())),
-                Box::new((*rhs).substitute(var.clone(), term.clone())),
-            ),
-            Formula::Implies(lhs, rhs) => Formula::Implies(
-                Box::new((*lhs).substitute(var.clone(), term.clone())),
-                Box::new((*rhs).substitute(var, term)),
-            ),
-            Formula::Forall(var, fml) => Formula::Forall(
-                var.clone(),
-                Box::new((*fml).substitute(var.clone(), term.clone())),
-            ),
-            Formula::Exists(var, fml) => Formula::Exists(
-                var.clone(),
-                Box::new((*fml).substitute(var.clone(), term.clone())),
-            ),
-        }
+        let sub: Substitution = std::iter::once((var, term)).collect();
+        sub.apply_to_formula(self)
     }
 }
@@ -0,0 +1,144 @@
+use crate::language::*;
+use crate::proof::*;
+
+// `name` in math mode: single letters and digits as they are, longer names
+// in italics with the characters special to TeX escaped.
+fn _identifier(name: &str) -> String {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if c.is_ascii_alphanumeric() {
+            return c.to_string();
+        }
+    }
+    let mut escaped = String::new();
+    for c in name.chars() {
+        match c {
+            '_' | '#' | '$' | '%' | '&' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped += "\\mbox{\\textasciitilde}",
+            '^' => escaped += "\\mbox{\\textasciicircum}",
+            '\\' => escaped += "\\backslash{}",
+            c => escaped.push(c),
+        }
+    }
+    format!("\\mathit{{{}}}", escaped)
+}
+
+fn _application(name: &str, args: &[Term]) -> String {
+    if args.is_empty() {
+        return _identifier(name);
+    }
+    let args = args.iter().map(Term::to_latex).collect::<Vec<_>>();
+    format!("{}({})", _identifier(name), args.join(", "))
+}
+
+// The label of `rule` for the `Symbolic` scheme, with connectives as math
+// symbols and the number of a rule as a subscript, e.g. `$\land$L$_1$`.
+fn _label(rule: &str) -> String {
+    let label = LabelScheme::Symbolic.label(rule, Notation::Unicode);
+    let mut latex = String::new();
+    for c in label.chars() {
+        let symbol = match c {
+            '¬' => "\\neg",
+            '∧' => "\\land",
+            '∨' => "\\lor",
+            '→' => "\\rightarrow",
+            '↔' => "\\leftrightarrow",
+            '∀' => "\\forall",
+            '∃' => "\\exists",
+            c if c.is_ascii_digit() => {
+                latex += &format!("$_{}$", c);
+                continue;
+            }
+            c => {
+                latex.push(c);
+                continue;
+            }
+        };
+        latex += &format!("${}$", symbol);
+    }
+    latex
+}
+
+impl Term {
+    /// The term in LaTeX math mode, e.g. `f(x, a)`.
+    pub fn to_latex(&self) -> String {
+        match self {
+            Term::Var(name) => _identifier(name),
+            Term::Func(name, args) => _application(name, args),
+        }
+    }
+}
+
+impl Formula {
+    /// The formula in LaTeX math mode, parenthesized like `Display`, e.g.
+    /// `\forall x\, (P(x) \rightarrow \bot)`.
+    pub fn to_latex(&self) -> String {
+        let binary = |lhs: &Formula, connective: &str, rhs: &Formula| {
+            format!("({} {} {})", lhs.to_latex(), connective, rhs.to_latex())
+        };
+        match self {
+            Formula::Pred(name, args) => _application(name, args),
+            Formula::Equal(lhs, rhs) => format!("{} = {}", lhs.to_latex(), rhs.to_latex()),
+            Formula::Not(fml) => format!("\\neg {}", fml.to_latex()),
+            Formula::And(lhs, rhs) => binary(lhs, "\\land", rhs),
+            Formula::Or(lhs, rhs) => binary(lhs, "\\lor", rhs),
+            Formula::Implies(lhs, rhs) => binary(lhs, "\\rightarrow", rhs),
+            Formula::Iff(lhs, rhs) => binary(lhs, "\\leftrightarrow", rhs),
+            Formula::Forall(var, fml) => {
+                format!("\\forall {}\\, {}", var.to_latex(), fml.to_latex())
+            }
+            Formula::Exists(var, fml) => {
+                format!("\\exists {}\\, {}", var.to_latex(), fml.to_latex())
+            }
+            Formula::Top => "\\top".to_string(),
+            Formula::Bottom => "\\bot".to_string(),
+        }
+    }
+}
+
+impl Sequent {
+    /// The sequent in LaTeX math mode, e.g. `A, B \Rightarrow A \land B`.
+    pub fn to_latex(&self) -> String {
+        let fmls = |fmls: &[Formula]| {
+            fmls.iter()
+                .map(Formula::to_latex)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let (antecedent, succedent) = (fmls(&self.antecedent), fmls(&self.succedent));
+        [antecedent.as_str(), "\\Rightarrow", succedent.as_str()]
+            .iter()
+            .filter(|part| !part.is_empty())
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl LK {
+    fn _latex_lines(&self, depth: usize, lines: &mut Vec<String>) {
+        for premise in self.premises() {
+            premise._latex_lines(depth + 1, lines);
+        }
+        lines.push(format!(
+            "{}\\infer{}[{}]{{{}}}",
+            "  ".repeat(depth),
+            self.premises().len(),
+            _label(self.rule_name()),
+            self.last().to_latex()
+        ));
+    }
+
+    /// The proof as an `ebproof` `prooftree` environment, one `\infer` per
+    /// inference labelled like `LabelScheme::Symbolic`. Premises come
+    /// before their conclusion, indented by their depth.
+    pub fn to_latex(&self) -> String {
+        let mut lines = vec!["\\begin{prooftree}".to_string()];
+        self._latex_lines(1, &mut lines);
+        lines.push("\\end{prooftree}".to_string());
+        lines.join("\n")
+    }
+}
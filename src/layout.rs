@@ -0,0 +1,180 @@
+use crate::language::*;
+use crate::proof::*;
+
+/// Columns between the renderings of adjacent premises.
+pub const PREMISE_GAP: usize = 4;
+
+// The rendering of an inference, measured before anything is drawn. Columns
+// are relative to the block of the subproof it concludes.
+struct Node {
+    lines: Vec<String>,
+    // The label, or `None` for an initial sequent.
+    label: Option<String>,
+    // Indices of the premises among the nodes.
+    premises: Vec<usize>,
+    // Columns of the blocks of the premises.
+    offsets: Vec<usize>,
+    // Height of the premises, the rule line being just below.
+    above: usize,
+    width: usize,
+    height: usize,
+    // Columns of the conclusion, `start..start + len`.
+    start: usize,
+    len: usize,
+    // Columns of the rule line.
+    rule: (usize, usize),
+}
+
+// The lines of `text` without the spaces common to their starts, nor those
+// at their ends.
+fn _trimmed_lines(text: &str) -> Vec<String> {
+    let indent = text
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.chars().take_while(|c| *c == ' ').count())
+        .min()
+        .unwrap_or(0);
+    text.lines()
+        .map(|l| {
+            l.chars()
+                .skip(indent)
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+fn _width(s: &str) -> usize {
+    s.chars().count()
+}
+
+impl Node {
+    fn new(proof: &LK, premises: Vec<usize>, nodes: &[Node], options: &RenderOptions) -> Node {
+        let lines = _trimmed_lines(&proof.last().render(options));
+        let len = lines.iter().map(|l| _width(l)).max().unwrap_or(0);
+        if premises.is_empty() {
+            return Node {
+                height: lines.len(),
+                lines,
+                label: None,
+                premises,
+                offsets: vec![],
+                above: 0,
+                width: len,
+                start: 0,
+                len,
+                rule: (0, 0),
+            };
+        }
+        let label = format!(
+            "({})",
+            options.labels.label(proof.rule_name(), options.notation)
+        );
+        let mut offsets = vec![];
+        let mut right = 0;
+        for &p in &premises {
+            offsets.push(right);
+            right += nodes[p].width + PREMISE_GAP;
+        }
+        let (first, last) = (&nodes[premises[0]], &nodes[*premises.last().unwrap()]);
+        let mut from = offsets[0] + first.start;
+        let mut to = offsets[offsets.len() - 1] + last.start + last.len;
+        // Centers the conclusion below the conclusions of the premises,
+        // moving these right if it is wider.
+        let centered = from as isize + (to as isize - from as isize - len as isize) / 2;
+        let shift = (-centered).max(0) as usize;
+        for offset in &mut offsets {
+            *offset += shift;
+        }
+        from += shift;
+        to += shift;
+        let start = (centered + shift as isize) as usize;
+        let rule = (from.min(start), to.max(start + len) + 1);
+        let above = premises.iter().map(|&p| nodes[p].height).max().unwrap();
+        Node {
+            above,
+            height: above + 1 + lines.len(),
+            width: (right - PREMISE_GAP + shift).max(rule.1 + _width(&label)),
+            lines,
+            label: Some(label),
+            premises,
+            offsets,
+            start,
+            len,
+            rule,
+        }
+    }
+}
+
+// Writes `s` into `row` from column `column`.
+fn _draw(row: &mut [char], column: usize, s: &str) {
+    for (cell, c) in row[column..].iter_mut().zip(s.chars()) {
+        *cell = c;
+    }
+}
+
+impl LK {
+    pub fn to_string(&self) -> String {
+        self.to_string_with(Notation::Unicode)
+    }
+
+    /// Renders the proof tree in the given notation.
+    pub fn to_string_with(&self, notation: Notation) -> String {
+        self.render(&RenderOptions {
+            notation,
+            ..RenderOptions::default()
+        })
+    }
+
+    /// Renders the proof tree, wrapping each sequent as described by
+    /// `RenderOptions::max_width`. Measures every subproof first, then draws
+    /// them into a grid of the size of the whole rendering, so the time
+    /// taken is linear in the size of the rendering.
+    pub fn render(&self, options: &RenderOptions) -> String {
+        let nodes = LK::_measure(self, options);
+        let root = nodes.last().unwrap();
+        let mut grid = vec![vec![' '; root.width]; root.height];
+        let mut stack = vec![(nodes.len() - 1, 0, 0)];
+        while let Some((index, column, row)) = stack.pop() {
+            let node = &nodes[index];
+            for (&p, &offset) in node.premises.iter().zip(&node.offsets) {
+                stack.push((p, column + offset, row + node.above - nodes[p].height));
+            }
+            if let Some(label) = &node.label {
+                let (from, to) = node.rule;
+                let line = "-".repeat(to - from) + label;
+                _draw(&mut grid[row + node.above], column + from, &line);
+            }
+            let first = row + node.height - node.lines.len();
+            for (i, line) in node.lines.iter().enumerate() {
+                _draw(&mut grid[first + i], column + node.start, line);
+            }
+        }
+        grid.iter()
+            .map(|row| row.iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // The nodes of the rendering, premises before conclusions, measured
+    // with an explicit stack so deep proofs do not overflow the call stack.
+    fn _measure(proof: &LK, options: &RenderOptions) -> Vec<Node> {
+        let mut stack = vec![(proof, false)];
+        let mut nodes = vec![];
+        let mut done = vec![];
+        while let Some((proof, expanded)) = stack.pop() {
+            let premises = proof.premises();
+            if !expanded {
+                stack.push((proof, true));
+                stack.extend(premises.into_iter().rev().map(|p| (p, false)));
+                continue;
+            }
+            let premises = done.split_off(done.len() - premises.len());
+            let node = Node::new(proof, premises, &nodes, options);
+            done.push(nodes.len());
+            nodes.push(node);
+        }
+        nodes
+    }
+}
@@ -5,13 +5,105 @@ extern crate assert_matches;
 #[allow(unused_macros)]
 #[macro_use]
 pub mod language;
+pub mod adapt;
+pub mod arena;
+pub mod background;
+pub mod bdd;
+pub mod binary;
+pub mod builder;
+pub mod cache;
+pub mod certificate;
+pub mod check;
+pub mod classify;
+pub mod cnf;
+pub mod congruence;
+pub mod cut;
+pub mod datatype;
+pub mod definition;
+pub mod dependency;
+pub mod differential;
+pub mod exchange;
+pub mod features;
+pub mod fresh;
+pub mod g3;
+pub mod generalize;
+pub mod generate;
+#[cfg(test)]
+mod golden;
+pub mod graph;
+pub mod ground;
+pub mod hilbert;
+pub mod hole;
+pub mod html;
+pub mod intern;
+pub mod interpolation;
+pub mod interpretation;
+pub mod latex;
+pub mod layout;
+pub mod limits;
+pub mod lint;
+pub mod metrics;
+pub mod midsequent;
+pub mod minimize;
+pub mod miniscope;
 pub mod model;
+pub mod nd;
+pub mod normal_form;
 pub mod parser;
+pub mod pipeline;
+/// The types most programs need. Their paths here are kept stable across
+/// releases, while the modules defining them may be reorganized.
+pub mod prelude;
 #[allow(unused_macros)]
 #[macro_use]
 pub mod proof;
+pub mod proof_term;
+pub mod provenance;
+pub mod prover;
+pub mod prune;
+pub mod record;
+pub mod rename;
+pub mod resolution;
+pub mod rewrite;
+pub mod rules;
+pub mod sat;
+pub mod schema;
+pub mod schematic;
+pub mod script;
+pub mod sequent_form;
+#[cfg(feature = "serde")]
+pub mod serialize;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod session;
+pub mod signature;
+pub mod simplify;
+pub mod skolem;
+pub mod smtlib;
 pub mod solver;
+pub mod sort;
+pub mod strength;
+pub mod substitution;
+pub mod tactic;
+pub mod theory;
 pub mod tokenizer;
+pub mod tptp;
+pub mod trace;
+pub mod truth_table;
+pub mod unify;
+pub mod visit;
+
+mod sealed {
+    /// Keeps traits only the crate's own types implement open to new
+    /// methods.
+    pub trait Sealed {}
+
+    impl Sealed for crate::language::Formula {}
+    impl Sealed for crate::proof::Sequent {}
+    impl Sealed for crate::proof::LK {}
+    impl Sealed for crate::g3::G3 {}
+    impl Sealed for crate::nd::ND {}
+}
 
 #[test]
 fn tokenizer_works() {
@@ -597,6 +689,25 @@ fn lk_inference_rule_works() {
         sequent!( => ),
     );
     assert!(!invalid_cut.is_valid_inference());
+
+    // The contexts of the premises Γ ⇒ Δ, A and A, Π ⇒ Σ are joined as
+    // Γ, Π ⇒ Δ, Σ, not Γ, Δ ⇒ Π, Σ.
+    let premises = || {
+        Box::new([
+            LK::Axiom(sequent!(pred!("g") => pred!("d"), pred!("a"))),
+            LK::Axiom(sequent!(pred!("a"), pred!("p") => pred!("s"))),
+        ])
+    };
+    let cut = LK::Cut(
+        premises(),
+        sequent!(pred!("g"), pred!("p") => pred!("d"), pred!("s")),
+    );
+    assert!(cut.is_valid_inference());
+    let cut = LK::Cut(
+        premises(),
+        sequent!(pred!("g"), pred!("d") => pred!("p"), pred!("s")),
+    );
+    assert!(!cut.is_valid_inference());
 }
 
 #[test]
@@ -679,3 +790,5240 @@ fn prove_with_lk_works() {
     let fml = str_to_fml("(Vx0 (Vx1 (^ (= (a x y) (b x y)) (v (p y) (> q r)))))");
     assert_matches!(prove_with_lk(fml, 4, false), Err(_));
 }
+
+#[test]
+fn partial_eval_works() {
+    use interpretation::*;
+    use language::*;
+
+    let numeral = |t: &Term| match t {
+        Term::Func(s, terms) if terms.is_empty() => s.parse::<u32>().ok(),
+        _ => None,
+    };
+    let mut interpretation = Interpretation::new();
+    interpretation.interpret_func(nlsym!("plus", 2), move |args| {
+        match (numeral(&args[0]), numeral(&args[1])) {
+            (Some(m), Some(n)) => Some(func!((m + n).to_string())),
+            _ => None,
+        }
+    });
+    interpretation.interpret_pred(nlsym!("lt", 2), move |args| {
+        match (numeral(&args[0]), numeral(&args[1])) {
+            (Some(m), Some(n)) => Some(m < n),
+            _ => None,
+        }
+    });
+    interpretation.declare_values(move |t| numeral(t).is_some());
+
+    let term = func!("f", func!("plus", func!("1"), func!("2")), var!("x"));
+    assert_eq!(
+        func!("f", func!("3"), var!("x")),
+        term.partial_eval(&interpretation)
+    );
+
+    let fml = and!(
+        equal!(func!("plus", func!("1"), func!("1")), func!("2")),
+        pred!("p", func!("plus", var!("x"), func!("0")))
+    );
+    assert_eq!(
+        pred!("p", func!("plus", var!("x"), func!("0"))),
+        fml.partial_eval(&interpretation)
+    );
+
+    let fml = forall!(
+        var!("x"),
+        implies!(
+            pred!("lt", func!("plus", func!("2"), func!("2")), func!("3")),
+            pred!("p", var!("x"))
+        )
+    );
+    assert_eq!(Formula::Top, fml.partial_eval(&interpretation));
+
+    let fml = or!(
+        equal!(func!("plus", func!("2"), func!("2")), func!("5")),
+        pred!("q")
+    );
+    assert_eq!(pred!("q"), fml.partial_eval(&interpretation));
+}
+
+#[test]
+fn parse_smtlib_works() {
+    use language::*;
+    use smtlib::*;
+
+    let script = parse_smtlib(
+        "; group axioms
+        (set-logic UF)
+        (declare-sort U 0)
+        (declare-fun mul (U U) U)
+        (declare-const e U)
+        (declare-fun p (U) Bool)
+        (assert (forall ((x U)) (= (mul e x) x)))
+        (assert (=> (p e) (exists ((y U) (z U)) (and (p y) (not (= y z))))))
+        (check-sat)",
+    )
+    .unwrap();
+
+    assert_eq!(hashset![nlsym!("mul", 2), nlsym!("e", 0)], script.funcs);
+    assert_eq!(hashset![nlsym!("p", 1)], script.preds);
+    assert_eq!(
+        vec![
+            forall!(
+                var!("x"),
+                equal!(func!("mul", func!("e"), var!("x")), var!("x"))
+            ),
+            implies!(
+                pred!("p", func!("e")),
+                exists!(
+                    var!("y"),
+                    exists!(
+                        var!("z"),
+                        and!(pred!("p", var!("y")), not!(equal!(var!("y"), var!("z"))))
+                    )
+                )
+            )
+        ],
+        script.assertions
+    );
+
+    assert_matches!(parse_smtlib("(assert (p x))"), Err(_));
+    assert_matches!(parse_smtlib("(declare-const c U) (assert (c c))"), Err(_));
+    assert_matches!(parse_smtlib("(assert true"), Err(_));
+}
+
+#[test]
+fn features_works() {
+    use features::*;
+    use language::*;
+    use solver::*;
+
+    let fml = forall!(
+        var!("x"),
+        exists!(
+            var!("y"),
+            and!(
+                or!(pred!("p", var!("x")), not!(pred!("q", var!("y")))),
+                equal!(func!("f", var!("x")), var!("y"))
+            )
+        )
+    );
+    let features = fml.features();
+    assert_eq!(FEATURE_NAMES.len(), features.len());
+    let feature = |name| features[FEATURE_NAMES.iter().position(|n| *n == name).unwrap()];
+    assert_eq!(8.0, feature("size"));
+    assert_eq!(6.0, feature("depth"));
+    assert_eq!(1.0, feature("quantifier_alternations"));
+    assert_eq!(2.0, feature("pred_symbols"));
+    assert_eq!(1.0, feature("func_symbols"));
+    assert_eq!(2.0, feature("clauses"));
+    assert_eq!(2.0, feature("max_clause_width"));
+
+    let smaller_first = FeatureModel {
+        model: |features: &[f64]| -features[0],
+    };
+    let fml = implies!(
+        forall!(var!("x"), pred!("p", var!("x"))),
+        pred!("p", var!("y"))
+    );
+    assert_matches!(prove_with_lk_guided(fml, 4, false, &smaller_first), Ok(_));
+}
+
+#[test]
+fn unicode_tokenizer_works() {
+    use language::*;
+    use parser::Parser;
+    use tokenizer::Tokenizer;
+
+    let mut tokenizer = Tokenizer::new();
+    let ascii = tokenizer.tokenize("(Vx (Ey (^ (= x y) (v (~ (p y)) (> q r)))))");
+    let mut tokenizer = Tokenizer::new();
+    let unicode = tokenizer.tokenize("(∀x (∃y (∧ (= x y) (∨ (¬ (p y)) (→ q r)))))");
+    assert_eq!(ascii, unicode);
+
+    let mut tokenizer = Tokenizer::new();
+    let tokens = tokenizer.tokenize("(↔ (∧ ⊤ p) (∨ ⊥ p))");
+    let mut parser = Parser::new();
+    assert_eq!(
+        Ok(iff!(
+            and!(Formula::Top, pred!("p")),
+            or!(Formula::Bottom, pred!("p"))
+        )),
+        parser.parse(&tokens)
+    );
+}
+
+#[test]
+fn sexpr_round_trip_works() {
+    use language::*;
+    use parser::Parser;
+    use proof::*;
+    use tokenizer::Tokenizer;
+
+    struct Gen(u64);
+    impl Gen {
+        fn next(&mut self, n: u64) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 % n
+        }
+        fn name(&mut self) -> String {
+            let names = [
+                "x", "y0", "f", "Vx", "a b", "p|q", "v", "E", "~c", "g(x)", "¬", "\\",
+            ];
+            names[self.next(names.len() as u64) as usize].to_string()
+        }
+        fn term(&mut self, depth: u32) -> Term {
+            if depth == 0 || self.next(3) == 0 {
+                Term::Var(self.name())
+            } else {
+                let arity = self.next(3);
+                Term::Func(
+                    self.name(),
+                    (0..arity).map(|_| self.term(depth - 1)).collect(),
+                )
+            }
+        }
+        fn formula(&mut self, depth: u32) -> Formula {
+            let choice = if depth == 0 {
+                self.next(4)
+            } else {
+                self.next(10)
+            };
+            let sub = |g: &mut Gen| Box::new(g.formula(depth - 1));
+            match choice {
+                0 => Formula::Pred(self.name(), vec![]),
+                1 => {
+                    let arity = 1 + self.next(2);
+                    Formula::Pred(self.name(), (0..arity).map(|_| self.term(2)).collect())
+                }
+                2 => Formula::Equal(self.term(2), self.term(2)),
+                3 => [Formula::Top, Formula::Bottom][self.next(2) as usize].clone(),
+                4 => Formula::Not(sub(self)),
+                5 => Formula::And(sub(self), sub(self)),
+                6 => Formula::Or(sub(self), sub(self)),
+                7 => Formula::Implies(sub(self), sub(self)),
+                8 => Formula::Iff(sub(self), sub(self)),
+                9 => Formula::Forall(Term::Var(self.name()), sub(self)),
+                _ => Formula::Exists(Term::Var(self.name()), sub(self)),
+            }
+        }
+    }
+
+    let mut gen = Gen(0x2545_f491_4f6c_dd1d);
+    for _ in 0..200 {
+        let term = gen.term(3);
+        let tokens = Tokenizer::new().tokenize(&term.to_sexpr());
+        assert_eq!(Ok(term), Parser::new().parse_term(&tokens));
+
+        let fml = gen.formula(4);
+        let tokens = Tokenizer::new().tokenize(&fml.to_sexpr());
+        assert_eq!(Ok(fml), Parser::new().parse(&tokens));
+
+        let sequent = Sequent {
+            antecedent: (0..gen.next(3)).map(|_| gen.formula(2)).collect(),
+            succedent: (0..gen.next(3)).map(|_| gen.formula(2)).collect(),
+        };
+        let tokens = Tokenizer::new().tokenize(&sequent.to_sexpr());
+        assert_eq!(Ok(sequent), Parser::new().parse_sequent(&tokens));
+    }
+}
+
+#[test]
+fn proof_step_recording_works() {
+    use language::*;
+    use record::*;
+    use solver::*;
+
+    let fml = implies!(and!(pred!("p"), pred!("q")), pred!("q"));
+    let (result, steps) = prove_with_lk_recorded(fml.clone(), 4, false, &Unguided);
+    let proof = result.unwrap();
+    assert!(steps.iter().filter(|s| s.in_proof).count() >= proof.height() as usize);
+    let root = steps
+        .iter()
+        .find(|s| s.state.succedent == vec![fml.clone()])
+        .unwrap();
+    assert_eq!(Some("ImpliesRight"), root.action);
+    assert!(root.proved && root.in_proof);
+
+    let mut out = vec![];
+    write_jsonl(&steps, &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert_eq!(steps.len(), out.lines().count());
+    assert!(out.contains(
+        r#"{"state": "(() ((> (^ p q) q)))", "action": "ImpliesRight", "premises": ["(((^ p q)) (q))"], "outcome": "proved", "in_proof": true"#
+    ));
+
+    let (result, steps) = prove_with_lk_recorded(pred!("p"), 3, false, &Unguided);
+    assert_matches!(result, Err(_));
+    assert!(steps.is_empty());
+}
+
+#[test]
+fn iff_works() {
+    use language::*;
+    use parser::*;
+    use proof::*;
+    use solver::*;
+    use tokenizer::*;
+
+    let mut tokenizer = Tokenizer::new();
+    let tokens = tokenizer.tokenize("(<> (^ p q) (^ q p))");
+    let mut parser = Parser::new();
+    let fml = parser.parse(&tokens).unwrap();
+    assert_eq!(
+        iff!(and!(pred!("p"), pred!("q")), and!(pred!("q"), pred!("p"))),
+        fml
+    );
+    assert_eq!("((p ∧ q) ↔ (q ∧ p))", format!("{}", fml));
+    assert_matches!(prove_with_lk(fml, 8, false), Ok(_));
+    assert_matches!(
+        prove_with_lk(iff!(pred!("p"), pred!("q")), 6, false),
+        Err(_)
+    );
+
+    let valid_iff_right = LK::IffRight(
+        Box::new([
+            LK::Axiom(sequent!(pred!("p") => pred!("p"))),
+            LK::Axiom(sequent!(pred!("p") => pred!("p"))),
+        ]),
+        sequent!( => iff!(pred!("p"), pred!("p"))),
+    );
+    assert!(valid_iff_right.is_valid_inference());
+
+    let invalid_iff_right = LK::IffRight(
+        Box::new([
+            LK::Axiom(sequent!(pred!("p") => pred!("p"))),
+            LK::Axiom(sequent!(pred!("p") => pred!("p"))),
+        ]),
+        sequent!( => iff!(pred!("p"), pred!("q"))),
+    );
+    assert!(!invalid_iff_right.is_valid_inference());
+
+    let invalid_iff_left = LK::IffLeft(
+        Box::new([
+            LK::Axiom(sequent!(pred!("p"), pred!("q") => pred!("p"), pred!("q"))),
+            LK::Axiom(sequent!(pred!("p"), pred!("q") => pred!("p"), pred!("q"))),
+        ]),
+        sequent!(iff!(pred!("p"), pred!("q")) => ),
+    );
+    assert!(!invalid_iff_left.is_valid_inference());
+
+    let valid_iff_left = LK::IffLeft(
+        Box::new([
+            LK::Axiom(sequent!(pred!("p"), pred!("q"), pred!("r") => pred!("r"))),
+            LK::Axiom(sequent!(pred!("r") => pred!("r"), pred!("p"), pred!("q"))),
+        ]),
+        sequent!(iff!(pred!("p"), pred!("q")), pred!("r") => pred!("r")),
+    );
+    assert!(valid_iff_left.is_valid_inference());
+}
+
+#[test]
+fn exercises_works() {
+    use generate::*;
+
+    let config = ExerciseConfig {
+        count: 5,
+        max_formula_depth: 2,
+        ..ExerciseConfig::default()
+    };
+    let generated = exercises(&config);
+    assert_eq!(5, generated.len());
+    for exercise in &generated {
+        assert_eq!(&exercise.sequent, exercise.proof.last());
+        assert!(exercise.height >= config.min_proof_depth);
+        assert!(exercise.height <= config.max_proof_depth);
+        assert!(!exercise.rules.is_empty());
+    }
+    assert!(generated
+        .windows(2)
+        .all(|w| w[0].difficulty <= w[1].difficulty));
+    assert_eq!(generated, exercises(&config));
+
+    let config = ExerciseConfig {
+        count: 2,
+        preds: vec!["p".into()],
+        quantifiers: true,
+        max_hypotheses: 1,
+        ..ExerciseConfig::default()
+    };
+    assert!(exercises(&config).len() <= 2);
+}
+
+#[test]
+fn hint_works() {
+    use language::*;
+    use proof::*;
+    use solver::*;
+
+    let goal = sequent!(and!(pred!("p"), pred!("q")) => pred!("q"));
+    assert_eq!(
+        Some(Hint {
+            rule: "AndLeft2",
+            subgoals: vec![sequent!(pred!("q") => pred!("q"))],
+        }),
+        hint(&goal)
+    );
+
+    let goal = sequent!( => implies!(pred!("p"), or!(pred!("p"), pred!("q"))));
+    assert_eq!(
+        Some(Hint {
+            rule: "ImpliesRight",
+            subgoals: vec![sequent!(pred!("p") => or!(pred!("p"), pred!("q")))],
+        }),
+        hint(&goal)
+    );
+
+    let goal = sequent!(pred!("p") => pred!("p"));
+    assert_eq!(
+        Some(Hint {
+            rule: "Axiom",
+            subgoals: vec![],
+        }),
+        hint(&goal)
+    );
+    assert_eq!(None, hint(&sequent!( => pred!("p"))));
+}
+
+#[test]
+fn structural_normal_form_works() {
+    use language::*;
+    use proof::*;
+
+    fn is_valid_proof(proof: &LK) -> bool {
+        proof.is_valid_inference() && proof.premises().iter().all(|p| is_valid_proof(p))
+    }
+
+    let axiom = LK::Axiom(sequent!(pred!("p") => pred!("p")));
+    // An exchange undone by a second one disappears.
+    let proof = LK::ExchangeLeft(
+        Box::new(LK::ExchangeLeft(
+            Box::new(LK::WeakeningLeft(
+                Box::new(axiom.clone()),
+                sequent!(pred!("q"), pred!("p") => pred!("p")),
+            )),
+            sequent!(pred!("p"), pred!("q") => pred!("p")),
+        )),
+        sequent!(pred!("q"), pred!("p") => pred!("p")),
+    );
+    assert!(is_valid_proof(&proof));
+    let normal = proof.structural_normal_form();
+    assert!(is_valid_proof(&normal));
+    assert_eq!(proof.last(), normal.last());
+    assert_eq!(2, normal.height());
+
+    // A weakening followed by a contraction of the same formula disappears.
+    let proof = LK::ContractionLeft(
+        Box::new(LK::WeakeningLeft(
+            Box::new(axiom.clone()),
+            sequent!(pred!("p"), pred!("p") => pred!("p")),
+        )),
+        sequent!(pred!("p") => pred!("p")),
+    );
+    assert!(is_valid_proof(&proof));
+    assert_eq!(axiom, proof.structural_normal_form());
+
+    // The order of independent weakenings does not matter.
+    let left_first = LK::WeakeningRight(
+        Box::new(LK::WeakeningLeft(
+            Box::new(axiom.clone()),
+            sequent!(pred!("q"), pred!("p") => pred!("p")),
+        )),
+        sequent!(pred!("q"), pred!("p") => pred!("p"), pred!("r")),
+    );
+    let right_first = LK::WeakeningLeft(
+        Box::new(LK::WeakeningRight(
+            Box::new(axiom.clone()),
+            sequent!(pred!("p") => pred!("p"), pred!("r")),
+        )),
+        sequent!(pred!("q"), pred!("p") => pred!("p"), pred!("r")),
+    );
+    assert!(is_valid_proof(&left_first) && is_valid_proof(&right_first));
+    assert_ne!(left_first, right_first);
+    assert_eq!(
+        left_first.structural_normal_form(),
+        right_first.structural_normal_form()
+    );
+
+    // Runs below logical inferences are normalized as well.
+    let proof = LK::ImpliesRight(
+        Box::new(proof),
+        sequent!( => implies!(pred!("p"), pred!("p"))),
+    );
+    let normal = proof.structural_normal_form();
+    assert!(is_valid_proof(&normal));
+    assert_eq!(2, normal.height());
+
+    // Found proofs stay valid.
+    let fml = implies!(
+        and!(pred!("p"), pred!("q")),
+        or!(pred!("r"), and!(pred!("q"), pred!("p")))
+    );
+    let proof = solver::prove_with_lk(fml, 8, false).unwrap();
+    let normal = proof.structural_normal_form();
+    assert!(is_valid_proof(&normal));
+    assert_eq!(proof.last(), normal.last());
+}
+
+#[test]
+fn replay_works() {
+    use language::*;
+    use proof::*;
+
+    let fml = implies!(and!(pred!("p"), pred!("q")), and!(pred!("q"), pred!("p")));
+    let proof = solver::prove_with_lk(fml, 8, false).unwrap();
+
+    let mut derived: Vec<Sequent> = vec![];
+    let mut rules = vec![];
+    proof.replay(
+        &mut |rule: &'static str, premises: &[&Sequent], conclusion: &Sequent| {
+            assert!(premises.iter().all(|p| derived.contains(p)));
+            derived.push(conclusion.clone());
+            rules.push(rule);
+        },
+    );
+    assert_eq!(Some(proof.last()), derived.last());
+    assert_eq!(Some(&"ImpliesRight"), rules.last());
+    assert_eq!(2, rules.iter().filter(|r| **r == "Axiom").count());
+
+    struct Counter {
+        inferences: usize,
+        branchings: usize,
+    }
+    impl ProofVisitor for Counter {
+        fn inference(&mut self, _: &'static str, premises: &[&Sequent], _: &Sequent) {
+            self.inferences += 1;
+            if premises.len() == 2 {
+                self.branchings += 1;
+            }
+        }
+    }
+    let mut counter = Counter {
+        inferences: 0,
+        branchings: 0,
+    };
+    proof.replay(&mut counter);
+    assert_eq!(rules.len(), counter.inferences);
+    assert_eq!(1, counter.branchings);
+}
+
+#[test]
+fn ascii_output_works() {
+    use language::*;
+    use proof::*;
+
+    let fml = forall!(
+        var!("x"),
+        implies!(
+            and!(pred!("p", var!("x")), not!(Formula::Top)),
+            iff!(exists!(var!("y"), pred!("p", var!("y"))), Formula::Bottom)
+        )
+    );
+    assert_eq!(
+        "forall x ((p(x) /\\ ~true) -> (exists y p(y) <-> false))",
+        fml.to_string_with(Notation::Ascii)
+    );
+    assert_eq!(
+        "∀x ((p(x) ∧ ¬⊤) → (∃y p(y) ↔ ⊥))",
+        fml.to_string_with(Notation::Unicode)
+    );
+    assert_eq!(format!("{}", fml), fml.to_string_with(Notation::Unicode));
+
+    let sequent = sequent!(pred!("p"), pred!("q") => or!(pred!("p"), pred!("r")));
+    assert_eq!("p, q => (p \\/ r)", sequent.to_string_with(Notation::Ascii));
+    assert_eq!("p, q ⇒  (p ∨ r)", format!("{}", sequent));
+
+    let proof =
+        solver::prove_with_lk(implies!(pred!("p"), or!(pred!("q"), pred!("p"))), 8, false).unwrap();
+    let rendered = proof.to_string_with(Notation::Ascii);
+    assert!(rendered.is_ascii());
+    assert!(rendered.contains("(->R)") && rendered.contains("(\\/R2)"));
+    assert_eq!(proof.to_string().lines().count(), rendered.lines().count());
+}
+
+#[test]
+fn skolemize_works() {
+    use fresh::*;
+    use language::*;
+
+    let fml = forall!(
+        var!("x"),
+        exists!(var!("y"), pred!("p", var!("x"), var!("y")))
+    );
+    let mut symbols = SymbolGenerator::new();
+    assert_eq!(
+        forall!(var!("x"), pred!("p", var!("x"), func!("sk0", var!("x")))),
+        fml.skolemize(&mut symbols)
+    );
+    assert_eq!(&[nlsym!("sk0", 1)], symbols.introduced());
+
+    // Existing function names are avoided.
+    let fml = exists!(var!("x"), pred!("p", var!("x"), func!("sk0")));
+    let mut symbols = SymbolGenerator::new();
+    assert_eq!(
+        pred!("p", func!("sk1"), func!("sk0")),
+        fml.skolemize(&mut symbols)
+    );
+    assert_eq!(&[nlsym!("sk1", 0)], symbols.introduced());
+
+    // Quantifiers are classified by polarity.
+    let fml = implies!(
+        forall!(var!("x"), pred!("p", var!("x"))),
+        not!(forall!(var!("y"), pred!("q", var!("y"))))
+    );
+    assert_eq!(
+        implies!(pred!("p", func!("sk0")), not!(pred!("q", func!("sk1")))),
+        fml.skolemize(&mut SymbolGenerator::new())
+    );
+
+    // Skolem terms are not captured by inner quantifiers.
+    let fml = forall!(
+        var!("x"),
+        exists!(
+            var!("y"),
+            forall!(var!("x"), pred!("p", var!("x"), var!("y")))
+        )
+    );
+    assert_eq!(
+        forall!(
+            var!("x"),
+            forall!(var!("x0"), pred!("p", var!("x0"), func!("sk0", var!("x"))))
+        ),
+        fml.skolemize(&mut SymbolGenerator::new())
+    );
+
+    // Quantified biconditionals are split.
+    let fml = iff!(pred!("q"), exists!(var!("x"), pred!("p", var!("x"))));
+    assert_eq!(
+        and!(
+            implies!(pred!("q"), pred!("p", func!("sk0"))),
+            implies!(exists!(var!("x"), pred!("p", var!("x"))), pred!("q"))
+        ),
+        fml.skolemize(&mut SymbolGenerator::new())
+    );
+}
+
+#[test]
+fn cnf_works() {
+    use cnf::*;
+    use fresh::*;
+    use language::*;
+
+    let pos = |atom: Formula| Literal {
+        atom,
+        positive: true,
+    };
+    let neg = |atom: Formula| Literal {
+        atom,
+        positive: false,
+    };
+    let clause = |literals: Vec<Literal>| literals.into_iter().collect::<Clause>();
+    let to_formula = |clauses: &[Clause]| {
+        clauses
+            .iter()
+            .map(|c| {
+                c.iter()
+                    .map(|l| l.to_formula())
+                    .fold(Formula::Bottom, |acc, l| or!(acc, l))
+            })
+            .fold(Formula::Top, |acc, c| and!(acc, c))
+    };
+
+    let fml = and!(
+        implies!(pred!("p"), pred!("q")),
+        iff!(pred!("q"), not!(pred!("r")))
+    );
+    let clauses = fml.to_cnf();
+    assert_eq!(3, clauses.len());
+    assert!(clauses.contains(&clause(vec![neg(pred!("p")), pos(pred!("q"))])));
+    assert!(clauses.contains(&clause(vec![pos(pred!("q")), pos(pred!("r"))])));
+    assert_eq!(
+        None,
+        solver::refute_on_finite_models(iff!(fml, to_formula(&clauses)), 1)
+    );
+
+    assert_eq!(
+        Vec::<Clause>::new(),
+        or!(pred!("p"), not!(pred!("p"))).to_cnf()
+    );
+    assert_eq!(
+        vec![Clause::new()],
+        and!(pred!("p"), Formula::Bottom).to_cnf()
+    );
+
+    let fml = or!(and!(pred!("p"), pred!("q")), and!(pred!("r"), pred!("s")));
+    assert_eq!(4, fml.to_cnf().len());
+    let mut symbols = SymbolGenerator::new();
+    let clauses = fml.to_cnf_with(CnfConversion::Tseitin, &mut symbols);
+    assert_eq!(
+        &[nlsym!("ts0", 0), nlsym!("ts1", 0)],
+        symbols.introduced_preds()
+    );
+    assert_eq!(5, clauses.len());
+    assert!(clauses.contains(&clause(vec![pos(pred!("ts0")), pos(pred!("ts1"))])));
+    assert!(clauses.contains(&clause(vec![neg(pred!("ts0")), pos(pred!("p"))])));
+    assert!(clauses.contains(&clause(vec![neg(pred!("ts1")), pos(pred!("s"))])));
+
+    // First-order formulas are Skolemized and their variables renamed apart.
+    let fml = forall!(
+        var!("x"),
+        exists!(var!("y"), pred!("p", var!("x"), var!("y")))
+    );
+    let mut symbols = SymbolGenerator::new();
+    assert_eq!(
+        vec![clause(vec![pos(pred!(
+            "p",
+            var!("x"),
+            func!("sk0", var!("x"))
+        ))])],
+        fml.to_cnf_with(CnfConversion::Distributive, &mut symbols)
+    );
+    assert_eq!(&[nlsym!("sk0", 1)], symbols.introduced());
+    let fml = or!(
+        forall!(var!("x"), pred!("p", var!("x"))),
+        not!(exists!(var!("x"), pred!("q", var!("x"))))
+    );
+    assert_eq!(
+        vec![clause(vec![
+            pos(pred!("p", var!("x"))),
+            neg(pred!("q", var!("x0")))
+        ])],
+        fml.to_cnf()
+    );
+}
+
+#[test]
+fn wrapped_rendering_works() {
+    use language::*;
+    use proof::*;
+
+    let conj = and!(
+        and!(pred!("alpha"), pred!("beta")),
+        and!(pred!("gamma"), pred!("delta"))
+    );
+    let sequent = sequent!(conj.clone(), pred!("epsilon") => pred!("alpha"), pred!("delta"));
+    let options = RenderOptions {
+        notation: Notation::Ascii,
+        max_width: Some(24),
+        ..RenderOptions::default()
+    };
+    let rendered = sequent.render(&options);
+    assert_eq!(
+        "((alpha /\\ beta) /\\\n    (gamma /\\ delta)),\n    epsilon => alpha,\n    delta",
+        rendered
+    );
+    assert!(rendered.lines().all(|l| l.chars().count() <= 24));
+    assert_eq!(
+        sequent.to_string_with(Notation::Ascii),
+        sequent.render(&RenderOptions {
+            notation: Notation::Ascii,
+            max_width: Some(80),
+            ..RenderOptions::default()
+        })
+    );
+
+    let proof = solver::prove_with_lk(implies!(conj.clone(), pred!("gamma")), 8, false).unwrap();
+    assert_eq!(proof.to_string(), proof.render(&RenderOptions::default()));
+    let wrapped = proof.render(&options);
+    assert!(wrapped.lines().count() > proof.to_string().lines().count());
+    assert!(wrapped.contains("    (gamma /\\ delta)) =>"));
+    // Every line is padded to the same width.
+    let width = wrapped.lines().next().unwrap().chars().count();
+    assert!(wrapped.lines().all(|l| l.chars().count() == width));
+}
+
+#[test]
+fn label_scheme_works() {
+    use language::*;
+    use proof::*;
+
+    let symbolic = LabelScheme::Symbolic;
+    assert_eq!("∧L1", symbolic.label("AndLeft1", Notation::Unicode));
+    assert_eq!("/\\L1", symbolic.label("AndLeft1", Notation::Ascii));
+    assert_eq!("forallR", symbolic.label("ForallRight", Notation::Ascii));
+    assert_eq!("wL", symbolic.label("WeakeningLeft", Notation::Unicode));
+    assert_eq!(
+        "and left 1",
+        LabelScheme::Named.label("AndLeft1", Notation::Unicode)
+    );
+    assert_eq!(
+        "exists right",
+        LabelScheme::Named.label("ExistsRight", Notation::Unicode)
+    );
+    assert_eq!(
+        "ES-GIBT-IA",
+        LabelScheme::Gentzen.label("ExistsLeft", Notation::Unicode)
+    );
+    assert_eq!(
+        "Verdünnung",
+        LabelScheme::Gentzen.label("WeakeningRight", Notation::Unicode)
+    );
+    assert_eq!(
+        "Verduennung",
+        LabelScheme::Gentzen.label("WeakeningRight", Notation::Ascii)
+    );
+    let custom = LabelScheme::Custom(hashmap!["AndRight".to_string() => "∧I".to_string()]);
+    assert_eq!("∧I", custom.label("AndRight", Notation::Unicode));
+    assert_eq!("→R", custom.label("ImpliesRight", Notation::Unicode));
+
+    let proof = solver::prove_with_lk(implies!(pred!("p"), pred!("p")), 4, false).unwrap();
+    let rendered = proof.render(&RenderOptions {
+        labels: LabelScheme::Gentzen,
+        ..RenderOptions::default()
+    });
+    assert!(rendered.contains("(FOLGT-IS)"));
+    assert!(proof.to_string().contains("(→R)"));
+}
+
+#[test]
+fn lint_works() {
+    use language::*;
+    use lint::*;
+    use proof::*;
+
+    let vacuous = exists!(var!("y"), pred!("q"));
+    let fml = forall!(var!("x"), and!(pred!("p", var!("x")), vacuous.clone()));
+    assert_eq!(vec![Finding::VacuousQuantifier(vacuous)], lint(&fml));
+    assert!(lint(&pred!("p")).is_empty());
+
+    let (p, q) = (pred!("p"), pred!("q"));
+    let axiom = sequent!(p.clone() => p.clone());
+    let proof = LK::ExchangeLeft(
+        Box::new(LK::WeakeningLeft(
+            Box::new(LK::AndRight(
+                Box::new([LK::Axiom(axiom.clone()), LK::Axiom(axiom.clone())]),
+                sequent!(p.clone() => and!(p.clone(), p.clone())),
+            )),
+            sequent!(q.clone(), p.clone() => and!(p.clone(), p.clone())),
+        )),
+        sequent!(p.clone(), q.clone() => and!(p.clone(), p.clone())),
+    );
+    assert_eq!(
+        vec![
+            Finding::UnusedHypothesis(q),
+            Finding::DuplicateAxiom(axiom, 2)
+        ],
+        lint(&proof)
+    );
+
+    let fml = implies!(and!(pred!("p"), pred!("q")), and!(pred!("q"), pred!("p")));
+    let proof = solver::prove_with_lk(fml, 8, false).unwrap();
+    assert!(proof.lint().is_empty());
+}
+
+#[test]
+fn unify_works() {
+    use language::*;
+    use unify::*;
+
+    let lhs = func!("f", var!("x"), func!("g", var!("y")));
+    let rhs = func!("f", func!("g", var!("z")), var!("x"));
+    let sub = unify(&lhs, &rhs).unwrap();
+    assert_eq!(sub.apply_to_term(&lhs), sub.apply_to_term(&rhs));
+    assert_eq!(2, sub.len());
+    assert!(sub.get(&var!("x")).is_some());
+
+    assert_eq!(None, unify(&var!("x"), &func!("f", var!("x"))));
+    assert_eq!(None, unify(&func!("a"), &func!("b")));
+    assert!(unify(&var!("x"), &var!("x")).unwrap().is_empty());
+
+    let sub = unify_atoms(
+        &pred!("p", var!("x"), func!("a")),
+        &pred!("p", func!("b"), var!("y")),
+    )
+    .unwrap();
+    assert_eq!(Some(&func!("b")), sub.get(&var!("x")));
+    assert_eq!(Some(&func!("a")), sub.get(&var!("y")));
+    assert_eq!(
+        None,
+        unify_atoms(&pred!("p", var!("x")), &pred!("q", var!("x")))
+    );
+    assert!(unify_atoms(
+        &equal!(var!("x"), func!("a")),
+        &equal!(func!("a"), var!("y"))
+    )
+    .is_some());
+}
+
+#[test]
+fn schematic_sequent_works() {
+    use language::*;
+    use proof::*;
+    use schematic::*;
+
+    let gamma = || Part::Context("Γ".to_string(), vec![]);
+    let delta = || Part::Context("Δ".to_string(), vec![]);
+    let (p, q, r, s) = (pred!("p"), pred!("q"), pred!("r"), pred!("s"));
+
+    let schema = SchematicSequent {
+        antecedent: vec![gamma(), Part::Formula(p.clone()), gamma()],
+        succedent: vec![delta()],
+    };
+    let bindings = schema
+        .matches(&sequent!(q.clone(), p.clone(), q.clone() => r.clone()))
+        .unwrap();
+    assert_eq!(vec![q.clone()], bindings["Γ"]);
+    assert_eq!(vec![r.clone()], bindings["Δ"]);
+    assert_eq!(
+        None,
+        schema.matches(&sequent!(q.clone(), p.clone() => r.clone()))
+    );
+
+    let eigen = SchematicSequent {
+        antecedent: vec![Part::Context(
+            "Γ".to_string(),
+            vec![Constraint::NotFree(var!("x")), Constraint::NonEmpty],
+        )],
+        succedent: vec![],
+    };
+    assert!(eigen
+        .matches(&sequent!(pred!("p", var!("y")) => ))
+        .is_some());
+    assert!(eigen
+        .matches(&sequent!(pred!("p", var!("x")) => ))
+        .is_none());
+    assert!(eigen.matches(&sequent!( => )).is_none());
+
+    // p, q, Γ ⇒ Δ / p∧q, Γ ⇒ Δ
+    let rule = SchematicRule {
+        premises: vec![SchematicSequent {
+            antecedent: vec![Part::Formula(p.clone()), Part::Formula(q.clone()), gamma()],
+            succedent: vec![delta()],
+        }],
+        conclusion: SchematicSequent {
+            antecedent: vec![Part::Formula(and!(p.clone(), q.clone())), gamma()],
+            succedent: vec![delta()],
+        },
+    };
+    let pq = and!(p.clone(), q.clone());
+    let premise = sequent!(p.clone(), q.clone(), r.clone() => s.clone());
+    let proof = LK::ContractionLeft(
+        Box::new(LK::AndLeft2(
+            Box::new(LK::ExchangeLeft(
+                Box::new(LK::AndLeft1(
+                    Box::new(LK::Axiom(premise.clone())),
+                    sequent!(pq.clone(), q.clone(), r.clone() => s.clone()),
+                )),
+                sequent!(q.clone(), pq.clone(), r.clone() => s.clone()),
+            )),
+            sequent!(pq.clone(), pq.clone(), r.clone() => s.clone()),
+        )),
+        sequent!(pq.clone(), r.clone() => s.clone()),
+    );
+    assert!(rule.is_derived_by(&proof));
+    assert!(rule.instance(&[premise], proof.last()).is_some());
+    assert!(rule
+        .instance(
+            &[sequent!(q.clone(), p.clone(), r.clone() => s.clone())],
+            proof.last()
+        )
+        .is_none());
+    let wrong = proof.with_premises(vec![LK::Axiom(sequent!(pq.clone(), r => s))]);
+    assert!(!rule.is_derived_by(&wrong));
+}
+
+#[test]
+fn solve_works() {
+    use pipeline::*;
+    use std::time::Duration;
+
+    let options = SolveOptions::default();
+    assert_matches!(solve("(> (^ p q) (^ q p))", &options), Outcome::Proof(_));
+    assert_matches!(solve("((p q) (q))", &options), Outcome::Proof(_));
+    assert_matches!(
+        solve("(> p q)", &options),
+        Outcome::Countermodel(model) if model.domain_size == 1
+    );
+    assert_eq!(
+        Outcome::Unknown(vec!["Parse error.".to_string()]),
+        solve("(^ p", &options)
+    );
+
+    let options = SolveOptions {
+        timeout: Some(Duration::from_secs(0)),
+        ..SolveOptions::default()
+    };
+    assert_eq!(
+        Outcome::Unknown(vec![
+            "symbol p is used with arities [1, 2]".to_string(),
+            "timed out after 0 rounds".to_string()
+        ]),
+        solve("(v (p (c)) (~ (p (c) (c))))", &options)
+    );
+}
+
+#[test]
+fn matching_works() {
+    use language::*;
+
+    let pattern = func!("f", var!("x"), func!("g", var!("y")));
+    let term = func!("f", func!("a"), func!("g", func!("h", var!("z"))));
+    let sub = term.matches(&pattern).unwrap();
+    assert_eq!(term, sub.apply_to_term(&pattern));
+    assert_eq!(Some(&func!("a")), sub.get(&var!("x")));
+    assert_eq!(None, sub.get(&var!("z")));
+
+    let pattern = func!("f", var!("x"), var!("x"));
+    assert!(func!("f", func!("a"), func!("a"))
+        .matches(&pattern)
+        .is_some());
+    assert!(func!("f", func!("a"), func!("b"))
+        .matches(&pattern)
+        .is_none());
+    assert!(func!("f", var!("x"))
+        .matches(&func!("f", func!("a")))
+        .is_none());
+    assert!(func!("f", var!("y"))
+        .matches(&func!("f", var!("x")))
+        .is_some());
+
+    let pattern = forall!(var!("y"), pred!("p", var!("x"), var!("y")));
+    let sub = forall!(var!("y"), pred!("p", func!("c"), var!("y")))
+        .matches(&pattern)
+        .unwrap();
+    assert_eq!(Some(&func!("c")), sub.get(&var!("x")));
+    assert!(forall!(var!("y"), pred!("p", var!("y"), var!("y")))
+        .matches(&pattern)
+        .is_none());
+    assert!(exists!(var!("y"), pred!("p", func!("c"), var!("y")))
+        .matches(&pattern)
+        .is_none());
+}
+
+#[test]
+fn graph_export_works() {
+    use language::*;
+    use proof::*;
+
+    let p = pred!("p");
+    let axiom = LK::Axiom(sequent!(p.clone() => p.clone()));
+    let proof = LK::AndRight(
+        Box::new([axiom.clone(), axiom]),
+        sequent!(p.clone() => and!(p.clone(), p.clone())),
+    );
+    let graph = proof.to_graph();
+    assert_eq!(2, graph.nodes.len());
+    assert_eq!(2, graph.edges.len());
+    let root = graph.nodes.last().unwrap();
+    assert_eq!(("AndRight", 2, 3), (root.rule, root.height, root.tree_size));
+    let shared = graph.shared().collect::<Vec<_>>();
+    assert_eq!(1, shared.len());
+    assert_eq!((2, 2), (shared[0].1.occurrences, shared[0].1.parents));
+
+    let graphml = graph.to_graphml();
+    assert!(graphml.contains("<node id=\"n1\"><data key=\"rule\">AndRight</data>"));
+    assert!(graphml.contains("<edge source=\"n1\" target=\"n0\"><data key=\"index\">1</data>"));
+    let json = graph.to_json();
+    assert!(json.contains("\"links\": [{\"source\": 1, \"target\": 0, \"key\": 0, \"index\": 0}"));
+    assert!(json.contains("\"occurrences\": 2"));
+}
+
+#[test]
+fn substitution_works() {
+    use language::*;
+    use proof::*;
+    use substitution::*;
+
+    let (x, y) = (var!("x"), var!("y"));
+    let fml = forall!(y.clone(), pred!("p", x.clone(), y.clone()));
+    assert_eq!(
+        forall!(y.clone(), pred!("p", func!("c"), y.clone())),
+        fml.substitute(x.clone(), func!("c"))
+    );
+    assert_eq!(fml, fml.substitute(y.clone(), func!("c")));
+    let sub: Substitution = vec![(x.clone(), func!("f", y.clone()))]
+        .into_iter()
+        .collect();
+    assert_eq!(
+        forall!(var!("y0"), pred!("p", func!("f", y.clone()), var!("y0"))),
+        fml.substitute_with(&sub)
+    );
+
+    let tau: Substitution = vec![(y.clone(), func!("a"))].into_iter().collect();
+    let composed = sub.compose(&tau);
+    assert_eq!(func!("f", func!("a")), x.substitute_with(&composed));
+    assert_eq!(func!("a"), y.substitute_with(&composed));
+    assert_eq!(
+        tau.apply_to_term(&sub.apply_to_term(&func!("g", x.clone(), y.clone()))),
+        composed.apply_to_term(&func!("g", x.clone(), y.clone()))
+    );
+    let restricted = composed.restrict(&hashset![x.clone()]);
+    assert_eq!(1, restricted.len());
+    assert_eq!(None, restricted.get(&y));
+
+    assert!(fml.is_substitutible(x.clone(), func!("c")));
+    assert!(fml.is_substitutible(x.clone(), var!("z")));
+    assert!(!fml.is_substitutible(x.clone(), y.clone()));
+    assert!(fml.is_substitutible(y.clone(), x.clone()));
+
+    let instance = LK::ForallLeft(
+        Box::new(LK::Axiom(
+            sequent!(pred!("p", func!("c")) => pred!("p", func!("c"))),
+        )),
+        sequent!(forall!(x.clone(), pred!("p", x.clone())) => pred!("p", func!("c"))),
+    );
+    assert!(instance.is_valid_inference());
+}
+
+#[test]
+fn symbol_generator_works() {
+    use fresh::*;
+    use language::*;
+
+    let fml = forall!(var!("x0"), pred!("p0", var!("x0"), func!("f0")));
+    let mut symbols = SymbolGenerator::avoiding(&[fml]);
+    assert_eq!(var!("x1"), symbols.fresh_var("x"));
+    assert_eq!(var!("x2"), symbols.fresh_var("x"));
+    assert_eq!(nlsym!("f1", 2), symbols.fresh_func("f", 2));
+    assert_eq!(nlsym!("p1", 1), symbols.fresh_pred("p", 1));
+    symbols.avoid_name("g0");
+    assert_eq!("g1", symbols.fresh_func("g", 0).name);
+
+    // The eigenvariable of ∀R only occurs above the end sequent.
+    let fml = implies!(
+        forall!(
+            var!("x"),
+            and!(pred!("p", var!("x")), pred!("q", var!("x")))
+        ),
+        forall!(var!("x"), pred!("p", var!("x")))
+    );
+    let proof = solver::prove_with_lk(fml, 8, false).unwrap();
+    let mut eigenvars = vec![];
+    proof.replay(
+        &mut |rule: &'static str, premises: &[&proof::Sequent], _: &proof::Sequent| {
+            if rule == "ForallRight" {
+                eigenvars.extend(premises[0].suc_last().get_free_vars());
+            }
+        },
+    );
+    assert!(!eigenvars.is_empty());
+    let mut symbols = SymbolGenerator::avoiding_proof(&proof);
+    for _ in 0..3 {
+        let var = symbols.fresh_var("x");
+        assert!(!eigenvars.contains(&var));
+    }
+}
+
+#[test]
+fn hypothesis_strength_works() {
+    use language::*;
+    use strength::*;
+
+    let (p, q, r) = (pred!("p"), pred!("q"), pred!("r"));
+    let bounds = Bounds::default();
+    let pq = and!(p.clone(), q.clone());
+    assert_eq!(Strength::Stronger, compare_strength(&[], &pq, &p, bounds));
+    assert_eq!(Strength::Weaker, compare_strength(&[], &p, &pq, bounds));
+    assert_eq!(
+        Strength::Incomparable,
+        compare_strength(&[], &p, &q, bounds)
+    );
+    assert_eq!(
+        Strength::Equivalent,
+        compare_strength(&[], &pq, &and!(q.clone(), p.clone()), bounds)
+    );
+    let theory = vec![implies!(q.clone(), p.clone())];
+    assert_eq!(
+        Strength::Equivalent,
+        compare_strength(&theory, &pq, &q, bounds)
+    );
+    assert_eq!(Entailment::Refuted, entails(&theory, &p, &q, bounds));
+
+    let candidates = vec![pq.clone(), q.clone(), r.clone()];
+    assert_eq!(
+        vec![pq.clone()],
+        weakest_among(&candidates, &p, &[], bounds)
+    );
+    // p ∧ q and q are equivalent given q → p.
+    assert_eq!(
+        vec![pq, q.clone()],
+        weakest_among(&candidates, &p, &theory, bounds)
+    );
+    let theory = vec![implies!(r, q.clone())];
+    assert_eq!(
+        vec![q.clone()],
+        weakest_among(&candidates, &q, &theory, bounds)
+    );
+}
+
+#[test]
+fn visitor_works() {
+    use language::*;
+    use visit::*;
+
+    struct AtomCounter(usize);
+    impl TermVisitor for AtomCounter {}
+    impl FormulaVisitor for AtomCounter {
+        fn visit_formula(&mut self, fml: &Formula) {
+            if let Formula::Pred(_, _) | Formula::Equal(_, _) = fml {
+                self.0 += 1;
+            }
+            walk_formula(self, fml);
+        }
+    }
+    let fml = forall!(
+        var!("x"),
+        implies!(
+            pred!("p", var!("x")),
+            or!(equal!(var!("x"), func!("c")), not!(pred!("q")))
+        )
+    );
+    let mut counter = AtomCounter(0);
+    counter.visit_formula(&fml);
+    assert_eq!(3, counter.0);
+
+    // Renames the constant c to d and drops double negations.
+    struct Rewriter;
+    impl TermFolder for Rewriter {
+        fn fold_term(&mut self, term: &Term) -> Term {
+            match term {
+                Term::Func(name, terms) if name == "c" && terms.is_empty() => func!("d"),
+                _ => fold_subterms(self, term),
+            }
+        }
+    }
+    impl FormulaFolder for Rewriter {
+        fn fold_formula(&mut self, fml: &Formula) -> Formula {
+            match fml {
+                Formula::Not(inner) => match &**inner {
+                    Formula::Not(fml) => self.fold_formula(fml),
+                    _ => fold_subformulas(self, fml),
+                },
+                _ => fold_subformulas(self, fml),
+            }
+        }
+    }
+    let fml = and!(
+        not!(not!(pred!("p", func!("f", func!("c"))))),
+        not!(equal!(var!("x"), func!("c")))
+    );
+    assert_eq!(
+        and!(
+            pred!("p", func!("f", func!("d"))),
+            not!(equal!(var!("x"), func!("d")))
+        ),
+        Rewriter.fold_formula(&fml)
+    );
+
+    let fml = and!(
+        forall!(var!("x"), pred!("p", var!("x"))),
+        pred!("q", var!("x"))
+    );
+    assert_eq!(hashset![var!("x")], fml.get_free_vars());
+    assert_eq!(hashset![var!("x")], fml.get_bound_vars());
+}
+
+#[test]
+fn prelude_works() {
+    use prelude::*;
+
+    let fml: Formula = "(> (^ p q) q)".parse().unwrap();
+    let proof = LkProver::default()
+        .prove_sequent(&fml.clone().into())
+        .unwrap();
+    assert_eq!(&Sequent::from(fml), proof.as_ref());
+    assert!(proof.is_valid_inference());
+    assert_eq!(Err("Parse error."), "(^ p q) r".parse::<Formula>());
+    assert_eq!(Ok(Term::Var("x".to_string())), "x".parse());
+    let sequent: Sequent = "((p) (p))".parse().unwrap();
+    assert!(LkProver::default().prove_sequent(&sequent).is_some());
+
+    let theory = Theory::from(vec!["(> p q)".parse().unwrap()]);
+    let goal: Formula = "(> p q)".parse().unwrap();
+    assert!(LkProver::default().prove_in(&theory, &goal).is_some());
+    let goal: Formula = "(> q p)".parse().unwrap();
+    let prover = LkProver {
+        max_depth: 4,
+        use_cut: false,
+    };
+    assert!(prover.prove_in(&theory, &goal).is_none());
+}
+
+#[test]
+fn prove_async_works() {
+    use background::*;
+    use prelude::*;
+    use std::future::Future;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, Wake};
+    use std::thread::{self, Thread};
+    use std::time::{Duration, Instant};
+
+    struct Unparker(Thread);
+    impl Wake for Unparker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+    fn block_on<F: Future + Unpin>(mut future: F) -> F::Output {
+        let waker = Arc::new(Unparker(thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match std::pin::Pin::new(&mut future).poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    let goal: Sequent = "((p q) ((^ q p)))".parse().unwrap();
+    let proof = block_on(prove_async(LkProver::default(), goal.clone()));
+    assert_eq!(Some(&goal), proof.as_ref().map(|p| p.last()));
+
+    // An unprovable goal with cut takes far longer than this test.
+    let prover = LkProver {
+        max_depth: 40,
+        use_cut: true,
+    };
+    let future = prove_async(
+        prover,
+        "(> (v p q) (^ p q))".parse::<Formula>().unwrap().into(),
+    );
+    thread::sleep(Duration::from_millis(50));
+    assert!(!future.is_finished());
+    let start = Instant::now();
+    future.cancel();
+    assert_eq!(None, block_on(future));
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    struct Panicking;
+    impl Prover for Panicking {
+        fn prove_sequent(&self, _: &Sequent) -> Option<LK> {
+            panic!("prover failed")
+        }
+    }
+    assert_eq!(None, block_on(prove_async(Panicking, goal)));
+}
+
+#[test]
+fn metrics_works() {
+    use crate::language::*;
+
+    let fml: Formula = "(V x (> (P x) (E y (v (Q x y) (~ (R y))))))"
+        .parse()
+        .unwrap();
+    assert_eq!(6, fml.depth());
+    assert_eq!(8, fml.size());
+    assert_eq!(2, fml.quantifier_rank());
+    assert_eq!(3, fml.count_connectives());
+
+    let fml: Formula = "(^ (V x (P x)) (E y (P y)))".parse().unwrap();
+    assert_eq!(3, fml.depth());
+    assert_eq!(1, fml.quantifier_rank());
+    assert_eq!(1, fml.count_connectives());
+    assert_eq!(1, Formula::Top.size());
+}
+
+#[cfg(feature = "serve")]
+#[test]
+fn serve_works() {
+    use crate::serve::*;
+
+    let (status, body) = handle("POST", "/check", r#"{"input": "(> p p)"}"#);
+    assert_eq!(200, status);
+    assert_eq!(r#"{"result": "valid"}"#, body);
+
+    let (status, body) = handle(
+        "POST",
+        "/check",
+        r#"{"input": "(> p q)", "max_proof_depth": 2, "max_domain_size": 1}"#,
+    );
+    assert_eq!(200, status);
+    assert!(body.starts_with(r#"{"result": "invalid", "countermodel": {"domain_size": 1"#));
+
+    let (status, body) = handle(
+        "POST",
+        "/prove",
+        r#"{"input": "((p) (p))", "notation": "ascii"}"#,
+    );
+    assert_eq!(200, status);
+    assert!(body.starts_with(r#"{"proved": true, "proof": {"sequent": "((p) (p))", "height": 1"#));
+
+    let (status, body) = handle("POST", "/normalize", r#"{"input": "(~ (^ p q))"}"#);
+    assert_eq!(
+        (200, r#"{"formula": "(v (~ p) (~ q))"}"#),
+        (status, body.as_str())
+    );
+    let (_, body) = handle(
+        "POST",
+        "/normalize",
+        r#"{"input": "(> p (^ q r))", "form": "cnf"}"#,
+    );
+    assert_eq!(r#"{"clauses": [["(~ p)", "q"], ["(~ p)", "r"]]}"#, body);
+
+    let verify = |proof: &crate::proof::LK| {
+        let document = crate::serialize::to_json(proof).unwrap();
+        let body = format!(r#"{{"input": {}}}"#, crate::record::json_string(&document));
+        handle("POST", "/verify", &body)
+    };
+    let axiom = crate::proof::LK::Axiom("((p) (p))".parse().unwrap());
+    assert_eq!((200, r#"{"valid": true}"#.to_string()), verify(&axiom));
+    let weakening = crate::proof::LK::WeakeningLeft(
+        Box::new(crate::proof::LK::Axiom("((p) (q))".parse().unwrap())),
+        "((r p) (q))".parse().unwrap(),
+    );
+    let (status, body) = verify(&weakening);
+    assert_eq!(200, status);
+    assert!(body.starts_with(r#"{"valid": false, "path": [0], "rule": "Axiom", "reason": "#));
+    assert_eq!(400, handle("POST", "/verify", r#"{"input": "{}"}"#).0);
+    assert_eq!(400, handle("POST", "/prove", r#"{"input": "(> p"}"#).0);
+    assert_eq!(400, handle("POST", "/prove", "[1, 2").0);
+    assert_eq!(404, handle("POST", "/refute", "{}").0);
+    assert_eq!(405, handle("GET", "/check", "").0);
+    assert_eq!(
+        (400, r#"{"error": "Nesting too deep."}"#.to_string()),
+        handle("POST", "/check", &"[".repeat(1_000_000))
+    );
+    // Search bounds are lowered to the server's and the search is given up
+    // after `SEARCH_TIMEOUT`. A serial strict order has no finite model.
+    let serial = r#""(> (^ (V x (~ (r x x))) (^ (V x (V y (V z (> (^ (r x y) (r y z)) (r x z))))) (V x (E y (r x y))))) ⊥)""#;
+    let start = std::time::Instant::now();
+    let (_, body) = handle(
+        "POST",
+        "/check",
+        &format!(
+            r#"{{"input": {}, "max_proof_depth": 4000000000, "max_domain_size": 4000000000}}"#,
+            serial
+        ),
+    );
+    assert!(body.starts_with(r#"{"result": "unknown""#));
+    assert!(start.elapsed() < SEARCH_TIMEOUT + std::time::Duration::from_secs(5));
+    let nested = format!("{}{}", "[".repeat(200), "]".repeat(200));
+    assert_eq!(
+        (400, r#"{"error": "Expected a JSON object."}"#.to_string()),
+        handle("POST", "/check", &nested)
+    );
+
+    // Oversized bodies are refused without reading them, and the server
+    // keeps answering.
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || serve_on(listener));
+    let request = |request: &str| {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    };
+    for length in ["18446744073709551615", "99999999999999999999999"] {
+        let response = request(&format!(
+            "POST /check HTTP/1.1\r\nContent-Length: {}\r\n\r\n",
+            length
+        ));
+        assert!(response.starts_with("HTTP/1.1 413 "));
+    }
+    let body = r#"{"input": "(> p p)"}"#;
+    let response = request(&format!(
+        "POST /check HTTP/1.1\r\nContent-Length: {}\r\n\r\n{}",
+        body.len(),
+        body
+    ));
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.ends_with(r#"{"result": "valid"}"#));
+}
+
+#[test]
+fn iter_subformulas_works() {
+    use crate::language::*;
+
+    let fml: Formula = "(^ (V x (p x)) (~ (^ (p x) q)))".parse().unwrap();
+    let expected = [
+        "(^ (V x (p x)) (~ (^ (p x) q)))",
+        "(V x (p x))",
+        "(p x)",
+        "(~ (^ (p x) q))",
+        "(^ (p x) q)",
+        "(p x)",
+        "q",
+    ]
+    .iter()
+    .map(|s| s.parse().unwrap())
+    .collect::<Vec<Formula>>();
+    assert_eq!(
+        expected,
+        fml.iter_subformulas().cloned().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        fml.get_subformulas(),
+        fml.iter_subformulas().cloned().collect()
+    );
+
+    let term: Term = "(f (g x) y)".parse().unwrap();
+    let subterms = term
+        .iter_subterms()
+        .map(|t| t.to_sexpr())
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["(f (g x) y)", "(g x)", "x", "y"], subterms);
+}
+
+#[test]
+fn signature_works() {
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::signature::*;
+
+    let fml: Formula = "(^ (p (f x) (c)) (= (g (f x) (c)) x))".parse().unwrap();
+    let signature = fml.signature();
+    assert_eq!(
+        hashset![nlsym!("f", 1), nlsym!("g", 2)],
+        signature.functions
+    );
+    assert_eq!(hashset!["c".to_string()], signature.constants);
+    assert_eq!(hashset![nlsym!("p", 2)], signature.predicates);
+    assert_eq!(Ok(()), signature.check_consistent_arities());
+
+    let sequent: Sequent = "(((p x)) ((p (f x)) (f x y)))".parse().unwrap();
+    assert_eq!(
+        Err(vec![ArityConflict {
+            name: "f".to_string(),
+            arities: vec![1, 2],
+        }]),
+        sequent.signature().check_consistent_arities()
+    );
+
+    // Symbols of cut formulas are part of the proof's signature.
+    let p = pred!("p");
+    let q = pred!("q");
+    let proof = LK::Cut(
+        Box::new([
+            LK::WeakeningRight(
+                Box::new(LK::Axiom(sequent!(p.clone() => p.clone()))),
+                sequent!(p.clone() => p.clone(), q.clone()),
+            ),
+            LK::WeakeningLeft(
+                Box::new(LK::Axiom(sequent!(p.clone() => p.clone()))),
+                sequent!(q.clone(), p.clone() => p.clone()),
+            ),
+        ]),
+        sequent!(p.clone(), p.clone() => p.clone(), p.clone()),
+    );
+    assert_eq!(
+        hashset![nlsym!("p", 0), nlsym!("q", 0)],
+        proof.signature().predicates
+    );
+}
+
+#[test]
+fn sort_works() {
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::signature::*;
+    use crate::sort::*;
+
+    let mut signature = Signature::new();
+    signature.declare_function("zero", vec![], "Nat".into());
+    signature.declare_function("len", vec!["List".into()], "Nat".into());
+    signature.declare_function("nil", vec![], "List".into());
+    signature.declare_predicate("le", vec!["Nat".into(), "Nat".into()]);
+    let mut env = SortEnv::new(signature);
+    env.declare_variable("n", "Nat");
+    env.declare_variable("l", "List");
+
+    let term: Term = "(len l)".parse().unwrap();
+    assert_eq!(Ok(Some("Nat".to_string())), term.sort_in(&env));
+    assert_eq!(Ok(None), "(f n)".parse::<Term>().unwrap().sort_in(&env));
+    assert!("(len n)".parse::<Term>().unwrap().sort_in(&env).is_err());
+
+    let fml: Formula = "(V n (V l (le (zero) (len l))))".parse().unwrap();
+    assert_eq!(Ok(()), fml.well_sorted(&env));
+    let fml: Formula = "(le (zero) (len (nil)))".parse().unwrap();
+    assert_eq!(Ok(()), fml.well_sorted(&env));
+    let fml: Formula = "(le l n)".parse().unwrap();
+    assert!(fml.well_sorted(&env).is_err());
+    let fml: Formula = "(= n (nil))".parse().unwrap();
+    assert!(fml.well_sorted(&env).is_err());
+    // Unsorted variables and symbols are not checked.
+    let fml: Formula = "(^ (= x (nil)) (p n l))".parse().unwrap();
+    assert_eq!(Ok(()), fml.well_sorted(&env));
+
+    let sequent: Sequent = "(((le n (zero))) ((= (len l) n) (= l n)))".parse().unwrap();
+    assert!(sequent.well_sorted(&env).is_err());
+}
+
+#[test]
+fn definition_works() {
+    use crate::definition::*;
+    use crate::language::*;
+
+    let x = var!("x");
+    let y = var!("y");
+    let mut defs = Definitions::new();
+    defs.define_function("double", vec![x.clone()], "(add x x)".parse().unwrap())
+        .unwrap();
+    defs.define_predicate(
+        "even",
+        vec![x.clone()],
+        "(E y (= x (double y)))".parse().unwrap(),
+    )
+    .unwrap();
+
+    // The bound `y` of the definition is renamed apart from the argument.
+    let fml: Formula = "(V y (> (even y) (even (double y))))".parse().unwrap();
+    let expanded = fml.expand_definitions(&defs).unwrap();
+    let expected: Formula = "(V y (> (E y0 (= y (add y0 y0))) (E y0 (= (add y y) (add y0 y0)))))"
+        .parse()
+        .unwrap();
+    assert_eq!(expected, expanded);
+
+    assert!("(even x y)"
+        .parse::<Formula>()
+        .unwrap()
+        .expand_definitions(&defs)
+        .is_err());
+    assert!(defs
+        .define_predicate("odd", vec![x.clone()], "(~ (even y))".parse().unwrap())
+        .is_err());
+    assert!(defs
+        .define_predicate("odd", vec![x.clone(), x.clone()], "(p x)".parse().unwrap())
+        .is_err());
+    assert!(defs
+        .define_predicate("even", vec![y.clone()], "(p y)".parse().unwrap())
+        .is_err());
+
+    // Circular through another definition.
+    defs.define_predicate("p", vec![x.clone()], "(q x)".parse().unwrap())
+        .unwrap();
+    assert!(defs
+        .define_predicate("q", vec![x.clone()], "(p x)".parse().unwrap())
+        .is_err());
+}
+
+#[test]
+fn rewrite_in_goal_works() {
+    use crate::proof::*;
+    use crate::rewrite::*;
+    use crate::solver::*;
+
+    fn is_valid_proof(proof: &LK) -> bool {
+        proof.is_valid_inference() && proof.premises().iter().all(|p| is_valid_proof(p))
+    }
+    let prove = |s: &str| prove_sequent_with_lk(s.parse().unwrap(), 8, false).unwrap();
+
+    let theorem = Equivalence::from_iff(prove("(() ((<> (~ (~ (p x))) (p x))))")).unwrap();
+    assert_eq!(
+        Equivalence::from_implications(
+            prove("(() ((> (~ (~ (p x))) (p x))))"),
+            prove("(() ((> (p x) (~ (~ (p x))))))"),
+        )
+        .unwrap()
+        .rhs(),
+        theorem.rhs()
+    );
+
+    let cases = vec![
+        (
+            "(((V x (> (~ (~ (p x))) q)) (p a)) (q))",
+            Side::Antecedent,
+            0,
+            vec![0, 0],
+        ),
+        (
+            "(() ((<> (E x (~ (~ (p x)))) (E x (p x)))))",
+            Side::Succedent,
+            0,
+            vec![0, 0],
+        ),
+        (
+            "(((^ r (~ (~ (~ (p x)))))) ((~ (p x))))",
+            Side::Antecedent,
+            0,
+            vec![1, 0],
+        ),
+        (
+            "(() (r (v (<> q (~ (~ (p x)))) (~ (<> q (p x))))))",
+            Side::Succedent,
+            1,
+            vec![0, 1],
+        ),
+    ];
+    for (goal, side, index, path) in cases {
+        let goal: Sequent = goal.parse().unwrap();
+        let position = Position { side, index, path };
+        let rewrite = rewrite_in_goal(&goal, &theorem, &position).unwrap();
+        assert!(!rewrite.subgoal.to_sexpr().contains("(~ (~ (p x)))"));
+        let subproof = prove_sequent_with_lk(rewrite.subgoal.clone(), 8, false).unwrap();
+        let proof = rewrite.justify(subproof).unwrap();
+        assert_eq!(&goal, proof.last());
+        assert!(is_valid_proof(&proof));
+    }
+
+    let goal: Sequent = "(((p x)) ((p x)))".parse().unwrap();
+    let position = Position {
+        side: Side::Succedent,
+        index: 0,
+        path: vec![],
+    };
+    assert!(rewrite_in_goal(&goal, &theorem, &position).is_err());
+    let rewrite = rewrite_in_goal(&goal, &theorem.reversed(), &position).unwrap();
+    assert_eq!(
+        "(((p x)) ((~ (~ (p x)))))".parse::<Sequent>().unwrap(),
+        rewrite.subgoal
+    );
+}
+
+#[test]
+fn generalize_works() {
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::solver::*;
+
+    fn is_valid_proof(proof: &LK) -> bool {
+        proof.is_valid_inference() && proof.premises().iter().all(|p| is_valid_proof(p))
+    }
+
+    // `r` is unrelated to the goal, `(s a)` is relevant through `(q (f a))`.
+    let goal: Sequent = "((r (> (s a) (q (f a))) (s a)) ((v (q (f a)) (= (g (f a)) (g (f a))))))"
+        .parse()
+        .unwrap();
+    let generalization = goal.generalize();
+    assert_eq!(vec![pred!("r")], generalization.dropped);
+    assert_eq!(
+        vec![
+            (var!("z0"), func!("g", func!("f", var!("a")))),
+            (var!("z1"), func!("f", var!("a")))
+        ],
+        generalization.replaced
+    );
+    assert_eq!(
+        "(((> (s a) (q z1)) (s a)) ((v (q z1) (= z0 z0))))"
+            .parse::<Sequent>()
+            .unwrap(),
+        generalization.generalized
+    );
+
+    let proof = prove_sequent_with_lk(generalization.generalized.clone(), 8, false).unwrap();
+    let proof = generalization.instantiate(&proof).unwrap();
+    assert_eq!(&goal, proof.last());
+    assert!(is_valid_proof(&proof));
+
+    // Terms with bound variables are kept.
+    let goal: Sequent = "(() ((V x (= (f x) (f x)))))".parse().unwrap();
+    assert_eq!(goal, goal.generalize().generalized);
+}
+
+#[test]
+fn classify_works() {
+    use crate::classify::Fragment::*;
+    use crate::language::*;
+
+    let classify = |s: &str| s.parse::<Formula>().unwrap().classify();
+    assert_eq!(
+        vec![
+            Ground,
+            Propositional,
+            QuantifierFree,
+            Universal,
+            Existential,
+            EffectivelyPropositional,
+            Monadic,
+            TwoVariable,
+            EqualityFree,
+            FunctionFree,
+            Horn,
+        ],
+        classify("(> (^ p q) r)")
+    );
+    assert_eq!(
+        vec![
+            Universal,
+            EffectivelyPropositional,
+            Monadic,
+            TwoVariable,
+            EqualityFree,
+            FunctionFree,
+            Horn,
+        ],
+        classify("(~ (E x (^ (p x) (~ (q x)))))")
+    );
+    assert_eq!(
+        vec![EffectivelyPropositional, TwoVariable, FunctionFree, Horn],
+        classify("(E x (V y (> (r x y) (= x y))))")
+    );
+    assert_eq!(
+        vec![TwoVariable, EqualityFree, FunctionFree],
+        classify("(V x (E y (v (r x y) (r y x))))")
+    );
+    assert_eq!(
+        vec![Universal, EqualityFree],
+        classify("(V x (V y (V z (v (p (f x)) (r y z)))))")
+    );
+    // Under `↔` a quantifier is both universal and existential.
+    assert!(!classify("(<> (V x (p x)) q)").contains(&Universal));
+}
+
+#[test]
+fn datatype_works() {
+    use crate::datatype::*;
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::signature::*;
+    use crate::solver::*;
+    use crate::theory::*;
+
+    fn is_valid_proof(proof: &LK) -> bool {
+        proof.is_valid_inference() && proof.premises().iter().all(|p| is_valid_proof(p))
+    }
+
+    let mut list = Datatype::new("List");
+    list.add_constructor("nil", vec![]);
+    list.add_constructor("cons", vec!["Elem".into(), "List".into()]);
+
+    let mut signature = Signature::new();
+    list.declare_in(&mut signature);
+    assert_eq!(hashset!["nil".to_string()], signature.constants);
+    assert_eq!(hashset![nlsym!("cons", 2)], signature.functions);
+
+    let parse = |s: &str| s.parse::<Formula>().unwrap();
+    assert_eq!(
+        vec![parse("(V y0 (V y1 (~ (= (nil) (cons y0 y1)))))")],
+        list.distinctness_axioms()
+    );
+    assert_eq!(
+        vec![parse(
+            "(V x0 (V x1 (V y0 (V y1 (> (= (cons x0 x1) (cons y0 y1)) (^ (= x0 y0) (= x1 y1)))))))"
+        )],
+        list.injectivity_axioms()
+    );
+    let mut theory = Theory::new();
+    theory.add_datatype(&list);
+    assert_eq!(2, theory.axioms.len());
+
+    assert_eq!(
+        parse("(> (^ (p (nil)) (V x0 (V x1 (> (p x1) (p (cons x0 x1)))))) (V l (p l)))"),
+        list.induction_axiom(&var!("l"), &parse("(p l)"))
+    );
+
+    let goal: Sequent = "(((p (nil)) (V h (V t (> (p t) (p (cons h t)))))) ((V l (p l))))"
+        .parse()
+        .unwrap();
+    let induction = list.induction(&goal).unwrap();
+    assert_eq!(2, induction.subgoals.len());
+    let proofs = induction
+        .subgoals
+        .iter()
+        .map(|s| prove_sequent_with_lk(s.clone(), 8, false).unwrap())
+        .collect();
+    let proof = induction.justify(proofs).unwrap();
+    assert_eq!(&induction.axiom, proof.last().ant_first());
+    assert_eq!(goal.antecedent, proof.last().ant_but_first());
+    assert_eq!(goal.succedent, proof.last().succedent);
+    assert!(is_valid_proof(&proof));
+
+    assert!(list.induction(&"(() ((p l)))".parse().unwrap()).is_err());
+}
+
+#[test]
+fn dependency_works() {
+    use crate::datatype::*;
+    use crate::definition::*;
+    use crate::dependency::*;
+    use crate::language::*;
+
+    let parse = |s: &str| s.parse::<Formula>().unwrap();
+    let mut defs = Definitions::new();
+    defs.define_function(
+        "double",
+        vec![var!("x")],
+        func!("plus", var!("x"), var!("x")),
+    )
+    .unwrap();
+    defs.define_predicate("even", vec![var!("x")], parse("(E y (= x (double y)))"))
+        .unwrap();
+    defs.define_predicate("odd", vec![var!("x")], parse("(~ (even x))"))
+        .unwrap();
+    let graph = defs.dependency_graph();
+    assert_eq!(
+        hashset!["double".to_string(), "plus".to_string()],
+        graph.transitive_dependencies("even").into_iter().collect()
+    );
+    assert!(graph.cycles().is_empty());
+    assert_eq!(
+        Ok(vec![
+            "plus".to_string(),
+            "double".to_string(),
+            "even".to_string(),
+            "odd".to_string()
+        ]),
+        graph.topological_order()
+    );
+    assert!(graph
+        .to_json()
+        .contains("{\"source\": \"odd\", \"target\": \"even\"}"));
+
+    let mut tree = Datatype::new("Tree");
+    tree.add_constructor("leaf", vec![]);
+    tree.add_constructor("node", vec!["Forest".into()]);
+    let mut forest = Datatype::new("Forest");
+    forest.add_constructor("empty", vec![]);
+    forest.add_constructor("cons", vec!["Tree".into(), "Forest".into()]);
+    let mut nat = Datatype::new("Nat");
+    nat.add_constructor("zero", vec![]);
+    let graph = Datatype::dependency_graph(&[forest, tree, nat]);
+    let cycles = graph.cycles();
+    assert_eq!(1, cycles.len());
+    assert_eq!("cyclic dependency Forest -> Forest", cycles[0].to_string());
+    assert_eq!(Err(cycles), graph.topological_order());
+
+    let mut graph = DependencyGraph::new();
+    graph.add("p", hashset!["q".to_string()].into_iter().collect());
+    graph.add("q", hashset!["p".to_string()].into_iter().collect());
+    assert_eq!(
+        "cyclic dependency p -> q -> p",
+        graph.cycles()[0].to_string()
+    );
+}
+
+#[test]
+fn rename_works() {
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::solver::*;
+
+    fn is_valid_proof(proof: &LK) -> bool {
+        proof.is_valid_inference() && proof.premises().iter().all(|p| is_valid_proof(p))
+    }
+
+    let parse = |s: &str| s.parse::<Formula>().unwrap();
+    let renaming = hashmap![
+        "p".to_string() => "q".to_string(),
+        "f".to_string() => "g".to_string(),
+        "x".to_string() => "y".to_string()
+    ];
+    assert_eq!(
+        Ok(parse("(V y (> (q (g y)) (q z)))")),
+        parse("(V x (> (p (f x)) (p z)))").rename_symbols(&renaming)
+    );
+    assert!(parse("(^ (p x) (q x))").rename_symbols(&renaming).is_err());
+    assert!(parse("(V x (p y))").rename_symbols(&renaming).is_err());
+    // Predicates and functions are renamed independently.
+    assert!(parse("(q (p))").rename_symbols(&renaming).is_ok());
+
+    let fml = parse("(> (V x (p x)) (E x (p x)))");
+    let proof = prove_with_lk(fml.clone(), 8, false).unwrap();
+    let renamed = proof.rename_symbols(&renaming).unwrap();
+    assert!(is_valid_proof(&renamed));
+    assert_eq!(
+        &fml.rename_symbols(&renaming).unwrap(),
+        renamed.last().suc_last()
+    );
+}
+
+#[test]
+fn provenance_works() {
+    use crate::cnf::*;
+    use crate::fresh::*;
+    use crate::language::*;
+
+    let parse = |s: &str| s.parse::<Formula>().unwrap();
+    let fml = parse("(~ (> (E x (p x)) (^ (q) (= (a) (b)))))");
+    let (nnf, provenance) = fml.to_nnf_with_provenance();
+    assert_eq!(fml.to_nnf(), nnf);
+    assert_eq!(parse("(^ (E x (p x)) (v (~ (q)) (~ (= (a) (b)))))"), nnf);
+    assert_eq!(Some(&[0, 0, 0][..]), provenance.origin(&[0, 0]));
+    assert_eq!(Some(&[0, 1, 1][..]), provenance.origin(&[1, 1, 0]));
+    assert_eq!(Some(&[0, 1][..]), provenance.origin(&[1]));
+    assert_eq!(Some(&[0][..]), provenance.origin(&[]));
+
+    let (skolemized, provenance) = nnf.skolemize_with_provenance(&mut SymbolGenerator::new());
+    assert_eq!(nnf.skolemize(&mut SymbolGenerator::new()), skolemized);
+    assert_eq!(Some(&[0, 0][..]), provenance.origin(&[0]));
+
+    for conversion in [CnfConversion::Distributive, CnfConversion::Tseitin].iter() {
+        let (clauses, provenances) =
+            fml.to_cnf_with_provenance(*conversion, &mut SymbolGenerator::new());
+        assert_eq!(
+            fml.to_cnf_with(*conversion, &mut SymbolGenerator::new()),
+            clauses
+        );
+        assert_eq!(clauses.len(), provenances.len());
+        let negated_eq = Literal {
+            atom: parse("(= (a) (b))"),
+            positive: false,
+        };
+        assert!(provenances
+            .iter()
+            .any(|p| p.get(&negated_eq) == Some(&vec![0, 1, 1])));
+    }
+}
+
+#[test]
+fn intern_works() {
+    use crate::intern::*;
+    use crate::language::*;
+
+    let parse = |s: &str| s.parse::<Formula>().unwrap();
+    let mut interner = Interner::new();
+    let fml = parse("(^ (V y (p x y)) (v (q (f x)) (V y (p x y))))");
+    let id = interner.intern(&fml);
+    assert_eq!(id, interner.intern(&fml.clone()));
+    assert_eq!(fml, interner.formula(id));
+    assert_eq!(fml.get_subformulas().len(), interner.subformulas(id).len());
+    let lhs = match interner.node(id) {
+        FormulaNode::And(lhs, rhs) => match interner.node(*rhs) {
+            FormulaNode::Or(_, shared) => {
+                assert_eq!(lhs, shared);
+                *lhs
+            }
+            _ => panic!(),
+        },
+        _ => panic!(),
+    };
+    let x = interner.intern_term(&var!("x"));
+    assert_eq!(&[x][..], interner.free_vars(lhs));
+
+    let a = interner.intern_term(&func!("a"));
+    let substituted = interner.substitute(id, x, a);
+    assert_eq!(
+        fml.substitute(var!("x"), func!("a")),
+        interner.formula(substituted)
+    );
+    let p = interner.intern(&parse("(p (a))"));
+    assert_eq!(p, interner.substitute(p, x, a));
+
+    let y = interner.intern_term(&var!("y"));
+    let captured = interner.substitute(lhs, x, y);
+    assert_eq!(parse("(V y0 (p y y0))"), interner.formula(captured));
+}
+
+#[test]
+fn arena_works() {
+    use crate::arena::*;
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::solver::*;
+
+    fn size(proof: &LK) -> usize {
+        1 + proof.premises().iter().map(|p| size(p)).sum::<usize>()
+    }
+
+    let fml: Formula = "(> (^ (p) (q)) (v (q) (r)))".parse().unwrap();
+    let proof = prove_with_lk(fml, 8, false).unwrap();
+    let (arena, root) = ProofArena::from_lk(&proof);
+    assert_eq!(size(&proof), arena.len());
+    assert_eq!(None, arena.invalid_node());
+    assert_eq!(proof, arena.to_lk(root));
+
+    let mut arena = ProofArena::new();
+    let axiom = arena
+        .add("Axiom", vec![], sequent!(pred!("p") => pred!("p")))
+        .unwrap();
+    let weakened = arena
+        .add(
+            "WeakeningLeft",
+            vec![axiom],
+            sequent!(pred!("q"), pred!("p") => pred!("p")),
+        )
+        .unwrap();
+    assert!(arena
+        .add("Cut", vec![axiom], sequent!(pred!("p") => pred!("p")))
+        .is_err());
+    assert!(arena
+        .add("Foo", vec![axiom], sequent!(pred!("p") => pred!("p")))
+        .is_err());
+    assert_eq!(None, arena.invalid_node());
+
+    // Deep proofs are checked without recursion.
+    let mut id = weakened;
+    for i in 0..100_000 {
+        let sequent = if i % 2 == 0 {
+            sequent!(pred!("p"), pred!("q") => pred!("p"))
+        } else {
+            sequent!(pred!("q"), pred!("p") => pred!("p"))
+        };
+        id = arena.add("ExchangeLeft", vec![id], sequent).unwrap();
+    }
+    assert_eq!(100_002, arena.len());
+    assert_eq!(None, arena.invalid_node());
+    let bad = arena
+        .add("NotRight", vec![id], sequent!(pred!("q") => pred!("p")))
+        .unwrap();
+    assert_eq!(Some(bad), arena.invalid_node());
+}
+
+#[test]
+fn ground_works() {
+    use crate::cnf::*;
+    use crate::language::*;
+    use crate::theory::*;
+
+    let parse = |s: &str| s.parse::<Formula>().unwrap();
+    let mut theory = Theory::new();
+    theory.add_named_axiom("base", parse("(p (zero))"));
+    theory.add_named_axiom("unrelated", parse("(V x (q x))"));
+    theory.add_named_axiom("step", parse("(V x (> (p x) (p (s x))))"));
+    theory.add_axiom(parse("(~ (p (s (s (zero)))))"));
+
+    assert!(theory.refute_by_grounding(0).is_none());
+    let report = theory.refute_by_grounding(1).unwrap();
+    assert_eq!(vec![0, 2, 3], report.axioms);
+    assert_eq!(vec!["base", "step", "axiom 3"], report.names);
+    assert_eq!(4, report.instances.len());
+    let steps = report
+        .instances
+        .iter()
+        .filter(|i| i.axiom == 2)
+        .map(|i| i.instantiation[0].1.to_string())
+        .collect::<std::collections::HashSet<_>>();
+    assert_eq!(hashset!["zero".to_string(), "s(zero)".to_string()], steps);
+    let step = report.instances.iter().find(|i| i.axiom == 2).unwrap();
+    let negated = Literal {
+        atom: parse("(p x)"),
+        positive: false,
+    };
+    assert_eq!(Some(&vec![0, 0]), step.origins.get(&negated));
+    assert!(report
+        .to_string()
+        .starts_with("inconsistent axioms: base, step, axiom 3\n"));
+
+    theory.axioms.pop();
+    assert!(theory.refute_by_grounding(3).is_none());
+    theory.add_named_axiom("absurd", Formula::Bottom);
+    assert_eq!(vec!["absurd"], theory.refute_by_grounding(0).unwrap().names);
+}
+
+#[test]
+fn explain_node_works() {
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::solver::*;
+
+    let fml = "(> (V x (P x)) (P (a)))".parse::<Formula>().unwrap();
+    let (result, trace) = prove_with_lk_traced(fml, 5, false, &Unguided);
+    assert!(result.is_ok());
+    let goal = "(((P (a))) ((P (a))))".parse::<Sequent>().unwrap();
+    let id = trace.steps.iter().position(|s| s.state == goal).unwrap();
+    let chain = trace.explain_node(id).unwrap();
+    let rules = chain.iter().map(|e| e.rule.unwrap()).collect::<Vec<_>>();
+    assert_eq!(vec!["WeakeningRight", "ImpliesRight", "ForallLeft"], rules);
+    assert_eq!(Some(func!("a")), chain[2].instantiation);
+    assert_eq!(goal, chain[2].subgoal);
+    assert!(chain[2].to_string().contains("--ForallLeft with a-->"));
+    assert_eq!(None, trace.explain_node(trace.steps.len()));
+
+    let fml = "(> (^ (P x) (Q x)) (^ (Q x) (P x)))"
+        .parse::<Formula>()
+        .unwrap();
+    let (result, trace) = prove_with_lk_traced(fml, 6, false, &Unguided);
+    assert!(result.is_ok());
+    let right = (0..trace.steps.len())
+        .filter_map(|id| trace.explain_node(id))
+        .find_map(|chain| chain.into_iter().find(|e| e.premise == 1))
+        .unwrap();
+    assert_eq!(Some("AndRight"), right.rule);
+}
+
+#[test]
+fn is_valid_proof_works() {
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::solver::*;
+
+    let fml: Formula = "(> (^ (p) (q)) (v (q) (r)))".parse().unwrap();
+    let proof = prove_with_lk(fml, 8, false).unwrap();
+    assert!(proof.is_valid_proof());
+    let broken = LK::WeakeningLeft(
+        Box::new(LK::Axiom(sequent!(pred!("p") => pred!("q")))),
+        sequent!(pred!("r"), pred!("p") => pred!("q")),
+    );
+    assert!(broken.is_valid_inference() && !broken.is_valid_proof());
+
+    // Deep proofs are checked, rendered and dropped without recursion.
+    let deep = |depth: usize| {
+        let mut proof = LK::WeakeningLeft(
+            Box::new(LK::Axiom(sequent!(pred!("p") => pred!("p")))),
+            sequent!(pred!("q"), pred!("p") => pred!("p")),
+        );
+        for i in 0..depth {
+            let sequent = if i % 2 == 0 {
+                sequent!(pred!("p"), pred!("q") => pred!("p"))
+            } else {
+                sequent!(pred!("q"), pred!("p") => pred!("p"))
+            };
+            proof = LK::ExchangeLeft(Box::new(proof), sequent);
+        }
+        proof
+    };
+    let proof = deep(100_000);
+    assert!(proof.is_valid_proof());
+    let proof = deep(1_000);
+    assert_eq!(2_003, proof.to_string().lines().count());
+}
+
+#[test]
+fn iter_subterms_works() {
+    use crate::language::*;
+
+    let fml: Formula = "(^ (V x (p (f (g x)))) (~ (= y (f y))))".parse().unwrap();
+    let subterms = |iter: crate::visit::Subterms| iter.map(|t| t.to_sexpr()).collect::<Vec<_>>();
+    assert_eq!(
+        vec!["(f (g x))", "(g x)", "x", "y", "(f y)", "y"],
+        subterms(fml.iter_subterms())
+    );
+    assert_eq!(
+        vec!["(f (g x))", "(g x)", "y", "(f y)", "y"],
+        subterms(fml.iter_subterms().max_depth(2))
+    );
+    assert_eq!(fml.get_subterms(), fml.iter_subterms().cloned().collect());
+    let term: Term = "(f (g x) y)".parse().unwrap();
+    assert_eq!(
+        vec!["(f (g x) y)"],
+        subterms(term.iter_subterms().max_depth(0))
+    );
+
+    let depths = (0..4)
+        .map(|depth| fml.iter_subformulas().max_depth(depth).count())
+        .collect::<Vec<_>>();
+    assert_eq!(vec![1, 3, 5, 5], depths);
+}
+
+#[test]
+fn check_works() {
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::solver::*;
+
+    let fml: Formula = "(> (V x (P x)) (E y (P y)))".parse().unwrap();
+    assert_eq!(Ok(()), prove_with_lk(fml, 8, false).unwrap().check());
+
+    let px = pred!("P", var!("x"));
+    let all = Formula::Forall(var!("x"), Box::new(px.clone()));
+    let proof = LK::WeakeningLeft(
+        Box::new(LK::ForallRight(
+            Box::new(LK::Axiom(sequent!(px.clone() => px.clone()))),
+            sequent!(px.clone() => all.clone()),
+        )),
+        sequent!(pred!("q"), px.clone() => all.clone()),
+    );
+    let error = proof.check().unwrap_err();
+    assert_eq!(vec![0], error.path);
+    assert_eq!("ForallRight", error.rule);
+    assert_eq!(vec![sequent!(px.clone() => px.clone())], error.premises);
+    assert_eq!("eigenvariable occurs free in context", error.reason);
+    assert!(error
+        .to_string()
+        .starts_with("invalid ForallRight at [0]: eigenvariable occurs free in context\n"));
+
+    let reason = |proof: LK| proof.check().unwrap_err().reason;
+    let axiom = LK::Axiom(sequent!(px.clone() => px.clone()));
+    assert_eq!(
+        "principal formula has the wrong main connective",
+        reason(LK::AndLeft1(
+            Box::new(axiom.clone()),
+            sequent!(px.clone() => px.clone())
+        ))
+    );
+    assert_eq!(
+        "premise formulas are not the subformulas of the principal formula",
+        reason(LK::OrRight2(
+            Box::new(axiom.clone()),
+            sequent!(px.clone() => Formula::Or(Box::new(px.clone()), Box::new(pred!("q"))))
+        ))
+    );
+    assert_eq!(
+        "sequent too short for the rule",
+        reason(LK::NotRight(
+            Box::new(LK::Axiom(sequent!( => pred!("q")))),
+            sequent!( => Formula::Not(Box::new(pred!("q"))))
+        ))
+    );
+    assert_eq!(
+        "premise formula is not an instance of the quantified formula",
+        reason(LK::ForallLeft(
+            Box::new(LK::Axiom(sequent!(pred!("q") => pred!("q")))),
+            sequent!(all.clone() => pred!("q"))
+        ))
+    );
+    assert_eq!(
+        "not an axiom A ⇒ A, ⇒ t = t, ⇒ ⊤ or ⊥ ⇒",
+        reason(LK::Axiom(sequent!(px.clone() => pred!("q"))))
+    );
+}
+
+#[test]
+fn cached_formula_works() {
+    use crate::cache::*;
+    use crate::language::*;
+
+    let fml: Formula = "(^ (V x (> (P x y) (E y (Q y)))) (~ (= (f z) z)))"
+        .parse()
+        .unwrap();
+    let cached = CachedFormula::new(fml.clone());
+    assert_eq!(fml.iter_subformulas().count(), cached.subformulas().count());
+    for (sub, info) in cached.subformulas() {
+        assert_eq!(sub.get_free_vars(), info.free_vars);
+        assert_eq!(sub.get_bound_vars(), info.bound_vars);
+        assert_eq!(sub.size(), info.size);
+        assert_eq!(CachedFormula::new(sub.clone()).info(), info);
+    }
+    assert_eq!(&hashset![var!("y"), var!("z")], cached.free_vars());
+    assert_eq!(8, cached.size());
+
+    let other: CachedFormula = "(^ (V x (> (P x y) (E y (Q y)))) (~ (= (f z) z)))"
+        .parse::<Formula>()
+        .unwrap()
+        .into();
+    let renamed = CachedFormula::new("(V y (P y))".parse().unwrap());
+    assert_eq!(cached, other);
+    assert_ne!(cached.info().hash, renamed.info().hash);
+    assert_eq!(2, hashset![cached, other, renamed].len());
+}
+
+#[test]
+fn proof_builder_works() {
+    use crate::builder::*;
+    use crate::language::*;
+    use crate::proof::*;
+
+    let pa = pred!("P", func!("a"));
+    let px = pred!("P", var!("x"));
+    let all = Formula::Forall(var!("x"), Box::new(px.clone()));
+    let goal = Formula::Implies(Box::new(all.clone()), Box::new(pa.clone()));
+
+    let mut builder = ProofBuilder::new();
+    let axiom = builder.axiom(sequent!(pa.clone() => pa.clone())).unwrap();
+    let error = builder
+        .forall_left(axiom, &func!("b"), sequent!(all.clone() => pa.clone()))
+        .unwrap_err();
+    match error {
+        BuildError::Invalid(error) => assert_eq!(
+            "premise formula is not the instance for the given term",
+            error.reason
+        ),
+        error => panic!("unexpected {:?}", error),
+    }
+    let instance = builder
+        .forall_left(axiom, &func!("a"), sequent!(all.clone() => pa.clone()))
+        .unwrap();
+    let root = builder
+        .implies_right(instance, sequent!( => goal.clone()))
+        .unwrap();
+    let proof = builder.build(root);
+    assert!(proof.is_valid_proof());
+    assert_eq!(&sequent!( => goal), proof.last());
+
+    assert_eq!(
+        Err(BuildError::UnknownRule),
+        builder.infer("Cut", &[axiom], sequent!(pa.clone() => pa.clone()))
+    );
+    let invalid = builder.and_right(axiom, axiom, sequent!(pa.clone() => pa.clone()));
+    assert!(
+        matches!(invalid, Err(BuildError::Invalid(ref error)) if error.rule == "AndRight"),
+        "{:?}",
+        invalid
+    );
+    let axiom = builder.axiom(sequent!(px.clone() => px.clone())).unwrap();
+    let captured = builder.forall_right(axiom, &var!("x"), sequent!(px.clone() => all.clone()));
+    assert_eq!(
+        "eigenvariable occurs free in context",
+        match captured {
+            Err(BuildError::Invalid(error)) => error.reason,
+            _ => "",
+        }
+    );
+    assert_eq!(
+        Err(BuildError::UnknownPremise(root)),
+        ProofBuilder::new().not_right(root, sequent!( => pa))
+    );
+}
+
+#[test]
+fn proof_state_works() {
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::tactic::*;
+
+    let parse = |s: &str| s.parse::<Formula>().unwrap();
+    let goal = parse("(> (V x (^ (P x) (Q x))) (V y (^ (Q y) (P y))))");
+    let mut state = ProofState::new(sequent!( => goal.clone()));
+    assert_eq!(Err("Goals remain open."), state.qed());
+    state.apply_implies_right().unwrap();
+    assert_eq!(
+        Err("Rule does not apply to the goal."),
+        state.apply_and_right()
+    );
+    state.apply_forall_right(var!("z")).unwrap();
+    state.apply_and_right().unwrap();
+    assert_eq!(2, state.goals().len());
+    state.defer();
+    assert_eq!(
+        Some(&sequent!(parse("(V x (^ (P x) (Q x)))") => parse("(P z)"))),
+        state.current()
+    );
+    state.apply_forall_left(var!("z")).unwrap();
+    state.apply_and_left1().unwrap();
+    state.axiom().unwrap();
+    state.apply_forall_left(var!("z")).unwrap();
+    assert_eq!(Err("Goal is not an axiom."), state.axiom());
+    state.apply_and_left2().unwrap();
+    state.axiom().unwrap();
+    assert!(state.is_complete());
+    assert_eq!(Err("No open goals."), state.weaken_right());
+
+    let proof = state.qed().unwrap();
+    assert!(proof.is_valid_proof());
+    assert_eq!(&sequent!( => goal), proof.last());
+
+    let mut state = ProofState::new(sequent!(parse("(> p q)"), parse("p") => parse("q")));
+    state.apply_implies_left(1, 0).unwrap();
+    state.axiom().unwrap();
+    state.axiom().unwrap();
+    assert!(state.qed().unwrap().is_valid_proof());
+
+    let mut state = ProofState::new(sequent!(parse("(P x)") => parse("(V y (P y))")));
+    assert_eq!(
+        Err("Eigenvariable occurs free in the goal."),
+        state.apply_forall_right(var!("x"))
+    );
+}
+
+#[test]
+fn check_with_works() {
+    use crate::check::*;
+    use crate::language::*;
+    use crate::proof::*;
+
+    let x = var!("x");
+    let fa = func!("f", func!("a"));
+    let pfa = pred!("P", fa.clone());
+    let all = Formula::Forall(x.clone(), Box::new(pred!("P", x)));
+    let proof = LK::ForallLeft(
+        Box::new(LK::Axiom(sequent!(pfa.clone() => pfa.clone()))),
+        sequent!(all => pfa),
+    );
+    assert_eq!(Ok(()), proof.check_with(&CheckOptions::default()));
+
+    let mut options = CheckOptions {
+        max_candidates: Some(1),
+        ..CheckOptions::default()
+    };
+    let error = proof.check_with(&options).unwrap_err();
+    assert_eq!(
+        "too many candidate instances to check without an annotation",
+        error.reason
+    );
+
+    options.instantiations.insert(vec![], fa);
+    assert_eq!(Ok(()), proof.check_with(&options));
+
+    options.instantiations.insert(vec![], func!("a"));
+    let error = proof.check_with(&options).unwrap_err();
+    assert_eq!(vec![] as Vec<usize>, error.path);
+    assert_eq!(
+        "premise formula is not the instance for the annotated term",
+        error.reason
+    );
+}
+
+#[test]
+fn from_script_works() {
+    use crate::proof::*;
+    use crate::script::*;
+
+    let script = "
+ImpliesLeft (((> p q) p) (q))
+  Axiom ((p) (p))
+  Axiom ((q) (q))
+";
+    let proof = LK::from_script(script).unwrap();
+    assert_eq!("ImpliesLeft", proof.rule_name());
+    assert_eq!(2, proof.premises().len());
+    assert_eq!(Ok(()), proof.check());
+
+    let script = "
+ImpliesRight (() ((> (V x (P x)) (E y (P y)))))
+    ExistsRight (((V x (P x))) ((E y (P y))))
+        ForallLeft (((V x (P x))) ((P x)))
+            Axiom (((P x)) ((P x)))
+";
+    assert!(LK::from_script(script).unwrap().is_valid_proof());
+
+    let error = |script| LK::from_script(script).unwrap_err();
+    assert_eq!(
+        ScriptError::Syntax(2, "expected a rule name and a sequent"),
+        error("Axiom ((p) (p))\n  Axiom")
+    );
+    assert_eq!(
+        ScriptError::Syntax(3, "inconsistent indentation"),
+        error("AndRight (() ((^ p p)))\n    Axiom ((p) (p))\n  Axiom ((p) (p))")
+    );
+    assert_eq!(
+        ScriptError::Syntax(2, "more than one end sequent"),
+        error("Axiom ((p) (p))\nAxiom ((q) (q))")
+    );
+    assert_eq!(
+        ScriptError::Syntax(1, "unknown rule or wrong number of premises"),
+        error("AndRight (() ((^ p p)))\n  Axiom ((p) (p))")
+    );
+    match error("OrRight1 ((p) ((v q p)))\n  Axiom ((p) (p))") {
+        ScriptError::Invalid(1, error) => assert_eq!("OrRight1", error.rule),
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn limits_works() {
+    use crate::check::*;
+    use crate::language::*;
+    use crate::limits::*;
+    use crate::parser::*;
+    use crate::proof::*;
+    use crate::script::*;
+    use crate::tokenizer::*;
+
+    let limits = Limits {
+        max_tokens: Some(8),
+        max_depth: Some(3),
+        max_proof_nodes: Some(2),
+    };
+    assert_eq!(Ok(pred!("p", var!("x"))), limits.parse_formula("(p   x)"));
+    assert_eq!(
+        Err(TOO_MANY_TOKENS),
+        limits.parse_formula("(^ (p x) (q x y))")
+    );
+    assert_eq!(
+        Err(TOO_MANY_TOKENS),
+        Tokenizer::with_limits(&limits).try_tokenize("a b c d e f g h i")
+    );
+    // `tokenize` does not truncate.
+    assert_eq!(
+        9,
+        Tokenizer::with_limits(&limits)
+            .tokenize("a b c d e f g h i")
+            .len()
+    );
+    let shallow = Limits {
+        max_depth: Some(3),
+        ..Limits::default()
+    };
+    assert_eq!(
+        Ok(not!(not!(pred!("p")))),
+        shallow.parse_formula("(~ (~ p))")
+    );
+    assert_eq!(Err(TOO_DEEP), shallow.parse_formula("(~ (~ (~ p)))"));
+    assert_eq!(
+        Ok(sequent!(pred!("p") => pred!("p"))),
+        limits.parse_sequent("((p) (p))")
+    );
+    assert_eq!(
+        Err(TOO_DEEP),
+        Limits::untrusted().parse_formula(&format!("{}p{}", "(~ ".repeat(1000), ")".repeat(1000)))
+    );
+    let deep = format!("(p {}{})", "(f ".repeat(100), ")".repeat(100));
+    let tokens = Tokenizer::new().tokenize(&deep);
+    assert!(Parser::new().parse(&tokens).is_ok());
+
+    let script = "ImpliesRight (() ((> p p)))\n  Axiom ((p) (p))";
+    let single = Limits {
+        max_proof_nodes: Some(1),
+        ..Limits::default()
+    };
+    assert_eq!(
+        ScriptError::Syntax(2, TOO_MANY_NODES),
+        LK::from_script_with(script, &single).unwrap_err()
+    );
+    assert!(LK::from_script_with(script, &Limits::untrusted()).is_ok());
+    let proof = LK::WeakeningRight(
+        Box::new(LK::Axiom(sequent!(pred!("p") => pred!("p")))),
+        sequent!(pred!("p") => pred!("p"), pred!("q")),
+    );
+    let options = CheckOptions {
+        max_nodes: Some(1),
+        ..CheckOptions::default()
+    };
+    assert_eq!(
+        "proof has more inferences than the limit",
+        proof.check_with(&options).unwrap_err().reason
+    );
+    assert_eq!(Ok(()), proof.check());
+}
+
+#[test]
+fn adapt_works() {
+    use crate::adapt::*;
+    use crate::language::*;
+    use crate::proof::*;
+
+    let p = pred!("p");
+    let q = pred!("q");
+    let r = pred!("r");
+    let proof = LK::WeakeningLeft(
+        Box::new(LK::WeakeningRight(
+            Box::new(LK::Axiom(sequent!(p.clone() => p.clone()))),
+            sequent!(p.clone() => p.clone(), q.clone()),
+        )),
+        sequent!(p.clone(), p.clone() => p.clone(), q.clone()),
+    );
+    let target = sequent!(r.clone(), p.clone() => q.clone(), r.clone(), p.clone());
+    let adapted = adapt(proof.clone(), &target).unwrap();
+    assert_eq!(&target, adapted.last());
+    assert!(adapted.is_valid_proof());
+    assert_eq!(Ok(()), adapted.check());
+
+    let target = sequent!(p.clone() => q.clone(), p.clone(), q.clone());
+    let adapted = adapt(proof.clone(), &target).unwrap();
+    assert_eq!(&target, adapted.last());
+    assert!(adapted.is_valid_proof());
+
+    assert_eq!(proof, adapt(proof.clone(), &proof.last().clone()).unwrap());
+    assert!(adapt(proof, &sequent!(p.clone() => p.clone())).is_err());
+}
+
+#[test]
+fn arbitrary_input_works() {
+    use crate::limits::*;
+    use crate::proof::*;
+    use crate::smtlib::*;
+    use crate::tokenizer::*;
+    use crate::tptp::*;
+
+    // The entry points of the fuzz targets must not panic on any input.
+    let limits = Limits::untrusted();
+    let check = |input: &str| {
+        let _ = Tokenizer::with_limits(&limits).try_tokenize(input);
+        let _ = limits.parse_formula(input);
+        let _ = limits.parse_sequent(input);
+        let _ = LK::from_script_with(input, &limits);
+        let _ = parse_smtlib(input);
+        let _ = parse_tstp(input);
+        let _ = LK::read_binary(&mut input.as_bytes());
+        #[cfg(feature = "serde")]
+        let _ = crate::serialize::from_json::<LK>(input);
+    };
+    let fragments = [
+        "(",
+        ")",
+        " ",
+        "\n  ",
+        "p",
+        "(f x)",
+        "(a)",
+        "V x",
+        "E",
+        "~",
+        "^",
+        "v",
+        ">",
+        "<>",
+        "=",
+        "|",
+        "\\",
+        "⊤",
+        "∀",
+        "Axiom ",
+        "Cut ",
+        "ExchangeLeft ",
+        "ForallLeft ",
+        "(assert ",
+        "(declare-fun f (Int) Int)",
+        "(forall ((x Int)) ",
+        "(distinct a b)",
+        ";",
+        "fof(a, axiom, ",
+        "cnf(c, plain, ",
+        "![X]: ",
+        "?[Y]:",
+        "p(X)",
+        "$false",
+        "&",
+        "=>",
+        ", inference(r, [status(thm)], [a, c])",
+        ").\n",
+        "%",
+        "RFOL\u{1}",
+        "{\"version\": 2, \"content\": ",
+    ];
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    for n in 0..5000 {
+        let input: String = (0..n % 24 + 1)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                fragments[(state % fragments.len() as u64) as usize]
+            })
+            .collect();
+        check(&input);
+    }
+    for deep in ["(~ ", "(f ", "(", "(assert (not "] {
+        check(&format!("{}p{}", deep.repeat(100_000), ")".repeat(100_000)));
+    }
+    // Formulas, terms and sources of TSTP steps.
+    for (start, open, close) in [
+        ("fof(a, axiom, ", "~(", ")"),
+        ("fof(a, axiom, ", "p(f(", "))"),
+        ("fof(a, plain, p, ", "[", "]"),
+        ("fof(a, plain, p, ", "f(", ")"),
+    ] {
+        let deep = format!(
+            "{}{}X{}).",
+            start,
+            open.repeat(100_000),
+            close.repeat(100_000)
+        );
+        assert_eq!(Err(TOO_DEEP), parse_tstp(&deep).map(|_| ()));
+        check(&deep);
+    }
+    check(&"Axiom ((p) (p))\n ".repeat(200_000));
+    check(&"cnf(c, axiom, p(X) | ~q(X)).\n".repeat(100_000));
+}
+
+#[test]
+fn satisfied_in_works() {
+    use crate::language::*;
+    use crate::model::*;
+    use crate::proof::*;
+    use crate::theory::*;
+
+    let mut model = FiniteModel::new(2);
+    model.assign_pred(nlsym!("p", 1), assign![[0] => true, [1] => false]);
+    model.assign_func(nlsym!("s", 1), assign![[0] => 1, [1] => 0]);
+    let px = pred!("p", var!("x"));
+    let psx = pred!("p", func!("s", var!("x")));
+
+    let sequent = sequent!(px.clone() => psx.clone());
+    assert!(!sequent.satisfied_in(&model, &assign![var!("x") => 0]));
+    assert!(sequent.satisfied_in(&model, &assign![var!("x") => 1]));
+    assert!(sequent!(=> px.clone(), psx.clone()).satisfied_in(&model, &assign![var!("x") => 1]));
+    assert!(!sequent!(=> ).satisfied_in(&model, &assign![]));
+    let shadowed = exists!(var!("x"), not!(px.clone()));
+    assert!(!sequent!(shadowed, px.clone() => ).satisfied_in(&model, &assign![var!("x") => 0]));
+
+    let theory = Theory::from(vec![or!(px.clone(), psx.clone())]);
+    assert!(model.validates(&theory));
+    let theory = Theory::from(vec![px, psx]);
+    assert!(!model.validates(&theory));
+
+    // `x` is free again after its quantifier.
+    let mut model = FiniteModel::new(2);
+    model.assign_pred(nlsym!("p", 1), assign![[0] => true, [1] => true]);
+    model.assign_pred(nlsym!("q", 1), assign![[0] => false, [1] => true]);
+    let fml = "(^ (V x (p x)) (q x))".parse::<Formula>().unwrap();
+    assert!(!sequent!(=> fml.clone()).satisfied_in(&model, &assign![var!("x") => 0]));
+    assert!(sequent!(=> fml.clone()).satisfied_in(&model, &assign![var!("x") => 1]));
+    assert!(!model.validates(&Theory::from(vec![fml])));
+}
+
+#[test]
+fn g3_works() {
+    use crate::g3::*;
+    use crate::proof::*;
+
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    let excluded_middle = G3::OrRight(
+        Box::new(G3::NotRight(
+            Box::new(G3::Axiom(seq("((p) (p))"))),
+            seq("(() ((~ p) p))"),
+        )),
+        seq("(() ((v p (~ p))))"),
+    );
+    assert!(excluded_middle.is_valid_proof());
+    let proof = excluded_middle.to_lk().unwrap();
+    assert_eq!(&seq("(() ((v p (~ p))))"), proof.last());
+    assert_eq!(Ok(()), proof.check());
+
+    let y = "(V x (> (P x) (Q x))) (V x (P x))";
+    let proof = G3::ForallRight(
+        Box::new(G3::ForallLeft(
+            Box::new(G3::ForallLeft(
+                Box::new(G3::ImpliesLeft(
+                    Box::new([
+                        G3::Axiom(seq(&format!("(({} (P y)) ((Q y) (P y)))", y))),
+                        G3::Axiom(seq(&format!("(((Q y) {} (P y)) ((Q y)))", y))),
+                    ]),
+                    seq(&format!("(((> (P y) (Q y)) {} (P y)) ((Q y)))", y)),
+                )),
+                seq(&format!("(({} (P y)) ((Q y)))", y)),
+            )),
+            seq(&format!("(({}) ((Q y)))", y)),
+        )),
+        seq(&format!("(({}) ((V x (Q x))))", y)),
+    );
+    assert!(proof.is_valid_proof());
+    let lk = proof.to_lk().unwrap();
+    assert_eq!(proof.last(), lk.last());
+    assert_eq!(Ok(()), lk.check());
+
+    let iff = G3::IffRight(
+        Box::new([
+            G3::AndLeft(
+                Box::new(G3::Axiom(seq("((p q r) (q))"))),
+                seq("(((^ p q) r) (q))"),
+            ),
+            G3::Axiom(seq("((⊥ r) ((^ p q)))")),
+        ]),
+        seq("((r ⊥) ((<> (^ p q) q)))"),
+    );
+    assert!(!iff.is_valid_proof());
+    let iff = G3::IffRight(
+        Box::new([
+            G3::AndLeft(
+                Box::new(G3::Axiom(seq("((p q ⊥) (q))"))),
+                seq("(((^ p q) ⊥) (q))"),
+            ),
+            G3::Axiom(seq("((q ⊥) ((^ p q)))")),
+        ]),
+        seq("((⊥) ((<> (^ p q) q)))"),
+    );
+    assert!(iff.is_valid_proof());
+    assert_eq!(Ok(()), iff.to_lk().unwrap().check());
+
+    let invalid = G3::ForallRight(
+        Box::new(G3::Axiom(seq("(((P x)) ((P x)))"))),
+        seq("(((P x)) ((V x (P x))))"),
+    );
+    assert!(!invalid.is_valid_inference());
+    assert!(invalid.to_lk().is_err());
+}
+
+#[test]
+fn countermodel_works() {
+    use crate::check::*;
+    use crate::language::*;
+    use crate::model::*;
+    use crate::proof::*;
+
+    let options = CheckOptions {
+        max_domain_size: Some(2),
+        ..CheckOptions::default()
+    };
+    let p = pred!("p", var!("x"));
+    let q = pred!("q");
+    let unsound = LK::WeakeningLeft(
+        Box::new(LK::Axiom(sequent!(q.clone() => q.clone()))),
+        sequent!(q.clone() => p.clone()),
+    );
+    let error = unsound.check_with(&options).unwrap_err();
+    let mut model = error.countermodel.clone().unwrap();
+    assert!(model.evaluate_formula(&q));
+    assert!(!model.evaluate_formula(&forall!(var!("x"), p.clone())));
+    assert!(error.to_string().ends_with("where the premises hold"));
+    assert_eq!(None, unsound.check().unwrap_err().countermodel);
+
+    let sound = LK::AndLeft1(
+        Box::new(LK::Axiom(sequent!(q.clone() => q.clone()))),
+        sequent!(and!(p.clone(), q.clone()) => q.clone()),
+    );
+    let error = sound.check_with(&options).unwrap_err();
+    assert_eq!(None, error.countermodel);
+
+    // Sound, with `x` free again after its quantifier in the conclusion.
+    let qx = pred!("q", var!("x"));
+    let sound = LK::WeakeningLeft(
+        Box::new(LK::Axiom(sequent!(qx.clone() => qx.clone()))),
+        sequent!(qx.clone() => implies!(forall!(var!("x"), p.clone()), qx.clone())),
+    );
+    let error = sound.check_with(&options).unwrap_err();
+    assert_eq!(None, error.countermodel);
+
+    // No search past the candidate limit.
+    let fa = func!("f", func!("a"));
+    let unsound = LK::ForallLeft(
+        Box::new(LK::Axiom(
+            sequent!(pred!("r", fa.clone()) => pred!("q", fa.clone())),
+        )),
+        sequent!(forall!(var!("x"), p.clone()) => pred!("q", fa.clone())),
+    );
+    assert!(unsound
+        .check_with(&options)
+        .unwrap_err()
+        .countermodel
+        .is_some());
+    let limited = CheckOptions {
+        max_candidates: Some(1),
+        ..options
+    };
+    let error = unsound.check_with(&limited).unwrap_err();
+    assert_eq!(
+        "too many candidate instances to check without an annotation",
+        error.reason
+    );
+    assert_eq!(None, error.countermodel);
+}
+
+#[test]
+fn prove_propositional_works() {
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::prover::*;
+
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    for goal in [
+        "(() ((v p (~ p))))",
+        "(() ((> (> (> p q) p) p)))",
+        "(() ((<> (~ (^ p q)) (v (~ p) (~ q)))))",
+        "(((<> p q) (v p q)) ((^ p q)))",
+        "(((V x (P x))) ((v (V x (P x)) ⊥)))",
+        "(() ((> ⊥ (= (a) (b)))))",
+    ] {
+        let proof = prove_propositional(&seq(goal)).unwrap();
+        assert_eq!(&seq(goal), proof.last());
+        assert_eq!(Ok(()), proof.check());
+    }
+
+    let valuation = prove_propositional(&seq("(((> p q)) ((> q p)))")).unwrap_err();
+    assert_eq!(hashmap![pred!("p") => false, pred!("q") => true], valuation);
+    let valuation = prove_propositional(&seq("((⊤) ((= (a) (b)) ⊥))")).unwrap_err();
+    assert_eq!(hashmap![equal!(func!("a"), func!("b")) => false], valuation);
+}
+
+#[test]
+fn first_order_prover_works() {
+    use crate::proof::*;
+    use crate::prover::*;
+    use crate::solver::*;
+    use std::time::Duration;
+
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    let prover = FirstOrderProver::default();
+    for goal in [
+        "(((V x (P x))) ((P (a))))",
+        "(((V x (> (P x) (Q x))) (P (a))) ((Q (a))))",
+        "(((E x (V y (R x y)))) ((V y (E x (R x y)))))",
+        "(() ((E x (> (P x) (V y (P y))))))",
+        "(((V x (> (P x) (P (f x)))) (P (a))) ((P (f (f (a))))))",
+        "(((V x (= x x))) ((E y (= y y))))",
+        // Vacuous quantifiers.
+        "(() ((> (V y (P (a))) (P (a)))))",
+        "(((E y (P (a)))) ((E z (P (a)))))",
+        "(((P (a))) ((V y (P (a)))))",
+        // Variables bound again in the scope.
+        "(() ((> (V x (V x (P x))) (P (a)))))",
+        "(((V x (^ (P x) (V x (Q x))))) ((Q (a))))",
+        "(((V x (E x (P x)))) ((E y (P y))))",
+        "(((E x (E x (P x)))) ((E y (P y))))",
+    ] {
+        let proof = prover.search(&seq(goal)).unwrap();
+        assert_eq!(&seq(goal), proof.last());
+        assert_eq!(Ok(()), proof.check());
+        assert!(prover.prove_sequent(&seq(goal)).is_some());
+    }
+
+    let goal = seq("(((V y (E x (R x y)))) ((E x (V y (R x y)))))");
+    let bounded = FirstOrderProver {
+        max_steps: Some(1_000),
+        ..prover
+    };
+    assert_eq!(Err(SearchFailure::OutOfSteps), bounded.search(&goal));
+    let shallow = FirstOrderProver {
+        max_instantiations: 1,
+        ..prover
+    };
+    let goal = seq("(() ((E x (> (P x) (V y (P y))))))");
+    assert_eq!(Err(SearchFailure::Exhausted), shallow.search(&goal));
+    let timed = FirstOrderProver {
+        timeout: Some(Duration::ZERO),
+        ..prover
+    };
+    assert_eq!(Err(SearchFailure::Timeout), timed.search(&goal));
+
+    let flag = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(true));
+    crate::solver::set_cancellation_flag(Some(flag));
+    assert_eq!(Err(SearchFailure::Cancelled), prover.search(&goal));
+    crate::solver::set_cancellation_flag(None);
+}
+
+#[test]
+fn sequent_normal_form_works() {
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::prover::*;
+
+    let fml = |s: &str| s.parse::<Formula>().unwrap();
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    assert_eq!(
+        vec![seq("((p q) (r))"), seq("(() (s))")],
+        fml("(^ (> (^ p q) r) s)").to_sequent_normal_form()
+    );
+    assert_eq!(
+        vec![seq("((p q) (r s))"), seq("((p) (t))")],
+        fml("(> p (^ (> q (v r s)) (v t ⊥)))").to_sequent_normal_form()
+    );
+    assert_eq!(
+        vec![seq("(() ((V x (> (P x) (Q x)))))")],
+        fml("(V x (> (P x) (Q x)))").to_sequent_normal_form()
+    );
+    assert_eq!(
+        vec![seq("((p q) ())")],
+        fml("(> (^ ⊤ p) (> q ⊥))").to_sequent_normal_form()
+    );
+
+    for s in [
+        "(^ (> (^ p q) r) s)",
+        "(> p (^ (> q (v r s)) (v t ⊥)))",
+        "(> (^ ⊤ p) (> q ⊥))",
+        "(<> p q)",
+    ] {
+        let sequents = fml(s).to_sequent_normal_form();
+        let back = Formula::from_sequents(&sequents);
+        let goal = Sequent {
+            antecedent: vec![],
+            succedent: vec![iff!(fml(s), back)],
+        };
+        assert!(prove_propositional(&goal).is_ok());
+    }
+    assert_eq!(Formula::Top, Formula::from_sequents(&[]));
+}
+
+#[test]
+fn resolution_works() {
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::prover::*;
+    use crate::resolution::*;
+    use crate::solver::*;
+
+    let clauses = |s: &str| s.parse::<Formula>().unwrap().to_cnf();
+    let prover = ResolutionProver::default();
+    let refutation = prover
+        .refute(&clauses(
+            "(^ (P (a)) (^ (V x (> (P x) (Q x))) (~ (Q (a)))))",
+        ))
+        .unwrap();
+    assert!(refutation.clauses.last().unwrap().0.is_empty());
+    for (i, (_, derivation)) in refutation.clauses.iter().enumerate() {
+        assert!(derivation.parents().iter().all(|p| *p < i));
+    }
+    assert_eq!(
+        Err(SearchFailure::Saturated),
+        prover.refute(&clauses("(^ (P (a)) (~ (P (b))))"))
+    );
+
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    for goal in [
+        "(((V x (> (P x) (Q x))) (P (a))) ((Q (a))))",
+        "(((E x (V y (R x y)))) ((V y (E x (R x y)))))",
+        "(((V x (v (P x) (Q x))) (V x (~ (P x)))) ((E x (Q x))))",
+        "(((P z)) ((P z)))",
+        "(() ((> (V x (P x)) (^ (P (a)) (P (f (b)))))))",
+        "(((V x (> (P x) (P (f x)))) (P (a))) ((P (f (f (a))))))",
+    ] {
+        let proof = prover.prove_sequent(&seq(goal)).unwrap();
+        assert_eq!(&seq(goal), proof.last());
+        assert_eq!(Ok(()), proof.check());
+    }
+    assert!(prover.prove_sequent(&seq("(((P z)) ((P w)))")).is_none());
+}
+
+#[test]
+fn resolution_to_lk_works() {
+    use crate::proof::*;
+    use crate::resolution::*;
+
+    // Refutations instantiating Skolem functions, which are turned into
+    // eigenvariables.
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    let prover = ResolutionProver::default();
+    for goal in [
+        "(() ((E x (> (P x) (V y (P y))))))",
+        "(((V x (E y (R x y))) (V x (V y (> (R x y) (Q y))))) ((E z (Q z))))",
+        "(((V x (E y (R x y)))) ((V x (E y (E z (^ (R x y) (R y z)))))))",
+        "(() ((E x (V y (> (P x) (P y))))))",
+        "(((V x (E y (^ (P y) (R x y))))) ((E x (P x))))",
+        "(() ((<> (E x (P x)) (E y (P y)))))",
+        // Vacuous quantifiers, and variables bound again in the scope.
+        "(() ((> (V y (p (a))) (p (a)))))",
+        "(((E x (V y (P y)))) ((P (a))))",
+        "(((V y (E x (P (a))))) ((P (a))))",
+        "(() ((> (V x (V x (P x))) (P (a)))))",
+        "(((V x (^ (P x) (V x (Q x))))) ((Q (a))))",
+        "(((V x (E x (P x)))) ((E y (P y))))",
+    ] {
+        let form = seq(goal).to_clause_form();
+        let refutation = prover.refute(&form.clauses).unwrap();
+        let proof = form.to_lk(&refutation).unwrap();
+        assert_eq!(&seq(goal), proof.last());
+        assert_eq!(Ok(()), proof.check());
+    }
+}
+
+#[test]
+fn theory_rules_works() {
+    use crate::check::*;
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::prover::*;
+    use crate::solver::*;
+    use crate::theory::*;
+
+    let fml = |s: &str| s.parse::<Formula>().unwrap();
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    let mut theory = Theory::new();
+    theory.add_axiom_as_rules("refl", &fml("(V x (R x x))"));
+    theory.add_axiom_as_rules(
+        "trans",
+        &fml("(V x (V y (V z (> (^ (R x y) (R y z)) (R x z)))))"),
+    );
+    assert_eq!(None, theory.rules[0].premise);
+    assert_eq!(
+        Some(seq("(((R x z) (R x y) (R y z)) ())")),
+        theory.rules[1].premise
+    );
+
+    let options = CheckOptions {
+        theory: theory.clone(),
+        ..CheckOptions::default()
+    };
+    let prover = FirstOrderProver::default();
+    for goal in [
+        "(() ((R (a) (a))))",
+        "(((R (a) (b)) (R (b) (c)) (R (c) (d))) ((R (a) (d))))",
+        "(() ((V x (V y (> (R x y) (E z (^ (R x z) (R z y))))))))",
+    ] {
+        let proof = prover.search_in(&theory, &seq(goal)).unwrap();
+        assert_eq!(&seq(goal), proof.last());
+        assert_eq!(Ok(()), proof.check_with(&options));
+        assert!(proof.check().is_err());
+    }
+    assert!(prover.prove_in(&theory, &fml("(R (a) (b))")).is_none());
+
+    let bogus = LK::TheoryRule(vec![], seq("(((R (a) (b))) ((R (b) (a))))"));
+    assert_eq!(
+        "not an instance of a rule of the theory",
+        bogus.check_with(&options).unwrap_err().reason
+    );
+}
+
+#[test]
+fn implicit_exchange_works() {
+    use crate::check::*;
+    use crate::proof::*;
+
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    let weakened =
+        |axiom: &str, s: &str| LK::WeakeningLeft(Box::new(LK::Axiom(seq(axiom))), seq(s));
+    let and = LK::AndRight(
+        Box::new([
+            weakened("((p) (p))", "((q p) (p))"),
+            weakened("((q) (q))", "((p q) (q))"),
+        ]),
+        seq("((p q) ((^ p q)))"),
+    );
+    let proof = LK::ImpliesRight(Box::new(and), seq("((q) ((> p (^ p q))))"));
+    let implies = LK::ImpliesRight(
+        Box::new(weakened("((p) (p))", "((q p) (p))")),
+        seq("((q) ((> p p)))"),
+    );
+    let options = CheckOptions {
+        implicit_exchange: true,
+        ..CheckOptions::default()
+    };
+    for proof in [proof, implies] {
+        assert!(proof.check().is_err());
+        assert_eq!(Ok(()), proof.check_with(&options));
+        let explicit = proof.with_exchanges(&options).unwrap();
+        assert_eq!(proof.last(), explicit.last());
+        assert_eq!(Ok(()), explicit.check());
+    }
+
+    let invalid = LK::Axiom(seq("((p) (q))"));
+    assert!(invalid.check_with(&options).is_err());
+    assert!(invalid.with_exchanges(&options).is_err());
+}
+
+#[test]
+fn differential_works() {
+    use crate::check::*;
+    use crate::differential::*;
+    use crate::proof::*;
+
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    let strict = LK::ImpliesRight(Box::new(LK::Axiom(seq("((p) (p))"))), seq("(() ((> p p)))"));
+    let multiset = LK::ImpliesRight(
+        Box::new(LK::WeakeningLeft(
+            Box::new(LK::Axiom(seq("((p) (p))"))),
+            seq("((q p) (p))"),
+        )),
+        seq("((q) ((> p p)))"),
+    );
+    let invalid = LK::Axiom(seq("((p) (q))"));
+    let proofs = [strict, multiset, invalid];
+    let changes = exchange_sensitive(&proofs, &CheckOptions::default());
+    assert_eq!(1, changes.len());
+    assert_eq!(1, changes[0].index);
+    assert!(changes[0].before.is_err());
+    assert_eq!(Ok(()), changes[0].after);
+
+    let options = CheckOptions::default();
+    assert!(verdict_changes(&proofs, &options, &options).is_empty());
+
+    #[cfg(feature = "serde")]
+    {
+        use crate::serialize::*;
+
+        let documents = proofs
+            .iter()
+            .map(|p| to_json(p).unwrap())
+            .collect::<Vec<_>>();
+        let documents = documents.iter().map(String::as_str).collect::<Vec<_>>();
+        let multiset = CheckOptions {
+            implicit_exchange: true,
+            ..CheckOptions::default()
+        };
+        assert_eq!(
+            Ok(changes),
+            stored_verdict_changes(&documents, &options, &multiset)
+        );
+        assert!(matches!(
+            stored_verdict_changes(&[documents[0], "{}"], &options, &multiset),
+            Err((1, FormatError::Syntax(_)))
+        ));
+    }
+}
+
+#[test]
+fn cdcl_works() {
+    use crate::language::*;
+    use crate::sat::*;
+
+    // Pigeonhole: 4 pigeons in 3 holes, `p(i, j)` being pigeon `i` in hole `j`.
+    let p = |i: i32, j: i32| 3 * i + j + 1;
+    let mut clauses = (0..4)
+        .map(|i| (0..3).map(|j| p(i, j)).collect())
+        .collect::<Vec<Vec<i32>>>();
+    for j in 0..3 {
+        for i in 0..4 {
+            for k in i + 1..4 {
+                clauses.push(vec![-p(i, j), -p(k, j)]);
+            }
+        }
+    }
+    let mut solver = CdclSolver::default();
+    assert_eq!(None, solver.solve(&clauses));
+    assert!(solver.conflicts > 0);
+    // Without the last pigeon.
+    clauses.retain(|c| c.iter().all(|l| l.abs() <= p(2, 2)));
+    let model = solver.solve(&clauses).unwrap();
+    assert!(clauses.iter().all(|c| c.iter().any(|l| model.contains(l))));
+
+    // Random 3-SAT against all assignments.
+    let mut seed = 7u64;
+    let mut random = |n: u64| {
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (seed >> 33) % n
+    };
+    for _ in 0..200 {
+        let clauses = (0..30)
+            .map(|_| {
+                (0..3)
+                    .map(|_| (random(8) as i32 + 1) * if random(2) == 0 { 1 } else { -1 })
+                    .collect()
+            })
+            .collect::<Vec<Vec<i32>>>();
+        let satisfies = |model: &[i32]| clauses.iter().all(|c| c.iter().any(|l| model.contains(l)));
+        let brute = (0..256).any(|bits| {
+            let model = (1..=8)
+                .map(|v| if bits >> (v - 1) & 1 == 1 { v } else { -v })
+                .collect::<Vec<_>>();
+            satisfies(&model)
+        });
+        match solver.solve(&clauses) {
+            Some(model) => assert!(satisfies(&model)),
+            None => assert!(!brute),
+        }
+        assert_eq!(brute, solver.solve(&clauses).is_some());
+    }
+
+    let fml = "(^ (v (P (a)) (Q (a))) (^ (~ (P (a))) (> (Q (a)) (R (b)))))";
+    let valuation = solver
+        .solve_clauses(&fml.parse::<Formula>().unwrap().to_cnf())
+        .unwrap();
+    assert_eq!(
+        Some(&true),
+        valuation.get(&"(R (b))".parse::<Formula>().unwrap())
+    );
+    assert_eq!(
+        Some(&false),
+        valuation.get(&"(P (a))".parse::<Formula>().unwrap())
+    );
+}
+
+#[test]
+fn congruence_works() {
+    use crate::congruence::*;
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::tactic::*;
+
+    let term = |s: &str| s.parse::<Term>().unwrap();
+    let mut closure = CongruenceClosure::new();
+    closure.merge(&term("(a)"), &term("(b)"));
+    closure.merge(&term("(f (a))"), &term("(c)"));
+    closure.merge(&term("(d)"), &term("(e)"));
+    assert!(closure.are_equal(&term("(f (b))"), &term("(c)")));
+    assert!(!closure.are_equal(&term("(f (c))"), &term("(c)")));
+    assert_eq!(
+        Some(vec![0, 1]),
+        closure.explain(&term("(c)"), &term("(f (b))"))
+    );
+
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    for goal in [
+        "(((= (a) (b)) (= (f (a)) (c))) ((= (f (b)) (c))))",
+        "(((= (f (f (f (a)))) (a)) (= (f (f (f (f (f (a)))))) (a))) ((= (f (a)) (a))))",
+        "(((= (a) (b)) (P (g (a) (b)))) ((P (g (b) (a)))))",
+        "(((= (b) (a)) (= (g (a) (a)) (c))) ((Q) (= (c) (g (b) (a)))))",
+    ] {
+        let goal = seq(goal);
+        let proof = prove_by_congruence(&goal).unwrap();
+        assert_eq!(&goal, proof.last());
+        assert_eq!(Ok(()), proof.check());
+
+        let mut state = ProofState::new(goal);
+        state.congruence().unwrap();
+        assert_eq!(Ok(()), state.qed().unwrap().check());
+    }
+    assert_eq!(
+        None,
+        prove_by_congruence(&seq("(((= (a) (b))) ((= (f (a)) (c))))"))
+    );
+}
+
+#[test]
+fn golden_renderings_works() {
+    use crate::golden::*;
+
+    let mut failures = vec![];
+    for (name, proof) in samples() {
+        for (renderer, options) in renderers() {
+            let file = format!("{}.{}.txt", name, renderer);
+            if let Err(diff) = check(&file, &(proof.render(&options) + "\n")) {
+                failures.push(diff);
+            }
+        }
+        for (exporter, export) in exporters() {
+            let file = format!("{}.{}.txt", name, exporter);
+            if let Err(diff) = check(&file, &(export(&proof) + "\n")) {
+                failures.push(diff);
+            }
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "{}\nrun with {}=1 to update",
+        failures.join("\n"),
+        UPDATE
+    );
+}
+
+#[test]
+fn equality_rules_works() {
+    use crate::language::*;
+    use crate::proof::*;
+
+    let scripts = [
+        "
+ForallRight (((E y (V x (R x y)))) ((V x (E y (R x y)))))
+  ExistsLeft (((E y (V x (R x y)))) ((E y (R z y))))
+    ForallLeft (((V x (R x w))) ((E y (R z y))))
+      ExistsRight (((R z w)) ((E y (R z y))))
+        Axiom (((R z w)) ((R z w)))
+",
+        "
+OrLeft (((v A B) (~ A)) (B))
+  WeakeningRight ((A (~ A)) (B))
+    ExchangeLeft ((A (~ A)) ())
+      NotLeft (((~ A) A) ())
+        Axiom ((A) (A))
+  ExchangeLeft ((B (~ A)) (B))
+    WeakeningLeft (((~ A) B) (B))
+      Axiom ((B) (B))
+",
+    ];
+    for script in scripts {
+        let proof = LK::from_script(script).unwrap();
+        let term = proof.proof_term().unwrap();
+        assert_eq!(Ok(()), term.check(proof.last()), "{}", term);
+    }
+    let term = LK::from_script(scripts[1]).unwrap().proof_term().unwrap();
+    assert_eq!(
+        "(case h0 of inl h2 ⇒ (abort (h1 h2)) | inr h3 ⇒ h3)",
+        term.to_string()
+    );
+    let script = "
+EqualityRight (((= (a) (b)) (P (a))) ((P (b))))
+  Axiom (((P (a))) ((P (a))))
+";
+    assert_eq!(Ok(()), LK::from_script(script).unwrap().check());
+    let script = "
+EqualityLeft (((= (a) (b)) (Q (b) (a))) ((Q (a) (a))))
+  Axiom (((Q (a) (a))) ((Q (a) (a))))
+";
+    assert_eq!(Ok(()), LK::from_script(script).unwrap().check());
+    let script = "
+EqualityRight (((= (f (a)) (b)) (V x (= (g (f (a)) x) (c)))) ((V x (= (g (b) x) (c)))))
+  Axiom (((V x (= (g (f (a)) x) (c)))) ((V x (= (g (f (a)) x) (c)))))
+";
+    assert_eq!(Ok(()), LK::from_script(script).unwrap().check());
+
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    let invalid = [
+        // Not an equation.
+        ("(((P (a))) ((P (a))))", "(((P (a)) (P (a))) ((P (b))))"),
+        // Replaced the wrong way.
+        ("(((P (a))) ((P (a))))", "(((= (b) (a)) (P (a))) ((P (b))))"),
+        // Replaced in the scope of a quantifier binding `x`.
+        (
+            "(((V x (= (f x) (c)))) ((V x (= (f x) (c)))))",
+            "(((= (f x) (a)) (V x (= (f x) (c)))) ((V x (= (a) (c)))))",
+        ),
+    ];
+    for (premise, conclusion) in invalid {
+        let premises = vec![LK::Axiom(seq(premise))];
+        let proof = LK::from_parts("EqualityRight", premises, seq(conclusion)).unwrap();
+        assert!(!proof.is_valid_inference());
+        assert!(proof.check().is_err());
+    }
+    let premises = vec![LK::Axiom(seq("(((P (a))) ((P (a))))"))];
+    let proof = LK::from_parts(
+        "EqualityRight",
+        premises,
+        seq("(((= (a) (b)) (P (a))) ((P (c))))"),
+    );
+    assert_eq!(
+        "conclusion formula is not the premise formula with equals replaced",
+        proof.unwrap().check().unwrap_err().reason
+    );
+
+    assert_eq!(
+        "=L",
+        LabelScheme::Symbolic.label("EqualityLeft", Notation::Unicode)
+    );
+    assert_eq!(
+        "equality right",
+        LabelScheme::Named.label("EqualityRight", Notation::Unicode)
+    );
+}
+
+#[test]
+fn layout_works() {
+    use crate::language::*;
+    use crate::proof::*;
+
+    let axiom = || LK::Axiom(sequent!(pred!("p") => pred!("p")));
+    let mut proofs = (0..8_192).map(|_| axiom()).collect::<Vec<_>>();
+    while proofs.len() > 1 {
+        proofs = proofs
+            .chunks_mut(2)
+            .map(|pair| {
+                let [lhs, rhs] = pair else { unreachable!() };
+                let premises = [
+                    std::mem::replace(lhs, axiom()),
+                    std::mem::replace(rhs, axiom()),
+                ];
+                LK::Cut(Box::new(premises), sequent!(pred!("p") => pred!("p")))
+            })
+            .collect();
+    }
+    let rendered = proofs[0].to_string();
+    let lines = rendered.lines().collect::<Vec<_>>();
+    assert_eq!(27, lines.len());
+    assert_eq!(8_191, rendered.matches("(Cut)").count());
+    let width = lines[0].chars().count();
+    assert!(lines.iter().all(|l| l.chars().count() == width));
+    // The conclusion is centered below the outermost premises.
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let (from, to) = (indent(lines[24]), lines[24].trim_end().chars().count());
+    assert_eq!(from + (to - from - 6) / 2, indent(lines[26]));
+
+    let mut proof = axiom();
+    for _ in 0..10_000 {
+        proof = LK::ContractionRight(
+            Box::new(LK::WeakeningRight(
+                Box::new(proof),
+                sequent!(pred!("p") => pred!("p"), pred!("p")),
+            )),
+            sequent!(pred!("p") => pred!("p")),
+        );
+    }
+    assert_eq!(40_001, proof.to_string().lines().count());
+}
+
+#[test]
+fn holes_works() {
+    use crate::hole::*;
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::script::*;
+
+    let fml = |s: &str| s.parse::<Formula>().unwrap();
+    assert_eq!(
+        Some(fml("(P (a) (f (b)))")),
+        fml("(P _ (f _))").elaborate(&fml("(P (a) (f (b)))"))
+    );
+    assert_eq!(
+        Some(fml("(> (Q (a)) (R (a)))")),
+        fml("(> _ (R _))").elaborate(&fml("(> (Q (a)) (R (a)))"))
+    );
+    assert_eq!(None, fml("(P _ (b))").elaborate(&fml("(P (a) (c))")));
+    assert_eq!(None, fml("(P _)").elaborate(&fml("(P _)")));
+    assert!(fml("(^ _ p)").has_holes());
+    let mut holes = Holes::new();
+    let x = holes.term();
+    assert!(!holes.unify_terms(&x, &Term::Func("f".into(), vec![x.clone()])));
+    assert_eq!(x, holes.resolve_term(&x));
+
+    let script = "
+ImpliesRight (() ((> (V x (P x)) (P (f (a))))))
+  ForallLeft (((V x (P x))) ((P (f (a)))))
+    Axiom (((P _)) (_))
+";
+    let proof = LK::from_script(script).unwrap();
+    assert_eq!(
+        &"(((P (f (a)))) ((P (f (a)))))".parse::<Sequent>().unwrap(),
+        proof.premises()[0].premises()[0].last()
+    );
+    let script = "
+ImpliesRight (() ((> _ (v p q))))
+  Cut ((_) ((v p q)))
+    AndLeft1 (((^ p q)) (_))
+      Axiom ((p) (p))
+    OrRight1 ((_) ((v p q)))
+      Axiom ((p) (_))
+";
+    let proof = LK::from_script(script).unwrap();
+    assert_eq!(Ok(()), proof.check());
+    assert_eq!(
+        &"(() ((> (^ p q) (v p q))))".parse::<Sequent>().unwrap(),
+        proof.last()
+    );
+    assert_eq!(
+        ScriptError::Syntax(1, "holes left unfilled"),
+        LK::from_script("Axiom ((_) (_))").unwrap_err()
+    );
+}
+
+#[test]
+fn theory_axioms_works() {
+    use crate::check::*;
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::theory::*;
+
+    let fml = |s: &str| s.parse::<Formula>().unwrap();
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    let mut theory = Theory::new();
+    theory.add_named_axiom("right identity", fml("(V x (= (+ x (0)) x))"));
+    theory.add_axiom(fml("(V x (V y (= (+ x (s y)) (s (+ x y)))))"));
+    assert!(theory.has_instance(&fml("(= (+ (a) (0)) (a))")));
+    assert!(theory.has_instance(&fml("(V y (= (+ (0) (s y)) (s (+ (0) y))))")));
+    assert!(!theory.has_instance(&fml("(= (+ (0) (a)) (a))")));
+
+    // `P(a + 0) ⇒ P(a)` by cutting the axiom with an equality inference.
+    let equation = LK::TheoryAxiom(seq("(() ((= (+ (a) (0)) (a))))"));
+    let replaced = LK::EqualityRight(
+        Box::new(LK::Axiom(seq("(((P (+ (a) (0)))) ((P (+ (a) (0)))))"))),
+        seq("(((= (+ (a) (0)) (a)) (P (+ (a) (0)))) ((P (a))))"),
+    );
+    let proof = LK::Cut(
+        Box::new([equation, replaced]),
+        seq("(((P (+ (a) (0)))) ((P (a))))"),
+    );
+    let options = CheckOptions {
+        theory: theory.clone(),
+        ..CheckOptions::default()
+    };
+    assert_eq!(Ok(()), proof.check_with(&options));
+    let error = proof.check().unwrap_err();
+    assert_eq!(vec![0], error.path);
+    assert_eq!("not an instance of an axiom of the theory", error.reason);
+
+    let contextual = LK::TheoryAxiom(seq("(((Q)) ((= (+ (a) (0)) (a))))"));
+    assert!(!is_valid_in(&contextual, &theory));
+    assert_eq!("TheoryAxiom", contextual.rule_name());
+}
+
+#[test]
+fn schema_works() {
+    use crate::check::*;
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::prover::*;
+    use crate::schema::*;
+    use crate::theory::*;
+
+    let fml = |s: &str| s.parse::<Formula>().unwrap();
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    let induction = Schema::new(
+        fml("(> (^ (P (0)) (V x (> (P x) (P (s x))))) (V x (P x)))"),
+        vec![nlsym!("P", 1)],
+        vec![],
+    );
+    let instance = fml("(> (^ (= (+ (0) (0)) (0)) (V x (> (= (+ x (0)) x) (= (+ (s x) (0)) (s x))))) (V x (= (+ x (0)) x)))");
+    let inst = induction.matches(&instance).unwrap();
+    assert_eq!(
+        (vec![var!("x")], fml("(= (+ x (0)) x)")),
+        inst.formulas["P"]
+    );
+    assert_eq!(Some(instance.clone()), induction.instantiate(&inst));
+    assert_eq!(None, induction.instantiate(&Instantiation::new()));
+    let renamed = fml("(> (^ (= (+ (0) (0)) (0)) (V y (> (= (+ y (0)) y) (= (+ (s y) (0)) (s y))))) (V y (= (+ y (0)) y)))");
+    assert!(induction.matches(&renamed).is_some());
+    assert_eq!(
+        None,
+        induction.matches(&fml(
+            "(> (^ (Q (0)) (V x (> (Q x) (Q (s x))))) (V x (P x)))"
+        ))
+    );
+
+    // Bound variables of the schema are renamed apart from the parameters.
+    let mut inst = Instantiation::new();
+    inst.formulas
+        .insert("P".into(), (vec![var!("z")], fml("(< z x)")));
+    let renamed = induction.instantiate(&inst).unwrap();
+    assert!(renamed.get_free_vars().contains(&var!("x")));
+    assert_eq!(
+        Some(renamed),
+        induction.instance_concluding(&fml("(V z (< z x))"))
+    );
+
+    // Term metavariables, and separation as a schema.
+    let separation = Schema::new(
+        fml("(E y (V x (<> (in x y) (^ (in x z) (Q x)))))"),
+        vec![nlsym!("Q", 1)],
+        vec![var!("z")],
+    );
+    let inst = separation
+        .matches(&fml("(E y (V x (<> (in x y) (^ (in x (a)) (= x x)))))"))
+        .unwrap();
+    assert_eq!(
+        Some(&Term::Func("a".into(), vec![])),
+        inst.terms.get(&var!("z"))
+    );
+
+    let mut theory = Theory::new();
+    theory.add_schema(induction);
+    let axiom = LK::TheoryAxiom(Sequent {
+        antecedent: vec![],
+        succedent: vec![instance],
+    });
+    assert!(is_valid_in(&axiom, &theory));
+    let goal = seq("(((P (0)) (V x (> (P x) (P (s x))))) ((V x (P x))))");
+    let proof = FirstOrderProver::default()
+        .search_in(&theory, &goal)
+        .unwrap();
+    assert_eq!(&goal, proof.last());
+    let options = CheckOptions {
+        theory,
+        ..CheckOptions::default()
+    };
+    assert_eq!(Ok(()), proof.check_with(&options));
+}
+
+#[test]
+fn certificate_works() {
+    use crate::certificate::*;
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::prover::*;
+
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    let goal = seq("(((V x (> (P x) (Q (f x)))) (P (a))) ((E y (Q y))))");
+    let proof = FirstOrderProver::default().search(&goal).unwrap();
+    let certificate = proof.certificate();
+    assert_eq!(
+        vec![
+            ("ForallLeft", func!("a")),
+            ("ExistsRight", func!("f", func!("a")))
+        ],
+        (certificate.instances.iter())
+            .map(|i| (i.rule, i.term.clone()))
+            .collect::<Vec<_>>()
+    );
+    let written = certificate.to_string();
+    assert_eq!(
+        "ForallLeft (Vx (> (P x) (Q (f x)))) (a)\nExistsRight (Ey (Q y)) (f (a))\n",
+        written
+    );
+    let read = written.parse::<Certificate>().unwrap();
+    assert_eq!(certificate, read);
+    let rebuilt = read.rebuild(&goal).unwrap();
+    assert_eq!(Ok(()), rebuilt.check());
+    assert_eq!(&goal, rebuilt.last());
+
+    // Eigenvariables are taken from the certificate.
+    let goal = seq("(((V x (P x))) ((V z (P z))))");
+    let certificate = "ForallRight (V z (P z)) w\nForallLeft (V x (P x)) w"
+        .parse::<Certificate>()
+        .unwrap();
+    let rebuilt = certificate.rebuild(&goal).unwrap();
+    assert_eq!(Ok(()), rebuilt.check());
+    assert_eq!(certificate.instances, rebuilt.certificate().instances);
+
+    let wrong = "ForallLeft (V x (> (P x) (Q (f x)))) (b)\nExistsRight (E y (Q y)) (f (b))"
+        .parse::<Certificate>()
+        .unwrap();
+    let goal = seq("(((V x (> (P x) (Q (f x)))) (P (a))) ((E y (Q y))))");
+    assert_eq!(
+        Err(SearchFailure::Exhausted),
+        wrong.rebuild(&goal).map(|_| ())
+    );
+    assert_eq!(
+        Err((2, "expected a quantifier rule name")),
+        "ForallLeft (V x (P x)) (a)\nCut (P x) x".parse::<Certificate>()
+    );
+}
+
+#[test]
+fn cut_elimination_works() {
+    use crate::cut::*;
+    use crate::proof::*;
+    use crate::prover::*;
+
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    let prove = |s: &str| FirstOrderProver::default().search(&seq(s)).unwrap();
+    let cut = |lhs: LK, rhs: LK| {
+        let (l, r) = (lhs.last(), rhs.last());
+        let conclusion = Sequent {
+            antecedent: [&l.antecedent[..], r.ant_but_first()].concat(),
+            succedent: [l.suc_but_last(), &r.succedent[..]].concat(),
+        };
+        LK::Cut(Box::new([lhs, rhs]), conclusion)
+    };
+    let cases = vec![
+        cut(prove("((p q) ((^ p q)))"), prove("(((^ p q)) ((^ q p)))")),
+        cut(prove("((p) ((v q p)))"), prove("(((v q p) (~ q)) (p))")),
+        cut(
+            prove("(((> p q) (> q p)) ((<> p q)))"),
+            prove("(((<> p q) p) (q))"),
+        ),
+        cut(prove("((q) ((~ (~ q))))"), prove("(((~ (~ q))) (q))")),
+        cut(
+            prove("(((V x (P x))) ((V y (P (f y)))))"),
+            prove("(((V y (P (f y)))) ((P (f (a)))))"),
+        ),
+        cut(
+            prove("(((P (a))) ((E x (P x))))"),
+            prove("(((E x (P x)) (V x (> (P x) (Q x)))) ((E x (Q x))))"),
+        ),
+        // A cut above a cut, with contractions on the cut formula.
+        cut(
+            cut(prove("((p) ((^ p p)))"), prove("(((^ p p)) ((v p q)))")),
+            prove("(((v p q) (> p r) (> q r)) ((^ r r)))"),
+        ),
+    ];
+    for proof in cases {
+        assert_eq!(Ok(()), proof.check());
+        assert!(!proof.is_cut_free());
+        let cut_free = proof.eliminate_cuts().unwrap();
+        assert!(cut_free.is_cut_free());
+        assert_eq!(proof.last(), cut_free.last());
+        assert_eq!(Ok(()), cut_free.check());
+    }
+
+    let proof = cut(prove("((p q) ((^ p q)))"), prove("(((^ p q)) ((^ q p)))"));
+    let options = CutOptions {
+        max_steps: Some(2),
+        ..CutOptions::default()
+    };
+    assert_eq!(
+        Err(CutError::TooManySteps),
+        proof.eliminate_cuts_with(&options)
+    );
+    // Reductions above branching inferences copy the other subproof.
+    let g = "(> (<> (^ s s) (<> q r)) (v (> r q) ⊤))";
+    let proof = cut(
+        prove_propositional(&seq(&format!("(((> (<> q r) (> r q))) ({g}))"))).unwrap(),
+        prove_propositional(&seq(&format!("(({g}) ((v {g} (<> (> r q) (<> q r)))))"))).unwrap(),
+    );
+    let options = CutOptions {
+        max_size: Some(1_000),
+        ..CutOptions::default()
+    };
+    assert_eq!(Err(CutError::TooLarge), proof.eliminate_cuts_with(&options));
+    let invalid = LK::Cut(
+        Box::new([prove("((p) (p))"), prove("((q) (q))")]),
+        seq("((p) (q))"),
+    );
+    assert!(matches!(
+        invalid.eliminate_cuts(),
+        Err(CutError::Invalid(_))
+    ));
+}
+
+#[test]
+fn rule_specs_work() {
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::prover::*;
+    use crate::rules::*;
+    use crate::schema::*;
+
+    let fml = |s: &str| s.parse::<Formula>().unwrap();
+    let fmls = |ss: &[&str]| ss.iter().map(|s| fml(s)).collect::<Vec<_>>();
+    let contexts = ["Gamma", "Delta", "Pi", "Sigma"];
+    let is_context = |f: &Formula| {
+        contexts
+            .iter()
+            .any(|c| f == &Formula::Pred(c.to_string(), vec![]))
+    };
+    let metavars = vec![nlsym!("A", 0), nlsym!("B", 0), nlsym!("F", 1)];
+    let term_vars = vec![var!("s"), var!("t"), var!("y")];
+    let inst = |a: &str, b: &str, f: &str, terms: [&str; 3]| {
+        let mut inst = Instantiation::new();
+        inst.formulas.insert("A".into(), (vec![], fml(a)));
+        inst.formulas.insert("B".into(), (vec![], fml(b)));
+        inst.formulas.insert("F".into(), (vec![var!("z")], fml(f)));
+        for (var, term) in ["s", "t", "y"].iter().zip(terms) {
+            inst.terms.insert(var!(*var), term.parse::<Term>().unwrap());
+        }
+        inst
+    };
+    // The instance of a schema sequent, with the lists of formulas of
+    // `lists` for the context metavariables.
+    let instance = |schema: &str, inst: &Instantiation, lists: &[Vec<Formula>; 4]| {
+        let schema = schema.parse::<Sequent>().unwrap();
+        let expand = |fmls: &[Formula]| -> Vec<Formula> {
+            fmls.iter()
+                .flat_map(|f| match contexts.iter().position(|c| f == &fml(c)) {
+                    Some(i) => lists[i].clone(),
+                    None => {
+                        let schema = Schema::new(f.clone(), metavars.clone(), term_vars.clone());
+                        vec![schema.instantiate(inst).unwrap()]
+                    }
+                })
+                .collect()
+        };
+        Sequent {
+            antecedent: expand(&schema.antecedent),
+            succedent: expand(&schema.succedent),
+        }
+    };
+    let inference = |spec: &RuleSpec, inst: &Instantiation, lists: &[Vec<Formula>; 4]| {
+        let (premises, conclusion) = spec.schema.unwrap();
+        let premises = (premises.iter())
+            .map(|p| LK::Axiom(instance(p, inst, lists)))
+            .collect();
+        LK::from_parts(spec.name, premises, instance(conclusion, inst, lists)).unwrap()
+    };
+
+    let empty = || [vec![], vec![], vec![], vec![]];
+    let lists = [
+        fmls(&["(G (a))"]),
+        fmls(&["(D (a))"]),
+        fmls(&["(H (a))"]),
+        fmls(&["(K (a))"]),
+    ];
+    let valid = [
+        (
+            inst("(P (a))", "(Q (b))", "(R z)", ["(a)", "(b)", "u"]),
+            empty(),
+        ),
+        (
+            inst(
+                "(^ (P (a)) (Q (a)))",
+                "(V x (P x))",
+                "(E w (S z w))",
+                ["(f (a))", "(a)", "u"],
+            ),
+            lists.clone(),
+        ),
+        // Vacuous quantifiers, whose instances have no terms.
+        (inst("⊤", "⊥", "⊤", ["(a)", "(b)", "u"]), lists.clone()),
+    ];
+    let prover = FirstOrderProver {
+        max_instantiations: 2,
+        ..FirstOrderProver::default()
+    };
+    for (i, spec) in RULES.iter().enumerate() {
+        assert_eq!(Some(spec), rule_spec(spec.name));
+        let (premises, conclusion) = match spec.schema {
+            Some(schema) => schema,
+            None => continue,
+        };
+        assert_eq!(spec.premises, Some(premises.len()));
+
+        // The principal formulas are where the specification says, the
+        // other formulas of the conclusion are its context, except for the
+        // formulas exchanged.
+        let conclusion = conclusion.parse::<Sequent>().unwrap();
+        let (ka, ks) = spec.principals;
+        let n = conclusion.succedent.len();
+        assert!(conclusion.antecedent[..ka].iter().all(|f| !is_context(f)));
+        assert!(conclusion.succedent[n - ks..]
+            .iter()
+            .all(|f| !is_context(f)));
+        if !spec.name.starts_with("Exchange") {
+            assert!(conclusion.antecedent[ka..].iter().all(is_context));
+            assert!(conclusion.succedent[..n - ks].iter().all(is_context));
+        }
+
+        for (inst, lists) in &valid {
+            let proof = inference(spec, inst, lists);
+            assert_eq!(spec.name, proof.rule_name());
+            assert_eq!(i, RULES.iter().position(|s| s == proof.spec()).unwrap());
+            assert!(proof.is_valid_inference(), "{}: {}", spec.name, proof);
+            // The contexts are carried over on both sides.
+            for (j, premise) in proof.premises().iter().enumerate() {
+                for left in [true, false] {
+                    let mut premises = (proof.premises().iter())
+                        .map(|p| (*p).clone())
+                        .collect::<Vec<_>>();
+                    let mut sequent = premise.last().clone();
+                    if left {
+                        sequent.antecedent.push(fml("X"));
+                    } else {
+                        sequent.succedent.insert(0, fml("X"));
+                    }
+                    premises[j] = LK::Axiom(sequent);
+                    let mutated = proof.with_premises(premises);
+                    assert!(!mutated.is_valid_inference(), "{}: {}", spec.name, mutated);
+                }
+            }
+        }
+
+        // Invertible rules have premises following from the conclusion, the
+        // others have an instance where some premise does not.
+        let proof = inference(spec, &valid[1].0, &valid[1].1);
+        let follows = proof.premises().iter().all(|p| {
+            let goal = Sequent {
+                antecedent: vec![proof.last().to_formula()],
+                succedent: vec![p.last().to_formula()],
+            };
+            prover.search(&goal).is_ok()
+        });
+        assert_eq!(spec.invertible, follows, "{}", spec.name);
+
+        // Instances breaking the side conditions.
+        let invalid = match spec.condition {
+            SideCondition::Eigenvariable => vec![
+                (
+                    inst("(P (a))", "(Q (b))", "(R z)", ["(a)", "(b)", "u"]),
+                    [fmls(&["(R u)"]), vec![], vec![], vec![]],
+                ),
+                (
+                    inst("(P (a))", "(Q (b))", "(R z)", ["(a)", "(b)", "u"]),
+                    [vec![], fmls(&["(R u)"]), vec![], vec![]],
+                ),
+                (
+                    inst("(P (a))", "(Q (b))", "(S z u)", ["(a)", "(b)", "u"]),
+                    empty(),
+                ),
+            ],
+            SideCondition::Substitutible => vec![(
+                inst("(P (a))", "(Q (b))", "(E w (S z w))", ["(a)", "w", "u"]),
+                empty(),
+            )],
+            _ => vec![],
+        };
+        for (inst, lists) in &invalid {
+            let proof = inference(spec, inst, lists);
+            assert!(!proof.is_valid_inference(), "{}: {}", spec.name, proof);
+        }
+    }
+}
+
+#[test]
+fn simplify_works() {
+    use crate::adapt::*;
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::solver::*;
+
+    let (a, b) = (pred!("A"), pred!("B"));
+    let axiom = LK::Axiom(sequent!(a.clone() => a.clone()));
+    // A weakening undone by a contraction.
+    let detour = LK::ContractionLeft(
+        Box::new(LK::WeakeningLeft(
+            Box::new(axiom.clone()),
+            sequent!(a.clone(), a.clone() => a.clone()),
+        )),
+        sequent!(a.clone() => a.clone()),
+    );
+    assert_eq!(axiom, detour.simplify());
+
+    // An exchange undone by another.
+    let swapped = LK::ExchangeLeft(
+        Box::new(LK::ExchangeLeft(
+            Box::new(LK::Axiom(
+                sequent!(a.clone(), b.clone() => a.clone(), b.clone()),
+            )),
+            sequent!(b.clone(), a.clone() => a.clone(), b.clone()),
+        )),
+        sequent!(a.clone(), b.clone() => a.clone(), b.clone()),
+    );
+    assert_eq!(1, swapped.simplify().size());
+
+    // The same sequent derived twice, the larger derivation is replaced.
+    let imp = implies!(a.clone(), a.clone());
+    let proof = LK::AndRight(
+        Box::new([
+            LK::ImpliesRight(Box::new(axiom.clone()), sequent!( => imp.clone())),
+            LK::ImpliesRight(Box::new(detour), sequent!( => imp.clone())),
+        ]),
+        sequent!( => and!(imp.clone(), imp.clone())),
+    );
+    assert_eq!(7, proof.size());
+    let simplified = proof.simplify();
+    assert_eq!(5, simplified.size());
+    assert_eq!(proof.last(), simplified.last());
+    assert!(simplified.is_valid_proof());
+
+    // Structural inferences added by `adapt` around a generated proof.
+    let fml: Formula = "(> (^ (p) (q)) (v (q) (r)))".parse().unwrap();
+    let generated = prove_with_lk(fml, 8, false).unwrap();
+    let mut target = generated.last().clone();
+    target.antecedent.extend([a.clone(), b.clone()]);
+    let weakened = adapt(generated.clone(), &target).unwrap();
+    let mut reversed = target.clone();
+    reversed.antecedent.reverse();
+    let padded = adapt(adapt(weakened.clone(), &reversed).unwrap(), &target).unwrap();
+    assert!(padded.size() > weakened.size());
+    let simplified = padded.simplify();
+    assert!(simplified.size() <= weakened.size());
+    assert_eq!(&target, simplified.last());
+    assert!(simplified.is_valid_proof());
+    assert_eq!(simplified, simplified.simplify());
+}
+
+#[test]
+fn midsequent_works() {
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::prover::*;
+    use crate::solver::*;
+
+    let fml = |s: &str| s.parse::<Formula>().unwrap();
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    let prover = FirstOrderProver::default();
+
+    // Two instances of the universal formula are needed.
+    let proof = prover
+        .search(&seq("(((V x (P x))) ((^ (P (a)) (P (b)))))"))
+        .unwrap();
+    let mid = proof.midsequent().unwrap();
+    assert!(mid.sequent.antecedent.contains(&fml("(P (a))")));
+    assert!(mid.sequent.antecedent.contains(&fml("(P (b))")));
+    assert_eq!(vec![fml("(^ (P (a)) (P (b)))")], mid.sequent.succedent);
+    let (universal, terms) = &mid.witnesses[0];
+    assert_eq!(&fml("(V x (P x))"), universal);
+    assert_eq!(2, terms.len());
+    assert!(prove_with_lk(mid.herbrand_disjunction(), 10, false).is_ok());
+
+    // The drinker, whose Herbrand disjunction has two instances.
+    let proof = prover
+        .search(&seq("(() ((E x (V y (> (P x) (P y))))))"))
+        .unwrap();
+    let mid = proof.midsequent().unwrap();
+    assert!(mid.sequent.antecedent.is_empty());
+    assert!(mid.sequent.succedent.len() >= 2);
+    let (_, terms) = &mid.witnesses[0];
+    for (instance, terms) in mid.sequent.succedent.iter().zip(terms) {
+        let mut matrix = fml("(> (P x) (P y))");
+        for (var, term) in ["x", "y"].iter().zip(terms) {
+            matrix = matrix.substitute(Term::Var(var.to_string()), term.clone());
+        }
+        assert_eq!(instance, &matrix);
+    }
+    assert!(prove_sequent_with_lk(mid.sequent, 10, false).is_ok());
+
+    let axiom = LK::Axiom(seq("((A) (A))"));
+    let cut = LK::Cut(Box::new([axiom.clone(), axiom]), seq("((A) (A))"));
+    assert_eq!(Err("The proof has cuts."), cut.midsequent());
+    let proof = prover
+        .search(&seq("(((~ (V x (P x)))) ((~ (V x (P x)))))"))
+        .unwrap();
+    assert_eq!(Err("The end sequent is not prenex."), proof.midsequent());
+}
+
+#[test]
+fn miniscope_works() {
+    use crate::fresh::*;
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::prover::*;
+
+    let fml = |s: &str| s.parse::<Formula>().unwrap();
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    for (input, expected) in [
+        ("(V x (^ (P x) (Q x)))", "(^ (V x (P x)) (V x (Q x)))"),
+        ("(E x (v (P x) (Q x)))", "(v (E x (P x)) (E x (Q x)))"),
+        ("(V x (v A (P x)))", "(v A (V x (P x)))"),
+        ("(E x (^ (P x) A))", "(^ (E x (P x)) A)"),
+        ("(V x (> (P x) A))", "(> (E x (P x)) A)"),
+        ("(E x (> (P x) (Q x)))", "(> (V x (P x)) (E x (Q x)))"),
+        (
+            "(V x (~ (v (P x) (Q x))))",
+            "(~ (v (E x (P x)) (E x (Q x))))",
+        ),
+        ("(V x (~ (R x x)))", "(V x (~ (R x x)))"),
+        ("(V x A)", "A"),
+        // The inner quantifier cannot move, the outer one is swapped in.
+        (
+            "(V x (V y (v (R x y) (P y))))",
+            "(V y (v (V x (R x y)) (P y)))",
+        ),
+        ("(V x (E y (^ (P x) (Q y))))", "(^ (V x (P x)) (E y (Q y)))"),
+        ("(<> (V x (P x)) A)", "(<> (V x (P x)) A)"),
+    ] {
+        assert_eq!(fml(expected), fml(input).miniscope());
+    }
+
+    // The result is equivalent to the formula.
+    let prover = FirstOrderProver::default();
+    for input in [
+        "(V x (E y (^ (P x) (Q y))))",
+        "(E x (> (P x) (Q x)))",
+        "(V x (V y (v (R x y) (P y))))",
+        "(V x (~ (v (P x) (Q x))))",
+        "(E x (V y (> (P x) (P y))))",
+    ] {
+        let goal = seq(&format!(
+            "(() ((<> {} {})))",
+            input,
+            fml(input).miniscope().to_sexpr()
+        ));
+        assert!(prover.search(&goal).is_ok(), "{}", input);
+    }
+
+    // Skolem functions lose the arguments of the quantifiers moved away.
+    let skolemized = fml("(V x (E y (^ (P x) (Q y))))")
+        .miniscope()
+        .skolemize(&mut SymbolGenerator::new());
+    assert_eq!(fml("(^ (V x (P x)) (Q (sk0)))"), skolemized);
+}
+
+#[test]
+fn interpolation_works() {
+    use crate::interpolation::*;
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::prover::*;
+    use crate::signature::*;
+
+    let fml = |s: &str| s.parse::<Formula>().unwrap();
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    let prover = FirstOrderProver::default();
+    let within = |fml: &Formula, part: &Sequent| {
+        let (symbols, own) = (fml.signature(), part.signature());
+        let vars = (part.antecedent.iter().chain(&part.succedent))
+            .flat_map(|f| f.get_free_vars())
+            .collect::<std::collections::HashSet<_>>();
+        symbols.functions.is_subset(&own.functions)
+            && symbols.constants.is_subset(&own.constants)
+            && symbols.predicates.is_subset(&own.predicates)
+            && fml.get_free_vars().is_subset(&vars)
+    };
+    for (goal, expected) in [
+        ("(((^ A B)) ((v B C)))", "B"),
+        ("(((V x (P x))) ((P (f (a)))))", "(V y0 (P y0))"),
+        ("(((P (a))) ((E x (P x))))", "(E y0 (P y0))"),
+        ("(((V x (^ (P x) (Q x)))) ((v (E y (P y)) R)))", ""),
+        ("(((^ (V x (R x (a))) A)) ((E x (R (b) x))))", ""),
+    ] {
+        let goal = seq(goal);
+        let proof = prover.search(&goal).unwrap();
+        let split = Split::antecedent_first(&goal);
+        let interpolant = proof.interpolant(&split, true).unwrap();
+        if !expected.is_empty() {
+            assert_eq!(fml(expected), interpolant.formula);
+        }
+        let (lhs, rhs) = split.parts(&goal);
+        assert!(
+            within(&interpolant.formula, &lhs),
+            "{}",
+            interpolant.formula
+        );
+        assert!(
+            within(&interpolant.formula, &rhs),
+            "{}",
+            interpolant.formula
+        );
+        let (left, right) = interpolant.proofs.unwrap();
+        assert_eq!(Ok(()), left.check());
+        assert_eq!(Ok(()), right.check());
+        assert_eq!(&interpolant.formula, left.last().suc_last());
+        assert_eq!(&interpolant.formula, right.last().ant_first());
+    }
+
+    // Any split of the sequent, here with the parts mixing both sides.
+    let goal = seq("(((> A B) A) (B C))");
+    let proof = prover.search(&goal).unwrap();
+    let split = Split {
+        antecedent: vec![true, false],
+        succedent: vec![true, false],
+    };
+    let interpolant = proof.interpolant(&split, true).unwrap();
+    assert_eq!(fml("(~ A)"), interpolant.formula);
+    assert!(interpolant.proofs.is_some());
+    assert!(proof.interpolant(&split, false).unwrap().proofs.is_none());
+
+    let axiom = LK::Axiom(seq("((A) (A))"));
+    let cut = LK::Cut(Box::new([axiom.clone(), axiom]), seq("((A) (A))"));
+    let split = Split::antecedent_first(cut.last());
+    assert_eq!(Err("The proof has cuts."), cut.interpolant(&split, false));
+}
+
+#[test]
+fn prune_works() {
+    use crate::language::*;
+    use crate::prover::*;
+    use crate::theory::*;
+
+    let fml = |s: &str| s.parse::<Formula>().unwrap();
+    let mut theory = Theory::new();
+    theory.add_named_axiom("mortal", fml("(V x (> (Man x) (Mortal x)))"));
+    theory.add_named_axiom("socrates", fml("(Man (s))"));
+    // `Greek` only occurs positively, so the axiom is trivially satisfied.
+    theory.add_named_axiom("greek", fml("(v (Greek (s)) (Philosopher (p)))"));
+    // Unrelated to the goal.
+    theory.add_named_axiom("zero", fml("(Nat (zero))"));
+    theory.add_named_axiom("succ", fml("(V x (> (Nat x) (Nat (succ x))))"));
+    theory.add_named_axiom("empty", fml("(V x (~ (In x (empty))))"));
+    let goal = fml("(Mortal (s))");
+    let (pruned, new_goal, report) = theory.prune(&goal);
+    assert_eq!(goal, new_goal);
+    assert_eq!(
+        vec![fml("(V x (> (Man x) (Mortal x)))"), fml("(Man (s))"),],
+        pruned.axioms
+    );
+    assert_eq!("socrates", pruned.name(1));
+    // `In` only occurs negatively, which makes `empty` trivial as well.
+    assert_eq!(vec![2, 5], report.trivial);
+    assert_eq!(vec![3, 4], report.unreachable);
+    let pure = report
+        .pure
+        .iter()
+        .map(|(sym, positive)| (sym.name.as_str(), *positive))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![("Greek", true), ("In", false), ("Philosopher", true)],
+        pure
+    );
+    let prover = FirstOrderProver::default();
+    assert!(prover.search(&pruned.sequent(new_goal)).is_ok());
+
+    // Predicates occurring in the goal count with the opposite polarity.
+    let theory = Theory::from(vec![fml("(P (a))")]);
+    let (pruned, goal, _) = theory.prune(&fml("(v (P (a)) (Q (a)))"));
+    assert_eq!(fml("(P (a))"), goal);
+    assert_eq!(vec![fml("(P (a))")], pruned.axioms);
+    let (pruned, goal, report) = theory.prune(&fml("(Q (a))"));
+    assert_eq!(Formula::Bottom, goal);
+    assert!(pruned.axioms.is_empty());
+    assert_eq!(vec![0], report.trivial);
+}
+
+#[test]
+fn minimize_works() {
+    use crate::minimize::*;
+    use crate::proof::*;
+    use crate::prover::*;
+
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    let prover = FirstOrderProver::default();
+    // A detour through a cut on an existential formula.
+    let lhs = prover
+        .search(&seq("(((V x (P x))) ((E y (P y))))"))
+        .unwrap();
+    let rhs = prover
+        .search(&seq("(((E y (P y)) (V x (> (P x) Q))) (Q))"))
+        .unwrap();
+    let target = seq("(((V x (P x)) (V x (> (P x) Q))) (Q))");
+    let proof = LK::Cut(Box::new([lhs, rhs]), target.clone());
+    assert!(proof.is_valid_proof());
+    let minimized = minimize(&proof, 8);
+    assert_eq!(&target, minimized.last());
+    assert!(minimized.is_valid_proof());
+    assert!(minimized.size() < proof.simplify().size());
+    assert!(minimized.is_cut_free());
+
+    // Without budget only `LK::simplify` is applied.
+    assert_eq!(proof.simplify(), minimize(&proof, 0));
+    let axiom = LK::Axiom(seq("((A) (A))"));
+    assert_eq!(axiom, minimize(&axiom, 8));
+}
+
+#[test]
+fn nd_works() {
+    use crate::language::*;
+    use crate::nd::*;
+    use crate::proof::*;
+    use crate::prover::*;
+
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    let assume = |s: &str| ND::Assumption(seq(s));
+    let swap = ND::AndIntro(
+        Box::new([
+            ND::AndElim2(
+                Box::new(assume("(((^ A B)) ((^ A B)))")),
+                seq("(((^ A B)) (B))"),
+            ),
+            ND::AndElim1(
+                Box::new(assume("(((^ A B)) ((^ A B)))")),
+                seq("(((^ A B)) (A))"),
+            ),
+        ]),
+        seq("(((^ A B)) ((^ B A)))"),
+    );
+    assert!(swap.is_valid_proof());
+    assert!(swap.is_intuitionistic());
+    let proof = nd_to_lk(&swap).unwrap();
+    assert_eq!(swap.last(), proof.last());
+    assert!(proof.is_valid_proof());
+    let implication = ND::ImpliesIntro(Box::new(swap), seq("(() ((> (^ A B) (^ B A))))"));
+    assert!(implication.is_valid_proof());
+    assert!(nd_to_lk(&implication).unwrap().is_valid_proof());
+
+    // Excluded middle, classically.
+    let em = "(v A (~ A))";
+    let refute = |premise: ND, s: &str| {
+        ND::NotElim(
+            Box::new([assume(&format!("(((~ {em})) ((~ {em})))")), premise]),
+            seq(s),
+        )
+    };
+    let inner = refute(
+        ND::OrIntro1(Box::new(assume("((A) (A))")), seq(&format!("((A) ({em}))"))),
+        &format!("(((~ {em}) A) (⊥))"),
+    );
+    let not_a = ND::NotIntro(Box::new(inner), seq(&format!("(((~ {em})) ((~ A)))")));
+    let outer = refute(
+        ND::OrIntro2(Box::new(not_a), seq(&format!("(((~ {em})) ({em}))"))),
+        &format!("(((~ {em})) (⊥))"),
+    );
+    let proof = ND::Raa(Box::new(outer), seq(&format!("(() ({em}))")));
+    assert!(proof.is_valid_proof());
+    assert!(!proof.is_intuitionistic());
+    assert!(nd_to_lk(&proof).unwrap().is_valid_proof());
+
+    // The eigenvariable may not be free in the assumptions.
+    let bad = ND::ForallIntro(
+        Box::new(assume("(((P y)) ((P y)))")),
+        seq("(((P y)) ((V x (P x))))"),
+    );
+    assert!(!bad.is_valid_proof());
+    assert_eq!(Err("Invalid inference."), nd_to_lk(&bad));
+    // Neither may assumptions be added when discharging.
+    let bad = ND::ImpliesIntro(Box::new(assume("((A) (A))")), seq("(() ((> B A)))"));
+    assert!(!bad.is_valid_proof());
+
+    let prover = FirstOrderProver::default();
+    for s in [
+        "(((V x (> (P x) (Q x))) (E x (P x))) ((E x (Q x))))",
+        "(((<> A B)) ((<> B A)))",
+        "(() (A (~ A)))",
+        "(((^ A (~ A))) ())",
+    ] {
+        let lk = prover.search(&seq(s)).expect(s);
+        let nd = lk_to_nd(&lk).unwrap();
+        assert!(nd.is_valid_proof(), "{}", s);
+        let disjunction = lk.last().succedent.iter().cloned().reduce(|l, r| or!(l, r));
+        assert_eq!(&disjunction.unwrap_or(Formula::Bottom), nd.conclusion());
+        for fml in &nd.last().antecedent {
+            assert!(lk.last().antecedent.contains(fml));
+        }
+        let back = nd_to_lk(&nd).unwrap();
+        assert!(back.is_valid_proof());
+    }
+    for script in [
+        "EqualityLeft (((= (a) (b)) (Q (b) (a))) ((Q (a) (a))))
+  Axiom (((Q (a) (a))) ((Q (a) (a))))",
+        "EqualityRight (((= (a) (b)) (P (a))) ((P (b))))
+  Axiom (((P (a))) ((P (a))))",
+    ] {
+        let nd = lk_to_nd(&LK::from_script(script).unwrap()).unwrap();
+        assert!(nd.is_valid_proof());
+        assert!(nd_to_lk(&nd).unwrap().is_valid_proof());
+    }
+    // Theory rules have no counterpart.
+    let axiom = LK::TheoryAxiom(seq("(() (A))"));
+    assert_eq!(Err("Theory rules are not supported."), lk_to_nd(&axiom));
+}
+
+#[test]
+fn session_works() {
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::prover::*;
+    use crate::session::*;
+
+    let mut session = Session::new();
+    session.goal("(((^ A B)) ((^ B A)))").unwrap();
+    session.apply(Tactic::AndRight).unwrap();
+    session.apply(Tactic::AndLeft2).unwrap();
+    session.apply(Tactic::Axiom).unwrap();
+    // Failed operations leave no trace.
+    assert!(session.apply(Tactic::OrLeft).is_err());
+    assert!(session.qed("swap").is_err());
+    session.apply(Tactic::AndLeft1).unwrap();
+    session.apply(Tactic::Axiom).unwrap();
+    assert!(session.qed("swap").unwrap().proof.is_valid_proof());
+
+    session.goal("(((V x (P x))) ((P (f (a)))))").unwrap();
+    session
+        .apply(Tactic::ForallLeft(func!("f", func!("a"))))
+        .unwrap();
+    session.apply(Tactic::Axiom).unwrap();
+    session.qed("instance").unwrap();
+    assert_eq!(
+        Err("A theorem has the name already."),
+        session.qed("instance").map(|_| ())
+    );
+
+    session
+        .goal("(((V x (> (P x) (Q x))) (P (a))) ((E y (Q y))))")
+        .unwrap();
+    session
+        .apply(Tactic::Cut("(Q (a))".parse().unwrap(), 2, 0))
+        .unwrap();
+    session.prove(&FirstOrderProver::default()).unwrap();
+    session.apply(Tactic::ExistsRight(func!("a"))).unwrap();
+    session.apply(Tactic::Axiom).unwrap();
+    session.qed("modus_ponens").unwrap();
+    let timeout = FirstOrderProver {
+        timeout: Some(std::time::Duration::from_secs(1)),
+        ..Default::default()
+    };
+    session.goal("((A) (A))").unwrap();
+    assert!(session.prove(&timeout).is_err());
+
+    let transcript = session.transcript();
+    assert_eq!(
+        Some("tactic forall_left (f (a))"),
+        transcript.lines().nth(8)
+    );
+    assert_eq!(Some("tactic cut 2 0 (Q (a))"), transcript.lines().nth(12));
+    assert_eq!(Some("prove 8 10000"), transcript.lines().nth(13));
+    let replayed = Session::replay(&transcript).unwrap();
+    assert_eq!(session.theorems(), replayed.theorems());
+    assert_eq!(session.operations(), replayed.operations());
+    assert_eq!(session.state(), replayed.state());
+    assert_eq!(transcript, replayed.transcript());
+
+    assert_eq!(
+        Err((3, "Unknown tactic.")),
+        Session::replay("goal ((A) (A))\n\ntactic assumption\n").map(|_| ())
+    );
+    assert_eq!(
+        Err((1, "No proof is under way.")),
+        Session::replay("tactic axiom").map(|_| ())
+    );
+    assert!("prove 8".parse::<Operation>().is_err());
+    assert_eq!(
+        Ok(Operation::Prove {
+            max_instantiations: 0,
+            max_steps: None
+        }),
+        "prove 0 none".parse()
+    );
+}
+
+#[test]
+fn hilbert_works() {
+    use crate::hilbert::*;
+    use crate::language::*;
+    use crate::proof::*;
+
+    let fml = |s: &str| s.parse::<Formula>().unwrap();
+    let line = |s: &str, justification| Line {
+        formula: fml(s),
+        justification,
+    };
+    use Justification::*;
+    // `A → A`.
+    let identity = Hilbert {
+        hypotheses: vec![],
+        lines: vec![
+            line(
+                "(> (> A (> (> A A) A)) (> (> A (> A A)) (> A A)))",
+                Axiom(HilbertAxiom::S),
+            ),
+            line("(> A (> (> A A) A))", Axiom(HilbertAxiom::K)),
+            line("(> (> A (> A A)) (> A A))", ModusPonens(1, 0)),
+            line("(> A (> A A))", Axiom(HilbertAxiom::K)),
+            line("(> A A)", ModusPonens(3, 2)),
+        ],
+    };
+    assert_eq!(Ok(()), identity.check());
+    let proof = identity.to_lk().unwrap();
+    assert!(proof.is_valid_proof());
+    assert_eq!(&identity.sequent().unwrap(), proof.last());
+    assert!(identity.to_nd().unwrap().is_valid_proof());
+
+    // `∀z (P z → Q z), ∀y P y ⊢ ∀x Q x`.
+    let quantified = Hilbert {
+        hypotheses: vec![fml("(V z (> (P z) (Q z)))"), fml("(V y (P y))")],
+        lines: vec![
+            line("(V y (P y))", Hypothesis(1)),
+            line("(> (V y (P y)) (P x))", Axiom(HilbertAxiom::Instantiation)),
+            line("(P x)", ModusPonens(0, 1)),
+            line("(V z (> (P z) (Q z)))", Hypothesis(0)),
+            line(
+                "(> (V z (> (P z) (Q z))) (> (P x) (Q x)))",
+                Axiom(HilbertAxiom::Instantiation),
+            ),
+            line("(> (P x) (Q x))", ModusPonens(3, 4)),
+            line("(Q x)", ModusPonens(2, 5)),
+            line("(V x (Q x))", Generalization(6)),
+        ],
+    };
+    assert_eq!(Ok(()), quantified.check());
+    let proof = quantified.to_lk().unwrap();
+    assert!(proof.is_valid_proof());
+    let nd = quantified.to_nd().unwrap();
+    assert!(nd.is_valid_proof());
+    assert_eq!(&fml("(V x (Q x))"), nd.conclusion());
+    let discharged = quantified.discharge().unwrap();
+    assert_eq!(Ok(()), discharged.check());
+    assert_eq!(
+        Some(&fml("(> (V y (P y)) (V x (Q x)))")),
+        discharged.conclusion()
+    );
+    let twice = discharged.discharge().unwrap();
+    assert_eq!(Ok(()), twice.check());
+    assert!(twice.hypotheses.is_empty());
+    assert!(twice.to_lk().unwrap().is_valid_proof());
+    assert_eq!(Err("The proof has no hypotheses."), twice.discharge());
+
+    for (axiom, s) in [
+        (
+            HilbertAxiom::OrElim,
+            "(> (> (P (a)) ⊥) (> (> Q ⊥) (> (v (P (a)) Q) ⊥)))",
+        ),
+        (
+            HilbertAxiom::DoubleNegation,
+            "(> (~ (~ (V x (P x)))) (V x (P x)))",
+        ),
+        (HilbertAxiom::TopIntro, "⊤"),
+        (HilbertAxiom::Witness, "(> (P (f (a))) (E x (P x)))"),
+        (
+            HilbertAxiom::ExistsElim,
+            "(> (V x (> (P x) Q)) (> (E x (P x)) Q))",
+        ),
+        (HilbertAxiom::Reflexivity, "(= (a) (a))"),
+        (
+            HilbertAxiom::Substitution,
+            "(> (= (a) (b)) (> (R (a) (a)) (R (a) (b))))",
+        ),
+    ] {
+        let proof = axiom.proof(&fml(s)).unwrap();
+        assert!(proof.is_valid_proof(), "{}", s);
+        assert_eq!(&Sequent::from(fml(s)), proof.last());
+    }
+    for (axiom, s) in [
+        (HilbertAxiom::K, "(> A (> B B))"),
+        // The variable is free on the left of the implication.
+        (
+            HilbertAxiom::Distribution,
+            "(> (V x (> (P x) (Q x))) (> (P x) (V x (Q x))))",
+        ),
+        (
+            HilbertAxiom::ExistsElim,
+            "(> (V x (> (P x) (Q x))) (> (E x (P x)) (Q x)))",
+        ),
+        (
+            HilbertAxiom::Instantiation,
+            "(> (V x (E y (P x y))) (E y (P y y)))",
+        ),
+    ] {
+        assert_eq!(None, axiom.proof(&fml(s)), "{}", s);
+    }
+
+    // Generalizing over a variable free in a hypothesis.
+    let unsound = Hilbert {
+        hypotheses: vec![fml("(P x)")],
+        lines: vec![
+            line("(P x)", Hypothesis(0)),
+            line("(V x (P x))", Generalization(0)),
+        ],
+    };
+    assert_eq!(
+        Err((1, "Generalized variable is free in a hypothesis.")),
+        unsound.check()
+    );
+    let backwards = Hilbert {
+        hypotheses: vec![],
+        lines: vec![line("A", ModusPonens(1, 2))],
+    };
+    assert_eq!(
+        Err((0, "Line refers to a line not before it.")),
+        backwards.check()
+    );
+    assert_eq!(Err("The proof is not valid."), backwards.to_lk());
+}
+
+#[test]
+fn proof_term_works() {
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::proof_term::*;
+    use crate::prover::*;
+
+    let seq = |s: &str| s.parse::<Sequent>().unwrap();
+    let script = "
+ImpliesRight (() ((> (^ A B) (^ B A))))
+  AndRight (((^ A B)) ((^ B A)))
+    AndLeft2 (((^ A B)) (B))
+      Axiom ((B) (B))
+    AndLeft1 (((^ A B)) (A))
+      Axiom ((A) (A))
+";
+    let proof = LK::from_script(script).unwrap();
+    assert!(proof.is_intuitionistic());
+    let term = proof.proof_term().unwrap();
+    assert_eq!("(λh0: (A ∧ B). ⟨(snd h0), (fst h0)⟩)", term.to_string());
+    assert_eq!(Ok(()), term.check(proof.last()));
+    assert!(term.check(&seq("(() ((> (^ A B) (^ A B))))")).is_err());
+
+    let prover = FirstOrderProver::default();
+    for s in [
+        "(((V x (> (P x) (Q x))) (E x (P x))) ((E x (Q x))))",
+        "(((v A B) (~ A)) (B))",
+        "(((<> A B) A) (B))",
+        "(() ((<> (^ A B) (^ B A))))",
+        "(((~ A) A) ())",
+        "(((E y (V x (R x y)))) ((V x (E y (R x y)))))",
+    ] {
+        let proof = prover.search(&seq(s)).unwrap();
+        if !proof.is_intuitionistic() {
+            assert_eq!(Err("The proof is not intuitionistic."), proof.proof_term());
+            continue;
+        }
+        let term = proof.proof_term().unwrap();
+        assert_eq!(Ok(()), term.check(&seq(s)), "{} {}", s, term);
+    }
+    let scripts = [
+        "
+ForallRight (((E y (V x (R x y)))) ((V x (E y (R x y)))))
+  ExistsLeft (((E y (V x (R x y)))) ((E y (R z y))))
+    ForallLeft (((V x (R x w))) ((E y (R z y))))
+      ExistsRight (((R z w)) ((E y (R z y))))
+        Axiom (((R z w)) ((R z w)))
+",
+        "
+OrLeft (((v A B) (~ A)) (B))
+  WeakeningRight ((A (~ A)) (B))
+    ExchangeLeft ((A (~ A)) ())
+      NotLeft (((~ A) A) ())
+        Axiom ((A) (A))
+  ExchangeLeft ((B (~ A)) (B))
+    WeakeningLeft (((~ A) B) (B))
+      Axiom ((B) (B))
+",
+    ];
+    for script in scripts {
+        let proof = LK::from_script(script).unwrap();
+        let term = proof.proof_term().unwrap();
+        assert_eq!(Ok(()), term.check(proof.last()), "{}", term);
+    }
+    let term = LK::from_script(scripts[1]).unwrap().proof_term().unwrap();
+    assert_eq!(
+        "(case h0 of inl h2 ⇒ (abort (h1 h2)) | inr h3 ⇒ h3)",
+        term.to_string()
+    );
+    let script = "
+EqualityRight (((= (a) (b)) (P (a))) ((P (b))))
+  Axiom (((P (a))) ((P (a))))
+";
+    let proof = LK::from_script(script).unwrap();
+    let term = proof.proof_term().unwrap();
+    assert_eq!("(rewrite h0 in h1)", term.to_string());
+    assert_eq!(Ok(()), term.check(proof.last()));
+    let excluded_middle = prover.search(&seq("(() ((v A (~ A))))")).unwrap();
+    assert_eq!(
+        Err("The proof is not intuitionistic."),
+        excluded_middle.proof_term()
+    );
+
+    // The eigenvariable may not occur in the hypotheses used.
+    let general = ProofTerm::Generalize(
+        var!("x"),
+        Box::new(ProofTerm::Var("h0".into())),
+        "(V x (P x))".parse().unwrap(),
+    );
+    assert!(general.check(&seq("(((P x)) ((V x (P x))))")).is_err());
+    let unused = ProofTerm::Generalize(
+        var!("x"),
+        Box::new(ProofTerm::Refl(var!("x"))),
+        "(V x (= x x))".parse().unwrap(),
+    );
+    assert_eq!(Ok(()), unused.check(&seq("(((P x)) ((V x (= x x))))")));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serialize_works() {
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::serialize::*;
+
+    let script = "
+ImpliesLeft (((> p q) p) (q))
+  Axiom ((p) (p))
+  Axiom ((q) (q))
+";
+    let proof = LK::from_script(script).unwrap();
+    let json = to_json(&proof).unwrap();
+    assert!(json.starts_with(r#"{"version":2,"#));
+    assert_eq!(Ok(proof.clone()), from_json::<LK>(&json));
+
+    let fml: Formula = "(V x (E y (= (f x) y)))".parse().unwrap();
+    assert_eq!(Ok(fml.clone()), from_json(&to_json(&fml).unwrap()));
+    let seq = proof.last().clone();
+    assert_eq!(Ok(seq.clone()), from_json(&to_json(&seq).unwrap()));
+
+    let newer = json.replacen(r#""version":2"#, r#""version":3"#, 1);
+    assert_eq!(Err(FormatError::Version(3)), from_json::<LK>(&newer));
+    assert!(matches!(
+        from_json::<LK>(r#"{"version":2,"content":{}}"#),
+        Err(FormatError::Syntax(_))
+    ));
+
+    assert!(json.contains(r#""premises":[0,1]"#));
+    for broken in [
+        json.replace(r#""premises":[0,1]"#, r#""premises":[0,0]"#),
+        json.replace(r#""premises":[0,1]"#, r#""premises":[0,2]"#),
+        json.replace(r#""premises":[0,1]"#, r#""premises":[0]"#),
+    ] {
+        assert!(matches!(
+            from_json::<LK>(&broken),
+            Err(FormatError::Syntax(_))
+        ));
+    }
+
+    // Proofs of any depth are read and written without recursion.
+    let mut deep = LK::WeakeningLeft(
+        Box::new(LK::Axiom(sequent!(pred!("p") => pred!("p")))),
+        sequent!(pred!("q"), pred!("p") => pred!("p")),
+    );
+    for i in 0..10_000 {
+        let sequent = if i % 2 == 0 {
+            sequent!(pred!("p"), pred!("q") => pred!("p"))
+        } else {
+            sequent!(pred!("q"), pred!("p") => pred!("p"))
+        };
+        deep = LK::ExchangeLeft(Box::new(deep), sequent);
+    }
+    let json = to_json(&deep).unwrap();
+    let read = from_json::<LK>(&json).unwrap();
+    assert!(read.is_valid_proof());
+    assert_eq!(Ok(json), to_json(&read));
+
+    // Formulas are nested at most `MAX_DEPTH` deep, as their handling recurses.
+    let mut fml = Formula::Top;
+    let mut written = vec![];
+    while let Ok(json) = to_json(&fml) {
+        written.push((fml.clone(), json));
+        fml = not!(fml);
+    }
+    assert_eq!(Err(FormatError::TooDeep), to_json(&fml));
+    assert_eq!(MAX_DEPTH, written.len());
+    for (fml, json) in written {
+        assert_eq!(Ok(fml), from_json(&json));
+    }
+    let n = 200_000;
+    let deep = format!(
+        r#"{{"version":2,"content":{}"Top"{}}}"#,
+        r#"{"Not":"#.repeat(n),
+        "}".repeat(n)
+    );
+    assert!(matches!(
+        from_json::<Formula>(&deep),
+        Err(FormatError::Syntax(_))
+    ));
+}
+
+#[test]
+fn binary_works() {
+    use crate::binary::*;
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::prover::FirstOrderProver;
+
+    let goal = "(((V x (> (P x) (Q x))) (V x (P x))) ((V x (Q x))))";
+    let proof = FirstOrderProver::default()
+        .search(&goal.parse().unwrap())
+        .unwrap();
+    let mut bytes = vec![];
+    proof.write_binary(&mut bytes).unwrap();
+    assert_eq!(b"RFOL", &bytes[..4]);
+    assert_eq!(BINARY_VERSION, bytes[4]);
+    assert_eq!(proof, LK::read_binary(&mut &bytes[..]).unwrap());
+    assert!(bytes.len() < format!("{:?}", proof).len() / 4);
+
+    let theory = LK::TheoryRule(
+        vec![proof.clone(), proof.clone(), proof],
+        "((A) (A))".parse().unwrap(),
+    );
+    let mut bytes = vec![];
+    theory.write_binary(&mut bytes).unwrap();
+    assert_eq!(theory, LK::read_binary(&mut &bytes[..]).unwrap());
+
+    let truncated = &bytes[..bytes.len() - 1];
+    assert!(LK::read_binary(&mut &truncated[..]).is_err());
+    let mut newer = bytes.clone();
+    newer[4] += 1;
+    assert_eq!(
+        std::io::ErrorKind::InvalidData,
+        LK::read_binary(&mut &newer[..]).unwrap_err().kind()
+    );
+    // A `TheoryRule` claiming 2^40 premises.
+    let mut huge = b"RFOL".to_vec();
+    huge.extend([
+        BINARY_VERSION,
+        0,
+        0,
+        0,
+        26,
+        0x80,
+        0x80,
+        0x80,
+        0x80,
+        0x80,
+        0x20,
+    ]);
+    assert_eq!(
+        std::io::ErrorKind::InvalidData,
+        LK::read_binary(&mut &huge[..]).unwrap_err().kind()
+    );
+    // Formula tables of `p` followed by `entry(i)` for the previous index `i`.
+    let table = |count: usize, entry: fn(usize) -> Vec<usize>| {
+        let mut numbers = vec![1, 1, b'p' as usize, 0, count + 1, 2, 0, 0];
+        for i in 0..count {
+            numbers.extend(entry(i));
+        }
+        let mut bytes = b"RFOL".to_vec();
+        bytes.push(BINARY_VERSION);
+        for mut n in numbers {
+            while n >= 0x80 {
+                bytes.push((n & 0x7f) as u8 | 0x80);
+                n >>= 7;
+            }
+            bytes.push(n as u8);
+        }
+        bytes
+    };
+    // `(^ f f)` chained 40 times is small, but expands to 2^40 formulas.
+    let doubling = table(40, |i| vec![3, 3, i, i]);
+    let start = std::time::Instant::now();
+    let err = LK::read_binary(&mut &doubling[..]).unwrap_err();
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    assert_eq!(std::io::ErrorKind::InvalidData, err.kind());
+    assert_eq!("proof too large", err.to_string());
+    let negations = table(100_000, |i| vec![2, 2, i]);
+    let err = LK::read_binary(&mut &negations[..]).unwrap_err();
+    assert_eq!(std::io::ErrorKind::InvalidData, err.kind());
+    assert_eq!("nesting too deep", err.to_string());
+
+    // Proofs of any depth are read and written without recursion.
+    let mut deep = LK::WeakeningLeft(
+        Box::new(LK::Axiom(sequent!(pred!("p") => pred!("p")))),
+        sequent!(pred!("q"), pred!("p") => pred!("p")),
+    );
+    for i in 0..100_000 {
+        let sequent = if i % 2 == 0 {
+            sequent!(pred!("p"), pred!("q") => pred!("p"))
+        } else {
+            sequent!(pred!("q"), pred!("p") => pred!("p"))
+        };
+        deep = LK::ExchangeLeft(Box::new(deep), sequent);
+    }
+    let mut bytes = vec![];
+    deep.write_binary(&mut bytes).unwrap();
+    let read = LK::read_binary(&mut &bytes[..]).unwrap();
+    assert!(read.is_valid_proof());
+    let mut again = vec![];
+    read.write_binary(&mut again).unwrap();
+    assert_eq!(bytes, again);
+}
+
+#[test]
+fn latex_works() {
+    use crate::language::*;
+
+    let fml: Formula = "(V x (> (~ (= (f x) (a))) (v (my_pred x) ⊥)))"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        r"\forall x\, (\neg f(x) = a \rightarrow (\mathit{my\_pred}(x) \lor \bot))",
+        fml.to_latex()
+    );
+    let fml: Formula = "(E x1 (^ (R% x1) ⊤))".parse().unwrap();
+    assert_eq!(
+        r"\exists \mathit{x1}\, (\mathit{R\%}(\mathit{x1}) \land \top)",
+        fml.to_latex()
+    );
+}
+
+#[test]
+fn dot_works() {
+    use crate::graph::DotOptions;
+    use crate::proof::LK;
+
+    let script = "
+AndRight (((^ p p)) ((^ p p)))
+  ContractionLeft (((^ p p)) (p))
+    AndLeft1 (((^ p p) (^ p p)) (p))
+      ExchangeLeft ((p (^ p p)) (p))
+        WeakeningLeft (((^ p p) p) (p))
+          Axiom ((p) (p))
+  ContractionLeft (((^ p p)) (p))
+    AndLeft1 (((^ p p) (^ p p)) (p))
+      ExchangeLeft ((p (^ p p)) (p))
+        WeakeningLeft (((^ p p) p) (p))
+          Axiom ((p) (p))
+";
+    let proof = LK::from_script(script).unwrap();
+    let dot = proof.to_dot(&DotOptions::default());
+    assert!(dot.starts_with("digraph proof {\n  rankdir=BT;\n"));
+    assert!(dot.contains("  n0 [label=\"p ⇒  p\", xlabel=\"ax\"];"));
+    // The two identical premises are drawn once.
+    assert_eq!(
+        6,
+        dot.matches(" [label=\"").count() - dot.matches(" -> ").count()
+    );
+    assert!(dot.contains("  n5 -> n4 [label=\"∧R\"];"));
+
+    let collapsed = proof.to_dot(&DotOptions {
+        collapse_structural: true,
+        ..DotOptions::default()
+    });
+    assert!(collapsed.contains("  n1 [label=\"(p ∧ p) ⇒  p\"];"));
+    assert!(collapsed.contains("  n1 -> n0 [label=\"∧L1 (cL)\"];"));
+    assert!(collapsed.contains("  n2 -> n1 [label=\"∧R\"];"));
+    assert!(collapsed.contains("  n0 [label=\"p, (p ∧ p) ⇒  p\", xlabel=\"ax (xL, wL)\"];"));
+}
+
+#[test]
+fn html_works() {
+    use crate::proof::LK;
+
+    let script = "
+ImpliesRight (() ((> (R&S) (R&S))))
+  Axiom (((R&S)) ((R&S)))
+";
+    let proof = LK::from_script(script).unwrap();
+    let fragment = proof.to_html_fragment();
+    assert!(fragment.starts_with("<ul class=\"proof\">\n  <li><details open>\n"));
+    assert!(fragment.contains(
+        "<li class=\"leaf\">\\(\\mathit{R\\&amp;S} \\Rightarrow \\mathit{R\\&amp;S}\\)<span class=\"rule\">ax</span></li>"
+    ));
+    assert_eq!(1, fragment.matches("<details open>").count());
+    let html = proof.to_html();
+    assert!(html.contains(&fragment));
+    assert!(html.contains("<title>⇒  (R&amp;S → R&amp;S)</title>"));
+}
+
+#[test]
+fn tptp_works() {
+    use crate::language::*;
+    use crate::proof::LK;
+    use crate::resolution::ResolutionProver;
+
+    let fml: Formula = "(V x (> (^ (P x) (~ (= (f x) (a)))) (E y' (v (Q x y') ⊤))))"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        "! [X] : (('P'(X) & f(X) != a) => ? [XXy_27_] : ('Q'(X,XXy_27_) | $true))",
+        fml.to_tptp()
+    );
+    let fml: Formula = "(~ (R (g x) z))".parse().unwrap();
+    assert_eq!("~ 'R'(g(X),Z)", fml.to_tptp());
+    assert_eq!("! [X,Z] : ~ 'R'(g(X),Z)", fml.to_tptp_closed());
+    let fml: Formula = "(is-even X)".parse().unwrap();
+    assert_eq!("'is-even'(XXX)", fml.to_tptp());
+
+    let script = "
+ImpliesRight (() ((> (P x) (P x))))
+  Axiom (((P x)) ((P x)))
+";
+    let proof = LK::from_script(script).unwrap();
+    assert_eq!(
+        "% SZS output start Proof
+fof(f0, plain, ! [X] : ('P'(X) => 'P'(X)), inference(axiom, [status(thm)], [])).
+fof(f1, plain, ! [X] : ('P'(X) => 'P'(X)), inference(implies_right, [status(thm)], [f0])).
+% SZS output end Proof",
+        proof.to_tstp()
+    );
+
+    let clauses = "(^ (P (a)) (V x (> (P x) (Q x))))"
+        .parse::<Formula>()
+        .unwrap()
+        .to_cnf();
+    let negated = "(~ (Q (a)))".parse::<Formula>().unwrap().to_cnf();
+    let clauses = [clauses, negated].concat();
+    let refutation = ResolutionProver::default().refute(&clauses).unwrap();
+    assert_eq!(
+        "% SZS output start CNFRefutation
+cnf(c0, axiom, 'P'(a), file('input', i0)).
+cnf(c1, axiom, 'Q'(X0) | ~ 'P'(X0), file('input', i1)).
+cnf(c2, axiom, ~ 'Q'(a), file('input', i2)).
+cnf(c3, plain, 'Q'(a), inference(resolution, [status(thm)], [c0,c1])).
+cnf(c4, plain, $false, inference(resolution, [status(thm)], [c3,c2])).
+% SZS output end CNFRefutation",
+        refutation.to_tstp()
+    );
+}
+
+#[test]
+fn tstp_import_works() {
+    use crate::language::*;
+    use crate::proof::*;
+    use crate::prover::FirstOrderProver;
+    use crate::resolution::ResolutionProver;
+    use crate::tptp::*;
+
+    let input = "
+% SZS output start CNFRefutation
+cnf(c_0_0, axiom, (p(X1)|~q(X1)), file('/tmp/problem.p', ax1)).
+cnf(c_0_1, axiom, q(a), file('/tmp/problem.p', ax2)).
+cnf(c_0_2, negated_conjecture, ~p(a), file('/tmp/problem.p', goal)).
+/* resolution */
+cnf(c_0_3, plain, (p(a)), inference(spm,[status(thm)],[c_0_0, c_0_1])).
+cnf(c_0_4, negated_conjecture, ($false), inference(sr,[status(thm)],[inference(rw,[status(thm)],[c_0_2, c_0_3]), theory(equality)]), ['proof']).
+% SZS output end CNFRefutation
+";
+    let derivation = parse_tstp(input).unwrap();
+    assert_eq!(5, derivation.steps.len());
+    let last = &derivation.steps[4];
+    assert_eq!(Some("sr"), last.rule.as_deref());
+    assert_eq!(Some("thm"), last.status.as_deref());
+    assert_eq!(vec![2, 3], last.parents);
+    assert!(last.is_clause && last.formula == Formula::Bottom);
+    assert_eq!(
+        "∀X1 (p(X1) ∨ ¬q(X1))",
+        derivation.steps[0].closure().to_string()
+    );
+
+    let prover = FirstOrderProver::default();
+    let proof = derivation.to_lk(&prover).unwrap();
+    assert!(proof.is_valid_proof());
+    let expected = "(((V X1 (v (p X1) (~ (q X1)))) (q (a)) (~ (p (a)))) (⊥))";
+    assert_eq!(expected.parse::<Sequent>().unwrap(), *proof.last());
+
+    // The negated conjecture is an assumption; Skolemization does not give
+    // a consequence.
+    let input = "
+fof(ax, axiom, ?[X]:q(X), file('p.p', ax)).
+fof(goal, conjecture, q(a), file('p.p', goal)).
+fof(c_0_1, negated_conjecture, ~q(a), inference(assume_negation,[status(cth)],[goal])).
+fof(c_0_2, plain, q(a), inference(skolemize,[status(esa)],[ax])).
+fof(c_0_3, plain, $false, inference(cn,[status(thm)],[c_0_1, c_0_2])).
+";
+    let derivation = parse_tstp(input).unwrap();
+    assert!(derivation.steps[2].is_assumption());
+    assert_eq!(
+        Err(("c_0_2".to_string(), "Step not proved.")),
+        derivation.to_lk(&prover)
+    );
+
+    // Refutations written by `Refutation::to_tstp` are read back.
+    let clauses = "(^ (P (a)) (^ (V x (> (P x) (Q x))) (~ (Q (a)))))"
+        .parse::<Formula>()
+        .unwrap()
+        .to_cnf();
+    let refutation = ResolutionProver::default().refute(&clauses).unwrap();
+    let derivation = parse_tstp(&refutation.to_tstp()).unwrap();
+    assert!(derivation.to_lk(&prover).unwrap().is_valid_proof());
+
+    assert_eq!(Err("Unknown parent."), parse_tstp("cnf(a, plain, p, a)."));
+    assert_eq!(
+        Err("Nesting too deep."),
+        parse_tstp(&format!(
+            "fof(a, axiom, {}p{}).",
+            "(".repeat(300),
+            ")".repeat(300)
+        ))
+    );
+}
+
+#[test]
+fn structure_works() {
+    use crate::language::*;
+    use crate::model::*;
+
+    let mut z3 = Structure::new(vec![0u8, 1, 2]);
+    z3.interpret_func(nlsym!("plus", 2), |args| (args[0] + args[1]) % 3);
+    z3.interpret_func(nlsym!("zero", 0), |_| 0);
+    z3.interpret_pred(nlsym!("Odd", 1), |args| args[0] % 2 == 1);
+    let parse = |s: &str| s.parse::<Formula>().unwrap();
+    assert!(z3.satisfies(&parse("(V x (V y (= (plus x y) (plus y x))))")));
+    assert!(z3.satisfies(&parse("(V x (E y (= (plus x y) (zero))))")));
+    assert!(z3.satisfies(&parse("(= (plus x (zero)) x)")));
+    assert!(!z3.satisfies(&parse("(V x (> (Odd x) (Odd (plus x x))))")));
+
+    let fml = parse("(^ (Odd x) (E x (~ (Odd x))))");
+    assert!(fml.eval(&z3, &assign![var!("x") => 1]));
+    assert!(!fml.eval(&z3, &assign![var!("x") => 2]));
+    assert_eq!(
+        2,
+        func!("plus", var!("x"), var!("x")).eval(&z3, &assign![var!("x") => 1])
+    );
+
+    let mut people = Structure::new(vec!["ann", "bob"]);
+    people.interpret_pred(nlsym!("Likes", 2), |args| args[0] != args[1]);
+    assert!(people.satisfies(&parse("(V x (E y (Likes x y)))")));
+    assert!(!people.satisfies(&parse("(E x (Likes x x))")));
+
+    let mut model = FiniteModel::new(2);
+    model.assign_pred(nlsym!("p", 1), assign![[0] => true, [1] => false]);
+    let structure = Structure::from(model.clone());
+    let fml = parse("(E x (^ (p x) (V y (> (p y) (= x y)))))");
+    assert_eq!(model.evaluate_formula(&fml), structure.satisfies(&fml));
+    assert!(structure.satisfies(&fml));
+}
+
+#[test]
+fn find_model_works() {
+    use crate::language::*;
+    use crate::model::*;
+
+    let parse = |s: &str| s.parse::<Formula>().unwrap();
+    let derangement = parse("(V x (~ (= (f x) x)))");
+    let model = find_model(std::slice::from_ref(&derangement), 4).unwrap();
+    assert_eq!(2, model.domain.len());
+    assert!(model.satisfies(&derangement));
+
+    let axioms = [
+        parse("(V x (> (P x) (Q x)))"),
+        parse("(E x (P x))"),
+        parse("(R (a) (b))"),
+    ];
+    let conjecture = parse("(V x (> (Q x) (P x)))");
+    let mut fmls = axioms.to_vec();
+    fmls.push(parse("(~ (V x (> (Q x) (P x))))"));
+    let countermodel = find_model(&fmls, 4).unwrap();
+    assert_eq!(2, countermodel.domain.len());
+    assert!(axioms.iter().all(|fml| countermodel.satisfies(fml)));
+    assert!(!countermodel.satisfies(&conjecture));
+
+    // Free variables are read universally.
+    let model = find_model(&[parse("(= x (c))"), parse("(P (c))")], 3).unwrap();
+    assert_eq!(1, model.domain.len());
+
+    assert!(find_model(&[parse("(^ (P (a)) (~ (P (a))))")], 4).is_none());
+    // An injective function that is not surjective only has infinite models.
+    let dedekind = [
+        parse("(V x (V y (> (= (s x) (s y)) (= x y))))"),
+        parse("(E x (V y (~ (= (s y) x))))"),
+    ];
+    assert!(find_model(&dedekind, 4).is_none());
+}
+
+#[test]
+fn herbrand_works() {
+    use crate::ground::*;
+    use crate::language::*;
+    use crate::signature::*;
+
+    let parse = |s: &str| s.parse::<Formula>().unwrap();
+    let signature = parse("(> (P (a)) (Q (f x) (b)))").signature();
+    let universe = signature
+        .herbrand_universe(1)
+        .iter()
+        .map(Term::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(vec!["a", "b", "f(a)", "f(b)"], universe);
+    assert_eq!(2, signature.herbrand_universe(0).len());
+    assert_eq!(6, signature.herbrand_universe(2).len());
+    let base = signature.herbrand_base(0);
+    assert_eq!(2 + 4, base.len());
+    assert_eq!("P(a)", base[0].to_string());
+
+    // Without constants an arbitrary one is made up.
+    let universe = parse("(P (g x x))").signature().herbrand_universe(1);
+    assert_eq!(2, universe.len());
+    assert_eq!(
+        vec![Term::Func("g".into(), vec![universe[0].clone(); 2])],
+        universe[1..]
+    );
+
+    let terms = signature.herbrand_universe(0);
+    let instances = parse("(R x y)").ground_instances(&terms);
+    assert_eq!(4, instances.len());
+    assert!(instances.iter().all(|fml| fml.get_free_vars().is_empty()));
+    assert_eq!("R(a,b)", instances[1].to_string());
+    assert_eq!(
+        vec![parse("(P (a))")],
+        parse("(P (a))").ground_instances(&terms)
+    );
+
+    let clause = parse("(v (P x) (~ (Q x (f y))))").to_cnf().pop().unwrap();
+    let instances = clause_instances(&clause, &terms);
+    assert_eq!(4, instances.len());
+    for (sub, instance) in instances {
+        assert_eq!(2, sub.len());
+        assert_eq!(2, instance.len());
+        assert!(instance.iter().all(|l| l.atom.get_free_vars().is_empty()));
+    }
+}
+
+#[test]
+fn truth_table_works() {
+    use crate::language::*;
+    use crate::truth_table::*;
+
+    let parse = |s: &str| s.parse::<Formula>().unwrap();
+    let table = parse("(> p (v q p))").truth_table().unwrap();
+    assert_eq!(vec!["p", "q"], table.atoms);
+    assert_eq!(4, table.rows.len());
+    assert_eq!((vec![true, false], true), table.rows[1]);
+    assert_eq!(Classification::Tautology, table.classification());
+
+    let table = parse("(^ long (~ p))").truth_table().unwrap();
+    assert_eq!(Classification::Contingency, table.classification());
+    assert_eq!(
+        "long p | (long ∧ ¬p)\nT    T | F\nT    F | T\nF    T | F\nF    F | F\n",
+        table.to_string()
+    );
+    let table = parse("(<> p (~ p))").truth_table().unwrap();
+    assert_eq!(Classification::Contradiction, table.classification());
+    let table = parse("⊤").truth_table().unwrap();
+    assert_eq!(vec![(vec![], true)], table.rows);
+
+    assert!(parse("(P x)").truth_table().is_err());
+    assert!(parse("(V x p)").truth_table().is_err());
+    assert!(parse("(= (a) (a))").truth_table().is_err());
+}
+
+#[test]
+fn bdd_works() {
+    use crate::language::*;
+    use crate::truth_table::*;
+
+    let parse = |s: &str| s.parse::<Formula>().unwrap();
+    let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+    let pq = names(&["p", "q"]);
+    let implication = parse("(> p q)").to_bdd(&pq).unwrap();
+    assert_eq!(implication, parse("(v (~ p) q)").to_bdd(&pq).unwrap());
+    assert_ne!(implication, parse("(> q p)").to_bdd(&pq).unwrap());
+    assert_eq!(4, implication.node_count());
+    assert_eq!(
+        Some(vec![("p".to_string(), false)]),
+        implication.satisfying_assignment()
+    );
+
+    let tautology = parse("(> (^ (> p q) p) q)").to_bdd(&pq).unwrap();
+    assert!(tautology.is_tautology());
+    assert_eq!(parse("⊤").to_bdd(&pq).unwrap(), tautology);
+    let contradiction = parse("(^ p (~ p))").to_bdd(&pq).unwrap();
+    assert!(!contradiction.is_satisfiable());
+    assert_eq!(None, contradiction.satisfying_assignment());
+
+    // The size of a BDD depends on the ordering.
+    let fml = parse("(v (^ a1 b1) (^ a2 b2))");
+    let good = fml.to_bdd(&names(&["a1", "b1", "a2", "b2"])).unwrap();
+    let bad = fml.to_bdd(&names(&["a1", "a2", "b1", "b2"])).unwrap();
+    assert_eq!((6, 8), (good.node_count(), bad.node_count()));
+    assert_eq!(
+        Classification::Contingency,
+        fml.truth_table().unwrap().classification()
+    );
+    assert!(good.is_satisfiable() && !good.is_tautology());
+
+    assert!(parse("(~ (v (~ p) (~ q)))")
+        .is_equivalent_by_bdd(&parse("(^ q p)"))
+        .unwrap());
+    assert!(parse("(<> (^ p q) (~ (v (~ p) (~ q))))")
+        .is_equivalent_by_bdd(&parse("⊤"))
+        .unwrap());
+    assert!(!parse("(> p q)").is_equivalent_by_bdd(&parse("q")).unwrap());
+    assert!(parse("(^ p r)").to_bdd(&pq).is_err());
+    assert!(parse("(P x)").to_bdd(&pq).is_err());
+}
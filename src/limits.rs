@@ -0,0 +1,47 @@
+use crate::language::*;
+use crate::parser::*;
+use crate::proof::*;
+use crate::tokenizer::*;
+
+pub const TOO_MANY_TOKENS: &str = "Too many tokens.";
+pub const TOO_DEEP: &str = "Nesting too deep.";
+pub const TOO_MANY_NODES: &str = "Too many proof nodes.";
+
+/// Bounds on the size of input read from untrusted sources, enforced by
+/// `Tokenizer::try_tokenize`, the `Parser` and `LK::check_with`. `None` is
+/// no bound, the default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Limits {
+    pub max_tokens: Option<usize>,
+    /// Most formulas and terms nested in one another.
+    pub max_depth: Option<usize>,
+    pub max_proof_nodes: Option<usize>,
+}
+
+impl Limits {
+    /// Bounds generous for hand-written input that keep the parser well
+    /// within the stack of a default thread.
+    pub fn untrusted() -> Limits {
+        Limits {
+            max_tokens: Some(100_000),
+            max_depth: Some(256),
+            max_proof_nodes: Some(100_000),
+        }
+    }
+
+    /// Never panics, as long as the limits are at most those of `untrusted`.
+    pub fn parse_formula(&self, s: &str) -> Result<Formula, &'static str> {
+        let tokens = Tokenizer::with_limits(self).try_tokenize(s)?;
+        let mut parser = Parser::with_limits(self);
+        let fml = parser.parse(&tokens)?;
+        at_end(&mut parser, fml)
+    }
+
+    /// Never panics, as long as the limits are at most those of `untrusted`.
+    pub fn parse_sequent(&self, s: &str) -> Result<Sequent, &'static str> {
+        let tokens = Tokenizer::with_limits(self).try_tokenize(s)?;
+        let mut parser = Parser::with_limits(self);
+        let sequent = parser.parse_sequent(&tokens)?;
+        at_end(&mut parser, sequent)
+    }
+}
@@ -0,0 +1,170 @@
+use crate::language::*;
+use crate::proof::*;
+use std::collections::HashSet;
+
+/// Something suspicious reported by `lint`. Findings never make a proof or a
+/// formula wrong; they point at parts that can be dropped.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Finding {
+    /// A quantified subformula whose variable does not occur free in its
+    /// body.
+    VacuousQuantifier(Formula),
+    /// A formula of the end sequent's antecedent that no axiom or logical
+    /// inference acts on, i.e. it is only ever weakened in.
+    UnusedHypothesis(Formula),
+    /// An axiom sequent closing more than one leaf, with the number of
+    /// leaves.
+    DuplicateAxiom(Sequent, usize),
+}
+
+pub trait Lint: crate::sealed::Sealed {
+    fn lint(&self) -> Vec<Finding>;
+}
+
+/// Findings for a formula, a sequent or a proof, in the order they are met.
+pub fn lint<T: Lint + ?Sized>(target: &T) -> Vec<Finding> {
+    target.lint()
+}
+
+fn _vacuous_quantifiers(fml: &Formula, findings: &mut Vec<Finding>) {
+    match fml {
+        Formula::Forall(var, body) | Formula::Exists(var, body) => {
+            let finding = Finding::VacuousQuantifier(fml.clone());
+            if !body.get_free_vars().contains(var) && !findings.contains(&finding) {
+                findings.push(finding);
+            }
+            _vacuous_quantifiers(body, findings);
+        }
+        Formula::Not(fml) => _vacuous_quantifiers(fml, findings),
+        Formula::And(lhs, rhs)
+        | Formula::Or(lhs, rhs)
+        | Formula::Implies(lhs, rhs)
+        | Formula::Iff(lhs, rhs) => {
+            _vacuous_quantifiers(lhs, findings);
+            _vacuous_quantifiers(rhs, findings);
+        }
+        Formula::Pred(_, _) | Formula::Equal(_, _) | Formula::Top | Formula::Bottom => {}
+    }
+}
+
+// Whether the occurrence at `antecedent[i]` of the conclusion of `proof` is
+// traced up to an axiom or to the principal formula of a logical inference.
+fn _uses_hypothesis(proof: &LK, i: usize) -> bool {
+    use LK::*;
+    let premises = proof.premises();
+    let uses = |k: usize, i: usize| _uses_hypothesis(premises[k], i);
+    match proof {
+        Axiom(_) | TheoryRule(_, _) | TheoryAxiom(_) => true,
+        WeakeningLeft(_, _) => i > 0 && uses(0, i - 1),
+        ContractionLeft(_, _) => {
+            if i == 0 {
+                uses(0, 0) || uses(0, 1)
+            } else {
+                uses(0, i + 1)
+            }
+        }
+        ExchangeLeft(_, _) => {
+            let premise = &premises[0].last().antecedent;
+            let conclusion = &proof.last().antecedent;
+            match (0..premise.len()).find(|&k| premise[k] != conclusion[k]) {
+                Some(k) if i == k => uses(0, k + 1),
+                Some(k) if i == k + 1 => uses(0, k),
+                _ => uses(0, i),
+            }
+        }
+        WeakeningRight(_, _)
+        | ContractionRight(_, _)
+        | ExchangeRight(_, _)
+        | OrRight1(_, _)
+        | OrRight2(_, _)
+        | ForallRight(_, _)
+        | ExistsRight(_, _) => uses(0, i),
+        AndRight(_, _) => uses(0, i) || uses(1, i),
+        AndLeft1(_, _) | AndLeft2(_, _) | ForallLeft(_, _) | ExistsLeft(_, _) => {
+            i == 0 || uses(0, i)
+        }
+        OrLeft(_, _) => i == 0 || uses(0, i) || uses(1, i),
+        NotLeft(_, _) => i == 0 || uses(0, i - 1),
+        EqualityLeft(_, _) => i <= 1 || uses(0, i - 1),
+        EqualityRight(_, _) => i == 0 || uses(0, i - 1),
+        ImpliesRight(_, _) | NotRight(_, _) => uses(0, i + 1),
+        IffRight(_, _) => uses(0, i + 1) || uses(1, i + 1),
+        IffLeft(_, _) => i == 0 || uses(0, i + 1) || uses(1, i - 1),
+        // A→B, Γ, Π ⇒ Δ, Σ from Γ ⇒ Δ, A and B, Π ⇒ Σ.
+        ImpliesLeft(_, _) => {
+            let n = premises[0].last().antecedent.len();
+            i == 0
+                || if i <= n {
+                    uses(0, i - 1)
+                } else {
+                    uses(1, i - n)
+                }
+        }
+        // Γ, Π ⇒ Δ, Σ from Γ ⇒ Δ, A and A, Π ⇒ Σ.
+        Cut(_, _) => {
+            let n = premises[0].last().antecedent.len();
+            if i < n {
+                uses(0, i)
+            } else {
+                uses(1, i - n + 1)
+            }
+        }
+    }
+}
+
+impl Lint for Formula {
+    fn lint(&self) -> Vec<Finding> {
+        let mut findings = vec![];
+        _vacuous_quantifiers(self, &mut findings);
+        findings
+    }
+}
+
+impl Lint for Sequent {
+    fn lint(&self) -> Vec<Finding> {
+        let mut findings = vec![];
+        for fml in self.antecedent.iter().chain(&self.succedent) {
+            _vacuous_quantifiers(fml, &mut findings);
+        }
+        findings
+    }
+}
+
+impl Lint for LK {
+    /// Vacuous quantifiers anywhere in the proof, end sequent first, then
+    /// unused hypotheses of the end sequent, then duplicate axiom leaves.
+    fn lint(&self) -> Vec<Finding> {
+        let mut findings = self.last().lint();
+        let mut axioms: Vec<(Sequent, usize)> = vec![];
+        self.replay(
+            &mut |rule: &'static str, _: &[&Sequent], conclusion: &Sequent| {
+                for finding in conclusion.lint() {
+                    if !findings.contains(&finding) {
+                        findings.push(finding);
+                    }
+                }
+                if rule == "Axiom" {
+                    match axioms.iter_mut().find(|(s, _)| s == conclusion) {
+                        Some((_, n)) => *n += 1,
+                        None => axioms.push((conclusion.clone(), 1)),
+                    }
+                }
+            },
+        );
+
+        let mut reported = HashSet::new();
+        for (i, fml) in self.last().antecedent.iter().enumerate() {
+            if !_uses_hypothesis(self, i) && reported.insert(fml) {
+                findings.push(Finding::UnusedHypothesis(fml.clone()));
+            }
+        }
+
+        findings.extend(
+            axioms
+                .into_iter()
+                .filter(|(_, n)| *n > 1)
+                .map(|(s, n)| Finding::DuplicateAxiom(s, n)),
+        );
+        findings
+    }
+}
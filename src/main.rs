@@ -1,18 +1,11 @@
-#[allow(unused_macros)]
-#[macro_use]
-mod language;
-mod model;
-mod parser;
-#[allow(unused_macros)]
-#[macro_use]
-mod proof;
-mod solver;
-mod tokenizer;
 extern crate clap;
+extern crate rfol;
 use clap::{App, Arg, SubCommand};
+use rfol::{parser, solver, tokenizer};
 
 fn main() {
-    let app = App::new("rfol")
+    #[allow(unused_mut)]
+    let mut app = App::new("rfol")
         .version("0.0.0")
         .author("kalgr <hoge@fuga.com>")
         .about("RFOL CLI")
@@ -61,11 +54,61 @@ fn main() {
                         .help("include cut rule in proofs to be searched")
                         .short("c")
                         .long("use_cut"),
+                )
+                .arg(
+                    Arg::with_name("ascii")
+                        .help("print the proof without unicode symbols")
+                        .long("ascii"),
+                )
+                .arg(
+                    Arg::with_name("width")
+                        .help("wrap sequents wider than this many characters")
+                        .long("width")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("labels")
+                        .help("rule label scheme")
+                        .long("labels")
+                        .takes_value(true)
+                        .possible_values(&["symbolic", "named", "gentzen"]),
+                )
+                .arg(
+                    Arg::with_name("record")
+                        .help("write the explored search states as JSONL to this file")
+                        .long("record")
+                        .takes_value(true),
                 ),
         );
+    #[cfg(feature = "serve")]
+    {
+        app = app.subcommand(
+            SubCommand::with_name("serve")
+                .about("serve /check, /prove and /normalize over HTTP")
+                .arg(
+                    Arg::with_name("addr")
+                        .help("address to listen on")
+                        .long("addr")
+                        .takes_value(true)
+                        .default_value("127.0.0.1:8080"),
+                ),
+        );
+    }
 
     let matches = app.get_matches();
 
+    #[cfg(feature = "serve")]
+    {
+        if let Some(matches) = matches.subcommand_matches("serve") {
+            let addr = matches.value_of("addr").unwrap();
+            if let Err(e) = rfol::serve::serve(addr) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+    }
+
     if let Some(ref matches) = matches.subcommand_matches("refute") {
         if let (Some(fml), Some(max_domain_size_str)) = (
             matches.value_of("input"),
@@ -105,8 +148,29 @@ fn main() {
                 Ok(fml) => {
                     use solver::*;
                     let use_cut = matches.is_present("use_cut");
-                    if let Ok(proof) = prove_with_lk(fml, max_proof_depth, use_cut) {
-                        println!("{}", proof);
+                    let (result, steps) =
+                        prove_with_lk_recorded(fml, max_proof_depth, use_cut, &Unguided);
+                    if let Some(path) = matches.value_of("record") {
+                        let mut file = std::fs::File::create(path).unwrap();
+                        rfol::record::write_jsonl(&steps, &mut file).unwrap();
+                    }
+                    if let Ok(proof) = result {
+                        use rfol::language::Notation;
+                        use rfol::proof::{LabelScheme, RenderOptions};
+                        let options = RenderOptions {
+                            notation: if matches.is_present("ascii") {
+                                Notation::Ascii
+                            } else {
+                                Notation::Unicode
+                            },
+                            max_width: matches.value_of("width").map(|w| w.parse().unwrap()),
+                            labels: match matches.value_of("labels") {
+                                Some("named") => LabelScheme::Named,
+                                Some("gentzen") => LabelScheme::Gentzen,
+                                _ => LabelScheme::Symbolic,
+                            },
+                        };
+                        println!("{}", proof.render(&options));
                     } else {
                         println!("No proof found.");
                     }
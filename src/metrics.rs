@@ -0,0 +1,55 @@
+use crate::language::*;
+
+impl Formula {
+    /// Height of the syntax tree, counting atoms, `⊤` and `⊥` as 1.
+    pub fn depth(&self) -> u32 {
+        match self {
+            Formula::Not(fml) | Formula::Forall(_, fml) | Formula::Exists(_, fml) => {
+                1 + fml.depth()
+            }
+            Formula::And(lhs, rhs)
+            | Formula::Or(lhs, rhs)
+            | Formula::Implies(lhs, rhs)
+            | Formula::Iff(lhs, rhs) => 1 + lhs.depth().max(rhs.depth()),
+            Formula::Pred(_, _) | Formula::Equal(_, _) | Formula::Top | Formula::Bottom => 1,
+        }
+    }
+
+    /// Number of nodes of the syntax tree. Terms are not counted.
+    pub fn size(&self) -> usize {
+        match self {
+            Formula::Not(fml) | Formula::Forall(_, fml) | Formula::Exists(_, fml) => 1 + fml.size(),
+            Formula::And(lhs, rhs)
+            | Formula::Or(lhs, rhs)
+            | Formula::Implies(lhs, rhs)
+            | Formula::Iff(lhs, rhs) => 1 + lhs.size() + rhs.size(),
+            Formula::Pred(_, _) | Formula::Equal(_, _) | Formula::Top | Formula::Bottom => 1,
+        }
+    }
+
+    /// Maximal number of nested quantifiers.
+    pub fn quantifier_rank(&self) -> u32 {
+        match self {
+            Formula::Forall(_, fml) | Formula::Exists(_, fml) => 1 + fml.quantifier_rank(),
+            Formula::Not(fml) => fml.quantifier_rank(),
+            Formula::And(lhs, rhs)
+            | Formula::Or(lhs, rhs)
+            | Formula::Implies(lhs, rhs)
+            | Formula::Iff(lhs, rhs) => lhs.quantifier_rank().max(rhs.quantifier_rank()),
+            Formula::Pred(_, _) | Formula::Equal(_, _) | Formula::Top | Formula::Bottom => 0,
+        }
+    }
+
+    /// Number of occurrences of `¬`, `∧`, `∨`, `→` and `↔`.
+    pub fn count_connectives(&self) -> usize {
+        match self {
+            Formula::Not(fml) => 1 + fml.count_connectives(),
+            Formula::Forall(_, fml) | Formula::Exists(_, fml) => fml.count_connectives(),
+            Formula::And(lhs, rhs)
+            | Formula::Or(lhs, rhs)
+            | Formula::Implies(lhs, rhs)
+            | Formula::Iff(lhs, rhs) => 1 + lhs.count_connectives() + rhs.count_connectives(),
+            Formula::Pred(_, _) | Formula::Equal(_, _) | Formula::Top | Formula::Bottom => 0,
+        }
+    }
+}
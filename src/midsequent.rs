@@ -0,0 +1,132 @@
+use crate::language::*;
+use crate::proof::*;
+
+/// The quantifier-free instances of the formulas of a prenex end sequent in
+/// a cut-free proof of it. By the midsequent theorem `sequent` is a
+/// tautology, from which the end sequent follows by quantifier inferences
+/// and contractions alone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Midsequent {
+    /// The quantifier-free formulas of the end sequent, then the instances
+    /// of the matrices of the others, each on the side of its formula.
+    pub sequent: Sequent,
+    /// For each formula of the end sequent, antecedent first, the terms
+    /// instantiating its quantifiers in each of its instances in `sequent`,
+    /// in the order of its prefix.
+    pub witnesses: Vec<(Formula, Vec<Vec<Term>>)>,
+}
+
+impl Midsequent {
+    /// The Herbrand disjunction: the disjunction of the instances of the
+    /// succedent and the negated ones of the antecedent, as an implication.
+    pub fn herbrand_disjunction(&self) -> Formula {
+        self.sequent.to_formula()
+    }
+}
+
+// The variables of the quantifier prefix of a prenex formula and its
+// matrix, or `None` if the formula is not prenex.
+fn _prefix(fml: &Formula) -> Option<(Vec<Term>, &Formula)> {
+    let mut vars = vec![];
+    let mut matrix = fml;
+    while let Formula::Forall(var, body) | Formula::Exists(var, body) = matrix {
+        vars.push(var.clone());
+        matrix = body;
+    }
+    if _is_quantifier_free(matrix) {
+        Some((vars, matrix))
+    } else {
+        None
+    }
+}
+
+fn _is_quantifier_free(fml: &Formula) -> bool {
+    !fml.iter_subformulas()
+        .any(|f| matches!(f, Formula::Forall(_, _) | Formula::Exists(_, _)))
+}
+
+// The terms making `instance` the matrix of `fml` with its prefix `vars`
+// instantiated, the variables of the prefix not occurring in the matrix
+// standing for themselves.
+fn _witness(
+    fml: &Formula,
+    vars: &[Term],
+    matrix: &Formula,
+    instance: &Formula,
+) -> Option<Vec<Term>> {
+    let sub = instance.matches(matrix)?;
+    let free = fml.get_free_vars();
+    if free.iter().any(|v| sub.get(v).is_some_and(|t| t != v)) {
+        return None;
+    }
+    Some(
+        vars.iter()
+            .map(|v| sub.get(v).unwrap_or(v).clone())
+            .collect(),
+    )
+}
+
+impl LK {
+    /// The midsequent of a cut-free proof of a prenex sequent: the
+    /// quantifier-free formulas the quantifier inferences of the proof
+    /// instantiate quantified formulas with, together with the
+    /// quantifier-free formulas of the end sequent.
+    pub fn midsequent(&self) -> Result<Midsequent, &'static str> {
+        if !self.is_cut_free() {
+            return Err("The proof has cuts.");
+        }
+        let end = self.last();
+        let prefixes = (end.antecedent.iter().map(|f| (true, f)))
+            .chain(end.succedent.iter().map(|f| (false, f)))
+            .map(|(left, f)| Some((left, f, _prefix(f)?)))
+            .collect::<Option<Vec<_>>>()
+            .ok_or("The end sequent is not prenex.")?;
+        let mut sequent = Sequent {
+            antecedent: (end.antecedent.iter())
+                .filter(|f| _is_quantifier_free(f))
+                .cloned()
+                .collect(),
+            succedent: (end.succedent.iter())
+                .filter(|f| _is_quantifier_free(f))
+                .cloned()
+                .collect(),
+        };
+        let mut stack = vec![self];
+        while let Some(proof) = stack.pop() {
+            let premises = proof.premises();
+            stack.extend(premises.iter().rev());
+            let (left, instance) = match proof {
+                LK::ForallLeft(premise, _) | LK::ExistsLeft(premise, _) => {
+                    (true, premise.last().ant_first())
+                }
+                LK::ForallRight(premise, _) | LK::ExistsRight(premise, _) => {
+                    (false, premise.last().suc_last())
+                }
+                _ => continue,
+            };
+            let side = if left {
+                &mut sequent.antecedent
+            } else {
+                &mut sequent.succedent
+            };
+            if _is_quantifier_free(instance) && !side.contains(instance) {
+                side.push(instance.clone());
+            }
+        }
+        let witnesses = prefixes
+            .into_iter()
+            .map(|(left, fml, (vars, matrix))| {
+                let side = if left {
+                    &sequent.antecedent
+                } else {
+                    &sequent.succedent
+                };
+                let terms = (side.iter())
+                    .filter_map(|instance| _witness(fml, &vars, matrix, instance))
+                    .collect();
+                (fml.clone(), terms)
+            })
+            .collect();
+        Ok(Midsequent { sequent, witnesses })
+    }
+}
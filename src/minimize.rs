@@ -0,0 +1,31 @@
+use crate::proof::*;
+use crate::solver::*;
+
+// Searches again for the subproofs of `proof`, the root first, keeping the
+// proofs found when smaller. Those found are not searched into again, as
+// they are the lowest the search gives.
+fn _minimize(proof: LK, budget: u32) -> LK {
+    let height = proof.height();
+    if height > 1 {
+        if let Ok(found) = prove_sequent_with_lk(proof.last().clone(), height.min(budget), false) {
+            let found = found.simplify();
+            if found.size() < proof.size() {
+                return found;
+            }
+        }
+    }
+    let premises = (proof.premises().into_iter())
+        .map(|p| _minimize(p.clone(), budget))
+        .collect::<Vec<_>>();
+    proof.with_premises(premises)
+}
+
+/// A proof of the same end sequent no larger than `proof`, with subproofs
+/// replaced by smaller ones found by searching for their end sequents
+/// again with `prove_sequent_with_lk`, outer subproofs before inner ones.
+/// Each search is for a cut-free proof no higher than `budget` nor than the
+/// subproof it would replace, so the time taken grows exponentially with
+/// `budget`. The proof is simplified before and after, see `LK::simplify`.
+pub fn minimize(proof: &LK, budget: u32) -> LK {
+    _minimize(proof.simplify(), budget).simplify()
+}
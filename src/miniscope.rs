@@ -0,0 +1,97 @@
+use crate::language::*;
+
+fn _quantify(forall: bool, var: Term, body: Formula) -> Formula {
+    if forall {
+        Formula::Forall(var, Box::new(body))
+    } else {
+        Formula::Exists(var, Box::new(body))
+    }
+}
+
+// Moves the quantifier over `var` into `body`, which is miniscoped already,
+// as far as it goes.
+fn _push(forall: bool, var: &Term, body: Formula) -> Formula {
+    let free = |fml: &Formula| fml.get_free_vars().contains(var);
+    if !free(&body) {
+        return body;
+    }
+    match body {
+        Formula::And(lhs, rhs) if forall => Formula::And(
+            Box::new(_push(forall, var, *lhs)),
+            Box::new(_push(forall, var, *rhs)),
+        ),
+        Formula::Or(lhs, rhs) if !forall => Formula::Or(
+            Box::new(_push(forall, var, *lhs)),
+            Box::new(_push(forall, var, *rhs)),
+        ),
+        Formula::And(lhs, rhs) if !free(&lhs) => {
+            Formula::And(lhs, Box::new(_push(forall, var, *rhs)))
+        }
+        Formula::And(lhs, rhs) if !free(&rhs) => {
+            Formula::And(Box::new(_push(forall, var, *lhs)), rhs)
+        }
+        Formula::Or(lhs, rhs) if !free(&lhs) => {
+            Formula::Or(lhs, Box::new(_push(forall, var, *rhs)))
+        }
+        Formula::Or(lhs, rhs) if !free(&rhs) => {
+            Formula::Or(Box::new(_push(forall, var, *lhs)), rhs)
+        }
+        // The antecedent of an implication is of the opposite polarity.
+        Formula::Implies(lhs, rhs) if !free(&lhs) => {
+            Formula::Implies(lhs, Box::new(_push(forall, var, *rhs)))
+        }
+        Formula::Implies(lhs, rhs) if !free(&rhs) => {
+            Formula::Implies(Box::new(_push(!forall, var, *lhs)), rhs)
+        }
+        Formula::Implies(lhs, rhs) if !forall => Formula::Implies(
+            Box::new(_push(true, var, *lhs)),
+            Box::new(_push(false, var, *rhs)),
+        ),
+        // Negations are crossed only if the quantifier moves further in.
+        Formula::Not(fml) => {
+            let pushed = _push(!forall, var, (*fml).clone());
+            if pushed == _quantify(!forall, var.clone(), (*fml).clone()) {
+                _quantify(forall, var.clone(), Formula::Not(fml))
+            } else {
+                Formula::Not(Box::new(pushed))
+            }
+        }
+        // Quantifiers of the same kind are swapped if the outer one moves
+        // further in, the inner one is then pushed again.
+        Formula::Forall(inner, fml) | Formula::Exists(inner, fml)
+            if matches!(body, Formula::Forall(_, _)) == forall =>
+        {
+            let pushed = _push(forall, var, (*fml).clone());
+            if pushed == _quantify(forall, var.clone(), (*fml).clone()) {
+                _quantify(forall, var.clone(), _quantify(forall, inner, *fml))
+            } else {
+                _push(forall, &inner, pushed)
+            }
+        }
+        body => _quantify(forall, var.clone(), body),
+    }
+}
+
+impl Formula {
+    /// Moves every quantifier as far into its scope as it goes, the reverse
+    /// of prenexing: universal quantifiers are distributed over conjunctions
+    /// and existential ones over disjunctions, quantifiers are moved past
+    /// the subformulas their variable does not occur in and over negations,
+    /// and vacuous ones are removed. The result is equivalent to the
+    /// formula. Skolemizing it gives Skolem functions of fewer arguments.
+    pub fn miniscope(&self) -> Formula {
+        let sub = |fml: &Formula| Box::new(fml.miniscope());
+        match self {
+            Formula::Not(fml) => Formula::Not(sub(fml)),
+            Formula::And(lhs, rhs) => Formula::And(sub(lhs), sub(rhs)),
+            Formula::Or(lhs, rhs) => Formula::Or(sub(lhs), sub(rhs)),
+            Formula::Implies(lhs, rhs) => Formula::Implies(sub(lhs), sub(rhs)),
+            Formula::Iff(lhs, rhs) => Formula::Iff(sub(lhs), sub(rhs)),
+            Formula::Forall(var, body) => _push(true, var, body.miniscope()),
+            Formula::Exists(var, body) => _push(false, var, body.miniscope()),
+            Formula::Pred(_, _) | Formula::Equal(_, _) | Formula::Top | Formula::Bottom => {
+                self.clone()
+            }
+        }
+    }
+}
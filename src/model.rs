@@ -1,5 +1,11 @@
+use crate::cnf::*;
+use crate::fresh::*;
 use crate::language::*;
-use std::collections::HashMap;
+use crate::proof::*;
+use crate::sat::*;
+use crate::theory::*;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct FiniteModel {
@@ -38,6 +44,33 @@ impl FiniteModel {
         let tmp = self.pred_assignment.get_mut(&s).unwrap();
         tmp.extend(assign);
     }
+
+    /// Whether every axiom of `theory` holds for all values of its free
+    /// variables.
+    pub fn validates(&self, theory: &Theory) -> bool {
+        let mut model = self.clone();
+        theory.axioms.iter().all(|axiom| {
+            let closure = axiom
+                .get_free_vars()
+                .into_iter()
+                .fold(axiom.clone(), |fml, var| {
+                    Formula::Forall(var, Box::new(fml))
+                });
+            model.evaluate_formula(&closure)
+        })
+    }
+}
+
+impl Sequent {
+    /// Whether some formula of the antecedent is false or some formula of
+    /// the succedent is true in `model`, with the free variables taking the
+    /// values of `assignment`, which must cover all of them.
+    pub fn satisfied_in(&self, model: &FiniteModel, assignment: &HashMap<Term, u32>) -> bool {
+        let mut model = model.clone();
+        model.assign_var(assignment.clone());
+        let mut holds = |fml: &Formula| model.evaluate_formula(fml);
+        !self.antecedent.iter().all(&mut holds) || self.succedent.iter().any(holds)
+    }
 }
 
 pub trait Model {
@@ -83,14 +116,28 @@ impl Model for FiniteModel {
             Formula::And(lhs, rhs) => self.evaluate_formula(lhs) && self.evaluate_formula(rhs),
             Formula::Or(lhs, rhs) => self.evaluate_formula(lhs) || self.evaluate_formula(rhs),
             Formula::Implies(lhs, rhs) => !self.evaluate_formula(lhs) || self.evaluate_formula(rhs),
-            Formula::Forall(Term::Var(name), bfml) => (0..self.domain_size).all(|v| {
-                self.var_assignment.insert(Term::Var(name.into()), v);
-                self.evaluate_formula(bfml)
-            }),
-            Formula::Exists(Term::Var(name), bfml) => (0..self.domain_size).any(|v| {
-                self.var_assignment.insert(Term::Var(name.into()), v);
-                self.evaluate_formula(bfml)
-            }),
+            Formula::Iff(lhs, rhs) => self.evaluate_formula(lhs) == self.evaluate_formula(rhs),
+            Formula::Forall(var @ Term::Var(_), bfml)
+            | Formula::Exists(var @ Term::Var(_), bfml) => {
+                // The variable gets its value outside the quantifier back.
+                let outer = self.var_assignment.get(var).copied();
+                let mut domain = 0..self.domain_size;
+                let mut holds = |v| {
+                    self.var_assignment.insert(var.clone(), v);
+                    self.evaluate_formula(bfml)
+                };
+                let value = match fml {
+                    Formula::Forall(_, _) => domain.all(&mut holds),
+                    _ => domain.any(&mut holds),
+                };
+                match outer {
+                    Some(v) => self.var_assignment.insert(var.clone(), v),
+                    None => self.var_assignment.remove(var),
+                };
+                value
+            }
+            Formula::Top => true,
+            Formula::Bottom => false,
             _ => {
                 assert!(false);
                 false
@@ -98,3 +145,406 @@ impl Model for FiniteModel {
         }
     }
 }
+
+/// Values of variables in a `Structure`.
+pub type Assignment<D> = HashMap<Term, D>;
+
+/// A structure with a finite domain of values of type `D` and tabulated
+/// interpretations of the function and predicate symbols, the general form
+/// of `FiniteModel`, whose domain is `0..domain_size`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Structure<D: Eq + Hash> {
+    pub domain: Vec<D>,
+    pub funcs: HashMap<NonLogicalSymbol, HashMap<Vec<D>, D>>,
+    pub preds: HashMap<NonLogicalSymbol, HashMap<Vec<D>, bool>>,
+}
+
+// All tuples of `arity` elements of `domain`.
+fn _tuples<D: Clone>(domain: &[D], arity: u32) -> Vec<Vec<D>> {
+    (0..arity).fold(vec![vec![]], |tuples, _| {
+        tuples
+            .iter()
+            .flat_map(|tuple| {
+                domain.iter().map(move |d| {
+                    let mut tuple = tuple.clone();
+                    tuple.push(d.clone());
+                    tuple
+                })
+            })
+            .collect()
+    })
+}
+
+impl<D: Clone + Eq + Hash> Structure<D> {
+    /// A structure over `domain` interpreting no symbols.
+    pub fn new(domain: Vec<D>) -> Structure<D> {
+        Structure {
+            domain,
+            funcs: HashMap::new(),
+            preds: HashMap::new(),
+        }
+    }
+
+    /// Interprets `s` by the table of `f` over the domain.
+    pub fn interpret_func<F: Fn(&[D]) -> D>(&mut self, s: NonLogicalSymbol, f: F) {
+        let table = _tuples(&self.domain, s.arity)
+            .into_iter()
+            .map(|args| {
+                let value = f(&args);
+                (args, value)
+            })
+            .collect();
+        self.funcs.insert(s, table);
+    }
+
+    /// Interprets `s` by the table of `f` over the domain.
+    pub fn interpret_pred<F: Fn(&[D]) -> bool>(&mut self, s: NonLogicalSymbol, f: F) {
+        let table = _tuples(&self.domain, s.arity)
+            .into_iter()
+            .map(|args| {
+                let value = f(&args);
+                (args, value)
+            })
+            .collect();
+        self.preds.insert(s, table);
+    }
+
+    /// Whether `fml` holds for all values of its free variables.
+    pub fn satisfies(&self, fml: &Formula) -> bool {
+        let mut vars = fml.get_free_vars().into_iter().collect::<Vec<_>>();
+        vars.sort_by_key(|var| var.to_string());
+        let closure = vars
+            .into_iter()
+            .fold(fml.clone(), |fml, var| forall!(var, fml));
+        closure.eval(self, &Assignment::new())
+    }
+}
+
+impl From<FiniteModel> for Structure<u32> {
+    /// The structure over `0..domain_size` interpreting the symbols of
+    /// `model` as it does. The variable assignment of `model` is dropped.
+    fn from(model: FiniteModel) -> Structure<u32> {
+        Structure {
+            domain: (0..model.domain_size).collect(),
+            funcs: model.func_assignment,
+            preds: model.pred_assignment,
+        }
+    }
+}
+
+impl Term {
+    /// The value of the term in `structure` with the variables taking their
+    /// values in `assignment`. Panics if a variable has no value or a symbol
+    /// no interpretation for the arguments.
+    pub fn eval<D: Clone + Eq + Hash>(
+        &self,
+        structure: &Structure<D>,
+        assignment: &Assignment<D>,
+    ) -> D {
+        match self {
+            Term::Var(_) => assignment[self].clone(),
+            Term::Func(name, args) => {
+                let func = nlsym!(name.clone(), args.len() as u32);
+                let args = args
+                    .iter()
+                    .map(|arg| arg.eval(structure, assignment))
+                    .collect::<Vec<_>>();
+                structure.funcs[&func][&args].clone()
+            }
+        }
+    }
+}
+
+impl Formula {
+    fn _eval<D: Clone + Eq + Hash>(
+        &self,
+        structure: &Structure<D>,
+        assignment: &mut Assignment<D>,
+    ) -> bool {
+        match self {
+            Formula::Pred(name, args) => {
+                let pred = nlsym!(name.clone(), args.len() as u32);
+                let args = args
+                    .iter()
+                    .map(|arg| arg.eval(structure, assignment))
+                    .collect::<Vec<_>>();
+                structure.preds[&pred][&args]
+            }
+            Formula::Equal(lhs, rhs) => {
+                lhs.eval(structure, assignment) == rhs.eval(structure, assignment)
+            }
+            Formula::Not(fml) => !fml._eval(structure, assignment),
+            Formula::And(lhs, rhs) => {
+                lhs._eval(structure, assignment) && rhs._eval(structure, assignment)
+            }
+            Formula::Or(lhs, rhs) => {
+                lhs._eval(structure, assignment) || rhs._eval(structure, assignment)
+            }
+            Formula::Implies(lhs, rhs) => {
+                !lhs._eval(structure, assignment) || rhs._eval(structure, assignment)
+            }
+            Formula::Iff(lhs, rhs) => {
+                lhs._eval(structure, assignment) == rhs._eval(structure, assignment)
+            }
+            Formula::Forall(var, fml) | Formula::Exists(var, fml) => {
+                let forall = matches!(self, Formula::Forall(_, _));
+                let previous = assignment.remove(var);
+                let mut holds = forall;
+                for d in &structure.domain {
+                    assignment.insert(var.clone(), d.clone());
+                    if fml._eval(structure, assignment) != forall {
+                        holds = !forall;
+                        break;
+                    }
+                }
+                assignment.remove(var);
+                assignment.extend(previous.map(|d| (var.clone(), d)));
+                holds
+            }
+            Formula::Top => true,
+            Formula::Bottom => false,
+        }
+    }
+
+    /// The truth value of the formula in `structure` with the free variables
+    /// taking their values in `assignment`. Panics like `Term::eval`.
+    pub fn eval<D: Clone + Eq + Hash>(
+        &self,
+        structure: &Structure<D>,
+        assignment: &Assignment<D>,
+    ) -> bool {
+        self._eval(structure, &mut assignment.clone())
+    }
+}
+
+// A literal of a flattened clause, over numbered variables: every function
+// application is named by a variable `r` with a literal `f(args) ≠ r`.
+enum Flat {
+    Pred(NonLogicalSymbol, Vec<usize>, bool),
+    Equal(usize, usize, bool),
+    Def(NonLogicalSymbol, Vec<usize>, usize),
+}
+
+// The variable of `term`, numbering the variables of the clause and the
+// function applications in it as they are met.
+fn _flatten_term(
+    term: &Term,
+    vars: &mut HashMap<Term, usize>,
+    count: &mut usize,
+    flat: &mut Vec<Flat>,
+) -> usize {
+    match term {
+        Term::Var(_) => *vars.entry(term.clone()).or_insert_with(|| {
+            *count += 1;
+            *count - 1
+        }),
+        Term::Func(name, args) => {
+            let args = args
+                .iter()
+                .map(|arg| _flatten_term(arg, vars, count, flat))
+                .collect::<Vec<_>>();
+            *count += 1;
+            flat.push(Flat::Def(
+                nlsym!(name.clone(), args.len() as u32),
+                args,
+                *count - 1,
+            ));
+            *count - 1
+        }
+    }
+}
+
+// The flattened literals of `clause` and the number of variables they use.
+fn _flatten(clause: &Clause) -> (Vec<Flat>, usize) {
+    let mut vars = HashMap::new();
+    let mut count = 0;
+    let mut flat = vec![];
+    for literal in clause {
+        let mut term = |term| _flatten_term(term, &mut vars, &mut count, &mut flat);
+        let lit = match &literal.atom {
+            Formula::Pred(name, args) => {
+                let args = args.iter().map(&mut term).collect::<Vec<_>>();
+                Flat::Pred(
+                    nlsym!(name.clone(), args.len() as u32),
+                    args,
+                    literal.positive,
+                )
+            }
+            Formula::Equal(lhs, rhs) => Flat::Equal(term(lhs), term(rhs), literal.positive),
+            _ => unreachable!(),
+        };
+        flat.push(lit);
+    }
+    (flat, count)
+}
+
+// Propositional variables of the atoms `P(d…)` and `f(d…) = e` over a
+// domain.
+#[derive(Default)]
+struct Atoms {
+    preds: HashMap<(NonLogicalSymbol, Vec<u32>), i32>,
+    funcs: HashMap<(NonLogicalSymbol, Vec<u32>, u32), i32>,
+}
+
+impl Atoms {
+    fn _next(&self) -> i32 {
+        (self.preds.len() + self.funcs.len() + 1) as i32
+    }
+
+    fn pred(&mut self, s: &NonLogicalSymbol, args: Vec<u32>) -> i32 {
+        let next = self._next();
+        *self.preds.entry((s.clone(), args)).or_insert(next)
+    }
+
+    fn func(&mut self, s: &NonLogicalSymbol, args: Vec<u32>, value: u32) -> i32 {
+        let next = self._next();
+        *self.funcs.entry((s.clone(), args, value)).or_insert(next)
+    }
+}
+
+// A model of `clauses` over `0..domain_size`, interpreting the symbols of
+// `funcs` and `preds`.
+fn _model_of_size(
+    clauses: &[(Vec<Flat>, usize)],
+    funcs: &[NonLogicalSymbol],
+    preds: &[NonLogicalSymbol],
+    domain_size: u32,
+) -> Option<Structure<u32>> {
+    let domain = (0..domain_size).collect::<Vec<_>>();
+    let mut atoms = Atoms::default();
+    let mut ground = vec![];
+    // Every function takes exactly one value at every tuple, and the
+    // `i`-th constant one of the first `i + 1` elements, as any model is
+    // isomorphic to one numbering the values of constants in order.
+    let mut constants = 0;
+    for func in funcs {
+        for args in _tuples(&domain, func.arity) {
+            let values = domain
+                .iter()
+                .map(|&v| atoms.func(func, args.clone(), v))
+                .collect::<Vec<_>>();
+            for (i, a) in values.iter().enumerate() {
+                for b in &values[i + 1..] {
+                    ground.push(vec![-a, -b]);
+                }
+                if func.arity == 0 && i > constants {
+                    ground.push(vec![-a]);
+                }
+            }
+            ground.push(values);
+        }
+        if func.arity == 0 {
+            constants += 1;
+        }
+    }
+    for (flat, count) in clauses {
+        for values in _tuples(&domain, *count as u32) {
+            let mut clause = vec![];
+            let mut satisfied = false;
+            for lit in flat {
+                let args = |vars: &[usize]| vars.iter().map(|&v| values[v]).collect::<Vec<_>>();
+                match lit {
+                    Flat::Pred(s, vars, positive) => {
+                        let atom = atoms.pred(s, args(vars));
+                        clause.push(if *positive { atom } else { -atom });
+                    }
+                    Flat::Equal(lhs, rhs, positive) => {
+                        satisfied |= (values[*lhs] == values[*rhs]) == *positive;
+                    }
+                    Flat::Def(s, vars, result) => {
+                        clause.push(-atoms.func(s, args(vars), values[*result]));
+                    }
+                }
+            }
+            if satisfied {
+                continue;
+            }
+            if clause.is_empty() {
+                return None;
+            }
+            ground.push(clause);
+        }
+    }
+    let model = CdclSolver::default().solve(&ground)?;
+    let holds = |atom: i32| model.get(atom as usize - 1).is_some_and(|&l| l > 0);
+    let mut structure = Structure::new(domain.clone());
+    for func in funcs {
+        let table = atoms
+            .funcs
+            .iter()
+            .filter(|((s, _, _), &atom)| s == func && holds(atom))
+            .map(|((_, args, value), _)| (args.clone(), *value))
+            .collect();
+        structure.funcs.insert(func.clone(), table);
+    }
+    for pred in preds {
+        let table = _tuples(&domain, pred.arity)
+            .into_iter()
+            .map(|args| {
+                let atom = atoms.preds.get(&(pred.clone(), args.clone()));
+                (args, atom.is_some_and(|&atom| holds(atom)))
+            })
+            .collect();
+        structure.preds.insert(pred.clone(), table);
+    }
+    Some(structure)
+}
+
+/// The smallest model of `formulas`, their free variables read
+/// universally, with at most `max_domain_size` elements, interpreting the
+/// symbols of `formulas`. Each domain size is searched MACE-style: the
+/// Skolemized clause form is flattened so that every function application
+/// is named by a variable, grounded over the domain, and handed to the SAT
+/// solver. A countermodel of a conjecture is a model of the axioms and the
+/// negated conjecture.
+pub fn find_model(formulas: &[Formula], max_domain_size: u32) -> Option<Structure<u32>> {
+    let mut symbols = SymbolGenerator::avoiding(formulas);
+    let mut clauses = vec![];
+    for fml in formulas {
+        let closure = fml
+            .get_free_vars()
+            .into_iter()
+            .fold(fml.clone(), |fml, var| forall!(var, fml));
+        clauses.extend(closure.to_cnf_with(CnfConversion::Tseitin, &mut symbols));
+    }
+    let mut funcs = clauses
+        .iter()
+        .flatten()
+        .flat_map(|literal| literal.atom.get_funcs())
+        .collect::<Vec<_>>();
+    funcs.sort_by(|a, b| (a.arity, &a.name).cmp(&(b.arity, &b.name)));
+    funcs.dedup();
+    let mut preds = clauses
+        .iter()
+        .flatten()
+        .flat_map(|literal| literal.atom.get_preds())
+        .collect::<Vec<_>>();
+    preds.sort_by(|a, b| (&a.name, a.arity).cmp(&(&b.name, b.arity)));
+    preds.dedup();
+    let flat = clauses.iter().map(_flatten).collect::<Vec<_>>();
+    let (input_funcs, input_preds) = formulas.iter().fold(
+        (HashSet::new(), HashSet::new()),
+        |(mut funcs, mut preds), fml| {
+            funcs.extend(fml.get_funcs());
+            preds.extend(fml.get_preds());
+            (funcs, preds)
+        },
+    );
+    (1..=max_domain_size).find_map(|domain_size| {
+        let mut structure = _model_of_size(&flat, &funcs, &preds, domain_size)?;
+        structure.funcs.retain(|s, _| input_funcs.contains(s));
+        structure.preds.retain(|s, _| input_preds.contains(s));
+        for pred in &input_preds {
+            // Predicates only in tautologies, which the clause form drops.
+            if !structure.preds.contains_key(pred) {
+                structure.interpret_pred(pred.clone(), |_| false);
+            }
+        }
+        for func in &input_funcs {
+            if !structure.funcs.contains_key(func) {
+                structure.interpret_func(func.clone(), |_| 0);
+            }
+        }
+        Some(structure)
+    })
+}
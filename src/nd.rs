@@ -0,0 +1,704 @@
+use crate::adapt::*;
+use crate::language::*;
+use crate::proof::*;
+
+/// A proof in natural deduction, NK, in sequent style: each inference
+/// concludes a judgment `Γ ⇒ A` of a formula `A` from the open assumptions
+/// `Γ`, kept as a sequent with a single formula in the succedent. The order
+/// of the assumptions does not matter and a premise may have fewer than the
+/// conclusion, besides those its inference discharges. Proofs without
+/// `Raa` are proofs of NJ, see `ND::is_intuitionistic`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ND {
+    /// `A ⇒ A`, with `A` among the assumptions.
+    Assumption(Sequent),
+    TopIntro(Sequent),
+    /// `⇒ t = t`.
+    EqualityIntro(Sequent),
+    /// `⊥` to any formula.
+    BottomElim(Box<ND>, Sequent),
+    AndIntro(Box<[ND; 2]>, Sequent),
+    AndElim1(Box<ND>, Sequent),
+    AndElim2(Box<ND>, Sequent),
+    OrIntro1(Box<ND>, Sequent),
+    OrIntro2(Box<ND>, Sequent),
+    /// `A ∨ B`, `C` from `A` and `C` from `B` to `C`, discharging `A` and
+    /// `B`.
+    OrElim(Box<[ND; 3]>, Sequent),
+    /// `B` from `A` to `A → B`, discharging `A`.
+    ImpliesIntro(Box<ND>, Sequent),
+    /// `A → B` and `A` to `B`.
+    ImpliesElim(Box<[ND; 2]>, Sequent),
+    /// `B` from `A` and `A` from `B` to `A ↔ B`, discharging both.
+    IffIntro(Box<[ND; 2]>, Sequent),
+    /// `A ↔ B` and `A` to `B`.
+    IffElim1(Box<[ND; 2]>, Sequent),
+    /// `A ↔ B` and `B` to `A`.
+    IffElim2(Box<[ND; 2]>, Sequent),
+    /// `⊥` from `A` to `¬A`, discharging `A`.
+    NotIntro(Box<ND>, Sequent),
+    /// `¬A` and `A` to `⊥`.
+    NotElim(Box<[ND; 2]>, Sequent),
+    /// `A[y/x]` to `∀x A`, for `y` not free in the assumptions of the
+    /// premise nor in `∀x A`.
+    ForallIntro(Box<ND>, Sequent),
+    ForallElim(Box<ND>, Sequent),
+    ExistsIntro(Box<ND>, Sequent),
+    /// `∃x A` and `C` from `A[y/x]` to `C`, discharging `A[y/x]`, for `y`
+    /// not free in `∃x A`, `C` nor the other assumptions of the second
+    /// premise.
+    ExistsElim(Box<[ND; 2]>, Sequent),
+    /// `s = t` and `A(s)` to `A(t)`, where `A(t)` is `A(s)` with some
+    /// occurrences of `s` replaced by `t`.
+    EqualityElim(Box<[ND; 2]>, Sequent),
+    /// `⊥` from `¬A` to `A`, discharging `¬A`: reductio ad absurdum, the
+    /// classical rule.
+    Raa(Box<ND>, Sequent),
+}
+
+fn _infer(rule: &str, premises: Vec<LK>, sequent: Sequent) -> Option<LK> {
+    LK::from_parts(rule, premises, sequent).filter(|proof| proof.is_valid_inference())
+}
+
+fn _axiom(fml: &Formula) -> LK {
+    LK::Axiom(Sequent {
+        antecedent: vec![fml.clone()],
+        succedent: vec![fml.clone()],
+    })
+}
+
+// Cuts the last formula of the succedent of `lhs` against an occurrence of
+// it in the antecedent of `rhs`.
+fn _cut(lhs: LK, rhs: LK) -> Option<LK> {
+    let fml = lhs.last().succedent.last()?.clone();
+    let mut rest = rhs.last().antecedent.clone();
+    rest.remove(rest.iter().position(|f| f == &fml)?);
+    let rhs = adapt(
+        rhs.clone(),
+        &Sequent {
+            antecedent: [std::slice::from_ref(&fml), &rest].concat(),
+            succedent: rhs.last().succedent.clone(),
+        },
+    )
+    .ok()?;
+    let conclusion = Sequent {
+        antecedent: [lhs.last().antecedent.clone(), rest].concat(),
+        succedent: [lhs.last().suc_but_last(), &rhs.last().succedent[..]].concat(),
+    };
+    _infer("Cut", vec![lhs, rhs], conclusion)
+}
+
+impl ND {
+    pub fn last(&self) -> &Sequent {
+        use ND::*;
+        match self {
+            Assumption(s) | TopIntro(s) | EqualityIntro(s) => s,
+            BottomElim(_, s)
+            | AndIntro(_, s)
+            | AndElim1(_, s)
+            | AndElim2(_, s)
+            | OrIntro1(_, s)
+            | OrIntro2(_, s)
+            | OrElim(_, s)
+            | ImpliesIntro(_, s)
+            | ImpliesElim(_, s)
+            | IffIntro(_, s)
+            | IffElim1(_, s)
+            | IffElim2(_, s)
+            | NotIntro(_, s)
+            | NotElim(_, s)
+            | ForallIntro(_, s)
+            | ForallElim(_, s)
+            | ExistsIntro(_, s)
+            | ExistsElim(_, s)
+            | EqualityElim(_, s)
+            | Raa(_, s) => s,
+        }
+    }
+
+    pub fn premises(&self) -> Vec<&ND> {
+        use ND::*;
+        match self {
+            Assumption(_) | TopIntro(_) | EqualityIntro(_) => vec![],
+            BottomElim(p, _)
+            | AndElim1(p, _)
+            | AndElim2(p, _)
+            | OrIntro1(p, _)
+            | OrIntro2(p, _)
+            | ImpliesIntro(p, _)
+            | NotIntro(p, _)
+            | ForallIntro(p, _)
+            | ForallElim(p, _)
+            | ExistsIntro(p, _)
+            | Raa(p, _) => vec![&**p],
+            OrElim(ps, _) => ps.iter().collect(),
+            AndIntro(ps, _)
+            | ImpliesElim(ps, _)
+            | IffIntro(ps, _)
+            | IffElim1(ps, _)
+            | IffElim2(ps, _)
+            | NotElim(ps, _)
+            | ExistsElim(ps, _)
+            | EqualityElim(ps, _) => ps.iter().collect(),
+        }
+    }
+
+    /// The formula the proof concludes.
+    pub fn conclusion(&self) -> &Formula {
+        &self.last().succedent[0]
+    }
+
+    /// Whether the proof is one of NJ, without `Raa`.
+    pub fn is_intuitionistic(&self) -> bool {
+        let mut stack = vec![self];
+        while let Some(proof) = stack.pop() {
+            if let ND::Raa(_, _) = proof {
+                return false;
+            }
+            stack.extend(proof.premises());
+        }
+        true
+    }
+
+    // Derives the conclusion in LK from `premises`, proofs of the premises.
+    // `None` if the inference is not valid.
+    fn _step(&self, premises: Vec<LK>) -> Option<LK> {
+        use Formula::*;
+        let s = self.last();
+        let c = match &s.succedent[..] {
+            [c] => c,
+            _ => return None,
+        };
+        let seq = |front: &[Formula], fml: Option<&Formula>| Sequent {
+            antecedent: [front, &s.antecedent].concat(),
+            succedent: fml.into_iter().cloned().collect(),
+        };
+        let fit = |p: LK, front: &[Formula], fml: &Formula| adapt(p, &seq(front, Some(fml))).ok();
+        let end = |p: LK| adapt(p, s).ok();
+        // The formula concluded by the `i`-th premise.
+        let major = |i: usize| premises[i].last().succedent.first().cloned();
+        let bottom = LK::Axiom(Sequent {
+            antecedent: vec![Bottom],
+            succedent: vec![],
+        });
+        let mut ps = premises.clone().into_iter();
+        let mut next = || ps.next();
+        match (self, c) {
+            (ND::Assumption(_), _) if s.antecedent.contains(c) => end(_axiom(c)),
+            (ND::TopIntro(_), Top) => {
+                _infer("Axiom", vec![], seq(&[], Some(c))).filter(|_| s.antecedent.is_empty())
+            }
+            (ND::EqualityIntro(_), Equal(_, _)) => {
+                _infer("Axiom", vec![], seq(&[], Some(c))).filter(|_| s.antecedent.is_empty())
+            }
+            (ND::BottomElim(_, _), _) => {
+                let p = fit(next()?, &[], &Bottom)?;
+                end(_cut(p, bottom)?)
+            }
+            (ND::AndIntro(_, _), And(a, b)) => {
+                let (p1, p2) = (fit(next()?, &[], a)?, fit(next()?, &[], b)?);
+                _infer("AndRight", vec![p1, p2], s.clone())
+            }
+            (ND::AndElim1(_, _), _) | (ND::AndElim2(_, _), _) => {
+                let rule = match self {
+                    ND::AndElim1(_, _) => "AndLeft1",
+                    _ => "AndLeft2",
+                };
+                let conclusion = Sequent {
+                    antecedent: vec![major(0)?],
+                    succedent: vec![c.clone()],
+                };
+                let elim = _infer(rule, vec![_axiom(c)], conclusion)?;
+                end(_cut(next()?, elim)?)
+            }
+            (ND::OrIntro1(_, _), Or(a, _)) | (ND::OrIntro2(_, _), Or(_, a)) => {
+                let rule = match self {
+                    ND::OrIntro1(_, _) => "OrRight1",
+                    _ => "OrRight2",
+                };
+                let p = fit(next()?, &[], a)?;
+                _infer(rule, vec![p], s.clone())
+            }
+            (ND::OrElim(_, _), _) => match major(0)? {
+                Or(a, b) => {
+                    let p = next()?;
+                    let (q1, q2) = (fit(next()?, &[*a], c)?, fit(next()?, &[*b], c)?);
+                    let elim = _infer("OrLeft", vec![q1, q2], seq(&[major(0)?], Some(c)))?;
+                    end(_cut(p, elim)?)
+                }
+                _ => None,
+            },
+            (ND::ImpliesIntro(_, _), Implies(a, b)) => {
+                let p = fit(next()?, &[(**a).clone()], b)?;
+                _infer("ImpliesRight", vec![p], s.clone())
+            }
+            (ND::ImpliesElim(_, _), _) => {
+                let (p1, p2) = (next()?, next()?);
+                let conclusion = Sequent {
+                    antecedent: [&[major(0)?], &p2.last().antecedent[..]].concat(),
+                    succedent: vec![c.clone()],
+                };
+                let elim = _infer("ImpliesLeft", vec![p2, _axiom(c)], conclusion)?;
+                end(_cut(p1, elim)?)
+            }
+            (ND::IffIntro(_, _), Iff(a, b)) => {
+                let q1 = fit(next()?, &[(**a).clone()], b)?;
+                let q2 = fit(next()?, &[(**b).clone()], a)?;
+                _infer("IffRight", vec![q1, q2], s.clone())
+            }
+            (ND::IffElim1(_, _), _) | (ND::IffElim2(_, _), _) => {
+                let (p1, p2) = (next()?, next()?);
+                let (a, b) = match major(0)? {
+                    Iff(a, b) => (*a, *b),
+                    _ => return None,
+                };
+                let context = p2.last().antecedent.clone();
+                let side = |front: Vec<Formula>, back: Vec<Formula>| Sequent {
+                    antecedent: [front, context.clone()].concat(),
+                    succedent: back,
+                };
+                let q1 = adapt(
+                    _axiom(c),
+                    &side(vec![a.clone(), b.clone()], vec![c.clone()]),
+                )
+                .ok()?;
+                let q2 = adapt(p2, &side(vec![], vec![c.clone(), a, b])).ok()?;
+                let elim = _infer(
+                    "IffLeft",
+                    vec![q1, q2],
+                    side(vec![major(0)?], vec![c.clone()]),
+                )?;
+                end(_cut(p1, elim)?)
+            }
+            (ND::NotIntro(_, _), Not(a)) => {
+                let p = fit(next()?, &[(**a).clone()], &Bottom)?;
+                let p = adapt(_cut(p, bottom)?, &seq(&[(**a).clone()], None)).ok()?;
+                _infer("NotRight", vec![p], s.clone())
+            }
+            (ND::NotElim(_, _), Bottom) => {
+                let (p1, p2) = (next()?, next()?);
+                let conclusion = Sequent {
+                    antecedent: [&[major(0)?], &p2.last().antecedent[..]].concat(),
+                    succedent: vec![],
+                };
+                let elim = _infer("NotLeft", vec![p2], conclusion)?;
+                end(_cut(p1, elim)?)
+            }
+            (ND::ForallIntro(_, _), Forall(_, _)) | (ND::ExistsIntro(_, _), Exists(_, _)) => {
+                let rule = match self {
+                    ND::ForallIntro(_, _) => "ForallRight",
+                    _ => "ExistsRight",
+                };
+                let p = next()?;
+                let conclusion = Sequent {
+                    antecedent: p.last().antecedent.clone(),
+                    succedent: vec![c.clone()],
+                };
+                end(_infer(rule, vec![p], conclusion)?)
+            }
+            (ND::ForallElim(_, _), _) => {
+                let conclusion = Sequent {
+                    antecedent: vec![major(0)?],
+                    succedent: vec![c.clone()],
+                };
+                let elim = _infer("ForallLeft", vec![_axiom(c)], conclusion)?;
+                end(_cut(next()?, elim)?)
+            }
+            (ND::ExistsElim(_, _), _) => {
+                let (p, q) = (next()?, next()?);
+                let mut instances = (q.last().antecedent.iter())
+                    .filter(|f| !s.antecedent.contains(f))
+                    .collect::<Vec<_>>();
+                instances.dedup();
+                let instance = match instances[..] {
+                    [] => return end(q),
+                    [instance] => instance.clone(),
+                    _ => return None,
+                };
+                let rest = (q.last().antecedent.iter())
+                    .filter(|f| *f != &instance)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                let side = |front: Formula| Sequent {
+                    antecedent: [vec![front], rest.clone()].concat(),
+                    succedent: vec![c.clone()],
+                };
+                let q = adapt(q, &side(instance)).ok()?;
+                let elim = _infer("ExistsLeft", vec![q], side(major(0)?))?;
+                end(_cut(p, elim)?)
+            }
+            (ND::EqualityElim(_, _), _) => {
+                let (p1, p2) = (next()?, next()?);
+                let conclusion = Sequent {
+                    antecedent: [&[major(0)?], &p2.last().antecedent[..]].concat(),
+                    succedent: vec![c.clone()],
+                };
+                let elim = _infer("EqualityRight", vec![p2], conclusion)?;
+                end(_cut(p1, elim)?)
+            }
+            (ND::Raa(_, _), _) => {
+                let negation = not!(c.clone());
+                let p = fit(next()?, std::slice::from_ref(&negation), &Bottom)?;
+                let p = _cut(p, bottom)?;
+                let excluded = Sequent {
+                    antecedent: vec![],
+                    succedent: vec![c.clone(), negation],
+                };
+                let excluded = _infer("NotRight", vec![_axiom(c)], excluded)?;
+                end(_cut(excluded, p)?)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The same proof in LK, eliminations becoming cuts against left rules.
+/// Fails if an inference is invalid.
+pub fn nd_to_lk(proof: &ND) -> Result<LK, &'static str> {
+    let premises = proof
+        .premises()
+        .iter()
+        .map(|p| nd_to_lk(p))
+        .collect::<Result<Vec<_>, _>>()?;
+    proof._step(premises).ok_or("Invalid inference.")
+}
+
+impl Proof for ND {
+    fn is_valid_proof(&self) -> bool {
+        let mut stack = vec![self];
+        while let Some(proof) = stack.pop() {
+            if !proof.is_valid_inference() {
+                return false;
+            }
+            stack.extend(proof.premises());
+        }
+        true
+    }
+
+    fn is_valid_inference(&self) -> bool {
+        let placeholders = self
+            .premises()
+            .iter()
+            .map(|p| LK::Axiom(p.last().clone()))
+            .collect();
+        self.premises()
+            .iter()
+            .all(|p| p.last().succedent.len() == 1)
+            && self._step(placeholders).is_some()
+    }
+}
+
+// The judgment concluding `fml` from the assumptions of `premises`, but
+// those each discharges.
+fn _judgment(premises: &[(&ND, &[Formula])], fml: Formula) -> Sequent {
+    let mut antecedent = vec![];
+    for (premise, discharged) in premises {
+        for assumption in &premise.last().antecedent {
+            if !discharged.contains(assumption) && !antecedent.contains(assumption) {
+                antecedent.push(assumption.clone());
+            }
+        }
+    }
+    Sequent {
+        antecedent,
+        succedent: vec![fml],
+    }
+}
+
+fn _assume(fml: &Formula) -> ND {
+    ND::Assumption(Sequent {
+        antecedent: vec![fml.clone()],
+        succedent: vec![fml.clone()],
+    })
+}
+
+fn _unary(
+    rule: fn(Box<ND>, Sequent) -> ND,
+    premise: ND,
+    discharged: &[Formula],
+    fml: Formula,
+) -> ND {
+    let judgment = _judgment(&[(&premise, discharged)], fml);
+    rule(Box::new(premise), judgment)
+}
+
+fn _binary(
+    rule: fn(Box<[ND; 2]>, Sequent) -> ND,
+    premises: [ND; 2],
+    discharged: &[Formula],
+    fml: Formula,
+) -> ND {
+    let judgment = _judgment(&[(&premises[0], &[]), (&premises[1], discharged)], fml);
+    rule(Box::new(premises), judgment)
+}
+
+// `fml` from `proof` of `⊥`, discharging `¬fml`.
+fn _derive(proof: ND, fml: &Formula) -> ND {
+    _unary(ND::Raa, proof, &[not!(fml.clone())], fml.clone())
+}
+
+// `⊥` from `proof` of `fml` and the assumption `¬fml`.
+fn _refute(fml: &Formula, proof: ND) -> ND {
+    _binary(
+        ND::NotElim,
+        [_assume(&not!(fml.clone())), proof],
+        &[],
+        Formula::Bottom,
+    )
+}
+
+// `proof` with the assumption `fml` derived by `derivation` instead.
+fn _subst(proof: ND, fml: &Formula, derivation: ND) -> ND {
+    if !proof.last().antecedent.contains(fml) {
+        return proof;
+    }
+    let conclusion = proof.conclusion().clone();
+    let intro = _unary(
+        ND::ImpliesIntro,
+        proof,
+        std::slice::from_ref(fml),
+        implies!(fml.clone(), conclusion.clone()),
+    );
+    _binary(ND::ImpliesElim, [intro, derivation], &[], conclusion)
+}
+
+// Introduces `fml` into `target` by disjunction introductions.
+fn _introduce(target: &Formula, fml: &Formula, proof: ND) -> Option<ND> {
+    if target == fml {
+        return Some(proof);
+    }
+    match target {
+        Formula::Or(lhs, rhs) => match _introduce(lhs, fml, proof.clone()) {
+            Some(proof) => Some(_unary(ND::OrIntro1, proof, &[], target.clone())),
+            None => Some(_unary(
+                ND::OrIntro2,
+                _introduce(rhs, fml, proof)?,
+                &[],
+                target.clone(),
+            )),
+        },
+        _ => None,
+    }
+}
+
+// A proof of `⊥` from the antecedent of the end sequent of `proof` and the
+// negations of its succedent.
+fn _refutation(proof: &LK) -> Result<ND, &'static str> {
+    use Formula::*;
+    let premises = proof
+        .premises()
+        .iter()
+        .map(|p| _refutation(p))
+        .collect::<Result<Vec<_>, _>>()?;
+    let s = proof.last();
+    let active = |i: usize, left: bool| {
+        let premise = proof.premises()[i].last();
+        if left {
+            premise.ant_first().clone()
+        } else {
+            premise.suc_last().clone()
+        }
+    };
+    let mut premises = premises.into_iter();
+    let mut next = || premises.next().unwrap();
+    let nd = match proof {
+        LK::Axiom(_) => match (&s.antecedent[..], &s.succedent[..]) {
+            ([Bottom], []) => _assume(&Bottom),
+            ([], [fml @ Top]) => _refute(fml, ND::TopIntro(_judgment(&[], Top))),
+            ([], [fml]) => _refute(fml, ND::EqualityIntro(_judgment(&[], fml.clone()))),
+            (fmls, _) => _refute(&fmls[0], _assume(&fmls[0])),
+        },
+        LK::WeakeningLeft(_, _)
+        | LK::WeakeningRight(_, _)
+        | LK::ContractionLeft(_, _)
+        | LK::ContractionRight(_, _)
+        | LK::ExchangeLeft(_, _)
+        | LK::ExchangeRight(_, _) => next(),
+        LK::AndLeft1(_, _) | LK::AndLeft2(_, _) | LK::ForallLeft(_, _) => {
+            let rule = match proof {
+                LK::AndLeft1(_, _) => ND::AndElim1,
+                LK::AndLeft2(_, _) => ND::AndElim2,
+                _ => ND::ForallElim,
+            };
+            let fml = active(0, true);
+            let elim = _unary(rule, _assume(s.ant_first()), &[], fml.clone());
+            _subst(next(), &fml, elim)
+        }
+        LK::AndRight(_, _) => {
+            let (a, b) = (active(0, false), active(1, false));
+            let (p1, p2) = (next(), next());
+            let intro = _binary(
+                ND::AndIntro,
+                [_derive(p1, &a), _derive(p2, &b)],
+                &[],
+                s.suc_last().clone(),
+            );
+            _refute(s.suc_last(), intro)
+        }
+        LK::OrLeft(_, _) => {
+            let (a, b) = (active(0, true), active(1, true));
+            let (p1, p2) = (next(), next());
+            let major = _assume(s.ant_first());
+            let judgment = _judgment(&[(&major, &[]), (&p1, &[a]), (&p2, &[b])], Bottom);
+            ND::OrElim(Box::new([major, p1, p2]), judgment)
+        }
+        LK::OrRight1(_, _) | LK::OrRight2(_, _) | LK::ExistsRight(_, _) | LK::ForallRight(_, _) => {
+            let rule = match proof {
+                LK::OrRight1(_, _) => ND::OrIntro1,
+                LK::OrRight2(_, _) => ND::OrIntro2,
+                LK::ExistsRight(_, _) => ND::ExistsIntro,
+                _ => ND::ForallIntro,
+            };
+            let fml = active(0, false);
+            let intro = _unary(rule, _derive(next(), &fml), &[], s.suc_last().clone());
+            _refute(s.suc_last(), intro)
+        }
+        LK::ImpliesLeft(_, _) => {
+            let (a, b) = (active(0, false), active(1, true));
+            let (p1, p2) = (next(), next());
+            let elim = _binary(
+                ND::ImpliesElim,
+                [_assume(s.ant_first()), _derive(p1, &a)],
+                &[],
+                b.clone(),
+            );
+            _subst(p2, &b, elim)
+        }
+        LK::ImpliesRight(_, _) => {
+            let (a, b) = (active(0, true), active(0, false));
+            let intro = _unary(
+                ND::ImpliesIntro,
+                _derive(next(), &b),
+                &[a],
+                s.suc_last().clone(),
+            );
+            _refute(s.suc_last(), intro)
+        }
+        LK::IffLeft(_, _) => {
+            let (a, b) = match s.ant_first() {
+                Iff(a, b) => ((**a).clone(), (**b).clone()),
+                _ => return Err("Invalid inference."),
+            };
+            let (p1, p2) = (next(), next());
+            let iff = _assume(s.ant_first());
+            // `¬A` and `¬B` from the first premise, which has `A, B`.
+            let not_a = _subst(
+                p1.clone(),
+                &b,
+                _binary(ND::IffElim1, [iff.clone(), _assume(&a)], &[], b.clone()),
+            );
+            let not_a = _unary(
+                ND::NotIntro,
+                not_a,
+                std::slice::from_ref(&a),
+                not!(a.clone()),
+            );
+            let not_b = _subst(
+                p1,
+                &a,
+                _binary(ND::IffElim2, [iff, _assume(&b)], &[], a.clone()),
+            );
+            let not_b = _unary(
+                ND::NotIntro,
+                not_b,
+                std::slice::from_ref(&b),
+                not!(b.clone()),
+            );
+            _subst(_subst(p2, &not!(a), not_a), &not!(b), not_b)
+        }
+        LK::IffRight(_, _) => {
+            let (a, b) = (active(0, true), active(1, true));
+            let (p1, p2) = (next(), next());
+            let (q1, q2) = (_derive(p1, &b), _derive(p2, &a));
+            let judgment = _judgment(&[(&q1, &[a]), (&q2, &[b])], s.suc_last().clone());
+            _refute(s.suc_last(), ND::IffIntro(Box::new([q1, q2]), judgment))
+        }
+        LK::NotLeft(_, _) => {
+            let a = active(0, false);
+            _refute(&a, _derive(next(), &a))
+        }
+        LK::NotRight(_, _) => {
+            let a = active(0, true);
+            let intro = _unary(ND::NotIntro, next(), &[a], s.suc_last().clone());
+            _refute(s.suc_last(), intro)
+        }
+        LK::ExistsLeft(_, _) => {
+            let instance = active(0, true);
+            let major = _assume(s.ant_first());
+            _binary(ND::ExistsElim, [major, next()], &[instance], Bottom)
+        }
+        LK::EqualityLeft(_, _) => {
+            let (lhs, rhs) = match s.ant_first() {
+                Equal(lhs, rhs) => (lhs.clone(), rhs.clone()),
+                _ => return Err("Invalid inference."),
+            };
+            let fml = active(0, true);
+            let reflexivity = ND::EqualityIntro(_judgment(&[], equal!(lhs.clone(), lhs.clone())));
+            let symmetry = _binary(
+                ND::EqualityElim,
+                [_assume(s.ant_first()), reflexivity],
+                &[],
+                equal!(rhs, lhs),
+            );
+            let elim = _binary(
+                ND::EqualityElim,
+                [symmetry, _assume(&s.antecedent[1])],
+                &[],
+                fml.clone(),
+            );
+            _subst(next(), &fml, elim)
+        }
+        LK::EqualityRight(_, _) => {
+            let fml = active(0, false);
+            let elim = _binary(
+                ND::EqualityElim,
+                [_assume(s.ant_first()), _derive(next(), &fml)],
+                &[],
+                s.suc_last().clone(),
+            );
+            _refute(s.suc_last(), elim)
+        }
+        LK::Cut(_, _) => {
+            let fml = active(0, false);
+            let (p1, p2) = (next(), next());
+            _subst(p2, &fml, _derive(p1, &fml))
+        }
+        LK::TheoryRule(_, _) | LK::TheoryAxiom(_) => return Err("Theory rules are not supported."),
+    };
+    Ok(nd)
+}
+
+/// The same proof in natural deduction, of the disjunction of the
+/// succedent of the end sequent, `⊥` if it is empty, from at most its
+/// antecedent. Sequents `Γ ⇒ Δ` are read as `Γ, ¬Δ ⇒ ⊥`, so the proof
+/// generally uses `Raa`. Fails on theory rules.
+pub fn lk_to_nd(proof: &LK) -> Result<ND, &'static str> {
+    let refutation = _refutation(proof)?;
+    let succedent = &proof.last().succedent;
+    let disjunction = match succedent.iter().cloned().reduce(|l, r| or!(l, r)) {
+        Some(disjunction) => disjunction,
+        None => return Ok(refutation),
+    };
+    let mut refutation = refutation;
+    if succedent.len() > 1 {
+        // Each `¬A` of the succedent from `¬(A ∨ ...)`.
+        let negation = not!(disjunction.clone());
+        for fml in succedent {
+            let intro = _introduce(&disjunction, fml, _assume(fml)).unwrap();
+            let elim = _binary(
+                ND::NotElim,
+                [_assume(&negation), intro],
+                &[],
+                Formula::Bottom,
+            );
+            let negated = _unary(
+                ND::NotIntro,
+                elim,
+                std::slice::from_ref(fml),
+                not!(fml.clone()),
+            );
+            refutation = _subst(refutation, &not!(fml.clone()), negated);
+        }
+    }
+    Ok(_derive(refutation, &disjunction))
+}
@@ -0,0 +1,166 @@
+use crate::language::*;
+use crate::proof::*;
+use std::collections::HashMap;
+
+pub(crate) fn is_structural(proof: &LK) -> bool {
+    matches!(
+        proof,
+        LK::WeakeningLeft(_, _)
+            | LK::WeakeningRight(_, _)
+            | LK::ContractionLeft(_, _)
+            | LK::ContractionRight(_, _)
+            | LK::ExchangeLeft(_, _)
+            | LK::ExchangeRight(_, _)
+    )
+}
+
+// Rewrites one side of the sequent from its current formulas into `target`
+// by appending structural inferences below `proof`. Sides are handled as
+// views whose index 0 is the active end of the side, i.e. the first formula
+// of the antecedent or the last formula of the succedent.
+struct Side {
+    left: bool,
+    view: Vec<Formula>,
+    other: Vec<Formula>,
+}
+
+impl Side {
+    fn new(sequent: &Sequent, left: bool) -> Side {
+        if left {
+            Side {
+                left,
+                view: sequent.antecedent.clone(),
+                other: sequent.succedent.clone(),
+            }
+        } else {
+            Side {
+                left,
+                view: sequent.succedent.iter().rev().cloned().collect(),
+                other: sequent.antecedent.clone(),
+            }
+        }
+    }
+
+    fn sequent(&self) -> Sequent {
+        if self.left {
+            Sequent {
+                antecedent: self.view.clone(),
+                succedent: self.other.clone(),
+            }
+        } else {
+            Sequent {
+                antecedent: self.other.clone(),
+                succedent: self.view.iter().rev().cloned().collect(),
+            }
+        }
+    }
+
+    fn infer(&self, proof: LK, rule: fn(Box<LK>, Sequent) -> LK) -> LK {
+        rule(Box::new(proof), self.sequent())
+    }
+
+    fn exchange(&mut self, proof: LK, from: usize, to: usize) -> LK {
+        let rule = if self.left {
+            LK::ExchangeLeft
+        } else {
+            LK::ExchangeRight
+        };
+        let mut proof = proof;
+        for i in (to..from).rev() {
+            self.view.swap(i, i + 1);
+            proof = self.infer(proof, rule);
+        }
+        proof
+    }
+
+    fn rewrite(&mut self, proof: LK, target: &[Formula]) -> Option<LK> {
+        let count = |fmls: &[Formula], fml: &Formula| fmls.iter().filter(|f| *f == fml).count();
+        if self.view.iter().any(|f| count(target, f) == 0) {
+            return None;
+        }
+        let mut proof = proof;
+
+        // Contractions first, on the two leftmost copies of each formula.
+        for fml in target {
+            while count(&self.view, fml) > count(target, fml) {
+                let i = self.view.iter().position(|f| f == fml).unwrap();
+                proof = self.exchange(proof, i, 0);
+                let j = self.view[1..].iter().position(|f| f == fml).unwrap() + 1;
+                proof = self.exchange(proof, j, 1);
+                self.view.remove(0);
+                let rule = if self.left {
+                    LK::ContractionLeft
+                } else {
+                    LK::ContractionRight
+                };
+                proof = self.infer(proof, rule);
+            }
+        }
+
+        // Then the missing formulas are weakened in.
+        let mut missing = target
+            .iter()
+            .map(|fml| (fml, count(target, fml) - count(&self.view, fml)))
+            .collect::<HashMap<_, _>>();
+        for fml in target.iter().rev() {
+            let n = missing.get_mut(fml).unwrap();
+            if *n > 0 {
+                *n -= 1;
+                self.view.insert(0, fml.clone());
+                let rule = if self.left {
+                    LK::WeakeningLeft
+                } else {
+                    LK::WeakeningRight
+                };
+                proof = self.infer(proof, rule);
+            }
+        }
+
+        // Exchanges come last, bubbling each target formula into place.
+        for (i, fml) in target.iter().enumerate() {
+            let j = self.view[i..].iter().position(|f| f == fml).unwrap() + i;
+            proof = self.exchange(proof, j, i);
+        }
+        Some(proof)
+    }
+}
+
+fn _structural_chain(top: LK, conclusion: &Sequent) -> Option<LK> {
+    let mut side = Side::new(top.last(), true);
+    let proof = side.rewrite(top, &conclusion.antecedent)?;
+    let mut side = Side::new(proof.last(), false);
+    let target = conclusion
+        .succedent
+        .iter()
+        .rev()
+        .cloned()
+        .collect::<Vec<_>>();
+    side.rewrite(proof, &target)
+}
+
+impl LK {
+    /// Canonical arrangement of the structural inferences. Each maximal run
+    /// of weakenings, contractions and exchanges is replaced by contractions
+    /// followed by weakenings followed by exchanges, antecedent before
+    /// succedent, so exchanges sit as low as possible and runs that cancel
+    /// out disappear. Runs are not moved across logical inferences.
+    pub fn structural_normal_form(&self) -> LK {
+        let mut top = self;
+        while is_structural(top) {
+            top = top.premises()[0];
+        }
+        let top = top.with_premises(
+            top.premises()
+                .iter()
+                .map(|p| p.structural_normal_form())
+                .collect(),
+        );
+        if !is_structural(self) {
+            return top;
+        }
+        match _structural_chain(top, self.last()) {
+            Some(proof) => proof,
+            None => self.with_premises(vec![self.premises()[0].structural_normal_form()]),
+        }
+    }
+}
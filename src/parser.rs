@@ -1,31 +1,65 @@
 use crate::language::*;
+use crate::limits::*;
+use crate::proof::Sequent;
+use crate::tokenizer::Tokenizer;
+use std::str::FromStr;
 
 #[derive(Debug)]
 pub struct Parser<'a> {
     pub iter: std::iter::Peekable<std::slice::Iter<'a, Token>>,
+    pub max_depth: Option<usize>,
+    depth: usize,
 }
 
 impl<'a> Parser<'a> {
     pub fn new() -> Parser<'a> {
         Parser {
             iter: [].iter().peekable(),
+            max_depth: None,
+            depth: 0,
         }
     }
 
+    /// A parser failing with `TOO_DEEP` on input nested deeper than
+    /// `limits.max_depth`.
+    pub fn with_limits(limits: &Limits) -> Parser<'a> {
+        Parser {
+            max_depth: limits.max_depth,
+            ..Parser::new()
+        }
+    }
+
+    // Runs `parse` one level deeper.
+    fn _nested<T>(
+        &mut self,
+        parse: fn(&mut Self) -> Result<T, &'static str>,
+    ) -> Result<T, &'static str> {
+        if self.max_depth.is_some_and(|max| self.depth >= max) {
+            return Err(TOO_DEEP);
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
     fn _parse_term(&mut self) -> Result<Term, &'static str> {
+        self._nested(Self::_parse_term_at_depth)
+    }
+
+    fn _parse(&mut self) -> Result<Formula, &'static str> {
+        self._nested(Self::_parse_at_depth)
+    }
+
+    fn _parse_term_at_depth(&mut self) -> Result<Term, &'static str> {
         if let Some(token) = self.iter.next() {
             let term = match token {
                 Token::LParen => {
                     if let Some(Token::Symbol(s)) = self.iter.next() {
                         let mut terms = vec![];
-                        while {
-                            if let Ok(term) = self._parse_term() {
-                                terms.push(term)
-                            } else {
-                                return Err("Parse error.");
-                            }
-                            !matches!(self.iter.peek(), Some(Token::RParen))
-                        } {}
+                        while !matches!(self.iter.peek(), Some(Token::RParen)) {
+                            terms.push(self._parse_term()?);
+                        }
                         Ok(Term::Func(s.into(), terms))
                     } else {
                         Err("Parse error.")
@@ -44,51 +78,37 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn _parse(&mut self) -> Result<Formula, &'static str> {
+    fn _parse_at_depth(&mut self) -> Result<Formula, &'static str> {
         match self.iter.next() {
             Some(Token::LParen) => {
                 let fml = match self.iter.next() {
                     Some(token) => match token {
                         Token::Symbol(s) => {
                             let mut terms = vec![];
-                            while {
-                                if let Ok(term) = self._parse_term() {
-                                    terms.push(term)
-                                } else {
-                                    return Err("Parse error.");
-                                }
-                                !matches!(self.iter.peek(), Some(Token::RParen))
-                            } {}
-                            Ok(Formula::Pred(s.into(), terms))
-                        }
-                        Token::Not => {
-                            if let Ok(fml) = self._parse() {
-                                Ok(Formula::Not(Box::new(fml)))
-                            } else {
-                                Err("Parse error.")
+                            while !matches!(self.iter.peek(), Some(Token::RParen)) {
+                                terms.push(self._parse_term()?);
                             }
+                            Ok(Formula::Pred(s.into(), terms))
                         }
-                        t @ Token::And | t @ Token::Or | t @ Token::Implies => {
-                            if let (Ok(lhs), Ok(rhs)) = (self._parse(), self._parse()) {
-                                match t {
-                                    Token::And => Ok(Formula::And(Box::new(lhs), Box::new(rhs))),
-                                    Token::Or => Ok(Formula::Or(Box::new(lhs), Box::new(rhs))),
-                                    _ => Ok(Formula::Implies(Box::new(lhs), Box::new(rhs))),
-                                }
-                            } else {
-                                Err("Parse error.")
+                        Token::Not => Ok(Formula::Not(Box::new(self._parse()?))),
+                        t @ Token::And | t @ Token::Or | t @ Token::Implies | t @ Token::Iff => {
+                            let lhs = Box::new(self._parse()?);
+                            let rhs = Box::new(self._parse()?);
+                            match t {
+                                Token::And => Ok(Formula::And(lhs, rhs)),
+                                Token::Or => Ok(Formula::Or(lhs, rhs)),
+                                Token::Iff => Ok(Formula::Iff(lhs, rhs)),
+                                _ => Ok(Formula::Implies(lhs, rhs)),
                             }
                         }
                         Token::Equal => {
-                            if let (Ok(lhs), Ok(rhs)) = (self._parse_term(), self._parse_term()) {
-                                Ok(Formula::Equal(lhs, rhs))
-                            } else {
-                                Err("Parse error.")
-                            }
+                            let lhs = self._parse_term()?;
+                            Ok(Formula::Equal(lhs, self._parse_term()?))
                         }
                         t @ Token::Forall | t @ Token::Exists => {
-                            match (self.iter.next(), self._parse()) {
-                                (Some(Token::Symbol(s)), Ok(fml)) => match t {
+                            let var = self.iter.next();
+                            match (var, self._parse()?) {
+                                (Some(Token::Symbol(s)), fml) => match t {
                                     Token::Forall => {
                                         Ok(Formula::Forall(Term::Var(s.into()), Box::new(fml)))
                                     }
@@ -111,12 +131,90 @@ impl<'a> Parser<'a> {
                 }
             }
             Some(Token::Symbol(s)) => Ok(Formula::Pred(s.into(), vec![])),
+            Some(Token::Top) => Ok(Formula::Top),
+            Some(Token::Bottom) => Ok(Formula::Bottom),
             _ => Err("Parse error."),
         }
     }
 
-    pub fn parse(&mut self, tokens: &'a Vec<Token>) -> Result<Formula, &'static str> {
+    fn _parse_formulas(&mut self) -> Result<Vec<Formula>, &'static str> {
+        if let Some(Token::LParen) = self.iter.next() {
+            let mut fmls = vec![];
+            while !matches!(self.iter.peek(), Some(Token::RParen)) {
+                fmls.push(self._parse()?);
+            }
+            self.iter.next();
+            Ok(fmls)
+        } else {
+            Err("Parse error.")
+        }
+    }
+
+    pub fn parse(&mut self, tokens: &'a [Token]) -> Result<Formula, &'static str> {
         self.iter = tokens.iter().peekable();
         self._parse()
     }
+
+    pub fn parse_term(&mut self, tokens: &'a [Token]) -> Result<Term, &'static str> {
+        self.iter = tokens.iter().peekable();
+        self._parse_term()
+    }
+
+    /// Parses a sequent written as `((A1 A2 ...) (B1 B2 ...))`, the format
+    /// produced by `Sequent::to_sexpr`.
+    pub fn parse_sequent(&mut self, tokens: &'a [Token]) -> Result<Sequent, &'static str> {
+        self.iter = tokens.iter().peekable();
+        if let Some(Token::LParen) = self.iter.next() {
+            let antecedent = self._parse_formulas()?;
+            let succedent = self._parse_formulas()?;
+            if let Some(Token::RParen) = self.iter.next() {
+                return Ok(Sequent {
+                    antecedent,
+                    succedent,
+                });
+            }
+        }
+        Err("Parse error.")
+    }
+}
+
+// Fails if tokens are left after `parsed`.
+pub(crate) fn at_end<T>(parser: &mut Parser, parsed: T) -> Result<T, &'static str> {
+    match parser.iter.next() {
+        None => Ok(parsed),
+        Some(_) => Err("Parse error."),
+    }
+}
+
+impl FromStr for Formula {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Formula, &'static str> {
+        let tokens = Tokenizer::new().tokenize(s);
+        let mut parser = Parser::new();
+        let fml = parser.parse(&tokens)?;
+        at_end(&mut parser, fml)
+    }
+}
+
+impl FromStr for Term {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Term, &'static str> {
+        let tokens = Tokenizer::new().tokenize(s);
+        let mut parser = Parser::new();
+        let term = parser.parse_term(&tokens)?;
+        at_end(&mut parser, term)
+    }
+}
+
+impl FromStr for Sequent {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Sequent, &'static str> {
+        let tokens = Tokenizer::new().tokenize(s);
+        let mut parser = Parser::new();
+        let sequent = parser.parse_sequent(&tokens)?;
+        at_end(&mut parser, sequent)
+    }
 }
@@ -0,0 +1,104 @@
+use crate::language::*;
+use crate::model::*;
+use crate::parser::*;
+use crate::proof::*;
+use crate::signature::*;
+use crate::solver::*;
+use crate::tokenizer::*;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Limits for `solve`. Proof depths and domain sizes are tried in
+/// alternation, one round at a time, until a result is found, both limits
+/// are exhausted or the timeout has passed. The timeout is only checked
+/// between rounds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolveOptions {
+    pub max_proof_depth: u32,
+    pub max_domain_size: u32,
+    pub use_cut: bool,
+    pub timeout: Option<Duration>,
+}
+
+impl Default for SolveOptions {
+    fn default() -> SolveOptions {
+        SolveOptions {
+            max_proof_depth: 8,
+            max_domain_size: 8,
+            use_cut: false,
+            timeout: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// A proof of the goal, in structural normal form.
+    Proof(LK),
+    /// A finite model falsifying the goal.
+    Countermodel(FiniteModel),
+    /// Neither was found. The diagnostics say why.
+    Unknown(Vec<String>),
+}
+
+fn _parse_goal(input: &str) -> Result<Sequent, &'static str> {
+    let tokens = Tokenizer::new().tokenize(input);
+    match Parser::new().parse(&tokens) {
+        Ok(fml) => Ok(Sequent {
+            antecedent: vec![],
+            succedent: vec![fml],
+        }),
+        Err(e) => Parser::new().parse_sequent(&tokens).map_err(|_| e),
+    }
+}
+
+// Symbols used with several arities are almost always typos.
+fn _check_signature(fml: &Formula) -> Vec<String> {
+    match fml.signature().check_consistent_arities() {
+        Ok(()) => vec![],
+        Err(conflicts) => conflicts.iter().map(|c| c.to_string()).collect(),
+    }
+}
+
+/// Parses `input`, a formula or a sequent in the syntax of `Sequent::to_sexpr`,
+/// and looks for an LK proof or a finite countermodel within `options`.
+pub fn solve(input: &str, options: &SolveOptions) -> Outcome {
+    let start = Instant::now();
+    let goal = match _parse_goal(input) {
+        Ok(goal) => goal,
+        Err(e) => return Outcome::Unknown(vec![e.to_string()]),
+    };
+    let fml = goal.to_formula();
+    let mut diagnostics = _check_signature(&fml);
+
+    let mut checked_sequents = HashMap::new();
+    let rounds = options.max_proof_depth.max(options.max_domain_size);
+    for round in 1..rounds + 1 {
+        if let Some(timeout) = options.timeout {
+            if start.elapsed() >= timeout {
+                diagnostics.push(format!("timed out after {} rounds", round - 1));
+                return Outcome::Unknown(diagnostics);
+            }
+        }
+        if round <= options.max_domain_size {
+            if let Some(model) = refute_on_models_of_size(&fml, round) {
+                return Outcome::Countermodel(model);
+            }
+        }
+        if round <= options.max_proof_depth {
+            if let Ok(proof) = prove_at_depth(&goal, round, options.use_cut, &mut checked_sequents)
+            {
+                return Outcome::Proof(proof.structural_normal_form());
+            }
+        }
+    }
+    diagnostics.push(format!(
+        "no proof of depth {} or less",
+        options.max_proof_depth
+    ));
+    diagnostics.push(format!(
+        "no countermodel of size {} or less",
+        options.max_domain_size
+    ));
+    Outcome::Unknown(diagnostics)
+}
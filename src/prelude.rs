@@ -0,0 +1,9 @@
+pub use crate::language::{Formula, NonLogicalSymbol, Term};
+pub use crate::parser::Parser;
+pub use crate::proof::{Proof, Sequent, LK};
+pub use crate::prover::FirstOrderProver;
+pub use crate::resolution::ResolutionProver;
+pub use crate::sat::{CdclSolver, SatSolver};
+pub use crate::solver::{LkProver, Prover};
+pub use crate::theory::Theory;
+pub use crate::tokenizer::Tokenizer;
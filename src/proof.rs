@@ -1,8 +1,9 @@
 use crate::language::*;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fmt::{self, Debug, Display, Formatter};
 
 #[derive(PartialEq, Eq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Sequent {
     pub antecedent: Vec<Formula>,
     pub succedent: Vec<Formula>,
@@ -25,6 +26,30 @@ impl Sequent {
         self.succedent.split_last().unwrap().1
     }
 
+    /// Renders the sequent as `((A1 A2 ...) (B1 B2 ...))`, the format read
+    /// back by `Parser::parse_sequent`.
+    pub fn to_sexpr(&self) -> String {
+        let fmls = |fmls: &[Formula]| {
+            fmls.iter()
+                .map(|fml| fml.to_sexpr())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        format!("(({}) ({}))", fmls(&self.antecedent), fmls(&self.succedent))
+    }
+
+    /// The formula `∧Γ → ∨Δ` valid exactly when the sequent `Γ ⇒ Δ` is, with
+    /// an empty antecedent left out and an empty succedent read as `⊥`.
+    pub fn to_formula(&self) -> Formula {
+        let join =
+            |fmls: &[Formula], f: fn(Formula, Formula) -> Formula| fmls.iter().cloned().reduce(f);
+        let succedent = join(&self.succedent, |l, r| or!(l, r)).unwrap_or(Formula::Bottom);
+        match join(&self.antecedent, |l, r| and!(l, r)) {
+            Some(antecedent) => implies!(antecedent, succedent),
+            None => succedent,
+        }
+    }
+
     pub fn get_subformulas(&self) -> HashSet<Formula> {
         [self.antecedent.clone(), self.succedent.clone()]
             .concat()
@@ -34,25 +59,236 @@ impl Sequent {
     }
 }
 
-impl Display for Sequent {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        write!(
-            f,
-            "{} ⇒  {}",
-            self.antecedent
-                .iter()
-                .map(|fml| format!("{}", fml))
-                .collect::<Vec<_>>()
-                .join(", "),
-            self.succedent
-                .iter()
-                .map(|fml| format!("{}", fml))
+impl Sequent {
+    pub fn to_string_with(&self, notation: Notation) -> String {
+        let fmls = |fmls: &[Formula]| {
+            fmls.iter()
+                .map(|fml| fml.to_string_with(notation))
                 .collect::<Vec<_>>()
                 .join(", ")
+        };
+        let arrow = match notation {
+            Notation::Unicode => "⇒ ",
+            Notation::Ascii => "=>",
+        };
+        format!(
+            "{} {} {}",
+            fmls(&self.antecedent),
+            arrow,
+            fmls(&self.succedent)
         )
     }
 }
 
+/// Naming scheme for the rule labels written next to inference lines.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum LabelScheme {
+    /// Connective and side, e.g. `∧R`, `wL`, `Cut`.
+    #[default]
+    Symbolic,
+    /// Spelled-out textbook names, e.g. `and right`, `weakening left`.
+    Named,
+    /// Gentzen's original German names, e.g. `UND-IS`, `Verdünnung`.
+    Gentzen,
+    /// User-supplied labels keyed by `LK::rule_name`, for instance a
+    /// translation. Rules without an entry fall back to `Symbolic`.
+    Custom(HashMap<String, String>),
+}
+
+impl LabelScheme {
+    /// Label of the rule named `rule` (see `LK::rule_name`), without the
+    /// parentheses added by the tree layout.
+    pub fn label(&self, rule: &str, notation: Notation) -> String {
+        let [not, and, or, implies, iff, forall, exists, _, _] = notation.symbols();
+        let (forall, exists) = (forall.trim_end(), exists.trim_end());
+        let (connective, side) = match rule {
+            "AndLeft1" | "AndLeft2" | "AndRight" => (and, "und"),
+            "OrLeft" | "OrRight1" | "OrRight2" => (or, "oder"),
+            "ImpliesLeft" | "ImpliesRight" => (implies, "folgt"),
+            "IffLeft" | "IffRight" => (iff, "äquivalenz"),
+            "NotLeft" | "NotRight" => (not, "nicht"),
+            "ForallLeft" | "ForallRight" => (forall, "alle"),
+            "ExistsLeft" | "ExistsRight" => (exists, "es-gibt"),
+            "EqualityLeft" | "EqualityRight" => ("=", "gleichheit"),
+            _ => ("", ""),
+        };
+        let left = rule.contains("Left");
+        match self {
+            LabelScheme::Symbolic => match rule {
+                "Axiom" => "ax".to_string(),
+                "WeakeningLeft" => "wL".to_string(),
+                "WeakeningRight" => "wR".to_string(),
+                "ContractionLeft" => "cL".to_string(),
+                "ContractionRight" => "cR".to_string(),
+                "ExchangeLeft" => "xL".to_string(),
+                "ExchangeRight" => "xR".to_string(),
+                "Cut" => "Cut".to_string(),
+                _ => format!(
+                    "{}{}{}",
+                    connective,
+                    if left { "L" } else { "R" },
+                    rule.trim_start_matches(|c: char| !c.is_ascii_digit())
+                ),
+            },
+            LabelScheme::Named => {
+                let mut words = String::new();
+                for c in rule.chars() {
+                    if c.is_uppercase() && !words.is_empty() {
+                        words.push(' ');
+                    }
+                    if c.is_ascii_digit() {
+                        words.push(' ');
+                    }
+                    words.extend(c.to_lowercase());
+                }
+                words
+            }
+            LabelScheme::Gentzen => {
+                let label = match rule {
+                    "Axiom" => "Grundsequenz".to_string(),
+                    "WeakeningLeft" | "WeakeningRight" => "Verdünnung".to_string(),
+                    "ContractionLeft" | "ContractionRight" => "Zusammenziehung".to_string(),
+                    "ExchangeLeft" | "ExchangeRight" => "Vertauschung".to_string(),
+                    "Cut" => "Schnitt".to_string(),
+                    _ => format!("{}-{}", side.to_uppercase(), if left { "IA" } else { "IS" }),
+                };
+                match notation {
+                    Notation::Unicode => label,
+                    Notation::Ascii => label.replace('ü', "ue").replace('Ä', "AE"),
+                }
+            }
+            LabelScheme::Custom(labels) => match labels.get(rule) {
+                Some(label) => label.clone(),
+                None => LabelScheme::Symbolic.label(rule, notation),
+            },
+        }
+    }
+}
+
+/// Options for rendering sequents and proofs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderOptions {
+    pub notation: Notation,
+    /// Sequents wider than this many characters are broken between
+    /// formulas, and overlong formulas at their connectives, continuing on
+    /// lines indented by `CONTINUATION_INDENT`.
+    pub max_width: Option<usize>,
+    pub labels: LabelScheme,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            notation: Notation::Unicode,
+            max_width: None,
+            labels: LabelScheme::Symbolic,
+        }
+    }
+}
+
+pub const CONTINUATION_INDENT: usize = 4;
+
+// Splits the rendering of `fml` into pieces no wider than `width` where
+// possible, breaking after binary connectives.
+fn _formula_pieces(fml: &Formula, notation: Notation, width: usize) -> Vec<String> {
+    let rendered = fml.to_string_with(notation);
+    if rendered.chars().count() <= width {
+        return vec![rendered];
+    }
+    let [not, and, or, implies, iff, forall, exists, _, _] = notation.symbols();
+    let prefixed = |prefix: String, fml: &Formula| {
+        let mut pieces = _formula_pieces(fml, notation, width);
+        pieces[0] = prefix + &pieces[0];
+        pieces
+    };
+    match fml {
+        Formula::And(lhs, rhs)
+        | Formula::Or(lhs, rhs)
+        | Formula::Implies(lhs, rhs)
+        | Formula::Iff(lhs, rhs) => {
+            let connective = match fml {
+                Formula::And(_, _) => and,
+                Formula::Or(_, _) => or,
+                Formula::Implies(_, _) => implies,
+                _ => iff,
+            };
+            let mut pieces = prefixed("(".to_string(), lhs);
+            *pieces.last_mut().unwrap() += &format!(" {}", connective);
+            pieces.extend(_formula_pieces(rhs, notation, width));
+            *pieces.last_mut().unwrap() += ")";
+            pieces
+        }
+        Formula::Not(fml) => prefixed(not.to_string(), fml),
+        Formula::Forall(var, fml) => prefixed(format!("{}{} ", forall, var), fml),
+        Formula::Exists(var, fml) => prefixed(format!("{}{} ", exists, var), fml),
+        _ => vec![rendered],
+    }
+}
+
+impl Sequent {
+    /// Renders the sequent, on several lines if it is wider than
+    /// `options.max_width`.
+    pub fn render(&self, options: &RenderOptions) -> String {
+        let line = self.to_string_with(options.notation);
+        let width = match options.max_width {
+            Some(width) if line.chars().count() > width => width,
+            _ => return line,
+        };
+        let inner = width.saturating_sub(CONTINUATION_INDENT).max(1);
+        let mut pieces = vec![];
+        for (i, fml) in self.antecedent.iter().enumerate() {
+            pieces.extend(_formula_pieces(fml, options.notation, inner));
+            if i + 1 < self.antecedent.len() {
+                *pieces.last_mut().unwrap() += ",";
+            }
+        }
+        pieces.push(
+            match options.notation {
+                Notation::Unicode => "⇒ ",
+                Notation::Ascii => "=>",
+            }
+            .to_string(),
+        );
+        for (i, fml) in self.succedent.iter().enumerate() {
+            pieces.extend(_formula_pieces(fml, options.notation, inner));
+            if i + 1 < self.succedent.len() {
+                *pieces.last_mut().unwrap() += ",";
+            }
+        }
+        let indent = " ".repeat(CONTINUATION_INDENT);
+        let mut lines: Vec<String> = vec![];
+        let mut current = String::new();
+        for piece in pieces {
+            if current.trim().is_empty() {
+                current += &piece;
+            } else if current.chars().count() + 1 + piece.chars().count() <= width {
+                current = current + " " + &piece;
+            } else {
+                lines.push(current);
+                current = indent.clone() + &piece;
+            }
+        }
+        lines.push(current);
+        lines.join("\n")
+    }
+}
+
+impl From<Formula> for Sequent {
+    /// The sequent `⇒ fml`.
+    fn from(fml: Formula) -> Sequent {
+        Sequent {
+            antecedent: vec![],
+            succedent: vec![fml],
+        }
+    }
+}
+
+impl Display for Sequent {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_string_with(Notation::Unicode))
+    }
+}
+
 impl Debug for Sequent {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         write!(
@@ -79,7 +315,9 @@ macro_rules! sequent{
     }};
 }
 
-#[derive(Debug, Clone)]
+/// With the `serde` feature, proofs serialize as the flat list of
+/// `crate::serialize`.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum LK {
     Axiom(Sequent),
     WeakeningLeft(Box<LK>, Sequent),
@@ -96,20 +334,33 @@ pub enum LK {
     OrRight2(Box<LK>, Sequent),
     ImpliesLeft(Box<[LK; 2]>, Sequent),
     ImpliesRight(Box<LK>, Sequent),
+    IffLeft(Box<[LK; 2]>, Sequent),
+    IffRight(Box<[LK; 2]>, Sequent),
     NotLeft(Box<LK>, Sequent),
     NotRight(Box<LK>, Sequent),
     ForallLeft(Box<LK>, Sequent),
     ForallRight(Box<LK>, Sequent),
     ExistsLeft(Box<LK>, Sequent),
     ExistsRight(Box<LK>, Sequent),
+    /// `A(s), Γ ⇒ Δ` to `s = t, A(t), Γ ⇒ Δ`, where `A(t)` is `A(s)` with
+    /// some occurrences of `s` replaced by `t`.
+    EqualityLeft(Box<LK>, Sequent),
+    /// `Γ ⇒ Δ, A(s)` to `s = t, Γ ⇒ Δ, A(t)`.
+    EqualityRight(Box<LK>, Sequent),
     Cut(Box<[LK; 2]>, Sequent),
+    /// An instance of a `SequentRule` of the theory the proof is checked
+    /// in, with as many premises as the rule.
+    TheoryRule(Vec<LK>, Sequent),
+    /// The initial sequent `⇒ A`, for `A` an instance of an axiom of the
+    /// theory the proof is checked in, see `Theory::has_instance`.
+    TheoryAxiom(Sequent),
 }
 
 impl LK {
     pub fn last(&self) -> &Sequent {
         use LK::*;
         match self {
-            Axiom(s) => s,
+            Axiom(s) | TheoryAxiom(s) => s,
             WeakeningLeft(_, s)
             | WeakeningRight(_, s)
             | ContractionLeft(_, s)
@@ -124,199 +375,356 @@ impl LK {
             | OrRight2(_, s)
             | ImpliesLeft(_, s)
             | ImpliesRight(_, s)
+            | IffLeft(_, s)
+            | IffRight(_, s)
             | NotLeft(_, s)
             | NotRight(_, s)
             | ForallLeft(_, s)
             | ForallRight(_, s)
             | ExistsLeft(_, s)
             | ExistsRight(_, s)
-            | Cut(_, s) => s,
+            | EqualityLeft(_, s)
+            | EqualityRight(_, s)
+            | Cut(_, s)
+            | TheoryRule(_, s) => s,
         }
     }
 
-    fn _get_prefix_spaces(s: String) -> u32 {
-        let s = s.split('\n').last().unwrap();
-        let mut len = 0;
-        for c in s.chars() {
-            if c == ' ' {
-                len += 1;
-            } else {
-                break;
-            }
+    pub fn premises(&self) -> Vec<&LK> {
+        use LK::*;
+        match self {
+            Axiom(_) => vec![],
+            WeakeningLeft(p, _)
+            | WeakeningRight(p, _)
+            | ContractionLeft(p, _)
+            | ContractionRight(p, _)
+            | ExchangeLeft(p, _)
+            | ExchangeRight(p, _)
+            | AndLeft1(p, _)
+            | AndLeft2(p, _)
+            | OrRight1(p, _)
+            | OrRight2(p, _)
+            | ImpliesRight(p, _)
+            | NotLeft(p, _)
+            | NotRight(p, _)
+            | ForallLeft(p, _)
+            | ForallRight(p, _)
+            | ExistsLeft(p, _)
+            | ExistsRight(p, _)
+            | EqualityLeft(p, _)
+            | EqualityRight(p, _) => vec![&**p],
+            AndRight(ps, _)
+            | OrLeft(ps, _)
+            | ImpliesLeft(ps, _)
+            | IffLeft(ps, _)
+            | IffRight(ps, _)
+            | Cut(ps, _) => ps.iter().collect(),
+            TheoryRule(ps, _) => ps.iter().collect(),
+            TheoryAxiom(_) => vec![],
         }
-        len
     }
 
-    fn _get_suffix_spaces(s: String) -> u32 {
-        let s = s.split('\n').last().unwrap();
-        let mut len = 0;
-        for c in s.chars().rev() {
-            if c == ' ' {
-                len += 1;
-            } else {
-                break;
-            }
+    /// The same inference with its premises replaced, in the order of
+    /// `premises`.
+    pub fn with_premises(&self, mut premises: Vec<LK>) -> LK {
+        use LK::*;
+        assert_eq!(self.premises().len(), premises.len());
+        let s = self.last().clone();
+        match self {
+            Axiom(_) => return Axiom(s),
+            TheoryRule(_, _) => return TheoryRule(premises, s),
+            TheoryAxiom(_) => return TheoryAxiom(s),
+            _ => {}
+        }
+        if premises.len() == 2 {
+            let rpremise = premises.pop().unwrap();
+            let lpremise = premises.pop().unwrap();
+            let ps = Box::new([lpremise, rpremise]);
+            return match self {
+                AndRight(_, _) => AndRight(ps, s),
+                OrLeft(_, _) => OrLeft(ps, s),
+                ImpliesLeft(_, _) => ImpliesLeft(ps, s),
+                IffLeft(_, _) => IffLeft(ps, s),
+                IffRight(_, _) => IffRight(ps, s),
+                _ => Cut(ps, s),
+            };
         }
-        len
+        let p = Box::new(premises.pop().unwrap());
+        match self {
+            WeakeningLeft(_, _) => WeakeningLeft(p, s),
+            WeakeningRight(_, _) => WeakeningRight(p, s),
+            ContractionLeft(_, _) => ContractionLeft(p, s),
+            ContractionRight(_, _) => ContractionRight(p, s),
+            ExchangeLeft(_, _) => ExchangeLeft(p, s),
+            ExchangeRight(_, _) => ExchangeRight(p, s),
+            AndLeft1(_, _) => AndLeft1(p, s),
+            AndLeft2(_, _) => AndLeft2(p, s),
+            OrRight1(_, _) => OrRight1(p, s),
+            OrRight2(_, _) => OrRight2(p, s),
+            ImpliesRight(_, _) => ImpliesRight(p, s),
+            NotLeft(_, _) => NotLeft(p, s),
+            NotRight(_, _) => NotRight(p, s),
+            ForallLeft(_, _) => ForallLeft(p, s),
+            ForallRight(_, _) => ForallRight(p, s),
+            ExistsLeft(_, _) => ExistsLeft(p, s),
+            ExistsRight(_, _) => ExistsRight(p, s),
+            EqualityLeft(_, _) => EqualityLeft(p, s),
+            _ => EqualityRight(p, s),
+        }
+    }
+
+    /// The inference named `rule` as by `rule_name`, or `None` if there is no
+    /// such rule or it takes another number of premises.
+    pub fn from_parts(rule: &str, mut premises: Vec<LK>, sequent: Sequent) -> Option<LK> {
+        use LK::*;
+        let unary: fn(Box<LK>, Sequent) -> LK = match rule {
+            "Axiom" if premises.is_empty() => return Some(Axiom(sequent)),
+            "TheoryRule" if premises.len() <= 1 => return Some(TheoryRule(premises, sequent)),
+            "TheoryAxiom" if premises.is_empty() => return Some(TheoryAxiom(sequent)),
+            "AndRight" | "OrLeft" | "ImpliesLeft" | "IffLeft" | "IffRight" | "Cut"
+                if premises.len() == 2 =>
+            {
+                let rpremise = premises.pop().unwrap();
+                let lpremise = premises.pop().unwrap();
+                let ps = Box::new([lpremise, rpremise]);
+                return Some(match rule {
+                    "AndRight" => AndRight(ps, sequent),
+                    "OrLeft" => OrLeft(ps, sequent),
+                    "ImpliesLeft" => ImpliesLeft(ps, sequent),
+                    "IffLeft" => IffLeft(ps, sequent),
+                    "IffRight" => IffRight(ps, sequent),
+                    _ => Cut(ps, sequent),
+                });
+            }
+            _ if premises.len() != 1 => return None,
+            "WeakeningLeft" => WeakeningLeft,
+            "WeakeningRight" => WeakeningRight,
+            "ContractionLeft" => ContractionLeft,
+            "ContractionRight" => ContractionRight,
+            "ExchangeLeft" => ExchangeLeft,
+            "ExchangeRight" => ExchangeRight,
+            "AndLeft1" => AndLeft1,
+            "AndLeft2" => AndLeft2,
+            "OrRight1" => OrRight1,
+            "OrRight2" => OrRight2,
+            "ImpliesRight" => ImpliesRight,
+            "NotLeft" => NotLeft,
+            "NotRight" => NotRight,
+            "ForallLeft" => ForallLeft,
+            "ForallRight" => ForallRight,
+            "ExistsLeft" => ExistsLeft,
+            "ExistsRight" => ExistsRight,
+            "EqualityLeft" => EqualityLeft,
+            "EqualityRight" => EqualityRight,
+            _ => return None,
+        };
+        Some(unary(Box::new(premises.pop().unwrap()), sequent))
     }
 
-    fn _last_line_len(s: String) -> u32 {
-        let s = s.split('\n').last().unwrap();
-        (s.chars().count() as i32 - LK::_get_prefix_spaces(s.into()) as i32) as u32
+    /// The same proof with `f` applied to every sequent.
+    pub fn map_sequents<F: FnMut(&Sequent) -> Sequent>(&self, f: &mut F) -> LK {
+        let premises = self
+            .premises()
+            .iter()
+            .map(|p| p.map_sequents(f))
+            .collect::<Vec<_>>();
+        let mut proof = self.with_premises(premises);
+        let sequent = f(self.last());
+        proof._set_last(sequent);
+        proof
     }
 
-    fn _get_label(&self) -> String {
+    fn _set_last(&mut self, sequent: Sequent) {
         use LK::*;
         match self {
-            Axiom(_) => "(ax)".to_string(),
-            WeakeningLeft(_, _) => "(wL)".to_string(),
-            WeakeningRight(_, _) => "(wR)".to_string(),
-            ContractionLeft(_, _) => "(cL)".to_string(),
-            ContractionRight(_, _) => "(cR)".to_string(),
-            ExchangeLeft(_, _) => "(xL)".to_string(),
-            ExchangeRight(_, _) => "(xR)".to_string(),
-            AndLeft1(_, _) => "(∧L1)".to_string(),
-            AndLeft2(_, _) => "(∧L2)".to_string(),
-            AndRight(_, _) => "(∧R)".to_string(),
-            OrLeft(_, _) => "(∨L)".to_string(),
-            OrRight1(_, _) => "(∨R1)".to_string(),
-            OrRight2(_, _) => "(∨R2)".to_string(),
-            ImpliesLeft(_, _) => "(→L)".to_string(),
-            ImpliesRight(_, _) => "(→R)".to_string(),
-            NotLeft(_, _) => "(¬L)".to_string(),
-            NotRight(_, _) => "(¬R)".to_string(),
-            ForallLeft(_, _) => "(∀L)".to_string(),
-            ForallRight(_, _) => "(∀R)".to_string(),
-            ExistsLeft(_, _) => "(∃L)".to_string(),
-            ExistsRight(_, _) => "(∃R)".to_string(),
-            Cut(_, _) => "(Cut)".to_string(),
+            Axiom(s) | TheoryAxiom(s) => *s = sequent,
+            WeakeningLeft(_, s)
+            | WeakeningRight(_, s)
+            | ContractionLeft(_, s)
+            | ContractionRight(_, s)
+            | ExchangeLeft(_, s)
+            | ExchangeRight(_, s)
+            | AndLeft1(_, s)
+            | AndLeft2(_, s)
+            | AndRight(_, s)
+            | OrLeft(_, s)
+            | OrRight1(_, s)
+            | OrRight2(_, s)
+            | ImpliesLeft(_, s)
+            | ImpliesRight(_, s)
+            | IffLeft(_, s)
+            | IffRight(_, s)
+            | NotLeft(_, s)
+            | NotRight(_, s)
+            | ForallLeft(_, s)
+            | ForallRight(_, s)
+            | ExistsLeft(_, s)
+            | ExistsRight(_, s)
+            | EqualityLeft(_, s)
+            | EqualityRight(_, s)
+            | Cut(_, s)
+            | TheoryRule(_, s) => *s = sequent,
         }
     }
 
-    fn _join_sequent_str(
-        &self,
-        parent_str: String,
-        sequent_str: String,
-        parent_body_prefix: u32,
-        parent_body_len: u32,
-    ) -> String {
-        let mut parent_str = parent_str;
-        let mut sequent_str = sequent_str;
-        let sequent_len = sequent_str.chars().count();
-        let mut offset =
-            (parent_body_len as i32 - sequent_len as i32) / 2 + parent_body_prefix as i32;
-        if offset > 0 {
-            sequent_str = (0..offset).map(|_| " ").collect::<String>() + &sequent_str;
-        } else {
-            parent_str = parent_str
-                .split("\n")
-                .map(|l| (0..-offset).map(|_| " ").collect::<String>() + l)
-                .collect::<Vec<_>>()
-                .join("\n");
-            offset = 0;
+    /// Name of the inference rule at the root, e.g. `"AndRight"`.
+    pub fn rule_name(&self) -> &'static str {
+        use LK::*;
+        match self {
+            Axiom(_) => "Axiom",
+            WeakeningLeft(_, _) => "WeakeningLeft",
+            WeakeningRight(_, _) => "WeakeningRight",
+            ContractionLeft(_, _) => "ContractionLeft",
+            ContractionRight(_, _) => "ContractionRight",
+            ExchangeLeft(_, _) => "ExchangeLeft",
+            ExchangeRight(_, _) => "ExchangeRight",
+            AndLeft1(_, _) => "AndLeft1",
+            AndLeft2(_, _) => "AndLeft2",
+            AndRight(_, _) => "AndRight",
+            OrLeft(_, _) => "OrLeft",
+            OrRight1(_, _) => "OrRight1",
+            OrRight2(_, _) => "OrRight2",
+            ImpliesLeft(_, _) => "ImpliesLeft",
+            ImpliesRight(_, _) => "ImpliesRight",
+            IffLeft(_, _) => "IffLeft",
+            IffRight(_, _) => "IffRight",
+            NotLeft(_, _) => "NotLeft",
+            NotRight(_, _) => "NotRight",
+            ForallLeft(_, _) => "ForallLeft",
+            ForallRight(_, _) => "ForallRight",
+            ExistsLeft(_, _) => "ExistsLeft",
+            ExistsRight(_, _) => "ExistsRight",
+            EqualityLeft(_, _) => "EqualityLeft",
+            EqualityRight(_, _) => "EqualityRight",
+            Cut(_, _) => "Cut",
+            TheoryRule(_, _) => "TheoryRule",
+            TheoryAxiom(_) => "TheoryAxiom",
         }
-        let sep_line = if sequent_len > parent_body_len as usize {
-            (0..offset).map(|_| " ").collect::<String>()
-                + &(0..sequent_len + 1).map(|_| "-").collect::<String>()
-                + &self._get_label()
-        } else {
-            (0..parent_body_prefix).map(|_| " ").collect::<String>()
-                + &(0..parent_body_len + 1).map(|_| "-").collect::<String>()
-                + &self._get_label()
-        };
-        sequent_str = parent_str + "\n" + &sep_line + "\n" + &sequent_str;
-        let max_len = sequent_str
-            .split("\n")
-            .map(|l| l.chars().count())
-            .fold(0, |m, v| m.max(v));
-        sequent_str = sequent_str
-            .split("\n")
-            .map(|l| {
-                l.to_string()
-                    + &(0..(max_len - l.chars().count()))
-                        .map(|_| " ")
-                        .collect::<String>()
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
-        sequent_str
-    }
-
-    pub fn to_string(&self) -> String {
+    }
+
+    // The least numbers of antecedent and succedent formulas of the
+    // conclusion and of each premise that the rule looks into.
+    pub(crate) fn min_lengths(&self) -> ((usize, usize), Vec<(usize, usize)>) {
+        use LK::*;
         match self {
-            LK::Axiom(s) => {
-                format!("{}", s)
-            }
-            LK::WeakeningLeft(parent, sequent)
-            | LK::WeakeningRight(parent, sequent)
-            | LK::ContractionLeft(parent, sequent)
-            | LK::ContractionRight(parent, sequent)
-            | LK::ExchangeLeft(parent, sequent)
-            | LK::ExchangeRight(parent, sequent)
-            | LK::AndLeft1(parent, sequent)
-            | LK::AndLeft2(parent, sequent)
-            | LK::OrRight1(parent, sequent)
-            | LK::OrRight2(parent, sequent)
-            | LK::ImpliesRight(parent, sequent)
-            | LK::NotLeft(parent, sequent)
-            | LK::NotRight(parent, sequent)
-            | LK::ForallLeft(parent, sequent)
-            | LK::ForallRight(parent, sequent)
-            | LK::ExistsLeft(parent, sequent)
-            | LK::ExistsRight(parent, sequent) => {
-                let parent_str = parent.to_string();
-                let parent_len = parent_str.split("\n").last().unwrap().chars().count();
-                let prefix_spaces = LK::_get_prefix_spaces(parent_str.clone());
-                let suffix_spaces = LK::_get_suffix_spaces(parent_str.clone());
-                let parent_body_len = parent_len - prefix_spaces as usize - suffix_spaces as usize;
-                let sequent_str = format!("{}", sequent);
-                self._join_sequent_str(
-                    parent_str,
-                    sequent_str,
-                    prefix_spaces,
-                    parent_body_len as u32,
-                )
-            }
-            LK::AndRight(premises, sequent)
-            | LK::OrLeft(premises, sequent)
-            | LK::ImpliesLeft(premises, sequent)
-            | LK::Cut(premises, sequent) => {
-                let [lhs, rhs] = &**premises;
-                let mut left_str = lhs.to_string();
-                let mut right_str = rhs.to_string();
-                let prefix_spaces = LK::_get_prefix_spaces(left_str.clone());
-                let suffix_spaces = LK::_get_suffix_spaces(right_str.clone());
-                let mut left_lines = left_str.split("\n").collect::<Vec<_>>().len();
-                let right_lines = right_str.split("\n").collect::<Vec<_>>().len();
-                if left_lines < right_lines {
-                    left_str = (0..right_lines - left_lines)
-                        .map(|_| "\n")
-                        .collect::<String>()
-                        + &left_str;
-                    left_lines = right_lines;
-                } else {
-                    right_str = (0..left_lines - right_lines)
-                        .map(|_| "\n")
-                        .collect::<String>()
-                        + &right_str;
-                }
-                let lefts = left_str.split("\n").collect::<Vec<_>>();
-                let rights = right_str.split("\n").collect::<Vec<_>>();
-                let parent_str = (0..left_lines)
-                    .map(|l| lefts[l].to_string() + "    " + rights[l])
-                    .collect::<Vec<_>>()
-                    .join("\n");
-                let parent_body_len = LK::_last_line_len(parent_str.clone()) as i32
-                    - prefix_spaces as i32
-                    - suffix_spaces as i32;
-                let sequent_str = format!("{}", sequent);
-                self._join_sequent_str(
-                    parent_str,
-                    sequent_str,
-                    prefix_spaces,
-                    parent_body_len as u32,
-                )
+            Axiom(_) => ((0, 0), vec![]),
+            WeakeningLeft(_, _) => ((1, 0), vec![(0, 0)]),
+            WeakeningRight(_, _) => ((0, 1), vec![(0, 0)]),
+            ContractionLeft(_, _) => ((0, 0), vec![(2, 0)]),
+            ContractionRight(_, _) => ((0, 0), vec![(0, 2)]),
+            ExchangeLeft(_, _) => ((2, 0), vec![(2, 0)]),
+            ExchangeRight(_, _) => ((0, 2), vec![(0, 2)]),
+            AndLeft1(_, _) | AndLeft2(_, _) | ForallLeft(_, _) | ExistsLeft(_, _) => {
+                ((1, 0), vec![(1, 0)])
             }
+            OrRight1(_, _) | OrRight2(_, _) | ForallRight(_, _) | ExistsRight(_, _) => {
+                ((0, 1), vec![(0, 1)])
+            }
+            AndRight(_, _) => ((0, 1), vec![(0, 1), (0, 1)]),
+            OrLeft(_, _) => ((1, 0), vec![(1, 0), (1, 0)]),
+            ImpliesLeft(_, _) => ((1, 0), vec![(0, 1), (1, 0)]),
+            ImpliesRight(_, _) => ((0, 1), vec![(1, 1)]),
+            IffLeft(_, _) => ((1, 0), vec![(2, 0), (0, 2)]),
+            IffRight(_, _) => ((0, 1), vec![(1, 1), (1, 1)]),
+            NotLeft(_, _) => ((1, 0), vec![(0, 1)]),
+            NotRight(_, _) => ((0, 1), vec![(1, 0)]),
+            EqualityLeft(_, _) => ((2, 0), vec![(1, 0)]),
+            EqualityRight(_, _) => ((1, 1), vec![(0, 1)]),
+            Cut(_, _) => ((0, 0), vec![(0, 1), (1, 0)]),
+            TheoryRule(ps, _) => ((0, 0), vec![(0, 0); ps.len()]),
+            TheoryAxiom(_) => ((0, 1), vec![]),
+        }
+    }
+
+    /// Number of inferences.
+    pub fn size(&self) -> usize {
+        let mut size = 0;
+        let mut stack = vec![self];
+        while let Some(proof) = stack.pop() {
+            size += 1;
+            stack.extend(proof.premises());
+        }
+        size
+    }
+
+    pub fn height(&self) -> u32 {
+        1 + self
+            .premises()
+            .iter()
+            .map(|p| p.height())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Walks the proof premises first, left to right, so every inference is
+    /// passed to `visitor` after the inferences deriving its premises.
+    pub fn replay<V: ProofVisitor + ?Sized>(&self, visitor: &mut V) {
+        let premises = self.premises();
+        for premise in &premises {
+            premise.replay(visitor);
+        }
+        let sequents = premises.iter().map(|p| p.last()).collect::<Vec<_>>();
+        visitor.inference(self.rule_name(), &sequents, self.last());
+    }
+}
+
+impl AsRef<Sequent> for LK {
+    fn as_ref(&self) -> &Sequent {
+        self.last()
+    }
+}
+
+// Placeholder left behind by premises taken out of a proof being dropped.
+fn _empty_proof() -> LK {
+    LK::Axiom(Sequent {
+        antecedent: vec![],
+        succedent: vec![],
+    })
+}
+
+// Drops the premises with an explicit stack, as the derived recursive drop
+// overflows the stack on deep proofs.
+impl Drop for LK {
+    fn drop(&mut self) {
+        let take = |proof: &mut LK| -> Vec<LK> {
+            use LK::*;
+            match proof {
+                Axiom(_) => vec![],
+                WeakeningLeft(p, _)
+                | WeakeningRight(p, _)
+                | ContractionLeft(p, _)
+                | ContractionRight(p, _)
+                | ExchangeLeft(p, _)
+                | ExchangeRight(p, _)
+                | AndLeft1(p, _)
+                | AndLeft2(p, _)
+                | OrRight1(p, _)
+                | OrRight2(p, _)
+                | ImpliesRight(p, _)
+                | NotLeft(p, _)
+                | NotRight(p, _)
+                | ForallLeft(p, _)
+                | ForallRight(p, _)
+                | ExistsLeft(p, _)
+                | ExistsRight(p, _)
+                | EqualityLeft(p, _)
+                | EqualityRight(p, _) => vec![std::mem::replace(&mut **p, _empty_proof())],
+                AndRight(ps, _)
+                | OrLeft(ps, _)
+                | ImpliesLeft(ps, _)
+                | IffLeft(ps, _)
+                | IffRight(ps, _)
+                | Cut(ps, _) => ps
+                    .iter_mut()
+                    .map(|p| std::mem::replace(p, _empty_proof()))
+                    .collect(),
+                TheoryRule(ps, _) => std::mem::take(ps),
+                TheoryAxiom(_) => vec![],
+            }
+        };
+        let mut stack = take(self);
+        while let Some(mut proof) = stack.pop() {
+            stack.extend(take(&mut proof));
         }
     }
 }
@@ -327,12 +735,128 @@ impl Display for LK {
     }
 }
 
-pub trait Proof {
+/// Observer for `LK::replay`, called once per inference with the rule name
+/// as given by `LK::rule_name`.
+pub trait ProofVisitor {
+    fn inference(&mut self, rule: &'static str, premises: &[&Sequent], conclusion: &Sequent);
+}
+
+impl<F: FnMut(&'static str, &[&Sequent], &Sequent)> ProofVisitor for F {
+    fn inference(&mut self, rule: &'static str, premises: &[&Sequent], conclusion: &Sequent) {
+        self(rule, premises, conclusion)
+    }
+}
+
+pub trait Proof: crate::sealed::Sealed {
     fn is_valid_inference(&self) -> bool;
+
+    /// Whether every inference of the proof is valid. Walks the proof with
+    /// an explicit stack, so arbitrarily deep proofs can be checked.
+    fn is_valid_proof(&self) -> bool;
 }
 
 impl Proof for LK {
+    fn is_valid_proof(&self) -> bool {
+        let mut stack = vec![self];
+        while let Some(proof) = stack.pop() {
+            if !proof.is_valid_inference() {
+                return false;
+            }
+            stack.extend(proof.premises());
+        }
+        true
+    }
+
     fn is_valid_inference(&self) -> bool {
+        self.is_valid_inference_with(None)
+    }
+}
+
+// Whether `to` is `from` with some occurrences of `s` replaced by `t`, none
+// of them in the scope of a quantifier binding a variable of `s` or `t`.
+fn _replaces(from: &Formula, to: &Formula, s: &Term, t: &Term) -> bool {
+    let vars = s
+        .get_vars()
+        .union(&t.get_vars())
+        .cloned()
+        .collect::<Vec<_>>();
+    _replaces_in(from, to, s, t, &mut vec![], &vars)
+}
+
+fn _replaces_term(from: &Term, to: &Term, s: &Term, t: &Term, free: bool) -> bool {
+    from == to
+        || (free && from == s && to == t)
+        || match (from, to) {
+            (Term::Func(f, xs), Term::Func(g, ys)) => {
+                f == g
+                    && xs.len() == ys.len()
+                    && xs
+                        .iter()
+                        .zip(ys)
+                        .all(|(x, y)| _replaces_term(x, y, s, t, free))
+            }
+            _ => false,
+        }
+}
+
+fn _replaces_in(
+    from: &Formula,
+    to: &Formula,
+    s: &Term,
+    t: &Term,
+    bound: &mut Vec<Term>,
+    vars: &[Term],
+) -> bool {
+    use Formula::*;
+    let free = !bound.iter().any(|v| vars.contains(v));
+    match (from, to) {
+        (Pred(p, xs), Pred(q, ys)) => {
+            p == q
+                && xs.len() == ys.len()
+                && xs
+                    .iter()
+                    .zip(ys)
+                    .all(|(x, y)| _replaces_term(x, y, s, t, free))
+        }
+        (Equal(a, b), Equal(c, d)) => {
+            _replaces_term(a, c, s, t, free) && _replaces_term(b, d, s, t, free)
+        }
+        (Not(f), Not(g)) => _replaces_in(f, g, s, t, bound, vars),
+        (And(a, b), And(c, d))
+        | (Or(a, b), Or(c, d))
+        | (Implies(a, b), Implies(c, d))
+        | (Iff(a, b), Iff(c, d)) => {
+            _replaces_in(a, c, s, t, bound, vars) && _replaces_in(b, d, s, t, bound, vars)
+        }
+        (Forall(x, f), Forall(y, g)) | (Exists(x, f), Exists(y, g)) if x == y => {
+            bound.push(x.clone());
+            let replaces = _replaces_in(f, g, s, t, bound, vars);
+            bound.pop();
+            replaces
+        }
+        _ => from == to,
+    }
+}
+
+impl LK {
+    /// Same as `is_valid_inference`, but for quantifier inferences only
+    /// `instantiation`, if given, is tried as the instance term or
+    /// eigenvariable instead of every candidate.
+    pub(crate) fn is_valid_inference_with(&self, instantiation: Option<&Term>) -> bool {
+        let annotated = |term: &&Term| instantiation.is_none_or(|i| i == *term);
+        let fits = |sequent: &Sequent, (ant, suc): (usize, usize)| {
+            sequent.antecedent.len() >= ant && sequent.succedent.len() >= suc
+        };
+        let (min_conclusion, min_premises) = self.min_lengths();
+        if !fits(self.last(), min_conclusion)
+            || !self
+                .premises()
+                .iter()
+                .zip(min_premises)
+                .all(|(premise, min)| fits(premise.last(), min))
+        {
+            return false;
+        }
         match self {
             LK::Axiom(conclusion) => {
                 (conclusion.antecedent == conclusion.succedent && conclusion.antecedent.len() > 0)
@@ -340,8 +864,11 @@ impl Proof for LK {
                         && conclusion.succedent.len() == 1
                         && match conclusion.suc_last() {
                             Formula::Equal(s, t) => s == t,
+                            Formula::Top => true,
                             _ => false,
                         })
+                    || (conclusion.antecedent == [Formula::Bottom]
+                        && conclusion.succedent.is_empty())
             }
             LK::WeakeningLeft(premise, conclusion) => {
                 premise.last().antecedent == conclusion.ant_but_first()
@@ -362,7 +889,9 @@ impl Proof for LK {
                     && premise.last().suc_but_last() == conclusion.succedent
             }
             LK::ExchangeLeft(premise, conclusion) => {
-                if premise.last().succedent == conclusion.succedent {
+                if premise.last().succedent == conclusion.succedent
+                    && premise.last().antecedent.len() == conclusion.antecedent.len()
+                {
                     let mut valid = false;
                     for i in 0..premise.last().antecedent.len() - 1 {
                         if premise.last().antecedent[..i] == conclusion.antecedent[..i]
@@ -380,7 +909,9 @@ impl Proof for LK {
                 }
             }
             LK::ExchangeRight(premise, conclusion) => {
-                if premise.last().antecedent == conclusion.antecedent {
+                if premise.last().antecedent == conclusion.antecedent
+                    && premise.last().succedent.len() == conclusion.succedent.len()
+                {
                     let mut valid = false;
                     for i in 0..premise.last().succedent.len() - 1 {
                         if premise.last().succedent[..i] == conclusion.succedent[..i]
@@ -483,6 +1014,45 @@ impl Proof for LK {
                         false
                     }
             }
+            LK::IffLeft(premises, conclusion) => {
+                let [lpremise, rpremise] = &**premises;
+                !conclusion.antecedent.is_empty()
+                    && lpremise.last().antecedent.len() >= 2
+                    && rpremise.last().succedent.len() >= 2
+                    && lpremise.last().succedent == conclusion.succedent
+                    && rpremise.last().antecedent == conclusion.ant_but_first()
+                    && lpremise.last().antecedent[2..] == *conclusion.ant_but_first()
+                    && rpremise.last().succedent[..rpremise.last().succedent.len() - 2]
+                        == conclusion.succedent[..]
+                    && if let Formula::Iff(lhs, rhs) = conclusion.ant_first() {
+                        let n = rpremise.last().succedent.len();
+                        lpremise.last().antecedent[0] == **lhs
+                            && lpremise.last().antecedent[1] == **rhs
+                            && rpremise.last().succedent[n - 2] == **lhs
+                            && rpremise.last().succedent[n - 1] == **rhs
+                    } else {
+                        false
+                    }
+            }
+            LK::IffRight(premises, conclusion) => {
+                let [lpremise, rpremise] = &**premises;
+                !conclusion.succedent.is_empty()
+                    && [lpremise, rpremise]
+                        .iter()
+                        .all(|p| !p.last().antecedent.is_empty() && !p.last().succedent.is_empty())
+                    && lpremise.last().ant_but_first() == &conclusion.antecedent[..]
+                    && rpremise.last().ant_but_first() == &conclusion.antecedent[..]
+                    && lpremise.last().suc_but_last() == conclusion.suc_but_last()
+                    && rpremise.last().suc_but_last() == conclusion.suc_but_last()
+                    && if let Formula::Iff(lhs, rhs) = conclusion.suc_last() {
+                        lpremise.last().ant_first() == &**lhs
+                            && lpremise.last().suc_last() == &**rhs
+                            && rpremise.last().ant_first() == &**rhs
+                            && rpremise.last().suc_last() == &**lhs
+                    } else {
+                        false
+                    }
+            }
             LK::NotLeft(premise, conclusion) => {
                 &premise.last().antecedent[..] == conclusion.ant_but_first()
                     && premise.last().suc_but_last() == conclusion.succedent
@@ -505,23 +1075,19 @@ impl Proof for LK {
                 premise.last().succedent == conclusion.succedent
                     && premise.last().ant_but_first() == conclusion.ant_but_first()
                     && if let Formula::Forall(var, fml) = &conclusion.ant_first() {
-                        if !fml.get_bound_vars().contains(var) {
-                            let mut valid = false;
-                            for term in premise.last().ant_first().get_subterms() {
-                                if fml.is_substitutible(var.clone(), term.clone()) {
-                                    let tfml = fml.substitute(var.clone(), term);
-                                    if &tfml == premise.last().ant_first() {
-                                        valid = true;
-                                        break;
-                                    }
-                                } else {
-                                    break;
-                                }
+                        // Any term instantiates a vacuous quantifier.
+                        let mut valid = !fml.get_free_vars().contains(var)
+                            && &**fml == premise.last().ant_first();
+                        for term in premise.last().ant_first().iter_subterms().filter(annotated) {
+                            if fml.is_substitutible(var.clone(), term.clone())
+                                && &fml.substitute(var.clone(), term.clone())
+                                    == premise.last().ant_first()
+                            {
+                                valid = true;
+                                break;
                             }
-                            valid
-                        } else {
-                            false
                         }
+                        valid
                     } else {
                         false
                     }
@@ -530,11 +1096,19 @@ impl Proof for LK {
                 premise.last().antecedent == conclusion.antecedent
                     && premise.last().suc_but_last() == conclusion.suc_but_last()
                     && if let Formula::Forall(term, fml) = &conclusion.suc_last() {
-                        let mut valid = false;
-                        for var in premise.last().suc_last().get_free_vars() {
+                        let free = conclusion.suc_last().get_free_vars();
+                        let mut valid = !fml.get_free_vars().contains(term)
+                            && &**fml == premise.last().suc_last();
+                        for var in premise
+                            .last()
+                            .suc_last()
+                            .get_free_vars()
+                            .iter()
+                            .filter(annotated)
+                        {
                             if fml.is_substitutible(term.clone(), var.clone()) {
                                 let tfml = fml.substitute(term.clone(), var.clone());
-                                if &tfml == premise.last().suc_last() {
+                                if &tfml == premise.last().suc_last() && !free.contains(var) {
                                     if !premise
                                         .last()
                                         .antecedent
@@ -565,23 +1139,18 @@ impl Proof for LK {
                 premise.last().antecedent == conclusion.antecedent
                     && premise.last().suc_but_last() == conclusion.suc_but_last()
                     && if let Formula::Exists(Term::Var(s), fml) = &conclusion.suc_last() {
-                        if !fml.get_bound_vars().contains(&var!(s)) {
-                            let mut valid = false;
-                            for term in premise.last().suc_last().get_subterms() {
-                                if fml.is_substitutible(var!(s), term.clone()) {
-                                    let tfml = fml.substitute(var!(s), term);
-                                    if &tfml == premise.last().suc_last() {
-                                        valid = true;
-                                        break;
-                                    }
-                                } else {
-                                    break;
-                                }
+                        let mut valid = !fml.get_free_vars().contains(&var!(s))
+                            && &**fml == premise.last().suc_last();
+                        for term in premise.last().suc_last().iter_subterms().filter(annotated) {
+                            if fml.is_substitutible(var!(s), term.clone())
+                                && &fml.substitute(var!(s), term.clone())
+                                    == premise.last().suc_last()
+                            {
+                                valid = true;
+                                break;
                             }
-                            valid
-                        } else {
-                            false
                         }
+                        valid
                     } else {
                         false
                     }
@@ -590,11 +1159,19 @@ impl Proof for LK {
                 premise.last().succedent == conclusion.succedent
                     && premise.last().ant_but_first() == conclusion.ant_but_first()
                     && if let Formula::Exists(term, fml) = &conclusion.ant_first() {
-                        let mut valid = false;
-                        for var in premise.last().ant_first().get_free_vars() {
+                        let free = conclusion.ant_first().get_free_vars();
+                        let mut valid = !fml.get_free_vars().contains(term)
+                            && &**fml == premise.last().ant_first();
+                        for var in premise
+                            .last()
+                            .ant_first()
+                            .get_free_vars()
+                            .iter()
+                            .filter(annotated)
+                        {
                             if fml.is_substitutible(term.clone(), var.clone()) {
                                 let tfml = fml.substitute(term.clone(), var.clone());
-                                if &tfml == premise.last().ant_first() {
+                                if &tfml == premise.last().ant_first() && !free.contains(var) {
                                     if !premise
                                         .last()
                                         .succedent
@@ -624,16 +1201,34 @@ impl Proof for LK {
             LK::Cut(premises, conclusion) => {
                 let [lpremise, rpremise] = &**premises;
                 if lpremise.last().suc_last() == rpremise.last().ant_first() {
-                    let gamma = &lpremise.last().antecedent;
+                    let gamma = &lpremise.last().antecedent[..];
                     let delta = lpremise.last().suc_but_last();
-                    let pi = &rpremise.last().ant_but_first();
+                    let pi = rpremise.last().ant_but_first();
                     let sigma = &rpremise.last().succedent[..];
-                    conclusion.antecedent == [gamma, delta].concat()
-                        && conclusion.succedent == [pi, sigma].concat()
+                    conclusion.antecedent == [gamma, pi].concat()
+                        && conclusion.succedent == [delta, sigma].concat()
                 } else {
                     false
                 }
             }
+            LK::EqualityLeft(premise, conclusion) => match conclusion.ant_first() {
+                Formula::Equal(s, t) => {
+                    premise.last().ant_but_first() == &conclusion.antecedent[2..]
+                        && premise.last().succedent == conclusion.succedent
+                        && _replaces(premise.last().ant_first(), &conclusion.antecedent[1], s, t)
+                }
+                _ => false,
+            },
+            LK::EqualityRight(premise, conclusion) => match conclusion.ant_first() {
+                Formula::Equal(s, t) => {
+                    premise.last().antecedent == conclusion.ant_but_first()
+                        && premise.last().suc_but_last() == conclusion.suc_but_last()
+                        && _replaces(premise.last().suc_last(), conclusion.suc_last(), s, t)
+                }
+                _ => false,
+            },
+            // Valid only with respect to a theory, see `CheckOptions::theory`.
+            LK::TheoryRule(_, _) | LK::TheoryAxiom(_) => false,
         }
     }
 }
@@ -0,0 +1,477 @@
+use crate::fresh::*;
+use crate::language::*;
+use crate::proof::*;
+use crate::trace::instantiation;
+use crate::visit::*;
+use std::collections::HashSet;
+use std::fmt;
+
+/// A term of the simply typed lambda calculus extended to first-order
+/// logic, whose type is the formula it proves (Curry–Howard). Proof
+/// variables are named by strings, separately from the variables of terms.
+/// `¬A` is read as `A → ⊥` and `A ↔ B` as `(A → B) ∧ (B → A)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofTerm {
+    Var(String),
+    /// `()`, of `⊤`.
+    Unit,
+    /// `λh: A. M`, of `A → B` for `M` of `B`.
+    Lambda(String, Formula, Box<ProofTerm>),
+    App(Box<ProofTerm>, Box<ProofTerm>),
+    Pair(Box<ProofTerm>, Box<ProofTerm>),
+    Fst(Box<ProofTerm>),
+    Snd(Box<ProofTerm>),
+    /// `inl M`, of the disjunction for `M` of its left side.
+    Inl(Box<ProofTerm>, Formula),
+    /// `inr M`, of the disjunction for `M` of its right side.
+    Inr(Box<ProofTerm>, Formula),
+    /// `case M of inl h1 ⇒ N1 | inr h2 ⇒ N2`.
+    Case(
+        Box<ProofTerm>,
+        String,
+        Box<ProofTerm>,
+        String,
+        Box<ProofTerm>,
+    ),
+    /// `abort M`, of the formula for `M` of `⊥`.
+    Abort(Box<ProofTerm>, Formula),
+    /// `Λy. M`, of the universal formula for `M` of its body with `y` for
+    /// the variable, `y` not free in the types of the context.
+    Generalize(Term, Box<ProofTerm>, Formula),
+    /// `M t`, of the instance by `t` of the universal formula of `M`.
+    Instantiate(Box<ProofTerm>, Term),
+    /// `⟨t, M⟩`, of the existential formula for `M` of its instance by `t`.
+    Pack(Term, Box<ProofTerm>, Formula),
+    /// `let ⟨y, h⟩ = M in N`, for `M` of `∃x A` and `N` with `h` of
+    /// `A[y/x]`, `y` not free in the type of `N` nor in the context.
+    Unpack(Box<ProofTerm>, Term, String, Box<ProofTerm>),
+    /// `refl t`, of `t = t`.
+    Refl(Term),
+    /// `rewrite e in M`, of the formula for `e` of `s = t` and `M` of the
+    /// formula with some occurrences of `t` replaced by `s` or the other
+    /// way round.
+    Rewrite(Box<ProofTerm>, Box<ProofTerm>, Formula),
+}
+
+// Reads negations and equivalences as implications.
+struct Unfold;
+
+impl TermFolder for Unfold {}
+
+impl FormulaFolder for Unfold {
+    fn fold_formula(&mut self, fml: &Formula) -> Formula {
+        match fml {
+            Formula::Not(fml) => implies!(self.fold_formula(fml), Formula::Bottom),
+            Formula::Iff(lhs, rhs) => {
+                let (lhs, rhs) = (self.fold_formula(lhs), self.fold_formula(rhs));
+                and!(implies!(lhs.clone(), rhs.clone()), implies!(rhs, lhs))
+            }
+            _ => fold_subformulas(self, fml),
+        }
+    }
+}
+
+fn _same(lhs: &Formula, rhs: &Formula) -> bool {
+    Unfold.fold_formula(lhs) == Unfold.fold_formula(rhs)
+}
+
+fn _infer(rule: &str, premises: Vec<LK>, sequent: Sequent) -> bool {
+    LK::from_parts(rule, premises, sequent).is_some_and(|proof| proof.is_valid_inference())
+}
+
+fn _axiom(fml: &Formula) -> LK {
+    LK::Axiom(Sequent {
+        antecedent: vec![fml.clone()],
+        succedent: vec![fml.clone()],
+    })
+}
+
+impl ProofTerm {
+    fn _free_names(&self, acc: &mut HashSet<String>) {
+        use ProofTerm::*;
+        let bound = |name: &String, body: &ProofTerm, acc: &mut HashSet<String>| {
+            let mut inner = HashSet::new();
+            body._free_names(&mut inner);
+            inner.remove(name);
+            acc.extend(inner);
+        };
+        match self {
+            Var(name) => {
+                acc.insert(name.clone());
+            }
+            Unit | Refl(_) => {}
+            Lambda(name, _, body) => bound(name, body, acc),
+            App(lhs, rhs) | Pair(lhs, rhs) | Rewrite(lhs, rhs, _) => {
+                lhs._free_names(acc);
+                rhs._free_names(acc);
+            }
+            Fst(term)
+            | Snd(term)
+            | Inl(term, _)
+            | Inr(term, _)
+            | Abort(term, _)
+            | Generalize(_, term, _)
+            | Instantiate(term, _)
+            | Pack(_, term, _) => term._free_names(acc),
+            Case(term, lhs, lhs_body, rhs, rhs_body) => {
+                term._free_names(acc);
+                bound(lhs, lhs_body, acc);
+                bound(rhs, rhs_body, acc);
+            }
+            Unpack(term, _, name, body) => {
+                term._free_names(acc);
+                bound(name, body, acc);
+            }
+        }
+    }
+
+    /// The proof variables free in the term.
+    pub fn free_names(&self) -> HashSet<String> {
+        let mut acc = HashSet::new();
+        self._free_names(&mut acc);
+        acc
+    }
+
+    /// The formula the term proves with the proof variables of `context`
+    /// proving its formulas, later ones shadowing earlier ones.
+    pub fn type_of(&self, context: &[(String, Formula)]) -> Result<Formula, &'static str> {
+        use Formula::*;
+        use ProofTerm::*;
+        let fail = "The term is not well typed.";
+        let unfolded = |term: &ProofTerm, context: &[(String, Formula)]| {
+            term.type_of(context).map(|fml| Unfold.fold_formula(&fml))
+        };
+        let with = |name: &String, fml: Formula| {
+            let mut context = context.to_vec();
+            context.push((name.clone(), fml));
+            context
+        };
+        // Whether `var` is free in `fmls` or in the types of the proof
+        // variables `term` uses.
+        let is_free = |var: &Term, term: &ProofTerm, fmls: &[&Formula]| {
+            let names = term.free_names();
+            (context.iter())
+                .filter(|(name, _)| names.contains(name))
+                .any(|(_, f)| f.get_free_vars().contains(var))
+                || fmls.iter().any(|f| f.get_free_vars().contains(var))
+        };
+        match self {
+            Var(name) => (context.iter().rev())
+                .find(|(n, _)| n == name)
+                .map(|(_, fml)| fml.clone())
+                .ok_or("The term has an unbound proof variable."),
+            Unit => Ok(Top),
+            Lambda(name, fml, body) => Ok(implies!(
+                fml.clone(),
+                body.type_of(&with(name, fml.clone()))?
+            )),
+            App(lhs, rhs) => match unfolded(lhs, context)? {
+                Implies(a, b) if _same(&a, &rhs.type_of(context)?) => Ok(*b),
+                _ => Err(fail),
+            },
+            Pair(lhs, rhs) => Ok(and!(lhs.type_of(context)?, rhs.type_of(context)?)),
+            Fst(term) | Snd(term) => match (self, unfolded(term, context)?) {
+                (Fst(_), And(a, _)) | (Snd(_), And(_, a)) => Ok(*a),
+                _ => Err(fail),
+            },
+            Inl(term, fml) | Inr(term, fml) => match (self, fml) {
+                (Inl(_, _), Or(a, _)) | (Inr(_, _), Or(_, a))
+                    if _same(a, &term.type_of(context)?) =>
+                {
+                    Ok(fml.clone())
+                }
+                _ => Err(fail),
+            },
+            Case(term, lhs, lhs_body, rhs, rhs_body) => match unfolded(term, context)? {
+                Or(a, b) => {
+                    let fml = lhs_body.type_of(&with(lhs, *a))?;
+                    match _same(&fml, &rhs_body.type_of(&with(rhs, *b))?) {
+                        true => Ok(fml),
+                        false => Err(fail),
+                    }
+                }
+                _ => Err(fail),
+            },
+            Abort(term, fml) => match term.type_of(context)? {
+                Bottom => Ok(fml.clone()),
+                _ => Err(fail),
+            },
+            Generalize(eigen, term, fml) => match fml {
+                Forall(var, body)
+                    if matches!(eigen, Term::Var(_))
+                        && !is_free(eigen, term, &[fml])
+                        && _same(
+                            &body.substitute(var.clone(), eigen.clone()),
+                            &term.type_of(context)?,
+                        ) =>
+                {
+                    Ok(fml.clone())
+                }
+                _ => Err(fail),
+            },
+            Instantiate(term, instance) => match term.type_of(context)? {
+                Forall(var, body) => Ok(body.substitute(var, instance.clone())),
+                _ => Err(fail),
+            },
+            Pack(instance, term, fml) => match fml {
+                Exists(var, body)
+                    if _same(
+                        &body.substitute(var.clone(), instance.clone()),
+                        &term.type_of(context)?,
+                    ) =>
+                {
+                    Ok(fml.clone())
+                }
+                _ => Err(fail),
+            },
+            Unpack(term, eigen, name, body) => match term.type_of(context)? {
+                fml @ Exists(_, _)
+                    if matches!(eigen, Term::Var(_)) && !is_free(eigen, self, &[&fml]) =>
+                {
+                    let (var, inner) = match &fml {
+                        Exists(var, inner) => (var, inner),
+                        _ => unreachable!(),
+                    };
+                    let instance = inner.substitute(var.clone(), eigen.clone());
+                    let result = body.type_of(&with(name, instance))?;
+                    match result.get_free_vars().contains(eigen) {
+                        true => Err(fail),
+                        false => Ok(result),
+                    }
+                }
+                _ => Err(fail),
+            },
+            Refl(term) => Ok(Equal(term.clone(), term.clone())),
+            Rewrite(eq, term, fml) => {
+                let eq = eq.type_of(context)?;
+                if !matches!(eq, Equal(_, _)) {
+                    return Err(fail);
+                }
+                let (before, after) = (unfolded(term, context)?, Unfold.fold_formula(fml));
+                let sequent = Sequent {
+                    antecedent: vec![eq, before.clone()],
+                    succedent: vec![after.clone()],
+                };
+                if _infer("EqualityRight", vec![_axiom(&before)], sequent.clone())
+                    || _infer("EqualityLeft", vec![_axiom(&after)], sequent)
+                {
+                    Ok(fml.clone())
+                } else {
+                    Err(fail)
+                }
+            }
+        }
+    }
+
+    /// Checks that the term proves `sequent` with `h0`, `h1`, ... proving
+    /// the formulas of the antecedent, the term then being of the formula
+    /// of the succedent, or of `⊥` if it is empty.
+    pub fn check(&self, sequent: &Sequent) -> Result<(), &'static str> {
+        let context = (sequent.antecedent.iter().enumerate())
+            .map(|(i, fml)| (format!("h{}", i), fml.clone()))
+            .collect::<Vec<_>>();
+        let expected = match &sequent.succedent[..] {
+            [] => Formula::Bottom,
+            [fml] => fml.clone(),
+            _ => return Err("The sequent has more than one formula in the succedent."),
+        };
+        match _same(&self.type_of(&context)?, &expected) {
+            true => Ok(()),
+            false => Err("The term does not prove the sequent."),
+        }
+    }
+}
+
+impl fmt::Display for ProofTerm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ProofTerm::*;
+        match self {
+            Var(name) => write!(f, "{}", name),
+            Unit => write!(f, "()"),
+            Lambda(name, fml, body) => write!(f, "(λ{}: {}. {})", name, fml, body),
+            App(lhs, rhs) => write!(f, "({} {})", lhs, rhs),
+            Pair(lhs, rhs) => write!(f, "⟨{}, {}⟩", lhs, rhs),
+            Fst(term) => write!(f, "(fst {})", term),
+            Snd(term) => write!(f, "(snd {})", term),
+            Inl(term, _) => write!(f, "(inl {})", term),
+            Inr(term, _) => write!(f, "(inr {})", term),
+            Case(term, lhs, lhs_body, rhs, rhs_body) => write!(
+                f,
+                "(case {} of inl {} ⇒ {} | inr {} ⇒ {})",
+                term, lhs, lhs_body, rhs, rhs_body
+            ),
+            Abort(term, _) => write!(f, "(abort {})", term),
+            Generalize(var, term, _) => write!(f, "(Λ{}. {})", var, term),
+            Instantiate(term, instance) => write!(f, "({} {})", term, instance),
+            Pack(instance, term, _) => write!(f, "⟨{}, {}⟩", instance, term),
+            Unpack(term, var, name, body) => {
+                write!(f, "(let ⟨{}, {}⟩ = {} in {})", var, name, term, body)
+            }
+            Refl(term) => write!(f, "(refl {})", term),
+            Rewrite(eq, term, _) => write!(f, "(rewrite {} in {})", eq, term),
+        }
+    }
+}
+
+// Assigns terms to LJ proofs, given the terms of the antecedent of the end
+// sequent.
+struct Extractor {
+    next: usize,
+}
+
+impl Extractor {
+    fn _fresh(&mut self) -> String {
+        self.next += 1;
+        format!("h{}", self.next - 1)
+    }
+
+    fn _extract(&mut self, proof: &LK, env: Vec<ProofTerm>) -> Result<ProofTerm, &'static str> {
+        use ProofTerm::*;
+        let s = proof.last();
+        let premises = proof.premises();
+        let rest = || env[1..].to_vec();
+        let with = |term: ProofTerm, rest: &[ProofTerm]| [&[term], rest].concat();
+        // The instance term or eigenvariable of a quantifier inference, a
+        // fresh variable if the quantifier is vacuous.
+        let instance = || {
+            let rule = proof.rule_name();
+            instantiation(rule, s, premises[0].last()).unwrap_or_else(|| {
+                let mut symbols = SymbolGenerator::new();
+                s.antecedent
+                    .iter()
+                    .chain(&s.succedent)
+                    .for_each(|f| symbols.avoid(f));
+                symbols.fresh_var("y")
+            })
+        };
+        let succedent = || s.succedent.last().cloned().unwrap_or(Formula::Bottom);
+        let term = match proof {
+            LK::Axiom(_) => match (&s.antecedent[..], &s.succedent[..]) {
+                ([_], _) => env[0].clone(),
+                ([], [Formula::Top]) => Unit,
+                ([], [Formula::Equal(t, _)]) => Refl(t.clone()),
+                _ => unreachable!(),
+            },
+            LK::WeakeningLeft(p, _) => self._extract(p, rest())?,
+            LK::WeakeningRight(p, _) => {
+                Abort(Box::new(self._extract(p, env)?), s.suc_last().clone())
+            }
+            LK::ContractionLeft(p, _) => self._extract(p, with(env[0].clone(), &env))?,
+            LK::ExchangeLeft(p, _) => {
+                let i = (s.antecedent.iter().zip(&p.last().antecedent))
+                    .position(|(f, g)| f != g)
+                    .unwrap_or(0);
+                let mut env = env;
+                env.swap(i, i + 1);
+                self._extract(p, env)?
+            }
+            LK::AndLeft1(p, _) => self._extract(p, with(Fst(Box::new(env[0].clone())), &rest()))?,
+            LK::AndLeft2(p, _) => self._extract(p, with(Snd(Box::new(env[0].clone())), &rest()))?,
+            LK::AndRight(ps, _) => Pair(
+                Box::new(self._extract(&ps[0], env.clone())?),
+                Box::new(self._extract(&ps[1], env)?),
+            ),
+            LK::OrLeft(ps, _) => {
+                let (lhs, rhs) = (self._fresh(), self._fresh());
+                let lhs_body = self._extract(&ps[0], with(Var(lhs.clone()), &rest()))?;
+                let rhs_body = self._extract(&ps[1], with(Var(rhs.clone()), &rest()))?;
+                Case(
+                    Box::new(env[0].clone()),
+                    lhs,
+                    Box::new(lhs_body),
+                    rhs,
+                    Box::new(rhs_body),
+                )
+            }
+            LK::OrRight1(p, _) => Inl(Box::new(self._extract(p, env)?), succedent()),
+            LK::OrRight2(p, _) => Inr(Box::new(self._extract(p, env)?), succedent()),
+            LK::ImpliesLeft(ps, _) => {
+                let g = ps[0].last().antecedent.len();
+                let arg = self._extract(&ps[0], env[1..1 + g].to_vec())?;
+                let app = App(Box::new(env[0].clone()), Box::new(arg));
+                self._extract(&ps[1], with(app, &env[1 + g..]))?
+            }
+            LK::ImpliesRight(p, _) | LK::NotRight(p, _) => {
+                let name = self._fresh();
+                let body = self._extract(p, with(Var(name.clone()), &env))?;
+                Lambda(name, p.last().ant_first().clone(), Box::new(body))
+            }
+            LK::IffRight(ps, _) => {
+                let mut lambda = |p: &LK| -> Result<ProofTerm, &'static str> {
+                    let name = self._fresh();
+                    let body = self._extract(p, with(Var(name.clone()), &env))?;
+                    Ok(Lambda(name, p.last().ant_first().clone(), Box::new(body)))
+                };
+                Pair(Box::new(lambda(&ps[0])?), Box::new(lambda(&ps[1])?))
+            }
+            LK::NotLeft(p, _) => {
+                let arg = self._extract(p, rest())?;
+                App(Box::new(env[0].clone()), Box::new(arg))
+            }
+            LK::ForallLeft(p, _) => {
+                let term = Instantiate(Box::new(env[0].clone()), instance());
+                self._extract(p, with(term, &rest()))?
+            }
+            LK::ForallRight(p, _) => {
+                Generalize(instance(), Box::new(self._extract(p, env)?), succedent())
+            }
+            LK::ExistsLeft(p, _) => {
+                let name = self._fresh();
+                let body = self._extract(p, with(Var(name.clone()), &rest()))?;
+                Unpack(Box::new(env[0].clone()), instance(), name, Box::new(body))
+            }
+            LK::ExistsRight(p, _) => {
+                Pack(instance(), Box::new(self._extract(p, env)?), succedent())
+            }
+            LK::EqualityLeft(p, _) => {
+                let fml = p.last().ant_first().clone();
+                let term = Rewrite(Box::new(env[0].clone()), Box::new(env[1].clone()), fml);
+                self._extract(p, with(term, &env[2..]))?
+            }
+            LK::EqualityRight(p, _) => {
+                let term = self._extract(p, rest())?;
+                Rewrite(Box::new(env[0].clone()), Box::new(term), succedent())
+            }
+            LK::Cut(ps, _) => {
+                let g = ps[0].last().antecedent.len();
+                let cut = self._extract(&ps[0], env[..g].to_vec())?;
+                self._extract(&ps[1], with(cut, &env[g..]))?
+            }
+            LK::TheoryRule(_, _) | LK::TheoryAxiom(_) => {
+                return Err("Theory rules are not supported.")
+            }
+            LK::ContractionRight(_, _) | LK::ExchangeRight(_, _) | LK::IffLeft(_, _) => {
+                return Err("The proof is not intuitionistic.")
+            }
+        };
+        Ok(term)
+    }
+}
+
+impl LK {
+    /// Whether the proof is one of LJ: no sequent of it has more than one
+    /// formula in its succedent.
+    pub fn is_intuitionistic(&self) -> bool {
+        let mut stack = vec![self];
+        while let Some(proof) = stack.pop() {
+            if proof.last().succedent.len() > 1 {
+                return false;
+            }
+            stack.extend(proof.premises());
+        }
+        true
+    }
+
+    /// The proof term of an LJ proof, with free proof variables `h0`, `h1`,
+    /// ... for the formulas of the antecedent of the end sequent, see
+    /// `ProofTerm::check`. Cuts become substitutions of the term of the cut
+    /// formula. Theory rules are not supported.
+    pub fn proof_term(&self) -> Result<ProofTerm, &'static str> {
+        if !self.is_intuitionistic() {
+            return Err("The proof is not intuitionistic.");
+        }
+        let n = self.last().antecedent.len();
+        let env = (0..n).map(|i| ProofTerm::Var(format!("h{}", i))).collect();
+        Extractor { next: n }._extract(self, env)
+    }
+}
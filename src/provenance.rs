@@ -0,0 +1,213 @@
+use crate::cnf::*;
+use crate::fresh::*;
+use crate::language::*;
+use std::collections::HashMap;
+
+/// Where the subformulas of the output of a transformation come from in its
+/// input, as paths in the sense of `rewrite::Position`. An atom is mapped to
+/// the atom it was obtained from, a compound subformula to the smallest
+/// subformula of the input containing the origins of all its atoms.
+/// Subformulas without atoms, such as a folded `⊤`, have no origin.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Provenance {
+    pub origins: HashMap<Vec<usize>, Vec<usize>>,
+}
+
+impl Provenance {
+    /// The input position of the output subformula at `path`.
+    pub fn origin(&self, path: &[usize]) -> Option<&[usize]> {
+        self.origins.get(path).map(|origin| &origin[..])
+    }
+}
+
+/// The literals of a clause with the input positions of their atoms.
+/// Literals of predicates introduced by the transformation have none.
+pub type ClauseProvenance = HashMap<Literal, Vec<usize>>;
+
+// The transformations are run on a copy of the input in which every atom is
+// replaced by a fresh predicate, so its occurrences in the output can be
+// traced back however they were moved, duplicated or negated.
+struct Tags {
+    atoms: HashMap<String, (Formula, Vec<usize>)>,
+}
+
+impl Tags {
+    fn tag(fml: &Formula) -> (Formula, Tags) {
+        let mut tags = Tags {
+            atoms: HashMap::new(),
+        };
+        let mut symbols = SymbolGenerator::new();
+        symbols.avoid(fml);
+        let tagged = tags._tag(fml, &mut vec![], &mut symbols);
+        (tagged, tags)
+    }
+
+    fn _tag(
+        &mut self,
+        fml: &Formula,
+        path: &mut Vec<usize>,
+        symbols: &mut SymbolGenerator,
+    ) -> Formula {
+        let mut child = |i: usize, fml: &Formula, tags: &mut Tags| {
+            path.push(i);
+            let fml = tags._tag(fml, path, symbols);
+            path.pop();
+            Box::new(fml)
+        };
+        match fml {
+            Formula::Pred(_, terms) => {
+                let pred = symbols.fresh_pred("at", terms.len() as u32);
+                self.atoms
+                    .insert(pred.name.clone(), (fml.clone(), path.clone()));
+                Formula::Pred(pred.name, terms.clone())
+            }
+            Formula::Equal(lhs, rhs) => {
+                let pred = symbols.fresh_pred("at", 2);
+                self.atoms
+                    .insert(pred.name.clone(), (fml.clone(), path.clone()));
+                Formula::Pred(pred.name, vec![lhs.clone(), rhs.clone()])
+            }
+            Formula::Not(sub) => Formula::Not(child(0, sub, self)),
+            Formula::Forall(var, sub) => Formula::Forall(var.clone(), child(0, sub, self)),
+            Formula::Exists(var, sub) => Formula::Exists(var.clone(), child(0, sub, self)),
+            Formula::And(lhs, rhs) => Formula::And(child(0, lhs, self), child(1, rhs, self)),
+            Formula::Or(lhs, rhs) => Formula::Or(child(0, lhs, self), child(1, rhs, self)),
+            Formula::Implies(lhs, rhs) => {
+                Formula::Implies(child(0, lhs, self), child(1, rhs, self))
+            }
+            Formula::Iff(lhs, rhs) => Formula::Iff(child(0, lhs, self), child(1, rhs, self)),
+            Formula::Top | Formula::Bottom => fml.clone(),
+        }
+    }
+
+    // The original atom with the arguments of the tagged one.
+    fn untag_atom(&self, atom: &Formula) -> Option<(Formula, &Vec<usize>)> {
+        let (name, terms) = match atom {
+            Formula::Pred(name, terms) => (name, terms),
+            _ => return None,
+        };
+        let (original, origin) = self.atoms.get(name)?;
+        let untagged = match original {
+            Formula::Pred(name, _) => Formula::Pred(name.clone(), terms.clone()),
+            _ => Formula::Equal(terms[0].clone(), terms[1].clone()),
+        };
+        Some((untagged, origin))
+    }
+
+    fn untag(&self, fml: &Formula) -> (Formula, Provenance) {
+        let mut provenance = Provenance::default();
+        let untagged = self._untag(fml, &mut vec![], &mut provenance);
+        (untagged, provenance)
+    }
+
+    fn _untag(&self, fml: &Formula, path: &mut Vec<usize>, provenance: &mut Provenance) -> Formula {
+        if let Some((atom, origin)) = self.untag_atom(fml) {
+            provenance.origins.insert(path.clone(), origin.clone());
+            return atom;
+        }
+        let mut child = |i: usize, fml: &Formula, provenance: &mut Provenance| {
+            path.push(i);
+            let fml = self._untag(fml, path, provenance);
+            path.pop();
+            Box::new(fml)
+        };
+        let untagged = match fml {
+            Formula::Not(sub) => Formula::Not(child(0, sub, provenance)),
+            Formula::Forall(var, sub) => Formula::Forall(var.clone(), child(0, sub, provenance)),
+            Formula::Exists(var, sub) => Formula::Exists(var.clone(), child(0, sub, provenance)),
+            Formula::And(lhs, rhs) => {
+                Formula::And(child(0, lhs, provenance), child(1, rhs, provenance))
+            }
+            Formula::Or(lhs, rhs) => {
+                Formula::Or(child(0, lhs, provenance), child(1, rhs, provenance))
+            }
+            Formula::Implies(lhs, rhs) => {
+                Formula::Implies(child(0, lhs, provenance), child(1, rhs, provenance))
+            }
+            Formula::Iff(lhs, rhs) => {
+                Formula::Iff(child(0, lhs, provenance), child(1, rhs, provenance))
+            }
+            _ => fml.clone(),
+        };
+        let children = (0..2)
+            .filter_map(|i| {
+                let mut child = path.clone();
+                child.push(i);
+                provenance.origins.get(&child).cloned()
+            })
+            .collect::<Vec<_>>();
+        if let Some((first, rest)) = children.split_first() {
+            let mut origin = first.clone();
+            for other in rest {
+                let common = origin.iter().zip(other).take_while(|(a, b)| a == b).count();
+                origin.truncate(common);
+            }
+            provenance.origins.insert(path.clone(), origin);
+        }
+        untagged
+    }
+
+    fn untag_clauses(&self, clauses: Vec<Clause>) -> (Vec<Clause>, Vec<ClauseProvenance>) {
+        let mut untagged: Vec<Clause> = vec![];
+        let mut provenances = vec![];
+        for tagged in clauses {
+            let mut clause = Clause::new();
+            let mut provenance = ClauseProvenance::new();
+            for literal in tagged {
+                let literal = match self.untag_atom(&literal.atom) {
+                    Some((atom, origin)) => {
+                        let literal = Literal {
+                            atom,
+                            positive: literal.positive,
+                        };
+                        let entry = provenance
+                            .entry(literal.clone())
+                            .or_insert_with(|| origin.clone());
+                        if origin < entry {
+                            *entry = origin.clone();
+                        }
+                        literal
+                    }
+                    None => literal,
+                };
+                clause.insert(literal);
+            }
+            if clause.iter().any(|l| clause.contains(&l.negate())) || untagged.contains(&clause) {
+                continue;
+            }
+            untagged.push(clause);
+            provenances.push(provenance);
+        }
+        (untagged, provenances)
+    }
+}
+
+impl Formula {
+    /// `to_nnf` with the provenance of the result.
+    pub fn to_nnf_with_provenance(&self) -> (Formula, Provenance) {
+        let (tagged, tags) = Tags::tag(self);
+        tags.untag(&tagged.to_nnf())
+    }
+
+    /// `skolemize` with the provenance of the result.
+    pub fn skolemize_with_provenance(
+        &self,
+        symbols: &mut SymbolGenerator,
+    ) -> (Formula, Provenance) {
+        symbols.avoid(self);
+        let (tagged, tags) = Tags::tag(self);
+        tags.untag(&tagged.skolemize(symbols))
+    }
+
+    /// `to_cnf_with` with the input position of the atom of each literal, so
+    /// that a clause can be reported in terms of the original formula.
+    pub fn to_cnf_with_provenance(
+        &self,
+        conversion: CnfConversion,
+        symbols: &mut SymbolGenerator,
+    ) -> (Vec<Clause>, Vec<ClauseProvenance>) {
+        symbols.avoid(self);
+        let (tagged, tags) = Tags::tag(self);
+        tags.untag_clauses(tagged.to_cnf_with(conversion, symbols))
+    }
+}
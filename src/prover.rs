@@ -0,0 +1,606 @@
+use crate::adapt::*;
+use crate::certificate::*;
+use crate::fresh::*;
+use crate::g3::*;
+use crate::language::*;
+use crate::proof::*;
+use crate::solver::*;
+use crate::substitution::*;
+use crate::theory::*;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Truth values of atoms, see `prove_propositional`.
+pub type Valuation = HashMap<Formula, bool>;
+
+fn _is_atom(fml: &Formula) -> bool {
+    !matches!(
+        fml,
+        Formula::Not(_)
+            | Formula::And(_, _)
+            | Formula::Or(_, _)
+            | Formula::Implies(_, _)
+            | Formula::Iff(_, _)
+    )
+}
+
+fn _is_axiom(sequent: &Sequent) -> bool {
+    sequent
+        .antecedent
+        .iter()
+        .any(|f| *f == Formula::Bottom || sequent.succedent.contains(f))
+        || sequent.succedent.iter().any(|f| match f {
+            Formula::Top => true,
+            Formula::Equal(lhs, rhs) => lhs == rhs,
+            _ => false,
+        })
+}
+
+// A backward step of G3c: the rule and its premises.
+pub(crate) enum Step {
+    Unary(fn(Box<G3>, Sequent) -> G3, Sequent),
+    Binary(fn(Box<[G3; 2]>, Sequent) -> G3, Sequent, Sequent),
+}
+
+// The step decomposing the `i`-th formula of a side of `sequent`, which is
+// compound.
+pub(crate) fn decompose(sequent: &Sequent, left: bool, i: usize) -> Step {
+    use Formula::*;
+    use Step::*;
+    let (mut gamma, mut delta) = (sequent.antecedent.clone(), sequent.succedent.clone());
+    let fml = if left {
+        gamma.remove(i)
+    } else {
+        delta.remove(i)
+    };
+    // `fmls, Γ ⇒ Δ, back` for the context `Γ ⇒ Δ` of `fml`.
+    let seq = |fmls: &[&Formula], back: &[&Formula]| {
+        let cloned = |fmls: &[&Formula]| fmls.iter().map(|f| (*f).clone()).collect();
+        Sequent {
+            antecedent: [cloned(fmls), gamma.clone()].concat(),
+            succedent: [delta.clone(), cloned(back)].concat(),
+        }
+    };
+    match (left, &fml) {
+        (true, And(a, b)) => Unary(G3::AndLeft, seq(&[a, b], &[])),
+        (true, Or(a, b)) => Binary(G3::OrLeft, seq(&[a], &[]), seq(&[b], &[])),
+        (true, Implies(a, b)) => Binary(G3::ImpliesLeft, seq(&[], &[a]), seq(&[b], &[])),
+        (true, Iff(a, b)) => Binary(G3::IffLeft, seq(&[a, b], &[]), seq(&[], &[a, b])),
+        (true, Not(a)) => Unary(G3::NotLeft, seq(&[], &[a])),
+        (false, And(a, b)) => Binary(G3::AndRight, seq(&[], &[a]), seq(&[], &[b])),
+        (false, Or(a, b)) => Unary(G3::OrRight, seq(&[], &[a, b])),
+        (false, Implies(a, b)) => Unary(G3::ImpliesRight, seq(&[a], &[b])),
+        (false, Iff(a, b)) => Binary(G3::IffRight, seq(&[a], &[b]), seq(&[b], &[a])),
+        (false, Not(a)) => Unary(G3::NotRight, seq(&[a], &[])),
+        _ => unreachable!(),
+    }
+}
+
+// The first compound formula of the antecedent, or else of the succedent.
+fn _compound(sequent: &Sequent) -> Option<(bool, usize)> {
+    let compound = |fmls: &[Formula]| fmls.iter().position(|f| !_is_atom(f));
+    match compound(&sequent.antecedent) {
+        Some(i) => Some((true, i)),
+        None => compound(&sequent.succedent).map(|i| (false, i)),
+    }
+}
+
+// Searches backwards with the invertible rules of G3c.
+fn _search(sequent: Sequent) -> Result<G3, Valuation> {
+    if _is_axiom(&sequent) {
+        return Ok(G3::Axiom(sequent));
+    }
+    let (left, i) = match _compound(&sequent) {
+        Some(position) => position,
+        None => return Err(_countermodel(sequent)),
+    };
+    match decompose(&sequent, left, i) {
+        Step::Unary(rule, premise) => Ok(rule(Box::new(_search(premise)?), sequent)),
+        Step::Binary(rule, lhs, rhs) => Ok(rule(Box::new([_search(lhs)?, _search(rhs)?]), sequent)),
+    }
+}
+
+// The valuation falsifying `sequent`, which has only atoms and is not an
+// axiom.
+fn _countermodel(sequent: Sequent) -> Valuation {
+    use Formula::*;
+    let mut valuation = Valuation::new();
+    for fml in sequent.succedent {
+        valuation.insert(fml, false);
+    }
+    for fml in sequent.antecedent {
+        valuation.insert(fml, true);
+    }
+    valuation.retain(|fml, _| !matches!(fml, Top | Bottom));
+    valuation
+}
+
+/// Decides `sequent` propositionally, returning an LK proof of it or a
+/// valuation of its atoms making the antecedent true and the succedent
+/// false. Atoms are predicates, equalities other than `t = t`, which is
+/// provable, and quantified formulas, which are not looked into.
+pub fn prove_propositional(sequent: &Sequent) -> Result<LK, Valuation> {
+    let proof = _search(sequent.clone())?;
+    Ok(proof
+        .to_lk()
+        .expect("the search only builds valid G3 proofs"))
+}
+
+/// Why `FirstOrderProver::search` gave up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchFailure {
+    /// No proof with at most `max_instantiations` quantifier instances on a
+    /// branch. The sequent may still be provable with more.
+    Exhausted,
+    OutOfSteps,
+    Timeout,
+    /// The search was cancelled, see `crate::background::prove_async`.
+    Cancelled,
+    /// Every inference was made without finding a proof.
+    Saturated,
+}
+
+impl fmt::Display for SearchFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SearchFailure::Exhausted => write!(f, "no proof within the instantiation depth"),
+            SearchFailure::OutOfSteps => write!(f, "step budget exhausted"),
+            SearchFailure::Timeout => write!(f, "timed out"),
+            SearchFailure::Cancelled => write!(f, "cancelled"),
+            SearchFailure::Saturated => write!(f, "saturated without a proof"),
+        }
+    }
+}
+
+/// Proof search for first-order sequents in G3c, with iterative deepening
+/// on the number of instances of `∀` on the left and `∃` on the right taken
+/// on a branch. Instances are guided by matching atoms of the quantified
+/// formula against the atoms of the sequent; vacuous quantifiers are
+/// instantiated with any term.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FirstOrderProver {
+    pub max_instantiations: u32,
+    /// Most sequents expanded over all rounds, `None` for no bound.
+    pub max_steps: Option<usize>,
+    pub timeout: Option<Duration>,
+}
+
+impl Default for FirstOrderProver {
+    fn default() -> FirstOrderProver {
+        FirstOrderProver {
+            max_instantiations: 8,
+            max_steps: Some(10_000),
+            timeout: None,
+        }
+    }
+}
+
+impl Prover for FirstOrderProver {
+    fn prove_sequent(&self, goal: &Sequent) -> Option<LK> {
+        self.search(goal).ok()
+    }
+
+    fn prove_in(&self, theory: &Theory, goal: &Formula) -> Option<LK> {
+        self.search_in(theory, &theory.sequent(goal.clone())).ok()
+    }
+}
+
+// The variable and scope of a quantified formula.
+fn _quantified(fml: &Formula) -> Option<(&Term, &Formula)> {
+    match fml {
+        Formula::Forall(var, body) | Formula::Exists(var, body) => Some((var, body)),
+        _ => None,
+    }
+}
+
+// The first `∃` of the antecedent, or else `∀` of the succedent, with its
+// side, index and the step instantiating it with a fresh variable.
+pub(crate) fn eigen_step(
+    sequent: &Sequent,
+    symbols: &mut SymbolGenerator,
+) -> Option<(bool, usize, Step)> {
+    _eigen_step(sequent, |_, var| symbols.fresh_var(&var.to_string()))
+}
+
+// Same as `eigen_step`, with the variable given by `eigenvariable` from the
+// principal formula and its bound variable.
+fn _eigen_step(
+    sequent: &Sequent,
+    eigenvariable: impl FnOnce(&Formula, &Term) -> Term,
+) -> Option<(bool, usize, Step)> {
+    use Formula::*;
+    let delta = |left: bool, fml: &Formula| {
+        _quantified(fml).is_some()
+            && matches!((left, fml), (true, Exists(_, _)) | (false, Forall(_, _)))
+    };
+    let (left, i) = match sequent.antecedent.iter().position(|f| delta(true, f)) {
+        Some(i) => (true, i),
+        None => (
+            false,
+            sequent.succedent.iter().position(|f| delta(false, f))?,
+        ),
+    };
+    let mut premise = sequent.clone();
+    let fml = if left {
+        premise.antecedent.remove(i)
+    } else {
+        premise.succedent.remove(i)
+    };
+    let (var, body) = _quantified(&fml).unwrap();
+    let fresh = eigenvariable(&fml, var);
+    let instance = body.substitute(var.clone(), fresh);
+    let step = if left {
+        premise.antecedent.insert(0, instance);
+        Step::Unary(G3::ExistsLeft, premise)
+    } else {
+        premise.succedent.push(instance);
+        Step::Unary(G3::ForallRight, premise)
+    };
+    Some((left, i, step))
+}
+
+fn _is_literal(fml: &Formula) -> bool {
+    matches!(fml, Formula::Pred(_, _) | Formula::Equal(_, _))
+}
+
+fn _substitute(sequent: &Sequent, sub: &Substitution) -> Sequent {
+    let apply = |fmls: &[Formula]| fmls.iter().map(|f| f.substitute_with(sub)).collect();
+    Sequent {
+        antecedent: apply(&sequent.antecedent),
+        succedent: apply(&sequent.succedent),
+    }
+}
+
+// The free variables of `sequent`, in a fixed order.
+fn _free_vars(sequent: &Sequent) -> Vec<Term> {
+    let mut vars = (sequent.to_formula().get_free_vars().into_iter()).collect::<Vec<_>>();
+    vars.sort_by_key(|v| v.to_string());
+    vars
+}
+
+struct Search<'a> {
+    prover: &'a FirstOrderProver,
+    rules: &'a [SequentRule],
+    // The only terms and eigenvariables to instantiate quantifiers with.
+    certificate: Option<&'a Certificate>,
+    steps: usize,
+    start: Instant,
+    symbols: SymbolGenerator,
+}
+
+impl Step {
+    // Derives `sequent` in LK from proofs of the premises, with the
+    // `principal`-th formula of its side as the principal formula.
+    fn infer(&self, mut premises: Vec<LK>, sequent: Sequent, principal: usize) -> LK {
+        let proof = match self {
+            Step::Unary(rule, _) => G3::infer(*rule, premises.remove(0), sequent, principal),
+            Step::Binary(rule, _, _) => {
+                let rhs = premises.pop().unwrap();
+                let lhs = premises.pop().unwrap();
+                G3::infer_binary(*rule, [lhs, rhs], sequent, principal)
+            }
+        };
+        proof.expect("the search only makes valid G3 inferences")
+    }
+}
+
+impl Search<'_> {
+    // Terms to instantiate `var` in `body` with on `sequent`: those making an
+    // atom of `body` equal to an atom of `sequent` first, then the terms of
+    // the atoms of `sequent`, or a fresh variable if it has none.
+    // With a certificate, only the terms it gives for `fml`, on the left if
+    // `left`.
+    fn _candidates(
+        &mut self,
+        sequent: &Sequent,
+        (left, fml): (bool, &Formula),
+        var: &Term,
+        body: &Formula,
+    ) -> Vec<Term> {
+        // Every instance of a vacuous quantifier is its scope.
+        if !body.get_free_vars().contains(var) {
+            return vec![var.clone()];
+        }
+        if let Some(certificate) = self.certificate {
+            let rule = if left { "ForallLeft" } else { "ExistsRight" };
+            return certificate.terms(rule, fml).into_iter().cloned().collect();
+        }
+        let atoms: Vec<&Formula> = sequent
+            .antecedent
+            .iter()
+            .chain(&sequent.succedent)
+            .filter(|f| _is_literal(f))
+            .collect();
+        let free = body.get_free_vars();
+        let mut terms = vec![];
+        for pattern in body.get_subformulas().iter().filter(|f| _is_literal(f)) {
+            for atom in &atoms {
+                if let Some(sub) = atom.matches(pattern) {
+                    let fixed = free
+                        .iter()
+                        .all(|v| v == var || sub.get(v).is_none_or(|t| t == v));
+                    if let (true, Some(term)) = (fixed, sub.get(var)) {
+                        terms.push(term.clone());
+                    }
+                }
+            }
+        }
+        terms.extend(atoms.iter().flat_map(|f| f.iter_subterms()).cloned());
+        if terms.is_empty() {
+            terms.push(self.symbols.fresh_var("x"));
+        }
+        let mut seen = vec![];
+        terms.retain(|t| {
+            let new = !seen.contains(t);
+            seen.push(t.clone());
+            new
+        });
+        terms
+    }
+
+    // The eigenvariable step, with the first eigenvariable the certificate
+    // gives for the principal formula that is not free in `sequent`, if any.
+    fn _eigen_step(&mut self, sequent: &Sequent) -> Option<(bool, usize, Step)> {
+        let free = sequent.to_formula().get_free_vars();
+        let (certificate, symbols) = (self.certificate, &mut self.symbols);
+        _eigen_step(sequent, |fml, var| {
+            let rule = match fml {
+                Formula::Exists(_, _) => "ExistsLeft",
+                _ => "ForallRight",
+            };
+            let given = certificate.and_then(|c| {
+                (c.terms(rule, fml).into_iter())
+                    .find(|t| matches!(t, Term::Var(_)) && !free.contains(t))
+            });
+            match given {
+                Some(term) => term.clone(),
+                None => symbols.fresh_var(&var.to_string()),
+            }
+        })
+    }
+
+    fn _expand(
+        &mut self,
+        step: Step,
+        sequent: Sequent,
+        principal: usize,
+        budget: u32,
+    ) -> Result<Option<LK>, SearchFailure> {
+        let premises = match &step {
+            Step::Unary(_, premise) => match self._search(premise.clone(), budget)? {
+                Some(p) => vec![p],
+                None => return Ok(None),
+            },
+            Step::Binary(_, lhs, rhs) => match self._search(lhs.clone(), budget)? {
+                Some(p1) => match self._search(rhs.clone(), budget)? {
+                    Some(p2) => vec![p1, p2],
+                    None => return Ok(None),
+                },
+                None => return Ok(None),
+            },
+        };
+        Ok(Some(step.infer(premises, sequent, principal)))
+    }
+
+    // An instance of an initial sequent of the theory whose formulas are
+    // in `sequent`, weakened to it.
+    fn _initial(&self, sequent: &Sequent) -> Option<LK> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.premise.is_none())
+            .find_map(|rule| {
+                let sub = rule.matches_in(sequent).into_iter().next()?;
+                let instance = LK::TheoryRule(vec![], _substitute(&rule.conclusion, &sub));
+                adapt(instance, sequent).ok()
+            })
+    }
+
+    // Tries the instances of the unary rules of the theory whose conclusion
+    // formulas are in `sequent`, adding the formulas of the premise that are
+    // new to it.
+    fn _apply_rules(
+        &mut self,
+        sequent: &Sequent,
+        budget: u32,
+    ) -> Result<Option<LK>, SearchFailure> {
+        for rule in self.rules {
+            let schema = match &rule.premise {
+                Some(schema) => schema,
+                None => continue,
+            };
+            for mut sub in rule.matches_in(sequent) {
+                for var in _free_vars(schema) {
+                    if sub.get(&var).is_none() {
+                        let fresh = self.symbols.fresh_var(&var.to_string());
+                        sub.insert(var, fresh);
+                    }
+                }
+                let premise = _substitute(schema, &sub);
+                let new = |fmls: &[Formula], old: &[Formula]| {
+                    (fmls.iter())
+                        .filter(|f| !old.contains(f))
+                        .cloned()
+                        .collect::<Vec<_>>()
+                };
+                let (ant, suc) = (&sequent.antecedent, &sequent.succedent);
+                let (new_ant, new_suc) =
+                    (new(&premise.antecedent, ant), new(&premise.succedent, suc));
+                if new_ant.is_empty() && new_suc.is_empty() {
+                    continue;
+                }
+                let extended = Sequent {
+                    antecedent: [new_ant, ant.clone()].concat(),
+                    succedent: [suc.clone(), new_suc].concat(),
+                };
+                if let Some(proof) = self._search(extended, budget - 1)? {
+                    let conclusion = _substitute(&rule.conclusion, &sub);
+                    let with_context = |s: Sequent| Sequent {
+                        antecedent: [s.antecedent, ant.clone()].concat(),
+                        succedent: [suc.clone(), s.succedent].concat(),
+                    };
+                    let proof = adapt(proof, &with_context(premise)).unwrap();
+                    let proof = LK::TheoryRule(vec![proof], with_context(conclusion));
+                    return Ok(Some(adapt(proof, sequent).unwrap()));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    // Searches with the invertible rules first, then with at most `budget`
+    // instances of `∀` on the left and `∃` on the right.
+    fn _search(&mut self, sequent: Sequent, budget: u32) -> Result<Option<LK>, SearchFailure> {
+        use Formula::*;
+        self.steps += 1;
+        if self.prover.max_steps.is_some_and(|max| self.steps > max) {
+            return Err(SearchFailure::OutOfSteps);
+        }
+        if self
+            .prover
+            .timeout
+            .is_some_and(|timeout| self.start.elapsed() >= timeout)
+        {
+            return Err(SearchFailure::Timeout);
+        }
+        if is_cancelled() {
+            return Err(SearchFailure::Cancelled);
+        }
+        if _is_axiom(&sequent) {
+            let proof = G3::Axiom(sequent).to_lk();
+            return Ok(Some(
+                proof.expect("the search only makes valid G3 inferences"),
+            ));
+        }
+        if let Some(proof) = self._initial(&sequent) {
+            return Ok(Some(proof));
+        }
+        if let Some((left, i)) = _compound(&sequent) {
+            let step = decompose(&sequent, left, i);
+            return self._expand(step, sequent, i, budget);
+        }
+        if let Some((_, i, step)) = self._eigen_step(&sequent) {
+            return self._expand(step, sequent, i, budget);
+        }
+        if budget == 0 {
+            return Ok(None);
+        }
+        if let Some(proof) = self._apply_rules(&sequent, budget)? {
+            return Ok(Some(proof));
+        }
+        let gamma = (sequent
+            .antecedent
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (true, i, f)))
+        .chain(
+            sequent
+                .succedent
+                .iter()
+                .enumerate()
+                .map(|(i, f)| (false, i, f)),
+        )
+        .filter(|(left, _, f)| matches!((left, f), (true, Forall(_, _)) | (false, Exists(_, _))))
+        .filter_map(|(left, i, f)| Some((left, i, f, _quantified(f)?)))
+        .collect::<Vec<_>>();
+        for (left, i, fml, (var, body)) in gamma {
+            for term in self._candidates(&sequent, (left, fml), var, body) {
+                if !body.is_substitutible(var.clone(), term.clone()) {
+                    continue;
+                }
+                let instance = body.substitute(var.clone(), term);
+                let mut premise = sequent.clone();
+                let (side, rule): (_, fn(Box<G3>, Sequent) -> G3) = if left {
+                    (&mut premise.antecedent, G3::ForallLeft)
+                } else {
+                    (&mut premise.succedent, G3::ExistsRight)
+                };
+                if side.contains(&instance) {
+                    continue;
+                }
+                side.push(instance);
+                if let Some(proof) = self._search(premise, budget - 1)? {
+                    let proof = G3::infer(rule, proof, sequent.clone(), i);
+                    return Ok(Some(
+                        proof.expect("the search only makes valid G3 inferences"),
+                    ));
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl FirstOrderProver {
+    /// Searches for a proof of `goal`, raising the instantiation depth from
+    /// `0` to `max_instantiations` until one is found. The steps and the
+    /// timeout are counted over all rounds.
+    pub fn search(&self, goal: &Sequent) -> Result<LK, SearchFailure> {
+        self.search_in(&Theory::new(), goal)
+    }
+
+    /// Same as `search`, with the rules of `theory` as primitive inferences.
+    /// Initial sequents close a branch as axioms do, and each instance of a
+    /// unary rule counts as a quantifier instance. The instances of schemas
+    /// concluding a formula of the succedent, see `Theory::schema_instances`,
+    /// are added to the antecedent and cut with `LK::TheoryAxiom`. The proof
+    /// has to be checked with the theory, see `CheckOptions::theory`.
+    pub fn search_in(&self, theory: &Theory, goal: &Sequent) -> Result<LK, SearchFailure> {
+        let instances = theory.schema_instances(goal);
+        let goal = Sequent {
+            antecedent: [&instances[..], &goal.antecedent[..]].concat(),
+            succedent: goal.succedent.clone(),
+        };
+        let mut search = Search {
+            prover: self,
+            rules: &theory.rules,
+            certificate: None,
+            steps: 0,
+            start: Instant::now(),
+            symbols: SymbolGenerator::avoiding(goal.antecedent.iter().chain(&goal.succedent)),
+        };
+        for budget in 0..=self.max_instantiations {
+            if let Some(proof) = search._search(goal.clone(), budget)? {
+                return Ok(instances.into_iter().fold(proof, |proof, inst| {
+                    let mut conclusion = proof.last().clone();
+                    conclusion.antecedent.remove(0);
+                    let axiom = LK::TheoryAxiom(Sequent {
+                        antecedent: vec![],
+                        succedent: vec![inst],
+                    });
+                    LK::Cut(Box::new([axiom, proof]), conclusion)
+                }));
+            }
+        }
+        Err(SearchFailure::Exhausted)
+    }
+
+    /// Same as `search`, instantiating quantifiers with the terms and
+    /// eigenvariables `certificate` gives for them only, see
+    /// `LK::certificate`.
+    pub fn search_with_certificate(
+        &self,
+        goal: &Sequent,
+        certificate: &Certificate,
+    ) -> Result<LK, SearchFailure> {
+        let mut symbols = SymbolGenerator::avoiding(goal.antecedent.iter().chain(&goal.succedent));
+        for instance in &certificate.instances {
+            symbols.avoid(&instance.formula);
+            symbols.avoid_term(&instance.term);
+        }
+        let mut search = Search {
+            prover: self,
+            rules: &[],
+            certificate: Some(certificate),
+            steps: 0,
+            start: Instant::now(),
+            symbols,
+        };
+        for budget in 0..=self.max_instantiations {
+            if let Some(proof) = search._search(goal.clone(), budget)? {
+                return Ok(proof);
+            }
+        }
+        Err(SearchFailure::Exhausted)
+    }
+}
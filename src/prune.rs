@@ -0,0 +1,225 @@
+use crate::dependency::*;
+use crate::language::*;
+use crate::theory::*;
+use crate::visit::*;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// What `Theory::prune` removed. Axioms are given by their index in the
+/// theory pruned.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PruneReport {
+    /// Predicates occurring with one polarity only, with `true` for those
+    /// occurring positively, in the order they were replaced by `⊤` or `⊥`.
+    pub pure: Vec<(NonLogicalSymbol, bool)>,
+    /// Axioms which became `⊤` once pure predicates were replaced.
+    pub trivial: Vec<usize>,
+    /// Axioms sharing no symbol with the goal, directly or through other
+    /// axioms.
+    pub unreachable: Vec<usize>,
+}
+
+// Records the polarities `fml` has its predicates with, `true` for
+// positive ones.
+fn _polarities(fml: &Formula, positive: bool, acc: &mut HashMap<NonLogicalSymbol, [bool; 2]>) {
+    match fml {
+        Formula::Pred(name, terms) => {
+            let sym = NonLogicalSymbol {
+                name: name.clone(),
+                arity: terms.len() as u32,
+            };
+            acc.entry(sym).or_default()[positive as usize] = true;
+        }
+        Formula::Not(fml) => _polarities(fml, !positive, acc),
+        Formula::And(lhs, rhs) | Formula::Or(lhs, rhs) => {
+            _polarities(lhs, positive, acc);
+            _polarities(rhs, positive, acc);
+        }
+        Formula::Implies(lhs, rhs) => {
+            _polarities(lhs, !positive, acc);
+            _polarities(rhs, positive, acc);
+        }
+        Formula::Iff(lhs, rhs) => {
+            for fml in [lhs, rhs] {
+                _polarities(fml, positive, acc);
+                _polarities(fml, !positive, acc);
+            }
+        }
+        Formula::Forall(_, body) | Formula::Exists(_, body) => _polarities(body, positive, acc),
+        Formula::Equal(_, _) | Formula::Top | Formula::Bottom => {}
+    }
+}
+
+// Replaces the atoms of a predicate by a truth value.
+struct Assign<'a> {
+    pred: &'a NonLogicalSymbol,
+    value: bool,
+}
+
+impl TermFolder for Assign<'_> {}
+
+impl FormulaFolder for Assign<'_> {
+    fn fold_formula(&mut self, fml: &Formula) -> Formula {
+        match fml {
+            Formula::Pred(name, terms)
+                if name == &self.pred.name && terms.len() as u32 == self.pred.arity =>
+            {
+                if self.value {
+                    Formula::Top
+                } else {
+                    Formula::Bottom
+                }
+            }
+            _ => fold_subformulas(self, fml),
+        }
+    }
+}
+
+// Removes `⊤` and `⊥` from `fml` unless it is one of them.
+fn _simplify(fml: &Formula) -> Formula {
+    use Formula::*;
+    let not = |fml: Formula| match fml {
+        Top => Bottom,
+        Bottom => Top,
+        fml => not!(fml),
+    };
+    match fml {
+        Not(fml) => not(_simplify(fml)),
+        And(lhs, rhs) => match (_simplify(lhs), _simplify(rhs)) {
+            (Bottom, _) | (_, Bottom) => Bottom,
+            (Top, fml) | (fml, Top) => fml,
+            (lhs, rhs) => and!(lhs, rhs),
+        },
+        Or(lhs, rhs) => match (_simplify(lhs), _simplify(rhs)) {
+            (Top, _) | (_, Top) => Top,
+            (Bottom, fml) | (fml, Bottom) => fml,
+            (lhs, rhs) => or!(lhs, rhs),
+        },
+        Implies(lhs, rhs) => match (_simplify(lhs), _simplify(rhs)) {
+            (Bottom, _) | (_, Top) => Top,
+            (Top, fml) => fml,
+            (fml, Bottom) => not(fml),
+            (lhs, rhs) => implies!(lhs, rhs),
+        },
+        Iff(lhs, rhs) => match (_simplify(lhs), _simplify(rhs)) {
+            (Top, fml) | (fml, Top) => fml,
+            (Bottom, fml) | (fml, Bottom) => not(fml),
+            (lhs, rhs) => iff!(lhs, rhs),
+        },
+        Forall(var, body) => match _simplify(body) {
+            fml @ (Top | Bottom) => fml,
+            body => forall!(var.clone(), body),
+        },
+        Exists(var, body) => match _simplify(body) {
+            fml @ (Top | Bottom) => fml,
+            body => exists!(var.clone(), body),
+        },
+        Pred(_, _) | Equal(_, _) | Top | Bottom => fml.clone(),
+    }
+}
+
+fn _symbols(fml: &Formula) -> BTreeSet<String> {
+    fml.get_preds()
+        .into_iter()
+        .chain(fml.get_funcs())
+        .map(|sym| sym.name)
+        .collect()
+}
+
+impl Theory {
+    // The formulas of the rules and schemas, which are kept as they are.
+    fn _fixed(&self) -> Vec<&Formula> {
+        let sequents = self
+            .rules
+            .iter()
+            .flat_map(|rule| rule.premise.iter().chain([&rule.conclusion]));
+        sequents
+            .flat_map(|s| s.antecedent.iter().chain(&s.succedent))
+            .chain(self.schemas.iter().map(|schema| &schema.formula))
+            .collect()
+    }
+
+    /// Simplifies the problem of proving `goal` from the theory before
+    /// searching for a proof. Predicates occurring only positively in the
+    /// axioms and only negatively in the goal are replaced by `⊤`, those
+    /// occurring the other way round by `⊥`, and axioms becoming `⊤` are
+    /// removed. Axioms sharing no symbol with the goal, directly or through
+    /// other axioms, are removed too. Predicates of the rules and schemas
+    /// are left alone, and so are axioms without any symbols.
+    ///
+    /// The goal follows from the pruned theory only if it follows from the
+    /// original one. The converse holds for the replaced predicates, while
+    /// unreachable axioms can only have mattered by being inconsistent or
+    /// by bounding the size of the domain.
+    pub fn prune(&self, goal: &Formula) -> (Theory, Formula, PruneReport) {
+        let mut axioms = self.axioms.iter().cloned().enumerate().collect::<Vec<_>>();
+        let mut goal = goal.clone();
+        let mut report = PruneReport::default();
+        let fixed = self._fixed();
+        let mut kept = HashSet::new();
+        for fml in &fixed {
+            kept.extend(fml.get_preds());
+        }
+        loop {
+            let mut polarities = HashMap::new();
+            for (_, axiom) in &axioms {
+                _polarities(axiom, true, &mut polarities);
+            }
+            _polarities(&goal, false, &mut polarities);
+            let mut pure = polarities
+                .into_iter()
+                .filter(|(sym, [negative, positive])| negative != positive && !kept.contains(sym))
+                .map(|(sym, [_, positive])| (sym, positive))
+                .collect::<Vec<_>>();
+            pure.sort_by(|(lhs, _), (rhs, _)| (&lhs.name, lhs.arity).cmp(&(&rhs.name, rhs.arity)));
+            if pure.is_empty() {
+                break;
+            }
+            for (pred, value) in &pure {
+                let mut assign = Assign {
+                    pred,
+                    value: *value,
+                };
+                for (_, axiom) in axioms.iter_mut() {
+                    *axiom = _simplify(&assign.fold_formula(axiom));
+                }
+                goal = _simplify(&assign.fold_formula(&goal));
+            }
+            report.pure.extend(pure);
+            (axioms.iter())
+                .filter(|(_, axiom)| axiom == &Formula::Top)
+                .for_each(|(i, _)| report.trivial.push(*i));
+            axioms.retain(|(_, axiom)| axiom != &Formula::Top);
+        }
+
+        let mut graph = DependencyGraph::new();
+        for (_, axiom) in &axioms {
+            let symbols = _symbols(axiom);
+            for symbol in &symbols {
+                graph.add(symbol, symbols.clone());
+            }
+        }
+        let mut reached = _symbols(&goal);
+        fixed.iter().for_each(|fml| reached.extend(_symbols(fml)));
+        for symbol in reached.clone() {
+            reached.extend(graph.transitive_dependencies(&symbol));
+        }
+        axioms.retain(|(i, axiom)| {
+            let symbols = _symbols(axiom);
+            let keep = symbols.is_empty() || !symbols.is_disjoint(&reached);
+            if !keep {
+                report.unreachable.push(*i);
+            }
+            keep
+        });
+
+        let theory = Theory {
+            names: (axioms.iter().enumerate())
+                .filter_map(|(j, (i, _))| Some((j, self.names.get(i)?.clone())))
+                .collect(),
+            axioms: axioms.into_iter().map(|(_, axiom)| axiom).collect(),
+            rules: self.rules.clone(),
+            schemas: self.schemas.clone(),
+        };
+        (theory, goal, report)
+    }
+}
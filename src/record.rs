@@ -0,0 +1,107 @@
+use crate::proof::*;
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+/// One explored search state of the LK prover.
+///
+/// Serialized by `to_json` as a single JSON object per line (JSONL):
+///
+/// ```text
+/// {"state": "((A ...) (B ...))",   // goal sequent, see Sequent::to_sexpr
+///  "action": "AndRight" | null,    // rule applied at the state, null if none succeeded
+///  "premises": ["((...) (...))"],  // subgoals produced by the action
+///  "outcome": "proved" | "failed",
+///  "in_proof": true | false,       // whether the state is used by the returned proof
+///  "depth": 3}                     // proof height if proved, exhausted depth if failed
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProofStep {
+    pub state: Sequent,
+    pub action: Option<&'static str>,
+    pub premises: Vec<Sequent>,
+    pub proved: bool,
+    pub in_proof: bool,
+    pub depth: u32,
+}
+
+pub(crate) fn json_string(s: &str) -> String {
+    let mut json = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+impl ProofStep {
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"state\": {}, \"action\": {}, \"premises\": [{}], \"outcome\": \"{}\", \"in_proof\": {}, \"depth\": {}}}",
+            json_string(&self.state.to_sexpr()),
+            match self.action {
+                Some(action) => json_string(action),
+                None => "null".to_string(),
+            },
+            self.premises
+                .iter()
+                .map(|p| json_string(&p.to_sexpr()))
+                .collect::<Vec<_>>()
+                .join(", "),
+            if self.proved { "proved" } else { "failed" },
+            self.in_proof,
+            self.depth
+        )
+    }
+}
+
+pub(crate) fn steps_from_search<'a>(
+    searched: impl Iterator<Item = (&'a Sequent, &'a Result<LK, u32>)>,
+    proof: Option<&LK>,
+) -> Vec<ProofStep> {
+    let mut used = HashSet::new();
+    proof.into_iter().for_each(|proof| {
+        proof.replay(
+            &mut |_: &'static str, _: &[&Sequent], conclusion: &Sequent| {
+                used.insert(conclusion.clone());
+            },
+        )
+    });
+    let mut steps = searched
+        .map(|(sequent, result)| match result {
+            Ok(prf) => ProofStep {
+                state: sequent.clone(),
+                action: Some(prf.rule_name()),
+                premises: prf.premises().iter().map(|p| p.last().clone()).collect(),
+                proved: true,
+                in_proof: used.contains(sequent),
+                depth: prf.height(),
+            },
+            Err(depth) => ProofStep {
+                state: sequent.clone(),
+                action: None,
+                premises: vec![],
+                proved: false,
+                in_proof: false,
+                depth: *depth,
+            },
+        })
+        .collect::<Vec<_>>();
+    steps.sort_by_key(|step| (!step.in_proof, !step.proved, step.state.to_sexpr()));
+    steps
+}
+
+/// Writes `steps` as JSONL, one `ProofStep::to_json` object per line.
+pub fn write_jsonl<W: Write>(steps: &[ProofStep], writer: &mut W) -> io::Result<()> {
+    for step in steps {
+        writeln!(writer, "{}", step.to_json())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use crate::language::*;
+use crate::proof::*;
+use crate::visit::*;
+use std::collections::{HashMap, HashSet};
+
+// Names of predicates, functions and variables, bound ones included.
+#[derive(Default)]
+struct Names {
+    preds: HashSet<String>,
+    funcs: HashSet<String>,
+    vars: HashSet<String>,
+}
+
+impl TermVisitor for Names {
+    fn visit_term(&mut self, term: &Term) {
+        match term {
+            Term::Var(name) => self.vars.insert(name.clone()),
+            Term::Func(name, _) => self.funcs.insert(name.clone()),
+        };
+        walk_term(self, term);
+    }
+}
+
+impl FormulaVisitor for Names {
+    fn visit_formula(&mut self, fml: &Formula) {
+        match fml {
+            Formula::Pred(name, _) => {
+                self.preds.insert(name.clone());
+            }
+            Formula::Forall(var, _) | Formula::Exists(var, _) => self.visit_term(var),
+            _ => {}
+        }
+        walk_formula(self, fml);
+    }
+}
+
+impl Names {
+    // Whether `renaming` keeps distinct names of the same kind distinct.
+    fn check(&self, renaming: &HashMap<String, String>) -> Result<(), &'static str> {
+        for names in &[&self.preds, &self.funcs, &self.vars] {
+            let renamed = names
+                .iter()
+                .map(|name| renaming.get(name).unwrap_or(name))
+                .collect::<HashSet<_>>();
+            if renamed.len() < names.len() {
+                return Err("Renaming makes distinct symbols clash.");
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Renamer<'a> {
+    renaming: &'a HashMap<String, String>,
+}
+
+impl<'a> Renamer<'a> {
+    fn rename(&self, name: &str) -> String {
+        self.renaming
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| name.into())
+    }
+}
+
+impl<'a> TermFolder for Renamer<'a> {
+    fn fold_term(&mut self, term: &Term) -> Term {
+        match term {
+            Term::Var(name) => Term::Var(self.rename(name)),
+            Term::Func(name, terms) => Term::Func(
+                self.rename(name),
+                terms.iter().map(|t| self.fold_term(t)).collect(),
+            ),
+        }
+    }
+}
+
+impl<'a> FormulaFolder for Renamer<'a> {
+    fn fold_formula(&mut self, fml: &Formula) -> Formula {
+        match fml {
+            Formula::Pred(name, terms) => Formula::Pred(
+                self.rename(name),
+                terms.iter().map(|t| self.fold_term(t)).collect(),
+            ),
+            Formula::Forall(var, body) => {
+                Formula::Forall(self.fold_term(var), Box::new(self.fold_formula(body)))
+            }
+            Formula::Exists(var, body) => {
+                Formula::Exists(self.fold_term(var), Box::new(self.fold_formula(body)))
+            }
+            _ => fold_subformulas(self, fml),
+        }
+    }
+}
+
+impl Formula {
+    /// Renames predicates, functions and variables, bound ones included, by
+    /// name. Fails if two distinct symbols of the same kind would end up
+    /// with the same name.
+    pub fn rename_symbols(
+        &self,
+        renaming: &HashMap<String, String>,
+    ) -> Result<Formula, &'static str> {
+        let mut names = Names::default();
+        names.visit_formula(self);
+        names.check(renaming)?;
+        Ok(Renamer { renaming }.fold_formula(self))
+    }
+}
+
+impl LK {
+    /// Renames symbols throughout the proof as `Formula::rename_symbols`
+    /// does. Since no two symbols clash the result is again a valid proof.
+    pub fn rename_symbols(&self, renaming: &HashMap<String, String>) -> Result<LK, &'static str> {
+        fn collect(proof: &LK, names: &mut Names) {
+            let sequent = proof.last();
+            for fml in sequent.antecedent.iter().chain(&sequent.succedent) {
+                names.visit_formula(fml);
+            }
+            for premise in proof.premises() {
+                collect(premise, names);
+            }
+        }
+        let mut names = Names::default();
+        collect(self, &mut names);
+        names.check(renaming)?;
+        let mut renamer = Renamer { renaming };
+        Ok(self.map_sequents(&mut |sequent: &Sequent| Sequent {
+            antecedent: sequent
+                .antecedent
+                .iter()
+                .map(|fml| renamer.fold_formula(fml))
+                .collect(),
+            succedent: sequent
+                .succedent
+                .iter()
+                .map(|fml| renamer.fold_formula(fml))
+                .collect(),
+        }))
+    }
+}
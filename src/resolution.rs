@@ -0,0 +1,881 @@
+use crate::adapt::*;
+use crate::certificate::*;
+use crate::cnf::*;
+use crate::fresh::*;
+use crate::g3::*;
+use crate::language::*;
+use crate::proof::*;
+use crate::prover::*;
+use crate::solver::*;
+use crate::unify::*;
+use crate::visit::*;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+const NOT_A_REFUTATION: &str = "Not a refutation of the clauses.";
+
+/// How a clause of a `Refutation` was derived from the clauses before it,
+/// referred to by index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Derivation {
+    /// The `n`-th input clause, with its variables renamed.
+    Input(usize),
+    /// The literals of `left σ` other than `atom` together with those of
+    /// `right τ` other than `¬atom`, for `σ = left_sub` and `τ = right_sub`.
+    Resolution {
+        left: usize,
+        right: usize,
+        left_sub: Substitution,
+        right_sub: Substitution,
+        atom: Formula,
+    },
+    /// `parent σ`, in which literals of `parent` coincide.
+    Factoring { parent: usize, sub: Substitution },
+}
+
+impl Derivation {
+    pub fn parents(&self) -> Vec<usize> {
+        match self {
+            Derivation::Input(_) => vec![],
+            Derivation::Resolution { left, right, .. } => vec![*left, *right],
+            Derivation::Factoring { parent, .. } => vec![*parent],
+        }
+    }
+}
+
+/// A resolution refutation as a derivation DAG: clauses derived from the
+/// ones before them, the last being the empty clause.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Refutation {
+    pub clauses: Vec<(Clause, Derivation)>,
+}
+
+/// Saturation of a set of clauses by binary resolution and factoring with
+/// the given-clause algorithm, selecting lightest clauses first and
+/// discarding tautologies and subsumed clauses. Equality is not built in,
+/// `=` is treated as any other predicate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolutionProver {
+    /// Most clauses selected over the search, `None` for no bound.
+    pub max_steps: Option<usize>,
+    pub timeout: Option<Duration>,
+}
+
+impl Default for ResolutionProver {
+    fn default() -> ResolutionProver {
+        ResolutionProver {
+            max_steps: Some(2_000),
+            timeout: None,
+        }
+    }
+}
+
+fn _apply(clause: &Clause, sub: &Substitution) -> Clause {
+    clause
+        .iter()
+        .map(|l| Literal {
+            atom: l.atom.substitute_with(sub),
+            positive: l.positive,
+        })
+        .collect()
+}
+
+fn _weight(clause: &Clause) -> usize {
+    clause
+        .iter()
+        .map(|l| l.atom.iter_subterms().count() + 1)
+        .sum()
+}
+
+fn _vars(clause: &Clause) -> HashSet<Term> {
+    clause.iter().flat_map(|l| l.atom.get_free_vars()).collect()
+}
+
+// Whether `lits σ ⊆ clause` for an extension `σ` of `sub` leaving `vars`,
+// the variables of `clause`, alone.
+fn _extends(lits: &[&Literal], clause: &Clause, vars: &HashSet<Term>, sub: &Substitution) -> bool {
+    let (lit, rest) = match lits.split_first() {
+        Some(split) => split,
+        None => return true,
+    };
+    let pattern = lit.atom.substitute_with(sub);
+    clause
+        .iter()
+        .filter(|l| l.positive == lit.positive)
+        .any(|l| match l.atom.matches(&pattern) {
+            Some(ext) => {
+                ext.iter().all(|(v, t)| v == t || !vars.contains(v))
+                    && _extends(rest, clause, vars, &sub.compose(&ext))
+            }
+            None => false,
+        })
+}
+
+// Whether `lhs` subsumes `rhs`, whose variables are distinct from its own.
+fn _subsumes(lhs: &Clause, rhs: &Clause) -> bool {
+    let lits = lhs.iter().collect::<Vec<_>>();
+    lhs.len() <= rhs.len() && _extends(&lits, rhs, &_vars(rhs), &Substitution::new())
+}
+
+struct Saturation {
+    clauses: Vec<(Clause, Derivation)>,
+    symbols: SymbolGenerator,
+    passive: Vec<usize>,
+    active: Vec<usize>,
+}
+
+impl Saturation {
+    fn _renaming(&mut self, clause: &Clause) -> Substitution {
+        let vars = _vars(clause);
+        vars.into_iter()
+            .map(|v| (v, self.symbols.fresh_var("x")))
+            .collect()
+    }
+
+    // Adds `clause` with its variables renamed apart from those of every
+    // other clause, unless it is a tautology. Returns its index if it is
+    // empty.
+    fn _add(&mut self, clause: Clause, derivation: Derivation) -> Option<usize> {
+        if clause.iter().any(|l| clause.contains(&l.negate())) {
+            return None;
+        }
+        let renaming = self._renaming(&clause);
+        let derivation = match derivation {
+            Derivation::Resolution {
+                left,
+                right,
+                left_sub,
+                right_sub,
+                atom,
+            } => Derivation::Resolution {
+                left,
+                right,
+                left_sub: left_sub.compose(&renaming),
+                right_sub: right_sub.compose(&renaming),
+                atom: atom.substitute_with(&renaming),
+            },
+            Derivation::Factoring { parent, sub } => Derivation::Factoring {
+                parent,
+                sub: sub.compose(&renaming),
+            },
+            input => input,
+        };
+        let id = self.clauses.len();
+        let clause = _apply(&clause, &renaming);
+        let empty = clause.is_empty();
+        self.clauses.push((clause, derivation));
+        if empty {
+            return Some(id);
+        }
+        self.passive.push(id);
+        None
+    }
+
+    // Adds the factors of the `given` clause and its resolvents with the
+    // active clauses, itself included. Returns the index of the empty
+    // clause if it is derived.
+    fn _infer(&mut self, given: usize) -> Option<usize> {
+        let clause = self.clauses[given].0.iter().cloned().collect::<Vec<_>>();
+        for (i, lhs) in clause.iter().enumerate() {
+            for rhs in clause[i + 1..]
+                .iter()
+                .filter(|l| l.positive == lhs.positive)
+            {
+                if let Some(sub) = unify_atoms(&lhs.atom, &rhs.atom) {
+                    let factor = _apply(&self.clauses[given].0, &sub);
+                    let derivation = Derivation::Factoring { parent: given, sub };
+                    if let Some(empty) = self._add(factor, derivation) {
+                        return Some(empty);
+                    }
+                }
+            }
+        }
+        for other in self.active.clone() {
+            let other_clause = self.clauses[other].0.clone();
+            let renaming = self._renaming(&other_clause);
+            let renamed = _apply(&other_clause, &renaming);
+            for lhs in &clause {
+                for rhs in renamed.iter().filter(|l| l.positive != lhs.positive) {
+                    let mgu = match unify_atoms(&lhs.atom, &rhs.atom) {
+                        Some(mgu) => mgu,
+                        None => continue,
+                    };
+                    let atom = lhs.atom.substitute_with(&mgu);
+                    let (given_sub, other_sub) = (mgu.clone(), renaming.compose(&mgu));
+                    let ((left, left_sub), (right, right_sub)) = if lhs.positive {
+                        ((given, given_sub), (other, other_sub))
+                    } else {
+                        ((other, other_sub), (given, given_sub))
+                    };
+                    let rest = |id: usize, sub: &Substitution, positive: bool| {
+                        let mut clause = _apply(&self.clauses[id].0, sub);
+                        clause.remove(&Literal {
+                            atom: atom.clone(),
+                            positive,
+                        });
+                        clause
+                    };
+                    let mut resolvent = rest(left, &left_sub, true);
+                    resolvent.extend(rest(right, &right_sub, false));
+                    let derivation = Derivation::Resolution {
+                        left,
+                        right,
+                        left_sub,
+                        right_sub,
+                        atom,
+                    };
+                    if let Some(empty) = self._add(resolvent, derivation) {
+                        return Some(empty);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    // The clauses the `empty` clause was derived from, renumbered.
+    fn _refutation(&self, empty: usize) -> Refutation {
+        let mut used = HashSet::new();
+        let mut stack = vec![empty];
+        while let Some(id) = stack.pop() {
+            if used.insert(id) {
+                stack.extend(self.clauses[id].1.parents());
+            }
+        }
+        let mut used = used.into_iter().collect::<Vec<_>>();
+        used.sort_unstable();
+        let index = |id: &usize| used.binary_search(id).unwrap();
+        let clauses = used
+            .iter()
+            .map(|id| {
+                let (clause, derivation) = self.clauses[*id].clone();
+                let derivation = match derivation {
+                    Derivation::Resolution {
+                        left,
+                        right,
+                        left_sub,
+                        right_sub,
+                        atom,
+                    } => Derivation::Resolution {
+                        left: index(&left),
+                        right: index(&right),
+                        left_sub,
+                        right_sub,
+                        atom,
+                    },
+                    Derivation::Factoring { parent, sub } => Derivation::Factoring {
+                        parent: index(&parent),
+                        sub,
+                    },
+                    input => input,
+                };
+                (clause, derivation)
+            })
+            .collect();
+        Refutation { clauses }
+    }
+}
+
+impl ResolutionProver {
+    /// Derives the empty clause from `clauses`, whose variables are read as
+    /// universally quantified. `SearchFailure::Saturated` means that the
+    /// clauses are satisfiable, taking `=` as an arbitrary relation.
+    pub fn refute(&self, clauses: &[Clause]) -> Result<Refutation, SearchFailure> {
+        let start = Instant::now();
+        let mut symbols = SymbolGenerator::new();
+        clauses
+            .iter()
+            .flatten()
+            .for_each(|l| symbols.avoid(&l.atom));
+        let mut saturation = Saturation {
+            clauses: vec![],
+            symbols,
+            passive: vec![],
+            active: vec![],
+        };
+        for (n, clause) in clauses.iter().enumerate() {
+            if let Some(empty) = saturation._add(clause.clone(), Derivation::Input(n)) {
+                return Ok(saturation._refutation(empty));
+            }
+        }
+        let mut steps = 0;
+        while let Some(k) = (0..saturation.passive.len())
+            .min_by_key(|k| _weight(&saturation.clauses[saturation.passive[*k]].0))
+        {
+            steps += 1;
+            if self.max_steps.is_some_and(|max| steps > max) {
+                return Err(SearchFailure::OutOfSteps);
+            }
+            if self
+                .timeout
+                .is_some_and(|timeout| start.elapsed() >= timeout)
+            {
+                return Err(SearchFailure::Timeout);
+            }
+            let given = saturation.passive.swap_remove(k);
+            let clauses = &saturation.clauses;
+            if (saturation.active.iter()).any(|a| _subsumes(&clauses[*a].0, &clauses[given].0)) {
+                continue;
+            }
+            saturation.active.push(given);
+            if let Some(empty) = saturation._infer(given) {
+                return Ok(saturation._refutation(empty));
+            }
+        }
+        Err(SearchFailure::Saturated)
+    }
+}
+
+impl Prover for ResolutionProver {
+    fn prove_sequent(&self, goal: &Sequent) -> Option<LK> {
+        let form = goal.to_clause_form();
+        let refutation = self.refute(&form.clauses).ok()?;
+        form.to_lk(&refutation).ok()
+    }
+}
+
+// A single-premise G3c rule, its conclusion and the index of its principal
+// formula.
+type Inference = (fn(Box<G3>, Sequent) -> G3, Sequent, usize);
+
+/// The clauses of the negation of a sequent `Γ ⇒ Δ`, i.e. of the formulas
+/// of `Γ` and the negated formulas of `Δ`, with what it takes to turn their
+/// refutations into LK proofs of the sequent. The single-premise invertible
+/// rules of G3c are applied to the sequent first, so that its outermost
+/// `∃` on the left and `∀` on the right get eigenvariables rather than
+/// Skolem constants. Free variables become fresh constants in the clauses.
+#[derive(Debug, Clone)]
+pub struct ClauseForm {
+    pub clauses: Vec<Clause>,
+    // The rules applied to the goal upward, with their conclusions and the
+    // indices of the principal formulas, and the sequent they leave.
+    steps: Vec<Inference>,
+    sequent: Sequent,
+    // The side and index in `sequent` of the formula of each clause.
+    sources: Vec<(bool, usize)>,
+    // The variables the constants stand for.
+    constants: HashMap<Term, Term>,
+    skolem: HashSet<String>,
+}
+
+impl Sequent {
+    pub fn to_clause_form(&self) -> ClauseForm {
+        use Formula::*;
+        let mut symbols = SymbolGenerator::avoiding(self.antecedent.iter().chain(&self.succedent));
+        let mut sequent = self.clone();
+        let mut steps = vec![];
+        let unary = |left: bool, fml: &Formula| {
+            matches!(
+                (left, fml),
+                (_, Not(_)) | (true, And(_, _)) | (false, Or(_, _)) | (false, Implies(_, _))
+            )
+        };
+        loop {
+            let position = match sequent.antecedent.iter().position(|f| unary(true, f)) {
+                Some(i) => Some((true, i)),
+                None => (sequent.succedent.iter())
+                    .position(|f| unary(false, f))
+                    .map(|i| (false, i)),
+            };
+            let (i, step) = match position {
+                Some((left, i)) => (i, decompose(&sequent, left, i)),
+                None => match eigen_step(&sequent, &mut symbols) {
+                    Some((_, i, step)) => (i, step),
+                    None => break,
+                },
+            };
+            if let Step::Unary(rule, premise) = step {
+                steps.push((rule, sequent, i));
+                sequent = premise;
+            }
+        }
+        let vars = (sequent.antecedent.iter().chain(&sequent.succedent))
+            .flat_map(|f| f.get_free_vars())
+            .collect::<HashSet<_>>();
+        let mut freeze = Substitution::new();
+        let mut constants = HashMap::new();
+        for var in vars {
+            let constant = Term::Func(symbols.fresh_func(&var.to_string(), 0).name, vec![]);
+            freeze.insert(var.clone(), constant.clone());
+            constants.insert(constant, var);
+        }
+        let introduced = symbols.introduced().len();
+        let (mut clauses, mut sources) = (vec![], vec![]);
+        for (left, fmls) in [(true, &sequent.antecedent), (false, &sequent.succedent)] {
+            for (i, fml) in fmls.iter().enumerate() {
+                let fml = fml.substitute_with(&freeze);
+                let fml = if left { fml } else { not!(fml) };
+                for clause in fml.to_cnf_with(CnfConversion::Distributive, &mut symbols) {
+                    clauses.push(clause);
+                    sources.push((left, i));
+                }
+            }
+        }
+        let skolem = symbols.introduced()[introduced..]
+            .iter()
+            .map(|s| s.name.clone())
+            .collect();
+        ClauseForm {
+            clauses,
+            steps,
+            sequent,
+            sources,
+            constants,
+            skolem,
+        }
+    }
+}
+
+// A formula stripped of its quantifiers, each bound variable replaced by a
+// metavariable for `∀` on the left and `∃` on the right, and by a function
+// of the enclosing metavariables for the others. The clauses of the formula
+// are instances of those of its matrix, with Skolem functions for these.
+#[derive(Default)]
+struct Template {
+    // The quantifier inferences on the way to the matrix: the rule, the
+    // quantified formula and the term it is instantiated with.
+    instances: Vec<(&'static str, Formula, Term)>,
+    // The metavariables with the variables they were bound to.
+    metavars: HashMap<Term, Term>,
+    // The functions standing for eigenvariables.
+    deltas: HashSet<String>,
+}
+
+// The terms the metavariables of a template stand for and the Skolem
+// functions its functions for eigenvariables stand for.
+#[derive(Debug, Clone, Default)]
+struct Binding {
+    terms: HashMap<Term, Term>,
+    funcs: HashMap<String, String>,
+}
+
+impl Template {
+    // The matrix of `fml`, occurring on the left if `left`, with `gammas`
+    // the metavariables of the enclosing quantifiers.
+    fn _strip(
+        &mut self,
+        fml: &Formula,
+        left: bool,
+        gammas: &mut Vec<Term>,
+        symbols: &mut SymbolGenerator,
+    ) -> Formula {
+        use Formula::*;
+        let quantified = |f: &Formula| {
+            f.iter_subformulas()
+                .any(|g| matches!(g, Forall(_, _) | Exists(_, _)))
+        };
+        match fml {
+            Not(f) => not!(self._strip(f, !left, gammas, symbols)),
+            And(lhs, rhs) => and!(
+                self._strip(lhs, left, gammas, symbols),
+                self._strip(rhs, left, gammas, symbols)
+            ),
+            Or(lhs, rhs) => or!(
+                self._strip(lhs, left, gammas, symbols),
+                self._strip(rhs, left, gammas, symbols)
+            ),
+            Implies(lhs, rhs) => implies!(
+                self._strip(lhs, !left, gammas, symbols),
+                self._strip(rhs, left, gammas, symbols)
+            ),
+            // Both sides occur in both polarities, as in the clause form.
+            Iff(lhs, rhs) if quantified(lhs) || quantified(rhs) => {
+                let fml = and!(
+                    implies!(*lhs.clone(), *rhs.clone()),
+                    implies!(*rhs.clone(), *lhs.clone())
+                );
+                self._strip(&fml, left, gammas, symbols)
+            }
+            Forall(var, body) | Exists(var, body) => {
+                let gamma = matches!(fml, Forall(_, _)) == left;
+                let rule = match (fml, left) {
+                    (Forall(_, _), true) => "ForallLeft",
+                    (Forall(_, _), false) => "ForallRight",
+                    (_, true) => "ExistsLeft",
+                    (_, false) => "ExistsRight",
+                };
+                let term = if gamma {
+                    let metavar = symbols.fresh_var(&var.to_string());
+                    self.metavars.insert(metavar.clone(), var.clone());
+                    metavar
+                } else {
+                    let func = symbols.fresh_func("sk", gammas.len() as u32).name;
+                    self.deltas.insert(func.clone());
+                    Term::Func(func, gammas.clone())
+                };
+                self.instances.push((rule, fml.clone(), term.clone()));
+                let body = body.substitute(var.clone(), term.clone());
+                if gamma {
+                    gammas.push(term);
+                }
+                let matrix = self._strip(&body, left, gammas, symbols);
+                if gamma {
+                    gammas.pop();
+                }
+                matrix
+            }
+            _ => fml.clone(),
+        }
+    }
+
+    fn _match_term(&self, pattern: &Term, term: &Term, binding: &mut Binding) -> bool {
+        match (pattern, term) {
+            (Term::Var(_), _) if self.metavars.contains_key(pattern) => {
+                match binding.terms.get(pattern) {
+                    Some(bound) => bound == term,
+                    None => {
+                        binding.terms.insert(pattern.clone(), term.clone());
+                        true
+                    }
+                }
+            }
+            (Term::Func(f, xs), Term::Func(g, ys)) if xs.len() == ys.len() => {
+                let same = match binding.funcs.get(f) {
+                    Some(h) => h == g,
+                    None if self.deltas.contains(f) => {
+                        binding.funcs.insert(f.clone(), g.clone());
+                        true
+                    }
+                    None => f == g,
+                };
+                same && xs
+                    .iter()
+                    .zip(ys)
+                    .all(|(x, y)| self._match_term(x, y, binding))
+            }
+            _ => pattern == term,
+        }
+    }
+
+    fn _match_atom(&self, pattern: &Formula, atom: &Formula, binding: &mut Binding) -> bool {
+        match (pattern, atom) {
+            (Formula::Pred(p, xs), Formula::Pred(q, ys)) => {
+                p == q
+                    && xs.len() == ys.len()
+                    && xs
+                        .iter()
+                        .zip(ys)
+                        .all(|(x, y)| self._match_term(x, y, binding))
+            }
+            (Formula::Equal(a, b), Formula::Equal(c, d)) => {
+                self._match_term(a, c, binding) && self._match_term(b, d, binding)
+            }
+            _ => false,
+        }
+    }
+
+    // A binding making the literals of `patterns` those of `clause`.
+    fn _match_clause(
+        &self,
+        patterns: &[&Literal],
+        clause: &Clause,
+        binding: Binding,
+    ) -> Option<Binding> {
+        let (pattern, rest) = match patterns.split_first() {
+            Some(split) => split,
+            None => return Some(binding),
+        };
+        clause
+            .iter()
+            .filter(|l| l.positive == pattern.positive)
+            .find_map(|l| {
+                let mut binding = binding.clone();
+                if self._match_atom(&pattern.atom, &l.atom, &mut binding) {
+                    self._match_clause(rest, clause, binding)
+                } else {
+                    None
+                }
+            })
+    }
+}
+
+// Replaces the metavariables and functions of a template as a binding says,
+// the metavariables it leaves unbound with their variables, then the terms
+// headed by Skolem functions with proxy variables, the same for equal terms.
+struct Proxies<'a> {
+    template: &'a Template,
+    binding: &'a Binding,
+    skolem: &'a HashSet<String>,
+    proxies: &'a mut HashMap<Term, Term>,
+    symbols: &'a mut SymbolGenerator,
+}
+
+impl TermFolder for Proxies<'_> {
+    fn fold_term(&mut self, term: &Term) -> Term {
+        match term {
+            Term::Var(_) => match (
+                self.binding.terms.get(term),
+                self.template.metavars.get(term),
+            ) {
+                (Some(bound), _) => self.fold_term(&bound.clone()),
+                (None, Some(var)) => var.clone(),
+                (None, None) => term.clone(),
+            },
+            Term::Func(name, args) => {
+                let name = self.binding.funcs.get(name).unwrap_or(name).clone();
+                let args = args.iter().map(|t| self.fold_term(t)).collect();
+                let term = Term::Func(name, args);
+                match &term {
+                    Term::Func(name, _)
+                        if self.skolem.contains(name) || self.template.deltas.contains(name) =>
+                    {
+                        let symbols = &mut self.symbols;
+                        (self.proxies.entry(term.clone()))
+                            .or_insert_with(|| symbols.fresh_var("v"))
+                            .clone()
+                    }
+                    _ => term,
+                }
+            }
+        }
+    }
+}
+
+impl FormulaFolder for Proxies<'_> {}
+
+impl ClauseForm {
+    fn _unfreeze_term(&self, term: &Term) -> Term {
+        match (self.constants.get(term), term) {
+            (Some(var), _) => var.clone(),
+            (None, Term::Func(name, terms)) => Term::Func(
+                name.clone(),
+                terms.iter().map(|t| self._unfreeze_term(t)).collect(),
+            ),
+            (None, var) => var.clone(),
+        }
+    }
+
+    fn _unfreeze(&self, atom: &Formula) -> Formula {
+        match atom {
+            Formula::Pred(name, terms) => Formula::Pred(
+                name.clone(),
+                terms.iter().map(|t| self._unfreeze_term(t)).collect(),
+            ),
+            Formula::Equal(lhs, rhs) => {
+                Formula::Equal(self._unfreeze_term(lhs), self._unfreeze_term(rhs))
+            }
+            fml => fml.clone(),
+        }
+    }
+
+    // The atoms of the negative and of the positive literals of `clause`.
+    fn _atoms(&self, clause: &Clause) -> (Vec<Formula>, Vec<Formula>) {
+        let mut literals = clause.iter().collect::<Vec<_>>();
+        literals.sort_by_key(|l| l.atom.to_string());
+        let atoms = |positive: bool| {
+            (literals.iter())
+                .filter(|l| l.positive == positive)
+                .map(|l| self._unfreeze(&l.atom))
+                .collect()
+        };
+        (atoms(false), atoms(true))
+    }
+
+    // An LK proof of `Γ, N ⇒ P, Δ` for the sequent `Γ ⇒ Δ` left by the
+    // invertible rules and the instance `C σ` of the `id`-th clause `C` of
+    // `refutation`, with `N` the atoms of its negative literals and `P` of
+    // its positive ones.
+    fn _prove(
+        &self,
+        refutation: &Refutation,
+        id: usize,
+        sub: &Substitution,
+    ) -> Result<LK, &'static str> {
+        let (clause, derivation) = refutation.clauses.get(id).ok_or(NOT_A_REFUTATION)?;
+        let instance = _apply(clause, sub);
+        let (negative, positive) = self._atoms(&instance);
+        let target = Sequent {
+            antecedent: [&self.sequent.antecedent[..], &negative].concat(),
+            succedent: [&positive[..], &self.sequent.succedent].concat(),
+        };
+        let proof = match derivation {
+            Derivation::Input(n) => {
+                let (left, i) = *self.sources.get(*n).ok_or(NOT_A_REFUTATION)?;
+                let (mut antecedent, mut succedent) = (negative, positive);
+                if left {
+                    antecedent.insert(0, self.sequent.antecedent[i].clone());
+                } else {
+                    succedent.push(self.sequent.succedent[i].clone());
+                }
+                FirstOrderProver::default()
+                    .search(&Sequent {
+                        antecedent,
+                        succedent,
+                    })
+                    .map_err(|_| "An input clause does not follow from its formula.")?
+            }
+            Derivation::Factoring {
+                parent,
+                sub: factor,
+            } => self._prove(refutation, *parent, &factor.compose(sub))?,
+            Derivation::Resolution {
+                left,
+                right,
+                left_sub,
+                right_sub,
+                atom,
+            } => {
+                let atom = self._unfreeze(&atom.substitute_with(sub));
+                let lproof = self._prove(refutation, *left, &left_sub.compose(sub))?;
+                let rproof = self._prove(refutation, *right, &right_sub.compose(sub))?;
+                let mut lsequent = lproof.last().clone();
+                lsequent.succedent.retain(|f| f != &atom);
+                lsequent.succedent.push(atom.clone());
+                let mut rsequent = rproof.last().clone();
+                rsequent.antecedent.retain(|f| f != &atom);
+                rsequent.antecedent.insert(0, atom);
+                let conclusion = Sequent {
+                    antecedent: [&lsequent.antecedent[..], rsequent.ant_but_first()].concat(),
+                    succedent: [lsequent.suc_but_last(), &rsequent.succedent].concat(),
+                };
+                let lproof = adapt(lproof, &lsequent)?;
+                let rproof = adapt(rproof, &rsequent)?;
+                LK::from_parts("Cut", vec![lproof, rproof], conclusion).ok_or(NOT_A_REFUTATION)?
+            }
+        };
+        adapt(proof, &target)
+    }
+
+    // The instances `C σ` of the input clauses the `id`-th clause of
+    // `refutation` is derived from under `sub`, with their indices.
+    fn _leaves(
+        &self,
+        refutation: &Refutation,
+        id: usize,
+        sub: &Substitution,
+        leaves: &mut Vec<(usize, Clause)>,
+    ) -> Result<(), &'static str> {
+        let (clause, derivation) = refutation.clauses.get(id).ok_or(NOT_A_REFUTATION)?;
+        match derivation {
+            Derivation::Input(n) => leaves.push((*n, _apply(clause, sub))),
+            Derivation::Factoring {
+                parent,
+                sub: factor,
+            } => self._leaves(refutation, *parent, &factor.compose(sub), leaves)?,
+            Derivation::Resolution {
+                left,
+                right,
+                left_sub,
+                right_sub,
+                ..
+            } => {
+                self._leaves(refutation, *left, &left_sub.compose(sub), leaves)?;
+                self._leaves(refutation, *right, &right_sub.compose(sub), leaves)?;
+            }
+        }
+        Ok(())
+    }
+
+    // An LK proof of the sequent left by the invertible rules from the
+    // instances of the input clauses a refutation uses. Their Skolem terms
+    // are replaced with proxy variables, which instantiate the quantifiers
+    // the Skolem functions stand for as eigenvariables, and the proof is
+    // searched for with these instances only, see `Certificate::rebuild`.
+    fn _deskolemize(&self, leaves: &[(usize, Clause)]) -> Result<LK, &'static str> {
+        const UNMATCHED: &str = "An input clause does not follow from its formula.";
+        let mut symbols = SymbolGenerator::avoiding(
+            self.sequent
+                .antecedent
+                .iter()
+                .chain(&self.sequent.succedent),
+        );
+        leaves
+            .iter()
+            .flat_map(|(_, clause)| clause)
+            .for_each(|l| symbols.avoid(&l.atom));
+        let (mut proxies, mut renaming) = (HashMap::new(), Substitution::new());
+        let (mut instances, mut unbound) = (vec![], vec![]);
+        for (n, instance) in leaves {
+            let (left, i) = *self.sources.get(*n).ok_or(NOT_A_REFUTATION)?;
+            let fml = if left {
+                &self.sequent.antecedent[i]
+            } else {
+                &self.sequent.succedent[i]
+            };
+            let mut template = Template::default();
+            let matrix = template._strip(fml, left, &mut vec![], &mut symbols);
+            let matrix = if left { matrix } else { not!(matrix) };
+            // The variables left in the instance are renamed apart from
+            // those of the sequent, the same in every instance.
+            for l in instance {
+                for var in l.atom.get_free_vars() {
+                    if renaming.get(&var).is_none() {
+                        let fresh = symbols.fresh_var(&var.to_string());
+                        renaming.insert(var, fresh);
+                    }
+                }
+            }
+            let clause = (instance.iter())
+                .map(|l| Literal {
+                    atom: self._unfreeze(&l.atom.substitute_with(&renaming)),
+                    positive: l.positive,
+                })
+                .collect::<Clause>();
+            let binding = (matrix.to_cnf().iter())
+                .find_map(|c| {
+                    let patterns = c.iter().collect::<Vec<_>>();
+                    template._match_clause(&patterns, &clause, Binding::default())
+                })
+                .ok_or(UNMATCHED)?;
+            let mut proxy = Proxies {
+                template: &template,
+                binding: &binding,
+                skolem: &self.skolem,
+                proxies: &mut proxies,
+                symbols: &mut symbols,
+            };
+            for (rule, fml, term) in &template.instances {
+                let instance = QuantifierInstance {
+                    rule,
+                    formula: proxy.fold_formula(fml),
+                    term: proxy.fold_term(term),
+                };
+                // Eigenvariables for Skolem functions the refutation does
+                // not use come last, so that those it uses are taken first.
+                match term {
+                    Term::Func(f, _) if !binding.funcs.contains_key(f) => unbound.push(instance),
+                    _ => instances.push(instance),
+                }
+            }
+        }
+        let mut certificate = Certificate::new();
+        for instance in instances.into_iter().chain(unbound) {
+            if !certificate.instances.contains(&instance) {
+                certificate.instances.push(instance);
+            }
+        }
+        certificate
+            .rebuild(&self.sequent)
+            .map_err(|_| "The de-Skolemized instances do not give a proof.")
+    }
+
+    /// Turns a refutation of `clauses` into an LK proof of the sequent,
+    /// cutting on the atoms resolved upon. If the refutation instantiates
+    /// Skolem functions, which stand for eigenvariables LK cannot introduce
+    /// above the cuts, the proof is instead rebuilt from the instances of
+    /// the input clauses it uses, with a proxy variable for each Skolem
+    /// term introduced as an eigenvariable.
+    pub fn to_lk(&self, refutation: &Refutation) -> Result<LK, &'static str> {
+        match refutation.clauses.last() {
+            Some((clause, _)) if clause.is_empty() => {}
+            _ => return Err(NOT_A_REFUTATION),
+        }
+        let empty = refutation.clauses.len() - 1;
+        let mut leaves = vec![];
+        self._leaves(refutation, empty, &Substitution::new(), &mut leaves)?;
+        let skolemized = |t: &Term| matches!(t, Term::Func(f, _) if self.skolem.contains(f));
+        let mut proof = if (leaves.iter().flat_map(|(_, clause)| clause))
+            .any(|l| l.atom.iter_subterms().any(skolemized))
+        {
+            self._deskolemize(&leaves)?
+        } else {
+            let proof = self._prove(refutation, empty, &Substitution::new())?;
+            adapt(proof, &self.sequent)?
+        };
+        for (rule, conclusion, principal) in self.steps.iter().rev() {
+            proof =
+                G3::infer(*rule, proof, conclusion.clone(), *principal).ok_or(NOT_A_REFUTATION)?;
+        }
+        Ok(proof)
+    }
+}
@@ -0,0 +1,398 @@
+use crate::adapt::*;
+use crate::language::*;
+use crate::proof::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Antecedent,
+    Succedent,
+}
+
+/// An occurrence of a subformula in a sequent: the `index`-th formula of
+/// `side`, then the operands along `path`, `0` for the left (or only) one
+/// and `1` for the right one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Position {
+    pub side: Side,
+    pub index: usize,
+    pub path: Vec<usize>,
+}
+
+impl Formula {
+    /// The subformula at `path`, see `Position`.
+    pub fn subformula_at(&self, path: &[usize]) -> Option<&Formula> {
+        match (self, path.split_first()) {
+            (_, None) => Some(self),
+            (Formula::Not(fml), Some((0, path)))
+            | (Formula::Forall(_, fml), Some((0, path)))
+            | (Formula::Exists(_, fml), Some((0, path))) => fml.subformula_at(path),
+            (Formula::And(lhs, rhs), Some((i, path)))
+            | (Formula::Or(lhs, rhs), Some((i, path)))
+            | (Formula::Implies(lhs, rhs), Some((i, path)))
+            | (Formula::Iff(lhs, rhs), Some((i, path))) => match i {
+                0 => lhs.subformula_at(path),
+                1 => rhs.subformula_at(path),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The formula with the subformula at `path` replaced by `fml`. Variables
+    /// of `fml` may get bound by the quantifiers above `path`.
+    pub fn replace_at(&self, path: &[usize], fml: Formula) -> Option<Formula> {
+        let (&i, path) = match path.split_first() {
+            Some(step) => step,
+            None => return Some(fml),
+        };
+        let replace = |sub: &Formula| sub.replace_at(path, fml).map(Box::new);
+        match (self, i) {
+            (Formula::Not(sub), 0) => Some(Formula::Not(replace(sub)?)),
+            (Formula::Forall(var, sub), 0) => Some(Formula::Forall(var.clone(), replace(sub)?)),
+            (Formula::Exists(var, sub), 0) => Some(Formula::Exists(var.clone(), replace(sub)?)),
+            (Formula::And(lhs, rhs), 0) => Some(Formula::And(replace(lhs)?, rhs.clone())),
+            (Formula::And(lhs, rhs), 1) => Some(Formula::And(lhs.clone(), replace(rhs)?)),
+            (Formula::Or(lhs, rhs), 0) => Some(Formula::Or(replace(lhs)?, rhs.clone())),
+            (Formula::Or(lhs, rhs), 1) => Some(Formula::Or(lhs.clone(), replace(rhs)?)),
+            (Formula::Implies(lhs, rhs), 0) => Some(Formula::Implies(replace(lhs)?, rhs.clone())),
+            (Formula::Implies(lhs, rhs), 1) => Some(Formula::Implies(lhs.clone(), replace(rhs)?)),
+            (Formula::Iff(lhs, rhs), 0) => Some(Formula::Iff(replace(lhs)?, rhs.clone())),
+            (Formula::Iff(lhs, rhs), 1) => Some(Formula::Iff(lhs.clone(), replace(rhs)?)),
+            _ => None,
+        }
+    }
+}
+
+/// A proved equivalence `A ↔ B`, kept as proofs of `A ⇒ B` and `B ⇒ A`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Equivalence {
+    lhs: Formula,
+    rhs: Formula,
+    forward: LK,
+    backward: LK,
+}
+
+fn _axiom(fml: &Formula) -> LK {
+    LK::Axiom(sequent!(fml.clone() => fml.clone()))
+}
+
+// Derives `target` from `proof` by structural rules, see `adapt`.
+pub(crate) fn restructure(proof: LK, target: &Sequent) -> LK {
+    adapt(proof, target).unwrap()
+}
+
+// Cuts the last formula of the succedent of `left` with the first formula of
+// the antecedent of `right`.
+fn _cut(left: LK, right: LK) -> LK {
+    let sequent = Sequent {
+        antecedent: [&left.last().antecedent[..], right.last().ant_but_first()].concat(),
+        succedent: [left.last().suc_but_last(), &right.last().succedent[..]].concat(),
+    };
+    LK::Cut(Box::new([left, right]), sequent)
+}
+
+// `lhs ↔ rhs, lhs ⇒ rhs` if `forward`, else `lhs ↔ rhs, rhs ⇒ lhs`.
+fn _iff_elim(lhs: &Formula, rhs: &Formula, forward: bool) -> LK {
+    let iff = iff!(lhs.clone(), rhs.clone());
+    let (from, to) = if forward { (lhs, rhs) } else { (rhs, lhs) };
+    let from = from.clone();
+    let to = to.clone();
+    LK::IffLeft(
+        Box::new([
+            restructure(
+                _axiom(&to),
+                &sequent!(lhs.clone(), rhs.clone(), from.clone() => to.clone()),
+            ),
+            restructure(
+                _axiom(&from),
+                &sequent!(from.clone() => to.clone(), lhs.clone(), rhs.clone()),
+            ),
+        ]),
+        sequent!(iff, from => to),
+    )
+}
+
+// `x ⇒ y` where `y` is `x` with the subformula at `path` replaced by the
+// one proved equivalent to it by `fwd` and `bwd`.
+fn _congruence(x: &Formula, path: &[usize], fwd: &LK, bwd: &LK) -> Result<LK, &'static str> {
+    let (&i, path) = match path.split_first() {
+        Some(step) => step,
+        None => return Ok(fwd.clone()),
+    };
+    let dst = bwd.last().ant_first().clone();
+    let y = x
+        .replace_at(&[&[i][..], path].concat(), dst)
+        .ok_or("Invalid position.")?;
+    let sequent = sequent!(x.clone() => y.clone());
+    Ok(match (x, &y, i) {
+        (Formula::Not(_), Formula::Not(y1), 0) => {
+            let sub = _congruence(y1, path, bwd, fwd)?;
+            let not_left = LK::NotLeft(Box::new(sub), sequent!(x.clone(), (**y1).clone() => ));
+            LK::NotRight(
+                Box::new(restructure(
+                    not_left,
+                    &sequent!((**y1).clone(), x.clone() => ),
+                )),
+                sequent,
+            )
+        }
+        (Formula::And(x1, x2), Formula::And(y1, y2), _) => {
+            let (left, right) = if i == 0 {
+                (
+                    LK::AndLeft1(
+                        Box::new(_congruence(x1, path, fwd, bwd)?),
+                        sequent!(x.clone() => (**y1).clone()),
+                    ),
+                    LK::AndLeft2(Box::new(_axiom(x2)), sequent!(x.clone() => (**y2).clone())),
+                )
+            } else {
+                (
+                    LK::AndLeft1(Box::new(_axiom(x1)), sequent!(x.clone() => (**y1).clone())),
+                    LK::AndLeft2(
+                        Box::new(_congruence(x2, path, fwd, bwd)?),
+                        sequent!(x.clone() => (**y2).clone()),
+                    ),
+                )
+            };
+            LK::AndRight(Box::new([left, right]), sequent)
+        }
+        (Formula::Or(x1, x2), Formula::Or(_, _), _) => {
+            let (left, right) = if i == 0 {
+                (_congruence(x1, path, fwd, bwd)?, _axiom(x2))
+            } else {
+                (_axiom(x1), _congruence(x2, path, fwd, bwd)?)
+            };
+            LK::OrLeft(
+                Box::new([
+                    LK::OrRight1(Box::new(left), sequent!((**x1).clone() => y.clone())),
+                    LK::OrRight2(Box::new(right), sequent!((**x2).clone() => y.clone())),
+                ]),
+                sequent,
+            )
+        }
+        (Formula::Implies(x1, x2), Formula::Implies(y1, y2), _) => {
+            let (left, right) = if i == 0 {
+                (_congruence(y1, path, bwd, fwd)?, _axiom(x2))
+            } else {
+                (_axiom(x1), _congruence(x2, path, fwd, bwd)?)
+            };
+            let implies_left = LK::ImpliesLeft(
+                Box::new([left, right]),
+                sequent!(x.clone(), (**y1).clone() => (**y2).clone()),
+            );
+            LK::ImpliesRight(
+                Box::new(restructure(
+                    implies_left,
+                    &sequent!((**y1).clone(), x.clone() => (**y2).clone()),
+                )),
+                sequent,
+            )
+        }
+        (Formula::Iff(x1, x2), Formula::Iff(y1, y2), _) => {
+            let (y1, y2) = ((**y1).clone(), (**y2).clone());
+            let (left, right) = if i == 0 {
+                (
+                    _cut(
+                        _congruence(&y1, path, bwd, fwd)?,
+                        restructure(
+                            _iff_elim(x1, x2, true),
+                            &sequent!((**x1).clone(), x.clone() => y2.clone()),
+                        ),
+                    ),
+                    _cut(
+                        restructure(
+                            _iff_elim(x1, x2, false),
+                            &sequent!(y2.clone(), x.clone() => (**x1).clone()),
+                        ),
+                        _congruence(x1, path, fwd, bwd)?,
+                    ),
+                )
+            } else {
+                (
+                    _cut(
+                        restructure(
+                            _iff_elim(x1, x2, true),
+                            &sequent!(y1.clone(), x.clone() => (**x2).clone()),
+                        ),
+                        _congruence(x2, path, fwd, bwd)?,
+                    ),
+                    _cut(
+                        _congruence(&y2, path, bwd, fwd)?,
+                        restructure(
+                            _iff_elim(x1, x2, false),
+                            &sequent!((**x2).clone(), x.clone() => y1.clone()),
+                        ),
+                    ),
+                )
+            };
+            LK::IffRight(Box::new([left, right]), sequent)
+        }
+        (Formula::Forall(_, x1), Formula::Forall(_, y1), 0) => {
+            let sub = _congruence(x1, path, fwd, bwd)?;
+            LK::ForallRight(
+                Box::new(LK::ForallLeft(
+                    Box::new(sub),
+                    sequent!(x.clone() => (**y1).clone()),
+                )),
+                sequent,
+            )
+        }
+        (Formula::Exists(_, x1), Formula::Exists(_, _), 0) => {
+            let sub = _congruence(x1, path, fwd, bwd)?;
+            LK::ExistsLeft(
+                Box::new(LK::ExistsRight(
+                    Box::new(sub),
+                    sequent!((**x1).clone() => y.clone()),
+                )),
+                sequent,
+            )
+        }
+        _ => return Err("Invalid position."),
+    })
+}
+
+impl Equivalence {
+    /// From a proof of `⇒ A ↔ B`.
+    pub fn from_iff(theorem: LK) -> Result<Equivalence, &'static str> {
+        let (lhs, rhs) = match (
+            &theorem.last().antecedent[..],
+            &theorem.last().succedent[..],
+        ) {
+            ([], [Formula::Iff(lhs, rhs)]) => ((**lhs).clone(), (**rhs).clone()),
+            _ => return Err("Theorem is not of the form ⇒ A ↔ B."),
+        };
+        Ok(Equivalence {
+            forward: _cut(theorem.clone(), _iff_elim(&lhs, &rhs, true)),
+            backward: _cut(theorem, _iff_elim(&lhs, &rhs, false)),
+            lhs,
+            rhs,
+        })
+    }
+
+    /// From proofs of `⇒ A → B` and `⇒ B → A`.
+    pub fn from_implications(forward: LK, backward: LK) -> Result<Equivalence, &'static str> {
+        let elim = |theorem: LK| match (
+            &theorem.last().antecedent[..],
+            &theorem.last().succedent[..],
+        ) {
+            ([], [Formula::Implies(lhs, rhs)]) => {
+                let (lhs, rhs) = ((**lhs).clone(), (**rhs).clone());
+                let implies_left = LK::ImpliesLeft(
+                    Box::new([_axiom(&lhs), _axiom(&rhs)]),
+                    sequent!(implies!(lhs.clone(), rhs.clone()), lhs.clone() => rhs.clone()),
+                );
+                Ok((lhs, rhs, _cut(theorem, implies_left)))
+            }
+            _ => Err("Theorem is not of the form ⇒ A → B."),
+        };
+        let (lhs, rhs, forward) = elim(forward)?;
+        let (rhs2, lhs2, backward) = elim(backward)?;
+        if lhs != lhs2 || rhs != rhs2 {
+            return Err("Implications are not converse.");
+        }
+        Ok(Equivalence {
+            lhs,
+            rhs,
+            forward,
+            backward,
+        })
+    }
+
+    pub fn lhs(&self) -> &Formula {
+        &self.lhs
+    }
+
+    pub fn rhs(&self) -> &Formula {
+        &self.rhs
+    }
+
+    /// The equivalence `B ↔ A`.
+    pub fn reversed(&self) -> Equivalence {
+        Equivalence {
+            lhs: self.rhs.clone(),
+            rhs: self.lhs.clone(),
+            forward: self.backward.clone(),
+            backward: self.forward.clone(),
+        }
+    }
+}
+
+/// A goal with an occurrence of `A` replaced by `B` using a proved
+/// equivalence `A ↔ B`. A proof of `subgoal` is turned into one of `goal`
+/// by `justify`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rewrite {
+    pub goal: Sequent,
+    pub subgoal: Sequent,
+    side: Side,
+    index: usize,
+    // `C[A] ⇒ C[B]` in the antecedent, `C[B] ⇒ C[A]` in the succedent.
+    lemma: LK,
+}
+
+impl Rewrite {
+    /// Extends a proof of `subgoal` to a proof of `goal` by cutting with the
+    /// congruence lemma, with exchanges around the cut.
+    pub fn justify(&self, proof: LK) -> Result<LK, &'static str> {
+        if proof.last() != &self.subgoal {
+            return Err("Proof does not end in the subgoal.");
+        }
+        let mut moved = self.subgoal.clone();
+        Ok(match self.side {
+            Side::Antecedent => {
+                let fml = moved.antecedent.remove(self.index);
+                moved.antecedent.insert(0, fml);
+                let cut = _cut(self.lemma.clone(), restructure(proof, &moved));
+                restructure(cut, &self.goal)
+            }
+            Side::Succedent => {
+                let fml = moved.succedent.remove(self.index);
+                moved.succedent.push(fml);
+                let cut = _cut(restructure(proof, &moved), self.lemma.clone());
+                restructure(cut, &self.goal)
+            }
+        })
+    }
+}
+
+/// Replaces the occurrence of `theorem.lhs()` at `position` in `goal` by
+/// `theorem.rhs()`. The justifying LK steps are built with cuts and the
+/// congruence of each connective and quantifier above the position.
+pub fn rewrite_in_goal(
+    goal: &Sequent,
+    theorem: &Equivalence,
+    position: &Position,
+) -> Result<Rewrite, &'static str> {
+    let fmls = match position.side {
+        Side::Antecedent => &goal.antecedent,
+        Side::Succedent => &goal.succedent,
+    };
+    let fml = fmls.get(position.index).ok_or("Invalid position.")?;
+    if fml.subformula_at(&position.path) != Some(&theorem.lhs) {
+        return Err("Theorem does not apply at the position.");
+    }
+    let new_fml = fml
+        .replace_at(&position.path, theorem.rhs.clone())
+        .ok_or("Invalid position.")?;
+    let lemma = match position.side {
+        Side::Antecedent => _congruence(fml, &position.path, &theorem.forward, &theorem.backward)?,
+        Side::Succedent => _congruence(
+            &new_fml,
+            &position.path,
+            &theorem.backward,
+            &theorem.forward,
+        )?,
+    };
+    let mut subgoal = goal.clone();
+    match position.side {
+        Side::Antecedent => subgoal.antecedent[position.index] = new_fml,
+        Side::Succedent => subgoal.succedent[position.index] = new_fml,
+    }
+    Ok(Rewrite {
+        goal: goal.clone(),
+        subgoal,
+        side: position.side,
+        index: position.index,
+        lemma,
+    })
+}
@@ -0,0 +1,303 @@
+use crate::proof::*;
+
+/// What an inference has to satisfy besides the shapes of its sequents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SideCondition {
+    None,
+    /// The eigenvariable is not free in the conclusion.
+    Eigenvariable,
+    /// The term is free for the bound variable it replaces.
+    Substitutible,
+    /// No occurrence replaced is in the scope of a quantifier binding a
+    /// variable of either side of the equation.
+    Replacement,
+    /// The inference is an instance of a rule or axiom of the theory.
+    Theory,
+}
+
+/// An inference rule of `LK` as the checker reads it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuleSpec {
+    /// The name given by `LK::rule_name`.
+    pub name: &'static str,
+    /// `None` for as many as the rule of the theory has.
+    pub premises: Option<usize>,
+    /// The numbers of principal formulas at the start of the antecedent
+    /// and at the end of the succedent of the conclusion.
+    pub principals: (usize, usize),
+    pub structural: bool,
+    /// Whether every premise follows from the conclusion.
+    pub invertible: bool,
+    pub condition: SideCondition,
+    /// The premises and the conclusion as sequents over metavariables, see
+    /// `RULES`, or `None` for the rules of a theory.
+    pub schema: Option<(&'static [&'static str], &'static str)>,
+}
+
+const fn _rule(
+    name: &'static str,
+    premises: &'static [&'static str],
+    conclusion: &'static str,
+    principals: (usize, usize),
+    invertible: bool,
+) -> RuleSpec {
+    RuleSpec {
+        name,
+        premises: Some(premises.len()),
+        principals,
+        structural: false,
+        invertible,
+        condition: SideCondition::None,
+        schema: Some((premises, conclusion)),
+    }
+}
+
+const fn _structural(
+    name: &'static str,
+    premise: &'static [&'static str],
+    conclusion: &'static str,
+    principals: (usize, usize),
+    invertible: bool,
+) -> RuleSpec {
+    RuleSpec {
+        structural: true,
+        ..(_rule(name, premise, conclusion, principals, invertible))
+    }
+}
+
+const fn _with(spec: RuleSpec, condition: SideCondition) -> RuleSpec {
+    RuleSpec { condition, ..spec }
+}
+
+/// The rules of `LK`, in the order of its variants. The schemas are written
+/// as sequents, with `A` and `B` for formulas, `F` for a formula of one
+/// parameter, `s` and `t` for terms, `y` for an eigenvariable, and `Gamma`,
+/// `Delta`, `Pi` and `Sigma` for lists of formulas.
+pub const RULES: [RuleSpec; 28] = [
+    _rule("Axiom", &[], "((A) (A))", (1, 1), true),
+    _structural(
+        "WeakeningLeft",
+        &["((Gamma) (Delta))"],
+        "((A Gamma) (Delta))",
+        (1, 0),
+        false,
+    ),
+    _structural(
+        "WeakeningRight",
+        &["((Gamma) (Delta))"],
+        "((Gamma) (Delta A))",
+        (0, 1),
+        false,
+    ),
+    _structural(
+        "ContractionLeft",
+        &["((A A Gamma) (Delta))"],
+        "((A Gamma) (Delta))",
+        (1, 0),
+        true,
+    ),
+    _structural(
+        "ContractionRight",
+        &["((Gamma) (Delta A A))"],
+        "((Gamma) (Delta A))",
+        (0, 1),
+        true,
+    ),
+    _structural(
+        "ExchangeLeft",
+        &["((Gamma A B Pi) (Delta))"],
+        "((Gamma B A Pi) (Delta))",
+        (0, 0),
+        true,
+    ),
+    _structural(
+        "ExchangeRight",
+        &["((Gamma) (Delta A B Sigma))"],
+        "((Gamma) (Delta B A Sigma))",
+        (0, 0),
+        true,
+    ),
+    _rule(
+        "AndLeft1",
+        &["((A Gamma) (Delta))"],
+        "(((^ A B) Gamma) (Delta))",
+        (1, 0),
+        false,
+    ),
+    _rule(
+        "AndLeft2",
+        &["((B Gamma) (Delta))"],
+        "(((^ A B) Gamma) (Delta))",
+        (1, 0),
+        false,
+    ),
+    _rule(
+        "AndRight",
+        &["((Gamma) (Delta A))", "((Gamma) (Delta B))"],
+        "((Gamma) (Delta (^ A B)))",
+        (0, 1),
+        true,
+    ),
+    _rule(
+        "OrLeft",
+        &["((A Gamma) (Delta))", "((B Gamma) (Delta))"],
+        "(((v A B) Gamma) (Delta))",
+        (1, 0),
+        true,
+    ),
+    _rule(
+        "OrRight1",
+        &["((Gamma) (Delta A))"],
+        "((Gamma) (Delta (v A B)))",
+        (0, 1),
+        false,
+    ),
+    _rule(
+        "OrRight2",
+        &["((Gamma) (Delta B))"],
+        "((Gamma) (Delta (v A B)))",
+        (0, 1),
+        false,
+    ),
+    // The contexts of the premises are split between them, so the rule is
+    // not invertible.
+    _rule(
+        "ImpliesLeft",
+        &["((Gamma) (Delta A))", "((B Pi) (Sigma))"],
+        "(((> A B) Gamma Pi) (Delta Sigma))",
+        (1, 0),
+        false,
+    ),
+    _rule(
+        "ImpliesRight",
+        &["((A Gamma) (Delta B))"],
+        "((Gamma) (Delta (> A B)))",
+        (0, 1),
+        true,
+    ),
+    _rule(
+        "IffLeft",
+        &["((A B Gamma) (Delta))", "((Gamma) (Delta A B))"],
+        "(((<> A B) Gamma) (Delta))",
+        (1, 0),
+        true,
+    ),
+    _rule(
+        "IffRight",
+        &["((A Gamma) (Delta B))", "((B Gamma) (Delta A))"],
+        "((Gamma) (Delta (<> A B)))",
+        (0, 1),
+        true,
+    ),
+    _rule(
+        "NotLeft",
+        &["((Gamma) (Delta A))"],
+        "(((~ A) Gamma) (Delta))",
+        (1, 0),
+        true,
+    ),
+    _rule(
+        "NotRight",
+        &["((A Gamma) (Delta))"],
+        "((Gamma) (Delta (~ A)))",
+        (0, 1),
+        true,
+    ),
+    _with(
+        _rule(
+            "ForallLeft",
+            &["(((F t) Gamma) (Delta))"],
+            "(((V x (F x)) Gamma) (Delta))",
+            (1, 0),
+            false,
+        ),
+        SideCondition::Substitutible,
+    ),
+    _with(
+        _rule(
+            "ForallRight",
+            &["((Gamma) (Delta (F y)))"],
+            "((Gamma) (Delta (V x (F x))))",
+            (0, 1),
+            true,
+        ),
+        SideCondition::Eigenvariable,
+    ),
+    _with(
+        _rule(
+            "ExistsLeft",
+            &["(((F y) Gamma) (Delta))"],
+            "(((E x (F x)) Gamma) (Delta))",
+            (1, 0),
+            true,
+        ),
+        SideCondition::Eigenvariable,
+    ),
+    _with(
+        _rule(
+            "ExistsRight",
+            &["((Gamma) (Delta (F t)))"],
+            "((Gamma) (Delta (E x (F x))))",
+            (0, 1),
+            false,
+        ),
+        SideCondition::Substitutible,
+    ),
+    _with(
+        _rule(
+            "EqualityLeft",
+            &["(((F s) Gamma) (Delta))"],
+            "(((= s t) (F t) Gamma) (Delta))",
+            (2, 0),
+            false,
+        ),
+        SideCondition::Replacement,
+    ),
+    _with(
+        _rule(
+            "EqualityRight",
+            &["((Gamma) (Delta (F s)))"],
+            "(((= s t) Gamma) (Delta (F t)))",
+            (1, 1),
+            false,
+        ),
+        SideCondition::Replacement,
+    ),
+    _rule(
+        "Cut",
+        &["((Gamma) (Delta A))", "((A Pi) (Sigma))"],
+        "((Gamma Pi) (Delta Sigma))",
+        (0, 0),
+        false,
+    ),
+    RuleSpec {
+        name: "TheoryRule",
+        premises: None,
+        principals: (0, 0),
+        structural: false,
+        invertible: false,
+        condition: SideCondition::Theory,
+        schema: None,
+    },
+    RuleSpec {
+        name: "TheoryAxiom",
+        premises: Some(0),
+        principals: (0, 1),
+        structural: false,
+        invertible: true,
+        condition: SideCondition::Theory,
+        schema: None,
+    },
+];
+
+/// The specification of the rule named `name`, see `LK::rule_name`.
+pub fn rule_spec(name: &str) -> Option<&'static RuleSpec> {
+    RULES.iter().find(|spec| spec.name == name)
+}
+
+impl LK {
+    /// The specification of the rule at the root.
+    pub fn spec(&self) -> &'static RuleSpec {
+        rule_spec(self.rule_name()).expect("every rule has a specification")
+    }
+}
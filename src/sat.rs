@@ -0,0 +1,307 @@
+use crate::cnf::*;
+use crate::prover::*;
+use std::collections::HashMap;
+
+/// Decides the satisfiability of propositional clauses given as in DIMACS:
+/// the literals of variable `n > 0` are `n` and `-n`.
+pub trait SatSolver {
+    /// A model of `clauses`, as the literal true in it of each variable up to
+    /// the largest one, or `None` if they are unsatisfiable.
+    fn solve(&mut self, clauses: &[Vec<i32>]) -> Option<Vec<i32>>;
+
+    /// Same as `solve`, for clauses over atoms. The model gives the truth
+    /// value of every atom of `clauses`.
+    fn solve_clauses(&mut self, clauses: &[Clause]) -> Option<Valuation> {
+        let mut atoms = vec![];
+        let mut numbers = HashMap::new();
+        let encoded = clauses
+            .iter()
+            .map(|clause| {
+                clause
+                    .iter()
+                    .map(|literal| {
+                        let n = *numbers.entry(&literal.atom).or_insert_with(|| {
+                            atoms.push(literal.atom.clone());
+                            atoms.len() as i32
+                        });
+                        if literal.positive {
+                            n
+                        } else {
+                            -n
+                        }
+                    })
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+        let model = self.solve(&encoded)?;
+        Some(
+            atoms
+                .into_iter()
+                .zip(model)
+                .map(|(a, l)| (a, l > 0))
+                .collect(),
+        )
+    }
+}
+
+/// Conflict-driven clause learning: unit propagation with two watched
+/// literals, first-UIP learning, backjumping, activity-based decisions and
+/// phase saving.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CdclSolver {
+    /// Conflicts met by the last call to `solve`, each giving a learned
+    /// clause.
+    pub conflicts: usize,
+    /// Decisions made by the last call to `solve`.
+    pub decisions: usize,
+}
+
+fn _var(literal: i32) -> usize {
+    literal.unsigned_abs() as usize
+}
+
+// The index of the watch list of `literal`.
+fn _watch(literal: i32) -> usize {
+    2 * _var(literal) + (literal < 0) as usize
+}
+
+struct State {
+    clauses: Vec<Vec<i32>>,
+    // Clauses whose first or second literal is the literal, by `_watch`.
+    watches: Vec<Vec<usize>>,
+    values: Vec<Option<bool>>,
+    levels: Vec<usize>,
+    reasons: Vec<Option<usize>>,
+    trail: Vec<i32>,
+    // Length of the trail before each decision.
+    decisions: Vec<usize>,
+    propagated: usize,
+    activity: Vec<f64>,
+    bump: f64,
+    phases: Vec<bool>,
+}
+
+impl State {
+    fn _value(&self, literal: i32) -> Option<bool> {
+        self.values[_var(literal)].map(|v| v == (literal > 0))
+    }
+
+    fn _assign(&mut self, literal: i32, reason: Option<usize>) {
+        let var = _var(literal);
+        self.values[var] = Some(literal > 0);
+        self.levels[var] = self.decisions.len();
+        self.reasons[var] = reason;
+        self.trail.push(literal);
+    }
+
+    fn _add(&mut self, clause: Vec<i32>) -> usize {
+        let index = self.clauses.len();
+        self.watches[_watch(clause[0])].push(index);
+        self.watches[_watch(clause[1])].push(index);
+        self.clauses.push(clause);
+        index
+    }
+
+    // Propagates the assignments of the trail, returning a clause all of
+    // whose literals are false if there is one.
+    fn _propagate(&mut self) -> Option<usize> {
+        while self.propagated < self.trail.len() {
+            let falsified = -self.trail[self.propagated];
+            self.propagated += 1;
+            let watching = std::mem::take(&mut self.watches[_watch(falsified)]);
+            let mut kept = vec![];
+            let mut conflict = None;
+            for (i, &c) in watching.iter().enumerate() {
+                if conflict.is_some() {
+                    kept.extend_from_slice(&watching[i..]);
+                    break;
+                }
+                if self.clauses[c][0] == falsified {
+                    self.clauses[c].swap(0, 1);
+                }
+                let first = self.clauses[c][0];
+                if self._value(first) == Some(true) {
+                    kept.push(c);
+                    continue;
+                }
+                let len = self.clauses[c].len();
+                match (2..len).find(|&k| self._value(self.clauses[c][k]) != Some(false)) {
+                    Some(k) => {
+                        self.clauses[c].swap(1, k);
+                        self.watches[_watch(self.clauses[c][1])].push(c);
+                    }
+                    None => {
+                        kept.push(c);
+                        match self._value(first) {
+                            Some(false) => conflict = Some(c),
+                            _ => self._assign(first, Some(c)),
+                        }
+                    }
+                }
+            }
+            self.watches[_watch(falsified)] = kept;
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+        None
+    }
+
+    // The first-UIP clause learned from `conflict`, with the asserting
+    // literal first and one of the highest level after it, and the level to
+    // jump back to.
+    fn _analyze(&mut self, conflict: usize) -> (Vec<i32>, usize) {
+        let level = self.decisions.len();
+        let mut seen = vec![false; self.values.len()];
+        let mut learned = vec![0];
+        let mut pending = 0;
+        let mut reason = conflict;
+        let mut literal = 0;
+        let mut index = self.trail.len();
+        loop {
+            for k in 0..self.clauses[reason].len() {
+                let q = self.clauses[reason][k];
+                let var = _var(q);
+                if q == literal || seen[var] || self.levels[var] == 0 {
+                    continue;
+                }
+                seen[var] = true;
+                self.activity[var] += self.bump;
+                if self.levels[var] == level {
+                    pending += 1;
+                } else {
+                    learned.push(q);
+                }
+            }
+            loop {
+                index -= 1;
+                if seen[_var(self.trail[index])] {
+                    break;
+                }
+            }
+            literal = self.trail[index];
+            pending -= 1;
+            if pending == 0 {
+                break;
+            }
+            reason = self.reasons[_var(literal)].unwrap();
+        }
+        learned[0] = -literal;
+        let mut back = 0;
+        for k in 1..learned.len() {
+            let l = self.levels[_var(learned[k])];
+            if l > back {
+                back = l;
+                learned.swap(1, k);
+            }
+        }
+        self.bump /= 0.95;
+        (learned, back)
+    }
+
+    fn _backtrack(&mut self, level: usize) {
+        if let Some(&start) = self.decisions.get(level) {
+            for literal in self.trail.drain(start..) {
+                let var = _var(literal);
+                self.phases[var] = literal > 0;
+                self.values[var] = None;
+                self.reasons[var] = None;
+            }
+            self.decisions.truncate(level);
+            self.propagated = self.trail.len();
+        }
+    }
+
+    fn _decide(&self) -> Option<i32> {
+        let var = (1..self.values.len())
+            .filter(|&v| self.values[v].is_none())
+            .max_by(|&a, &b| {
+                self.activity[a]
+                    .total_cmp(&self.activity[b])
+                    .then(b.cmp(&a))
+            })?;
+        Some(if self.phases[var] {
+            var as i32
+        } else {
+            -(var as i32)
+        })
+    }
+}
+
+impl SatSolver for CdclSolver {
+    fn solve(&mut self, clauses: &[Vec<i32>]) -> Option<Vec<i32>> {
+        self.conflicts = 0;
+        self.decisions = 0;
+        let vars = clauses
+            .iter()
+            .flatten()
+            .map(|&l| _var(l))
+            .max()
+            .unwrap_or(0);
+        let mut state = State {
+            clauses: vec![],
+            watches: vec![vec![]; 2 * vars + 2],
+            values: vec![None; vars + 1],
+            levels: vec![0; vars + 1],
+            reasons: vec![None; vars + 1],
+            trail: vec![],
+            decisions: vec![],
+            propagated: 0,
+            activity: vec![0.0; vars + 1],
+            bump: 1.0,
+            phases: vec![false; vars + 1],
+        };
+        for clause in clauses {
+            let mut literals = clause.clone();
+            literals.sort_unstable();
+            literals.dedup();
+            if literals.iter().any(|l| literals.contains(&-l)) {
+                continue;
+            }
+            match literals.len() {
+                0 => return None,
+                1 => match state._value(literals[0]) {
+                    Some(false) => return None,
+                    Some(true) => {}
+                    None => state._assign(literals[0], None),
+                },
+                _ => {
+                    state._add(literals);
+                }
+            }
+        }
+        loop {
+            if let Some(conflict) = state._propagate() {
+                self.conflicts += 1;
+                if state.decisions.is_empty() {
+                    return None;
+                }
+                let (learned, back) = state._analyze(conflict);
+                state._backtrack(back);
+                let asserting = learned[0];
+                let reason = match learned.len() {
+                    1 => None,
+                    _ => Some(state._add(learned)),
+                };
+                state._assign(asserting, reason);
+                continue;
+            }
+            match state._decide() {
+                Some(literal) => {
+                    self.decisions += 1;
+                    state.decisions.push(state.trail.len());
+                    state._assign(literal, None);
+                }
+                None => {
+                    let model = (1..=vars)
+                        .map(|v| match state.values[v] {
+                            Some(false) => -(v as i32),
+                            _ => v as i32,
+                        })
+                        .collect();
+                    return Some(model);
+                }
+            }
+        }
+    }
+}
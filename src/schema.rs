@@ -0,0 +1,294 @@
+use crate::fresh::*;
+use crate::language::*;
+use crate::substitution::*;
+use crate::visit::*;
+use std::collections::{HashMap, HashSet};
+
+/// A formula with metavariables, standing for the formulas it has as
+/// instances. Formula metavariables are predicate symbols standing for
+/// formulas of as many parameters, term metavariables are variables standing
+/// for terms. E.g. the induction schema
+/// `φ(0) ∧ ∀x (φ(x) → φ(s(x))) → ∀x φ(x)`, with `φ` of arity 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schema {
+    pub formula: Formula,
+    pub formula_vars: Vec<NonLogicalSymbol>,
+    pub term_vars: Vec<Term>,
+}
+
+/// The formulas and terms the metavariables of a schema stand for. A
+/// formula metavariable stands for a formula of its parameters, which are
+/// replaced by the arguments of each occurrence.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Instantiation {
+    pub formulas: HashMap<String, (Vec<Term>, Formula)>,
+    pub terms: Substitution,
+}
+
+impl Instantiation {
+    pub fn new() -> Instantiation {
+        Instantiation::default()
+    }
+
+    // The formula `name` stands for, with `args` for its parameters.
+    fn _apply(&self, name: &str, args: &[Term]) -> Formula {
+        let (params, body) = &self.formulas[name];
+        let sub = params.iter().cloned().zip(args.iter().cloned());
+        sub.collect::<Substitution>().apply_to_formula(body)
+    }
+
+    // Whether binding `var` would capture a variable of a formula a
+    // metavariable stands for.
+    fn _captures(&self, var: &Term) -> bool {
+        self.formulas
+            .values()
+            .any(|(params, body)| !params.contains(var) && body.get_free_vars().contains(var))
+    }
+}
+
+struct Replacement<'a> {
+    schema: &'a Schema,
+    inst: &'a Instantiation,
+}
+
+impl TermFolder for Replacement<'_> {}
+
+impl FormulaFolder for Replacement<'_> {
+    fn fold_formula(&mut self, fml: &Formula) -> Formula {
+        use Formula::*;
+        match fml {
+            Pred(name, args) if self.schema._is_formula_var(name, args.len()) => {
+                self.inst._apply(name, args)
+            }
+            Forall(var, body) | Exists(var, body) if self.inst._captures(var) => {
+                let mut symbols = SymbolGenerator::new();
+                symbols.avoid(body);
+                self.inst
+                    .formulas
+                    .values()
+                    .for_each(|(_, f)| symbols.avoid(f));
+                let renamed = match var {
+                    Term::Var(name) => symbols.fresh_var(name),
+                    _ => symbols.fresh_var("x"),
+                };
+                let body =
+                    Box::new(self.fold_formula(&body.substitute(var.clone(), renamed.clone())));
+                match fml {
+                    Forall(_, _) => Forall(renamed, body),
+                    _ => Exists(renamed, body),
+                }
+            }
+            _ => fold_subformulas(self, fml),
+        }
+    }
+}
+
+// Second-order matching of a schema against a formula, restricted to
+// occurrences of formula metavariables applied to distinct variables.
+// Other occurrences are matched once their metavariable is bound.
+struct Matcher<'a> {
+    schema: &'a Schema,
+    inst: Instantiation,
+    // Occurrences of unbound metavariables with the formulas they are
+    // matched with and the variables bound there.
+    deferred: Vec<(Formula, Formula, Vec<Term>)>,
+}
+
+impl Matcher<'_> {
+    fn _is_term_var(&self, term: &Term, bound: &[Term]) -> bool {
+        self.schema.term_vars.contains(term) && !bound.contains(term)
+    }
+
+    fn _term(&mut self, pattern: &Term, term: &Term, bound: &[Term]) -> bool {
+        match (pattern, term) {
+            (Term::Var(_), _) if self._is_term_var(pattern, bound) => {
+                match self.inst.terms.get(pattern) {
+                    Some(t) => t == term,
+                    None if term.get_vars().iter().any(|v| bound.contains(v)) => false,
+                    None => {
+                        self.inst.terms.insert(pattern.clone(), term.clone());
+                        true
+                    }
+                }
+            }
+            (Term::Func(f, xs), Term::Func(g, ys)) => {
+                f == g
+                    && xs.len() == ys.len()
+                    && xs.iter().zip(ys).all(|(x, y)| self._term(x, y, bound))
+            }
+            _ => pattern == term,
+        }
+    }
+
+    // The arguments with the term metavariables replaced, or `None` if some
+    // are not bound yet.
+    fn _resolve(&self, args: &[Term], bound: &[Term]) -> Option<Vec<Term>> {
+        let vars = args
+            .iter()
+            .flat_map(|a| a.get_vars())
+            .collect::<HashSet<_>>();
+        if vars
+            .iter()
+            .any(|v| self._is_term_var(v, bound) && self.inst.terms.get(v).is_none())
+        {
+            return None;
+        }
+        let mut sub = self.inst.terms.clone();
+        bound.iter().for_each(|v| sub.insert(v.clone(), v.clone()));
+        Some(args.iter().map(|a| sub.apply_to_term(a)).collect())
+    }
+
+    fn _metavar(&mut self, pattern: &Formula, fml: &Formula, bound: &[Term]) -> bool {
+        let (name, args) = match pattern {
+            Formula::Pred(name, args) => (name, args),
+            _ => unreachable!(),
+        };
+        let args = self._resolve(args, bound);
+        match (self.inst.formulas.contains_key(name), args) {
+            (true, Some(args)) => return self.inst._apply(name, &args) == *fml,
+            (false, Some(args))
+                if args.iter().all(|a| matches!(a, Term::Var(_)))
+                    && args.iter().collect::<HashSet<_>>().len() == args.len()
+                    && fml
+                        .get_free_vars()
+                        .iter()
+                        .all(|v| !bound.contains(v) || args.contains(v)) =>
+            {
+                self.inst.formulas.insert(name.clone(), (args, fml.clone()));
+            }
+            _ => self
+                .deferred
+                .push((pattern.clone(), fml.clone(), bound.to_vec())),
+        }
+        true
+    }
+
+    fn _formula(&mut self, pattern: &Formula, fml: &Formula, bound: &mut Vec<Term>) -> bool {
+        use Formula::*;
+        match (pattern, fml) {
+            (Pred(name, args), _) if self.schema._is_formula_var(name, args.len()) => {
+                self._metavar(pattern, fml, bound)
+            }
+            (Pred(p, xs), Pred(q, ys)) => {
+                p == q
+                    && xs.len() == ys.len()
+                    && xs.iter().zip(ys).all(|(x, y)| self._term(x, y, bound))
+            }
+            (Equal(a, b), Equal(c, d)) => self._term(a, c, bound) && self._term(b, d, bound),
+            (Not(f), Not(g)) => self._formula(f, g, bound),
+            (And(a, b), And(c, d))
+            | (Or(a, b), Or(c, d))
+            | (Implies(a, b), Implies(c, d))
+            | (Iff(a, b), Iff(c, d)) => self._formula(a, c, bound) && self._formula(b, d, bound),
+            (Forall(x, f), Forall(y, g)) | (Exists(x, f), Exists(y, g)) => {
+                // Renames the bound variable of the pattern to that of the
+                // formula.
+                let f = if x == y {
+                    (**f).clone()
+                } else if f.get_free_vars().contains(y) {
+                    return false;
+                } else {
+                    f.substitute(x.clone(), y.clone())
+                };
+                bound.push(y.clone());
+                let matched = self._formula(&f, g, bound);
+                bound.pop();
+                matched
+            }
+            _ => pattern == fml,
+        }
+    }
+}
+
+impl Schema {
+    pub fn new(
+        formula: Formula,
+        formula_vars: Vec<NonLogicalSymbol>,
+        term_vars: Vec<Term>,
+    ) -> Schema {
+        Schema {
+            formula,
+            formula_vars,
+            term_vars,
+        }
+    }
+
+    fn _is_formula_var(&self, name: &str, arity: usize) -> bool {
+        self.formula_vars
+            .iter()
+            .any(|v| v.name == name && v.arity as usize == arity)
+    }
+
+    /// The instance of the schema by `inst`, or `None` if a metavariable
+    /// of the schema is not bound, or a formula metavariable is bound with
+    /// the wrong number of parameters. Bound variables of the schema that
+    /// would capture variables of the formulas and terms are renamed apart.
+    pub fn instantiate(&self, inst: &Instantiation) -> Option<Formula> {
+        let preds = self.formula.get_preds();
+        let free = self.formula.get_free_vars();
+        for var in &self.formula_vars {
+            match inst.formulas.get(&var.name) {
+                Some((params, _)) if params.len() == var.arity as usize => {}
+                None if !preds.contains(var) => {}
+                _ => return None,
+            }
+        }
+        let term_vars = self.term_vars.iter().cloned().collect::<HashSet<_>>();
+        if free
+            .iter()
+            .any(|v| term_vars.contains(v) && inst.terms.get(v).is_none())
+        {
+            return None;
+        }
+        let fml = inst
+            .terms
+            .restrict(&term_vars)
+            .apply_to_formula(&self.formula);
+        Some(Replacement { schema: self, inst }.fold_formula(&fml))
+    }
+
+    /// The instantiation making `fml` an instance of the schema, if any is
+    /// found. Occurrences of a formula metavariable are matched as a
+    /// function of their arguments when these are distinct variables, as
+    /// in `∀x φ(x)`; the others only have to agree with such a match.
+    pub fn matches(&self, fml: &Formula) -> Option<Instantiation> {
+        let mut matcher = Matcher {
+            schema: self,
+            inst: Instantiation::new(),
+            deferred: vec![],
+        };
+        if !matcher._formula(&self.formula, fml, &mut vec![]) {
+            return None;
+        }
+        loop {
+            let deferred = std::mem::take(&mut matcher.deferred);
+            if deferred.is_empty() {
+                return Some(matcher.inst);
+            }
+            let count = deferred.len();
+            for (pattern, fml, mut bound) in deferred {
+                if !matcher._formula(&pattern, &fml, &mut bound) {
+                    return None;
+                }
+            }
+            if matcher.deferred.len() == count {
+                return None;
+            }
+        }
+    }
+
+    /// For a schema `A → B`, the instance whose conclusion `B` is `goal`,
+    /// if one is found by matching `B` alone.
+    pub fn instance_concluding(&self, goal: &Formula) -> Option<Formula> {
+        let conclusion = match &self.formula {
+            Formula::Implies(_, conclusion) => conclusion,
+            _ => return None,
+        };
+        let inst = Schema {
+            formula: (**conclusion).clone(),
+            ..self.clone()
+        }
+        .matches(goal)?;
+        self.instantiate(&inst)
+    }
+}
@@ -0,0 +1,164 @@
+use crate::language::*;
+use crate::proof::*;
+use std::collections::HashMap;
+
+/// Restriction on the formulas a context may stand for.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Constraint {
+    NonEmpty,
+    MaxLength(usize),
+    /// Every formula is a predicate or an equation.
+    Atomic,
+    /// The variable occurs free in none of the formulas, as for the
+    /// eigenvariable of ∀R and ∃L.
+    NotFree(Term),
+}
+
+impl Constraint {
+    pub fn allows(&self, fmls: &[Formula]) -> bool {
+        match self {
+            Constraint::NonEmpty => !fmls.is_empty(),
+            Constraint::MaxLength(n) => fmls.len() <= *n,
+            Constraint::Atomic => fmls
+                .iter()
+                .all(|f| matches!(f, Formula::Pred(_, _) | Formula::Equal(_, _))),
+            Constraint::NotFree(var) => fmls.iter().all(|f| !f.get_free_vars().contains(var)),
+        }
+    }
+}
+
+/// One position of a side of a schematic sequent.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Part {
+    Formula(Formula),
+    /// A named list of formulas such as Γ. Every occurrence of a name stands
+    /// for the same list, which has to satisfy the constraints given here.
+    Context(String, Vec<Constraint>),
+}
+
+/// The lists the contexts of a schematic sequent are bound to.
+pub type Bindings = HashMap<String, Vec<Formula>>;
+
+/// A sequent whose sides mix concrete formulas with contexts, e.g.
+/// `Γ, p ⇒ Δ`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SchematicSequent {
+    pub antecedent: Vec<Part>,
+    pub succedent: Vec<Part>,
+}
+
+fn _match_side(parts: &[Part], fmls: &[Formula], bindings: &Bindings) -> Option<Bindings> {
+    match parts.split_first() {
+        None if fmls.is_empty() => Some(bindings.clone()),
+        None => None,
+        Some((Part::Formula(fml), parts)) => match fmls.split_first() {
+            Some((f, fmls)) if f == fml => _match_side(parts, fmls, bindings),
+            _ => None,
+        },
+        Some((Part::Context(name, constraints), parts)) => {
+            let allowed = |list: &[Formula]| constraints.iter().all(|c| c.allows(list));
+            if let Some(list) = bindings.get(name) {
+                return if fmls.starts_with(list) && allowed(list) {
+                    _match_side(parts, &fmls[list.len()..], bindings)
+                } else {
+                    None
+                };
+            }
+            (0..fmls.len() + 1).find_map(|k| {
+                if !allowed(&fmls[..k]) {
+                    return None;
+                }
+                let mut bindings = bindings.clone();
+                bindings.insert(name.clone(), fmls[..k].to_vec());
+                _match_side(parts, &fmls[k..], &bindings)
+            })
+        }
+    }
+}
+
+fn _instantiate_side(parts: &[Part], bindings: &Bindings) -> Option<Vec<Formula>> {
+    let mut fmls = vec![];
+    for part in parts {
+        match part {
+            Part::Formula(fml) => fmls.push(fml.clone()),
+            Part::Context(name, _) => fmls.extend(bindings.get(name)?.iter().cloned()),
+        }
+    }
+    Some(fmls)
+}
+
+impl SchematicSequent {
+    /// Binds the contexts so that the schema becomes `sequent`, keeping the
+    /// bindings already in `bindings`. Splits are tried leftmost-shortest
+    /// first, so the first solution found is returned.
+    pub fn match_with(&self, sequent: &Sequent, bindings: &Bindings) -> Option<Bindings> {
+        let bindings = _match_side(&self.antecedent, &sequent.antecedent, bindings)?;
+        _match_side(&self.succedent, &sequent.succedent, &bindings)
+    }
+
+    pub fn matches(&self, sequent: &Sequent) -> Option<Bindings> {
+        self.match_with(sequent, &Bindings::new())
+    }
+
+    /// The sequent obtained by replacing each context by its binding, or
+    /// `None` if some context is unbound.
+    pub fn instantiate(&self, bindings: &Bindings) -> Option<Sequent> {
+        Some(Sequent {
+            antecedent: _instantiate_side(&self.antecedent, bindings)?,
+            succedent: _instantiate_side(&self.succedent, bindings)?,
+        })
+    }
+}
+
+/// A rule stated over schematic sequents, e.g. a derived or admissible rule
+/// claimed to hold for every choice of its contexts.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SchematicRule {
+    pub premises: Vec<SchematicSequent>,
+    pub conclusion: SchematicSequent,
+}
+
+fn _derives(proof: &LK, premises: &[Sequent]) -> bool {
+    if let LK::Axiom(s) = proof {
+        if premises.contains(s) {
+            return true;
+        }
+    }
+    proof.is_valid_inference() && proof.premises().iter().all(|p| _derives(p, premises))
+}
+
+impl SchematicRule {
+    /// Bindings under which the inference from `premises` to `conclusion` is
+    /// an instance of the rule.
+    pub fn instance(&self, premises: &[Sequent], conclusion: &Sequent) -> Option<Bindings> {
+        if premises.len() != self.premises.len() {
+            return None;
+        }
+        let mut bindings = self.conclusion.matches(conclusion)?;
+        for (schema, premise) in self.premises.iter().zip(premises) {
+            bindings = schema.match_with(premise, &bindings)?;
+        }
+        Some(bindings)
+    }
+
+    /// Whether `proof` derives an instance of the rule: its end sequent
+    /// matches the conclusion and each leaf is either a valid axiom or, given
+    /// as `LK::Axiom`, the corresponding instance of a premise. The instance
+    /// is fixed by the end sequent, so the check fails for rules with
+    /// contexts occurring only in premises.
+    pub fn is_derived_by(&self, proof: &LK) -> bool {
+        let bindings = match self.conclusion.matches(proof.last()) {
+            Some(bindings) => bindings,
+            None => return false,
+        };
+        match self
+            .premises
+            .iter()
+            .map(|p| p.instantiate(&bindings))
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(premises) => _derives(proof, &premises),
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,358 @@
+use crate::check::*;
+use crate::hole::*;
+use crate::language::*;
+use crate::limits::*;
+use crate::proof::*;
+use std::fmt;
+
+/// An error in a proof script, see `LK::from_script`. Lines are numbered
+/// from 1.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptError {
+    /// The line is not a rule name followed by a sequent, or does not fit
+    /// in the indentation of the proof.
+    Syntax(usize, &'static str),
+    /// The inference concluding at the line is invalid.
+    Invalid(usize, Box<ProofError>),
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScriptError::Syntax(line, reason) => write!(f, "line {}: {}", line, reason),
+            ScriptError::Invalid(line, error) => write!(f, "line {}: {}", line, error),
+        }
+    }
+}
+
+// An inference whose premises are still being read.
+struct Open {
+    line: usize,
+    indent: usize,
+    rule: String,
+    sequent: Sequent,
+    // The indentation of the premises, once the first one is read.
+    premise_indent: Option<usize>,
+    premises: Vec<LK>,
+}
+
+// The immediate subformulas of `fml`, unified with the connective `shape`
+// applied to fresh holes.
+fn _parts(
+    holes: &mut Holes,
+    fml: &Formula,
+    shape: fn(Formula, Formula) -> Formula,
+) -> Option<[Formula; 2]> {
+    let (lhs, rhs) = (holes.formula(), holes.formula());
+    if !holes.unify(fml, &shape(lhs.clone(), rhs.clone())) {
+        return None;
+    }
+    Some([holes.resolve(&lhs), holes.resolve(&rhs)])
+}
+
+// The body of the quantified formula `fml` with a fresh hole for its
+// variable.
+fn _instance(holes: &mut Holes, fml: &Formula) -> Option<Formula> {
+    match holes.resolve(fml) {
+        Formula::Forall(var, body) | Formula::Exists(var, body) => {
+            Some(body.substitute(var, holes.term()))
+        }
+        _ => None,
+    }
+}
+
+// The premises `index` of an inference by `rule` concluding `conclusion`
+// that the rule allows, with fresh holes for what it leaves open. The
+// premise as `written` splits the contexts of binary rules, and `earlier`
+// are the premises before it.
+fn _expected_premises(
+    rule: &str,
+    index: usize,
+    conclusion: &Sequent,
+    written: &Sequent,
+    earlier: &[LK],
+    holes: &mut Holes,
+) -> Option<Vec<Sequent>> {
+    use Formula::*;
+    let (ant, suc) = (&conclusion.antecedent, &conclusion.succedent);
+    let sequent = |antecedent: Vec<Formula>, succedent: Vec<Formula>| Sequent {
+        antecedent,
+        succedent,
+    };
+    let (first, gamma) = match ant.split_first() {
+        Some((first, gamma)) => (Some(first), gamma.to_vec()),
+        None => (None, vec![]),
+    };
+    let (last, delta) = match suc.split_last() {
+        Some((last, delta)) => (Some(last), delta.to_vec()),
+        None => (None, vec![]),
+    };
+    let pick = |[lhs, rhs]: [Formula; 2], second: bool| if second { rhs } else { lhs };
+    // The contexts of the conclusion split after the premise as written,
+    // for the left premise of a binary rule or the right one.
+    let (ant_split, suc_split) = (
+        written.antecedent.len().saturating_sub(index),
+        (written.succedent.len() + index).saturating_sub(1),
+    );
+    let expected = match rule {
+        "WeakeningLeft" => sequent(gamma, suc.clone()),
+        "WeakeningRight" => sequent(ant.clone(), delta),
+        "ContractionLeft" => sequent([&[first?.clone()], &ant[..]].concat(), suc.clone()),
+        "ContractionRight" => sequent(ant.clone(), [&suc[..], &[last?.clone()]].concat()),
+        "ExchangeLeft" | "ExchangeRight" => {
+            let left = rule == "ExchangeLeft";
+            let side = if left { ant } else { suc };
+            return Some(
+                (1..side.len())
+                    .map(|i| {
+                        let mut exchanged = side.clone();
+                        exchanged.swap(i - 1, i);
+                        if left {
+                            sequent(exchanged, suc.clone())
+                        } else {
+                            sequent(ant.clone(), exchanged)
+                        }
+                    })
+                    .collect(),
+            );
+        }
+        "AndLeft1" | "AndLeft2" => {
+            let parts = _parts(holes, first?, |l, r| And(Box::new(l), Box::new(r)))?;
+            sequent(
+                [vec![pick(parts, rule == "AndLeft2")], gamma].concat(),
+                suc.clone(),
+            )
+        }
+        "OrLeft" => {
+            let parts = _parts(holes, first?, |l, r| Or(Box::new(l), Box::new(r)))?;
+            sequent([vec![pick(parts, index == 1)], gamma].concat(), suc.clone())
+        }
+        "AndRight" | "OrRight1" | "OrRight2" => {
+            let parts = if rule == "AndRight" {
+                _parts(holes, last?, |l, r| And(Box::new(l), Box::new(r)))?
+            } else {
+                _parts(holes, last?, |l, r| Or(Box::new(l), Box::new(r)))?
+            };
+            let second = index == 1 || rule == "OrRight2";
+            sequent(ant.clone(), [delta, vec![pick(parts, second)]].concat())
+        }
+        "ImpliesLeft" => {
+            let [lhs, rhs] = _parts(holes, first?, |l, r| Implies(Box::new(l), Box::new(r)))?;
+            if index == 0 {
+                let (gamma, delta) = (gamma.get(..ant_split)?, suc.get(..suc_split)?);
+                sequent(gamma.to_vec(), [delta, &[lhs]].concat())
+            } else {
+                let pi = gamma.get(gamma.len().checked_sub(ant_split)?..)?;
+                let sigma = suc.get(suc.len().checked_sub(suc_split)?..)?;
+                sequent([&[rhs], pi].concat(), sigma.to_vec())
+            }
+        }
+        "ImpliesRight" => {
+            let [lhs, rhs] = _parts(holes, last?, |l, r| Implies(Box::new(l), Box::new(r)))?;
+            sequent(
+                [vec![lhs], ant.clone()].concat(),
+                [delta, vec![rhs]].concat(),
+            )
+        }
+        "IffLeft" => {
+            let [lhs, rhs] = _parts(holes, first?, |l, r| Iff(Box::new(l), Box::new(r)))?;
+            if index == 0 {
+                sequent([vec![lhs, rhs], gamma].concat(), suc.clone())
+            } else {
+                sequent(gamma, [suc.clone(), vec![lhs, rhs]].concat())
+            }
+        }
+        "IffRight" => {
+            let parts = _parts(holes, last?, |l, r| Iff(Box::new(l), Box::new(r)))?;
+            let [lhs, rhs] = if index == 0 {
+                parts
+            } else {
+                [parts[1].clone(), parts[0].clone()]
+            };
+            sequent(
+                [vec![lhs], ant.clone()].concat(),
+                [delta, vec![rhs]].concat(),
+            )
+        }
+        "NotLeft" | "NotRight" => {
+            let body = holes.formula();
+            let principal = if rule == "NotLeft" { first? } else { last? };
+            if !holes.unify(principal, &Not(Box::new(body.clone()))) {
+                return None;
+            }
+            let body = holes.resolve(&body);
+            if rule == "NotLeft" {
+                sequent(gamma, [suc.clone(), vec![body]].concat())
+            } else {
+                sequent([vec![body], ant.clone()].concat(), delta)
+            }
+        }
+        "ForallLeft" | "ExistsLeft" => sequent(
+            [vec![_instance(holes, first?)?], gamma].concat(),
+            suc.clone(),
+        ),
+        "ForallRight" | "ExistsRight" => sequent(
+            ant.clone(),
+            [delta, vec![_instance(holes, last?)?]].concat(),
+        ),
+        "EqualityLeft" => sequent(
+            [vec![holes.formula()], ant.get(2..)?.to_vec()].concat(),
+            suc.clone(),
+        ),
+        "EqualityRight" => sequent(gamma, [delta, vec![holes.formula()]].concat()),
+        "Cut" => {
+            let cut = match earlier.first() {
+                Some(left) => left.last().suc_last().clone(),
+                None => holes.formula(),
+            };
+            if index == 0 {
+                let (gamma, delta) = (ant.get(..ant_split)?, suc.get(..suc_split)?);
+                sequent(gamma.to_vec(), [delta, &[cut]].concat())
+            } else {
+                let pi = ant.get(ant.len().checked_sub(ant_split)?..)?;
+                let sigma = suc.get(suc.len().checked_sub(suc_split)?..)?;
+                sequent([&[cut], pi].concat(), sigma.to_vec())
+            }
+        }
+        _ => return None,
+    };
+    Some(vec![expected])
+}
+
+// Fills holes of the line `sequent` with what the inference `parent` it is
+// a premise of, or the axiom it is, allows.
+fn _elaborate(sequent: &Sequent, rule: &str, parent: Option<&Open>, holes: &mut Holes) {
+    if rule == "Axiom" {
+        match (&sequent.antecedent[..], &sequent.succedent[..]) {
+            ([lhs], [rhs]) => holes.unify(lhs, rhs),
+            ([], [Formula::Equal(lhs, rhs)]) => holes.unify_terms(lhs, rhs),
+            _ => false,
+        };
+    }
+    let parent = match parent {
+        Some(parent) => parent,
+        None => return,
+    };
+    let conclusion = holes.resolve_sequent(&parent.sequent);
+    if !conclusion.has_holes() && !holes.resolve_sequent(sequent).has_holes() {
+        return;
+    }
+    let index = parent.premises.len();
+    let earlier = &parent.premises;
+    if let Some(candidates) =
+        _expected_premises(&parent.rule, index, &conclusion, sequent, earlier, holes)
+    {
+        for candidate in candidates {
+            if holes.unify_sequents(&candidate, sequent) {
+                break;
+            }
+        }
+    }
+}
+
+// Elaborates and checks the inference `open`, now that its premises are read.
+fn _close(open: Open, holes: &Holes) -> Result<LK, ScriptError> {
+    let line = open.line;
+    let sequent = holes.resolve_sequent(&open.sequent);
+    if sequent.has_holes() {
+        return Err(ScriptError::Syntax(line, "holes left unfilled"));
+    }
+    let proof = LK::from_parts(&open.rule, open.premises, sequent).ok_or(ScriptError::Syntax(
+        line,
+        "unknown rule or wrong number of premises",
+    ))?;
+    proof
+        .check_inference()
+        .map_err(|error| ScriptError::Invalid(line, Box::new(error)))?;
+    Ok(proof)
+}
+
+// Closes `open` and adds it to the premises of the inference below it, or
+// makes it the end of the proof.
+fn _attach(
+    open: Open,
+    stack: &mut [Open],
+    root: &mut Option<LK>,
+    holes: &Holes,
+) -> Result<(), ScriptError> {
+    let proof = _close(open, holes)?;
+    match stack.last_mut() {
+        Some(parent) => parent.premises.push(proof),
+        None => *root = Some(proof),
+    }
+    Ok(())
+}
+
+impl LK {
+    /// Reads a proof written one inference per line, as a rule name as by
+    /// `rule_name` followed by the concluded sequent as parsed by `Sequent`.
+    /// The premises of an inference follow it, indented deeper, left to
+    /// right. Blank lines are skipped. Every inference is checked.
+    ///
+    /// Sequents may have holes `_` for terms and formulas, see `HOLE`,
+    /// filled by unification with the premise the inference below allows,
+    /// or for an axiom with its other side. The holes of a line must be
+    /// filled once the lines of its premises are read.
+    ///
+    /// ```text
+    /// ImpliesRight (() ((> p p)))
+    ///   Axiom ((p) (p))
+    /// ```
+    pub fn from_script(script: &str) -> Result<LK, ScriptError> {
+        LK::from_script_with(script, &Limits::default())
+    }
+
+    /// Same as `from_script`, bounding the tokens and nesting of each
+    /// sequent and the number of inferences by `limits`. Never panics, as
+    /// long as the limits are at most those of `Limits::untrusted`.
+    pub fn from_script_with(script: &str, limits: &Limits) -> Result<LK, ScriptError> {
+        let mut nodes = 0;
+        let mut stack: Vec<Open> = vec![];
+        let mut root = None;
+        let mut holes = Holes::new();
+        for (index, text) in script.lines().enumerate() {
+            let line = index + 1;
+            let trimmed = text.trim_start();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let indent = text.len() - trimmed.len();
+            let (rule, sequent) = trimmed.split_at(trimmed.find(' ').unwrap_or(trimmed.len()));
+            nodes += 1;
+            if limits.max_proof_nodes.is_some_and(|max| nodes > max) {
+                return Err(ScriptError::Syntax(line, TOO_MANY_NODES));
+            }
+            let sequent = limits.parse_sequent(sequent).map_err(|e| match e {
+                TOO_MANY_TOKENS | TOO_DEEP => ScriptError::Syntax(line, e),
+                _ => ScriptError::Syntax(line, "expected a rule name and a sequent"),
+            })?;
+            let sequent = holes.number_sequent(&sequent);
+            while stack.last().is_some_and(|open| open.indent >= indent) {
+                let open = stack.pop().unwrap();
+                _attach(open, &mut stack, &mut root, &holes)?;
+            }
+            let premise_indent = stack
+                .last_mut()
+                .map(|parent| *parent.premise_indent.get_or_insert(indent));
+            if premise_indent.is_some_and(|premise_indent| premise_indent != indent) {
+                return Err(ScriptError::Syntax(line, "inconsistent indentation"));
+            }
+            if premise_indent.is_none() && root.is_some() {
+                return Err(ScriptError::Syntax(line, "more than one end sequent"));
+            }
+            _elaborate(&sequent, rule, stack.last(), &mut holes);
+            stack.push(Open {
+                line,
+                indent,
+                rule: rule.to_string(),
+                sequent,
+                premise_indent: None,
+                premises: vec![],
+            });
+        }
+        while let Some(open) = stack.pop() {
+            _attach(open, &mut stack, &mut root, &holes)?;
+        }
+        root.ok_or(ScriptError::Syntax(0, "empty script"))
+    }
+}
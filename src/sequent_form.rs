@@ -0,0 +1,60 @@
+use crate::language::*;
+use crate::proof::*;
+
+// Splits `sequent` on conjunctions in the antecedent and on conjunctions,
+// disjunctions and implications in the succedent, dropping `⊤` from the
+// antecedent and `⊥` from the succedent.
+fn _split(mut sequent: Sequent) -> Vec<Sequent> {
+    use Formula::*;
+    if let Some(i) = (sequent.antecedent.iter()).position(|f| matches!(f, And(_, _) | Top)) {
+        if let And(a, b) = sequent.antecedent.remove(i) {
+            sequent.antecedent.splice(i..i, [*a, *b]);
+        }
+        return _split(sequent);
+    }
+    let splittable = |f: &Formula| matches!(f, And(_, _) | Or(_, _) | Implies(_, _) | Bottom);
+    if let Some(i) = sequent.succedent.iter().position(splittable) {
+        match sequent.succedent.remove(i) {
+            And(a, b) => {
+                let mut other = sequent.clone();
+                sequent.succedent.insert(i, *a);
+                other.succedent.insert(i, *b);
+                return [_split(sequent), _split(other)].concat();
+            }
+            Or(a, b) => {
+                sequent.succedent.splice(i..i, [*a, *b]);
+            }
+            Implies(a, b) => {
+                sequent.antecedent.push(*a);
+                sequent.succedent.insert(i, *b);
+            }
+            _ => {}
+        }
+        return _split(sequent);
+    }
+    vec![sequent]
+}
+
+impl Formula {
+    /// Sequents whose conjunction is equivalent to the formula, obtained by
+    /// splitting its conjunctions and reading implications `A → B` as
+    /// `A ⇒ B` and disjunctions in the succedent as commas, e.g.
+    /// `(p ∧ q → r) ∧ s` gives `p, q ⇒ r` and `⇒ s`. Quantifiers are left
+    /// in place.
+    pub fn to_sequent_normal_form(&self) -> Vec<Sequent> {
+        _split(Sequent {
+            antecedent: vec![],
+            succedent: vec![self.clone()],
+        })
+    }
+
+    /// The conjunction of `Sequent::to_formula` of `sequents`, `⊤` if there
+    /// are none. Inverse to `to_sequent_normal_form` up to equivalence.
+    pub fn from_sequents(sequents: &[Sequent]) -> Formula {
+        sequents
+            .iter()
+            .map(|s| s.to_formula())
+            .reduce(|l, r| and!(l, r))
+            .unwrap_or(Formula::Top)
+    }
+}
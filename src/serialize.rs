@@ -0,0 +1,217 @@
+use crate::proof::*;
+use serde::de::{self, DeserializeOwned, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+
+/// The version of the JSON format written by `to_json`. Terms, formulas and
+/// sequents are written as serde derives them from their definitions: enums
+/// as objects with the variant name as the only key, structs as objects
+/// with their field names as keys. A proof is the list of its inferences
+/// `{"nodes": [{"rule": ..., "premises": [...], "sequent": ...}, ...]}`,
+/// premises before their conclusions and referred to by their position,
+/// the end sequent last, so that reading and writing do not recurse once
+/// per inference. The version changes whenever any of these changes.
+pub const FORMAT_VERSION: u32 = 2;
+
+/// The most JSON arrays and objects nested in one another in a document,
+/// the recursion limit of the JSON parser. It bounds the nesting of the
+/// formulas and terms written and read, whose handling recurses.
+pub const MAX_DEPTH: usize = 127;
+
+/// An error writing a document with `to_json` or reading one with
+/// `from_json`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormatError {
+    /// The document does not follow the format, with the reason given by
+    /// the JSON parser. Documents nested deeper than `MAX_DEPTH` are
+    /// rejected with the reason `recursion limit exceeded`.
+    Syntax(String),
+    /// The document is of a version this build does not read.
+    Version(u32),
+    /// The value nests deeper than `MAX_DEPTH` in JSON.
+    TooDeep,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FormatError::Syntax(reason) => write!(f, "invalid document: {}", reason),
+            FormatError::Version(version) => {
+                write!(
+                    f,
+                    "unsupported format version {}, expected {}",
+                    version, FORMAT_VERSION
+                )
+            }
+            FormatError::TooDeep => write!(f, "nesting deeper than {}", MAX_DEPTH),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct NodeRef<'a> {
+    rule: &'static str,
+    premises: Vec<usize>,
+    sequent: &'a Sequent,
+}
+
+#[derive(Deserialize)]
+struct Node {
+    rule: String,
+    premises: Vec<usize>,
+    sequent: Sequent,
+}
+
+#[derive(Serialize)]
+struct NodesRef<'a> {
+    nodes: Vec<NodeRef<'a>>,
+}
+
+#[derive(Deserialize)]
+struct Nodes {
+    nodes: Vec<Node>,
+}
+
+impl Serialize for LK {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut nodes = vec![];
+        // Positions of the subproofs written so far whose conclusion is
+        // still to be written.
+        let mut positions = vec![];
+        let mut stack = vec![(self, false)];
+        while let Some((proof, expanded)) = stack.pop() {
+            let premises = proof.premises();
+            if expanded {
+                let premises = positions.split_off(positions.len() - premises.len());
+                positions.push(nodes.len());
+                nodes.push(NodeRef {
+                    rule: proof.rule_name(),
+                    premises,
+                    sequent: proof.last(),
+                });
+            } else {
+                stack.push((proof, true));
+                stack.extend(premises.into_iter().rev().map(|premise| (premise, false)));
+            }
+        }
+        NodesRef { nodes }.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for LK {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<LK, D::Error> {
+        let nodes = Nodes::deserialize(deserializer)?.nodes;
+        let mut proofs: Vec<Option<LK>> = vec![];
+        for node in nodes {
+            let mut premises = vec![];
+            for &premise in &node.premises {
+                let proof = proofs
+                    .get_mut(premise)
+                    .ok_or_else(|| de::Error::custom("premise is not an earlier node"))?
+                    .take()
+                    .ok_or_else(|| de::Error::custom("node is the premise of two inferences"))?;
+                premises.push(proof);
+            }
+            let proof = if node.rule == "TheoryRule" {
+                LK::TheoryRule(premises, node.sequent)
+            } else {
+                LK::from_parts(&node.rule, premises, node.sequent)
+                    .ok_or_else(|| de::Error::custom("unknown rule or wrong number of premises"))?
+            };
+            proofs.push(Some(proof));
+        }
+        let proof = proofs
+            .pop()
+            .flatten()
+            .ok_or_else(|| de::Error::custom("no end sequent"))?;
+        if proofs.iter().any(Option::is_some) {
+            return Err(de::Error::custom("node is not part of the proof"));
+        }
+        Ok(proof)
+    }
+}
+
+#[derive(Serialize)]
+struct Document<'a, T> {
+    version: u32,
+    content: &'a T,
+}
+
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+#[derive(Deserialize)]
+struct Owned<T> {
+    content: T,
+}
+
+// Compact JSON that fails once arrays and objects nest deeper than
+// `MAX_DEPTH`, before serialization recurses any further.
+struct DepthLimit {
+    depth: usize,
+}
+
+impl DepthLimit {
+    fn _enter(&mut self) -> io::Result<()> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(io::Error::other("nesting too deep"));
+        }
+        Ok(())
+    }
+}
+
+impl serde_json::ser::Formatter for DepthLimit {
+    fn begin_array<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self._enter()?;
+        writer.write_all(b"[")
+    }
+
+    fn end_array<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.depth -= 1;
+        writer.write_all(b"]")
+    }
+
+    fn begin_object<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self._enter()?;
+        writer.write_all(b"{")
+    }
+
+    fn end_object<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.depth -= 1;
+        writer.write_all(b"}")
+    }
+}
+
+/// `value` as a JSON document `{"version": ..., "content": ...}`. Fails
+/// with `FormatError::TooDeep` if it nests deeper than `MAX_DEPTH`.
+pub fn to_json<T: Serialize>(value: &T) -> Result<String, FormatError> {
+    let document = Document {
+        version: FORMAT_VERSION,
+        content: value,
+    };
+    let mut json = vec![];
+    let mut serializer = serde_json::Serializer::with_formatter(&mut json, DepthLimit { depth: 0 });
+    document
+        .serialize(&mut serializer)
+        .map_err(|_| FormatError::TooDeep)?;
+    Ok(String::from_utf8(json).expect("serde_json writes UTF-8"))
+}
+
+fn _deserialize<T: DeserializeOwned>(s: &str) -> Result<T, FormatError> {
+    serde_json::from_str(s).map_err(|e| FormatError::Syntax(e.to_string()))
+}
+
+/// Reads a document written by `to_json`, checking its version before its
+/// content.
+pub fn from_json<T: DeserializeOwned>(s: &str) -> Result<T, FormatError> {
+    let header = _deserialize::<Header>(s)?;
+    if header.version != FORMAT_VERSION {
+        return Err(FormatError::Version(header.version));
+    }
+    Ok(_deserialize::<Owned<T>>(s)?.content)
+}
@@ -0,0 +1,575 @@
+use crate::check::*;
+use crate::cnf::*;
+use crate::fresh::*;
+use crate::language::*;
+use crate::limits::*;
+use crate::model::*;
+use crate::parser::*;
+use crate::pipeline::*;
+use crate::proof::*;
+use crate::record::json_string;
+use crate::serialize::*;
+use crate::solver::*;
+use crate::tokenizer::*;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+/// Most arrays and objects nested in one another in a request.
+const MAX_JSON_DEPTH: usize = 256;
+
+struct JsonParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    depth: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn _skip_whitespace(&mut self) {
+        while let Some(c) = self.chars.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.chars.next();
+        }
+    }
+
+    fn _expect(&mut self, s: &str) -> Result<(), &'static str> {
+        for c in s.chars() {
+            if self.chars.next() != Some(c) {
+                return Err("Invalid JSON.");
+            }
+        }
+        Ok(())
+    }
+
+    fn _parse_string(&mut self) -> Result<String, &'static str> {
+        self._expect("\"")?;
+        let mut s = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.chars.next() {
+                    Some('n') => s.push('\n'),
+                    Some('r') => s.push('\r'),
+                    Some('t') => s.push('\t'),
+                    Some('b') => s.push('\u{8}'),
+                    Some('f') => s.push('\u{c}'),
+                    Some('u') => {
+                        let hex = (0..4).filter_map(|_| self.chars.next()).collect::<String>();
+                        let code = u32::from_str_radix(&hex, 16).map_err(|_| "Invalid JSON.")?;
+                        s.push(std::char::from_u32(code).ok_or("Invalid JSON.")?);
+                    }
+                    Some(c) => s.push(c),
+                    None => return Err("Invalid JSON."),
+                },
+                Some(c) => s.push(c),
+                None => return Err("Invalid JSON."),
+            }
+        }
+    }
+
+    fn _parse(&mut self) -> Result<Json, &'static str> {
+        if self.depth == MAX_JSON_DEPTH {
+            return Err(TOO_DEEP);
+        }
+        self.depth += 1;
+        let json = self._parse_value();
+        self.depth -= 1;
+        json
+    }
+
+    fn _parse_value(&mut self) -> Result<Json, &'static str> {
+        self._skip_whitespace();
+        match self.chars.peek() {
+            Some('n') => self._expect("null").map(|_| Json::Null),
+            Some('t') => self._expect("true").map(|_| Json::Bool(true)),
+            Some('f') => self._expect("false").map(|_| Json::Bool(false)),
+            Some('"') => self._parse_string().map(Json::String),
+            Some('[') => {
+                self.chars.next();
+                let mut items = vec![];
+                self._skip_whitespace();
+                if self.chars.peek() == Some(&']') {
+                    self.chars.next();
+                    return Ok(Json::Array(items));
+                }
+                loop {
+                    items.push(self._parse()?);
+                    self._skip_whitespace();
+                    match self.chars.next() {
+                        Some(',') => {}
+                        Some(']') => return Ok(Json::Array(items)),
+                        _ => return Err("Invalid JSON."),
+                    }
+                }
+            }
+            Some('{') => {
+                self.chars.next();
+                let mut fields = vec![];
+                self._skip_whitespace();
+                if self.chars.peek() == Some(&'}') {
+                    self.chars.next();
+                    return Ok(Json::Object(fields));
+                }
+                loop {
+                    self._skip_whitespace();
+                    let key = self._parse_string()?;
+                    self._skip_whitespace();
+                    self._expect(":")?;
+                    fields.push((key, self._parse()?));
+                    self._skip_whitespace();
+                    match self.chars.next() {
+                        Some(',') => {}
+                        Some('}') => return Ok(Json::Object(fields)),
+                        _ => return Err("Invalid JSON."),
+                    }
+                }
+            }
+            Some(c) if *c == '-' || c.is_ascii_digit() => {
+                let mut number = String::new();
+                while let Some(&c) = self.chars.peek() {
+                    if !(c.is_ascii_digit() || "+-.eE".contains(c)) {
+                        break;
+                    }
+                    number.push(c);
+                    self.chars.next();
+                }
+                number
+                    .parse()
+                    .map(Json::Number)
+                    .map_err(|_| "Invalid JSON.")
+            }
+            _ => Err("Invalid JSON."),
+        }
+    }
+}
+
+fn _parse_json(s: &str) -> Result<Json, &'static str> {
+    let mut parser = JsonParser {
+        chars: s.chars().peekable(),
+        depth: 0,
+    };
+    let json = parser._parse()?;
+    parser._skip_whitespace();
+    match parser.chars.next() {
+        None => Ok(json),
+        Some(_) => Err("Invalid JSON."),
+    }
+}
+
+struct Request {
+    fields: Vec<(String, Json)>,
+}
+
+impl Request {
+    fn parse(body: &str) -> Result<Request, &'static str> {
+        match _parse_json(body)? {
+            Json::Object(fields) => Ok(Request { fields }),
+            _ => Err("Expected a JSON object."),
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&Json> {
+        self.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    fn input(&self) -> Result<&str, &'static str> {
+        match self.get("input") {
+            Some(Json::String(s)) => Ok(s),
+            _ => Err("Missing string field \"input\"."),
+        }
+    }
+
+    fn number(&self, key: &str, default: u32) -> Result<u32, &'static str> {
+        match self.get(key) {
+            None | Some(Json::Null) => Ok(default),
+            Some(Json::Number(n)) if *n >= 0.0 && n.fract() == 0.0 => Ok(*n as u32),
+            _ => Err("Expected a non-negative integer."),
+        }
+    }
+
+    fn flag(&self, key: &str) -> Result<bool, &'static str> {
+        match self.get(key) {
+            None | Some(Json::Null) => Ok(false),
+            Some(Json::Bool(b)) => Ok(*b),
+            _ => Err("Expected a boolean."),
+        }
+    }
+
+    fn notation(&self) -> Result<Notation, &'static str> {
+        match self.get("notation") {
+            None | Some(Json::Null) => Ok(Notation::Unicode),
+            Some(Json::String(s)) if s == "unicode" => Ok(Notation::Unicode),
+            Some(Json::String(s)) if s == "ascii" => Ok(Notation::Ascii),
+            _ => Err("Expected \"unicode\" or \"ascii\"."),
+        }
+    }
+}
+
+fn _parse_sequent(input: &str) -> Result<Sequent, &'static str> {
+    let limits = Limits::untrusted();
+    let tokens = Tokenizer::with_limits(&limits).try_tokenize(input)?;
+    match Parser::with_limits(&limits).parse(&tokens) {
+        Ok(fml) => Ok(Sequent::from(fml)),
+        Err(TOO_DEEP) => Err(TOO_DEEP),
+        Err(e) => Parser::with_limits(&limits)
+            .parse_sequent(&tokens)
+            .map_err(|_| e),
+    }
+}
+
+fn _json_array<T, F: Fn(&T) -> String>(items: &[T], f: F) -> String {
+    format!("[{}]", items.iter().map(f).collect::<Vec<_>>().join(", "))
+}
+
+fn _model_json(model: &FiniteModel) -> String {
+    let mut vars = model
+        .var_assignment
+        .iter()
+        .map(|(var, value)| format!("[{}, {}]", json_string(&var.to_sexpr()), value))
+        .collect::<Vec<_>>();
+    vars.sort();
+    let mut funcs = model
+        .func_assignment
+        .iter()
+        .map(|(sym, table)| {
+            let mut table = table.iter().collect::<Vec<_>>();
+            table.sort();
+            format!(
+                "{{\"name\": {}, \"arity\": {}, \"table\": {}}}",
+                json_string(&sym.name),
+                sym.arity,
+                _json_array(&table, |(args, value)| format!("[{:?}, {}]", args, value))
+            )
+        })
+        .collect::<Vec<_>>();
+    funcs.sort();
+    let mut preds = model
+        .pred_assignment
+        .iter()
+        .map(|(sym, table)| {
+            let mut table = table.iter().collect::<Vec<_>>();
+            table.sort();
+            format!(
+                "{{\"name\": {}, \"arity\": {}, \"table\": {}}}",
+                json_string(&sym.name),
+                sym.arity,
+                _json_array(&table, |(args, value)| format!("[{:?}, {}]", args, value))
+            )
+        })
+        .collect::<Vec<_>>();
+    preds.sort();
+    format!(
+        "{{\"domain_size\": {}, \"variables\": [{}], \"functions\": [{}], \"predicates\": [{}]}}",
+        model.domain_size,
+        vars.join(", "),
+        funcs.join(", "),
+        preds.join(", ")
+    )
+}
+
+fn _proof_json(proof: &LK, notation: Notation) -> String {
+    format!(
+        "{{\"sequent\": {}, \"height\": {}, \"text\": {}, \"graph\": {}}}",
+        json_string(&proof.last().to_sexpr()),
+        proof.height(),
+        json_string(&proof.to_string_with(notation)),
+        proof.to_graph().to_json()
+    )
+}
+
+/// Largest `max_proof_depth` searched, larger ones are lowered to it.
+pub const MAX_PROOF_DEPTH: u32 = 12;
+
+/// Largest `max_domain_size` searched, larger ones are lowered to it.
+pub const MAX_DOMAIN_SIZE: u32 = 8;
+
+/// How long `/check` and `/prove` search before giving up.
+pub const SEARCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Runs `search` on its own thread, or `None` if it takes `SEARCH_TIMEOUT`,
+// in which case the LK and countermodel searches it makes are cancelled.
+fn _within_timeout<T: Send, F: FnOnce() -> T + Send>(search: F) -> Option<T> {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let (done, finished) = mpsc::channel();
+    thread::scope(|scope| {
+        let searching = scope.spawn(|| {
+            set_cancellation_flag(Some(cancelled.clone()));
+            let result = search();
+            let _ = done.send(());
+            result
+        });
+        let timed_out = finished.recv_timeout(SEARCH_TIMEOUT).is_err();
+        cancelled.store(true, Ordering::Relaxed);
+        let result = searching.join().unwrap();
+        (!timed_out).then_some(result)
+    })
+}
+
+// {"input": goal, "max_proof_depth": 8, "max_domain_size": 8, "use_cut": false}
+fn _check(request: &Request) -> Result<String, &'static str> {
+    let options = SolveOptions {
+        max_proof_depth: request.number("max_proof_depth", 8)?.min(MAX_PROOF_DEPTH),
+        max_domain_size: request.number("max_domain_size", 8)?.min(MAX_DOMAIN_SIZE),
+        use_cut: request.flag("use_cut")?,
+        timeout: Some(SEARCH_TIMEOUT),
+    };
+    let input = request.input()?;
+    _parse_sequent(input)?;
+    Ok(match _within_timeout(|| solve(input, &options)) {
+        None => format!(
+            "{{\"result\": \"unknown\", \"diagnostics\": [\"timed out after {} s\"]}}",
+            SEARCH_TIMEOUT.as_secs()
+        ),
+        Some(Outcome::Proof(_)) => "{\"result\": \"valid\"}".to_string(),
+        Some(Outcome::Countermodel(model)) => format!(
+            "{{\"result\": \"invalid\", \"countermodel\": {}}}",
+            _model_json(&model)
+        ),
+        Some(Outcome::Unknown(diagnostics)) => format!(
+            "{{\"result\": \"unknown\", \"diagnostics\": {}}}",
+            _json_array(&diagnostics, |d| json_string(d))
+        ),
+    })
+}
+
+// {"input": goal, "max_proof_depth": 8, "use_cut": false, "notation": "unicode"}
+fn _prove(request: &Request) -> Result<String, &'static str> {
+    let max_depth = request.number("max_proof_depth", 8)?.min(MAX_PROOF_DEPTH);
+    let use_cut = request.flag("use_cut")?;
+    let notation = request.notation()?;
+    let goal = _parse_sequent(request.input()?)?;
+    let proof = _within_timeout(|| prove_sequent_with_lk(goal, max_depth, use_cut));
+    Ok(match proof {
+        Some(Ok(proof)) => format!(
+            "{{\"proved\": true, \"proof\": {}}}",
+            _proof_json(&proof.structural_normal_form(), notation)
+        ),
+        Some(Err(_)) | None => "{\"proved\": false}".to_string(),
+    })
+}
+
+// {"input": formula, "form": "nnf" | "skolem" | "cnf" | "tseitin"}
+fn _normalize(request: &Request) -> Result<String, &'static str> {
+    let limits = Limits::untrusted();
+    let tokens = Tokenizer::with_limits(&limits).try_tokenize(request.input()?)?;
+    let fml = Parser::with_limits(&limits).parse(&tokens)?;
+    let mut symbols = SymbolGenerator::new();
+    let clauses = |conversion| {
+        let mut symbols = SymbolGenerator::new();
+        let clauses = fml.to_cnf_with(conversion, &mut symbols);
+        _json_array(&clauses, |clause| {
+            let mut literals = clause
+                .iter()
+                .map(|l| json_string(&l.to_formula().to_sexpr()))
+                .collect::<Vec<_>>();
+            literals.sort();
+            format!("[{}]", literals.join(", "))
+        })
+    };
+    match request.get("form") {
+        None | Some(Json::Null) => Ok(format!(
+            "{{\"formula\": {}}}",
+            json_string(&fml.to_nnf().to_sexpr())
+        )),
+        Some(Json::String(form)) => match form.as_str() {
+            "nnf" => Ok(format!(
+                "{{\"formula\": {}}}",
+                json_string(&fml.to_nnf().to_sexpr())
+            )),
+            "skolem" => Ok(format!(
+                "{{\"formula\": {}}}",
+                json_string(&fml.to_nnf().skolemize(&mut symbols).to_sexpr())
+            )),
+            "cnf" => Ok(format!(
+                "{{\"clauses\": {}}}",
+                clauses(CnfConversion::Distributive)
+            )),
+            "tseitin" => Ok(format!(
+                "{{\"clauses\": {}}}",
+                clauses(CnfConversion::Tseitin)
+            )),
+            _ => Err("Unknown normal form."),
+        },
+        _ => Err("Unknown normal form."),
+    }
+}
+
+/// Most candidate terms `/verify` tries for a quantifier inference.
+const MAX_CANDIDATES: usize = 1 << 10;
+
+// {"input": proof document of `serialize::to_json`}
+fn _verify(request: &Request) -> Result<String, &'static str> {
+    let proof: LK = from_json(request.input()?).map_err(|e| match e {
+        FormatError::Version(_) => "Unsupported proof document version.",
+        _ => "Invalid proof document.",
+    })?;
+    let options = CheckOptions {
+        max_candidates: Some(MAX_CANDIDATES),
+        max_nodes: Limits::untrusted().max_proof_nodes,
+        ..CheckOptions::default()
+    };
+    Ok(match proof.check_with(&options) {
+        Ok(()) => "{\"valid\": true}".to_string(),
+        Err(e) => format!(
+            "{{\"valid\": false, \"path\": {:?}, \"rule\": {}, \"reason\": {}}}",
+            e.path,
+            json_string(e.rule),
+            json_string(e.reason)
+        ),
+    })
+}
+
+/// Answers one request of the service. Returns the HTTP status code and the
+/// JSON response body. Errors are reported as `{"error": message}`. Search
+/// bounds above `MAX_PROOF_DEPTH` and `MAX_DOMAIN_SIZE` are lowered to them.
+///
+/// * `POST /check` tells whether the goal `input`, a formula or a sequent,
+///   is `"valid"`, `"invalid"` (with a countermodel) or `"unknown"`, the
+///   latter also once the search has taken `SEARCH_TIMEOUT`.
+/// * `POST /prove` searches for an LK proof of `input` for at most
+///   `SEARCH_TIMEOUT` and returns it as text and as a proof graph.
+/// * `POST /normalize` converts the formula `input` to the normal `form`
+///   `"nnf"` (default), `"skolem"`, `"cnf"` or `"tseitin"`.
+/// * `POST /verify` checks the proof `input`, a document of
+///   `serialize::to_json`, and tells the `path` from the end sequent to the
+///   first invalid inference and the `reason` it is invalid.
+pub fn handle(method: &str, path: &str, body: &str) -> (u16, String) {
+    let endpoint = match path {
+        "/check" => _check,
+        "/prove" => _prove,
+        "/normalize" => _normalize,
+        "/verify" => _verify,
+        _ => return (404, "{\"error\": \"Not found.\"}".to_string()),
+    };
+    if method != "POST" {
+        return (405, "{\"error\": \"Method not allowed.\"}".to_string());
+    }
+    match Request::parse(body).and_then(|request| endpoint(&request)) {
+        Ok(response) => (200, response),
+        Err(e) => (400, format!("{{\"error\": {}}}", json_string(e))),
+    }
+}
+
+/// Largest request body accepted, larger ones are answered with `413`.
+/// Bounds memory like `Limits::untrusted` bounds parsing: a body of the most
+/// tokens takes well under this.
+pub const MAX_BODY_BYTES: usize = 4 << 20;
+
+/// Largest request line and headers accepted.
+const MAX_HEADER_BYTES: u64 = 16 << 10;
+
+/// How long a connection may stall before it is dropped, so that one idle
+/// client cannot hold up the others.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+struct HttpRequest {
+    method: String,
+    path: String,
+    body: Vec<u8>,
+}
+
+// The request read from `stream`, or the status refusing it.
+fn _read_request(stream: &TcpStream) -> io::Result<Result<HttpRequest, u16>> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut head = Read::by_ref(&mut reader).take(MAX_HEADER_BYTES);
+    let mut request_line = String::new();
+    head.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("").to_string();
+    let path = parts.next().unwrap_or("").to_string();
+    let mut content_length = 0;
+    loop {
+        let mut line = String::new();
+        if head.read_line(&mut line)? == 0 {
+            return Ok(Err(400));
+        }
+        if line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = match value.trim().parse::<u64>() {
+                    Ok(length) if length > MAX_BODY_BYTES as u64 => return Ok(Err(413)),
+                    Ok(length) => length as usize,
+                    // Too many digits for a `u64` is too large, too.
+                    Err(_) if value.trim().bytes().all(|b| b.is_ascii_digit()) => {
+                        return Ok(Err(413))
+                    }
+                    Err(_) => return Ok(Err(400)),
+                };
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    Ok(Ok(HttpRequest { method, path, body }))
+}
+
+fn _respond(stream: &mut TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    let (status, response) = match _read_request(stream)? {
+        Ok(request) => handle(
+            &request.method,
+            &request.path,
+            &String::from_utf8_lossy(&request.body),
+        ),
+        Err(413) => (413, "{\"error\": \"Request body too large.\"}".to_string()),
+        Err(status) => (status, "{\"error\": \"Malformed request.\"}".to_string()),
+    };
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Payload Too Large",
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        response.len(),
+        response
+    )?;
+    stream.flush()
+}
+
+/// Serves `handle` over HTTP/1.1 on `addr`, one connection at a time.
+pub fn serve<A: ToSocketAddrs>(addr: A) -> io::Result<()> {
+    serve_on(TcpListener::bind(addr)?)
+}
+
+/// Serves `handle` on the connections of `listener`, one at a time.
+pub fn serve_on(listener: TcpListener) -> io::Result<()> {
+    for stream in listener.incoming() {
+        // A failed accept, e.g. out of file descriptors, is no reason to
+        // stop; pausing keeps a lasting one from flooding the log.
+        let mut stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("{}", e);
+                thread::sleep(Duration::from_millis(100));
+                continue;
+            }
+        };
+        if let Err(e) = _respond(&mut stream) {
+            eprintln!("{}", e);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,393 @@
+use crate::language::*;
+use crate::proof::*;
+use crate::prover::*;
+use crate::tactic::*;
+use std::fmt;
+use std::str::FromStr;
+
+/// A tactic of `ProofState`, named after its method without `apply_`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tactic {
+    Axiom,
+    Congruence,
+    AndLeft1,
+    AndLeft2,
+    AndRight,
+    OrLeft,
+    OrRight1,
+    OrRight2,
+    ImpliesLeft(usize, usize),
+    ImpliesRight,
+    IffLeft,
+    IffRight,
+    NotLeft,
+    NotRight,
+    ForallLeft(Term),
+    ForallRight(Term),
+    ExistsLeft(Term),
+    ExistsRight(Term),
+    WeakenLeft,
+    WeakenRight,
+    ContractLeft,
+    ContractRight,
+    ExchangeLeft(usize),
+    ExchangeRight(usize),
+    Cut(Formula, usize, usize),
+    Defer,
+}
+
+impl Tactic {
+    pub fn apply(&self, state: &mut ProofState) -> Result<(), &'static str> {
+        use Tactic::*;
+        match self {
+            Axiom => state.axiom(),
+            Congruence => state.congruence(),
+            AndLeft1 => state.apply_and_left1(),
+            AndLeft2 => state.apply_and_left2(),
+            AndRight => state.apply_and_right(),
+            OrLeft => state.apply_or_left(),
+            OrRight1 => state.apply_or_right1(),
+            OrRight2 => state.apply_or_right2(),
+            ImpliesLeft(gamma, delta) => state.apply_implies_left(*gamma, *delta),
+            ImpliesRight => state.apply_implies_right(),
+            IffLeft => state.apply_iff_left(),
+            IffRight => state.apply_iff_right(),
+            NotLeft => state.apply_not_left(),
+            NotRight => state.apply_not_right(),
+            ForallLeft(term) => state.apply_forall_left(term.clone()),
+            ForallRight(var) => state.apply_forall_right(var.clone()),
+            ExistsLeft(var) => state.apply_exists_left(var.clone()),
+            ExistsRight(term) => state.apply_exists_right(term.clone()),
+            WeakenLeft => state.weaken_left(),
+            WeakenRight => state.weaken_right(),
+            ContractLeft => state.contract_left(),
+            ContractRight => state.contract_right(),
+            ExchangeLeft(i) => state.exchange_left(*i),
+            ExchangeRight(i) => state.exchange_right(*i),
+            Cut(fml, gamma, delta) => state.cut(fml.clone(), *gamma, *delta),
+            Defer => {
+                state.defer();
+                Ok(())
+            }
+        }
+    }
+
+    fn _name(&self) -> &'static str {
+        use Tactic::*;
+        match self {
+            Axiom => "axiom",
+            Congruence => "congruence",
+            AndLeft1 => "and_left1",
+            AndLeft2 => "and_left2",
+            AndRight => "and_right",
+            OrLeft => "or_left",
+            OrRight1 => "or_right1",
+            OrRight2 => "or_right2",
+            ImpliesLeft(_, _) => "implies_left",
+            ImpliesRight => "implies_right",
+            IffLeft => "iff_left",
+            IffRight => "iff_right",
+            NotLeft => "not_left",
+            NotRight => "not_right",
+            ForallLeft(_) => "forall_left",
+            ForallRight(_) => "forall_right",
+            ExistsLeft(_) => "exists_left",
+            ExistsRight(_) => "exists_right",
+            WeakenLeft => "weaken_left",
+            WeakenRight => "weaken_right",
+            ContractLeft => "contract_left",
+            ContractRight => "contract_right",
+            ExchangeLeft(_) => "exchange_left",
+            ExchangeRight(_) => "exchange_right",
+            Cut(_, _, _) => "cut",
+            Defer => "defer",
+        }
+    }
+}
+
+impl fmt::Display for Tactic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Tactic::*;
+        write!(f, "{}", self._name())?;
+        match self {
+            ImpliesLeft(gamma, delta) => write!(f, " {} {}", gamma, delta),
+            ForallLeft(term) | ForallRight(term) | ExistsLeft(term) | ExistsRight(term) => {
+                write!(f, " {}", term.to_sexpr())
+            }
+            ExchangeLeft(i) | ExchangeRight(i) => write!(f, " {}", i),
+            Cut(fml, gamma, delta) => write!(f, " {} {} {}", gamma, delta, fml.to_sexpr()),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn _index(s: Option<&str>) -> Result<usize, &'static str> {
+    s.and_then(|s| s.parse().ok()).ok_or("Expected an index.")
+}
+
+impl FromStr for Tactic {
+    type Err = &'static str;
+
+    /// Reads a tactic as written by `Display`: its name followed by the
+    /// indices and then the term or formula it takes.
+    fn from_str(s: &str) -> Result<Tactic, &'static str> {
+        use Tactic::*;
+        let s = s.trim();
+        let (name, rest) = s.split_at(s.find(' ').unwrap_or(s.len()));
+        let rest = rest.trim();
+        let mut words = rest.splitn(3, ' ');
+        let tactic = match name {
+            "implies_left" => ImpliesLeft(_index(words.next())?, _index(words.next())?),
+            "forall_left" => ForallLeft(rest.parse()?),
+            "forall_right" => ForallRight(rest.parse()?),
+            "exists_left" => ExistsLeft(rest.parse()?),
+            "exists_right" => ExistsRight(rest.parse()?),
+            "exchange_left" => ExchangeLeft(_index(words.next())?),
+            "exchange_right" => ExchangeRight(_index(words.next())?),
+            "cut" => {
+                let (gamma, delta) = (_index(words.next())?, _index(words.next())?);
+                Cut(
+                    words.next().ok_or("Expected a formula.")?.parse()?,
+                    gamma,
+                    delta,
+                )
+            }
+            _ => {
+                let tactic = [
+                    Axiom,
+                    Congruence,
+                    AndLeft1,
+                    AndLeft2,
+                    AndRight,
+                    OrLeft,
+                    OrRight1,
+                    OrRight2,
+                    ImpliesRight,
+                    IffLeft,
+                    IffRight,
+                    NotLeft,
+                    NotRight,
+                    WeakenLeft,
+                    WeakenRight,
+                    ContractLeft,
+                    ContractRight,
+                    Defer,
+                ]
+                .iter()
+                .find(|t| t._name() == name)
+                .cloned()
+                .ok_or("Unknown tactic.")?;
+                if !rest.is_empty() {
+                    return Err("The tactic takes no arguments.");
+                }
+                return Ok(tactic);
+            }
+        };
+        match (&tactic, words.next()) {
+            (ImpliesLeft(_, _) | ExchangeLeft(_) | ExchangeRight(_), Some(_)) => {
+                Err("Too many arguments.")
+            }
+            _ => Ok(tactic),
+        }
+    }
+}
+
+/// A step of a `Session`, written as one line of its transcript.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    /// `goal ((A) (B))` starts a proof of the sequent, dropping the one
+    /// under way if any.
+    Goal(Sequent),
+    /// `tactic and_right` applies a tactic to the current goal.
+    Tactic(Tactic),
+    /// `prove 8 10000` closes the current goal with a `FirstOrderProver`
+    /// with these `max_instantiations` and `max_steps`, `none` for no
+    /// bound. The search is deterministic, so the budget decides what it
+    /// finds.
+    Prove {
+        max_instantiations: u32,
+        max_steps: Option<usize>,
+    },
+    /// `qed name` makes the finished proof a theorem with the name.
+    Qed(String),
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operation::Goal(sequent) => write!(f, "goal {}", sequent.to_sexpr()),
+            Operation::Tactic(tactic) => write!(f, "tactic {}", tactic),
+            Operation::Prove {
+                max_instantiations,
+                max_steps,
+            } => match max_steps {
+                Some(steps) => write!(f, "prove {} {}", max_instantiations, steps),
+                None => write!(f, "prove {} none", max_instantiations),
+            },
+            Operation::Qed(name) => write!(f, "qed {}", name),
+        }
+    }
+}
+
+impl FromStr for Operation {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Operation, &'static str> {
+        let s = s.trim();
+        let (keyword, rest) = s.split_at(s.find(' ').unwrap_or(s.len()));
+        let rest = rest.trim();
+        match keyword {
+            "goal" => Ok(Operation::Goal(rest.parse()?)),
+            "tactic" => Ok(Operation::Tactic(rest.parse()?)),
+            "prove" => match rest.split(' ').collect::<Vec<_>>()[..] {
+                [instantiations, steps] => Ok(Operation::Prove {
+                    max_instantiations: instantiations
+                        .parse()
+                        .map_err(|_| "Expected a number of instantiations.")?,
+                    max_steps: match steps {
+                        "none" => None,
+                        steps => Some(_index(Some(steps))?),
+                    },
+                }),
+                _ => Err("Expected the budget of the prover."),
+            },
+            "qed" => Ok(Operation::Qed(rest.to_string())),
+            _ => Err("Unknown operation."),
+        }
+    }
+}
+
+/// A proof checked and named by a `Session`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theorem {
+    pub name: String,
+    pub proof: LK,
+}
+
+impl Theorem {
+    pub fn sequent(&self) -> &Sequent {
+        self.proof.last()
+    }
+}
+
+/// A development of theorems proved one after another with tactics and
+/// the prover, recording the operations that succeed into a transcript.
+/// Replaying the transcript gives back the same theorems with the same
+/// proofs. Prover calls with a timeout are refused, as what they find
+/// would depend on the speed of the machine.
+#[derive(Debug, Clone, Default)]
+pub struct Session {
+    operations: Vec<Operation>,
+    state: Option<ProofState>,
+    theorems: Vec<Theorem>,
+}
+
+impl Session {
+    pub fn new() -> Session {
+        Session::default()
+    }
+
+    /// Performs `operation`, recording it if it succeeds.
+    pub fn run(&mut self, operation: Operation) -> Result<(), &'static str> {
+        match &operation {
+            Operation::Goal(sequent) => self.state = Some(ProofState::new(sequent.clone())),
+            Operation::Tactic(tactic) => tactic.apply(self._state()?)?,
+            Operation::Prove {
+                max_instantiations,
+                max_steps,
+            } => {
+                let prover = FirstOrderProver {
+                    max_instantiations: *max_instantiations,
+                    max_steps: *max_steps,
+                    timeout: None,
+                };
+                self._state()?.prove(&prover)?;
+            }
+            Operation::Qed(name) => {
+                if name.is_empty() || name.contains(char::is_whitespace) {
+                    return Err("Theorem names are single words.");
+                }
+                if self.theorem(name).is_some() {
+                    return Err("A theorem has the name already.");
+                }
+                let proof = self._state()?.qed()?;
+                self.state = None;
+                self.theorems.push(Theorem {
+                    name: name.clone(),
+                    proof,
+                });
+            }
+        }
+        self.operations.push(operation);
+        Ok(())
+    }
+
+    fn _state(&mut self) -> Result<&mut ProofState, &'static str> {
+        self.state.as_mut().ok_or("No proof is under way.")
+    }
+
+    /// Starts a proof of the sequent written in `s`.
+    pub fn goal(&mut self, s: &str) -> Result<(), &'static str> {
+        self.run(Operation::Goal(s.parse()?))
+    }
+
+    pub fn apply(&mut self, tactic: Tactic) -> Result<(), &'static str> {
+        self.run(Operation::Tactic(tactic))
+    }
+
+    /// Closes the current goal with `prover`, which must have no timeout.
+    pub fn prove(&mut self, prover: &FirstOrderProver) -> Result<(), &'static str> {
+        if prover.timeout.is_some() {
+            return Err("Prover calls with a timeout cannot be replayed.");
+        }
+        self.run(Operation::Prove {
+            max_instantiations: prover.max_instantiations,
+            max_steps: prover.max_steps,
+        })
+    }
+
+    /// Names the finished proof, returning the theorem.
+    pub fn qed(&mut self, name: &str) -> Result<&Theorem, &'static str> {
+        self.run(Operation::Qed(name.to_string()))?;
+        Ok(self.theorems.last().unwrap())
+    }
+
+    /// The proof under way, if any.
+    pub fn state(&self) -> Option<&ProofState> {
+        self.state.as_ref()
+    }
+
+    pub fn theorems(&self) -> &[Theorem] {
+        &self.theorems
+    }
+
+    pub fn theorem(&self, name: &str) -> Option<&Theorem> {
+        self.theorems.iter().find(|t| t.name == name)
+    }
+
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    /// The operations performed, one per line.
+    pub fn transcript(&self) -> String {
+        self.operations
+            .iter()
+            .map(|operation| format!("{}\n", operation))
+            .collect()
+    }
+
+    /// Performs the operations of `transcript` in a new session, failing
+    /// with the line number and the reason at the first that does not
+    /// parse or fails. Blank lines are skipped.
+    pub fn replay(transcript: &str) -> Result<Session, (usize, &'static str)> {
+        let mut session = Session::new();
+        for (index, line) in transcript.lines().enumerate() {
+            if !line.trim().is_empty() {
+                let operation = line.parse().map_err(|e| (index + 1, e))?;
+                session.run(operation).map_err(|e| (index + 1, e))?;
+            }
+        }
+        Ok(session)
+    }
+}
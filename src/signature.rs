@@ -0,0 +1,146 @@
+use crate::language::*;
+use crate::proof::*;
+use crate::sort::Sort;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+/// The non-logical symbols occurring in a formula, a sequent or a proof.
+/// Nullary function symbols are kept apart as constants; nullary predicate
+/// symbols stay among the predicates.
+///
+/// Symbols may optionally be given sorts, see `crate::sort`. Symbols without
+/// a declared sort are unsorted.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Signature {
+    pub functions: HashSet<NonLogicalSymbol>,
+    pub constants: HashSet<String>,
+    pub predicates: HashSet<NonLogicalSymbol>,
+    /// Argument sorts and result sort of function symbols and constants.
+    pub function_sorts: HashMap<NonLogicalSymbol, (Vec<Sort>, Sort)>,
+    /// Argument sorts of predicate symbols.
+    pub predicate_sorts: HashMap<NonLogicalSymbol, Vec<Sort>>,
+}
+
+/// A symbol used with more than one arity, with its arities in increasing
+/// order. Function, constant and predicate uses of a name are compared with
+/// each other.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ArityConflict {
+    pub name: String,
+    pub arities: Vec<u32>,
+}
+
+impl fmt::Display for ArityConflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "symbol {} is used with arities {:?}",
+            self.name, self.arities
+        )
+    }
+}
+
+impl Signature {
+    pub fn new() -> Signature {
+        Signature::default()
+    }
+
+    pub fn add_function(&mut self, sym: NonLogicalSymbol) {
+        if sym.arity == 0 {
+            self.constants.insert(sym.name);
+        } else {
+            self.functions.insert(sym);
+        }
+    }
+
+    pub fn add_predicate(&mut self, sym: NonLogicalSymbol) {
+        self.predicates.insert(sym);
+    }
+
+    /// Adds `name` as a function symbol, or a constant without arguments,
+    /// of the given sorts.
+    pub fn declare_function(&mut self, name: &str, args: Vec<Sort>, result: Sort) {
+        let sym = nlsym!(name, args.len() as u32);
+        self.add_function(sym.clone());
+        self.function_sorts.insert(sym, (args, result));
+    }
+
+    /// Adds `name` as a predicate symbol with arguments of the given sorts.
+    pub fn declare_predicate(&mut self, name: &str, args: Vec<Sort>) {
+        let sym = nlsym!(name, args.len() as u32);
+        self.add_predicate(sym.clone());
+        self.predicate_sorts.insert(sym, args);
+    }
+
+    /// Adds the symbols of `other`. Its sort declarations take precedence.
+    pub fn extend(&mut self, other: Signature) {
+        self.functions.extend(other.functions);
+        self.constants.extend(other.constants);
+        self.predicates.extend(other.predicates);
+        self.function_sorts.extend(other.function_sorts);
+        self.predicate_sorts.extend(other.predicate_sorts);
+    }
+
+    /// The symbols used with conflicting arities, ordered by name.
+    pub fn check_consistent_arities(&self) -> Result<(), Vec<ArityConflict>> {
+        let mut arities: BTreeMap<&str, BTreeSet<u32>> = BTreeMap::new();
+        for sym in self.functions.iter().chain(&self.predicates) {
+            arities.entry(&sym.name).or_default().insert(sym.arity);
+        }
+        for name in &self.constants {
+            arities.entry(name).or_default().insert(0);
+        }
+        let conflicts = arities
+            .into_iter()
+            .filter(|(_, arities)| arities.len() > 1)
+            .map(|(name, arities)| ArityConflict {
+                name: name.to_string(),
+                arities: arities.into_iter().collect(),
+            })
+            .collect::<Vec<_>>();
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(conflicts)
+        }
+    }
+}
+
+pub trait HasSignature: crate::sealed::Sealed {
+    fn signature(&self) -> Signature;
+}
+
+impl HasSignature for Formula {
+    fn signature(&self) -> Signature {
+        let mut signature = Signature::new();
+        self.get_funcs()
+            .into_iter()
+            .for_each(|sym| signature.add_function(sym));
+        self.get_preds()
+            .into_iter()
+            .for_each(|sym| signature.add_predicate(sym));
+        signature
+    }
+}
+
+impl HasSignature for Sequent {
+    fn signature(&self) -> Signature {
+        let mut signature = Signature::new();
+        for fml in self.antecedent.iter().chain(&self.succedent) {
+            signature.extend(fml.signature());
+        }
+        signature
+    }
+}
+
+impl HasSignature for LK {
+    /// The symbols of every sequent of the proof, including those removed
+    /// by cuts and weakenings.
+    fn signature(&self) -> Signature {
+        let mut signature = self.last().signature();
+        for premise in self.premises() {
+            signature.extend(premise.signature());
+        }
+        signature
+    }
+}
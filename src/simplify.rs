@@ -0,0 +1,73 @@
+use crate::adapt::*;
+use crate::proof::*;
+use std::collections::HashMap;
+
+// Records the smallest subproof of `proof` for each end sequent, with its
+// size, the first one found among those of the same size. Returns the size
+// of `proof`.
+fn _collect<'a>(proof: &'a LK, smallest: &mut HashMap<&'a Sequent, (usize, &'a LK)>) -> usize {
+    let size = 1
+        + (proof.premises().into_iter())
+            .map(|p| _collect(p, smallest))
+            .sum::<usize>();
+    let entry = smallest.entry(proof.last()).or_insert((size, proof));
+    if size < entry.0 {
+        *entry = (size, proof);
+    }
+    size
+}
+
+// The structural inferences at the root of `proof` derived again by
+// `adapt` from the first other inference above them, if that is shorter.
+fn _shorten(proof: LK) -> LK {
+    let mut top = &proof;
+    while top.spec().structural {
+        top = top.premises()[0];
+    }
+    match adapt(top.clone(), proof.last()) {
+        Ok(shorter) if shorter.size() < proof.size() => shorter,
+        _ => proof,
+    }
+}
+
+// `proof` with every subproof replaced by the smallest one for the same end
+// sequent and its structural inferences shortened.
+fn _rebuild(proof: &LK, smallest: &HashMap<&Sequent, (usize, &LK)>) -> LK {
+    let proof = smallest[proof.last()].1;
+    let premises = (proof.premises().into_iter())
+        .map(|p| _rebuild(p, smallest))
+        .collect();
+    let proof = proof.with_premises(premises);
+    if proof.spec().structural {
+        _shorten(proof)
+    } else {
+        proof
+    }
+}
+
+impl LK {
+    /// A proof of the same end sequent, without detours through a sequent
+    /// derived again further down, as a weakening undone by a contraction
+    /// or an exchange undone by another. Every subproof is replaced by the
+    /// smallest one of the proof for the same end sequent, and runs of
+    /// structural inferences by those `adapt` gives when fewer, until the
+    /// proof gets no smaller. Inferences only depend on their premises and
+    /// conclusion, so the result is valid if the proof is.
+    pub fn simplify(&self) -> LK {
+        let mut proof = self.clone();
+        let mut size = proof.size();
+        loop {
+            let simpler = {
+                let mut smallest = HashMap::new();
+                _collect(&proof, &mut smallest);
+                _rebuild(&proof, &smallest)
+            };
+            let simpler_size = simpler.size();
+            if simpler_size >= size {
+                return proof;
+            }
+            proof = simpler;
+            size = simpler_size;
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use crate::fresh::*;
+use crate::language::*;
+use std::collections::HashMap;
+
+fn _apply(term: &Term, env: &HashMap<Term, Term>) -> Term {
+    match term {
+        Term::Var(_) => env.get(term).cloned().unwrap_or_else(|| term.clone()),
+        Term::Func(name, terms) => {
+            Term::Func(name.clone(), terms.iter().map(|t| _apply(t, env)).collect())
+        }
+    }
+}
+
+fn _has_quantifier(fml: &Formula) -> bool {
+    match fml {
+        Formula::Forall(_, _) | Formula::Exists(_, _) => true,
+        Formula::Not(fml) => _has_quantifier(fml),
+        Formula::And(lhs, rhs)
+        | Formula::Or(lhs, rhs)
+        | Formula::Implies(lhs, rhs)
+        | Formula::Iff(lhs, rhs) => _has_quantifier(lhs) || _has_quantifier(rhs),
+        Formula::Pred(_, _) | Formula::Equal(_, _) | Formula::Top | Formula::Bottom => false,
+    }
+}
+
+// `env` maps the variables of removed existentials to their Skolem terms and
+// renamed universal variables to their new names.
+fn _skolemize(
+    fml: &Formula,
+    positive: bool,
+    universals: &mut Vec<Term>,
+    env: &HashMap<Term, Term>,
+    symbols: &mut SymbolGenerator,
+) -> Formula {
+    let mut sub = |fml: &Formula, positive: bool, symbols: &mut SymbolGenerator| {
+        Box::new(_skolemize(fml, positive, universals, env, symbols))
+    };
+    match fml {
+        Formula::Pred(name, terms) => {
+            Formula::Pred(name.clone(), terms.iter().map(|t| _apply(t, env)).collect())
+        }
+        Formula::Equal(lhs, rhs) => Formula::Equal(_apply(lhs, env), _apply(rhs, env)),
+        Formula::Not(fml) => Formula::Not(sub(fml, !positive, symbols)),
+        Formula::And(lhs, rhs) => {
+            Formula::And(sub(lhs, positive, symbols), sub(rhs, positive, symbols))
+        }
+        Formula::Or(lhs, rhs) => {
+            Formula::Or(sub(lhs, positive, symbols), sub(rhs, positive, symbols))
+        }
+        Formula::Implies(lhs, rhs) => {
+            Formula::Implies(sub(lhs, !positive, symbols), sub(rhs, positive, symbols))
+        }
+        // Both sides of a biconditional occur in both polarities, so
+        // quantified ones are split into two implications first.
+        Formula::Iff(lhs, rhs) if _has_quantifier(lhs) || _has_quantifier(rhs) => {
+            let fml = and!(
+                implies!(*lhs.clone(), *rhs.clone()),
+                implies!(*rhs.clone(), *lhs.clone())
+            );
+            *sub(&fml, positive, symbols)
+        }
+        Formula::Iff(lhs, rhs) => {
+            Formula::Iff(sub(lhs, positive, symbols), sub(rhs, positive, symbols))
+        }
+        Formula::Forall(var, body) | Formula::Exists(var, body) => {
+            let mut env = env.clone();
+            if matches!(fml, Formula::Forall(_, _)) == positive {
+                // Keeps Skolem terms from being captured by the quantifier.
+                let new_var = if env.values().any(|t| t.get_vars().contains(var)) {
+                    match var {
+                        Term::Var(name) => symbols.fresh_var(name),
+                        _ => var.clone(),
+                    }
+                } else {
+                    var.clone()
+                };
+                env.remove(var);
+                if &new_var != var {
+                    env.insert(var.clone(), new_var.clone());
+                }
+                universals.push(new_var.clone());
+                let body = Box::new(_skolemize(body, positive, universals, &env, symbols));
+                universals.pop();
+                match fml {
+                    Formula::Forall(_, _) => Formula::Forall(new_var, body),
+                    _ => Formula::Exists(new_var, body),
+                }
+            } else {
+                let func = symbols.fresh_func("sk", universals.len() as u32);
+                env.insert(var.clone(), Term::Func(func.name, universals.clone()));
+                _skolemize(body, positive, universals, &env, symbols)
+            }
+        }
+        Formula::Top | Formula::Bottom => fml.clone(),
+    }
+}
+
+impl Formula {
+    /// Removes the existential quantifiers of positive polarity (and the
+    /// universal ones of negative polarity) by replacing their variables with
+    /// fresh Skolem functions applied to the enclosing universal variables.
+    /// The result is equisatisfiable with the formula. The new symbols are
+    /// recorded in `symbols`, which is made to avoid every name of the
+    /// formula first.
+    pub fn skolemize(&self, symbols: &mut SymbolGenerator) -> Formula {
+        symbols.avoid(self);
+        _skolemize(self, true, &mut vec![], &HashMap::new(), symbols)
+    }
+}
@@ -0,0 +1,354 @@
+use crate::language::*;
+use crate::limits::*;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, PartialEq)]
+enum SExpr {
+    Atom(String),
+    List(Vec<SExpr>),
+}
+
+/// Result of reading an SMT-LIB 2 script: the asserted formulas in order and
+/// the declared function and predicate symbols (Bool-valued declarations
+/// become predicates).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmtScript {
+    pub assertions: Vec<Formula>,
+    pub funcs: HashSet<NonLogicalSymbol>,
+    pub preds: HashSet<NonLogicalSymbol>,
+}
+
+fn _read_sexprs(input: &str) -> Result<Vec<SExpr>, &'static str> {
+    let mut stack: Vec<Vec<SExpr>> = vec![vec![]];
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '(' => {
+                if Limits::untrusted()
+                    .max_depth
+                    .is_some_and(|max| stack.len() > max)
+                {
+                    return Err(TOO_DEEP);
+                }
+                stack.push(vec![])
+            }
+            ')' => {
+                if stack.len() < 2 {
+                    return Err("Unbalanced parenthesis.");
+                }
+                let list = stack.pop().unwrap();
+                stack.last_mut().unwrap().push(SExpr::List(list));
+            }
+            ';' => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '|' => {
+                let mut symbol = String::new();
+                loop {
+                    match chars.next() {
+                        Some('|') => break,
+                        Some(c) => symbol.push(c),
+                        None => return Err("Unterminated quoted symbol."),
+                    }
+                }
+                stack.last_mut().unwrap().push(SExpr::Atom(symbol));
+            }
+            '"' => {
+                let mut string = String::from("\"");
+                loop {
+                    match chars.next() {
+                        Some('"') => {
+                            if chars.peek() == Some(&'"') {
+                                chars.next();
+                                string.push('"');
+                            } else {
+                                break;
+                            }
+                        }
+                        Some(c) => string.push(c),
+                        None => return Err("Unterminated string literal."),
+                    }
+                }
+                string.push('"');
+                stack.last_mut().unwrap().push(SExpr::Atom(string));
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut symbol = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | ';' | '|' | '"') {
+                        break;
+                    }
+                    symbol.push(c);
+                    chars.next();
+                }
+                stack.last_mut().unwrap().push(SExpr::Atom(symbol));
+            }
+        }
+    }
+    if stack.len() == 1 {
+        Ok(stack.pop().unwrap())
+    } else {
+        Err("Unbalanced parenthesis.")
+    }
+}
+
+#[derive(Debug)]
+struct SmtReader {
+    // Declared symbols with their argument count and whether they are Bool-valued.
+    decls: HashMap<String, (usize, bool)>,
+    script: SmtScript,
+}
+
+impl SmtReader {
+    fn _declare(&mut self, name: &str, args: &[SExpr], sort: &SExpr) -> Result<(), &'static str> {
+        let is_bool = matches!(sort, SExpr::Atom(s) if s == "Bool");
+        if args
+            .iter()
+            .any(|s| matches!(s, SExpr::Atom(s) if s == "Bool"))
+        {
+            return Err("Bool-sorted arguments are not supported.");
+        }
+        let sym = nlsym!(name, args.len() as u32);
+        if is_bool {
+            self.script.preds.insert(sym);
+        } else {
+            self.script.funcs.insert(sym);
+        }
+        self.decls.insert(name.into(), (args.len(), is_bool));
+        Ok(())
+    }
+
+    fn _command(&mut self, sexpr: &SExpr) -> Result<(), &'static str> {
+        let items = match sexpr {
+            SExpr::List(items) if !items.is_empty() => items,
+            _ => return Err("Command expected."),
+        };
+        match (&items[0], &items[1..]) {
+            (SExpr::Atom(cmd), [SExpr::Atom(name), SExpr::List(args), sort])
+                if cmd == "declare-fun" =>
+            {
+                self._declare(name, args, sort)
+            }
+            (SExpr::Atom(cmd), [SExpr::Atom(name), sort]) if cmd == "declare-const" => {
+                self._declare(name, &[], sort)
+            }
+            (SExpr::Atom(cmd), [fml]) if cmd == "assert" => {
+                let fml = self._formula(fml, &HashSet::new())?;
+                self.script.assertions.push(fml);
+                Ok(())
+            }
+            (SExpr::Atom(cmd), _)
+                if matches!(
+                    cmd.as_str(),
+                    "set-logic"
+                        | "set-info"
+                        | "set-option"
+                        | "declare-sort"
+                        | "define-sort"
+                        | "check-sat"
+                        | "get-model"
+                        | "get-proof"
+                        | "get-unsat-core"
+                        | "exit"
+                ) =>
+            {
+                Ok(())
+            }
+            _ => Err("Unsupported command."),
+        }
+    }
+
+    fn _term(&self, sexpr: &SExpr, bound: &HashSet<String>) -> Result<Term, &'static str> {
+        match sexpr {
+            SExpr::Atom(s) if bound.contains(s) => Ok(Term::Var(s.into())),
+            SExpr::Atom(s) => match self.decls.get(s) {
+                Some((0, false)) => Ok(Term::Func(s.into(), vec![])),
+                Some(_) => Err("Symbol used with wrong sort or arity."),
+                None => Err("Undeclared symbol."),
+            },
+            SExpr::List(items) => match items.split_first() {
+                Some((SExpr::Atom(s), args)) => match self.decls.get(s) {
+                    Some((arity, false)) if *arity == args.len() => Ok(Term::Func(
+                        s.into(),
+                        args.iter()
+                            .map(|arg| self._term(arg, bound))
+                            .collect::<Result<Vec<_>, _>>()?,
+                    )),
+                    Some(_) => Err("Symbol used with wrong sort or arity."),
+                    None => Err("Undeclared symbol."),
+                },
+                _ => Err("Term expected."),
+            },
+        }
+    }
+
+    fn _is_formula(&self, sexpr: &SExpr) -> bool {
+        match sexpr {
+            SExpr::Atom(s) => {
+                s == "true" || s == "false" || matches!(self.decls.get(s), Some((0, true)))
+            }
+            SExpr::List(items) => match items.first() {
+                Some(SExpr::Atom(s)) => {
+                    matches!(
+                        s.as_str(),
+                        "not" | "and" | "or" | "=>" | "=" | "distinct" | "forall" | "exists" | "!"
+                    ) || matches!(self.decls.get(s), Some((_, true)))
+                }
+                _ => false,
+            },
+        }
+    }
+
+    fn _fold(
+        &self,
+        args: &[SExpr],
+        bound: &HashSet<String>,
+        connective: fn(Formula, Formula) -> Formula,
+        unit: Formula,
+    ) -> Result<Formula, &'static str> {
+        let mut fmls = args
+            .iter()
+            .map(|arg| self._formula(arg, bound))
+            .collect::<Result<Vec<_>, _>>()?;
+        match fmls.pop() {
+            Some(last) => Ok(fmls
+                .into_iter()
+                .rev()
+                .fold(last, |acc, f| connective(f, acc))),
+            None => Ok(unit),
+        }
+    }
+
+    fn _chain(
+        &self,
+        args: &[SExpr],
+        bound: &HashSet<String>,
+        distinct: bool,
+    ) -> Result<Formula, &'static str> {
+        if args.len() < 2 {
+            return Err("Too few arguments.");
+        }
+        let mut pairs = vec![];
+        if args.iter().all(|arg| self._is_formula(arg)) {
+            let fmls = args
+                .iter()
+                .map(|arg| self._formula(arg, bound))
+                .collect::<Result<Vec<_>, _>>()?;
+            for i in 0..fmls.len() - 1 {
+                let iff = iff!(fmls[i].clone(), fmls[i + 1].clone());
+                pairs.push(if distinct { not!(iff) } else { iff });
+            }
+        } else {
+            let terms = args
+                .iter()
+                .map(|arg| self._term(arg, bound))
+                .collect::<Result<Vec<_>, _>>()?;
+            if distinct {
+                for i in 0..terms.len() {
+                    for j in i + 1..terms.len() {
+                        pairs.push(not!(equal!(terms[i].clone(), terms[j].clone())));
+                    }
+                }
+            } else {
+                for i in 0..terms.len() - 1 {
+                    pairs.push(equal!(terms[i].clone(), terms[i + 1].clone()));
+                }
+            }
+        }
+        let last = pairs.pop().unwrap();
+        Ok(pairs.into_iter().rev().fold(last, |acc, f| and!(f, acc)))
+    }
+
+    fn _formula(&self, sexpr: &SExpr, bound: &HashSet<String>) -> Result<Formula, &'static str> {
+        match sexpr {
+            SExpr::Atom(s) if s == "true" => Ok(Formula::Top),
+            SExpr::Atom(s) if s == "false" => Ok(Formula::Bottom),
+            SExpr::Atom(s) => match self.decls.get(s) {
+                Some((0, true)) => Ok(Formula::Pred(s.into(), vec![])),
+                Some(_) => Err("Symbol used with wrong sort or arity."),
+                None => Err("Undeclared symbol."),
+            },
+            SExpr::List(items) => {
+                let (head, args) = match items.split_first() {
+                    Some((SExpr::Atom(head), args)) => (head.as_str(), args),
+                    _ => return Err("Formula expected."),
+                };
+                match head {
+                    "not" if args.len() == 1 => Ok(not!(self._formula(&args[0], bound)?)),
+                    "and" => self._fold(args, bound, |l, r| and!(l, r), Formula::Top),
+                    "or" => self._fold(args, bound, |l, r| or!(l, r), Formula::Bottom),
+                    "=>" if args.len() >= 2 => {
+                        self._fold(args, bound, |l, r| implies!(l, r), Formula::Top)
+                    }
+                    "=" => self._chain(args, bound, false),
+                    "distinct" => self._chain(args, bound, true),
+                    "!" if !args.is_empty() => self._formula(&args[0], bound),
+                    "forall" | "exists" if args.len() == 2 => {
+                        let vars = match &args[0] {
+                            SExpr::List(vars) if !vars.is_empty() => vars,
+                            _ => return Err("Sorted variable list expected."),
+                        };
+                        let mut names = vec![];
+                        for var in vars {
+                            match var {
+                                SExpr::List(pair) => match &pair[..] {
+                                    [SExpr::Atom(_), SExpr::Atom(sort)] if sort == "Bool" => {
+                                        return Err("Bool-sorted variables are not supported.")
+                                    }
+                                    [SExpr::Atom(name), _] => names.push(name.clone()),
+                                    _ => return Err("Sorted variable expected."),
+                                },
+                                _ => return Err("Sorted variable expected."),
+                            }
+                        }
+                        let mut inner = bound.clone();
+                        inner.extend(names.iter().cloned());
+                        let body = self._formula(&args[1], &inner)?;
+                        Ok(names.into_iter().rev().fold(body, |fml, name| {
+                            if head == "forall" {
+                                forall!(var!(name), fml)
+                            } else {
+                                exists!(var!(name), fml)
+                            }
+                        }))
+                    }
+                    s => match self.decls.get(s) {
+                        Some((arity, true)) if *arity == args.len() => Ok(Formula::Pred(
+                            s.into(),
+                            args.iter()
+                                .map(|arg| self._term(arg, bound))
+                                .collect::<Result<Vec<_>, _>>()?,
+                        )),
+                        Some(_) => Err("Symbol used with wrong sort or arity."),
+                        None => Err("Unsupported formula."),
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// Reads the supported subset of SMT-LIB 2: `declare-fun`, `declare-const`,
+/// `assert` and the informational commands, with `forall`/`exists`, `=`,
+/// `distinct`, `and`, `or`, `not` and `=>`. Sorts other than `Bool` are
+/// collapsed into the single domain of first-order logic. Expressions nested
+/// deeper than `Limits::untrusted` allows are rejected. Never panics.
+pub fn parse_smtlib(input: &str) -> Result<SmtScript, &'static str> {
+    let mut reader = SmtReader {
+        decls: HashMap::new(),
+        script: SmtScript {
+            assertions: vec![],
+            funcs: HashSet::new(),
+            preds: HashSet::new(),
+        },
+    };
+    for sexpr in _read_sexprs(input)? {
+        reader._command(&sexpr)?;
+    }
+    Ok(reader.script)
+}
@@ -1,7 +1,13 @@
 use crate::language::*;
 use crate::model::*;
 use crate::proof::*;
-use std::collections::HashMap;
+use crate::record::*;
+use crate::theory::*;
+use crate::trace::*;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use Formula::*;
 
 fn _enumerate_vecs(vectors: Vec<Vec<u32>>, size: u32, rem_size: u32) -> Vec<Vec<u32>> {
@@ -20,71 +26,56 @@ fn _enumerate_vecs(vectors: Vec<Vec<u32>>, size: u32, rem_size: u32) -> Vec<Vec<
     }
 }
 
-fn _enumerate_assign_func(
-    assigns: Vec<HashMap<Vec<u32>, u32>>,
-    vector: &[Vec<u32>],
-    domain_size: u32,
-) -> Vec<HashMap<Vec<u32>, u32>> {
-    if vector.len() == 0 {
-        assigns
-    } else {
-        let mut new_assigns = vec![];
-        for assign in assigns {
-            for n in 0..domain_size {
-                let mut tmp = assign.clone();
-                tmp.insert(vector[0].clone(), n);
-                new_assigns.push(tmp);
-            }
-        }
-        _enumerate_assign_func(new_assigns, &vector[1..], domain_size)
-    }
+/// Every table from the argument vectors of an arity to `values`, built one
+/// at a time, the last argument vector's value changing first.
+struct Tables<T> {
+    args: Vec<Vec<u32>>,
+    values: Vec<T>,
+    // The index of the value of each argument vector in the next table.
+    next: Option<Vec<usize>>,
 }
 
-fn enumerate_assign_func(arity: u32, domain_size: u32) -> Vec<HashMap<Vec<u32>, u32>> {
-    if arity == 0 {
-        let mut assigns = vec![];
-        for n in 0..domain_size {
-            assigns.push(assign![[] => n]);
-        }
-        assigns
-    } else {
-        let mut vectors = vec![vec![]];
-        vectors = _enumerate_vecs(vectors, domain_size, arity);
-        _enumerate_assign_func(vec![assign![]], &vectors[..], arity)
+impl<T> Tables<T> {
+    fn new(arity: u32, domain_size: u32, values: Vec<T>) -> Tables<T> {
+        let args = _enumerate_vecs(vec![vec![]], domain_size, arity);
+        let next = if values.is_empty() {
+            None
+        } else {
+            Some(vec![0; args.len()])
+        };
+        Tables { args, values, next }
     }
 }
 
-fn _enumerate_assign_pred(
-    assigns: Vec<HashMap<Vec<u32>, bool>>,
-    vector: &[Vec<u32>],
-    domain_size: u32,
-) -> Vec<HashMap<Vec<u32>, bool>> {
-    if vector.len() == 0 {
-        assigns
-    } else {
-        let mut new_assigns = vec![];
-        for assign in assigns {
-            for b in [true, false].iter() {
-                let mut tmp = assign.clone();
-                tmp.insert(vector[0].clone(), *b);
-                new_assigns.push(tmp);
+impl<T: Clone> Iterator for Tables<T> {
+    type Item = HashMap<Vec<u32>, T>;
+
+    fn next(&mut self) -> Option<HashMap<Vec<u32>, T>> {
+        let (digits, values) = (self.next.as_mut()?, &self.values);
+        let table = self
+            .args
+            .iter()
+            .zip(digits.iter())
+            .map(|(args, &digit)| (args.clone(), values[digit].clone()))
+            .collect();
+        for digit in digits.iter_mut().rev() {
+            *digit += 1;
+            if *digit < values.len() {
+                return Some(table);
             }
+            *digit = 0;
         }
-        _enumerate_assign_pred(new_assigns, &vector[1..], domain_size)
+        self.next = None;
+        Some(table)
     }
 }
 
-fn enumerate_assign_pred(arity: u32, domain_size: u32) -> Vec<HashMap<Vec<u32>, bool>> {
-    if arity == 0 {
-        let mut assigns = vec![];
-        assigns.push(assign![[] => true]);
-        assigns.push(assign![[] => false]);
-        assigns
-    } else {
-        let mut vectors = vec![vec![]];
-        vectors = _enumerate_vecs(vectors, domain_size, arity);
-        _enumerate_assign_pred(vec![assign![]], &vectors[..], arity)
-    }
+fn enumerate_assign_func(arity: u32, domain_size: u32) -> Tables<u32> {
+    Tables::new(arity, domain_size, (0..domain_size).collect())
+}
+
+fn enumerate_assign_pred(arity: u32, domain_size: u32) -> Tables<bool> {
+    Tables::new(arity, domain_size, vec![true, false])
 }
 
 fn _refute_on_finite_models(
@@ -97,6 +88,9 @@ fn _refute_on_finite_models(
 ) -> Option<FiniteModel> {
     if !free_vars.is_empty() {
         for n in 0..domain_size {
+            if is_cancelled() {
+                return None;
+            }
             model.assign_var(assign![free_vars[0].clone() => n]);
             if let m @ Some(_) =
                 _refute_on_finite_models(fml, domain_size, &free_vars[1..], funcs, preds, model)
@@ -107,6 +101,9 @@ fn _refute_on_finite_models(
         None
     } else if !funcs.is_empty() {
         for assign in enumerate_assign_func(funcs[0].arity, domain_size) {
+            if is_cancelled() {
+                return None;
+            }
             model.assign_func(funcs[0].clone(), assign);
             if let m @ Some(_) =
                 _refute_on_finite_models(fml, domain_size, free_vars, &funcs[1..], preds, model)
@@ -117,6 +114,9 @@ fn _refute_on_finite_models(
         None
     } else if !preds.is_empty() {
         for assign in enumerate_assign_pred(preds[0].arity, domain_size) {
+            if is_cancelled() {
+                return None;
+            }
             model.assign_pred(preds[0].clone(), assign);
             if let t @ Some(_) =
                 _refute_on_finite_models(fml, domain_size, free_vars, funcs, &preds[1..], model)
@@ -135,7 +135,9 @@ fn _refute_on_finite_models(
     }
 }
 
-pub fn refute_on_finite_models(fml: Formula, max_domain_size: u32) -> Option<FiniteModel> {
+/// Countermodel of the given domain size, if any. Free variables are
+/// assigned too, so they are read universally.
+pub fn refute_on_models_of_size(fml: &Formula, domain_size: u32) -> Option<FiniteModel> {
     let free_vars = fml.get_free_vars().into_iter().collect::<Vec<Term>>();
     let funcs = fml
         .get_funcs()
@@ -146,31 +148,127 @@ pub fn refute_on_finite_models(fml: Formula, max_domain_size: u32) -> Option<Fin
         .into_iter()
         .collect::<Vec<NonLogicalSymbol>>();
 
-    for domain_size in 1..max_domain_size + 1 {
-        let mut model = FiniteModel::new(domain_size);
-        if let Some(m) = _refute_on_finite_models(
-            &fml,
-            domain_size,
-            &free_vars[..],
-            &funcs[..],
-            &preds[..],
-            &mut model,
-        ) {
-            let mut model = m.clone();
-            model.var_assignment.retain(|k, _| free_vars.contains(&k));
-            return Some(model);
-        }
+    let mut model = FiniteModel::new(domain_size);
+    let mut model = _refute_on_finite_models(
+        fml,
+        domain_size,
+        &free_vars[..],
+        &funcs[..],
+        &preds[..],
+        &mut model,
+    )?;
+    model.var_assignment.retain(|k, _| free_vars.contains(&k));
+    Some(model)
+}
+
+pub fn refute_on_finite_models(fml: Formula, max_domain_size: u32) -> Option<FiniteModel> {
+    (1..max_domain_size + 1).find_map(|domain_size| refute_on_models_of_size(&fml, domain_size))
+}
+
+/// Scores candidate formulas during proof search. Candidates with higher
+/// scores are tried first.
+pub trait Heuristic {
+    fn score(&self, fml: &Formula) -> f64;
+}
+
+/// Keeps the prover's built-in candidate order.
+pub struct Unguided;
+
+impl Heuristic for Unguided {
+    fn score(&self, _: &Formula) -> f64 {
+        0.0
     }
-    None
+}
+
+/// Hook for a user-supplied scoring model over `Formula::features`, e.g. a
+/// learned premise selector.
+pub struct FeatureModel<F: Fn(&[f64]) -> f64> {
+    pub model: F,
+}
+
+impl<F: Fn(&[f64]) -> f64> Heuristic for FeatureModel<F> {
+    fn score(&self, fml: &Formula) -> f64 {
+        (self.model)(&fml.features())
+    }
+}
+
+fn _order_by_score<T>(
+    candidates: impl IntoIterator<Item = T>,
+    score: impl Fn(&T) -> f64,
+) -> Vec<T> {
+    let mut scored = candidates
+        .into_iter()
+        .map(|c| (score(&c), c))
+        .collect::<Vec<_>>();
+    scored.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+    scored.into_iter().map(|(_, c)| c).collect()
+}
+
+thread_local! {
+    static CANCELLED: RefCell<Option<Arc<AtomicBool>>> = const { RefCell::new(None) };
+    static TRACE: RefCell<Option<Tracer>> = const { RefCell::new(None) };
+}
+
+// The subgoals the search is working on and, in order, each pair of a goal
+// and a subgoal it generated.
+#[derive(Default)]
+struct Tracer {
+    stack: Vec<Sequent>,
+    edges: Vec<(Sequent, Sequent)>,
+    seen: HashSet<(Sequent, Sequent)>,
+}
+
+/// Makes the LK searches running on the current thread give up once `flag`
+/// is set.
+pub(crate) fn set_cancellation_flag(flag: Option<Arc<AtomicBool>>) {
+    CANCELLED.with(|cancelled| *cancelled.borrow_mut() = flag);
+}
+
+/// Whether the flag of `set_cancellation_flag` is set on this thread.
+pub(crate) fn is_cancelled() -> bool {
+    CANCELLED.with(|cancelled| {
+        cancelled
+            .borrow()
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::Relaxed))
+    })
 }
 
 fn _prove_with_lk(
     sequent: &Sequent,
     max_depth: u32,
     use_cut: bool,
+    heuristic: &dyn Heuristic,
     checked_sequents: &mut HashMap<Sequent, Result<LK, u32>>,
 ) -> Result<LK, u32> {
-    if max_depth == 0 {
+    let tracing = TRACE.with(|trace| match trace.borrow_mut().as_mut() {
+        Some(tracer) => {
+            if let Some(goal) = tracer.stack.last() {
+                let edge = (goal.clone(), sequent.clone());
+                if tracer.seen.insert(edge.clone()) {
+                    tracer.edges.push(edge);
+                }
+            }
+            tracer.stack.push(sequent.clone());
+            true
+        }
+        None => false,
+    });
+    let result = _search(sequent, max_depth, use_cut, heuristic, checked_sequents);
+    if tracing {
+        TRACE.with(|trace| trace.borrow_mut().as_mut().map(|tracer| tracer.stack.pop()));
+    }
+    result
+}
+
+fn _search(
+    sequent: &Sequent,
+    max_depth: u32,
+    use_cut: bool,
+    heuristic: &dyn Heuristic,
+    checked_sequents: &mut HashMap<Sequent, Result<LK, u32>>,
+) -> Result<LK, u32> {
+    if max_depth == 0 || is_cancelled() {
         Err(0)
     } else if checked_sequents.contains_key(sequent)
         && match checked_sequents[sequent] {
@@ -185,8 +283,10 @@ fn _prove_with_lk(
                 && sequent.succedent.len() == 1
                 && match sequent.suc_last() {
                     Formula::Equal(s, t) => s == t,
+                    Formula::Top => true,
                     _ => false,
                 })
+            || (sequent.antecedent == [Formula::Bottom] && sequent.succedent.is_empty())
         {
             let prf = Ok(LK::Axiom(sequent.clone()));
             checked_sequents.insert(sequent.clone(), prf.clone());
@@ -203,7 +303,7 @@ fn _prove_with_lk(
                         succedent: parent_suc,
                     };
                     if let Ok(subprf) =
-                        _prove_with_lk(&parent, max_depth - 1, use_cut, checked_sequents)
+                        _prove_with_lk(&parent, max_depth - 1, use_cut, heuristic, checked_sequents)
                     {
                         let prf = LK::NotLeft(Box::new(subprf), sequent.clone());
                         checked_sequents.insert(sequent.clone(), Ok(prf.clone()));
@@ -218,7 +318,7 @@ fn _prove_with_lk(
                         succedent: sequent.succedent.clone(),
                     };
                     if let Ok(subprf) =
-                        _prove_with_lk(&parent, max_depth - 1, use_cut, checked_sequents)
+                        _prove_with_lk(&parent, max_depth - 1, use_cut, heuristic, checked_sequents)
                     {
                         let prf = LK::AndLeft1(Box::new(subprf), sequent.clone());
                         checked_sequents.insert(sequent.clone(), Ok(prf.clone()));
@@ -230,7 +330,7 @@ fn _prove_with_lk(
                         succedent: sequent.succedent.clone(),
                     };
                     if let Ok(subprf) =
-                        _prove_with_lk(&parent, max_depth - 1, use_cut, checked_sequents)
+                        _prove_with_lk(&parent, max_depth - 1, use_cut, heuristic, checked_sequents)
                     {
                         let prf = LK::AndLeft2(Box::new(subprf), sequent.clone());
                         checked_sequents.insert(sequent.clone(), Ok(prf.clone()));
@@ -243,8 +343,20 @@ fn _prove_with_lk(
                     let mut right_sequent = sequent.clone();
                     right_sequent.antecedent[0] = *rhs.clone();
                     if let (Ok(lprf), Ok(rprf)) = (
-                        _prove_with_lk(&left_sequent, max_depth - 1, use_cut, checked_sequents),
-                        _prove_with_lk(&right_sequent, max_depth - 1, use_cut, checked_sequents),
+                        _prove_with_lk(
+                            &left_sequent,
+                            max_depth - 1,
+                            use_cut,
+                            heuristic,
+                            checked_sequents,
+                        ),
+                        _prove_with_lk(
+                            &right_sequent,
+                            max_depth - 1,
+                            use_cut,
+                            heuristic,
+                            checked_sequents,
+                        ),
                     ) {
                         let prf = LK::OrLeft(Box::new([lprf, rprf]), sequent.clone());
                         checked_sequents.insert(sequent.clone(), Ok(prf.clone()));
@@ -279,12 +391,14 @@ fn _prove_with_lk(
                                     &left_sequent,
                                     max_depth - 1,
                                     use_cut,
+                                    heuristic,
                                     checked_sequents,
                                 ),
                                 _prove_with_lk(
                                     &right_sequent,
                                     max_depth - 1,
                                     use_cut,
+                                    heuristic,
                                     checked_sequents,
                                 ),
                             ) {
@@ -295,6 +409,37 @@ fn _prove_with_lk(
                         }
                     }
                 }
+                Iff(lhs, rhs) => {
+                    let mut left_sequent = sequent.clone();
+                    left_sequent.antecedent = vec![*lhs.clone(), *rhs.clone()];
+                    left_sequent
+                        .antecedent
+                        .extend(sequent.ant_but_first().to_vec());
+                    let mut right_sequent = sequent.clone();
+                    right_sequent.antecedent = sequent.ant_but_first().to_vec();
+                    right_sequent.succedent.push(*lhs.clone());
+                    right_sequent.succedent.push(*rhs.clone());
+                    if let (Ok(lprf), Ok(rprf)) = (
+                        _prove_with_lk(
+                            &left_sequent,
+                            max_depth - 1,
+                            use_cut,
+                            heuristic,
+                            checked_sequents,
+                        ),
+                        _prove_with_lk(
+                            &right_sequent,
+                            max_depth - 1,
+                            use_cut,
+                            heuristic,
+                            checked_sequents,
+                        ),
+                    ) {
+                        let prf = LK::IffLeft(Box::new([lprf, rprf]), sequent.clone());
+                        checked_sequents.insert(sequent.clone(), Ok(prf.clone()));
+                        return Ok(prf);
+                    }
+                }
                 Forall(term, bfml) => {
                     let mut parent_ant = sequent.antecedent.clone();
                     parent_ant[0] = *bfml.clone();
@@ -307,6 +452,9 @@ fn _prove_with_lk(
                         let terms = fml.get_subterms();
                         substitutible_terms.extend(terms);
                     }
+                    let substitutible_terms = _order_by_score(substitutible_terms, |t| {
+                        heuristic.score(&parent.antecedent[0].substitute(term.clone(), t.clone()))
+                    });
                     for t in substitutible_terms {
                         if parent.antecedent[0].is_substitutible(term.clone(), t.clone()) {
                             let tmp_fml = parent.antecedent[0].substitute(term.clone(), t.clone());
@@ -316,6 +464,7 @@ fn _prove_with_lk(
                                 &tmp_sequent,
                                 max_depth - 1,
                                 use_cut,
+                                heuristic,
                                 checked_sequents,
                             ) {
                                 let prf = LK::ForallLeft(Box::new(subprf), sequent.clone());
@@ -336,6 +485,8 @@ fn _prove_with_lk(
                     for fml in [sequent.antecedent.clone(), sequent.succedent.clone()].concat() {
                         let vars = fml.get_free_vars();
                         free_vars.extend(vars);
+                        // Bound variables too, or substituting would capture.
+                        free_vars.extend(fml.get_bound_vars());
                     }
                     let mut idx = 1;
                     while free_vars.contains(&var!(format!("x{}", idx))) {
@@ -345,9 +496,13 @@ fn _prove_with_lk(
                     let tmp_fml = parent.antecedent[0].substitute(term.clone(), v.clone());
                     let mut tmp_sequent = parent.clone();
                     tmp_sequent.antecedent[0] = tmp_fml;
-                    if let Ok(subprf) =
-                        _prove_with_lk(&tmp_sequent, max_depth - 1, use_cut, checked_sequents)
-                    {
+                    if let Ok(subprf) = _prove_with_lk(
+                        &tmp_sequent,
+                        max_depth - 1,
+                        use_cut,
+                        heuristic,
+                        checked_sequents,
+                    ) {
                         let prf = LK::ExistsLeft(Box::new(subprf), sequent.clone());
                         checked_sequents.insert(sequent.clone(), Ok(prf.clone()));
                         return Ok(prf);
@@ -367,7 +522,7 @@ fn _prove_with_lk(
                         succedent: parent_suc.to_vec(),
                     };
                     if let Ok(subprf) =
-                        _prove_with_lk(&parent, max_depth - 1, use_cut, checked_sequents)
+                        _prove_with_lk(&parent, max_depth - 1, use_cut, heuristic, checked_sequents)
                     {
                         let prf = LK::NotRight(Box::new(subprf), sequent.clone());
                         checked_sequents.insert(sequent.clone(), Ok(prf.clone()));
@@ -383,7 +538,7 @@ fn _prove_with_lk(
                         succedent: parent_suc.clone(),
                     };
                     if let Ok(subprf) =
-                        _prove_with_lk(&parent, max_depth - 1, use_cut, checked_sequents)
+                        _prove_with_lk(&parent, max_depth - 1, use_cut, heuristic, checked_sequents)
                     {
                         let prf = LK::OrRight1(Box::new(subprf), sequent.clone());
                         checked_sequents.insert(sequent.clone(), Ok(prf.clone()));
@@ -396,7 +551,7 @@ fn _prove_with_lk(
                         succedent: parent_suc.clone(),
                     };
                     if let Ok(subprf) =
-                        _prove_with_lk(&parent, max_depth - 1, use_cut, checked_sequents)
+                        _prove_with_lk(&parent, max_depth - 1, use_cut, heuristic, checked_sequents)
                     {
                         let prf = LK::OrRight2(Box::new(subprf), sequent.clone());
                         checked_sequents.insert(sequent.clone(), Ok(prf.clone()));
@@ -411,8 +566,20 @@ fn _prove_with_lk(
                     let len = right_sequent.succedent.len();
                     right_sequent.succedent[len - 1] = *rhs.clone();
                     if let (Ok(lprf), Ok(rprf)) = (
-                        _prove_with_lk(&left_sequent, max_depth - 1, use_cut, checked_sequents),
-                        _prove_with_lk(&right_sequent, max_depth - 1, use_cut, checked_sequents),
+                        _prove_with_lk(
+                            &left_sequent,
+                            max_depth - 1,
+                            use_cut,
+                            heuristic,
+                            checked_sequents,
+                        ),
+                        _prove_with_lk(
+                            &right_sequent,
+                            max_depth - 1,
+                            use_cut,
+                            heuristic,
+                            checked_sequents,
+                        ),
                     ) {
                         let prf = LK::AndRight(Box::new([lprf, rprf]), sequent.clone());
                         checked_sequents.insert(sequent.clone(), Ok(prf.clone()));
@@ -425,14 +592,50 @@ fn _prove_with_lk(
                     parent_sequent.antecedent.extend(sequent.antecedent.clone());
                     let len = parent_sequent.succedent.len();
                     parent_sequent.succedent[len - 1] = *rhs.clone();
-                    if let Ok(subprf) =
-                        _prove_with_lk(&parent_sequent, max_depth - 1, use_cut, checked_sequents)
-                    {
+                    if let Ok(subprf) = _prove_with_lk(
+                        &parent_sequent,
+                        max_depth - 1,
+                        use_cut,
+                        heuristic,
+                        checked_sequents,
+                    ) {
                         let prf = LK::ImpliesRight(Box::new(subprf), sequent.clone());
                         checked_sequents.insert(sequent.clone(), Ok(prf.clone()));
                         return Ok(prf);
                     }
                 }
+                Iff(lhs, rhs) => {
+                    let mut left_sequent = sequent.clone();
+                    left_sequent.antecedent = vec![*lhs.clone()];
+                    left_sequent.antecedent.extend(sequent.antecedent.clone());
+                    let len = left_sequent.succedent.len();
+                    left_sequent.succedent[len - 1] = *rhs.clone();
+                    let mut right_sequent = sequent.clone();
+                    right_sequent.antecedent = vec![*rhs.clone()];
+                    right_sequent.antecedent.extend(sequent.antecedent.clone());
+                    let len = right_sequent.succedent.len();
+                    right_sequent.succedent[len - 1] = *lhs.clone();
+                    if let (Ok(lprf), Ok(rprf)) = (
+                        _prove_with_lk(
+                            &left_sequent,
+                            max_depth - 1,
+                            use_cut,
+                            heuristic,
+                            checked_sequents,
+                        ),
+                        _prove_with_lk(
+                            &right_sequent,
+                            max_depth - 1,
+                            use_cut,
+                            heuristic,
+                            checked_sequents,
+                        ),
+                    ) {
+                        let prf = LK::IffRight(Box::new([lprf, rprf]), sequent.clone());
+                        checked_sequents.insert(sequent.clone(), Ok(prf.clone()));
+                        return Ok(prf);
+                    }
+                }
                 Exists(term, bfml) => {
                     let mut parent_suc = sequent.succedent.clone();
                     let len = parent_suc.len();
@@ -446,6 +649,9 @@ fn _prove_with_lk(
                         let terms = fml.get_subterms();
                         substitutible_terms.extend(terms);
                     }
+                    let substitutible_terms = _order_by_score(substitutible_terms, |t| {
+                        heuristic.score(&parent.suc_last().substitute(term.clone(), t.clone()))
+                    });
                     for t in substitutible_terms {
                         if parent.suc_last().is_substitutible(term.clone(), t.clone()) {
                             let tmp_fml = parent.suc_last().substitute(term.clone(), t.clone());
@@ -456,6 +662,7 @@ fn _prove_with_lk(
                                 &tmp_sequent,
                                 max_depth - 1,
                                 use_cut,
+                                heuristic,
                                 checked_sequents,
                             ) {
                                 let prf = LK::ExistsRight(Box::new(subprf), sequent.clone());
@@ -477,6 +684,8 @@ fn _prove_with_lk(
                     for fml in [sequent.antecedent.clone(), sequent.succedent.clone()].concat() {
                         let vars = fml.get_free_vars();
                         free_vars.extend(vars);
+                        // Bound variables too, or substituting would capture.
+                        free_vars.extend(fml.get_bound_vars());
                     }
                     let mut idx = 1;
                     while free_vars.contains(&var!(format!("x{}", idx))) {
@@ -487,9 +696,13 @@ fn _prove_with_lk(
                     let mut tmp_sequent = parent.clone();
                     let len = tmp_sequent.succedent.len();
                     tmp_sequent.succedent[len - 1] = tmp_fml;
-                    if let Ok(subprf) =
-                        _prove_with_lk(&tmp_sequent, max_depth - 1, use_cut, checked_sequents)
-                    {
+                    if let Ok(subprf) = _prove_with_lk(
+                        &tmp_sequent,
+                        max_depth - 1,
+                        use_cut,
+                        heuristic,
+                        checked_sequents,
+                    ) {
                         let prf = LK::ForallRight(Box::new(subprf), sequent.clone());
                         checked_sequents.insert(sequent.clone(), Ok(prf.clone()));
                         return Ok(prf);
@@ -501,9 +714,13 @@ fn _prove_with_lk(
         if sequent.antecedent.len() > 0 {
             let mut parent_sequent = sequent.clone();
             parent_sequent.antecedent = sequent.ant_but_first().to_vec();
-            if let Ok(subprf) =
-                _prove_with_lk(&parent_sequent, max_depth - 1, use_cut, checked_sequents)
-            {
+            if let Ok(subprf) = _prove_with_lk(
+                &parent_sequent,
+                max_depth - 1,
+                use_cut,
+                heuristic,
+                checked_sequents,
+            ) {
                 let prf = LK::WeakeningLeft(Box::new(subprf), sequent.clone());
                 checked_sequents.insert(sequent.clone(), Ok(prf.clone()));
                 return Ok(prf);
@@ -512,9 +729,13 @@ fn _prove_with_lk(
             parent_sequent = sequent.clone();
             parent_sequent.antecedent = vec![sequent.ant_first().clone()];
             parent_sequent.antecedent.extend(sequent.antecedent.clone());
-            if let Ok(subprf) =
-                _prove_with_lk(&parent_sequent, max_depth - 1, use_cut, checked_sequents)
-            {
+            if let Ok(subprf) = _prove_with_lk(
+                &parent_sequent,
+                max_depth - 1,
+                use_cut,
+                heuristic,
+                checked_sequents,
+            ) {
                 let prf = LK::ContractionLeft(Box::new(subprf), sequent.clone());
                 checked_sequents.insert(sequent.clone(), Ok(prf.clone()));
                 return Ok(prf);
@@ -523,9 +744,13 @@ fn _prove_with_lk(
         if sequent.succedent.len() > 0 {
             let mut parent_sequent = sequent.clone();
             parent_sequent.succedent = parent_sequent.suc_but_last().to_vec();
-            if let Ok(subprf) =
-                _prove_with_lk(&parent_sequent, max_depth - 1, use_cut, checked_sequents)
-            {
+            if let Ok(subprf) = _prove_with_lk(
+                &parent_sequent,
+                max_depth - 1,
+                use_cut,
+                heuristic,
+                checked_sequents,
+            ) {
                 let prf = LK::WeakeningRight(Box::new(subprf), sequent.clone());
                 checked_sequents.insert(sequent.clone(), Ok(prf.clone()));
                 return Ok(prf);
@@ -533,9 +758,13 @@ fn _prove_with_lk(
 
             let mut parent_sequent = sequent.clone();
             parent_sequent.succedent.push(sequent.suc_last().clone());
-            if let Ok(subprf) =
-                _prove_with_lk(&parent_sequent, max_depth - 1, use_cut, checked_sequents)
-            {
+            if let Ok(subprf) = _prove_with_lk(
+                &parent_sequent,
+                max_depth - 1,
+                use_cut,
+                heuristic,
+                checked_sequents,
+            ) {
                 let prf = LK::ContractionRight(Box::new(subprf), sequent.clone());
                 checked_sequents.insert(sequent.clone(), Ok(prf.clone()));
                 return Ok(prf);
@@ -545,9 +774,13 @@ fn _prove_with_lk(
             for idx in 0..sequent.antecedent.len() - 1 {
                 let mut tmp_sequent = sequent.clone();
                 tmp_sequent.antecedent.swap(idx, idx + 1);
-                if let Ok(subprf) =
-                    _prove_with_lk(&tmp_sequent, max_depth - 1, use_cut, checked_sequents)
-                {
+                if let Ok(subprf) = _prove_with_lk(
+                    &tmp_sequent,
+                    max_depth - 1,
+                    use_cut,
+                    heuristic,
+                    checked_sequents,
+                ) {
                     let prf = LK::ExchangeLeft(Box::new(subprf), sequent.clone());
                     checked_sequents.insert(sequent.clone(), Ok(prf.clone()));
                     return Ok(prf);
@@ -558,9 +791,13 @@ fn _prove_with_lk(
             for idx in 0..sequent.succedent.len() - 1 {
                 let mut tmp_sequent = sequent.clone();
                 tmp_sequent.succedent.swap(idx, idx + 1);
-                if let Ok(subprf) =
-                    _prove_with_lk(&tmp_sequent, max_depth - 1, use_cut, checked_sequents)
-                {
+                if let Ok(subprf) = _prove_with_lk(
+                    &tmp_sequent,
+                    max_depth - 1,
+                    use_cut,
+                    heuristic,
+                    checked_sequents,
+                ) {
                     let prf = LK::ExchangeRight(Box::new(subprf), sequent.clone());
                     checked_sequents.insert(sequent.clone(), Ok(prf.clone()));
                     return Ok(prf);
@@ -570,7 +807,7 @@ fn _prove_with_lk(
         if use_cut {
             let left_len = sequent.antecedent.len();
             let right_len = sequent.succedent.len();
-            let subfmls = sequent.get_subformulas();
+            let subfmls = _order_by_score(sequent.get_subformulas(), |f| heuristic.score(f));
             for l in 0..left_len + 1 {
                 let gamma = sequent.antecedent[..l].to_vec();
                 let pi = sequent.antecedent[l..].to_vec();
@@ -589,11 +826,18 @@ fn _prove_with_lk(
                         };
                         right_sequent.antecedent.extend(pi.clone());
                         if let (Ok(lprf), Ok(rprf)) = (
-                            _prove_with_lk(&left_sequent, max_depth - 1, use_cut, checked_sequents),
+                            _prove_with_lk(
+                                &left_sequent,
+                                max_depth - 1,
+                                use_cut,
+                                heuristic,
+                                checked_sequents,
+                            ),
                             _prove_with_lk(
                                 &right_sequent,
                                 max_depth - 1,
                                 use_cut,
+                                heuristic,
                                 checked_sequents,
                             ),
                         ) {
@@ -622,12 +866,162 @@ fn _prove_with_lk(
 }
 
 pub fn prove_with_lk(fml: Formula, max_depth: u32, use_cut: bool) -> Result<LK, u32> {
-    let sequent = sequent!( => fml);
+    prove_with_lk_guided(fml, max_depth, use_cut, &Unguided)
+}
+
+/// Same as `prove_with_lk`, but tries cut formulas and quantifier instances in
+/// the order given by `heuristic`.
+pub fn prove_with_lk_guided(
+    fml: Formula,
+    max_depth: u32,
+    use_cut: bool,
+    heuristic: &dyn Heuristic,
+) -> Result<LK, u32> {
     let mut checked_sequents = hashmap![];
+    _iterative_deepening(
+        sequent!( => fml),
+        max_depth,
+        use_cut,
+        heuristic,
+        &mut checked_sequents,
+    )
+}
+
+/// Proves an arbitrary sequent `Γ ⇒ Δ` instead of a single formula.
+pub fn prove_sequent_with_lk(sequent: Sequent, max_depth: u32, use_cut: bool) -> Result<LK, u32> {
+    let mut checked_sequents = hashmap![];
+    _iterative_deepening(
+        sequent,
+        max_depth,
+        use_cut,
+        &Unguided,
+        &mut checked_sequents,
+    )
+}
+
+/// Common interface of the proof search procedures.
+pub trait Prover {
+    fn prove_sequent(&self, goal: &Sequent) -> Option<LK>;
+
+    /// Proves `goal` from the axioms of `theory`.
+    fn prove_in(&self, theory: &Theory, goal: &Formula) -> Option<LK> {
+        self.prove_sequent(&theory.sequent(goal.clone()))
+    }
+}
+
+/// The search of `prove_sequent_with_lk`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LkProver {
+    pub max_depth: u32,
+    pub use_cut: bool,
+}
+
+impl Default for LkProver {
+    fn default() -> LkProver {
+        LkProver {
+            max_depth: 8,
+            use_cut: false,
+        }
+    }
+}
+
+impl Prover for LkProver {
+    fn prove_sequent(&self, goal: &Sequent) -> Option<LK> {
+        prove_sequent_with_lk(goal.clone(), self.max_depth, self.use_cut).ok()
+    }
+}
+
+/// Maximum proof depth searched by `hint`.
+pub const HINT_DEPTH: u32 = 6;
+
+/// First step of a proof: the rule to apply to the goal and the subgoals it
+/// leaves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    pub rule: &'static str,
+    pub subgoals: Vec<Sequent>,
+}
+
+/// Recommends the rule to apply to `goal` without revealing the rest of the
+/// proof, or `None` if no proof of depth `HINT_DEPTH` exists.
+pub fn hint(goal: &Sequent) -> Option<Hint> {
+    let proof = prove_sequent_with_lk(goal.clone(), HINT_DEPTH, false).ok()?;
+    Some(Hint {
+        rule: proof.rule_name(),
+        subgoals: proof.premises().iter().map(|p| p.last().clone()).collect(),
+    })
+}
+
+// One round of iterative deepening, reusing the results of earlier rounds
+// kept in `checked_sequents`.
+pub(crate) fn prove_at_depth(
+    sequent: &Sequent,
+    depth: u32,
+    use_cut: bool,
+    checked_sequents: &mut HashMap<Sequent, Result<LK, u32>>,
+) -> Result<LK, u32> {
+    _prove_with_lk(sequent, depth, use_cut, &Unguided, checked_sequents)
+}
+
+fn _iterative_deepening(
+    sequent: Sequent,
+    max_depth: u32,
+    use_cut: bool,
+    heuristic: &dyn Heuristic,
+    checked_sequents: &mut HashMap<Sequent, Result<LK, u32>>,
+) -> Result<LK, u32> {
     for d in 1..max_depth + 1 {
-        if let p @ Ok(_) = _prove_with_lk(&sequent, d, use_cut, &mut checked_sequents) {
+        if let p @ Ok(_) = _prove_with_lk(&sequent, d, use_cut, heuristic, checked_sequents) {
             return p;
         }
     }
     Err(max_depth)
 }
+
+/// Same as `prove_with_lk_guided`, additionally returning every state the
+/// search explored when a proof is found (see `record::ProofStep` for the
+/// exported schema). No steps are returned for unsuccessful searches.
+pub fn prove_with_lk_recorded(
+    fml: Formula,
+    max_depth: u32,
+    use_cut: bool,
+    heuristic: &dyn Heuristic,
+) -> (Result<LK, u32>, Vec<ProofStep>) {
+    let mut checked_sequents = hashmap![];
+    let result = _iterative_deepening(
+        sequent!( => fml),
+        max_depth,
+        use_cut,
+        heuristic,
+        &mut checked_sequents,
+    );
+    let steps = match &result {
+        Ok(proof) => steps_from_search(checked_sequents.iter(), Some(proof)),
+        Err(_) => vec![],
+    };
+    (result, steps)
+}
+
+/// Same as `prove_with_lk_recorded`, also recording which goal each
+/// explored state was generated from, so `SearchTrace::explain_node` can
+/// tell how the search got there. States are recorded for unsuccessful
+/// searches too.
+pub fn prove_with_lk_traced(
+    fml: Formula,
+    max_depth: u32,
+    use_cut: bool,
+    heuristic: &dyn Heuristic,
+) -> (Result<LK, u32>, SearchTrace) {
+    let mut checked_sequents = hashmap![];
+    TRACE.with(|trace| *trace.borrow_mut() = Some(Tracer::default()));
+    let result = _iterative_deepening(
+        sequent!( => fml),
+        max_depth,
+        use_cut,
+        heuristic,
+        &mut checked_sequents,
+    );
+    let tracer = TRACE.with(|trace| trace.borrow_mut().take()).unwrap();
+    let steps = steps_from_search(checked_sequents.iter(), result.as_ref().ok());
+    (result, SearchTrace::new(steps, tracer.edges))
+}
@@ -0,0 +1,111 @@
+use crate::language::*;
+use crate::proof::*;
+use crate::signature::*;
+use std::collections::HashMap;
+
+pub type Sort = String;
+
+/// Sorts of the variables and symbols for `well_sorted`. Variables are
+/// sorted by name, so all variables named `x`, free or bound, share a sort.
+/// Variables and symbols without a sort are unsorted and fit anywhere.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SortEnv {
+    pub signature: Signature,
+    pub variables: HashMap<String, Sort>,
+}
+
+impl SortEnv {
+    pub fn new(signature: Signature) -> SortEnv {
+        SortEnv {
+            signature,
+            variables: HashMap::new(),
+        }
+    }
+
+    pub fn declare_variable(&mut self, name: &str, sort: &str) {
+        self.variables.insert(name.into(), sort.into());
+    }
+}
+
+fn _check_args(
+    terms: &[Term],
+    sorts: Option<&Vec<Sort>>,
+    env: &SortEnv,
+    error: &'static str,
+) -> Result<(), &'static str> {
+    for (i, term) in terms.iter().enumerate() {
+        let sort = term.sort_in(env)?;
+        if let (Some(sort), Some(expected)) = (sort, sorts.map(|sorts| &sorts[i])) {
+            if sort != *expected {
+                return Err(error);
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Term {
+    /// The sort of the term in `env`, `None` if it is unsorted. Fails if an
+    /// argument has a sort other than the declared one.
+    pub fn sort_in(&self, env: &SortEnv) -> Result<Option<Sort>, &'static str> {
+        match self {
+            Term::Var(name) => Ok(env.variables.get(name).cloned()),
+            Term::Func(name, terms) => {
+                let sorts = env
+                    .signature
+                    .function_sorts
+                    .get(&nlsym!(name.as_str(), terms.len() as u32));
+                _check_args(
+                    terms,
+                    sorts.map(|(args, _)| args),
+                    env,
+                    "Function argument of the wrong sort.",
+                )?;
+                Ok(sorts.map(|(_, result)| result.clone()))
+            }
+        }
+    }
+}
+
+impl Formula {
+    /// Whether the arguments of every function and predicate symbol have the
+    /// declared sorts and both sides of every equality have the same sort.
+    pub fn well_sorted(&self, env: &SortEnv) -> Result<(), &'static str> {
+        match self {
+            Formula::Pred(name, terms) => _check_args(
+                terms,
+                env.signature
+                    .predicate_sorts
+                    .get(&nlsym!(name.as_str(), terms.len() as u32)),
+                env,
+                "Predicate argument of the wrong sort.",
+            ),
+            Formula::Equal(lhs, rhs) => match (lhs.sort_in(env)?, rhs.sort_in(env)?) {
+                (Some(lhs), Some(rhs)) if lhs != rhs => {
+                    Err("Equality between terms of different sorts.")
+                }
+                _ => Ok(()),
+            },
+            Formula::Not(fml) | Formula::Forall(_, fml) | Formula::Exists(_, fml) => {
+                fml.well_sorted(env)
+            }
+            Formula::And(lhs, rhs)
+            | Formula::Or(lhs, rhs)
+            | Formula::Implies(lhs, rhs)
+            | Formula::Iff(lhs, rhs) => {
+                lhs.well_sorted(env)?;
+                rhs.well_sorted(env)
+            }
+            Formula::Top | Formula::Bottom => Ok(()),
+        }
+    }
+}
+
+impl Sequent {
+    pub fn well_sorted(&self, env: &SortEnv) -> Result<(), &'static str> {
+        self.antecedent
+            .iter()
+            .chain(&self.succedent)
+            .try_for_each(|fml| fml.well_sorted(env))
+    }
+}
@@ -0,0 +1,109 @@
+use crate::language::*;
+use crate::proof::*;
+use crate::solver::*;
+
+/// Search limits for the entailment checks of this module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub max_proof_depth: u32,
+    pub max_domain_size: u32,
+}
+
+impl Default for Bounds {
+    fn default() -> Bounds {
+        Bounds {
+            max_proof_depth: 6,
+            max_domain_size: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Entailment {
+    /// An LK proof was found.
+    Proved,
+    /// A finite countermodel was found.
+    Refuted,
+    /// Neither within the bounds.
+    Unknown,
+}
+
+/// Logical strength of one hypothesis relative to another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Strength {
+    Equivalent,
+    Stronger,
+    Weaker,
+    /// Neither entails the other.
+    Incomparable,
+    /// Some entailment could be neither proved nor refuted.
+    Unknown,
+}
+
+/// Whether `theory, hypothesis ⇒ goal` is provable.
+pub fn entails(
+    theory: &[Formula],
+    hypothesis: &Formula,
+    goal: &Formula,
+    bounds: Bounds,
+) -> Entailment {
+    let mut antecedent = theory.to_vec();
+    antecedent.push(hypothesis.clone());
+    let sequent = Sequent {
+        antecedent,
+        succedent: vec![goal.clone()],
+    };
+    if refute_on_finite_models(sequent.to_formula(), bounds.max_domain_size).is_some() {
+        Entailment::Refuted
+    } else if prove_sequent_with_lk(sequent, bounds.max_proof_depth, false).is_ok() {
+        Entailment::Proved
+    } else {
+        Entailment::Unknown
+    }
+}
+
+/// Compares `lhs` with `rhs` by proving or refuting the entailment in both
+/// directions relative to `theory`.
+pub fn compare_strength(
+    theory: &[Formula],
+    lhs: &Formula,
+    rhs: &Formula,
+    bounds: Bounds,
+) -> Strength {
+    use Entailment::*;
+    match (
+        entails(theory, lhs, rhs, bounds),
+        entails(theory, rhs, lhs, bounds),
+    ) {
+        (Proved, Proved) => Strength::Equivalent,
+        (Proved, Refuted) => Strength::Stronger,
+        (Refuted, Proved) => Strength::Weaker,
+        (Refuted, Refuted) => Strength::Incomparable,
+        _ => Strength::Unknown,
+    }
+}
+
+/// The candidates sufficient to prove `goal` relative to `theory` that are
+/// not provably stronger than another sufficient candidate, in their
+/// original order. Equivalent candidates are all kept.
+pub fn weakest_among(
+    candidates: &[Formula],
+    goal: &Formula,
+    theory: &[Formula],
+    bounds: Bounds,
+) -> Vec<Formula> {
+    let sufficient = candidates
+        .iter()
+        .filter(|c| entails(theory, c, goal, bounds) == Entailment::Proved)
+        .collect::<Vec<_>>();
+    sufficient
+        .iter()
+        .filter(|c| {
+            !sufficient.iter().any(|d| {
+                entails(theory, c, d, bounds) == Entailment::Proved
+                    && entails(theory, d, c, bounds) != Entailment::Proved
+            })
+        })
+        .map(|c| (*c).clone())
+        .collect()
+}
@@ -0,0 +1,140 @@
+use crate::fresh::*;
+use crate::language::*;
+use std::collections::{HashMap, HashSet};
+use std::iter::FromIterator;
+
+/// A finite map from variables to terms, applied simultaneously.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Substitution {
+    pub(crate) map: HashMap<Term, Term>,
+}
+
+impl FromIterator<(Term, Term)> for Substitution {
+    fn from_iter<I: IntoIterator<Item = (Term, Term)>>(iter: I) -> Substitution {
+        let mut sub = Substitution::new();
+        for (var, term) in iter {
+            sub.insert(var, term);
+        }
+        sub
+    }
+}
+
+impl Substitution {
+    pub fn new() -> Substitution {
+        Substitution {
+            map: HashMap::new(),
+        }
+    }
+
+    /// Maps `var` to `term`, replacing its previous binding. Binding a
+    /// variable to itself removes it.
+    pub fn insert(&mut self, var: Term, term: Term) {
+        if var == term {
+            self.map.remove(&var);
+        } else {
+            self.map.insert(var, term);
+        }
+    }
+
+    pub fn get(&self, var: &Term) -> Option<&Term> {
+        self.map.get(var)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Term, &Term)> {
+        self.map.iter()
+    }
+
+    pub fn apply_to_term(&self, term: &Term) -> Term {
+        match term {
+            Term::Var(_) => self.map.get(term).cloned().unwrap_or_else(|| term.clone()),
+            Term::Func(name, terms) => Term::Func(
+                name.clone(),
+                terms.iter().map(|t| self.apply_to_term(t)).collect(),
+            ),
+        }
+    }
+
+    /// Replaces the free occurrences of the variables. Bound variables that
+    /// would capture a variable of an inserted term are renamed apart.
+    pub fn apply_to_formula(&self, fml: &Formula) -> Formula {
+        let sub = |fml: &Formula| Box::new(self.apply_to_formula(fml));
+        match fml {
+            Formula::Pred(name, terms) => Formula::Pred(
+                name.clone(),
+                terms.iter().map(|t| self.apply_to_term(t)).collect(),
+            ),
+            Formula::Equal(lhs, rhs) => {
+                Formula::Equal(self.apply_to_term(lhs), self.apply_to_term(rhs))
+            }
+            Formula::Not(fml) => Formula::Not(sub(fml)),
+            Formula::And(lhs, rhs) => Formula::And(sub(lhs), sub(rhs)),
+            Formula::Or(lhs, rhs) => Formula::Or(sub(lhs), sub(rhs)),
+            Formula::Implies(lhs, rhs) => Formula::Implies(sub(lhs), sub(rhs)),
+            Formula::Iff(lhs, rhs) => Formula::Iff(sub(lhs), sub(rhs)),
+            Formula::Forall(var, body) | Formula::Exists(var, body) => {
+                let mut inner = self.restrict(&body.get_free_vars());
+                inner.map.remove(var);
+                let mut var = var.clone();
+                if inner.map.values().any(|t| t.get_vars().contains(&var)) {
+                    let mut symbols = SymbolGenerator::new();
+                    symbols.avoid(body);
+                    inner.map.values().for_each(|t| symbols.avoid_term(t));
+                    let new_var = match &var {
+                        Term::Var(name) => symbols.fresh_var(name),
+                        _ => symbols.fresh_var("x"),
+                    };
+                    inner.map.insert(var, new_var.clone());
+                    var = new_var;
+                }
+                let body = Box::new(inner.apply_to_formula(body));
+                match fml {
+                    Formula::Forall(_, _) => Formula::Forall(var, body),
+                    _ => Formula::Exists(var, body),
+                }
+            }
+            Formula::Top | Formula::Bottom => fml.clone(),
+        }
+    }
+
+    /// The substitution applying `self` first and `other` afterwards, i.e.
+    /// `self.compose(other).apply_to_term(t) == other.apply_to_term(&self.apply_to_term(t))`.
+    pub fn compose(&self, other: &Substitution) -> Substitution {
+        let mut sub = other.clone();
+        for (var, term) in &self.map {
+            sub.insert(var.clone(), other.apply_to_term(term));
+        }
+        sub
+    }
+
+    /// The bindings of the variables in `vars` only.
+    pub fn restrict(&self, vars: &HashSet<Term>) -> Substitution {
+        Substitution {
+            map: self
+                .map
+                .iter()
+                .filter(|(var, _)| vars.contains(var))
+                .map(|(var, term)| (var.clone(), term.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl Term {
+    pub fn substitute_with(&self, sub: &Substitution) -> Term {
+        sub.apply_to_term(self)
+    }
+}
+
+impl Formula {
+    pub fn substitute_with(&self, sub: &Substitution) -> Formula {
+        sub.apply_to_formula(self)
+    }
+}
@@ -0,0 +1,474 @@
+use crate::congruence::*;
+use crate::language::*;
+use crate::proof::*;
+use crate::prover::*;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Goal {
+    sequent: Sequent,
+    // The rule closing the goal and its subgoals, once a tactic is applied.
+    rule: Option<&'static str>,
+    subgoals: Vec<usize>,
+    // A whole proof of the goal, closed by a decision procedure instead.
+    proof: Option<LK>,
+}
+
+/// A proof under construction from the end sequent upwards. Tactics apply a
+/// rule backwards to the current goal, replacing it with the premises of
+/// the rule as new goals, until `qed` assembles the proof once no goals are
+/// left open.
+///
+/// The principal formula of a left rule is the first formula of the
+/// antecedent and that of a right rule the last of the succedent, as in
+/// `LK`. Exchange, weakening and contraction move formulas into place.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProofState {
+    goals: Vec<Goal>,
+    // Open goals, the current one first.
+    open: Vec<usize>,
+}
+
+fn _sequent(antecedent: Vec<Formula>, succedent: Vec<Formula>) -> Sequent {
+    Sequent {
+        antecedent,
+        succedent,
+    }
+}
+
+impl ProofState {
+    pub fn new(goal: Sequent) -> ProofState {
+        ProofState {
+            goals: vec![Goal {
+                sequent: goal,
+                rule: None,
+                subgoals: vec![],
+                proof: None,
+            }],
+            open: vec![0],
+        }
+    }
+
+    /// The goal tactics apply to.
+    pub fn current(&self) -> Option<&Sequent> {
+        self.open.first().map(|&g| &self.goals[g].sequent)
+    }
+
+    /// The open goals, the current one first.
+    pub fn goals(&self) -> Vec<&Sequent> {
+        self.open.iter().map(|&g| &self.goals[g].sequent).collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.open.is_empty()
+    }
+
+    /// Makes the next open goal the current one, moving the current goal
+    /// last.
+    pub fn defer(&mut self) {
+        if !self.open.is_empty() {
+            self.open.rotate_left(1);
+        }
+    }
+
+    fn _goal(&self) -> Result<&Sequent, &'static str> {
+        self.current().ok_or("No open goals.")
+    }
+
+    fn _left(&self) -> Result<(&Formula, &[Formula], &[Formula]), &'static str> {
+        let goal = self._goal()?;
+        match goal.antecedent.first() {
+            Some(fml) => Ok((fml, goal.ant_but_first(), &goal.succedent)),
+            None => Err("Rule does not apply to the goal."),
+        }
+    }
+
+    fn _right(&self) -> Result<(&Formula, &[Formula], &[Formula]), &'static str> {
+        let goal = self._goal()?;
+        match goal.succedent.last() {
+            Some(fml) => Ok((fml, &goal.antecedent, goal.suc_but_last())),
+            None => Err("Rule does not apply to the goal."),
+        }
+    }
+
+    // Closes the current goal with `rule` from `premises`, which become the
+    // new goals in front of the others.
+    fn _apply(&mut self, rule: &'static str, premises: Vec<Sequent>) -> Result<(), &'static str> {
+        let goal = self._goal()?.clone();
+        let shallow = premises.iter().cloned().map(LK::Axiom).collect();
+        if !LK::from_parts(rule, shallow, goal).is_some_and(|lk| lk.is_valid_inference()) {
+            return Err("Rule does not apply to the goal.");
+        }
+        let current = self.open.remove(0);
+        let subgoals = (self.goals.len()..self.goals.len() + premises.len()).collect::<Vec<_>>();
+        self.goals.extend(premises.into_iter().map(|sequent| Goal {
+            sequent,
+            rule: None,
+            subgoals: vec![],
+            proof: None,
+        }));
+        self.goals[current].rule = Some(rule);
+        self.goals[current].subgoals = subgoals.clone();
+        self.open.splice(0..0, subgoals);
+        Ok(())
+    }
+
+    /// Closes the current goal if it is an axiom.
+    pub fn axiom(&mut self) -> Result<(), &'static str> {
+        let goal = self._goal()?.clone();
+        if !LK::Axiom(goal).is_valid_inference() {
+            return Err("Goal is not an axiom.");
+        }
+        self._apply("Axiom", vec![])
+    }
+
+    /// Closes the current goal if an equation or atom of its succedent
+    /// follows from the equations of its antecedent, see
+    /// `prove_by_congruence`.
+    pub fn congruence(&mut self) -> Result<(), &'static str> {
+        let proof =
+            prove_by_congruence(self._goal()?).ok_or("Goal does not follow by congruence.")?;
+        let current = self.open.remove(0);
+        self.goals[current].proof = Some(proof);
+        Ok(())
+    }
+
+    /// Closes the current goal with a proof found by `prover`.
+    pub fn prove(&mut self, prover: &FirstOrderProver) -> Result<(), &'static str> {
+        let proof = prover
+            .search(self._goal()?)
+            .map_err(|_| "Prover found no proof of the goal.")?;
+        let current = self.open.remove(0);
+        self.goals[current].proof = Some(proof);
+        Ok(())
+    }
+
+    pub fn apply_and_left1(&mut self) -> Result<(), &'static str> {
+        let (fml, rest, suc) = self._left()?;
+        let premise = match fml {
+            Formula::And(lhs, _) => _sequent([&[(**lhs).clone()], rest].concat(), suc.to_vec()),
+            _ => return Err("Rule does not apply to the goal."),
+        };
+        self._apply("AndLeft1", vec![premise])
+    }
+
+    pub fn apply_and_left2(&mut self) -> Result<(), &'static str> {
+        let (fml, rest, suc) = self._left()?;
+        let premise = match fml {
+            Formula::And(_, rhs) => _sequent([&[(**rhs).clone()], rest].concat(), suc.to_vec()),
+            _ => return Err("Rule does not apply to the goal."),
+        };
+        self._apply("AndLeft2", vec![premise])
+    }
+
+    pub fn apply_and_right(&mut self) -> Result<(), &'static str> {
+        let (fml, ant, rest) = self._right()?;
+        let premises = match fml {
+            Formula::And(lhs, rhs) => vec![
+                _sequent(ant.to_vec(), [rest, &[(**lhs).clone()]].concat()),
+                _sequent(ant.to_vec(), [rest, &[(**rhs).clone()]].concat()),
+            ],
+            _ => return Err("Rule does not apply to the goal."),
+        };
+        self._apply("AndRight", premises)
+    }
+
+    pub fn apply_or_left(&mut self) -> Result<(), &'static str> {
+        let (fml, rest, suc) = self._left()?;
+        let premises = match fml {
+            Formula::Or(lhs, rhs) => vec![
+                _sequent([&[(**lhs).clone()], rest].concat(), suc.to_vec()),
+                _sequent([&[(**rhs).clone()], rest].concat(), suc.to_vec()),
+            ],
+            _ => return Err("Rule does not apply to the goal."),
+        };
+        self._apply("OrLeft", premises)
+    }
+
+    pub fn apply_or_right1(&mut self) -> Result<(), &'static str> {
+        let (fml, ant, rest) = self._right()?;
+        let premise = match fml {
+            Formula::Or(lhs, _) => _sequent(ant.to_vec(), [rest, &[(**lhs).clone()]].concat()),
+            _ => return Err("Rule does not apply to the goal."),
+        };
+        self._apply("OrRight1", vec![premise])
+    }
+
+    pub fn apply_or_right2(&mut self) -> Result<(), &'static str> {
+        let (fml, ant, rest) = self._right()?;
+        let premise = match fml {
+            Formula::Or(_, rhs) => _sequent(ant.to_vec(), [rest, &[(**rhs).clone()]].concat()),
+            _ => return Err("Rule does not apply to the goal."),
+        };
+        self._apply("OrRight2", vec![premise])
+    }
+
+    /// Splits the context: the first `gamma` formulas left of the
+    /// implication and the first `delta` of the succedent go to the left
+    /// premise, the rest to the right one.
+    pub fn apply_implies_left(&mut self, gamma: usize, delta: usize) -> Result<(), &'static str> {
+        let (fml, rest, suc) = self._left()?;
+        if gamma > rest.len() || delta > suc.len() {
+            return Err("Context is too short for the split.");
+        }
+        let premises = match fml {
+            Formula::Implies(lhs, rhs) => vec![
+                _sequent(
+                    rest[..gamma].to_vec(),
+                    [&suc[..delta], &[(**lhs).clone()]].concat(),
+                ),
+                _sequent(
+                    [&[(**rhs).clone()], &rest[gamma..]].concat(),
+                    suc[delta..].to_vec(),
+                ),
+            ],
+            _ => return Err("Rule does not apply to the goal."),
+        };
+        self._apply("ImpliesLeft", premises)
+    }
+
+    pub fn apply_implies_right(&mut self) -> Result<(), &'static str> {
+        let (fml, ant, rest) = self._right()?;
+        let premise = match fml {
+            Formula::Implies(lhs, rhs) => _sequent(
+                [&[(**lhs).clone()], ant].concat(),
+                [rest, &[(**rhs).clone()]].concat(),
+            ),
+            _ => return Err("Rule does not apply to the goal."),
+        };
+        self._apply("ImpliesRight", vec![premise])
+    }
+
+    pub fn apply_iff_left(&mut self) -> Result<(), &'static str> {
+        let (fml, rest, suc) = self._left()?;
+        let premises = match fml {
+            Formula::Iff(lhs, rhs) => vec![
+                _sequent(
+                    [&[(**lhs).clone(), (**rhs).clone()], rest].concat(),
+                    suc.to_vec(),
+                ),
+                _sequent(
+                    rest.to_vec(),
+                    [suc, &[(**lhs).clone(), (**rhs).clone()]].concat(),
+                ),
+            ],
+            _ => return Err("Rule does not apply to the goal."),
+        };
+        self._apply("IffLeft", premises)
+    }
+
+    pub fn apply_iff_right(&mut self) -> Result<(), &'static str> {
+        let (fml, ant, rest) = self._right()?;
+        let premises = match fml {
+            Formula::Iff(lhs, rhs) => vec![
+                _sequent(
+                    [&[(**lhs).clone()], ant].concat(),
+                    [rest, &[(**rhs).clone()]].concat(),
+                ),
+                _sequent(
+                    [&[(**rhs).clone()], ant].concat(),
+                    [rest, &[(**lhs).clone()]].concat(),
+                ),
+            ],
+            _ => return Err("Rule does not apply to the goal."),
+        };
+        self._apply("IffRight", premises)
+    }
+
+    pub fn apply_not_left(&mut self) -> Result<(), &'static str> {
+        let (fml, rest, suc) = self._left()?;
+        let premise = match fml {
+            Formula::Not(fml) => _sequent(rest.to_vec(), [suc, &[(**fml).clone()]].concat()),
+            _ => return Err("Rule does not apply to the goal."),
+        };
+        self._apply("NotLeft", vec![premise])
+    }
+
+    pub fn apply_not_right(&mut self) -> Result<(), &'static str> {
+        let (fml, ant, rest) = self._right()?;
+        let premise = match fml {
+            Formula::Not(fml) => _sequent([&[(**fml).clone()], ant].concat(), rest.to_vec()),
+            _ => return Err("Rule does not apply to the goal."),
+        };
+        self._apply("NotRight", vec![premise])
+    }
+
+    /// Instantiates the universal quantifier with `term`.
+    pub fn apply_forall_left(&mut self, term: Term) -> Result<(), &'static str> {
+        let (fml, rest, suc) = self._left()?;
+        let premise = match fml {
+            Formula::Forall(var, body) => _sequent(
+                [&[body.substitute(var.clone(), term)], rest].concat(),
+                suc.to_vec(),
+            ),
+            _ => return Err("Rule does not apply to the goal."),
+        };
+        self._apply("ForallLeft", vec![premise])
+    }
+
+    /// Replaces the quantified variable with the eigenvariable
+    /// `fresh_var`, which must not occur free in the goal.
+    pub fn apply_forall_right(&mut self, fresh_var: Term) -> Result<(), &'static str> {
+        let (fml, ant, rest) = self._right()?;
+        let premise = match fml {
+            Formula::Forall(var, body) => _sequent(
+                ant.to_vec(),
+                [rest, &[body.substitute(var.clone(), fresh_var.clone())]].concat(),
+            ),
+            _ => return Err("Rule does not apply to the goal."),
+        };
+        self._check_fresh(&fresh_var)?;
+        self._apply("ForallRight", vec![premise])
+    }
+
+    /// Replaces the quantified variable with the eigenvariable
+    /// `fresh_var`, which must not occur free in the goal.
+    pub fn apply_exists_left(&mut self, fresh_var: Term) -> Result<(), &'static str> {
+        let (fml, rest, suc) = self._left()?;
+        let premise = match fml {
+            Formula::Exists(var, body) => _sequent(
+                [&[body.substitute(var.clone(), fresh_var.clone())], rest].concat(),
+                suc.to_vec(),
+            ),
+            _ => return Err("Rule does not apply to the goal."),
+        };
+        self._check_fresh(&fresh_var)?;
+        self._apply("ExistsLeft", vec![premise])
+    }
+
+    /// Instantiates the existential quantifier with `term`.
+    pub fn apply_exists_right(&mut self, term: Term) -> Result<(), &'static str> {
+        let (fml, ant, rest) = self._right()?;
+        let premise = match fml {
+            Formula::Exists(var, body) => _sequent(
+                ant.to_vec(),
+                [rest, &[body.substitute(var.clone(), term)]].concat(),
+            ),
+            _ => return Err("Rule does not apply to the goal."),
+        };
+        self._apply("ExistsRight", vec![premise])
+    }
+
+    fn _check_fresh(&self, var: &Term) -> Result<(), &'static str> {
+        let goal = self._goal()?;
+        if !matches!(var, Term::Var(_)) {
+            return Err("Eigenvariable is not a variable.");
+        }
+        if goal
+            .antecedent
+            .iter()
+            .chain(&goal.succedent)
+            .any(|f| f.get_free_vars().contains(var))
+        {
+            return Err("Eigenvariable occurs free in the goal.");
+        }
+        Ok(())
+    }
+
+    /// Drops the first formula of the antecedent.
+    pub fn weaken_left(&mut self) -> Result<(), &'static str> {
+        let (_, rest, suc) = self._left()?;
+        let premise = _sequent(rest.to_vec(), suc.to_vec());
+        self._apply("WeakeningLeft", vec![premise])
+    }
+
+    /// Drops the last formula of the succedent.
+    pub fn weaken_right(&mut self) -> Result<(), &'static str> {
+        let (_, ant, rest) = self._right()?;
+        let premise = _sequent(ant.to_vec(), rest.to_vec());
+        self._apply("WeakeningRight", vec![premise])
+    }
+
+    /// Duplicates the first formula of the antecedent.
+    pub fn contract_left(&mut self) -> Result<(), &'static str> {
+        let goal = self._goal()?;
+        let fml = goal
+            .antecedent
+            .first()
+            .ok_or("Rule does not apply to the goal.")?;
+        let premise = _sequent(
+            [std::slice::from_ref(fml), &goal.antecedent[..]].concat(),
+            goal.succedent.clone(),
+        );
+        self._apply("ContractionLeft", vec![premise])
+    }
+
+    /// Duplicates the last formula of the succedent.
+    pub fn contract_right(&mut self) -> Result<(), &'static str> {
+        let goal = self._goal()?;
+        let fml = goal
+            .succedent
+            .last()
+            .ok_or("Rule does not apply to the goal.")?;
+        let premise = _sequent(
+            goal.antecedent.clone(),
+            [&goal.succedent[..], std::slice::from_ref(fml)].concat(),
+        );
+        self._apply("ContractionRight", vec![premise])
+    }
+
+    /// Swaps the antecedent formulas at `i` and `i + 1`.
+    pub fn exchange_left(&mut self, i: usize) -> Result<(), &'static str> {
+        let goal = self._goal()?;
+        if i + 1 >= goal.antecedent.len() {
+            return Err("Rule does not apply to the goal.");
+        }
+        let mut premise = goal.clone();
+        premise.antecedent.swap(i, i + 1);
+        self._apply("ExchangeLeft", vec![premise])
+    }
+
+    /// Swaps the succedent formulas at `i` and `i + 1`.
+    pub fn exchange_right(&mut self, i: usize) -> Result<(), &'static str> {
+        let goal = self._goal()?;
+        if i + 1 >= goal.succedent.len() {
+            return Err("Rule does not apply to the goal.");
+        }
+        let mut premise = goal.clone();
+        premise.succedent.swap(i, i + 1);
+        self._apply("ExchangeRight", vec![premise])
+    }
+
+    /// Cuts on `fml`, splitting the context like `apply_implies_left`.
+    pub fn cut(&mut self, fml: Formula, gamma: usize, delta: usize) -> Result<(), &'static str> {
+        let goal = self._goal()?;
+        if gamma > goal.antecedent.len() || delta > goal.succedent.len() {
+            return Err("Context is too short for the split.");
+        }
+        let premises = vec![
+            _sequent(
+                goal.antecedent[..gamma].to_vec(),
+                [&goal.succedent[..delta], std::slice::from_ref(&fml)].concat(),
+            ),
+            _sequent(
+                [&[fml], &goal.antecedent[gamma..]].concat(),
+                goal.succedent[delta..].to_vec(),
+            ),
+        ];
+        self._apply("Cut", premises)
+    }
+
+    /// The proof of the goal the state was started from, once every goal
+    /// is closed.
+    pub fn qed(&self) -> Result<LK, &'static str> {
+        if !self.is_complete() {
+            return Err("Goals remain open.");
+        }
+        let mut stack = vec![(0, false)];
+        let mut proofs = vec![];
+        while let Some((goal, expanded)) = stack.pop() {
+            let subgoals = &self.goals[goal].subgoals;
+            if !expanded {
+                stack.push((goal, true));
+                stack.extend(subgoals.iter().rev().map(|&g| (g, false)));
+                continue;
+            }
+            let premises = proofs.split_off(proofs.len() - subgoals.len());
+            let goal = &self.goals[goal];
+            proofs.push(match &goal.proof {
+                Some(proof) => proof.clone(),
+                None => LK::from_parts(goal.rule.unwrap(), premises, goal.sequent.clone()).unwrap(),
+            });
+        }
+        Ok(proofs.pop().unwrap())
+    }
+}
@@ -0,0 +1,244 @@
+use crate::language::*;
+use crate::proof::*;
+use crate::schema::*;
+use crate::substitution::*;
+use std::collections::HashMap;
+
+/// A set of axioms goals are proved from, either in the antecedent of the
+/// end sequent or as `LK::TheoryAxiom` initial sequents.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Theory {
+    pub axioms: Vec<Formula>,
+    /// Names of axioms by index, used in diagnostics.
+    pub names: HashMap<usize, String>,
+    /// Rules `LK::TheoryRule` inferences are instances of.
+    pub rules: Vec<SequentRule>,
+    /// Schemas whose instances are axioms too.
+    pub schemas: Vec<Schema>,
+}
+
+/// An initial sequent, without `premise`, or a unary rule of a theory.
+/// Unlike a `SchematicRule`, the context is implicit and the free variables
+/// are schematic: an instance substitutes terms for them and adds the same
+/// `Γ ⇒ Δ` to both sequents, after the antecedent and before the succedent
+/// of the schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SequentRule {
+    pub name: String,
+    pub premise: Option<Sequent>,
+    pub conclusion: Sequent,
+}
+
+fn _conjunction<'a>(fmls: impl DoubleEndedIterator<Item = &'a Formula>) -> Formula {
+    fmls.rev()
+        .cloned()
+        .reduce(|r, l| and!(l, r))
+        .unwrap_or(Formula::Top)
+}
+
+// The formulas of `sequent` at the positions of those of `schema`, and the
+// remaining context, if `sequent` is long enough.
+fn _split<'a>(
+    schema: &Sequent,
+    sequent: &'a Sequent,
+) -> Option<(Vec<&'a Formula>, [&'a [Formula]; 2])> {
+    let (ant, suc) = (&sequent.antecedent, &sequent.succedent);
+    let k = schema.antecedent.len();
+    let n = suc.len().checked_sub(schema.succedent.len())?;
+    if ant.len() < k {
+        return None;
+    }
+    let fmls = ant[..k].iter().chain(&suc[n..]).collect();
+    Some((fmls, [&ant[k..], &suc[..n]]))
+}
+
+// The ways of picking distinct formulas of `fmls` for each of `count`
+// positions.
+fn _picks(count: usize, fmls: &[Formula]) -> Vec<Vec<usize>> {
+    let mut picks = vec![vec![]];
+    for _ in 0..count {
+        picks = picks
+            .into_iter()
+            .flat_map(|pick: Vec<usize>| {
+                (0..fmls.len())
+                    .filter(|i| !pick.contains(i))
+                    .map(|i| [&pick[..], &[i]].concat())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+    }
+    picks
+}
+
+impl SequentRule {
+    // The formulas of the schema, those of the conclusion first.
+    fn _schema(&self) -> Vec<&Formula> {
+        let sequents = std::iter::once(&self.conclusion).chain(&self.premise);
+        sequents
+            .flat_map(|s| s.antecedent.iter().chain(&s.succedent))
+            .collect()
+    }
+
+    /// Whether the inference from `premises` to `conclusion` is an instance
+    /// of the rule.
+    pub fn is_instance(&self, premises: &[&Sequent], conclusion: &Sequent) -> bool {
+        if premises.len() != self.premise.iter().count() {
+            return false;
+        }
+        let (mut fmls, context) = match _split(&self.conclusion, conclusion) {
+            Some(split) => split,
+            None => return false,
+        };
+        if let (Some(schema), Some(premise)) = (&self.premise, premises.first()) {
+            match _split(schema, premise) {
+                Some((premise_fmls, premise_context)) if premise_context == context => {
+                    fmls.extend(premise_fmls)
+                }
+                _ => return false,
+            }
+        }
+        _conjunction(fmls.into_iter())
+            .matches(&_conjunction(self._schema().into_iter()))
+            .is_some()
+    }
+
+    /// The substitutions making each formula of the conclusion a different
+    /// formula on the same side of `sequent`.
+    pub fn matches_in(&self, sequent: &Sequent) -> Vec<Substitution> {
+        let schema = &self.conclusion;
+        let pattern = _conjunction(schema.antecedent.iter().chain(&schema.succedent));
+        let mut subs = vec![];
+        for ant in _picks(schema.antecedent.len(), &sequent.antecedent) {
+            for suc in _picks(schema.succedent.len(), &sequent.succedent) {
+                let fmls = (ant.iter().map(|&i| &sequent.antecedent[i]))
+                    .chain(suc.iter().map(|&i| &sequent.succedent[i]));
+                let target = _conjunction(fmls.collect::<Vec<_>>().into_iter());
+                subs.extend(target.matches(&pattern));
+            }
+        }
+        subs
+    }
+}
+
+impl From<Vec<Formula>> for Theory {
+    fn from(axioms: Vec<Formula>) -> Theory {
+        Theory {
+            axioms,
+            names: HashMap::new(),
+            rules: vec![],
+            schemas: vec![],
+        }
+    }
+}
+
+impl Theory {
+    pub fn new() -> Theory {
+        Theory::default()
+    }
+
+    pub fn add_axiom(&mut self, axiom: Formula) {
+        self.axioms.push(axiom);
+    }
+
+    pub fn add_named_axiom(&mut self, name: &str, axiom: Formula) {
+        self.names.insert(self.axioms.len(), name.into());
+        self.axioms.push(axiom);
+    }
+
+    pub fn add_rule(&mut self, rule: SequentRule) {
+        self.rules.push(rule);
+    }
+
+    pub fn add_schema(&mut self, schema: Schema) {
+        self.schemas.push(schema);
+    }
+
+    /// Adds `axiom` as rules named `name`, stripping its leading `∀` and
+    /// splitting it into sequents with `Formula::to_sequent_normal_form`. A
+    /// sequent `Π ⇒ A` with `Π` non-empty becomes the unary rule from
+    /// `A, Π, Γ ⇒ Δ` to `Π, Γ ⇒ Δ`, any other the initial sequent
+    /// `Π, Γ ⇒ Δ, Σ`.
+    pub fn add_axiom_as_rules(&mut self, name: &str, axiom: &Formula) {
+        let mut body = axiom;
+        while let Formula::Forall(_, fml) = body {
+            body = fml;
+        }
+        for sequent in body.to_sequent_normal_form() {
+            let premise = match &sequent.succedent[..] {
+                [fml] if !sequent.antecedent.is_empty() => Some(Sequent {
+                    antecedent: [std::slice::from_ref(fml), &sequent.antecedent[..]].concat(),
+                    succedent: vec![],
+                }),
+                _ => None,
+            };
+            let conclusion = match premise {
+                Some(_) => Sequent {
+                    antecedent: sequent.antecedent,
+                    succedent: vec![],
+                },
+                None => sequent,
+            };
+            self.rules.push(SequentRule {
+                name: name.into(),
+                premise,
+                conclusion,
+            });
+        }
+    }
+
+    /// Whether `fml` is an instance of an axiom: the axiom with some of its
+    /// leading `∀` removed and terms substituted for the variables they
+    /// bound and its free variables, or an instance of a schema.
+    pub fn has_instance(&self, fml: &Formula) -> bool {
+        if self.schemas.iter().any(|s| s.matches(fml).is_some()) {
+            return true;
+        }
+        self.axioms.iter().any(|axiom| {
+            let mut body = axiom;
+            loop {
+                if fml.matches(body).is_some() {
+                    return true;
+                }
+                match body {
+                    Formula::Forall(_, inner) => body = inner,
+                    _ => return false,
+                }
+            }
+        })
+    }
+
+    /// The instances of the schemas `A → B` whose conclusion `B` is in the
+    /// succedent of `goal` and which are not in its antecedent yet.
+    pub fn schema_instances(&self, goal: &Sequent) -> Vec<Formula> {
+        let mut instances = vec![];
+        for schema in &self.schemas {
+            for fml in &goal.succedent {
+                match schema.instance_concluding(fml) {
+                    Some(inst)
+                        if !goal.antecedent.contains(&inst) && !instances.contains(&inst) =>
+                    {
+                        instances.push(inst)
+                    }
+                    _ => {}
+                }
+            }
+        }
+        instances
+    }
+
+    /// The name of the `index`-th axiom, `axiom <index>` if it has none.
+    pub fn name(&self, index: usize) -> String {
+        match self.names.get(&index) {
+            Some(name) => name.clone(),
+            None => format!("axiom {}", index),
+        }
+    }
+
+    /// The sequent `axioms ⇒ goal`.
+    pub fn sequent(&self, goal: Formula) -> Sequent {
+        Sequent {
+            antecedent: self.axioms.clone(),
+            succedent: vec![goal],
+        }
+    }
+}
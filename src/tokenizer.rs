@@ -1,12 +1,63 @@
 extern crate itertools;
 
 use crate::language::*;
+use crate::limits::*;
 use itertools::Itertools;
 
+fn _is_delimiter(c: char) -> bool {
+    matches!(
+        c,
+        '(' | ')'
+            | '='
+            | 'V'
+            | 'E'
+            | ' '
+            | '|'
+            | '¬'
+            | '∧'
+            | '∨'
+            | '→'
+            | '↔'
+            | '∀'
+            | '∃'
+            | '⊤'
+            | '⊥'
+    )
+}
+
+/// Returns `name` as it has to be written so that the tokenizer reads it back
+/// as a single `Token::Symbol(name)`, quoting it with `|...|` when needed.
+pub fn quote_symbol(name: &str) -> String {
+    let plain = match name.chars().next() {
+        Some(c) => {
+            !matches!(c, '~' | '^' | 'v' | '>')
+                && !name.starts_with("<>")
+                && name
+                    .chars()
+                    .all(|c| !_is_delimiter(c) && !c.is_whitespace())
+        }
+        None => false,
+    };
+    if plain {
+        name.to_string()
+    } else {
+        let mut quoted = String::from("|");
+        for c in name.chars() {
+            if c == '|' || c == '\\' {
+                quoted.push('\\');
+            }
+            quoted.push(c);
+        }
+        quoted.push('|');
+        quoted
+    }
+}
+
 #[derive(Debug)]
 pub struct Tokenizer<'a> {
     pub iter: std::str::Chars<'a>,
     pub tokens: Vec<Token>,
+    pub max_tokens: Option<usize>,
 }
 
 impl<'a> Tokenizer<'a> {
@@ -14,12 +65,25 @@ impl<'a> Tokenizer<'a> {
         Tokenizer {
             iter: "".chars(),
             tokens: Vec::new(),
+            max_tokens: None,
         }
     }
 
-    fn _tokenize(&mut self) -> () {
+    /// A tokenizer whose `try_tokenize` fails after `limits.max_tokens`
+    /// tokens.
+    pub fn with_limits(limits: &Limits) -> Tokenizer<'a> {
+        Tokenizer {
+            max_tokens: limits.max_tokens,
+            ..Tokenizer::new()
+        }
+    }
+
+    fn _tokenize(&mut self) -> Result<(), &'static str> {
         use Token::*;
-        if let Some(s) = self.iter.next() {
+        while let Some(s) = self.iter.next() {
+            if self.max_tokens.is_some_and(|max| self.tokens.len() >= max) && s != ' ' {
+                return Err(TOO_MANY_TOKENS);
+            }
             let token = match s {
                 '(' => LParen,
                 ')' => RParen,
@@ -28,25 +92,60 @@ impl<'a> Tokenizer<'a> {
                 'v' => Or,
                 '>' => Implies,
                 '=' => Equal,
+                '<' if self.iter.clone().next() == Some('>') => {
+                    self.iter.next();
+                    Iff
+                }
                 'V' => Forall,
                 'E' => Exists,
-                ' ' => return self._tokenize(),
+                '¬' => Not,
+                '∧' => And,
+                '∨' => Or,
+                '→' => Implies,
+                '↔' => Iff,
+                '∀' => Forall,
+                '∃' => Exists,
+                '⊤' => Top,
+                '⊥' => Bottom,
+                '|' => {
+                    let mut symbol = String::new();
+                    while let Some(c) = self.iter.next() {
+                        match c {
+                            '|' => break,
+                            '\\' => symbol.extend(self.iter.next()),
+                            c => symbol.push(c),
+                        }
+                    }
+                    Symbol(symbol)
+                }
+                ' ' => continue,
                 _ => {
-                    let symbol = self
-                        .iter
-                        .take_while_ref(|s| !matches!(s, '(' | ')' | '=' | 'V' | 'E' | ' '));
+                    let symbol = self.iter.take_while_ref(|s| !_is_delimiter(*s));
                     Symbol(s.to_string() + &symbol.collect::<String>())
                 }
             };
             self.tokens.push(token);
-            self._tokenize();
         }
+        Ok(())
     }
 
+    /// The tokens of `s`, however many there are. Use `try_tokenize` to
+    /// enforce `max_tokens`.
     pub fn tokenize(&mut self, s: &'a str) -> Vec<Token> {
         self.iter = s.chars();
         self.tokens.clear();
-        self._tokenize();
+        let max_tokens = self.max_tokens.take();
+        let _ = self._tokenize();
+        self.max_tokens = max_tokens;
         self.tokens.to_vec()
     }
+
+    /// The tokens of `s`, or `TOO_MANY_TOKENS` if there are more than
+    /// `max_tokens`. Never panics.
+    pub fn try_tokenize(&mut self, s: &'a str) -> Result<Vec<Token>, &'static str> {
+        self.iter = s.chars();
+        self.tokens.clear();
+        self._tokenize()?;
+        Ok(self.tokens.to_vec())
+    }
 }
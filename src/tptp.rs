@@ -0,0 +1,741 @@
+use crate::adapt::*;
+use crate::cnf::*;
+use crate::language::*;
+use crate::limits::*;
+use crate::proof::*;
+use crate::resolution::*;
+use crate::solver::Prover;
+use std::collections::{BTreeSet, HashMap};
+
+// A TPTP variable for `name`. Lower-case words are capitalized, `x1` to
+// `X1`; other names are written after `XX` with `_` doubled and characters
+// other than ASCII letters and digits as `_<hex>_`, `x'` to `XXx_27_`. The
+// second character tells the two apart, so the mapping is one-to-one.
+fn _variable(name: &str) -> String {
+    let mut chars = name.chars();
+    if let Some(first) = chars.next() {
+        if first.is_ascii_lowercase()
+            && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        {
+            return first.to_ascii_uppercase().to_string() + &name[1..];
+        }
+    }
+    let mut variable = String::from("XX");
+    for c in name.chars() {
+        match c {
+            '_' => variable += "__",
+            c if c.is_ascii_alphanumeric() => variable.push(c),
+            c => variable += &format!("_{:x}_", c as u32),
+        }
+    }
+    variable
+}
+
+// A TPTP functor or predicate for `name`: lower words as they are, other
+// names single-quoted.
+fn _functor(name: &str) -> String {
+    let mut chars = name.chars();
+    let lower_word = chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if lower_word {
+        return name.to_string();
+    }
+    format!("'{}'", name.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+fn _application(name: &str, args: &[Term]) -> String {
+    if args.is_empty() {
+        return _functor(name);
+    }
+    let args = args.iter().map(Term::to_tptp).collect::<Vec<_>>();
+    format!("{}({})", _functor(name), args.join(","))
+}
+
+// `AndLeft1` as `and_left1`.
+fn _rule(rule: &str) -> String {
+    let mut snake = String::new();
+    for c in rule.chars() {
+        if c.is_ascii_uppercase() {
+            if !snake.is_empty() {
+                snake.push('_');
+            }
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+impl Term {
+    /// The term in TPTP syntax. Variables are capitalized, `x` to `X`, and
+    /// symbols that are not lower words are quoted.
+    pub fn to_tptp(&self) -> String {
+        match self {
+            Term::Var(name) => _variable(name),
+            Term::Func(name, args) => _application(name, args),
+        }
+    }
+}
+
+impl Formula {
+    /// The formula in TPTP FOF syntax, e.g. `! [X] : (p(X) => $false)`.
+    /// Free variables are written as variables, unlike FOF requires; see
+    /// `to_tptp_closed`.
+    pub fn to_tptp(&self) -> String {
+        let binary = |lhs: &Formula, connective: &str, rhs: &Formula| {
+            format!("({} {} {})", lhs.to_tptp(), connective, rhs.to_tptp())
+        };
+        match self {
+            Formula::Pred(name, args) => _application(name, args),
+            Formula::Equal(lhs, rhs) => format!("{} = {}", lhs.to_tptp(), rhs.to_tptp()),
+            Formula::Not(fml) => match &**fml {
+                Formula::Equal(lhs, rhs) => format!("{} != {}", lhs.to_tptp(), rhs.to_tptp()),
+                fml => format!("~ {}", fml.to_tptp()),
+            },
+            Formula::And(lhs, rhs) => binary(lhs, "&", rhs),
+            Formula::Or(lhs, rhs) => binary(lhs, "|", rhs),
+            Formula::Implies(lhs, rhs) => binary(lhs, "=>", rhs),
+            Formula::Iff(lhs, rhs) => binary(lhs, "<=>", rhs),
+            Formula::Forall(var, fml) => format!("! [{}] : {}", var.to_tptp(), fml.to_tptp()),
+            Formula::Exists(var, fml) => format!("? [{}] : {}", var.to_tptp(), fml.to_tptp()),
+            Formula::Top => "$true".to_string(),
+            Formula::Bottom => "$false".to_string(),
+        }
+    }
+
+    /// `to_tptp` of the universal closure, the free variables bound in
+    /// order of name.
+    pub fn to_tptp_closed(&self) -> String {
+        let mut vars = self.get_free_vars().into_iter().collect::<Vec<_>>();
+        vars.sort_by_key(|var| var.to_string());
+        if vars.is_empty() {
+            return self.to_tptp();
+        }
+        let vars = vars.iter().map(Term::to_tptp).collect::<Vec<_>>();
+        format!("! [{}] : {}", vars.join(","), self.to_tptp())
+    }
+}
+
+// The clause as a TPTP CNF disjunction, its literals in order of rendering.
+fn _clause(clause: &Clause) -> String {
+    let mut literals = clause
+        .iter()
+        .map(|l| l.to_formula().to_tptp())
+        .collect::<Vec<_>>();
+    if literals.is_empty() {
+        return "$false".to_string();
+    }
+    literals.sort();
+    literals.join(" | ")
+}
+
+fn _parents(prefix: char, parents: &[usize]) -> String {
+    let parents = parents
+        .iter()
+        .map(|p| format!("{}{}", prefix, p))
+        .collect::<Vec<_>>();
+    format!("[{}]", parents.join(","))
+}
+
+impl LK {
+    /// The proof as a TSTP derivation with an `fof` line per distinct
+    /// subproof, premises first, named `f0`, `f1`, .... Each states the
+    /// universal closure of its sequent `Γ ⇒ Δ` as `∧Γ → ∨Δ`, inferred by
+    /// the snake-cased rule, e.g. `and_left1`, from its premises. Theory
+    /// axioms are given the role `axiom`.
+    pub fn to_tstp(&self) -> String {
+        let graph = self.to_graph();
+        let mut lines = vec!["% SZS output start Proof".to_string()];
+        for (id, node) in graph.nodes.iter().enumerate() {
+            let fml = node.sequent.to_formula().to_tptp_closed();
+            if node.rule == "TheoryAxiom" {
+                lines.push(format!("fof(f{}, axiom, {}).", id, fml));
+                continue;
+            }
+            let mut premises = graph
+                .edges
+                .iter()
+                .filter(|edge| edge.conclusion == id)
+                .collect::<Vec<_>>();
+            premises.sort_by_key(|edge| edge.index);
+            let premises = premises.iter().map(|e| e.premise).collect::<Vec<_>>();
+            lines.push(format!(
+                "fof(f{}, plain, {}, inference({}, [status(thm)], {})).",
+                id,
+                fml,
+                _rule(node.rule),
+                _parents('f', &premises)
+            ));
+        }
+        lines.push("% SZS output end Proof".to_string());
+        lines.join("\n")
+    }
+}
+
+impl Refutation {
+    /// The refutation as a TSTP derivation with a `cnf` line per clause,
+    /// named `c0`, `c1`, ... in order. Input clauses are axioms, named
+    /// after the input clause in a `file` source.
+    pub fn to_tstp(&self) -> String {
+        let mut lines = vec!["% SZS output start CNFRefutation".to_string()];
+        for (id, (clause, derivation)) in self.clauses.iter().enumerate() {
+            let clause = _clause(clause);
+            let line = match derivation {
+                Derivation::Input(n) => {
+                    format!("cnf(c{}, axiom, {}, file('input', i{})).", id, clause, n)
+                }
+                Derivation::Resolution { .. } | Derivation::Factoring { .. } => {
+                    let rule = match derivation {
+                        Derivation::Resolution { .. } => "resolution",
+                        _ => "factoring",
+                    };
+                    format!(
+                        "cnf(c{}, plain, {}, inference({}, [status(thm)], {})).",
+                        id,
+                        clause,
+                        rule,
+                        _parents('c', &derivation.parents())
+                    )
+                }
+            };
+            lines.push(line);
+        }
+        lines.push("% SZS output end CNFRefutation".to_string());
+        lines.join("\n")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TptpToken {
+    Punct(char),
+    Op(&'static str),
+    // Lower and `$` words, single-quoted symbols unquoted, numbers and
+    // distinct objects with their quotes.
+    Word(String),
+    Var(String),
+}
+
+const OPERATORS: [&str; 12] = [
+    "<~>", "<=>", "=>", "<=", "~|", "~&", "!=", "=", "~", "&", "|", "!",
+];
+
+fn _tokenize(input: &str) -> Result<Vec<TptpToken>, &'static str> {
+    let mut tokens = vec![];
+    let mut i = 0;
+    while let Some(c) = input[i..].chars().next() {
+        let rest = &input[i..];
+        let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+        if c.is_whitespace() {
+            i += c.len_utf8();
+        } else if c == '%' {
+            i += rest.find('\n').unwrap_or(rest.len());
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            i += comment.find("*/").ok_or("Unterminated comment.")? + 4;
+        } else if "()[],.:?".contains(c) {
+            tokens.push(TptpToken::Punct(c));
+            i += 1;
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(*op)) {
+            tokens.push(TptpToken::Op(op));
+            i += op.len();
+        } else if c == '\'' || c == '"' {
+            let mut word = String::new();
+            let mut chars = rest[1..].chars();
+            loop {
+                match chars.next() {
+                    Some('\\') => word.extend(chars.next()),
+                    Some(d) if d == c => break,
+                    Some(d) => word.push(d),
+                    None => return Err("Unterminated quote."),
+                }
+            }
+            i += rest.len() - chars.as_str().len();
+            if c == '"' {
+                word = format!("\"{}\"", word);
+            }
+            tokens.push(TptpToken::Word(word));
+        } else if is_word(c) || c == '$' || c == '+' || c == '-' {
+            let len = 1 + rest[1..].find(|d| !is_word(d)).unwrap_or(rest.len() - 1);
+            let word = rest[..len].to_string();
+            i += len;
+            if c.is_ascii_uppercase() || c == '_' {
+                tokens.push(TptpToken::Var(word));
+            } else {
+                tokens.push(TptpToken::Word(word));
+            }
+        } else {
+            return Err("Unexpected character.");
+        }
+    }
+    Ok(tokens)
+}
+
+// A TPTP general term, as found in the sources of annotated formulas.
+// Formulas embedded with `$fof(...)` and the like are skipped.
+#[derive(Debug, Clone, PartialEq)]
+enum General {
+    Word(String),
+    App(String, Vec<General>),
+    List(Vec<General>),
+    Colon(Box<General>, Box<General>),
+    Skipped,
+}
+
+impl General {
+    // The names of the steps a parent list refers to, looking into the
+    // parents of nested inferences.
+    fn names(&self, names: &mut Vec<String>) {
+        match self {
+            General::Word(name) => names.push(name.clone()),
+            General::App(f, args) if f == "inference" && args.len() == 3 => args[2].names(names),
+            General::List(items) => items.iter().for_each(|item| item.names(names)),
+            General::Colon(name, _) => name.names(names),
+            _ => {}
+        }
+    }
+}
+
+struct TptpParser<'a> {
+    tokens: &'a [TptpToken],
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> TptpParser<'a> {
+    fn peek(&self) -> Option<&'a TptpToken> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Result<&'a TptpToken, &'static str> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .ok_or("Unexpected end of input.")?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn is(&self, token: &TptpToken) -> bool {
+        self.peek() == Some(token)
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), &'static str> {
+        match self.next()? {
+            TptpToken::Punct(d) if *d == c => Ok(()),
+            _ => Err("Unexpected token."),
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), &'static str> {
+        self.depth += 1;
+        match Limits::untrusted().max_depth {
+            Some(max) if self.depth > max => Err(TOO_DEEP),
+            _ => Ok(()),
+        }
+    }
+
+    fn name(&mut self) -> Result<String, &'static str> {
+        match self.next()? {
+            TptpToken::Word(name) => Ok(name.clone()),
+            _ => Err("Expected a name."),
+        }
+    }
+
+    fn term(&mut self) -> Result<Term, &'static str> {
+        self.enter()?;
+        let term = match self.next()? {
+            TptpToken::Var(name) => Term::Var(name.clone()),
+            TptpToken::Word(name) => Term::Func(name.clone(), self.args()?),
+            _ => return Err("Expected a term."),
+        };
+        self.depth -= 1;
+        Ok(term)
+    }
+
+    fn args(&mut self) -> Result<Vec<Term>, &'static str> {
+        let mut args = vec![];
+        if self.is(&TptpToken::Punct('(')) {
+            self.pos += 1;
+            args.push(self.term()?);
+            while self.is(&TptpToken::Punct(',')) {
+                self.pos += 1;
+                args.push(self.term()?);
+            }
+            self.expect(')')?;
+        }
+        Ok(args)
+    }
+
+    // A formula that is not a binary formula outside parentheses. The
+    // cases are parsed by separate functions to keep the recursion shallow.
+    fn unit(&mut self) -> Result<Formula, &'static str> {
+        self.enter()?;
+        let fml = match self.peek().ok_or("Unexpected end of input.")? {
+            TptpToken::Punct('(') => {
+                self.pos += 1;
+                let fml = self.formula()?;
+                self.expect(')')?;
+                fml
+            }
+            TptpToken::Op("~") => {
+                self.pos += 1;
+                not!(self.unit()?)
+            }
+            TptpToken::Op("!") | TptpToken::Punct('?') => self.quantified()?,
+            _ => self.atom()?,
+        };
+        self.depth -= 1;
+        Ok(fml)
+    }
+
+    fn quantified(&mut self) -> Result<Formula, &'static str> {
+        let forall = self.next()? == &TptpToken::Op("!");
+        self.expect('[')?;
+        let mut vars = vec![];
+        loop {
+            match self.next()? {
+                TptpToken::Var(name) => vars.push(Term::Var(name.clone())),
+                _ => return Err("Expected a variable."),
+            }
+            if !self.is(&TptpToken::Punct(',')) {
+                break;
+            }
+            self.pos += 1;
+        }
+        self.expect(']')?;
+        self.expect(':')?;
+        let mut fml = self.unit()?;
+        for var in vars.into_iter().rev() {
+            fml = if forall {
+                forall!(var, fml)
+            } else {
+                exists!(var, fml)
+            };
+        }
+        Ok(fml)
+    }
+
+    fn atom(&mut self) -> Result<Formula, &'static str> {
+        match self.peek() {
+            Some(TptpToken::Word(word)) if word == "$true" => {
+                self.pos += 1;
+                return Ok(Formula::Top);
+            }
+            Some(TptpToken::Word(word)) if word == "$false" => {
+                self.pos += 1;
+                return Ok(Formula::Bottom);
+            }
+            _ => {}
+        }
+        let lhs = self.term()?;
+        match self.peek() {
+            Some(TptpToken::Op("=")) => {
+                self.pos += 1;
+                Ok(equal!(lhs, self.term()?))
+            }
+            Some(TptpToken::Op("!=")) => {
+                self.pos += 1;
+                Ok(not!(equal!(lhs, self.term()?)))
+            }
+            _ => match lhs {
+                Term::Func(name, args) => Ok(Formula::Pred(name, args)),
+                Term::Var(_) => Err("Expected a formula."),
+            },
+        }
+    }
+
+    fn formula(&mut self) -> Result<Formula, &'static str> {
+        let lhs = self.unit()?;
+        let op = match self.peek() {
+            Some(TptpToken::Op(op)) if !["~", "!", "=", "!="].contains(op) => *op,
+            _ => return Ok(lhs),
+        };
+        self.pos += 1;
+        let rhs = self.unit()?;
+        let mut fml = match op {
+            "&" => and!(lhs, rhs),
+            "|" => or!(lhs, rhs),
+            "=>" => implies!(lhs, rhs),
+            "<=" => implies!(rhs, lhs),
+            "<=>" => iff!(lhs, rhs),
+            "<~>" => not!(iff!(lhs, rhs)),
+            "~|" => not!(or!(lhs, rhs)),
+            _ => not!(and!(lhs, rhs)),
+        };
+        // Only `&` and `|` associate, to the left. The formula nests deeper
+        // with each operand.
+        let depth = self.depth;
+        while (op == "&" || op == "|") && self.is(&TptpToken::Op(op)) {
+            self.pos += 1;
+            self.enter()?;
+            let rhs = self.unit()?;
+            fml = if op == "&" {
+                and!(fml, rhs)
+            } else {
+                or!(fml, rhs)
+            };
+        }
+        self.depth = depth;
+        Ok(fml)
+    }
+
+    fn general(&mut self) -> Result<General, &'static str> {
+        self.enter()?;
+        let general = match self.next()? {
+            TptpToken::Punct('[') => {
+                let mut items = vec![];
+                if !self.is(&TptpToken::Punct(']')) {
+                    items.push(self.general()?);
+                    while self.is(&TptpToken::Punct(',')) {
+                        self.pos += 1;
+                        items.push(self.general()?);
+                    }
+                }
+                self.expect(']')?;
+                General::List(items)
+            }
+            TptpToken::Word(word) if word.starts_with('$') && self.is(&TptpToken::Punct('(')) => {
+                let mut depth = 0;
+                loop {
+                    match self.next()? {
+                        TptpToken::Punct('(') => depth += 1,
+                        TptpToken::Punct(')') => depth -= 1,
+                        _ => {}
+                    }
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                General::Skipped
+            }
+            TptpToken::Word(word) if self.is(&TptpToken::Punct('(')) => {
+                self.pos += 1;
+                let mut args = vec![self.general()?];
+                while self.is(&TptpToken::Punct(',')) {
+                    self.pos += 1;
+                    args.push(self.general()?);
+                }
+                self.expect(')')?;
+                General::App(word.clone(), args)
+            }
+            TptpToken::Word(word) | TptpToken::Var(word) => General::Word(word.clone()),
+            _ => return Err("Expected a general term."),
+        };
+        let general = if self.is(&TptpToken::Punct(':')) {
+            self.pos += 1;
+            General::Colon(Box::new(general), Box::new(self.general()?))
+        } else {
+            general
+        };
+        self.depth -= 1;
+        Ok(general)
+    }
+}
+
+/// An annotated formula of a TSTP derivation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TstpStep {
+    pub name: String,
+    /// E.g. `axiom`, `negated_conjecture` or `plain`.
+    pub role: String,
+    /// The formula, or the disjunction of the literals of a clause, whose
+    /// variables are then free.
+    pub formula: Formula,
+    pub is_clause: bool,
+    /// The rule of an `inference(...)` source, e.g. `resolution`.
+    pub rule: Option<String>,
+    /// The `status(...)` of the inference, e.g. `thm` or `esa`.
+    pub status: Option<String>,
+    /// Indices of the steps the formula is inferred from, all before it.
+    pub parents: Vec<usize>,
+}
+
+impl TstpStep {
+    /// The universal closure of `formula`, its free variables bound in
+    /// order of name.
+    pub fn closure(&self) -> Formula {
+        let mut vars = self.formula.get_free_vars().into_iter().collect::<Vec<_>>();
+        vars.sort_by_key(|var| var.to_string());
+        vars.into_iter()
+            .rev()
+            .fold(self.formula.clone(), |fml, var| forall!(var, fml))
+    }
+
+    /// Whether the step is taken for granted by `TstpDerivation::to_lk`:
+    /// an input formula, or the negation of the conjecture, which does not
+    /// follow from the conjecture.
+    pub fn is_assumption(&self) -> bool {
+        self.parents.is_empty()
+            || self.status.as_deref() == Some("cth")
+            || self.rule.as_deref() == Some("negated_conjecture")
+    }
+}
+
+/// A TSTP derivation as written by E or Vampire: a DAG of annotated
+/// formulas in order, parents first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TstpDerivation {
+    pub steps: Vec<TstpStep>,
+}
+
+/// Reads the `fof` and `cnf` annotated formulas of a TSTP derivation. Parents
+/// are the names in the parent list of an `inference(...)` source, or a
+/// source that is just a name; other sources make input formulas. Symbols
+/// keep their TPTP names, quotes removed, so that variables are upper-case.
+pub fn parse_tstp(input: &str) -> Result<TstpDerivation, &'static str> {
+    let tokens = _tokenize(input)?;
+    let mut parser = TptpParser {
+        tokens: &tokens,
+        pos: 0,
+        depth: 0,
+    };
+    let mut steps: Vec<TstpStep> = vec![];
+    while parser.peek().is_some() {
+        let is_clause = match parser.name()?.as_str() {
+            "fof" => false,
+            "cnf" => true,
+            "include" => return Err("Includes are not supported."),
+            _ => return Err("Only fof and cnf formulas are supported."),
+        };
+        parser.expect('(')?;
+        let name = parser.name()?;
+        parser.expect(',')?;
+        let role = parser.name()?;
+        parser.expect(',')?;
+        let formula = parser.formula()?;
+        let (mut rule, mut status, mut names) = (None, None, vec![]);
+        if parser.is(&TptpToken::Punct(',')) {
+            parser.pos += 1;
+            match parser.general()? {
+                General::App(f, args) if f == "inference" && args.len() == 3 => {
+                    if let General::Word(r) = &args[0] {
+                        rule = Some(r.clone());
+                    }
+                    if let General::List(info) = &args[1] {
+                        for item in info {
+                            if let General::App(f, args) = item {
+                                if let (true, [General::Word(s)]) = (f == "status", &args[..]) {
+                                    status = Some(s.clone());
+                                }
+                            }
+                        }
+                    }
+                    args[2].names(&mut names);
+                }
+                General::Word(parent) => names.push(parent),
+                _ => {}
+            }
+            if parser.is(&TptpToken::Punct(',')) {
+                parser.pos += 1;
+                parser.general()?;
+            }
+        }
+        parser.expect(')')?;
+        parser.expect('.')?;
+        if steps.iter().any(|step| step.name == name) {
+            return Err("Duplicate formula name.");
+        }
+        let parents = names
+            .iter()
+            .map(|parent| steps.iter().position(|step| step.name == *parent))
+            .collect::<Option<Vec<_>>>()
+            .ok_or("Unknown parent.")?;
+        steps.push(TstpStep {
+            name,
+            role,
+            formula,
+            is_clause,
+            rule,
+            status,
+            parents,
+        });
+    }
+    Ok(TstpDerivation { steps })
+}
+
+// Cuts the formula `lhs` proves, the last of its succedent, with its last
+// occurrence in the antecedent of `rhs`.
+fn _cut(lhs: LK, rhs: LK) -> LK {
+    let fml = lhs.last().suc_last().clone();
+    let mut rest = rhs.last().antecedent.clone();
+    rest.remove(rest.iter().rposition(|f| *f == fml).unwrap());
+    let target = Sequent {
+        antecedent: [vec![fml], rest.clone()].concat(),
+        succedent: rhs.last().succedent.clone(),
+    };
+    let rhs = adapt(rhs, &target).unwrap();
+    let conclusion = Sequent {
+        antecedent: [lhs.last().antecedent.clone(), rest].concat(),
+        succedent: [lhs.last().suc_but_last(), &rhs.last().succedent[..]].concat(),
+    };
+    LK::Cut(Box::new([lhs, rhs]), conclusion)
+}
+
+impl TstpDerivation {
+    // A proof of the closure of `step` from the closures of the
+    // assumptions it depends on, whose indices are returned in order.
+    fn _derive(
+        &self,
+        step: usize,
+        prover: &dyn Prover,
+        derived: &mut HashMap<usize, (LK, BTreeSet<usize>)>,
+    ) -> Result<(LK, BTreeSet<usize>), (String, &'static str)> {
+        if let Some(derivation) = derived.get(&step) {
+            return Ok(derivation.clone());
+        }
+        let current = &self.steps[step];
+        let fml = current.closure();
+        if current.is_assumption() {
+            let axiom = LK::Axiom(Sequent {
+                antecedent: vec![fml.clone()],
+                succedent: vec![fml],
+            });
+            return Ok((axiom, std::iter::once(step).collect()));
+        }
+        let mut parents: Vec<(usize, Formula)> = vec![];
+        for &parent in &current.parents {
+            let closure = self.steps[parent].closure();
+            if parents.iter().all(|(_, f)| *f != closure) {
+                parents.push((parent, closure));
+            }
+        }
+        let goal = Sequent {
+            antecedent: parents.iter().map(|(_, f)| f.clone()).collect(),
+            succedent: vec![fml],
+        };
+        let mut proof = prover
+            .prove_sequent(&goal)
+            .and_then(|proof| adapt(proof, &goal).ok())
+            .ok_or_else(|| (current.name.clone(), "Step not proved."))?;
+        let mut assumptions = BTreeSet::new();
+        for (parent, _) in parents.iter().rev() {
+            let (premise, used) = self._derive(*parent, prover, derived)?;
+            proof = _cut(premise, proof);
+            assumptions.extend(used);
+        }
+        let target = Sequent {
+            antecedent: assumptions
+                .iter()
+                .map(|&i| self.steps[i].closure())
+                .collect(),
+            succedent: goal.succedent,
+        };
+        let proof = adapt(proof, &target).unwrap();
+        derived.insert(step, (proof.clone(), assumptions.clone()));
+        Ok((proof, assumptions))
+    }
+
+    /// An LK proof of the closure of the last step from the closures of the
+    /// assumptions it depends on (see `TstpStep::is_assumption`), in order.
+    /// Each inference is proved again by `prover` and the proofs are joined
+    /// by cuts, so steps that are not logical consequences of their parents,
+    /// such as Skolemization, or that `prover` cannot prove, fail with the
+    /// name of the step.
+    pub fn to_lk(&self, prover: &dyn Prover) -> Result<LK, (String, &'static str)> {
+        if self.steps.is_empty() {
+            return Err((String::new(), "Empty derivation."));
+        }
+        let (proof, _) = self._derive(self.steps.len() - 1, prover, &mut HashMap::new())?;
+        Ok(proof)
+    }
+}
@@ -0,0 +1,208 @@
+use crate::language::*;
+use crate::proof::*;
+use crate::record::ProofStep;
+use std::collections::HashMap;
+use std::fmt;
+
+/// One step on the way from the goal of a search to an explored state:
+/// applying `rule` to `goal` gave `subgoal` as its `premise`-th premise.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub goal: Sequent,
+    /// `None` if no rule derives `goal` from `subgoal`, which does not
+    /// happen for traces of the LK search.
+    pub rule: Option<&'static str>,
+    pub premise: usize,
+    /// The term a quantifier was instantiated with, or the eigenvariable.
+    pub instantiation: Option<Term>,
+    pub subgoal: Sequent,
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}  --{}", self.goal, self.rule.unwrap_or("?"))?;
+        if let Some(term) = &self.instantiation {
+            write!(f, " with {}", term)?;
+        }
+        write!(f, "-->  {}", self.subgoal)
+    }
+}
+
+/// The states explored by `solver::prove_with_lk_traced` together with the
+/// goal each was first generated from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchTrace {
+    pub steps: Vec<ProofStep>,
+    edges: Vec<(Sequent, Sequent)>,
+    // The first edge leading to each state.
+    parents: HashMap<Sequent, usize>,
+}
+
+const UNARY_RULES: [&str; 17] = [
+    "NotLeft",
+    "NotRight",
+    "AndLeft1",
+    "AndLeft2",
+    "OrRight1",
+    "OrRight2",
+    "ImpliesRight",
+    "ForallLeft",
+    "ForallRight",
+    "ExistsLeft",
+    "ExistsRight",
+    "WeakeningLeft",
+    "WeakeningRight",
+    "ContractionLeft",
+    "ContractionRight",
+    "ExchangeLeft",
+    "ExchangeRight",
+];
+
+const BINARY_RULES: [&str; 6] = [
+    "AndRight",
+    "OrLeft",
+    "ImpliesLeft",
+    "IffLeft",
+    "IffRight",
+    "Cut",
+];
+
+fn _is_valid(rule: &str, premises: &[&Sequent], conclusion: &Sequent) -> bool {
+    let premises = premises.iter().map(|s| LK::Axiom((*s).clone())).collect();
+    LK::from_parts(rule, premises, conclusion.clone()).is_some_and(|lk| lk.is_valid_inference())
+}
+
+// The right premise of the binary `rule` with the given conclusion and left
+// premise, which it is determined by.
+fn _right_premise(rule: &str, conclusion: &Sequent, left: &Sequent) -> Option<Sequent> {
+    let sequent = |antecedent: Vec<Formula>, succedent: Vec<Formula>| Sequent {
+        antecedent,
+        succedent,
+    };
+    let ant = &conclusion.antecedent;
+    let suc = &conclusion.succedent;
+    match (rule, ant.first(), suc.last()) {
+        ("AndRight", _, Some(Formula::And(_, rhs))) => Some(sequent(
+            ant.clone(),
+            [conclusion.suc_but_last(), &[(**rhs).clone()]].concat(),
+        )),
+        ("OrLeft", Some(Formula::Or(_, rhs)), _) => Some(sequent(
+            [&[(**rhs).clone()], conclusion.ant_but_first()].concat(),
+            suc.clone(),
+        )),
+        ("IffLeft", Some(Formula::Iff(lhs, rhs)), _) => Some(sequent(
+            conclusion.ant_but_first().to_vec(),
+            [&suc[..], &[(**lhs).clone(), (**rhs).clone()]].concat(),
+        )),
+        ("IffRight", _, Some(Formula::Iff(lhs, rhs))) => Some(sequent(
+            [&[(**rhs).clone()], &ant[..]].concat(),
+            [conclusion.suc_but_last(), &[(**lhs).clone()]].concat(),
+        )),
+        ("ImpliesLeft", Some(Formula::Implies(_, rhs)), _) => {
+            let pi = conclusion.ant_but_first().get(left.antecedent.len()..)?;
+            let sigma = suc.get(left.succedent.len().checked_sub(1)?..)?;
+            Some(sequent([&[(**rhs).clone()], pi].concat(), sigma.to_vec()))
+        }
+        ("Cut", _, _) => {
+            let pi = ant.get(left.antecedent.len()..)?;
+            let sigma = suc.get(left.succedent.len().checked_sub(1)?..)?;
+            Some(sequent(
+                [&[left.succedent.last()?.clone()], pi].concat(),
+                sigma.to_vec(),
+            ))
+        }
+        _ => None,
+    }
+}
+
+// The term `var` is replaced with in `body` to give `instance`.
+fn _instance_term(var: &Term, body: &Formula, instance: &Formula) -> Option<Term> {
+    if !body.get_free_vars().contains(var) {
+        return None;
+    }
+    instance
+        .get_subterms()
+        .into_iter()
+        .find(|term| &body.substitute(var.clone(), term.clone()) == instance)
+}
+
+// The term a quantifier inference from `subgoal` to `goal` instantiates its
+// principal formula with, or its eigenvariable.
+pub(crate) fn instantiation(rule: &str, goal: &Sequent, subgoal: &Sequent) -> Option<Term> {
+    match (rule, goal.antecedent.first(), goal.succedent.last()) {
+        ("ForallLeft", Some(Formula::Forall(var, body)), _)
+        | ("ExistsLeft", Some(Formula::Exists(var, body)), _) => {
+            _instance_term(var, body, subgoal.antecedent.first()?)
+        }
+        ("ForallRight", _, Some(Formula::Forall(var, body)))
+        | ("ExistsRight", _, Some(Formula::Exists(var, body))) => {
+            _instance_term(var, body, subgoal.succedent.last()?)
+        }
+        _ => None,
+    }
+}
+
+impl SearchTrace {
+    pub(crate) fn new(steps: Vec<ProofStep>, edges: Vec<(Sequent, Sequent)>) -> SearchTrace {
+        let mut parents = HashMap::new();
+        for (i, (_, subgoal)) in edges.iter().enumerate() {
+            parents.entry(subgoal.clone()).or_insert(i);
+        }
+        SearchTrace {
+            steps,
+            edges,
+            parents,
+        }
+    }
+
+    // The rule and premise index by which `goal` generated `subgoal`.
+    fn _identify(&self, goal: &Sequent, subgoal: &Sequent) -> (Option<&'static str>, usize) {
+        if let Some(rule) = UNARY_RULES
+            .iter()
+            .find(|rule| _is_valid(rule, &[subgoal], goal))
+        {
+            return (Some(rule), 0);
+        }
+        for rule in BINARY_RULES.iter() {
+            if let Some(right) = _right_premise(rule, goal, subgoal) {
+                if _is_valid(rule, &[subgoal, &right], goal) {
+                    return (Some(rule), 0);
+                }
+            }
+            let siblings = self.edges.iter().filter(|(g, _)| g == goal);
+            for (_, left) in siblings {
+                if _is_valid(rule, &[left, subgoal], goal) {
+                    return (Some(rule), 1);
+                }
+            }
+        }
+        (None, 0)
+    }
+
+    /// The chain of rule applications from the goal of the search to the
+    /// state of `steps[id]`, as the search first generated it. Empty for the
+    /// goal itself, `None` if there is no such step.
+    pub fn explain_node(&self, id: usize) -> Option<Vec<Explanation>> {
+        let mut state = &self.steps.get(id)?.state;
+        let mut chain = vec![];
+        let mut visited = vec![state];
+        while let Some(&edge) = self.parents.get(state) {
+            let (goal, subgoal) = &self.edges[edge];
+            if visited.contains(&goal) {
+                break;
+            }
+            let (rule, premise) = self._identify(goal, subgoal);
+            chain.push(Explanation {
+                goal: goal.clone(),
+                rule,
+                premise,
+                instantiation: rule.and_then(|rule| instantiation(rule, goal, subgoal)),
+                subgoal: subgoal.clone(),
+            });
+            visited.push(goal);
+            state = goal;
+        }
+        chain.reverse();
+        Some(chain)
+    }
+}
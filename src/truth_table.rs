@@ -0,0 +1,132 @@
+use crate::language::*;
+use std::collections::HashMap;
+use std::fmt;
+
+/// The most atoms `Formula::truth_table` accepts, as the table has a row
+/// for every valuation.
+pub const MAX_TRUTH_TABLE_ATOMS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Classification {
+    /// True in every row.
+    Tautology,
+    /// True in some rows and false in others.
+    Contingency,
+    /// False in every row.
+    Contradiction,
+}
+
+/// The truth values of a propositional formula under every valuation of its
+/// atoms.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TruthTable {
+    /// The formula the table is of.
+    pub formula: Formula,
+    /// Names of the atoms, in order of name.
+    pub atoms: Vec<String>,
+    /// The values of `atoms` and of the formula, from all atoms true to all
+    /// false.
+    pub rows: Vec<(Vec<bool>, bool)>,
+}
+
+impl TruthTable {
+    pub fn classification(&self) -> Classification {
+        if self.rows.iter().all(|(_, value)| *value) {
+            Classification::Tautology
+        } else if self.rows.iter().any(|(_, value)| *value) {
+            Classification::Contingency
+        } else {
+            Classification::Contradiction
+        }
+    }
+}
+
+fn _value(value: bool) -> &'static str {
+    if value {
+        "T"
+    } else {
+        "F"
+    }
+}
+
+impl fmt::Display for TruthTable {
+    /// A column per atom and one for the formula, with `T` and `F` for the
+    /// truth values.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let formula = self.formula.to_string();
+        let widths = self.atoms.iter().map(|atom| atom.chars().count());
+        writeln!(f, "{} | {}", self.atoms.join(" "), formula)?;
+        for (values, value) in &self.rows {
+            let values = values
+                .iter()
+                .zip(widths.clone())
+                .map(|(&v, width)| format!("{:<width$}", _value(v), width = width))
+                .collect::<Vec<_>>();
+            writeln!(f, "{} | {}", values.join(" "), _value(*value))?;
+        }
+        Ok(())
+    }
+}
+
+// The value of `fml` with its atoms valued by `valuation`.
+fn _truth(fml: &Formula, valuation: &HashMap<&str, bool>) -> bool {
+    match fml {
+        Formula::Pred(name, _) => valuation[name.as_str()],
+        Formula::Not(fml) => !_truth(fml, valuation),
+        Formula::And(lhs, rhs) => _truth(lhs, valuation) && _truth(rhs, valuation),
+        Formula::Or(lhs, rhs) => _truth(lhs, valuation) || _truth(rhs, valuation),
+        Formula::Implies(lhs, rhs) => !_truth(lhs, valuation) || _truth(rhs, valuation),
+        Formula::Iff(lhs, rhs) => _truth(lhs, valuation) == _truth(rhs, valuation),
+        Formula::Top => true,
+        Formula::Bottom => false,
+        Formula::Equal(_, _) | Formula::Forall(_, _) | Formula::Exists(_, _) => unreachable!(),
+    }
+}
+
+/// Whether `fml` is built from nullary predicates, `⊤` and `⊥` with the
+/// connectives.
+pub(crate) fn is_propositional(fml: &Formula) -> bool {
+    fml.iter_subformulas().all(|f| match f {
+        Formula::Pred(_, args) => args.is_empty(),
+        Formula::Equal(_, _) | Formula::Forall(_, _) | Formula::Exists(_, _) => false,
+        _ => true,
+    })
+}
+
+impl Formula {
+    /// The truth table of a formula built from nullary predicates, `⊤` and
+    /// `⊥` with the connectives.
+    pub fn truth_table(&self) -> Result<TruthTable, &'static str> {
+        if !is_propositional(self) {
+            return Err("Not a propositional formula.");
+        }
+        let mut atoms = self
+            .get_preds()
+            .into_iter()
+            .map(|p| p.name)
+            .collect::<Vec<_>>();
+        atoms.sort();
+        if atoms.len() > MAX_TRUTH_TABLE_ATOMS {
+            return Err("Too many atoms.");
+        }
+        let rows = (0..1usize << atoms.len())
+            .map(|row| {
+                let values = (0..atoms.len())
+                    .map(|i| row >> (atoms.len() - 1 - i) & 1 == 0)
+                    .collect::<Vec<_>>();
+                let valuation = atoms
+                    .iter()
+                    .map(String::as_str)
+                    .zip(values.iter().copied())
+                    .collect();
+                let value = _truth(self, &valuation);
+                (values, value)
+            })
+            .collect();
+        Ok(TruthTable {
+            formula: self.clone(),
+            atoms,
+            rows,
+        })
+    }
+}
@@ -0,0 +1,156 @@
+use crate::language::*;
+
+pub use crate::substitution::Substitution;
+
+// The unifiers built here are idempotent: no variable they replace occurs in
+// the terms they replace variables with. Matchers need not be.
+impl Substitution {
+    // Extends the substitution with `var ↦ term`, keeping it idempotent.
+    // Fails the occurs check if `var` occurs in `term`.
+    fn _bind(&mut self, var: Term, term: Term) -> bool {
+        if term.get_vars().contains(&var) {
+            return false;
+        }
+        for t in self.map.values_mut() {
+            *t = t.substitute(var.clone(), term.clone());
+        }
+        self.map.insert(var, term);
+        true
+    }
+
+    fn _unify(&mut self, lhs: &Term, rhs: &Term) -> bool {
+        let lhs = self.apply_to_term(lhs);
+        let rhs = self.apply_to_term(rhs);
+        match (&lhs, &rhs) {
+            _ if lhs == rhs => true,
+            (Term::Var(_), _) => self._bind(lhs, rhs),
+            (_, Term::Var(_)) => self._bind(rhs, lhs),
+            (Term::Func(f, lterms), Term::Func(g, rterms)) => {
+                f == g && self._unify_all(lterms, rterms)
+            }
+        }
+    }
+
+    fn _unify_all(&mut self, lhs: &[Term], rhs: &[Term]) -> bool {
+        lhs.len() == rhs.len() && lhs.iter().zip(rhs).all(|(l, r)| self._unify(l, r))
+    }
+
+    // `bound` holds the variables bound at this position, which stand for
+    // themselves and may not be captured by an instantiated variable.
+    fn _match(&mut self, pattern: &Term, term: &Term, bound: &[Term]) -> bool {
+        match pattern {
+            Term::Var(_) if bound.contains(pattern) => pattern == term,
+            Term::Var(_) => {
+                if term.get_vars().iter().any(|v| bound.contains(v)) {
+                    return false;
+                }
+                match self.map.get(pattern) {
+                    Some(t) => t == term,
+                    None => {
+                        self.map.insert(pattern.clone(), term.clone());
+                        true
+                    }
+                }
+            }
+            Term::Func(f, patterns) => match term {
+                Term::Func(g, terms) => f == g && self._match_all(patterns, terms, bound),
+                Term::Var(_) => false,
+            },
+        }
+    }
+
+    fn _match_all(&mut self, patterns: &[Term], terms: &[Term], bound: &[Term]) -> bool {
+        patterns.len() == terms.len()
+            && patterns
+                .iter()
+                .zip(terms)
+                .all(|(p, t)| self._match(p, t, bound))
+    }
+
+    fn _match_formula(&mut self, pattern: &Formula, fml: &Formula, bound: &mut Vec<Term>) -> bool {
+        use Formula::*;
+        match (pattern, fml) {
+            (Pred(p, patterns), Pred(q, terms)) => {
+                p == q && self._match_all(patterns, terms, bound)
+            }
+            (Equal(pl, pr), Equal(l, r)) => self._match(pl, l, bound) && self._match(pr, r, bound),
+            (Not(pattern), Not(fml)) => self._match_formula(pattern, fml, bound),
+            (And(pl, pr), And(l, r))
+            | (Or(pl, pr), Or(l, r))
+            | (Implies(pl, pr), Implies(l, r))
+            | (Iff(pl, pr), Iff(l, r)) => {
+                self._match_formula(pl, l, bound) && self._match_formula(pr, r, bound)
+            }
+            (Forall(pvar, pattern), Forall(var, fml))
+            | (Exists(pvar, pattern), Exists(var, fml)) => {
+                if pvar != var {
+                    return false;
+                }
+                bound.push(var.clone());
+                let matched = self._match_formula(pattern, fml, bound);
+                bound.pop();
+                matched
+            }
+            (Top, Top) | (Bottom, Bottom) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Term {
+    /// Substitution `σ` with `pattern σ == self`, instantiating only the
+    /// variables of `pattern`.
+    pub fn matches(&self, pattern: &Term) -> Option<Substitution> {
+        let mut sub = Substitution::new();
+        if sub._match(pattern, self, &[]) {
+            Some(sub)
+        } else {
+            None
+        }
+    }
+}
+
+impl Formula {
+    /// Substitution `σ` instantiating the free variables of `pattern` so that
+    /// it becomes `self`. Quantifiers have to bind the same variables on both
+    /// sides, and a free variable is never mapped to a term containing a
+    /// variable bound at its position.
+    pub fn matches(&self, pattern: &Formula) -> Option<Substitution> {
+        let mut sub = Substitution::new();
+        if sub._match_formula(pattern, self, &mut vec![]) {
+            Some(sub)
+        } else {
+            None
+        }
+    }
+}
+
+/// Most general unifier of two terms, or `None` if they do not unify.
+pub fn unify(lhs: &Term, rhs: &Term) -> Option<Substitution> {
+    let mut sub = Substitution::new();
+    if sub._unify(lhs, rhs) {
+        Some(sub)
+    } else {
+        None
+    }
+}
+
+/// Most general unifier of two atomic formulas, i.e. predicates with the same
+/// name and arity or two equations, unified argument by argument.
+pub fn unify_atoms(lhs: &Formula, rhs: &Formula) -> Option<Substitution> {
+    let mut sub = Substitution::new();
+    let unified = match (lhs, rhs) {
+        (Formula::Pred(p, lterms), Formula::Pred(q, rterms)) => {
+            p == q && sub._unify_all(lterms, rterms)
+        }
+        (Formula::Equal(l1, r1), Formula::Equal(l2, r2)) => {
+            sub._unify(l1, l2) && sub._unify(r1, r2)
+        }
+        _ => false,
+    };
+    if unified {
+        Some(sub)
+    } else {
+        None
+    }
+}
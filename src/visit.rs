@@ -0,0 +1,230 @@
+use crate::language::*;
+
+/// Read-only traversal of a term. The default `visit_term` descends into
+/// the arguments through `walk_term`; implementations overriding it call
+/// `walk_term` themselves to keep descending.
+pub trait TermVisitor {
+    fn visit_term(&mut self, term: &Term) {
+        walk_term(self, term);
+    }
+}
+
+pub fn walk_term<V: TermVisitor + ?Sized>(visitor: &mut V, term: &Term) {
+    if let Term::Func(_, terms) = term {
+        for term in terms {
+            visitor.visit_term(term);
+        }
+    }
+}
+
+/// Read-only traversal of a formula, visiting its subformulas and the
+/// argument terms of its atoms. Variables right after quantifiers are not
+/// visited as terms.
+pub trait FormulaVisitor: TermVisitor {
+    fn visit_formula(&mut self, fml: &Formula) {
+        walk_formula(self, fml);
+    }
+}
+
+pub fn walk_formula<V: FormulaVisitor + ?Sized>(visitor: &mut V, fml: &Formula) {
+    match fml {
+        Formula::Pred(_, terms) => {
+            for term in terms {
+                visitor.visit_term(term);
+            }
+        }
+        Formula::Equal(lhs, rhs) => {
+            visitor.visit_term(lhs);
+            visitor.visit_term(rhs);
+        }
+        Formula::Not(fml) | Formula::Forall(_, fml) | Formula::Exists(_, fml) => {
+            visitor.visit_formula(fml)
+        }
+        Formula::And(lhs, rhs)
+        | Formula::Or(lhs, rhs)
+        | Formula::Implies(lhs, rhs)
+        | Formula::Iff(lhs, rhs) => {
+            visitor.visit_formula(lhs);
+            visitor.visit_formula(rhs);
+        }
+        Formula::Top | Formula::Bottom => {}
+    }
+}
+
+/// Bottom-up rewrite of a term. The default `fold_term` rebuilds the term
+/// from its folded arguments through `fold_subterms`.
+pub trait TermFolder {
+    fn fold_term(&mut self, term: &Term) -> Term {
+        fold_subterms(self, term)
+    }
+}
+
+pub fn fold_subterms<F: TermFolder + ?Sized>(folder: &mut F, term: &Term) -> Term {
+    match term {
+        Term::Var(_) => term.clone(),
+        Term::Func(name, terms) => Term::Func(
+            name.clone(),
+            terms.iter().map(|t| folder.fold_term(t)).collect(),
+        ),
+    }
+}
+
+/// Bottom-up rewrite of a formula. The default `fold_formula` rebuilds the
+/// formula from its folded subformulas and atom arguments through
+/// `fold_subformulas`. Quantified variables are kept as they are.
+pub trait FormulaFolder: TermFolder {
+    fn fold_formula(&mut self, fml: &Formula) -> Formula {
+        fold_subformulas(self, fml)
+    }
+}
+
+pub fn fold_subformulas<F: FormulaFolder + ?Sized>(folder: &mut F, fml: &Formula) -> Formula {
+    let mut fold = |fml: &Formula| Box::new(folder.fold_formula(fml));
+    match fml {
+        Formula::Pred(name, terms) => Formula::Pred(
+            name.clone(),
+            terms.iter().map(|t| folder.fold_term(t)).collect(),
+        ),
+        Formula::Equal(lhs, rhs) => Formula::Equal(folder.fold_term(lhs), folder.fold_term(rhs)),
+        Formula::Not(fml) => Formula::Not(fold(fml)),
+        Formula::And(lhs, rhs) => Formula::And(fold(lhs), fold(rhs)),
+        Formula::Or(lhs, rhs) => Formula::Or(fold(lhs), fold(rhs)),
+        Formula::Implies(lhs, rhs) => Formula::Implies(fold(lhs), fold(rhs)),
+        Formula::Iff(lhs, rhs) => Formula::Iff(fold(lhs), fold(rhs)),
+        Formula::Forall(var, fml) => Formula::Forall(var.clone(), fold(fml)),
+        Formula::Exists(var, fml) => Formula::Exists(var.clone(), fold(fml)),
+        Formula::Top | Formula::Bottom => fml.clone(),
+    }
+}
+
+/// Pre-order iterator over the subterms of a term or of the atoms of a
+/// formula, see `Term::iter_subterms` and `Formula::iter_subterms`.
+#[derive(Debug, Clone)]
+pub struct Subterms<'a> {
+    formulas: Vec<&'a Formula>,
+    stack: Vec<(&'a Term, usize)>,
+    max_depth: Option<usize>,
+}
+
+impl<'a> Subterms<'a> {
+    /// Leaves out subterms nested more than `depth` applications below the
+    /// term, or below the atom for `Formula::iter_subterms`.
+    pub fn max_depth(mut self, depth: usize) -> Subterms<'a> {
+        self.max_depth = Some(depth);
+        self
+    }
+}
+
+impl<'a> Iterator for Subterms<'a> {
+    type Item = &'a Term;
+
+    fn next(&mut self) -> Option<&'a Term> {
+        loop {
+            if let Some((term, depth)) = self.stack.pop() {
+                if let Term::Func(_, terms) = term {
+                    if self.max_depth.is_none_or(|max| depth < max) {
+                        self.stack
+                            .extend(terms.iter().rev().map(|t| (t, depth + 1)));
+                    }
+                }
+                return Some(term);
+            }
+            match self.formulas.pop()? {
+                Formula::Pred(_, terms) => {
+                    self.stack.extend(terms.iter().rev().map(|t| (t, 1)));
+                }
+                Formula::Equal(lhs, rhs) => {
+                    self.stack.push((rhs, 1));
+                    self.stack.push((lhs, 1));
+                }
+                Formula::Not(fml) | Formula::Forall(_, fml) | Formula::Exists(_, fml) => {
+                    self.formulas.push(fml)
+                }
+                Formula::And(lhs, rhs)
+                | Formula::Or(lhs, rhs)
+                | Formula::Implies(lhs, rhs)
+                | Formula::Iff(lhs, rhs) => {
+                    self.formulas.push(rhs);
+                    self.formulas.push(lhs);
+                }
+                Formula::Top | Formula::Bottom => {}
+            }
+        }
+    }
+}
+
+/// Pre-order iterator over the subformulas of a formula, see
+/// `Formula::iter_subformulas`.
+#[derive(Debug, Clone)]
+pub struct Subformulas<'a> {
+    stack: Vec<(&'a Formula, usize)>,
+    max_depth: Option<usize>,
+}
+
+impl<'a> Subformulas<'a> {
+    /// Leaves out subformulas nested more than `depth` connectives or
+    /// quantifiers below the formula.
+    pub fn max_depth(mut self, depth: usize) -> Subformulas<'a> {
+        self.max_depth = Some(depth);
+        self
+    }
+}
+
+impl<'a> Iterator for Subformulas<'a> {
+    type Item = &'a Formula;
+
+    fn next(&mut self) -> Option<&'a Formula> {
+        let (fml, depth) = self.stack.pop()?;
+        if self.max_depth.is_none_or(|max| depth < max) {
+            match fml {
+                Formula::Not(sub) | Formula::Forall(_, sub) | Formula::Exists(_, sub) => {
+                    self.stack.push((sub, depth + 1))
+                }
+                Formula::And(lhs, rhs)
+                | Formula::Or(lhs, rhs)
+                | Formula::Implies(lhs, rhs)
+                | Formula::Iff(lhs, rhs) => {
+                    self.stack.push((rhs, depth + 1));
+                    self.stack.push((lhs, depth + 1));
+                }
+                Formula::Pred(_, _) | Formula::Equal(_, _) | Formula::Top | Formula::Bottom => {}
+            }
+        }
+        Some(fml)
+    }
+}
+
+impl Term {
+    /// The term and its subterms in pre-order, left to right, with repeated
+    /// occurrences repeated.
+    pub fn iter_subterms(&self) -> Subterms<'_> {
+        Subterms {
+            formulas: vec![],
+            stack: vec![(self, 0)],
+            max_depth: None,
+        }
+    }
+}
+
+impl Formula {
+    /// The formula and its subformulas in pre-order, left to right, with
+    /// repeated occurrences repeated. Unlike `get_subformulas`, nothing is
+    /// cloned.
+    pub fn iter_subformulas(&self) -> Subformulas<'_> {
+        Subformulas {
+            stack: vec![(self, 0)],
+            max_depth: None,
+        }
+    }
+
+    /// The subterms of the atoms in pre-order, left to right, with repeated
+    /// occurrences repeated. The arguments of an atom are at depth 1.
+    /// Unlike `get_subterms`, nothing is cloned.
+    pub fn iter_subterms(&self) -> Subterms<'_> {
+        Subterms {
+            formulas: vec![self],
+            stack: vec![],
+            max_depth: None,
+        }
+    }
+}